@@ -10,7 +10,7 @@ use vfs::{file_set::FileSet, VfsPath};
 
 use crate::{
     input::CrateName, Change, CrateDisplayName, CrateGraph, CrateId, Edition, Env, FileId,
-    FilePosition, FileRange, SourceDatabaseExt, SourceRoot, SourceRootId,
+    FilePosition, FileRange, ProcMacro, SourceDatabaseExt, SourceRoot, SourceRootId,
 };
 
 pub const WORKSPACE: SourceRootId = SourceRootId(0);
@@ -32,6 +32,19 @@ pub trait WithFixture: Default + SourceDatabaseExt + 'static {
         (db, fixture.files)
     }
 
+    /// Like `with_many_files`, but the crate named `proc_macros` gets the given
+    /// expanders, matched by name against its `#[proc_macro*]` functions.
+    fn with_many_files_and_proc_macros(
+        ra_fixture: &str,
+        proc_macros: Vec<ProcMacro>,
+    ) -> (Self, Vec<FileId>) {
+        let fixture = ChangeFixture::parse_with_proc_macros(ra_fixture, proc_macros);
+        let mut db = Self::default();
+        fixture.change.apply(&mut db);
+        assert!(fixture.file_position.is_none());
+        (db, fixture.files)
+    }
+
     fn with_files(ra_fixture: &str) -> Self {
         let fixture = ChangeFixture::parse(ra_fixture);
         let mut db = Self::default();
@@ -81,6 +94,13 @@ pub struct ChangeFixture {
 
 impl ChangeFixture {
     pub fn parse(ra_fixture: &str) -> ChangeFixture {
+        ChangeFixture::parse_with_proc_macros(ra_fixture, Vec::new())
+    }
+
+    pub fn parse_with_proc_macros(
+        ra_fixture: &str,
+        mut proc_macros: Vec<ProcMacro>,
+    ) -> ChangeFixture {
         let (mini_core, fixture) = Fixture::parse(ra_fixture);
         let mut change = Change::new();
 
@@ -90,6 +110,7 @@ impl ChangeFixture {
         let mut crate_deps = Vec::new();
         let mut default_crate_root: Option<FileId> = None;
         let mut default_cfg = CfgOptions::default();
+        let mut default_env = Env::default();
 
         let mut file_set = FileSet::default();
         let source_root_prefix = "/".to_string();
@@ -131,7 +152,12 @@ impl ChangeFixture {
                     meta.cfg.clone(),
                     meta.cfg,
                     meta.env,
-                    Default::default(),
+                    if &*crate_name == "proc_macros" {
+                        mem::take(&mut proc_macros)
+                    } else {
+                        Vec::new()
+                    },
+                    None,
                 );
                 let prev = crates.insert(crate_name.clone(), crate_id);
                 assert!(prev.is_none());
@@ -143,6 +169,7 @@ impl ChangeFixture {
                 assert!(default_crate_root.is_none());
                 default_crate_root = Some(file_id);
                 default_cfg = meta.cfg;
+                default_env = meta.env;
             }
 
             change.change_file(file_id, Some(Arc::new(text)));
@@ -160,8 +187,9 @@ impl ChangeFixture {
                 Some(CrateName::new("test").unwrap().into()),
                 default_cfg.clone(),
                 default_cfg,
-                Env::default(),
+                default_env,
                 Default::default(),
+                None,
             );
         } else {
             for (from, to) in crate_deps {
@@ -191,6 +219,7 @@ impl ChangeFixture {
                 CfgOptions::default(),
                 Env::default(),
                 Vec::new(),
+                None,
             );
 
             for krate in all_crates {
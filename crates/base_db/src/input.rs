@@ -10,8 +10,8 @@ use std::{fmt, iter::FromIterator, ops, panic::RefUnwindSafe, str::FromStr, sync
 
 use cfg::CfgOptions;
 use rustc_hash::{FxHashMap, FxHashSet};
-use syntax::SmolStr;
-use tt::{ExpansionError, Subtree};
+use syntax::{SmolStr, TextRange};
+use tt::{ExpansionError, Subtree, TokenId};
 use vfs::{file_set::FileSet, FileId, VfsPath};
 
 /// Files are grouped into source roots. A source root is a directory on the
@@ -154,13 +154,58 @@ pub enum ProcMacroKind {
     Attr,
 }
 
+/// Source locations of the input of a procedural macro, which let the macro
+/// ask where its spans come from (`Span::source_file`, `Span::start`, ...).
+///
+/// Locations are relative to the start of the macro call, so that edits which
+/// only move the call don't change them and don't re-run the macro. Calls which
+/// are not written in a file of their own, like the ones produced by another
+/// macro, have no locations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcMacroSpans {
+    /// Path of the file containing the macro call.
+    pub file: String,
+    /// Source text of the macro input.
+    pub text: String,
+    /// Ranges of the input tokens, relative to `text`. The range of a
+    /// delimiter covers the whole delimited group.
+    pub tokens: Vec<(TokenId, TextRange)>,
+}
+
+/// The output of a procedural macro.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcMacroExpansion {
+    pub subtree: Subtree,
+    /// Diagnostics emitted by the macro with `proc_macro::Diagnostic::emit`.
+    pub diagnostics: Vec<ProcMacroDiagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcMacroDiagnostic {
+    pub level: ProcMacroDiagnosticLevel,
+    pub message: String,
+    /// Input tokens the diagnostic points at, empty if it is about the whole
+    /// macro call.
+    pub spans: Vec<TokenId>,
+    pub children: Vec<ProcMacroDiagnostic>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProcMacroDiagnosticLevel {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
 pub trait ProcMacroExpander: fmt::Debug + Send + Sync + RefUnwindSafe {
     fn expand(
         &self,
         subtree: &Subtree,
         attrs: Option<&Subtree>,
         env: &Env,
-    ) -> Result<Subtree, ExpansionError>;
+        spans: &ProcMacroSpans,
+    ) -> Result<ProcMacroExpansion, ExpansionError>;
 }
 
 #[derive(Debug, Clone)]
@@ -186,6 +231,9 @@ pub struct CrateData {
     pub env: Env,
     pub dependencies: Vec<Dependency>,
     pub proc_macro: Vec<ProcMacro>,
+    /// The LLVM data layout string of the target the crate is compiled for,
+    /// as reported by `rustc --print target-spec-json`.
+    pub target_layout: Option<Arc<str>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -216,6 +264,7 @@ impl CrateGraph {
         potential_cfg_options: CfgOptions,
         env: Env,
         proc_macro: Vec<ProcMacro>,
+        target_layout: Option<Arc<str>>,
     ) -> CrateId {
         let data = CrateData {
             root_file_id: file_id,
@@ -226,6 +275,7 @@ impl CrateGraph {
             env,
             proc_macro,
             dependencies: Vec::new(),
+            target_layout,
         };
         let crate_id = CrateId(self.arena.len() as u32);
         let prev = self.arena.insert(crate_id, data);
@@ -503,6 +553,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -512,6 +563,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         let crate3 = graph.add_crate_root(
             FileId(3u32),
@@ -521,6 +573,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         assert!(graph.add_dep(crate1, CrateName::new("crate2").unwrap(), crate2).is_ok());
         assert!(graph.add_dep(crate2, CrateName::new("crate3").unwrap(), crate3).is_ok());
@@ -538,6 +591,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -547,6 +601,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         assert!(graph.add_dep(crate1, CrateName::new("crate2").unwrap(), crate2).is_ok());
         assert!(graph.add_dep(crate2, CrateName::new("crate2").unwrap(), crate2).is_err());
@@ -563,6 +618,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -572,6 +628,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         let crate3 = graph.add_crate_root(
             FileId(3u32),
@@ -581,6 +638,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         assert!(graph.add_dep(crate1, CrateName::new("crate2").unwrap(), crate2).is_ok());
         assert!(graph.add_dep(crate2, CrateName::new("crate3").unwrap(), crate3).is_ok());
@@ -597,6 +655,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -606,6 +665,7 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            None,
         );
         assert!(graph
             .add_dep(crate1, CrateName::normalize_dashes("crate-name-with-dashes"), crate2)
@@ -12,7 +12,8 @@ pub use crate::{
     change::Change,
     input::{
         CrateData, CrateDisplayName, CrateGraph, CrateId, CrateName, Dependency, Edition, Env,
-        ProcMacro, ProcMacroExpander, ProcMacroId, ProcMacroKind, SourceRoot, SourceRootId,
+        ProcMacro, ProcMacroDiagnostic, ProcMacroDiagnosticLevel, ProcMacroExpander,
+        ProcMacroExpansion, ProcMacroId, ProcMacroKind, ProcMacroSpans, SourceRoot, SourceRootId,
     },
 };
 pub use salsa::{self, Cancelled};
@@ -54,6 +55,9 @@ pub trait FileLoader {
     /// Text of the file.
     fn file_text(&self, file_id: FileId) -> Arc<String>;
     fn resolve_path(&self, path: AnchoredPath) -> Option<FileId>;
+    /// Path of the file, reported to procedural macros asking for the source
+    /// file of their spans.
+    fn file_path(&self, file_id: FileId) -> Option<VfsPath>;
     fn relevant_crates(&self, file_id: FileId) -> Arc<FxHashSet<CrateId>>;
 }
 
@@ -119,6 +123,11 @@ impl<T: SourceDatabaseExt> FileLoader for FileLoaderDelegate<&'_ T> {
         source_root.file_set.resolve_path(path)
     }
 
+    fn file_path(&self, file_id: FileId) -> Option<VfsPath> {
+        let source_root = self.0.file_source_root(file_id);
+        self.0.source_root(source_root).path_for_file(&file_id).cloned()
+    }
+
     fn relevant_crates(&self, file_id: FileId) -> Arc<FxHashSet<CrateId>> {
         let _p = profile::span("relevant_crates");
         let source_root = self.0.file_source_root(file_id);
@@ -3,12 +3,15 @@
 //!
 //! This probably isn't the best way to do this -- ideally, diagnistics should
 //! be expressed in terms of hir types themselves.
+use base_db::ProcMacroDiagnosticLevel;
 use cfg::{CfgExpr, CfgOptions};
 use either::Either;
 use hir_def::path::ModPath;
 use hir_expand::{name::Name, HirFileId, InFile};
 use syntax::{ast, AstPtr, SyntaxNodePtr, TextRange};
 
+use crate::{CastError, Local, Type};
+
 macro_rules! diagnostics {
     ($($diag:ident,)*) => {
         pub enum AnyDiagnostic {$(
@@ -29,6 +32,8 @@ diagnostics![
     BreakOutsideOfLoop,
     InactiveCode,
     IncorrectCase,
+    InvalidCast,
+    LossyCast,
     MacroError,
     MismatchedArgCount,
     MissingFields,
@@ -36,14 +41,23 @@ diagnostics![
     MissingOkOrSomeInTailExpr,
     MissingUnsafe,
     NoSuchField,
+    NonDivergingLetElse,
     RemoveThisSemicolon,
     ReplaceFilterMapNextWithFindMap,
+    TrivialCast,
+    TypeMismatch,
     UnimplementedBuiltinMacro,
+    UnreachableCode,
     UnresolvedExternCrate,
+    UnresolvedField,
     UnresolvedImport,
     UnresolvedMacroCall,
+    UnresolvedMethodCall,
     UnresolvedModule,
     UnresolvedProcMacro,
+    UnusedAssignment,
+    UnusedMut,
+    UnusedVariable,
 ];
 
 #[derive(Debug)]
@@ -75,6 +89,13 @@ pub struct InactiveCode {
     pub opts: CfgOptions,
 }
 
+/// A statement or match arm which can never be executed. The node is a
+/// statement, the tail expression of a block, or a match arm.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnreachableCode {
+    pub node: InFile<SyntaxNodePtr>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct UnresolvedProcMacro {
     pub node: InFile<SyntaxNodePtr>,
@@ -82,12 +103,20 @@ pub struct UnresolvedProcMacro {
     /// to use instead.
     pub precise_location: Option<TextRange>,
     pub macro_name: Option<String>,
+    /// Why the macro was not expanded, if it is known.
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct MacroError {
     pub node: InFile<SyntaxNodePtr>,
+    /// If the diagnostic can be pinpointed more accurately than via `node`, this is the `TextRange`
+    /// to use instead.
+    pub precise_location: Option<TextRange>,
     pub message: String,
+    /// Expansion errors are always errors, diagnostics emitted by proc macros
+    /// keep the level the macro gave them.
+    pub level: ProcMacroDiagnosticLevel,
 }
 
 #[derive(Debug)]
@@ -125,6 +154,25 @@ pub struct ReplaceFilterMapNextWithFindMap {
     pub next_expr: AstPtr<ast::Expr>,
 }
 
+#[derive(Debug)]
+pub struct NonDivergingLetElse {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedField {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub receiver: Type,
+    pub name: Name,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedMethodCall {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub receiver: Type,
+    pub name: Name,
+}
+
 #[derive(Debug)]
 pub struct MismatchedArgCount {
     pub call_expr: InFile<AstPtr<ast::Expr>>,
@@ -144,6 +192,30 @@ pub struct MissingOkOrSomeInTailExpr {
     pub required: String,
 }
 
+#[derive(Debug)]
+pub struct TypeMismatch {
+    // FIXME: add mismatches in patterns as well
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub expected: Type,
+    pub actual: Type,
+}
+
+#[derive(Debug)]
+pub struct UnusedVariable {
+    pub local: Local,
+}
+
+#[derive(Debug)]
+pub struct UnusedMut {
+    pub local: Local,
+}
+
+#[derive(Debug)]
+pub struct UnusedAssignment {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub local: Local,
+}
+
 #[derive(Debug)]
 pub struct MissingMatchArms {
     pub file: HirFileId,
@@ -152,3 +224,24 @@ pub struct MissingMatchArms {
 }
 
 pub use hir_ty::diagnostics::IncorrectCase;
+
+#[derive(Debug)]
+pub struct InvalidCast {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub error: CastError,
+    pub expr_ty: Type,
+    pub cast_ty: Type,
+}
+
+#[derive(Debug)]
+pub struct TrivialCast {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub ty: Type,
+}
+
+#[derive(Debug)]
+pub struct LossyCast {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub expr_ty: Type,
+    pub cast_ty: Type,
+}
@@ -34,12 +34,14 @@ mod display;
 use std::{iter, sync::Arc};
 
 use arrayvec::ArrayVec;
-use base_db::{CrateDisplayName, CrateId, Edition, FileId};
+use base_db::{
+    CrateDisplayName, CrateId, Edition, FileId, ProcMacroDiagnostic, ProcMacroDiagnosticLevel,
+};
 use either::Either;
 use hir_def::{
-    adt::{ReprKind, VariantData},
-    body::{BodyDiagnostic, SyntheticSyntax},
-    expr::{BindingAnnotation, LabelId, Pat, PatId},
+    adt::{ReprOptions, VariantData},
+    body::{BodyDiagnostic, BodySourceMap, SyntheticSyntax},
+    expr::{BindingAnnotation, Expr, ExprId, LabelId, Pat, PatId},
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
     nameres,
@@ -51,12 +53,12 @@ use hir_def::{
     LocalEnumVariantId, LocalFieldId, Lookup, ModuleId, StaticId, StructId, TraitId, TypeAliasId,
     TypeParamId, UnionId,
 };
-use hir_expand::{name::name, MacroCallKind, MacroDefId, MacroDefKind};
+use hir_expand::{name::name, MacroCallId, MacroCallKind, MacroDefId, MacroDefKind};
 use hir_ty::{
     autoderef,
     consteval::ConstExt,
     could_unify,
-    diagnostics::BodyValidationDiagnostic,
+    diagnostics::{BodyValidationDiagnostic, LivenessDiagnostic},
     method_resolution::{self, TyFingerprint},
     primitive::UintTy,
     subst_prefix,
@@ -82,11 +84,13 @@ use crate::db::{DefDatabase, HirDatabase};
 pub use crate::{
     attrs::{HasAttrs, Namespace},
     diagnostics::{
-        AnyDiagnostic, BreakOutsideOfLoop, InactiveCode, IncorrectCase, MacroError,
-        MismatchedArgCount, MissingFields, MissingMatchArms, MissingOkOrSomeInTailExpr,
-        MissingUnsafe, NoSuchField, RemoveThisSemicolon, ReplaceFilterMapNextWithFindMap,
-        UnimplementedBuiltinMacro, UnresolvedExternCrate, UnresolvedImport, UnresolvedMacroCall,
-        UnresolvedModule, UnresolvedProcMacro,
+        AnyDiagnostic, BreakOutsideOfLoop, InactiveCode, IncorrectCase, InvalidCast, LossyCast,
+        MacroError, MismatchedArgCount, MissingFields, MissingMatchArms, MissingOkOrSomeInTailExpr,
+        MissingUnsafe, NoSuchField, NonDivergingLetElse, RemoveThisSemicolon,
+        ReplaceFilterMapNextWithFindMap, TrivialCast, TypeMismatch, UnimplementedBuiltinMacro,
+        UnreachableCode, UnresolvedExternCrate, UnresolvedField, UnresolvedImport,
+        UnresolvedMacroCall, UnresolvedMethodCall, UnresolvedModule, UnresolvedProcMacro,
+        UnusedAssignment, UnusedMut, UnusedVariable,
     },
     has_source::HasSource,
     semantics::{PathResolution, Semantics, SemanticsScope},
@@ -115,10 +119,17 @@ pub use {
         visibility::Visibility,
     },
     hir_expand::{
+        builtin_derive::builtin_derive_impl,
         name::{known, Name},
-        ExpandResult, HirFileId, InFile, MacroFile, Origin,
+        ExpandResult, ExpansionTrace, HirFileId, InFile, MacroFile, MatchFailure, Origin,
+        RuleTrace,
+    },
+    hir_ty::{
+        consteval::{ComputedExpr, ConstEvalError},
+        display::HirDisplay,
+        layout::{Layout, LayoutError},
+        CastError,
     },
-    hir_ty::display::HirDisplay,
 };
 
 // These are negative re-exports: pub using these names is forbidden, they
@@ -489,7 +500,7 @@ impl Module {
                     );
                 }
 
-                DefDiagnosticKind::UnresolvedProcMacro { ast } => {
+                DefDiagnosticKind::UnresolvedProcMacro { ast, reason } => {
                     let mut precise_location = None;
                     let (node, name) = match ast {
                         MacroCallKind::FnLike { ast_id, .. } => {
@@ -544,7 +555,13 @@ impl Module {
                         }
                     };
                     acc.push(
-                        UnresolvedProcMacro { node, precise_location, macro_name: name }.into(),
+                        UnresolvedProcMacro {
+                            node,
+                            precise_location,
+                            macro_name: name,
+                            reason: reason.clone(),
+                        }
+                        .into(),
                     );
                 }
 
@@ -560,19 +577,21 @@ impl Module {
                 }
 
                 DefDiagnosticKind::MacroError { ast, message } => {
-                    let node = match ast {
-                        MacroCallKind::FnLike { ast_id, .. } => {
-                            let node = ast_id.to_node(db.upcast());
-                            ast_id.with_value(SyntaxNodePtr::from(AstPtr::new(&node)))
-                        }
-                        MacroCallKind::Derive { ast_id, .. }
-                        | MacroCallKind::Attr { ast_id, .. } => {
-                            // FIXME: point to the attribute instead, this creates very large diagnostics
-                            let node = ast_id.to_node(db.upcast());
-                            ast_id.with_value(SyntaxNodePtr::from(AstPtr::new(&node)))
+                    let node = macro_call_node(db, ast);
+                    acc.push(
+                        MacroError {
+                            node,
+                            precise_location: None,
+                            message: message.clone(),
+                            level: ProcMacroDiagnosticLevel::Error,
                         }
-                    };
-                    acc.push(MacroError { node, message: message.clone() }.into());
+                        .into(),
+                    );
+                }
+
+                DefDiagnosticKind::ProcMacroDiagnostic { ast, call, diagnostic } => {
+                    let node = macro_call_node(db, ast);
+                    acc.push(proc_macro_diagnostic(db, node, *call, diagnostic).into());
                 }
 
                 DefDiagnosticKind::UnimplementedBuiltinMacro { ast } => {
@@ -673,6 +692,28 @@ impl Field {
     pub fn parent_def(&self, _db: &dyn HirDatabase) -> VariantDef {
         self.parent
     }
+
+    pub fn layout(&self, db: &dyn HirDatabase) -> Result<Layout, LayoutError> {
+        self.ty(db).layout(db)
+    }
+
+    /// The offset of the field within its struct, union or enum variant.
+    pub fn offset(&self, db: &dyn HirDatabase) -> Option<u64> {
+        let idx = u32::from(self.id.into_raw()) as usize;
+        match self.parent {
+            VariantDef::Struct(it) => Adt::from(it).layout(db).ok()?.fields.offset(idx),
+            VariantDef::Union(it) => Adt::from(it).layout(db).ok()?.fields.offset(idx),
+            VariantDef::Variant(it) => {
+                let layout = Adt::from(it.parent).layout(db).ok()?;
+                match layout.variants {
+                    hir_ty::layout::Variants::Single => layout.fields.offset(idx),
+                    hir_ty::layout::Variants::Multiple { variants, .. } => {
+                        variants.get(u32::from(it.id.into_raw()) as usize)?.fields.offset(idx)
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl HasVisibility for Field {
@@ -711,8 +752,8 @@ impl Struct {
         Type::from_def(db, self.id.lookup(db.upcast()).container.krate(), self.id)
     }
 
-    pub fn repr(self, db: &dyn HirDatabase) -> Option<ReprKind> {
-        db.struct_data(self.id).repr.clone()
+    pub fn repr(self, db: &dyn HirDatabase) -> Option<ReprOptions> {
+        db.struct_data(self.id).repr
     }
 
     pub fn kind(self, db: &dyn HirDatabase) -> StructKind {
@@ -871,6 +912,11 @@ impl Adt {
             Adt::Enum(e) => e.name(db),
         }
     }
+
+    /// The layout of this ADT, which is only known if it isn't generic.
+    pub fn layout(self, db: &dyn HirDatabase) -> Result<Layout, LayoutError> {
+        self.ty(db).layout(db)
+    }
 }
 
 impl HasVisibility for Adt {
@@ -1029,15 +1075,22 @@ impl Function {
                 BodyDiagnostic::MacroError { node, message } => acc.push(
                     MacroError {
                         node: node.clone().map(|it| it.into()),
+                        precise_location: None,
                         message: message.to_string(),
+                        level: ProcMacroDiagnosticLevel::Error,
                     }
                     .into(),
                 ),
-                BodyDiagnostic::UnresolvedProcMacro { node } => acc.push(
+                BodyDiagnostic::ProcMacroDiagnostic { node, call, diagnostic } => acc.push(
+                    proc_macro_diagnostic(db, node.clone().map(|it| it.into()), *call, diagnostic)
+                        .into(),
+                ),
+                BodyDiagnostic::UnresolvedProcMacro { node, reason } => acc.push(
                     UnresolvedProcMacro {
                         node: node.clone().map(|it| it.into()),
                         precise_location: None,
                         macro_name: None,
+                        reason: reason.clone(),
                     }
                     .into(),
                 ),
@@ -1061,6 +1114,48 @@ impl Function {
                         .expect("break outside of loop in synthetic syntax");
                     acc.push(BreakOutsideOfLoop { expr }.into())
                 }
+                hir_ty::InferenceDiagnostic::NonDivergingLetElse { expr } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        acc.push(NonDivergingLetElse { expr }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::UnresolvedField { expr, receiver, name } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let receiver = Type::new(db, krate, self.id, receiver.clone());
+                        acc.push(UnresolvedField { expr, receiver, name: name.clone() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::UnresolvedMethodCall { expr, receiver, name } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let receiver = Type::new(db, krate, self.id, receiver.clone());
+                        acc.push(UnresolvedMethodCall { expr, receiver, name: name.clone() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::UnreachableCode { node } => {
+                    if let Some(node) = unreachable_stmt(db, &source_map, *node) {
+                        acc.push(UnreachableCode { node }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::InvalidCast { expr, error, expr_ty, cast_ty } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let expr_ty = Type::new(db, krate, self.id, expr_ty.clone());
+                        let cast_ty = Type::new(db, krate, self.id, cast_ty.clone());
+                        acc.push(InvalidCast { expr, error: *error, expr_ty, cast_ty }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::TrivialCast { expr, ty } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let ty = Type::new(db, krate, self.id, ty.clone());
+                        acc.push(TrivialCast { expr, ty }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::LossyCast { expr, expr_ty, cast_ty } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let expr_ty = Type::new(db, krate, self.id, expr_ty.clone());
+                        let cast_ty = Type::new(db, krate, self.id, cast_ty.clone());
+                        acc.push(LossyCast { expr, expr_ty, cast_ty }.into())
+                    }
+                }
             }
         }
 
@@ -1074,6 +1169,9 @@ impl Function {
             }
         }
 
+        // Mismatches which are already reported by a more specific diagnostic.
+        let body = db.body(self.id.into());
+        let mut reported_mismatches = FxHashSet::default();
         for diagnostic in BodyValidationDiagnostic::collect(db, self.id.into()) {
             match diagnostic {
                 BodyValidationDiagnostic::RecordMissingFields {
@@ -1160,12 +1258,14 @@ impl Function {
                     }
                 }
                 BodyValidationDiagnostic::RemoveThisSemicolon { expr } => {
+                    reported_mismatches.insert(body.body_expr);
                     match source_map.expr_syntax(expr) {
                         Ok(expr) => acc.push(RemoveThisSemicolon { expr }.into()),
                         Err(SyntheticSyntax) => (),
                     }
                 }
                 BodyValidationDiagnostic::MissingOkOrSomeInTailExpr { expr, required } => {
+                    reported_mismatches.insert(expr);
                     match source_map.expr_syntax(expr) {
                         Ok(expr) => acc.push(MissingOkOrSomeInTailExpr { expr, required }.into()),
                         Err(SyntheticSyntax) => (),
@@ -1195,6 +1295,73 @@ impl Function {
                         Err(SyntheticSyntax) => (),
                     }
                 }
+                BodyValidationDiagnostic::UnreachableMatchArm { arm_pat } => {
+                    if let Some(node) = unreachable_match_arm(db, &source_map, arm_pat) {
+                        acc.push(UnreachableCode { node }.into())
+                    }
+                }
+            }
+        }
+
+        for (expr, mismatch) in infer.expr_type_mismatches() {
+            if reported_mismatches.contains(&expr) {
+                continue;
+            }
+            // A block whose tail expression mismatches is reported at the tail only.
+            if let Expr::Block { tail: Some(tail), .. } = &body[expr] {
+                if infer.type_mismatch_for_expr(*tail).is_some() {
+                    continue;
+                }
+            }
+            let expected = Type::new(db, krate, self.id, mismatch.expected.clone());
+            let actual = Type::new(db, krate, self.id, mismatch.actual.clone());
+            // Mismatches involving unknown types are most likely caused by
+            // unresolved code, don't report them.
+            if expected.contains_unknown() || actual.contains_unknown() {
+                continue;
+            }
+            if let Ok(expr) = source_map.expr_syntax(expr) {
+                acc.push(TypeMismatch { expr, expected, actual }.into());
+            }
+        }
+
+        // Bodies with broken macro calls are missing code which may use the
+        // variables, so we would report false positives.
+        let has_macro_errors = source_map.diagnostics().iter().any(|diag| match diag {
+            // Diagnostics emitted by a proc macro don't mean that its expansion is missing.
+            BodyDiagnostic::InactiveCode { .. } | BodyDiagnostic::ProcMacroDiagnostic { .. } => {
+                false
+            }
+            BodyDiagnostic::MacroError { .. }
+            | BodyDiagnostic::UnresolvedProcMacro { .. }
+            | BodyDiagnostic::UnresolvedMacroCall { .. } => true,
+        });
+        if !has_macro_errors {
+            let is_in_macro = |pat| match source_map.pat_syntax(pat) {
+                Ok(src) => src.file_id.call_node(db.upcast()).is_some(),
+                Err(SyntheticSyntax) => true,
+            };
+            for diagnostic in LivenessDiagnostic::collect(db, self.id.into()) {
+                match diagnostic {
+                    LivenessDiagnostic::UnusedVariable { pat } => {
+                        if !is_in_macro(pat) {
+                            let local = Local { parent: self.id.into(), pat_id: pat };
+                            acc.push(UnusedVariable { local }.into())
+                        }
+                    }
+                    LivenessDiagnostic::UnusedMut { pat } => {
+                        if !is_in_macro(pat) {
+                            let local = Local { parent: self.id.into(), pat_id: pat };
+                            acc.push(UnusedMut { local }.into())
+                        }
+                    }
+                    LivenessDiagnostic::UnusedAssignment { expr, pat } => {
+                        if let Ok(expr) = source_map.expr_syntax(expr) {
+                            let local = Local { parent: self.id.into(), pat_id: pat };
+                            acc.push(UnusedAssignment { expr, local }.into())
+                        }
+                    }
+                }
             }
         }
 
@@ -1203,6 +1370,19 @@ impl Function {
         }
     }
 
+    /// Statements and match arms of the body which can never be executed.
+    pub fn unreachable_code(self, db: &dyn HirDatabase) -> Vec<UnreachableCode> {
+        let source_map = db.body_with_source_map(self.id.into()).1;
+        let unreachable = db.unreachable_code(self.id.into());
+        let stmts =
+            unreachable.stmts.iter().filter_map(|&node| unreachable_stmt(db, &source_map, node));
+        let arms = unreachable
+            .match_arms
+            .iter()
+            .filter_map(|&arm_pat| unreachable_match_arm(db, &source_map, arm_pat));
+        stmts.chain(arms).map(|node| UnreachableCode { node }).collect()
+    }
+
     /// Whether this function declaration has a definition.
     ///
     /// This is false in the case of required (not provided) trait methods.
@@ -1224,6 +1404,86 @@ impl Function {
     }
 }
 
+/// Returns the syntax node of the macro call or of the item a derive or
+/// attribute macro is applied to.
+fn macro_call_node(db: &dyn HirDatabase, ast: &MacroCallKind) -> InFile<SyntaxNodePtr> {
+    match ast {
+        MacroCallKind::FnLike { ast_id, .. } => {
+            let node = ast_id.to_node(db.upcast());
+            ast_id.with_value(SyntaxNodePtr::from(AstPtr::new(&node)))
+        }
+        MacroCallKind::Derive { ast_id, .. } | MacroCallKind::Attr { ast_id, .. } => {
+            // FIXME: point to the attribute instead, this creates very large diagnostics
+            let node = ast_id.to_node(db.upcast());
+            ast_id.with_value(SyntaxNodePtr::from(AstPtr::new(&node)))
+        }
+    }
+}
+
+/// Turns a diagnostic emitted by a proc macro into a `MacroError` pointing at
+/// the input tokens it was emitted for. Child diagnostics are appended to the
+/// message, the way rustc prints them.
+fn proc_macro_diagnostic(
+    db: &dyn HirDatabase,
+    node: InFile<SyntaxNodePtr>,
+    call: MacroCallId,
+    diagnostic: &ProcMacroDiagnostic,
+) -> MacroError {
+    let precise_location =
+        hir_expand::proc_macro::diagnostic_range(db.upcast(), call, &diagnostic.spans)
+            .filter(|range| range.file_id == node.file_id)
+            .map(|range| range.value);
+    let mut message = diagnostic.message.clone();
+    for child in &diagnostic.children {
+        let level = match child.level {
+            ProcMacroDiagnosticLevel::Error => "error",
+            ProcMacroDiagnosticLevel::Warning => "warning",
+            ProcMacroDiagnosticLevel::Note => "note",
+            ProcMacroDiagnosticLevel::Help => "help",
+        };
+        format_to!(message, "\n{}: {}", level, child.message);
+    }
+    MacroError { node, precise_location, message, level: diagnostic.level }
+}
+
+/// Maps an unreachable statement or tail expression to its syntax node.
+fn unreachable_stmt(
+    db: &dyn HirDatabase,
+    source_map: &BodySourceMap,
+    node: Either<ExprId, PatId>,
+) -> Option<InFile<SyntaxNodePtr>> {
+    let node = match node {
+        Either::Left(expr) => {
+            let src = source_map.expr_syntax(expr).ok()?;
+            let root = src.file_syntax(db.upcast());
+            let expr = src.value.to_node(&root);
+            let stmt = expr.syntax().parent().filter(|it| ast::ExprStmt::can_cast(it.kind()));
+            src.with_value(stmt.unwrap_or_else(|| expr.syntax().clone()))
+        }
+        Either::Right(pat) => {
+            let src = source_map.pat_syntax(pat).ok()?;
+            let root = src.file_syntax(db.upcast());
+            let pat = src.value.as_ref().left()?.to_node(&root);
+            let let_stmt = pat.syntax().ancestors().find_map(<ast::LetStmt as AstNode>::cast)?;
+            src.with_value(let_stmt.syntax().clone())
+        }
+    };
+    Some(node.map(|it| SyntaxNodePtr::new(&it)))
+}
+
+/// Maps the pattern of an unreachable match arm to the syntax node of the arm.
+fn unreachable_match_arm(
+    db: &dyn HirDatabase,
+    source_map: &BodySourceMap,
+    arm_pat: PatId,
+) -> Option<InFile<SyntaxNodePtr>> {
+    let src = source_map.pat_syntax(arm_pat).ok()?;
+    let root = src.file_syntax(db.upcast());
+    let pat = src.value.as_ref().left()?.to_node(&root);
+    let arm = pat.syntax().ancestors().find_map(<ast::MatchArm as AstNode>::cast)?;
+    Some(src.with_value(SyntaxNodePtr::new(arm.syntax())))
+}
+
 // Note: logically, this belongs to `hir_ty`, but we are not using it there yet.
 pub enum Access {
     Shared,
@@ -1338,6 +1598,10 @@ impl Const {
     pub fn type_ref(self, db: &dyn HirDatabase) -> TypeRef {
         db.const_data(self.id).type_ref.as_ref().clone()
     }
+
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        db.const_eval(self.id)
+    }
 }
 
 impl HasVisibility for Const {
@@ -1365,6 +1629,10 @@ impl Static {
     pub fn is_mut(self, db: &dyn HirDatabase) -> bool {
         db.static_data(self.id).mutable
     }
+
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        db.const_eval_static(self.id)
+    }
 }
 
 impl HasVisibility for Static {
@@ -2035,6 +2303,10 @@ pub struct Type {
 }
 
 impl Type {
+    pub fn layout(&self, db: &dyn HirDatabase) -> Result<Layout, LayoutError> {
+        hir_ty::layout::layout_of_ty(db, &self.ty, self.krate)
+    }
+
     pub(crate) fn new_with_resolver(
         db: &dyn HirDatabase,
         resolver: &Resolver,
@@ -2235,7 +2507,7 @@ impl Type {
 
         let adt = adt_id.into();
         match adt {
-            Adt::Struct(s) => matches!(s.repr(db), Some(ReprKind::Packed)),
+            Adt::Struct(s) => s.repr(db).map_or(false, |it| it.packed.is_some()),
             _ => false,
         }
     }
@@ -2569,6 +2841,113 @@ impl Type {
     }
 }
 
+/// An implicit conversion inserted by the compiler on an expression, like an
+/// autoderef or an autoref. See [`Semantics::expr_adjustments`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Adjustment {
+    pub source: Type,
+    pub target: Type,
+    pub kind: Adjust,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Adjust {
+    /// Go from `!` to any type.
+    NeverToAny,
+    /// Dereference once; `Some` for a call to `Deref::deref` or `DerefMut::deref_mut`.
+    Deref(Option<OverloadedDeref>),
+    /// Take the address and produce a reference or raw pointer.
+    Borrow(AutoBorrow),
+    /// A pointer-to-pointer cast, like reifying a fn item or an unsizing.
+    Pointer(PointerCast),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoBorrow {
+    Ref(Mutability),
+    RawPtr(Mutability),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverloadedDeref(pub Mutability);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerCast {
+    /// Go from a fn-item type to a fn-pointer type.
+    ReifyFnPointer,
+    /// Go from a safe fn pointer to an unsafe fn pointer.
+    UnsafeFnPointer,
+    /// Go from a non-capturing closure to an fn pointer or an unsafe fn pointer.
+    ClosureFnPointer(Safety),
+    /// Go from a mut raw pointer to a const raw pointer.
+    MutToConstPointer,
+    /// Unsize a pointer/reference value, e.g. `&[T; n]` to `&[T]`.
+    Unsize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Safety {
+    Safe,
+    Unsafe,
+}
+
+impl From<hir_ty::Adjust> for Adjust {
+    fn from(adjust: hir_ty::Adjust) -> Adjust {
+        let mutability = |m| Mutability::from_mutable(m == hir_ty::Mutability::Mut);
+        match adjust {
+            hir_ty::Adjust::NeverToAny => Adjust::NeverToAny,
+            hir_ty::Adjust::Deref(deref) => Adjust::Deref(
+                deref.map(|hir_ty::OverloadedDeref(m)| OverloadedDeref(mutability(m))),
+            ),
+            hir_ty::Adjust::Borrow(hir_ty::AutoBorrow::Ref(m)) => {
+                Adjust::Borrow(AutoBorrow::Ref(mutability(m)))
+            }
+            hir_ty::Adjust::Borrow(hir_ty::AutoBorrow::RawPtr(m)) => {
+                Adjust::Borrow(AutoBorrow::RawPtr(mutability(m)))
+            }
+            hir_ty::Adjust::Pointer(cast) => Adjust::Pointer(match cast {
+                hir_ty::PointerCast::ReifyFnPointer => PointerCast::ReifyFnPointer,
+                hir_ty::PointerCast::UnsafeFnPointer => PointerCast::UnsafeFnPointer,
+                hir_ty::PointerCast::ClosureFnPointer(safety) => {
+                    PointerCast::ClosureFnPointer(match safety {
+                        hir_ty::Safety::Safe => Safety::Safe,
+                        hir_ty::Safety::Unsafe => Safety::Unsafe,
+                    })
+                }
+                hir_ty::PointerCast::MutToConstPointer => PointerCast::MutToConstPointer,
+                hir_ty::PointerCast::Unsize => PointerCast::Unsize,
+            }),
+        }
+    }
+}
+
+/// A variable captured by a closure. See [`Semantics::closure_captures`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClosureCapture {
+    pub local: Local,
+    pub kind: CaptureKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CaptureKind {
+    /// Captured by shared reference, `&x`.
+    SharedRef,
+    /// Captured by mutable reference, `&mut x`.
+    MutableRef,
+    /// Moved (or copied) into the closure.
+    Move,
+}
+
+impl From<hir_ty::CaptureKind> for CaptureKind {
+    fn from(kind: hir_ty::CaptureKind) -> CaptureKind {
+        match kind {
+            hir_ty::CaptureKind::ByRef => CaptureKind::SharedRef,
+            hir_ty::CaptureKind::ByMutRef => CaptureKind::MutableRef,
+            hir_ty::CaptureKind::ByValue => CaptureKind::Move,
+        }
+    }
+}
+
 // FIXME: closures
 #[derive(Debug)]
 pub struct Callable {
@@ -10,7 +10,7 @@ use hir_def::{
     resolver::{self, HasResolver, Resolver, TypeNs},
     AsMacroCall, FunctionId, TraitId, VariantId,
 };
-use hir_expand::{name::AsName, ExpansionInfo};
+use hir_expand::{name::AsName, ExpansionInfo, ExpansionTrace};
 use hir_ty::{associated_type_shorthand_candidates, Interner};
 use itertools::Itertools;
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -24,9 +24,9 @@ use crate::{
     db::HirDatabase,
     semantics::source_to_def::{ChildContainer, SourceToDefCache, SourceToDefCtx},
     source_analyzer::{resolve_hir_path, SourceAnalyzer},
-    Access, AssocItem, Callable, ConstParam, Crate, Field, Function, HirFileId, Impl, InFile,
-    Label, LifetimeParam, Local, MacroDef, Module, ModuleDef, Name, Path, ScopeDef, Trait, Type,
-    TypeAlias, TypeParam, VariantDef,
+    Access, Adjustment, AssocItem, Callable, ClosureCapture, ConstParam, Crate, Field, Function,
+    HirFileId, Impl, InFile, Label, LifetimeParam, Local, MacroDef, Module, ModuleDef, Name, Path,
+    ScopeDef, Trait, Type, TypeAlias, TypeParam, VariantDef,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -125,10 +125,21 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.expand_attr_macro(item)
     }
 
+    /// If `attr` is a `#[derive]` attribute, expands the derive macros it invokes.
+    pub fn expand_derive_macro(&self, attr: &ast::Attr) -> Option<Vec<SyntaxNode>> {
+        self.imp.expand_derive_macro(attr)
+    }
+
     pub fn is_attr_macro_call(&self, item: &ast::Item) -> bool {
         self.imp.is_attr_macro_call(item)
     }
 
+    /// Records how the arguments of `macro_call` were matched against the rules
+    /// of a `macro_rules` macro.
+    pub fn trace_macro_expansion(&self, macro_call: &ast::MacroCall) -> Option<ExpansionTrace> {
+        self.imp.trace_macro_expansion(macro_call)
+    }
+
     pub fn speculative_expand(
         &self,
         actual_macro_call: &ast::MacroCall,
@@ -154,6 +165,12 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.original_range(node)
     }
 
+    /// Maps a token from a macro expansion back to the token in a real file it
+    /// was produced from, either in the macro call or in the macro definition.
+    pub fn original_token_range(&self, token: &SyntaxToken) -> Option<FileRange> {
+        self.imp.original_token_range(token)
+    }
+
     pub fn diagnostics_display_range(&self, diagnostics: InFile<SyntaxNodePtr>) -> FileRange {
         self.imp.diagnostics_display_range(diagnostics)
     }
@@ -216,6 +233,17 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.type_of_expr(expr)
     }
 
+    /// Returns the implicit conversions applied to the value of `expr`, in the
+    /// order they are applied, e.g. autoderefs and autorefs of a method receiver.
+    pub fn expr_adjustments(&self, expr: &ast::Expr) -> Option<Vec<Adjustment>> {
+        self.imp.expr_adjustments(expr)
+    }
+
+    /// Returns the variables captured by `closure`, in order of their first use.
+    pub fn closure_captures(&self, closure: &ast::ClosureExpr) -> Option<Vec<ClosureCapture>> {
+        self.imp.closure_captures(closure)
+    }
+
     pub fn type_of_pat(&self, pat: &ast::Pat) -> Option<Type> {
         self.imp.type_of_pat(pat)
     }
@@ -354,12 +382,40 @@ impl<'db> SemanticsImpl<'db> {
         Some(node)
     }
 
+    fn expand_derive_macro(&self, attr: &ast::Attr) -> Option<Vec<SyntaxNode>> {
+        let item = attr.syntax().parent().and_then(ast::Item::cast)?;
+        let sa = self.analyze(item.syntax());
+        let item = InFile::new(sa.file_id, &item);
+        let src = InFile::new(sa.file_id, attr.clone());
+        let macro_call_ids = self.with_ctx(|ctx| ctx.attr_to_derive_macro_call(item, src))?;
+        let expansions = macro_call_ids
+            .iter()
+            .filter_map(|call| {
+                let file_id = call.as_file();
+                let node = self.db.parse_or_expand(file_id)?;
+                self.cache(node.clone(), file_id);
+                Some(node)
+            })
+            .collect();
+        Some(expansions)
+    }
+
     fn is_attr_macro_call(&self, item: &ast::Item) -> bool {
         let sa = self.analyze(item.syntax());
         let src = InFile::new(sa.file_id, item.clone());
         self.with_ctx(|ctx| ctx.item_to_macro_call(src).is_some())
     }
 
+    fn trace_macro_expansion(&self, macro_call: &ast::MacroCall) -> Option<ExpansionTrace> {
+        let sa = self.analyze(macro_call.syntax());
+        let macro_call = InFile::new(sa.file_id, macro_call);
+        let krate = sa.resolver.krate()?;
+        let macro_call_id = macro_call.as_call_id(self.db.upcast(), krate, |path| {
+            sa.resolver.resolve_path_as_macro(self.db.upcast(), &path)
+        })?;
+        hir_expand::db::trace_macro_expansion(self.db.upcast(), macro_call_id)
+    }
+
     fn speculative_expand(
         &self,
         actual_macro_call: &ast::MacroCall,
@@ -473,6 +529,15 @@ impl<'db> SemanticsImpl<'db> {
         node.as_ref().original_file_range(self.db.upcast())
     }
 
+    fn original_token_range(&self, token: &SyntaxToken) -> Option<FileRange> {
+        let mut token = self.find_file(token.parent()?).with_value(token.clone());
+        while let Some(info) = token.file_id.expansion_info(self.db.upcast()) {
+            token = info.map_token_up(token.as_ref())?.0;
+        }
+        let file_id = token.file_id.original_file(self.db.upcast());
+        Some(FileRange { file_id, range: token.value.text_range() })
+    }
+
     fn diagnostics_display_range(&self, src: InFile<SyntaxNodePtr>) -> FileRange {
         let root = self.db.parse_or_expand(src.file_id).unwrap();
         let node = src.value.to_node(&root);
@@ -560,6 +625,14 @@ impl<'db> SemanticsImpl<'db> {
         self.analyze(expr.syntax()).type_of_expr(self.db, expr)
     }
 
+    fn expr_adjustments(&self, expr: &ast::Expr) -> Option<Vec<Adjustment>> {
+        self.analyze(expr.syntax()).expr_adjustments(self.db, expr)
+    }
+
+    fn closure_captures(&self, closure: &ast::ClosureExpr) -> Option<Vec<ClosureCapture>> {
+        self.analyze(closure.syntax()).closure_captures(self.db, closure)
+    }
+
     fn type_of_pat(&self, pat: &ast::Pat) -> Option<Type> {
         self.analyze(pat.syntax()).type_of_pat(self.db, pat)
     }
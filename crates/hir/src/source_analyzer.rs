@@ -18,7 +18,11 @@ use hir_def::{
     resolver::{resolver_for_scope, Resolver, TypeNs, ValueNs},
     AsMacroCall, DefWithBodyId, FieldId, FunctionId, LocalFieldId, VariantId,
 };
-use hir_expand::{hygiene::Hygiene, name::AsName, HirFileId, InFile};
+use hir_expand::{
+    hygiene::{Hygiene, SyntaxContext},
+    name::AsName,
+    HirFileId, InFile,
+};
 use hir_ty::{
     diagnostics::{record_literal_missing_fields, record_pattern_missing_fields},
     InferenceResult, Interner, Substitution, TyExt, TyLoweringContext,
@@ -29,8 +33,9 @@ use syntax::{
 };
 
 use crate::{
-    db::HirDatabase, semantics::PathResolution, Adt, BuiltinType, Const, Field, Function, Local,
-    MacroDef, ModuleDef, Static, Struct, Trait, Type, TypeAlias, TypeParam, Variant,
+    db::HirDatabase, semantics::PathResolution, Adjustment, Adt, BuiltinType, ClosureCapture,
+    Const, Field, Function, Local, MacroDef, ModuleDef, Static, Struct, Trait, Type, TypeAlias,
+    TypeParam, Variant,
 };
 use base_db::CrateId;
 
@@ -122,6 +127,46 @@ impl SourceAnalyzer {
         Type::new_with_resolver(db, &self.resolver, ty)
     }
 
+    pub(crate) fn expr_adjustments(
+        &self,
+        db: &dyn HirDatabase,
+        expr: &ast::Expr,
+    ) -> Option<Vec<Adjustment>> {
+        let expr_id = self.expr_id(db, expr)?;
+        let infer = self.infer.as_ref()?;
+        let mut source = Type::new_with_resolver(db, &self.resolver, infer[expr_id].clone())?;
+        infer
+            .expr_adjustments(expr_id)
+            .iter()
+            .map(|adjustment| {
+                let target =
+                    Type::new_with_resolver(db, &self.resolver, adjustment.target.clone())?;
+                let source = std::mem::replace(&mut source, target.clone());
+                Some(Adjustment { source, target, kind: adjustment.kind.into() })
+            })
+            .collect()
+    }
+
+    pub(crate) fn closure_captures(
+        &self,
+        db: &dyn HirDatabase,
+        closure: &ast::ClosureExpr,
+    ) -> Option<Vec<ClosureCapture>> {
+        let expr_id = self.expr_id(db, &closure.clone().into())?;
+        let parent = self.resolver.body_owner()?;
+        let captures = self
+            .infer
+            .as_ref()?
+            .closure_captures(expr_id)
+            .iter()
+            .map(|capture| ClosureCapture {
+                local: Local { parent, pat_id: capture.local },
+                kind: capture.kind.into(),
+            })
+            .collect();
+        Some(captures)
+    }
+
     pub(crate) fn type_of_pat(&self, db: &dyn HirDatabase, pat: &ast::Pat) -> Option<Type> {
         let pat_id = self.pat_id(pat)?;
         let ty = self.infer.as_ref()?[pat_id].clone();
@@ -171,7 +216,11 @@ impl SourceAnalyzer {
             None
         } else {
             let path = ModPath::from_segments(PathKind::Plain, once(local_name.clone()));
-            match self.resolver.resolve_path_in_value_ns_fully(db.upcast(), &path) {
+            match self.resolver.resolve_path_in_value_ns_fully(
+                db.upcast(),
+                &path,
+                SyntaxContext::ROOT,
+            ) {
                 Some(ValueNs::LocalBinding(pat_id)) => {
                     Some(Local { pat_id, parent: self.resolver.body_owner()? })
                 }
@@ -236,8 +285,10 @@ impl SourceAnalyzer {
     ) -> Option<PathResolution> {
         let parent = || path.syntax().parent();
         let mut prefer_value_ns = false;
+        let mut syntax_context = SyntaxContext::ROOT;
         if let Some(path_expr) = parent().and_then(ast::PathExpr::cast) {
             let expr_id = self.expr_id(db, &path_expr.into())?;
+            syntax_context = self.body.as_ref()?.path_context(expr_id);
             let infer = self.infer.as_ref()?;
             if let Some(assoc) = infer.assoc_resolutions_for_expr(expr_id) {
                 return Some(PathResolution::AssocItem(assoc.into()));
@@ -311,7 +362,7 @@ impl SourceAnalyzer {
         if parent().map_or(false, |it| ast::Visibility::can_cast(it.kind())) {
             resolve_hir_path_qualifier(db, &self.resolver, &hir_path)
         } else {
-            resolve_hir_path_(db, &self.resolver, &hir_path, prefer_value_ns)
+            resolve_hir_path_(db, &self.resolver, &hir_path, prefer_value_ns, syntax_context)
         }
     }
 
@@ -482,7 +533,7 @@ pub(crate) fn resolve_hir_path(
     resolver: &Resolver,
     path: &Path,
 ) -> Option<PathResolution> {
-    resolve_hir_path_(db, resolver, path, false)
+    resolve_hir_path_(db, resolver, path, false, SyntaxContext::ROOT)
 }
 
 fn resolve_hir_path_(
@@ -490,6 +541,7 @@ fn resolve_hir_path_(
     resolver: &Resolver,
     path: &Path,
     prefer_value_ns: bool,
+    syntax_context: SyntaxContext,
 ) -> Option<PathResolution> {
     let types = || {
         let (ty, unresolved) = match path.type_anchor() {
@@ -531,7 +583,9 @@ fn resolve_hir_path_(
 
     let body_owner = resolver.body_owner();
     let values = || {
-        resolver.resolve_path_in_value_ns_fully(db.upcast(), path.mod_path()).and_then(|val| {
+        let val =
+            resolver.resolve_path_in_value_ns_fully(db.upcast(), path.mod_path(), syntax_context);
+        val.and_then(|val| {
             let res = match val {
                 ValueNs::LocalBinding(pat_id) => {
                     let var = Local { parent: body_owner?, pat_id };
@@ -14,13 +14,14 @@ use tt::{Delimiter, DelimiterKind, Leaf, Subtree, TokenTree};
 
 use crate::{
     body::{CfgExpander, LowerCtx},
+    builtin_type::{BuiltinInt, BuiltinUint},
     db::DefDatabase,
     intern::Interned,
     item_tree::{AttrOwner, Field, Fields, ItemTree, ModItem, RawVisibilityId},
     src::HasChildSource,
     src::HasSource,
     trace::Trace,
-    type_ref::TypeRef,
+    type_ref::{ConstScalarOrPath, TypeRef},
     visibility::RawVisibility,
     EnumId, LocalEnumVariantId, LocalFieldId, Lookup, ModuleId, StructId, UnionId, VariantId,
 };
@@ -31,7 +32,7 @@ use cfg::CfgOptions;
 pub struct StructData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
-    pub repr: Option<ReprKind>,
+    pub repr: Option<ReprOptions>,
     pub visibility: RawVisibility,
 }
 
@@ -39,6 +40,7 @@ pub struct StructData {
 pub struct EnumData {
     pub name: Name,
     pub variants: Arena<EnumVariantData>,
+    pub repr: Option<ReprOptions>,
     pub visibility: RawVisibility,
 }
 
@@ -46,6 +48,8 @@ pub struct EnumData {
 pub struct EnumVariantData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
+    /// The explicit discriminant, given as `Variant = expr`.
+    pub discriminant: Option<ConstScalarOrPath>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,10 +67,17 @@ pub struct FieldData {
     pub visibility: RawVisibility,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ReprKind {
-    Packed,
-    Other,
+/// The options given in `#[repr(...)]` attributes of an ADT.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReprOptions {
+    /// `#[repr(C)]`
+    pub c: bool,
+    /// `#[repr(packed)]` or `#[repr(packed(N))]`, holding the maximum alignment.
+    pub packed: Option<u64>,
+    /// `#[repr(align(N))]`
+    pub align: Option<u64>,
+    /// `#[repr(u8)]` and friends, the type of an enum's discriminant.
+    pub int: Option<Either<BuiltinInt, BuiltinUint>>,
 }
 
 fn repr_from_value(
@@ -74,20 +85,48 @@ fn repr_from_value(
     krate: CrateId,
     item_tree: &ItemTree,
     of: AttrOwner,
-) -> Option<ReprKind> {
-    item_tree.attrs(db, krate, of).by_key("repr").tt_values().find_map(parse_repr_tt)
+) -> Option<ReprOptions> {
+    let mut res = None;
+    for tt in item_tree.attrs(db, krate, of).by_key("repr").tt_values() {
+        parse_repr_tt(tt, res.get_or_insert_with(ReprOptions::default));
+    }
+    res
 }
 
-fn parse_repr_tt(tt: &Subtree) -> Option<ReprKind> {
+fn parse_repr_tt(tt: &Subtree, repr: &mut ReprOptions) {
     match tt.delimiter {
         Some(Delimiter { kind: DelimiterKind::Parenthesis, .. }) => {}
-        _ => return None,
+        _ => return,
     }
 
-    let mut it = tt.token_trees.iter();
-    match it.next()? {
-        TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "packed" => Some(ReprKind::Packed),
-        _ => Some(ReprKind::Other),
+    let mut it = tt.token_trees.iter().peekable();
+    while let Some(tt) = it.next() {
+        let ident = match tt {
+            TokenTree::Leaf(Leaf::Ident(ident)) => ident,
+            _ => continue,
+        };
+        let arg = match it.peek() {
+            Some(TokenTree::Subtree(arg)) => {
+                it.next();
+                match arg.token_trees.first() {
+                    Some(TokenTree::Leaf(Leaf::Literal(lit))) => lit.text.parse::<u64>().ok(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match &*ident.text {
+            "C" => repr.c = true,
+            "packed" => repr.packed = Some(arg.unwrap_or(1)),
+            "align" => repr.align = arg,
+            it => {
+                if let Some(int) = BuiltinInt::from_suffix(it) {
+                    repr.int = Some(Either::Left(int));
+                } else if let Some(uint) = BuiltinUint::from_suffix(it) {
+                    repr.int = Some(Either::Right(uint));
+                }
+            }
+        }
     }
 }
 
@@ -132,6 +171,7 @@ impl EnumData {
         let loc = e.lookup(db);
         let krate = loc.container.krate;
         let item_tree = loc.id.item_tree(db);
+        let repr = repr_from_value(db, krate, &item_tree, ModItem::from(loc.id.value).into());
         let cfg_options = db.crate_graph()[krate].cfg_options.clone();
 
         let enum_ = &item_tree[loc.id.value];
@@ -151,6 +191,7 @@ impl EnumData {
                 variants.alloc(EnumVariantData {
                     name: var.name.clone(),
                     variant_data: Arc::new(var_data),
+                    discriminant: var.discriminant.clone(),
                 });
             }
         }
@@ -158,6 +199,7 @@ impl EnumData {
         Arc::new(EnumData {
             name: enum_.name.clone(),
             variants,
+            repr,
             visibility: item_tree[enum_.visibility].clone(),
         })
     }
@@ -188,6 +230,7 @@ fn lower_enum(
     module_id: ModuleId,
 ) {
     let expander = CfgExpander::new(db, ast.file_id, module_id.krate);
+    let ctx = LowerCtx::new(db, ast.file_id);
     let variants = ast
         .value
         .variant_list()
@@ -200,6 +243,7 @@ fn lower_enum(
             || EnumVariantData {
                 name: var.name().map_or_else(Name::missing, |it| it.as_name()),
                 variant_data: Arc::new(VariantData::new(db, ast.with_value(var.kind()), module_id)),
+                discriminant: var.expr().map(|expr| ConstScalarOrPath::from_expr(&ctx, expr)),
             },
         );
     }
@@ -7,17 +7,19 @@ pub mod scope;
 
 use std::{mem, ops::Index, sync::Arc};
 
-use base_db::CrateId;
+use base_db::{CrateId, ProcMacroDiagnostic};
 use cfg::{CfgExpr, CfgOptions};
 use drop_bomb::DropBomb;
 use either::Either;
 use hir_expand::{
-    ast_id_map::AstIdMap, hygiene::Hygiene, AstId, ExpandResult, HirFileId, InFile, MacroDefId,
+    ast_id_map::AstIdMap,
+    hygiene::{Hygiene, SyntaxContext},
+    AstId, ExpandResult, HirFileId, InFile, MacroCallId, MacroDefId,
 };
 use la_arena::{Arena, ArenaMap};
 use profile::Count;
 use rustc_hash::FxHashMap;
-use syntax::{ast, AstNode, AstPtr, SyntaxNodePtr};
+use syntax::{ast, AstNode, AstPtr, SyntaxNodePtr, SyntaxToken};
 
 use crate::{
     attr::{Attrs, RawAttrs},
@@ -123,6 +125,28 @@ impl Expander {
             }
         };
 
+        Ok(self.enter_expand_inner(db, call_id, err))
+    }
+
+    /// Enters the expansion of an already resolved macro call, like an attribute macro.
+    pub fn enter_expand_id<T: ast::AstNode>(
+        &mut self,
+        db: &dyn DefDatabase,
+        call_id: MacroCallId,
+    ) -> ExpandResult<Option<(Mark, T)>> {
+        if self.recursion_limit + 1 > EXPANSION_RECURSION_LIMIT {
+            cov_mark::hit!(your_stack_belongs_to_me);
+            return ExpandResult::str_err("reached recursion limit during macro expansion".into());
+        }
+        self.enter_expand_inner(db, call_id, None)
+    }
+
+    fn enter_expand_inner<T: ast::AstNode>(
+        &mut self,
+        db: &dyn DefDatabase,
+        call_id: MacroCallId,
+        mut err: Option<mbe::ExpandError>,
+    ) -> ExpandResult<Option<(Mark, T)>> {
         if err.is_none() {
             err = db.macro_expand_error(call_id);
         }
@@ -137,9 +161,9 @@ impl Expander {
                     log::warn!("no error despite `parse_or_expand` failing");
                 }
 
-                return Ok(ExpandResult::only_err(err.unwrap_or_else(|| {
+                return ExpandResult::only_err(err.unwrap_or_else(|| {
                     mbe::ExpandError::Other("failed to parse macro invocation".into())
-                })));
+                }));
             }
         };
 
@@ -147,7 +171,7 @@ impl Expander {
             Some(it) => it,
             None => {
                 // This can happen without being an error, so only forward previous errors.
-                return Ok(ExpandResult { value: None, err });
+                return ExpandResult { value: None, err };
             }
         };
 
@@ -163,7 +187,7 @@ impl Expander {
         self.current_file_id = file_id;
         self.ast_id_map = db.ast_id_map(file_id);
 
-        Ok(ExpandResult { value: Some((mark, node)), err })
+        ExpandResult { value: Some((mark, node)), err }
     }
 
     pub fn exit(&mut self, db: &dyn DefDatabase, mut mark: Mark) {
@@ -190,12 +214,20 @@ impl Expander {
         self.current_file_id
     }
 
+    fn syntax_context(&self, db: &dyn DefDatabase, token: &SyntaxToken) -> SyntaxContext {
+        self.cfg_expander.hygiene.syntax_context(db.upcast(), token)
+    }
+
     fn parse_path(&mut self, db: &dyn DefDatabase, path: ast::Path) -> Option<Path> {
         let ctx = LowerCtx::with_hygiene(db, &self.cfg_expander.hygiene);
         Path::from_src(path, &ctx)
     }
 
-    fn resolve_path_as_macro(&self, db: &dyn DefDatabase, path: &ModPath) -> Option<MacroDefId> {
+    pub(crate) fn resolve_path_as_macro(
+        &self,
+        db: &dyn DefDatabase,
+        path: &ModPath,
+    ) -> Option<MacroDefId> {
         self.def_map.resolve_path(db, self.module, path, BuiltinShadowMode::Other).0.take_macros()
     }
 
@@ -229,6 +261,13 @@ pub struct Body {
     pub body_expr: ExprId,
     /// Block expressions in this body that may contain inner items.
     block_scopes: Vec<BlockId>,
+    /// Syntax contexts of the bindings introduced by macro expansions. Bindings not in this
+    /// map are in the root context.
+    binding_contexts: FxHashMap<PatId, SyntaxContext>,
+    /// Syntax contexts of the single-identifier path expressions produced by macro
+    /// expansions, used to resolve them to local variables hygienically. Paths not in this
+    /// map are in the root context.
+    path_contexts: FxHashMap<ExprId, SyntaxContext>,
     _c: Count<Self>,
 }
 
@@ -279,10 +318,28 @@ pub struct SyntheticSyntax;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum BodyDiagnostic {
-    InactiveCode { node: InFile<SyntaxNodePtr>, cfg: CfgExpr, opts: CfgOptions },
-    MacroError { node: InFile<AstPtr<ast::MacroCall>>, message: String },
-    UnresolvedProcMacro { node: InFile<AstPtr<ast::MacroCall>> },
-    UnresolvedMacroCall { node: InFile<AstPtr<ast::MacroCall>>, path: ModPath },
+    InactiveCode {
+        node: InFile<SyntaxNodePtr>,
+        cfg: CfgExpr,
+        opts: CfgOptions,
+    },
+    MacroError {
+        node: InFile<AstPtr<ast::MacroCall>>,
+        message: String,
+    },
+    ProcMacroDiagnostic {
+        node: InFile<AstPtr<ast::MacroCall>>,
+        call: MacroCallId,
+        diagnostic: ProcMacroDiagnostic,
+    },
+    UnresolvedProcMacro {
+        node: InFile<AstPtr<ast::MacroCall>>,
+        reason: Option<String>,
+    },
+    UnresolvedMacroCall {
+        node: InFile<AstPtr<ast::MacroCall>>,
+        path: ModPath,
+    },
 }
 
 impl Body {
@@ -331,6 +388,17 @@ impl Body {
             .map(move |block| (*block, db.block_def_map(*block).expect("block ID without DefMap")))
     }
 
+    /// Returns the syntax context of the binding introduced by `pat`.
+    pub fn binding_context(&self, pat: PatId) -> SyntaxContext {
+        self.binding_contexts.get(&pat).copied().unwrap_or_default()
+    }
+
+    /// Returns the syntax context of the path expression `expr`, which local variables are
+    /// resolved in.
+    pub fn path_context(&self, expr: ExprId) -> SyntaxContext {
+        self.path_contexts.get(&expr).copied().unwrap_or_default()
+    }
+
     fn new(
         db: &dyn DefDatabase,
         expander: Expander,
@@ -341,8 +409,20 @@ impl Body {
     }
 
     fn shrink_to_fit(&mut self) {
-        let Self { _c: _, body_expr: _, block_scopes, exprs, labels, params, pats } = self;
+        let Self {
+            _c: _,
+            body_expr: _,
+            block_scopes,
+            binding_contexts,
+            path_contexts,
+            exprs,
+            labels,
+            params,
+            pats,
+        } = self;
         block_scopes.shrink_to_fit();
+        binding_contexts.shrink_to_fit();
+        path_contexts.shrink_to_fit();
         exprs.shrink_to_fit();
         labels.shrink_to_fit();
         params.shrink_to_fit();
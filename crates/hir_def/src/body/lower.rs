@@ -12,6 +12,7 @@ use hir_expand::{
 };
 use la_arena::Arena;
 use profile::Count;
+use rustc_hash::FxHashMap;
 use syntax::{
     ast::{
         self, ArgListOwner, ArrayExprKind, AstChildren, LiteralKind, LoopBodyOwner, NameOwner,
@@ -27,9 +28,9 @@ use crate::{
     builtin_type::{BuiltinFloat, BuiltinInt, BuiltinUint},
     db::DefDatabase,
     expr::{
-        dummy_expr_id, ArithOp, Array, BinaryOp, BindingAnnotation, CmpOp, Expr, ExprId, Label,
-        LabelId, Literal, LogicOp, MatchArm, Ordering, Pat, PatId, RecordFieldPat, RecordLitField,
-        Statement,
+        dummy_expr_id, ArithOp, Array, BinaryOp, BindingAnnotation, CaptureBy, CmpOp, Expr, ExprId,
+        Label, LabelId, Literal, LogicOp, MatchArm, Ordering, Pat, PatId, RecordFieldPat,
+        RecordLitField, Statement,
     },
     intern::Interned,
     item_scope::BuiltinShadowMode,
@@ -92,6 +93,8 @@ pub(super) fn lower(
             params: Vec::new(),
             body_expr: dummy_expr_id(),
             block_scopes: Vec::new(),
+            binding_contexts: FxHashMap::default(),
+            path_contexts: FxHashMap::default(),
             _c: Count::new(),
         },
         expander,
@@ -160,9 +163,6 @@ impl ExprCollector<'_> {
     fn alloc_expr_desugared(&mut self, expr: Expr) -> ExprId {
         self.make_expr(expr, Err(SyntheticSyntax))
     }
-    fn unit(&mut self) -> ExprId {
-        self.alloc_expr_desugared(Expr::Tuple { exprs: Vec::new() })
-    }
     fn missing_expr(&mut self) -> ExprId {
         self.alloc_expr_desugared(Expr::Missing)
     }
@@ -210,6 +210,7 @@ impl ExprCollector<'_> {
 
         Some(match expr {
             ast::Expr::IfExpr(e) => {
+                let condition = self.collect_expr_opt(e.condition());
                 let then_branch = self.collect_block_opt(e.then_branch());
 
                 let else_branch = e.else_branch().map(|b| match b {
@@ -220,32 +221,13 @@ impl ExprCollector<'_> {
                     }
                 });
 
-                let condition = match e.condition() {
-                    None => self.missing_expr(),
-                    Some(condition) => match condition.pat() {
-                        None => self.collect_expr_opt(condition.expr()),
-                        // if let -- desugar to match
-                        Some(pat) => {
-                            let pat = self.collect_pat(pat);
-                            let match_expr = self.collect_expr_opt(condition.expr());
-                            let placeholder_pat = self.missing_pat();
-                            let arms = vec![
-                                MatchArm { pat, expr: then_branch, guard: None },
-                                MatchArm {
-                                    pat: placeholder_pat,
-                                    expr: else_branch.unwrap_or_else(|| self.unit()),
-                                    guard: None,
-                                },
-                            ];
-                            return Some(
-                                self.alloc_expr(Expr::Match { expr: match_expr, arms }, syntax_ptr),
-                            );
-                        }
-                    },
-                };
-
                 self.alloc_expr(Expr::If { condition, then_branch, else_branch }, syntax_ptr)
             }
+            ast::Expr::LetExpr(e) => {
+                let pat = self.collect_pat_opt(e.pat());
+                let expr = self.collect_expr_opt(e.expr());
+                self.alloc_expr(Expr::Let { pat, expr }, syntax_ptr)
+            }
             ast::Expr::EffectExpr(e) => match e.effect() {
                 ast::Effect::Try(_) => {
                     let body = self.collect_block_opt(e.block_expr());
@@ -290,33 +272,8 @@ impl ExprCollector<'_> {
             }
             ast::Expr::WhileExpr(e) => {
                 let label = e.label().map(|label| self.collect_label(label));
+                let condition = self.collect_expr_opt(e.condition());
                 let body = self.collect_block_opt(e.loop_body());
-
-                let condition = match e.condition() {
-                    None => self.missing_expr(),
-                    Some(condition) => match condition.pat() {
-                        None => self.collect_expr_opt(condition.expr()),
-                        // if let -- desugar to match
-                        Some(pat) => {
-                            cov_mark::hit!(infer_resolve_while_let);
-                            let pat = self.collect_pat(pat);
-                            let match_expr = self.collect_expr_opt(condition.expr());
-                            let placeholder_pat = self.missing_pat();
-                            let break_ =
-                                self.alloc_expr_desugared(Expr::Break { expr: None, label: None });
-                            let arms = vec![
-                                MatchArm { pat, expr: body, guard: None },
-                                MatchArm { pat: placeholder_pat, expr: break_, guard: None },
-                            ];
-                            let match_expr =
-                                self.alloc_expr_desugared(Expr::Match { expr: match_expr, arms });
-                            return Some(
-                                self.alloc_expr(Expr::Loop { body: match_expr, label }, syntax_ptr),
-                            );
-                        }
-                    },
-                };
-
                 self.alloc_expr(Expr::While { condition, body, label }, syntax_ptr)
             }
             ast::Expr::ForExpr(e) => {
@@ -374,12 +331,15 @@ impl ExprCollector<'_> {
                 self.alloc_expr(Expr::Match { expr, arms }, syntax_ptr)
             }
             ast::Expr::PathExpr(e) => {
-                let path = e
-                    .path()
-                    .and_then(|path| self.expander.parse_path(self.db, path))
-                    .map(Expr::Path)
-                    .unwrap_or(Expr::Missing);
-                self.alloc_expr(path, syntax_ptr)
+                let ast_path = e.path();
+                let path =
+                    ast_path.clone().and_then(|path| self.expander.parse_path(self.db, path));
+                let is_ident = matches!(&path, Some(it) if it.mod_path().is_ident());
+                let id = self.alloc_expr(path.map(Expr::Path).unwrap_or(Expr::Missing), syntax_ptr);
+                if let (true, Some(ast_path)) = (is_ident, ast_path) {
+                    self.record_path_context(id, &ast_path);
+                }
+                id
             }
             ast::Expr::ContinueExpr(e) => self.alloc_expr(
                 Expr::Continue { label: e.lifetime().map(|l| Name::new_lifetime(&l)) },
@@ -499,7 +459,12 @@ impl ExprCollector<'_> {
                     .and_then(|r| r.ty())
                     .map(|it| Interned::new(TypeRef::from_ast(&self.ctx(), it)));
                 let body = self.collect_expr_opt(e.body());
-                self.alloc_expr(Expr::Lambda { args, arg_types, ret_type, body }, syntax_ptr)
+                let capture_by =
+                    if e.move_token().is_some() { CaptureBy::Value } else { CaptureBy::Ref };
+                self.alloc_expr(
+                    Expr::Lambda { args, arg_types, ret_type, body, capture_by },
+                    syntax_ptr,
+                )
             }
             ast::Expr::BinExpr(e) => {
                 let lhs = self.collect_expr_opt(e.lhs());
@@ -602,12 +567,19 @@ impl ExprCollector<'_> {
         match &res.err {
             Some(ExpandError::UnresolvedProcMacro) => {
                 self.source_map.diagnostics.push(BodyDiagnostic::UnresolvedProcMacro {
-                    node: InFile::new(outer_file, syntax_ptr),
+                    node: InFile::new(outer_file, syntax_ptr.clone()),
+                    reason: None,
+                });
+            }
+            Some(ExpandError::ProcMacroError(tt::ExpansionError::Disabled(reason))) => {
+                self.source_map.diagnostics.push(BodyDiagnostic::UnresolvedProcMacro {
+                    node: InFile::new(outer_file, syntax_ptr.clone()),
+                    reason: Some(reason.clone()),
                 });
             }
             Some(err) => {
                 self.source_map.diagnostics.push(BodyDiagnostic::MacroError {
-                    node: InFile::new(outer_file, syntax_ptr),
+                    node: InFile::new(outer_file, syntax_ptr.clone()),
                     message: err.to_string(),
                 });
             }
@@ -617,12 +589,20 @@ impl ExprCollector<'_> {
         match res.value {
             Some((mark, expansion)) => {
                 // FIXME: Statements are too complicated to recover from error for now.
-                // It is because we don't have any hygiene for local variable expansion right now.
                 if !is_error_recoverable && res.err.is_some() {
                     self.expander.exit(self.db, mark);
                     collector(self, None);
                 } else {
                     self.source_map.expansions.insert(macro_call, self.expander.current_file_id);
+                    if let Some(call) = self.expander.current_file_id.macro_call_id() {
+                        for diagnostic in self.db.proc_macro_diagnostics(call).iter() {
+                            self.source_map.diagnostics.push(BodyDiagnostic::ProcMacroDiagnostic {
+                                node: InFile::new(outer_file, syntax_ptr.clone()),
+                                call,
+                                diagnostic: diagnostic.clone(),
+                            });
+                        }
+                    }
 
                     let id = collector(self, Some(expansion));
                     self.expander.exit(self.db, mark);
@@ -642,6 +622,8 @@ impl ExprCollector<'_> {
     }
 
     fn collect_stmt(&mut self, s: ast::Stmt) {
+        // FIXME: attribute macros on statements and expressions are not expanded, the statement
+        // is lowered as if they were absent. `MacroCallKind::Attr` only supports items so far.
         match s {
             ast::Stmt::LetStmt(stmt) => {
                 if self.check_cfg(&stmt).is_none() {
@@ -651,7 +633,16 @@ impl ExprCollector<'_> {
                 let type_ref =
                     stmt.ty().map(|it| Interned::new(TypeRef::from_ast(&self.ctx(), it)));
                 let initializer = stmt.initializer().map(|e| self.collect_expr(e));
-                self.statements_in_scope.push(Statement::Let { pat, type_ref, initializer });
+                let else_branch = stmt
+                    .let_else()
+                    .and_then(|let_else| let_else.block_expr())
+                    .map(|block| self.collect_block(block));
+                self.statements_in_scope.push(Statement::Let {
+                    pat,
+                    type_ref,
+                    initializer,
+                    else_branch,
+                });
             }
             ast::Stmt::ExprStmt(stmt) => {
                 if self.check_cfg(&stmt).is_none() {
@@ -900,7 +891,34 @@ impl ExprCollector<'_> {
             ast::Pat::RangePat(_) => Pat::Missing,
         };
         let ptr = AstPtr::new(&pat);
-        self.alloc_pat(pattern, Either::Left(ptr))
+        let is_binding = matches!(pattern, Pat::Bind { .. });
+        let id = self.alloc_pat(pattern, Either::Left(ptr));
+        if let (true, ast::Pat::IdentPat(bp)) = (is_binding, &pat) {
+            self.record_binding_context(id, bp);
+        }
+        id
+    }
+
+    fn record_binding_context(&mut self, pat: PatId, bp: &ast::IdentPat) {
+        let token = match bp.name().and_then(|name| name.syntax().first_token()) {
+            Some(it) => it,
+            None => return,
+        };
+        let ctx = self.expander.syntax_context(self.db, &token);
+        if !ctx.is_root() {
+            self.body.binding_contexts.insert(pat, ctx);
+        }
+    }
+
+    fn record_path_context(&mut self, expr: ExprId, path: &ast::Path) {
+        let token = match path.segment().and_then(|it| it.name_ref()?.syntax().first_token()) {
+            Some(it) => it,
+            None => return,
+        };
+        let ctx = self.expander.syntax_context(self.db, &token);
+        if !ctx.is_root() {
+            self.body.path_contexts.insert(expr, ctx);
+        }
     }
 
     fn collect_pat_opt(&mut self, pat: Option<ast::Pat>) -> PatId {
@@ -1,7 +1,7 @@
 //! Name resolution for expressions.
 use std::sync::Arc;
 
-use hir_expand::name::Name;
+use hir_expand::{hygiene::SyntaxContext, name::Name};
 use la_arena::{Arena, Idx};
 use rustc_hash::FxHashMap;
 
@@ -24,6 +24,7 @@ pub struct ExprScopes {
 pub struct ScopeEntry {
     name: Name,
     pat: PatId,
+    syntax_context: SyntaxContext,
 }
 
 impl ScopeEntry {
@@ -34,6 +35,10 @@ impl ScopeEntry {
     pub fn pat(&self) -> PatId {
         self.pat
     }
+
+    pub fn syntax_context(&self) -> SyntaxContext {
+        self.syntax_context
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -53,9 +58,9 @@ impl ExprScopes {
     fn new(body: &Body) -> ExprScopes {
         let mut scopes =
             ExprScopes { scopes: Arena::default(), scope_by_expr: FxHashMap::default() };
-        let root = scopes.root_scope();
+        let mut root = scopes.root_scope();
         scopes.add_params_bindings(body, root, &body.params);
-        compute_expr_scopes(body.body_expr, body, &mut scopes, root);
+        compute_expr_scopes(body.body_expr, body, &mut scopes, &mut root);
         scopes
     }
 
@@ -124,7 +129,8 @@ impl ExprScopes {
     fn add_bindings(&mut self, body: &Body, scope: ScopeId, pat: PatId) {
         let pattern = &body[pat];
         if let Pat::Bind { name, .. } = pattern {
-            let entry = ScopeEntry { name: name.clone(), pat };
+            let entry =
+                ScopeEntry { name: name.clone(), pat, syntax_context: body.binding_context(pat) };
             self.scopes[scope].entries.push(entry);
         }
 
@@ -145,20 +151,22 @@ fn compute_block_scopes(
     tail: Option<ExprId>,
     body: &Body,
     scopes: &mut ExprScopes,
-    mut scope: ScopeId,
+    scope: &mut ScopeId,
 ) {
     for stmt in statements {
         match stmt {
-            Statement::Let { pat, initializer, .. } => {
+            Statement::Let { pat, initializer, else_branch, .. } => {
                 if let Some(expr) = initializer {
-                    scopes.set_scope(*expr, scope);
                     compute_expr_scopes(*expr, body, scopes, scope);
                 }
-                scope = scopes.new_scope(scope);
-                scopes.add_bindings(body, scope, *pat);
+                // The bindings of the pattern are not in scope in the else block.
+                if let Some(expr) = else_branch {
+                    compute_expr_scopes(*expr, body, scopes, scope);
+                }
+                *scope = scopes.new_scope(*scope);
+                scopes.add_bindings(body, *scope, *pat);
             }
             Statement::Expr { expr, .. } => {
-                scopes.set_scope(*expr, scope);
                 compute_expr_scopes(*expr, body, scopes, scope);
             }
         }
@@ -168,50 +176,64 @@ fn compute_block_scopes(
     }
 }
 
-fn compute_expr_scopes(expr: ExprId, body: &Body, scopes: &mut ExprScopes, scope: ScopeId) {
+/// Computes the scopes of `expr` and its children. A `let` expression opens a
+/// new scope for its bindings and leaves it in `scope`, which makes them
+/// visible to the following operands of a `&&` chain.
+fn compute_expr_scopes(expr: ExprId, body: &Body, scopes: &mut ExprScopes, scope: &mut ScopeId) {
     let make_label =
         |label: &Option<_>| label.map(|label| (label, body.labels[label].name.clone()));
 
-    scopes.set_scope(expr, scope);
+    scopes.set_scope(expr, *scope);
     match &body[expr] {
         Expr::Block { statements, tail, id, label } => {
-            let scope = scopes.new_block_scope(scope, *id, make_label(label));
+            let mut scope = scopes.new_block_scope(*scope, *id, make_label(label));
             // Overwrite the old scope for the block expr, so that every block scope can be found
             // via the block itself (important for blocks that only contain items, no expressions).
             scopes.set_scope(expr, scope);
-            compute_block_scopes(statements, *tail, body, scopes, scope);
+            compute_block_scopes(statements, *tail, body, scopes, &mut scope);
         }
         Expr::For { iterable, pat, body: body_expr, label } => {
             compute_expr_scopes(*iterable, body, scopes, scope);
-            let scope = scopes.new_labeled_scope(scope, make_label(label));
+            let mut scope = scopes.new_labeled_scope(*scope, make_label(label));
             scopes.add_bindings(body, scope, *pat);
-            compute_expr_scopes(*body_expr, body, scopes, scope);
+            compute_expr_scopes(*body_expr, body, scopes, &mut scope);
+        }
+        Expr::If { condition, then_branch, else_branch } => {
+            let mut then_scope = *scope;
+            compute_expr_scopes(*condition, body, scopes, &mut then_scope);
+            compute_expr_scopes(*then_branch, body, scopes, &mut then_scope);
+            if let Some(else_branch) = else_branch {
+                compute_expr_scopes(*else_branch, body, scopes, scope);
+            }
+        }
+        Expr::Let { pat, expr } => {
+            compute_expr_scopes(*expr, body, scopes, scope);
+            *scope = scopes.new_scope(*scope);
+            scopes.add_bindings(body, *scope, *pat);
         }
         Expr::While { condition, body: body_expr, label } => {
-            let scope = scopes.new_labeled_scope(scope, make_label(label));
-            compute_expr_scopes(*condition, body, scopes, scope);
-            compute_expr_scopes(*body_expr, body, scopes, scope);
+            let mut scope = scopes.new_labeled_scope(*scope, make_label(label));
+            compute_expr_scopes(*condition, body, scopes, &mut scope);
+            compute_expr_scopes(*body_expr, body, scopes, &mut scope);
         }
         Expr::Loop { body: body_expr, label } => {
-            let scope = scopes.new_labeled_scope(scope, make_label(label));
-            compute_expr_scopes(*body_expr, body, scopes, scope);
+            let mut scope = scopes.new_labeled_scope(*scope, make_label(label));
+            compute_expr_scopes(*body_expr, body, scopes, &mut scope);
         }
         Expr::Lambda { args, body: body_expr, .. } => {
-            let scope = scopes.new_scope(scope);
+            let mut scope = scopes.new_scope(*scope);
             scopes.add_params_bindings(body, scope, args);
-            compute_expr_scopes(*body_expr, body, scopes, scope);
+            compute_expr_scopes(*body_expr, body, scopes, &mut scope);
         }
         Expr::Match { expr, arms } => {
             compute_expr_scopes(*expr, body, scopes, scope);
             for arm in arms {
-                let scope = scopes.new_scope(scope);
+                let mut scope = scopes.new_scope(*scope);
                 scopes.add_bindings(body, scope, arm.pat);
                 if let Some(guard) = arm.guard {
-                    scopes.set_scope(guard, scope);
-                    compute_expr_scopes(guard, body, scopes, scope);
+                    compute_expr_scopes(guard, body, scopes, &mut scope);
                 }
-                scopes.set_scope(arm.expr, scope);
-                compute_expr_scopes(arm.expr, body, scopes, scope);
+                compute_expr_scopes(arm.expr, body, scopes, &mut scope);
             }
         }
         e => e.walk_child_exprs(|e| compute_expr_scopes(e, body, scopes, scope)),
@@ -368,6 +390,47 @@ fn foo() {
         );
     }
 
+    #[test]
+    fn test_let_else_scope() {
+        do_check(
+            r"
+fn foo(x: Option<u32>) {
+    let y = 1;
+    let Some(z) = x else { $0 };
+}
+",
+            &["y", "x"],
+        );
+    }
+
+    #[test]
+    fn test_let_chain_scope() {
+        do_check(
+            r"
+fn foo(x: Option<u32>, y: Result<u32, ()>) {
+    if let Some(a) = x && a > 3 && let Ok(b) = $0 {}
+}
+",
+            &["a", "x", "y"],
+        );
+        do_check(
+            r"
+fn foo(x: Option<u32>, y: Result<u32, ()>) {
+    if let Some(a) = x && let Ok(b) = y { $0 }
+}
+",
+            &["b", "a", "x", "y"],
+        );
+        do_check(
+            r"
+fn foo(x: Option<u32>) {
+    if let Some(a) = x {} else { $0 }
+}
+",
+            &["x"],
+        );
+    }
+
     #[test]
     fn macro_inner_item() {
         do_check(
@@ -490,8 +553,7 @@ fn foo() {
     }
 
     #[test]
-    fn while_let_desugaring() {
-        cov_mark::check!(infer_resolve_while_let);
+    fn while_let_scope() {
         do_check_local_name(
             r#"
 fn test() {
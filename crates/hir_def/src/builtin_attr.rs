@@ -8,9 +8,50 @@
 //! name resolution, and `BUILTIN_ATTRIBUTES` is almost entirely unchanged from the original, to
 //! ease updating.
 
+use crate::path::{ModPath, PathKind};
+
 /// Ignored attribute namespaces used by tools.
 pub const TOOL_MODULES: &[&str] = &["rustfmt", "clippy"];
 
+/// Returns whether `path` refers to an inert built-in or tool attribute, including the ones
+/// registered with `#![register_attr]` and `#![register_tool]`. These are never macros.
+pub(crate) fn is_builtin_or_registered_attr(
+    path: &ModPath,
+    registered_tools: &[String],
+    registered_attrs: &[String],
+) -> bool {
+    if path.kind != PathKind::Plain {
+        return false;
+    }
+
+    if let Some(tool_module) = path.segments().first() {
+        let tool_module = tool_module.to_string();
+        if TOOL_MODULES
+            .iter()
+            .copied()
+            .chain(registered_tools.iter().map(|s| &**s))
+            .any(|m| tool_module == *m)
+        {
+            return true;
+        }
+    }
+
+    if let Some(name) = path.as_ident() {
+        let name = name.to_string();
+        if INERT_ATTRIBUTES
+            .iter()
+            .chain(EXTRA_ATTRIBUTES)
+            .copied()
+            .chain(registered_attrs.iter().map(|s| &**s))
+            .any(|attr| name == *attr)
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
 type BuiltinAttribute = &'static str;
 
 macro_rules! ungated {
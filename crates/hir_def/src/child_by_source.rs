@@ -5,6 +5,8 @@
 //! node for a *child*, and get its hir.
 
 use either::Either;
+use rustc_hash::FxHashMap;
+use syntax::ast::AttrsOwner;
 
 use crate::{
     db::DefDatabase,
@@ -28,6 +30,12 @@ pub trait ChildBySource {
 impl ChildBySource for TraitId {
     fn child_by_source_to(&self, db: &dyn DefDatabase, res: &mut DynMap) {
         let data = db.trait_data(*self);
+
+        data.attribute_calls().for_each(|(ast_id, call_id)| {
+            let item = ast_id.with_value(ast_id.to_node(db.upcast()));
+            res[keys::ATTR_MACRO].insert(item, call_id);
+        });
+
         for (_name, item) in data.items.iter() {
             match *item {
                 AssocItemId::FunctionId(func) => {
@@ -50,6 +58,12 @@ impl ChildBySource for TraitId {
 impl ChildBySource for ImplId {
     fn child_by_source_to(&self, db: &dyn DefDatabase, res: &mut DynMap) {
         let data = db.impl_data(*self);
+
+        data.attribute_calls().for_each(|(ast_id, call_id)| {
+            let item = ast_id.with_value(ast_id.to_node(db.upcast()));
+            res[keys::ATTR_MACRO].insert(item, call_id);
+        });
+
         for &item in data.items.iter() {
             match item {
                 AssocItemId::FunctionId(func) => {
@@ -89,6 +103,19 @@ impl ChildBySource for ItemScope {
             let item = ast_id.with_value(ast_id.to_node(db.upcast()));
             res[keys::ATTR_MACRO].insert(item, call_id);
         });
+        self.derive_macro_invocs().for_each(|(ast_id, calls)| {
+            let item = ast_id.to_node(db.upcast());
+            let mut calls_by_attr: FxHashMap<_, Vec<_>> = FxHashMap::default();
+            for &(attr_id, call_id) in calls {
+                calls_by_attr.entry(attr_id).or_default().push(call_id);
+            }
+            for (attr_id, calls) in calls_by_attr {
+                if let Some(attr) = item.attrs().nth(attr_id.ast_index as usize) {
+                    res[keys::DERIVE_MACRO]
+                        .insert(ast_id.with_value(attr), calls.into_boxed_slice());
+                }
+            }
+        });
 
         fn add_module_def(db: &dyn DefDatabase, map: &mut DynMap, item: ModuleDefId) {
             match item {
@@ -2,19 +2,22 @@
 
 use std::sync::Arc;
 
-use hir_expand::{name::Name, InFile};
+use base_db::ProcMacroKind;
+use hir_expand::{name::Name, AstId, InFile, MacroCallId, MacroDefKind};
 use syntax::ast;
 
 use crate::{
     attr::Attrs,
-    body::Expander,
+    attr_macro_as_call_id,
+    body::{Expander, Mark},
+    builtin_attr,
     db::DefDatabase,
     intern::Interned,
-    item_tree::{AssocItem, FnFlags, ItemTreeId, ModItem, Param},
+    item_tree::{AssocItem, FnFlags, ItemTree, ItemTreeId, ModItem, Param},
     type_ref::{TraitRef, TypeBound, TypeRef},
     visibility::RawVisibility,
-    AssocContainerId, AssocItemId, ConstId, ConstLoc, FunctionId, FunctionLoc, HasModule, ImplId,
-    Intern, Lookup, ModuleId, StaticId, TraitId, TypeAliasId, TypeAliasLoc,
+    AssocContainerId, AssocItemId, AstIdWithPath, ConstId, ConstLoc, FunctionId, FunctionLoc,
+    HasModule, ImplId, Intern, Lookup, ModuleId, StaticId, TraitId, TypeAliasId, TypeAliasLoc,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -147,6 +150,8 @@ pub struct TraitData {
     /// method calls to this trait's methods when the receiver is an array and the crate edition is
     /// 2015 or 2018.
     pub skip_array_during_method_dispatch: bool,
+    /// Attribute macro calls on the trait's items, and the items they were applied to.
+    attribute_calls: Vec<(AstId<ast::Item>, MacroCallId)>,
 }
 
 impl TraitData {
@@ -166,15 +171,9 @@ impl TraitData {
             .by_key("rustc_skip_array_during_method_dispatch")
             .exists();
 
-        let items = collect_items(
-            db,
-            module_id,
-            &mut expander,
-            tr_def.items.iter().copied(),
-            tr_loc.id.file_id(),
-            container,
-            100,
-        );
+        let mut collector = AssocItemCollector::new(db, module_id, &mut expander, container);
+        collector.collect(tr_loc.id.file_id(), tr_def.items.iter().copied(), 100);
+        let AssocItemCollector { items, attribute_calls, .. } = collector;
 
         Arc::new(TraitData {
             name,
@@ -183,6 +182,7 @@ impl TraitData {
             is_unsafe,
             visibility,
             skip_array_during_method_dispatch,
+            attribute_calls,
         })
     }
 
@@ -193,6 +193,10 @@ impl TraitData {
         })
     }
 
+    pub fn attribute_calls(&self) -> impl Iterator<Item = (AstId<ast::Item>, MacroCallId)> + '_ {
+        self.attribute_calls.iter().copied()
+    }
+
     pub fn associated_type_by_name(&self, name: &Name) -> Option<TypeAliasId> {
         self.items.iter().find_map(|(item_name, item)| match item {
             AssocItemId::TypeAliasId(t) if item_name == name => Some(*t),
@@ -207,6 +211,8 @@ pub struct ImplData {
     pub self_ty: Interned<TypeRef>,
     pub items: Vec<AssocItemId>,
     pub is_negative: bool,
+    /// Attribute macro calls on the impl's items, and the items they were applied to.
+    attribute_calls: Vec<(AstId<ast::Item>, MacroCallId)>,
 }
 
 impl ImplData {
@@ -223,18 +229,16 @@ impl ImplData {
         let container = AssocContainerId::ImplId(id);
         let mut expander = Expander::new(db, impl_loc.id.file_id(), module_id);
 
-        let items = collect_items(
-            db,
-            module_id,
-            &mut expander,
-            impl_def.items.iter().copied(),
-            impl_loc.id.file_id(),
-            container,
-            100,
-        );
+        let mut collector = AssocItemCollector::new(db, module_id, &mut expander, container);
+        collector.collect(impl_loc.id.file_id(), impl_def.items.iter().copied(), 100);
+        let AssocItemCollector { items, attribute_calls, .. } = collector;
         let items = items.into_iter().map(|(_, item)| item).collect();
 
-        Arc::new(ImplData { target_trait, self_ty, items, is_negative })
+        Arc::new(ImplData { target_trait, self_ty, items, is_negative, attribute_calls })
+    }
+
+    pub fn attribute_calls(&self) -> impl Iterator<Item = (AstId<ast::Item>, MacroCallId)> + '_ {
+        self.attribute_calls.iter().copied()
     }
 }
 
@@ -285,79 +289,165 @@ impl StaticData {
     }
 }
 
-fn collect_items(
-    db: &dyn DefDatabase,
+struct AssocItemCollector<'a> {
+    db: &'a dyn DefDatabase,
     module: ModuleId,
-    expander: &mut Expander,
-    assoc_items: impl Iterator<Item = AssocItem>,
-    file_id: crate::HirFileId,
+    expander: &'a mut Expander,
     container: AssocContainerId,
-    limit: usize,
-) -> Vec<(Name, AssocItemId)> {
-    if limit == 0 {
-        return Vec::new();
-    }
 
-    let item_tree = db.file_item_tree(file_id);
-    let crate_graph = db.crate_graph();
-    let cfg_options = &crate_graph[module.krate].cfg_options;
+    items: Vec<(Name, AssocItemId)>,
+    attribute_calls: Vec<(AstId<ast::Item>, MacroCallId)>,
+}
+
+impl<'a> AssocItemCollector<'a> {
+    fn new(
+        db: &'a dyn DefDatabase,
+        module: ModuleId,
+        expander: &'a mut Expander,
+        container: AssocContainerId,
+    ) -> Self {
+        Self { db, module, expander, container, items: Vec::new(), attribute_calls: Vec::new() }
+    }
 
-    let mut items = Vec::new();
-    for item in assoc_items {
-        let attrs = item_tree.attrs(db, module.krate, ModItem::from(item).into());
-        if !attrs.is_cfg_enabled(cfg_options) {
-            continue;
+    fn collect(
+        &mut self,
+        file_id: crate::HirFileId,
+        assoc_items: impl Iterator<Item = AssocItem>,
+        limit: usize,
+    ) {
+        if limit == 0 {
+            return;
         }
 
-        match item {
-            AssocItem::Function(id) => {
-                let item = &item_tree[id];
-                let def = FunctionLoc { container, id: ItemTreeId::new(file_id, id) }.intern(db);
-                items.push((item.name.clone(), def.into()));
-            }
-            AssocItem::Const(id) => {
-                let item = &item_tree[id];
-                let name = match item.name.clone() {
-                    Some(name) => name,
-                    None => continue,
-                };
-                let def = ConstLoc { container, id: ItemTreeId::new(file_id, id) }.intern(db);
-                items.push((name, def.into()));
+        let db = self.db;
+        let item_tree = db.file_item_tree(file_id);
+        let crate_graph = db.crate_graph();
+        let cfg_options = &crate_graph[self.module.krate].cfg_options;
+
+        for item in assoc_items {
+            let attrs = item_tree.attrs(db, self.module.krate, ModItem::from(item).into());
+            if !attrs.is_cfg_enabled(cfg_options) {
+                continue;
             }
-            AssocItem::TypeAlias(id) => {
-                let item = &item_tree[id];
-                let def = TypeAliasLoc { container, id: ItemTreeId::new(file_id, id) }.intern(db);
-                items.push((item.name.clone(), def.into()));
+
+            if !matches!(item, AssocItem::MacroCall(_))
+                && self.collect_attr_macro_expansion(&item_tree, file_id, item, &attrs, limit)
+            {
+                continue;
             }
-            AssocItem::MacroCall(call) => {
-                let call = &item_tree[call];
-                let ast_id_map = db.ast_id_map(file_id);
-                let root = db.parse_or_expand(file_id).unwrap();
-                let call = ast_id_map.get(call.ast_id).to_node(&root);
-                let res = expander.enter_expand(db, call);
-
-                if let Ok(res) = res {
-                    if let Some((mark, mac)) = res.value {
-                        let src: InFile<ast::MacroItems> = expander.to_source(mac);
-                        let item_tree = db.file_item_tree(src.file_id);
-                        let iter =
-                            item_tree.top_level_items().iter().filter_map(ModItem::as_assoc_item);
-                        items.extend(collect_items(
-                            db,
-                            module,
-                            expander,
-                            iter,
-                            src.file_id,
-                            container,
-                            limit - 1,
-                        ));
-
-                        expander.exit(db, mark);
+
+            let container = self.container;
+            match item {
+                AssocItem::Function(id) => {
+                    let item = &item_tree[id];
+                    let def =
+                        FunctionLoc { container, id: ItemTreeId::new(file_id, id) }.intern(db);
+                    self.items.push((item.name.clone(), def.into()));
+                }
+                AssocItem::Const(id) => {
+                    let item = &item_tree[id];
+                    let name = match item.name.clone() {
+                        Some(name) => name,
+                        None => continue,
+                    };
+                    let def = ConstLoc { container, id: ItemTreeId::new(file_id, id) }.intern(db);
+                    self.items.push((name, def.into()));
+                }
+                AssocItem::TypeAlias(id) => {
+                    let item = &item_tree[id];
+                    let def =
+                        TypeAliasLoc { container, id: ItemTreeId::new(file_id, id) }.intern(db);
+                    self.items.push((item.name.clone(), def.into()));
+                }
+                AssocItem::MacroCall(call) => {
+                    let call = &item_tree[call];
+                    let ast_id_map = db.ast_id_map(file_id);
+                    let root = db.parse_or_expand(file_id).unwrap();
+                    let call = ast_id_map.get(call.ast_id).to_node(&root);
+                    let res = self.expander.enter_expand(db, call);
+
+                    if let Ok(res) = res {
+                        if let Some((mark, mac)) = res.value {
+                            self.collect_macro_items(mark, mac, limit);
+                        }
                     }
                 }
             }
         }
     }
 
-    items
+    /// Expands the first attribute macro applied to `item` and collects the resulting items.
+    ///
+    /// Returns `false` if there is no such attribute, or if the expansion failed, in which case
+    /// the item should be collected as if the attribute was not there.
+    fn collect_attr_macro_expansion(
+        &mut self,
+        item_tree: &ItemTree,
+        file_id: crate::HirFileId,
+        item: AssocItem,
+        attrs: &Attrs,
+        limit: usize,
+    ) -> bool {
+        let db = self.db;
+        if !db.enable_proc_attr_macros() {
+            return false;
+        }
+
+        let def_map = self.module.def_map(db);
+        for attr in attrs.iter() {
+            if builtin_attr::is_builtin_or_registered_attr(
+                &attr.path,
+                def_map.registered_tools(),
+                def_map.registered_attrs(),
+            ) {
+                continue;
+            }
+            let def = match self.expander.resolve_path_as_macro(db, &attr.path) {
+                Some(it) => it,
+                None => continue,
+            };
+            match def.kind {
+                MacroDefKind::BuiltInAttr(..) => {}
+                // Proc macros that cannot be expanded are ignored, like in name resolution.
+                MacroDefKind::ProcMacro(exp, ProcMacroKind::Attr, _) if !exp.is_dummy() => {}
+                _ => continue,
+            }
+
+            let ast_id = ModItem::from(item).ast_id(item_tree);
+            let ast_id = AstIdWithPath::new(file_id, ast_id, attr.path.as_ref().clone());
+            let expander = &*self.expander;
+            let call_id =
+                match attr_macro_as_call_id(&ast_id, attr, db, self.module.krate, |path| {
+                    expander.resolve_path_as_macro(db, &path)
+                }) {
+                    Ok(it) => it,
+                    Err(_) => return false,
+                };
+
+            let res = self.expander.enter_expand_id::<ast::MacroItems>(db, call_id);
+            return match res.value {
+                Some((mark, mac)) if res.err.is_none() => {
+                    self.attribute_calls.push((ast_id.ast_id, call_id));
+                    self.collect_macro_items(mark, mac, limit);
+                    true
+                }
+                Some((mark, _)) => {
+                    self.expander.exit(db, mark);
+                    false
+                }
+                None => false,
+            };
+        }
+
+        false
+    }
+
+    fn collect_macro_items(&mut self, mark: Mark, mac: ast::MacroItems, limit: usize) {
+        let src: InFile<ast::MacroItems> = self.expander.to_source(mac);
+        let item_tree = self.db.file_item_tree(src.file_id);
+        let iter = item_tree.top_level_items().iter().filter_map(ModItem::as_assoc_item);
+        self.collect(src.file_id, iter, limit - 1);
+
+        self.expander.exit(self.db, mark);
+    }
 }
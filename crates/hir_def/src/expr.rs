@@ -12,6 +12,8 @@
 //!
 //! See also a neighboring `body` module.
 
+use std::fmt;
+
 use hir_expand::name::Name;
 use la_arena::{Idx, RawIdx};
 use syntax::ast::RangeOp;
@@ -58,6 +60,12 @@ pub enum Expr {
         then_branch: ExprId,
         else_branch: Option<ExprId>,
     },
+    /// A `let` expression in the condition of an `if` or `while`, possibly
+    /// chained with other conditions through `&&`.
+    Let {
+        pat: PatId,
+        expr: ExprId,
+    },
     Block {
         id: BlockId,
         statements: Vec<Statement>,
@@ -165,6 +173,7 @@ pub enum Expr {
         arg_types: Vec<Option<Interned<TypeRef>>>,
         ret_type: Option<Interned<TypeRef>>,
         body: ExprId,
+        capture_by: CaptureBy,
     },
     Tuple {
         exprs: Vec<ExprId>,
@@ -179,6 +188,15 @@ pub enum Expr {
     Literal(Literal),
 }
 
+/// How a closure captures the variables it uses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CaptureBy {
+    /// `move |x| y + x`
+    Value,
+    /// `|x| y + x`
+    Ref,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BinaryOp {
     LogicOp(LogicOp),
@@ -219,6 +237,24 @@ pub enum ArithOp {
     BitAnd,
 }
 
+impl fmt::Display for ArithOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            ArithOp::Add => "+",
+            ArithOp::Mul => "*",
+            ArithOp::Sub => "-",
+            ArithOp::Div => "/",
+            ArithOp::Rem => "%",
+            ArithOp::Shl => "<<",
+            ArithOp::Shr => ">>",
+            ArithOp::BitXor => "^",
+            ArithOp::BitOr => "|",
+            ArithOp::BitAnd => "&",
+        };
+        f.write_str(op)
+    }
+}
+
 pub use syntax::ast::PrefixOp as UnaryOp;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Array {
@@ -241,8 +277,17 @@ pub struct RecordLitField {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Statement {
-    Let { pat: PatId, type_ref: Option<Interned<TypeRef>>, initializer: Option<ExprId> },
-    Expr { expr: ExprId, has_semi: bool },
+    Let {
+        pat: PatId,
+        type_ref: Option<Interned<TypeRef>>,
+        initializer: Option<ExprId>,
+        /// The block of a `let ... else` statement, which must diverge.
+        else_branch: Option<ExprId>,
+    },
+    Expr {
+        expr: ExprId,
+        has_semi: bool,
+    },
 }
 
 impl Expr {
@@ -257,13 +302,17 @@ impl Expr {
                     f(*else_branch);
                 }
             }
+            Expr::Let { expr, .. } => f(*expr),
             Expr::Block { statements, tail, .. } => {
                 for stmt in statements {
                     match stmt {
-                        Statement::Let { initializer, .. } => {
+                        Statement::Let { initializer, else_branch, .. } => {
                             if let Some(expr) = initializer {
                                 f(*expr);
                             }
+                            if let Some(expr) = else_branch {
+                                f(*expr);
+                            }
                         }
                         Statement::Expr { expr: expression, .. } => f(*expression),
                     }
@@ -65,7 +65,7 @@ use crate::{
     generics::GenericParams,
     intern::Interned,
     path::{path, AssociatedTypeBinding, GenericArgs, ImportAlias, ModPath, Path, PathKind},
-    type_ref::{Mutability, TraitRef, TypeBound, TypeRef},
+    type_ref::{ConstScalarOrPath, Mutability, TraitRef, TypeBound, TypeRef},
     visibility::RawVisibility,
 };
 
@@ -917,6 +917,8 @@ impl From<AssocItem> for ModItem {
 pub struct Variant {
     pub name: Name,
     pub fields: Fields,
+    /// The explicit discriminant, given as `Variant = expr`.
+    pub discriminant: Option<ConstScalarOrPath>,
 }
 
 /// A range of densely allocated ItemTree IDs.
@@ -310,7 +310,9 @@ impl<'a> Ctx<'a> {
     fn lower_variant(&mut self, variant: &ast::Variant) -> Option<Variant> {
         let name = variant.name()?.as_name();
         let fields = self.lower_fields(&variant.kind());
-        let res = Variant { name, fields };
+        let discriminant =
+            variant.expr().map(|expr| ConstScalarOrPath::from_expr(&self.body_ctx, expr));
+        let res = Variant { name, fields, discriminant };
         Some(res)
     }
 
@@ -110,7 +110,9 @@ enum E {
     Struct {
         /// comment on a: u8
         a: u8,
-    }
+    },
+    Discriminant = 2,
+    Expr = -(1 << 3) * X,
 }
         "#,
         expect![[r##"
@@ -143,6 +145,8 @@ enum E {
                     #[doc = " comment on a: u8"]  // AttrId { is_doc_comment: true, ast_index: 0 }
                     pub(self) a: u8,
                 },
+                Discriminant = 2,
+                Expr = -(1 << 3) * X,
             }
         "##]],
     );
@@ -106,6 +106,10 @@ pub struct DefMap {
     exported_proc_macros: FxHashMap<MacroDefId, ProcMacroDef>,
 
     edition: Edition,
+    /// Custom attributes registered with `#![register_attr]`.
+    registered_attrs: Vec<String>,
+    /// Custom tool modules registered with `#![register_tool]`.
+    registered_tools: Vec<String>,
     diagnostics: Vec<DefDiagnostic>,
 }
 
@@ -233,6 +237,8 @@ impl DefMap {
         let parent_map = block.module.def_map(db);
         let mut def_map = DefMap::empty(block.module.krate, parent_map.edition);
         def_map.block = Some(block_info);
+        def_map.registered_attrs = parent_map.registered_attrs.clone();
+        def_map.registered_tools = parent_map.registered_tools.clone();
 
         let def_map = collector::collect_defs(db, def_map, Some(block.ast_id));
         Some(Arc::new(def_map))
@@ -251,6 +257,8 @@ impl DefMap {
             prelude: None,
             root,
             modules,
+            registered_attrs: Vec::new(),
+            registered_tools: Vec::new(),
             diagnostics: Vec::new(),
         }
     }
@@ -426,18 +434,32 @@ impl DefMap {
             krate: _,
             prelude: _,
             root: _,
+            registered_attrs,
+            registered_tools,
         } = self;
 
         extern_prelude.shrink_to_fit();
         exported_proc_macros.shrink_to_fit();
         diagnostics.shrink_to_fit();
         modules.shrink_to_fit();
+        registered_attrs.shrink_to_fit();
+        registered_tools.shrink_to_fit();
         for (_, module) in modules.iter_mut() {
             module.children.shrink_to_fit();
             module.scope.shrink_to_fit();
         }
     }
 
+    /// Custom attributes registered in the crate with `#![register_attr]`.
+    pub(crate) fn registered_attrs(&self) -> &[String] {
+        &self.registered_attrs
+    }
+
+    /// Custom tool modules registered in the crate with `#![register_tool]`.
+    pub(crate) fn registered_tools(&self) -> &[String] {
+        &self.registered_tools
+    }
+
     /// Get a reference to the def map's diagnostics.
     pub fn diagnostics(&self) -> &[DefDiagnostic] {
         self.diagnostics.as_slice()
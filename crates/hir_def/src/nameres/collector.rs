@@ -98,8 +98,6 @@ pub(super) fn collect_defs(
         from_glob_import: Default::default(),
         skip_attrs: Default::default(),
         derive_helpers_in_scope: Default::default(),
-        registered_attrs: Default::default(),
-        registered_tools: Default::default(),
     };
     match block {
         Some(block) => {
@@ -261,10 +259,6 @@ struct DefCollector<'a> {
     /// Tracks which custom derives are in scope for an item, to allow resolution of derive helper
     /// attributes.
     derive_helpers_in_scope: FxHashMap<AstId<ast::Item>, Vec<Name>>,
-    /// Custom attributes registered with `#![register_attr]`.
-    registered_attrs: Vec<String>,
-    /// Custom tool modules registered with `#![register_tool]`.
-    registered_tools: Vec<String>,
 }
 
 impl DefCollector<'_> {
@@ -300,10 +294,10 @@ impl DefCollector<'_> {
                 };
 
                 if *attr_name == hir_expand::name![register_attr] {
-                    self.registered_attrs.push(registered_name.to_string());
+                    self.def_map.registered_attrs.push(registered_name.to_string());
                     cov_mark::hit!(register_attr);
                 } else {
-                    self.registered_tools.push(registered_name.to_string());
+                    self.def_map.registered_tools.push(registered_name.to_string());
                     cov_mark::hit!(register_tool);
                 }
             }
@@ -1020,15 +1014,29 @@ impl DefCollector<'_> {
 
             match &directive.kind {
                 MacroDirectiveKind::FnLike { ast_id, fragment } => {
+                    let mut error = None;
                     match macro_call_as_call_id(
                         ast_id,
                         *fragment,
                         self.db,
                         self.def_map.krate,
                         &resolver,
-                        &mut |_err| (),
+                        &mut |err| {
+                            error.get_or_insert(err);
+                        },
                     ) {
                         Ok(Ok(call_id)) => {
+                            // Eager macros like `compile_error!` can fail but still expand.
+                            if let Some(err) = error {
+                                self.def_map.diagnostics.push(DefDiagnostic::macro_error(
+                                    directive.module_id,
+                                    MacroCallKind::FnLike {
+                                        ast_id: ast_id.ast_id,
+                                        fragment: *fragment,
+                                    },
+                                    err.to_string(),
+                                ));
+                            }
                             resolved.push((directive.module_id, call_id, directive.depth));
                             res = ReachedFixedPoint::No;
                             return false;
@@ -1045,6 +1053,11 @@ impl DefCollector<'_> {
                         &resolver,
                     ) {
                         Ok(call_id) => {
+                            self.def_map.modules[directive.module_id].scope.add_derive_macro_invoc(
+                                ast_id.ast_id,
+                                call_id,
+                                *derive_attr,
+                            );
                             resolved.push((directive.module_id, call_id, directive.depth));
                             res = ReachedFixedPoint::No;
                             return false;
@@ -1103,6 +1116,7 @@ impl DefCollector<'_> {
                                         DefDiagnostic::unresolved_proc_macro(
                                             directive.module_id,
                                             loc.kind,
+                                            None,
                                         ),
                                     );
 
@@ -1173,13 +1187,24 @@ impl DefCollector<'_> {
             let diag = match err {
                 hir_expand::ExpandError::UnresolvedProcMacro => {
                     // Missing proc macros are non-fatal, so they are handled specially.
-                    DefDiagnostic::unresolved_proc_macro(module_id, loc.kind.clone())
+                    DefDiagnostic::unresolved_proc_macro(module_id, loc.kind.clone(), None)
+                }
+                hir_expand::ExpandError::ProcMacroError(tt::ExpansionError::Disabled(reason)) => {
+                    DefDiagnostic::unresolved_proc_macro(module_id, loc.kind.clone(), Some(reason))
                 }
                 _ => DefDiagnostic::macro_error(module_id, loc.kind.clone(), err.to_string()),
             };
 
             self.def_map.diagnostics.push(diag);
         }
+        for diagnostic in self.db.proc_macro_diagnostics(macro_call_id).iter() {
+            self.def_map.diagnostics.push(DefDiagnostic::proc_macro_diagnostic(
+                module_id,
+                loc.kind.clone(),
+                macro_call_id,
+                diagnostic.clone(),
+            ));
+        }
 
         // If we've just resolved a derive, record its helper attributes.
         if let MacroCallKind::Derive { ast_id, .. } = &loc.kind {
@@ -1706,34 +1731,11 @@ impl ModCollector<'_, '_> {
     }
 
     fn is_builtin_or_registered_attr(&self, path: &ModPath) -> bool {
-        if path.kind == PathKind::Plain {
-            if let Some(tool_module) = path.segments().first() {
-                let tool_module = tool_module.to_string();
-                if builtin_attr::TOOL_MODULES
-                    .iter()
-                    .copied()
-                    .chain(self.def_collector.registered_tools.iter().map(|s| &**s))
-                    .any(|m| tool_module == *m)
-                {
-                    return true;
-                }
-            }
-
-            if let Some(name) = path.as_ident() {
-                let name = name.to_string();
-                if builtin_attr::INERT_ATTRIBUTES
-                    .iter()
-                    .chain(builtin_attr::EXTRA_ATTRIBUTES)
-                    .copied()
-                    .chain(self.def_collector.registered_attrs.iter().map(|s| &**s))
-                    .any(|attr| name == *attr)
-                {
-                    return true;
-                }
-            }
-        }
-
-        false
+        builtin_attr::is_builtin_or_registered_attr(
+            path,
+            self.def_collector.def_map.registered_tools(),
+            self.def_collector.def_map.registered_attrs(),
+        )
     }
 
     fn collect_derive(&mut self, attr: &Attr, mod_item: ModItem) {
@@ -1796,15 +1798,26 @@ impl ModCollector<'_, '_> {
 
         // Case 1: builtin macros
         if attrs.by_key("rustc_builtin_macro").exists() {
-            // `#[rustc_builtin_macro = "builtin_name"]` overrides the `macro_rules!` name.
+            // `#[rustc_builtin_macro = "builtin_name"]` and `#[rustc_builtin_macro(builtin_name)]`
+            // override the `macro_rules!` name.
+            let builtin_attr = attrs.by_key("rustc_builtin_macro");
             let name;
-            let name = match attrs.by_key("rustc_builtin_macro").string_value() {
+            let name = match builtin_attr.string_value() {
                 Some(it) => {
                     // FIXME: a hacky way to create a Name from string.
                     name = tt::Ident { text: it.clone(), id: tt::TokenId::unspecified() }.as_name();
                     &name
                 }
-                None => &mac.name,
+                None => match builtin_attr.tt_values().find_map(|tt| match &*tt.token_trees {
+                    [tt::TokenTree::Leaf(tt::Leaf::Ident(it))] => Some(it.as_name()),
+                    _ => None,
+                }) {
+                    Some(it) => {
+                        name = it;
+                        &name
+                    }
+                    None => &mac.name,
+                },
             };
             let krate = self.def_collector.def_map.krate;
             match find_builtin_macro(name, krate, ast_id) {
@@ -1910,6 +1923,15 @@ impl ModCollector<'_, '_> {
             },
         ) {
             Ok(Ok(macro_call_id)) => {
+                // Eager macros like `compile_error!` can fail but still expand.
+                if let Some(err) = error {
+                    self.def_collector.def_map.diagnostics.push(DefDiagnostic::macro_error(
+                        self.module_id,
+                        MacroCallKind::FnLike { ast_id: ast_id.ast_id, fragment: mac.fragment },
+                        err.to_string(),
+                    ));
+                }
+
                 // Legacy macros need to be expanded immediately, so that any macros they produce
                 // are in scope.
                 self.def_collector.collect_macro_expansion(
@@ -1996,8 +2018,6 @@ mod tests {
             from_glob_import: Default::default(),
             skip_attrs: Default::default(),
             derive_helpers_in_scope: Default::default(),
-            registered_attrs: Default::default(),
-            registered_tools: Default::default(),
         };
         collector.seed_with_top_level();
         collector.collect();
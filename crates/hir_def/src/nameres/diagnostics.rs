@@ -1,7 +1,8 @@
 //! Diagnostics emitted during DefMap construction.
 
+use base_db::ProcMacroDiagnostic;
 use cfg::{CfgExpr, CfgOptions};
-use hir_expand::MacroCallKind;
+use hir_expand::{MacroCallId, MacroCallKind};
 use la_arena::Idx;
 use syntax::ast;
 
@@ -22,12 +23,14 @@ pub enum DefDiagnosticKind {
 
     UnconfiguredCode { ast: AstId<ast::Item>, cfg: CfgExpr, opts: CfgOptions },
 
-    UnresolvedProcMacro { ast: MacroCallKind },
+    UnresolvedProcMacro { ast: MacroCallKind, reason: Option<String> },
 
     UnresolvedMacroCall { ast: AstId<ast::MacroCall>, path: ModPath },
 
     MacroError { ast: MacroCallKind, message: String },
 
+    ProcMacroDiagnostic { ast: MacroCallKind, call: MacroCallId, diagnostic: ProcMacroDiagnostic },
+
     UnimplementedBuiltinMacro { ast: AstId<ast::Macro> },
 }
 
@@ -76,8 +79,12 @@ impl DefDiagnostic {
         Self { in_module: container, kind: DefDiagnosticKind::UnconfiguredCode { ast, cfg, opts } }
     }
 
-    pub(super) fn unresolved_proc_macro(container: LocalModuleId, ast: MacroCallKind) -> Self {
-        Self { in_module: container, kind: DefDiagnosticKind::UnresolvedProcMacro { ast } }
+    pub(super) fn unresolved_proc_macro(
+        container: LocalModuleId,
+        ast: MacroCallKind,
+        reason: Option<String>,
+    ) -> Self {
+        Self { in_module: container, kind: DefDiagnosticKind::UnresolvedProcMacro { ast, reason } }
     }
 
     pub(super) fn macro_error(
@@ -88,6 +95,18 @@ impl DefDiagnostic {
         Self { in_module: container, kind: DefDiagnosticKind::MacroError { ast, message } }
     }
 
+    pub(super) fn proc_macro_diagnostic(
+        container: LocalModuleId,
+        ast: MacroCallKind,
+        call: MacroCallId,
+        diagnostic: ProcMacroDiagnostic,
+    ) -> Self {
+        Self {
+            in_module: container,
+            kind: DefDiagnosticKind::ProcMacroDiagnostic { ast, call, diagnostic },
+        }
+    }
+
     pub(super) fn unresolved_macro_call(
         container: LocalModuleId,
         ast: AstId<ast::MacroCall>,
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use base_db::CrateId;
 use hir_expand::{
+    hygiene::SyntaxContext,
     name::{name, Name},
     MacroDefId,
 };
@@ -229,15 +230,20 @@ impl Resolver {
         }
     }
 
+    /// Resolves `path` in the value namespace. Local variables are only visible to it if
+    /// they were introduced in one of the contexts visible from `syntax_context`, the
+    /// context a single-identifier path was written in.
     pub fn resolve_path_in_value_ns(
         &self,
         db: &dyn DefDatabase,
         path: &ModPath,
+        syntax_context: SyntaxContext,
     ) -> Option<ResolveValueResult> {
         let n_segments = path.segments().len();
         let tmp = name![self];
         let first_name = if path.is_self() { &tmp } else { path.segments().first()? };
         let skip_to_mod = path.kind != PathKind::Plain && !path.is_self();
+        let mut visible_contexts = None;
         for scope in self.scopes.iter().rev() {
             match scope {
                 Scope::AdtScope(_)
@@ -250,11 +256,11 @@ impl Resolver {
                 }
 
                 Scope::ExprScope(scope) if n_segments <= 1 => {
-                    let entry = scope
-                        .expr_scopes
-                        .entries(scope.scope_id)
-                        .iter()
-                        .find(|entry| entry.name() == first_name);
+                    let contexts = visible_contexts
+                        .get_or_insert_with(|| syntax_context.visible_contexts(db.upcast()));
+                    let entry = scope.expr_scopes.entries(scope.scope_id).iter().find(|entry| {
+                        entry.name() == first_name && contexts.contains(&entry.syntax_context())
+                    });
 
                     if let Some(e) = entry {
                         return Some(ResolveValueResult::ValueNs(ValueNs::LocalBinding(e.pat())));
@@ -312,8 +318,9 @@ impl Resolver {
         &self,
         db: &dyn DefDatabase,
         path: &ModPath,
+        syntax_context: SyntaxContext,
     ) -> Option<ValueNs> {
-        match self.resolve_path_in_value_ns(db, path)? {
+        match self.resolve_path_in_value_ns(db, path, syntax_context)? {
             ResolveValueResult::ValueNs(it) => Some(it),
             ResolveValueResult::Partial(..) => None,
         }
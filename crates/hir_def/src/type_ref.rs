@@ -5,7 +5,12 @@ use hir_expand::{name::Name, AstId, InFile};
 use std::convert::TryInto;
 use syntax::ast;
 
-use crate::{body::LowerCtx, intern::Interned, path::Path};
+use crate::{
+    body::LowerCtx,
+    expr::{ArithOp, BinaryOp, UnaryOp},
+    intern::Interned,
+    path::{ModPath, Path},
+};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Mutability {
@@ -86,7 +91,7 @@ pub enum TypeRef {
     Reference(Box<TypeRef>, Option<LifetimeRef>, Mutability),
     // FIXME: for full const generics, the latter element (length) here is going to have to be an
     // expression that is further lowered later in hir_ty.
-    Array(Box<TypeRef>, ConstScalar),
+    Array(Box<TypeRef>, ConstScalarOrPath),
     Slice(Box<TypeRef>),
     /// A fn pointer. Last element of the vector is the return type.
     Fn(Vec<TypeRef>, bool /*varargs*/),
@@ -153,8 +158,8 @@ impl TypeRef {
                 // let v: [u8; 2 + 2] = [0u8; 4];
                 let len = inner
                     .expr()
-                    .map(ConstScalar::usize_from_literal_expr)
-                    .unwrap_or(ConstScalar::Unknown);
+                    .map(|expr| ConstScalarOrPath::from_expr(ctx, expr))
+                    .unwrap_or(ConstScalarOrPath::Scalar(ConstScalar::Unknown));
 
                 TypeRef::Array(Box::new(TypeRef::from_ast_opt(ctx, inner.ty())), len)
             }
@@ -328,6 +333,81 @@ impl TypeBound {
     }
 }
 
+/// A constant outside of a body, like the length of an array type, as written in the source:
+/// a literal, a path to a constant, or arithmetic on those. Paths are resolved and the whole
+/// expression is evaluated in `hir_ty`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConstScalarOrPath {
+    Scalar(ConstScalar),
+    Path(ModPath),
+    UnaryOp { op: UnaryOp, expr: Box<ConstScalarOrPath> },
+    BinaryOp { op: ArithOp, lhs: Box<ConstScalarOrPath>, rhs: Box<ConstScalarOrPath> },
+}
+
+impl std::fmt::Display for ConstScalarOrPath {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ConstScalarOrPath::Scalar(s) => write!(fmt, "{}", s),
+            ConstScalarOrPath::Path(p) => write!(fmt, "{}", p),
+            ConstScalarOrPath::UnaryOp { op, expr } => {
+                let op = match op {
+                    UnaryOp::Deref => "*",
+                    UnaryOp::Not => "!",
+                    UnaryOp::Neg => "-",
+                };
+                write!(fmt, "{}", op)?;
+                expr.fmt_operand(fmt)
+            }
+            ConstScalarOrPath::BinaryOp { op, lhs, rhs } => {
+                lhs.fmt_operand(fmt)?;
+                write!(fmt, " {} ", op)?;
+                rhs.fmt_operand(fmt)
+            }
+        }
+    }
+}
+
+impl ConstScalarOrPath {
+    pub(crate) fn from_expr(ctx: &LowerCtx, expr: ast::Expr) -> Self {
+        match expr {
+            ast::Expr::PathExpr(p) => match p.path().and_then(|it| ctx.lower_path(it)) {
+                Some(path) => ConstScalarOrPath::Path(path.mod_path().clone()),
+                None => ConstScalarOrPath::Scalar(ConstScalar::Unknown),
+            },
+            ast::Expr::ParenExpr(p) => match p.expr() {
+                Some(inner) => ConstScalarOrPath::from_expr(ctx, inner),
+                None => ConstScalarOrPath::Scalar(ConstScalar::Unknown),
+            },
+            ast::Expr::PrefixExpr(p) => match (p.op_kind(), p.expr()) {
+                (Some(op), Some(expr)) if op != UnaryOp::Deref => ConstScalarOrPath::UnaryOp {
+                    op,
+                    expr: Box::new(ConstScalarOrPath::from_expr(ctx, expr)),
+                },
+                _ => ConstScalarOrPath::Scalar(ConstScalar::Unknown),
+            },
+            ast::Expr::BinExpr(e) => match (e.op_kind().map(BinaryOp::from), e.lhs(), e.rhs()) {
+                (Some(BinaryOp::ArithOp(op)), Some(lhs), Some(rhs)) => {
+                    ConstScalarOrPath::BinaryOp {
+                        op,
+                        lhs: Box::new(ConstScalarOrPath::from_expr(ctx, lhs)),
+                        rhs: Box::new(ConstScalarOrPath::from_expr(ctx, rhs)),
+                    }
+                }
+                _ => ConstScalarOrPath::Scalar(ConstScalar::Unknown),
+            },
+            expr => ConstScalarOrPath::Scalar(ConstScalar::usize_from_literal_expr(expr)),
+        }
+    }
+
+    /// Formats `self` as the operand of an operator, parenthesizing nested binary operations.
+    fn fmt_operand(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ConstScalarOrPath::BinaryOp { .. } => write!(fmt, "({})", self),
+            _ => write!(fmt, "{}", self),
+        }
+    }
+}
+
 /// A concrete constant value
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ConstScalar {
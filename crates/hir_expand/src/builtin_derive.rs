@@ -2,9 +2,10 @@
 
 use log::debug;
 
+use mbe::TokenMap;
 use parser::FragmentKind;
 use syntax::{
-    ast::{self, AstNode, GenericParamsOwner, ModuleItemOwner, NameOwner},
+    ast::{self, AstNode, AttrsOwner, GenericParamsOwner, ModuleItemOwner, NameOwner},
     match_ast,
 };
 
@@ -23,11 +24,20 @@ macro_rules! register_builtin {
                 db: &dyn AstDatabase,
                 id: MacroCallId,
                 tt: &tt::Subtree,
+            ) -> Result<tt::Subtree, mbe::ExpandError> {
+                let krate = find_builtin_crate(db, id);
+                self.expand_with_krate(&krate, tt)
+            }
+
+            fn expand_with_krate(
+                &self,
+                krate: &tt::Ident,
+                tt: &tt::Subtree,
             ) -> Result<tt::Subtree, mbe::ExpandError> {
                 let expander = match *self {
                     $( BuiltinDeriveExpander::$trait => $expand, )*
                 };
-                expander(db, id, tt)
+                expander(krate, tt)
             }
 
             fn find_by_name(name: &name::Name) -> Option<Self> {
@@ -66,9 +76,163 @@ pub fn find_builtin_derive(
     })
 }
 
+/// Returns the impl the builtin derive `trait_name` generates for `adt`, referring to items of
+/// `core` by absolute paths.
+///
+/// The impl is parsed from a token tree, so it contains no whitespace.
+pub fn builtin_derive_impl(trait_name: &name::Name, adt: &ast::Adt) -> Option<ast::Impl> {
+    let expander = BuiltinDeriveExpander::find_by_name(trait_name)?;
+    let (tt, _) = mbe::syntax_node_to_token_tree(adt.syntax());
+    let krate = tt::Ident { text: "core".into(), id: tt::TokenId::unspecified() };
+    let expanded = expander.expand_with_krate(&krate, &tt).ok()?;
+    let (parsed, _) = mbe::token_tree_to_syntax_node(&expanded, FragmentKind::Items).ok()?;
+    ast::MacroItems::cast(parsed.syntax_node())?.items().find_map(|item| match item {
+        ast::Item::Impl(it) => Some(it),
+        _ => None,
+    })
+}
+
 struct BasicAdtInfo {
     name: tt::Ident,
     type_params: usize,
+    shape: AdtShape,
+}
+
+enum AdtShape {
+    Struct(VariantShape),
+    Enum {
+        variants: Vec<(tt::Ident, VariantShape)>,
+        /// The variant marked with `#[default]`.
+        default_variant: Option<usize>,
+    },
+    Union,
+}
+
+enum VariantShape {
+    Struct(Vec<tt::Ident>),
+    Tuple(usize),
+    Unit,
+}
+
+/// A variant of an enum, or a struct, as seen by the generated code.
+struct Variant<'a> {
+    name: &'a tt::Ident,
+    /// `Name` for structs and `Name::Variant` for enum variants.
+    path: Vec<tt::TokenTree>,
+    shape: &'a VariantShape,
+}
+
+impl AdtShape {
+    fn variants<'a>(&'a self, name: &'a tt::Ident) -> Vec<Variant<'a>> {
+        match self {
+            AdtShape::Struct(shape) => {
+                vec![Variant { name, path: quote! { #name }.token_trees, shape }]
+            }
+            AdtShape::Enum { variants, .. } => variants
+                .iter()
+                .map(|(variant, shape)| Variant {
+                    name: variant,
+                    path: quote! { #name :: #variant }.token_trees,
+                    shape,
+                })
+                .collect(),
+            AdtShape::Union => Vec::new(),
+        }
+    }
+
+    fn is_empty_enum(&self) -> bool {
+        matches!(self, AdtShape::Enum { variants, .. } if variants.is_empty())
+    }
+}
+
+impl VariantShape {
+    fn new(
+        field_list: Option<ast::FieldList>,
+        token_map: &TokenMap,
+    ) -> Result<Self, mbe::ExpandError> {
+        let shape = match field_list {
+            Some(ast::FieldList::RecordFieldList(it)) => VariantShape::Struct(
+                it.fields()
+                    .map(|field| name_to_token(token_map, field.name()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Some(ast::FieldList::TupleFieldList(it)) => VariantShape::Tuple(it.fields().count()),
+            None => VariantShape::Unit,
+        };
+        Ok(shape)
+    }
+
+    fn field_count(&self) -> usize {
+        match self {
+            VariantShape::Struct(fields) => fields.len(),
+            VariantShape::Tuple(n) => *n,
+            VariantShape::Unit => 0,
+        }
+    }
+
+    /// The names `pattern` binds the fields to.
+    fn bindings(&self, prefix: &str) -> Vec<tt::Ident> {
+        (0..self.field_count())
+            .map(|idx| tt::Ident {
+                text: format!("{}_{}", prefix, idx).into(),
+                id: tt::TokenId::unspecified(),
+            })
+            .collect()
+    }
+
+    /// A pattern binding the fields of the variant at `path` to `{prefix}_{field index}`.
+    fn pattern(&self, path: &[tt::TokenTree], prefix: &str) -> Vec<tt::TokenTree> {
+        let bindings = self.bindings(prefix).into_iter().map(|it| quote! { #it }.token_trees);
+        self.construct(path, bindings)
+    }
+
+    /// A pattern matching the variant at `path`, whatever its fields are.
+    fn pattern_ignoring_fields(&self, path: &[tt::TokenTree]) -> Vec<tt::TokenTree> {
+        let path = path.to_vec();
+        match self {
+            VariantShape::Struct(_) => quote! { ##path { .. } }.token_trees,
+            VariantShape::Tuple(_) => quote! { ##path ( .. ) }.token_trees,
+            VariantShape::Unit => path,
+        }
+    }
+
+    /// A struct expression (or pattern) for the variant at `path`, with the given fields.
+    fn construct(
+        &self,
+        path: &[tt::TokenTree],
+        fields: impl Iterator<Item = Vec<tt::TokenTree>>,
+    ) -> Vec<tt::TokenTree> {
+        let path = path.to_vec();
+        match self {
+            VariantShape::Struct(names) => {
+                let fields = names
+                    .iter()
+                    .zip(fields)
+                    .flat_map(|(name, field)| quote! { #name : ##field , }.token_trees);
+                quote! { ##path { ##fields } }.token_trees
+            }
+            VariantShape::Tuple(_) => {
+                let fields = fields.flat_map(|field| quote! { ##field , }.token_trees);
+                quote! { ##path ( ##fields ) }.token_trees
+            }
+            VariantShape::Unit => path,
+        }
+    }
+}
+
+fn name_to_token(
+    token_map: &TokenMap,
+    name: Option<ast::Name>,
+) -> Result<tt::Ident, mbe::ExpandError> {
+    let name = name.ok_or_else(|| {
+        debug!("parsed item has no name");
+        mbe::ExpandError::NoMatchingRule
+    })?;
+    let name_token_id = token_map.token_by_range(name.syntax().text_range()).ok_or_else(|| {
+        debug!("name token not found");
+        mbe::ExpandError::ConversionError
+    })?;
+    Ok(tt::Ident { id: name_token_id, text: name.text().into() })
 }
 
 fn parse_adt(tt: &tt::Subtree) -> Result<BasicAdtInfo, mbe::ExpandError> {
@@ -82,28 +246,39 @@ fn parse_adt(tt: &tt::Subtree) -> Result<BasicAdtInfo, mbe::ExpandError> {
         mbe::ExpandError::NoMatchingRule
     })?;
     let node = item.syntax();
-    let (name, params) = match_ast! {
+    let (name, params, shape) = match_ast! {
         match node {
-            ast::Struct(it) => (it.name(), it.generic_param_list()),
-            ast::Enum(it) => (it.name(), it.generic_param_list()),
-            ast::Union(it) => (it.name(), it.generic_param_list()),
+            ast::Struct(it) => (
+                it.name(),
+                it.generic_param_list(),
+                AdtShape::Struct(VariantShape::new(it.field_list(), &token_map)?),
+            ),
+            ast::Enum(it) => {
+                let variants: Vec<_> = it.variant_list().into_iter().flat_map(|it| it.variants()).collect();
+                let default_variant = variants.iter().position(|variant| {
+                    variant.attrs().any(|attr| attr.simple_name().as_deref() == Some("default"))
+                });
+                let variants = variants
+                    .into_iter()
+                    .map(|variant| {
+                        Ok((
+                            name_to_token(&token_map, variant.name())?,
+                            VariantShape::new(variant.field_list(), &token_map)?,
+                        ))
+                    })
+                    .collect::<Result<_, mbe::ExpandError>>()?;
+                (it.name(), it.generic_param_list(), AdtShape::Enum { variants, default_variant })
+            },
+            ast::Union(it) => (it.name(), it.generic_param_list(), AdtShape::Union),
             _ => {
                 debug!("unexpected node is {:?}", node);
                 return Err(mbe::ExpandError::ConversionError)
             },
         }
     };
-    let name = name.ok_or_else(|| {
-        debug!("parsed item has no name");
-        mbe::ExpandError::NoMatchingRule
-    })?;
-    let name_token_id = token_map.token_by_range(name.syntax().text_range()).ok_or_else(|| {
-        debug!("name token not found");
-        mbe::ExpandError::ConversionError
-    })?;
-    let name_token = tt::Ident { id: name_token_id, text: name.text().into() };
+    let name = name_to_token(&token_map, name)?;
     let type_params = params.map_or(0, |type_param_list| type_param_list.type_params().count());
-    Ok(BasicAdtInfo { name: name_token, type_params })
+    Ok(BasicAdtInfo { name, type_params, shape })
 }
 
 fn make_type_args(n: usize, bound: Vec<tt::TokenTree>) -> Vec<tt::TokenTree> {
@@ -150,8 +325,10 @@ fn make_type_args(n: usize, bound: Vec<tt::TokenTree>) -> Vec<tt::TokenTree> {
 fn expand_simple_derive(
     tt: &tt::Subtree,
     trait_path: tt::Subtree,
+    trait_body: impl FnOnce(&BasicAdtInfo) -> Vec<tt::TokenTree>,
 ) -> Result<tt::Subtree, mbe::ExpandError> {
     let info = parse_adt(tt)?;
+    let trait_body = trait_body(&info);
     let name = info.name;
     let trait_path_clone = trait_path.token_trees.clone();
     let bound = (quote! { : ##trait_path_clone }).token_trees;
@@ -159,12 +336,12 @@ fn expand_simple_derive(
     let type_args = make_type_args(info.type_params, Vec::new());
     let trait_path = trait_path.token_trees;
     let expanded = quote! {
-        impl ##type_params ##trait_path for #name ##type_args {}
+        impl ##type_params ##trait_path for #name ##type_args { ##trait_body }
     };
     Ok(expanded)
 }
 
-fn find_builtin_crate(db: &dyn AstDatabase, id: MacroCallId) -> tt::TokenTree {
+fn find_builtin_crate(db: &dyn AstDatabase, id: MacroCallId) -> tt::Ident {
     // FIXME: make hygiene works for builtin derive macro
     // such that $crate can be used here.
     let cg = db.crate_graph();
@@ -173,94 +350,299 @@ fn find_builtin_crate(db: &dyn AstDatabase, id: MacroCallId) -> tt::TokenTree {
     // XXX
     //  All crates except core itself should have a dependency on core,
     //  We detect `core` by seeing whether it doesn't have such a dependency.
-    let tt = if cg[krate].dependencies.iter().any(|dep| &*dep.name == "core") {
-        quote! { core }
+    let text = if cg[krate].dependencies.iter().any(|dep| &*dep.name == "core") {
+        "core"
     } else {
-        quote! { crate }
+        "crate"
     };
 
-    tt.token_trees[0].clone()
+    tt::Ident { text: text.into(), id: tt::TokenId::unspecified() }
 }
 
-fn copy_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::marker::Copy })
+/// `match self { #arms }`, or `match *self {}` if there are no variants to match on.
+fn match_self(adt: &BasicAdtInfo, arms: Vec<tt::TokenTree>) -> Vec<tt::TokenTree> {
+    if adt.shape.is_empty_enum() {
+        return quote! { match *self {} }.token_trees;
+    }
+    quote! { match self { ##arms } }.token_trees
 }
 
-fn clone_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::clone::Clone })
+/// `match (self, other) { #arms }`, with a fallback `_ => #fallback` arm for enums with several
+/// variants.
+fn match_self_and_other(
+    adt: &BasicAdtInfo,
+    arms: Vec<tt::TokenTree>,
+    fallback: Vec<tt::TokenTree>,
+) -> Vec<tt::TokenTree> {
+    if adt.shape.is_empty_enum() {
+        return quote! { match *self {} }.token_trees;
+    }
+    let fallback = match &adt.shape {
+        AdtShape::Enum { variants, .. } if variants.len() > 1 => {
+            quote! { _ => ##fallback , }.token_trees
+        }
+        _ => Vec::new(),
+    };
+    quote! { match (self, other) { ##arms ##fallback } }.token_trees
 }
 
-fn default_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::default::Default })
+/// Arms of a `match (self, other)` for each variant, binding the fields of `self` to `__self_N`
+/// and those of `other` to `__arg1_N`.
+fn pairwise_arms(
+    adt: &BasicAdtInfo,
+    mut body: impl FnMut(Vec<(tt::Ident, tt::Ident)>) -> Vec<tt::TokenTree>,
+) -> Vec<tt::TokenTree> {
+    adt.shape
+        .variants(&adt.name)
+        .into_iter()
+        .flat_map(|variant| {
+            let self_pat = variant.shape.pattern(&variant.path, "__self");
+            let other_pat = variant.shape.pattern(&variant.path, "__arg1");
+            let fields = variant
+                .shape
+                .bindings("__self")
+                .into_iter()
+                .zip(variant.shape.bindings("__arg1"))
+                .collect();
+            let body = body(fields);
+            quote! { ( ##self_pat , ##other_pat ) => ##body , }.token_trees
+        })
+        .collect()
 }
 
-fn debug_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::fmt::Debug })
+/// An expression evaluating to the index of the variant `scrutinee` is.
+// FIXME: rustc compares discriminant values, which differ from the indices if they are set
+// explicitly.
+fn variant_index(adt: &BasicAdtInfo, scrutinee: Vec<tt::TokenTree>) -> Vec<tt::TokenTree> {
+    let arms = adt.shape.variants(&adt.name).into_iter().enumerate().flat_map(|(idx, variant)| {
+        let pat = variant.shape.pattern_ignoring_fields(&variant.path);
+        quote! { ##pat => #idx , }.token_trees
+    });
+    quote! { match ##scrutinee { ##arms } }.token_trees
 }
 
-fn hash_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::hash::Hash })
+fn copy_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::marker::Copy }, |_| Vec::new())
 }
 
-fn eq_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::cmp::Eq })
+fn clone_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::clone::Clone }, |adt| {
+        if let AdtShape::Union = adt.shape {
+            return quote! { fn clone(&self) -> Self { *self } }.token_trees;
+        }
+        let arms = adt
+            .shape
+            .variants(&adt.name)
+            .into_iter()
+            .flat_map(|variant| {
+                let pat = variant.shape.pattern(&variant.path, "__self");
+                let fields = variant
+                    .shape
+                    .bindings("__self")
+                    .into_iter()
+                    .map(|it| quote! { #krate::clone::Clone::clone(#it) }.token_trees);
+                let expr = variant.shape.construct(&variant.path, fields);
+                quote! { ##pat => ##expr , }.token_trees
+            })
+            .collect();
+        let body = match_self(adt, arms);
+        quote! { fn clone(&self) -> Self { ##body } }.token_trees
+    })
 }
 
-fn partial_eq_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::cmp::PartialEq })
+fn default_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::default::Default }, |adt| {
+        let variants = adt.shape.variants(&adt.name);
+        let variant = match &adt.shape {
+            AdtShape::Struct(_) => &variants[0],
+            AdtShape::Enum { default_variant: Some(idx), .. } => &variants[*idx],
+            AdtShape::Enum { default_variant: None, .. } | AdtShape::Union => return Vec::new(),
+        };
+        let fields = (0..variant.shape.field_count())
+            .map(|_| quote! { #krate::default::Default::default() }.token_trees);
+        let body = variant.shape.construct(&variant.path, fields);
+        quote! { fn default() -> Self { ##body } }.token_trees
+    })
 }
 
-fn ord_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
-    tt: &tt::Subtree,
-) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::cmp::Ord })
+fn debug_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::fmt::Debug }, |adt| {
+        if let AdtShape::Union = adt.shape {
+            return Vec::new();
+        }
+        let arms = adt
+            .shape
+            .variants(&adt.name)
+            .into_iter()
+            .flat_map(|variant| {
+                let pat = variant.shape.pattern(&variant.path, "__self");
+                let name = variant.name.text.to_string();
+                let bindings = variant.shape.bindings("__self");
+                let mut expr = match variant.shape {
+                    VariantShape::Struct(fields) => {
+                        let mut expr = quote! { f.debug_struct(#name) }.token_trees;
+                        for (field, binding) in fields.iter().zip(bindings) {
+                            let field = field.text.to_string();
+                            expr.extend(quote! { .field(#field, #binding) }.token_trees);
+                        }
+                        expr
+                    }
+                    VariantShape::Tuple(_) => {
+                        let mut expr = quote! { f.debug_tuple(#name) }.token_trees;
+                        for binding in bindings {
+                            expr.extend(quote! { .field(#binding) }.token_trees);
+                        }
+                        expr
+                    }
+                    VariantShape::Unit => quote! { f.write_str(#name) }.token_trees,
+                };
+                if !matches!(variant.shape, VariantShape::Unit) {
+                    expr.extend(quote! { .finish() }.token_trees);
+                }
+                quote! { ##pat => ##expr , }.token_trees
+            })
+            .collect();
+        let body = match_self(adt, arms);
+        quote! {
+            fn fmt(&self, f: &mut #krate::fmt::Formatter) -> #krate::fmt::Result { ##body }
+        }
+        .token_trees
+    })
+}
+
+fn hash_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::hash::Hash }, |adt| {
+        if let AdtShape::Union = adt.shape {
+            return Vec::new();
+        }
+        let discriminant = match adt.shape {
+            AdtShape::Enum { .. } => {
+                quote! { #krate::hash::Hash::hash(&#krate::mem::discriminant(self), state); }
+                    .token_trees
+            }
+            _ => Vec::new(),
+        };
+        let arms =
+            adt.shape
+                .variants(&adt.name)
+                .into_iter()
+                .flat_map(|variant| {
+                    let pat = variant.shape.pattern(&variant.path, "__self");
+                    let stmts = variant.shape.bindings("__self").into_iter().flat_map(|it| {
+                        quote! { #krate::hash::Hash::hash(#it, state); }.token_trees
+                    });
+                    quote! { ##pat => { ##stmts } , }.token_trees
+                })
+                .collect();
+        let body = match_self(adt, arms);
+        quote! {
+            fn hash<H: #krate::hash::Hasher>(&self, state: &mut H) { ##discriminant ##body }
+        }
+        .token_trees
+    })
+}
+
+fn eq_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::cmp::Eq }, |_| Vec::new())
+}
+
+fn partial_eq_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::cmp::PartialEq }, |adt| {
+        if let AdtShape::Union = adt.shape {
+            return Vec::new();
+        }
+        let arms = pairwise_arms(adt, |fields| {
+            let mut expr = Vec::new();
+            for (lhs, rhs) in fields {
+                if !expr.is_empty() {
+                    expr.extend(quote! { && }.token_trees);
+                }
+                expr.extend(quote! { #lhs == #rhs }.token_trees);
+            }
+            if expr.is_empty() {
+                expr = quote! { true }.token_trees;
+            }
+            expr
+        });
+        let body = match_self_and_other(adt, arms, quote! { false }.token_trees);
+        quote! { fn eq(&self, other: &Self) -> bool { ##body } }.token_trees
+    })
+}
+
+fn ord_expand(krate: &tt::Ident, tt: &tt::Subtree) -> Result<tt::Subtree, mbe::ExpandError> {
+    expand_simple_derive(tt, quote! { #krate::cmp::Ord }, |adt| {
+        if let AdtShape::Union = adt.shape {
+            return Vec::new();
+        }
+        let arms = pairwise_arms(adt, |fields| {
+            // Compare the fields lexicographically, innermost last.
+            let mut expr = quote! { #krate::cmp::Ordering::Equal }.token_trees;
+            for (lhs, rhs) in fields.into_iter().rev() {
+                expr = quote! {
+                    match #krate::cmp::Ord::cmp(#lhs, #rhs) {
+                        #krate::cmp::Ordering::Equal => ##expr ,
+                        cmp => cmp ,
+                    }
+                }
+                .token_trees;
+            }
+            expr
+        });
+        let lhs = variant_index(adt, quote! { self }.token_trees);
+        let rhs = variant_index(adt, quote! { other }.token_trees);
+        let fallback = quote! {
+            {
+                let __self_vi = ##lhs ;
+                let __arg1_vi = ##rhs ;
+                #krate::cmp::Ord::cmp(&__self_vi, &__arg1_vi)
+            }
+        };
+        let fallback = vec![fallback.into()];
+        let body = match_self_and_other(adt, arms, fallback);
+        quote! { fn cmp(&self, other: &Self) -> #krate::cmp::Ordering { ##body } }.token_trees
+    })
 }
 
 fn partial_ord_expand(
-    db: &dyn AstDatabase,
-    id: MacroCallId,
+    krate: &tt::Ident,
     tt: &tt::Subtree,
 ) -> Result<tt::Subtree, mbe::ExpandError> {
-    let krate = find_builtin_crate(db, id);
-    expand_simple_derive(tt, quote! { #krate::cmp::PartialOrd })
+    expand_simple_derive(tt, quote! { #krate::cmp::PartialOrd }, |adt| {
+        if let AdtShape::Union = adt.shape {
+            return Vec::new();
+        }
+        let arms = pairwise_arms(adt, |fields| {
+            // Compare the fields lexicographically, innermost last.
+            let mut expr =
+                quote! { #krate::option::Option::Some(#krate::cmp::Ordering::Equal) }.token_trees;
+            for (lhs, rhs) in fields.into_iter().rev() {
+                expr = quote! {
+                    match #krate::cmp::PartialOrd::partial_cmp(#lhs, #rhs) {
+                        #krate::option::Option::Some(#krate::cmp::Ordering::Equal) => ##expr ,
+                        cmp => cmp ,
+                    }
+                }
+                .token_trees;
+            }
+            expr
+        });
+        let lhs = variant_index(adt, quote! { self }.token_trees);
+        let rhs = variant_index(adt, quote! { other }.token_trees);
+        let fallback = quote! {
+            {
+                let __self_vi = ##lhs ;
+                let __arg1_vi = ##rhs ;
+                #krate::cmp::PartialOrd::partial_cmp(&__self_vi, &__arg1_vi)
+            }
+        };
+        let fallback = vec![fallback.into()];
+        let body = match_self_and_other(adt, arms, fallback);
+        quote! {
+            fn partial_cmp(&self, other: &Self)
+                -> #krate::option::Option<#krate::cmp::Ordering> { ##body }
+        }
+        .token_trees
+    })
 }
 
 #[cfg(test)]
@@ -383,7 +765,145 @@ $0
             #[derive(Clone)]
             struct Foo<A, B>;
             "#,
-            expect![["impl<T0:core::clone::Clone,T1:core::clone::Clone>core::clone::CloneforFoo<T0,T1>{}"]],
+            expect![[
+                r#"impl<T0:core::clone::Clone,T1:core::clone::Clone>core::clone::CloneforFoo<T0,T1>{fnclone(&self)->Self{matchself{Foo=>Foo,}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_clone_expand_enum() {
+        check_derive(
+            r#"
+            macro Clone {}
+            #[derive(Clone)]
+            enum Foo { A(u8, u8), B { x: u32 }, C }
+            "#,
+            expect![[
+                r#"impl< >core::clone::CloneforFoo< >{fnclone(&self)->Self{matchself{Foo::A(__self_0,__self_1,)=>Foo::A(core::clone::Clone::clone(__self_0),core::clone::Clone::clone(__self_1),),Foo::B{x:__self_0,}=>Foo::B{x:core::clone::Clone::clone(__self_0),},Foo::C=>Foo::C,}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_clone_expand_union() {
+        check_derive(
+            r#"
+            macro Clone {}
+            #[derive(Clone)]
+            union Foo { x: u8, y: i8 }
+            "#,
+            expect![[r#"impl< >core::clone::CloneforFoo< >{fnclone(&self)->Self{*self}}"#]],
+        );
+    }
+
+    #[test]
+    fn test_default_expand() {
+        check_derive(
+            r#"
+            macro Default {}
+            #[derive(Default)]
+            struct Foo { x: u32, y: (u8, u8) }
+            "#,
+            expect![[
+                r#"impl< >core::default::DefaultforFoo< >{fndefault()->Self{Foo{x:core::default::Default::default(),y:core::default::Default::default(),}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_default_expand_enum() {
+        check_derive(
+            r#"
+            macro Default {}
+            #[derive(Default)]
+            enum Foo { A(u8), #[default] B, C }
+            "#,
+            expect![[r#"impl< >core::default::DefaultforFoo< >{fndefault()->Self{Foo::B}}"#]],
+        );
+    }
+
+    #[test]
+    fn test_debug_expand() {
+        check_derive(
+            r#"
+            macro Debug {}
+            #[derive(Debug)]
+            enum Foo { A(u8, u8), B { x: u32 }, C }
+            "#,
+            expect![[
+                r#"impl< >core::fmt::DebugforFoo< >{fnfmt(&self,f: &mutcore::fmt::Formatter)->core::fmt::Result{matchself{Foo::A(__self_0,__self_1,)=>f.debug_tuple("A").field(__self_0).field(__self_1).finish(),Foo::B{x:__self_0,}=>f.debug_struct("B").field("x",__self_0).finish(),Foo::C=>f.write_str("C"),}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_hash_expand() {
+        check_derive(
+            r#"
+            macro Hash {}
+            #[derive(Hash)]
+            enum Foo { A(u8), B { x: u32 } }
+            "#,
+            expect![[
+                r#"impl< >core::hash::HashforFoo< >{fnhash<H:core::hash::Hasher>(&self,state: &mutH){core::hash::Hash::hash(&core::mem::discriminant(self),state);matchself{Foo::A(__self_0,)=>{core::hash::Hash::hash(__self_0,state);},Foo::B{x:__self_0,}=>{core::hash::Hash::hash(__self_0,state);},}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_partial_eq_expand() {
+        check_derive(
+            r#"
+            macro PartialEq {}
+            #[derive(PartialEq)]
+            enum Foo { A(u8), B { x: u32, y: u32 }, C }
+            "#,
+            expect![[
+                r#"impl< >core::cmp::PartialEqforFoo< >{fneq(&self,other: &Self)->bool{match(self,other){(Foo::A(__self_0,),Foo::A(__arg1_0,))=>__self_0==__arg1_0,(Foo::B{x:__self_0,y:__self_1,},Foo::B{x:__arg1_0,y:__arg1_1,})=>__self_0==__arg1_0&&__self_1==__arg1_1,(Foo::C,Foo::C)=>true,_=>false,}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_partial_eq_expand_empty_enum() {
+        check_derive(
+            r#"
+            macro PartialEq {}
+            #[derive(PartialEq)]
+            enum Foo {}
+            "#,
+            expect![[
+                r#"impl< >core::cmp::PartialEqforFoo< >{fneq(&self,other: &Self)->bool{match*self{}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_partial_ord_expand() {
+        check_derive(
+            r#"
+            macro PartialOrd {}
+            #[derive(PartialOrd)]
+            struct Foo(u8, u32);
+            "#,
+            expect![[
+                r#"impl< >core::cmp::PartialOrdforFoo< >{fnpartial_cmp(&self,other: &Self)->core::option::Option<core::cmp::Ordering>{match(self,other){(Foo(__self_0,__self_1,),Foo(__arg1_0,__arg1_1,))=>matchcore::cmp::PartialOrd::partial_cmp(__self_0,__arg1_0){core::option::Option::Some(core::cmp::Ordering::Equal)=>matchcore::cmp::PartialOrd::partial_cmp(__self_1,__arg1_1){core::option::Option::Some(core::cmp::Ordering::Equal)=>core::option::Option::Some(core::cmp::Ordering::Equal),cmp=>cmp,},cmp=>cmp,},}}}"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_ord_expand() {
+        check_derive(
+            r#"
+            macro Ord {}
+            #[derive(Ord)]
+            enum Foo { A(u8), B }
+            "#,
+            expect![[
+                r#"impl< >core::cmp::OrdforFoo< >{fncmp(&self,other: &Self)->core::cmp::Ordering{match(self,other){(Foo::A(__self_0,),Foo::A(__arg1_0,))=>matchcore::cmp::Ord::cmp(__self_0,__arg1_0){core::cmp::Ordering::Equal=>core::cmp::Ordering::Equal,cmp=>cmp,},(Foo::B,Foo::B)=>core::cmp::Ordering::Equal,_=>{let__self_vi=matchself{Foo::A(..)=>0,Foo::B=>1,};let__arg1_vi=matchother{Foo::A(..)=>0,Foo::B=>1,};core::cmp::Ord::cmp(&__self_vi, &__arg1_vi)},}}}"#
+            ]],
         );
     }
 }
@@ -1,13 +1,14 @@
 //! Builtin macro
 use crate::{
-    db::AstDatabase, name, quote, AstId, CrateId, MacroCallId, MacroCallLoc, MacroDefId,
-    MacroDefKind, TextSize,
+    db::AstDatabase, name, quote, AstId, CrateId, MacroCallId, MacroCallKind, MacroCallLoc,
+    MacroDefId, MacroDefKind, TextSize,
 };
 
 use base_db::{AnchoredPath, Edition, FileId};
 use cfg::CfgExpr;
 use either::Either;
 use mbe::{parse_exprs_with_sep, parse_to_token_tree, ExpandResult};
+use parser::FragmentKind;
 use syntax::ast::{self, AstToken};
 
 macro_rules! register_builtin {
@@ -106,16 +107,21 @@ register_builtin! {
     // format_args_nl only differs in that it adds a newline in the end,
     // so we use the same stub expansion for now
     (format_args_nl, FormatArgsNl) => format_args_expand,
+    (const_format_args, ConstFormatArgs) => format_args_expand,
     (llvm_asm, LlvmAsm) => asm_expand,
     (asm, Asm) => asm_expand,
     (global_asm, GlobalAsm) => global_asm_expand,
     (cfg, Cfg) => cfg_expand,
     (core_panic, CorePanic) => panic_expand,
     (std_panic, StdPanic) => panic_expand,
+    (unreachable, Unreachable) => unreachable_expand,
+    (trace_macros, TraceMacros) => no_op_expand,
+    (log_syntax, LogSyntax) => no_op_expand,
 
     EAGER:
     (compile_error, CompileError) => compile_error_expand,
     (concat, Concat) => concat_expand,
+    (concat_bytes, ConcatBytes) => concat_bytes_expand,
     (concat_idents, ConcatIdents) => concat_idents_expand,
     (include, Include) => include_expand,
     (include_bytes, IncludeBytes) => include_bytes_expand,
@@ -314,6 +320,40 @@ fn panic_expand(
     ExpandResult::ok(call)
 }
 
+fn unreachable_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> ExpandResult<tt::Subtree> {
+    let loc: MacroCallLoc = db.lookup_intern_macro(id);
+    // Expand to a macro call `$crate::panic::unreachable_{edition}`
+    let krate = tt::Ident { text: "$crate".into(), id: tt::TokenId::unspecified() };
+    let mut call = if db.crate_graph()[loc.krate].edition == Edition::Edition2021 {
+        quote!(#krate::panic::unreachable_2021!)
+    } else {
+        quote!(#krate::panic::unreachable_2015!)
+    };
+
+    // Pass the original arguments
+    call.token_trees.push(tt::TokenTree::Subtree(tt.clone()));
+    ExpandResult::ok(call)
+}
+
+fn no_op_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    _tt: &tt::Subtree,
+) -> ExpandResult<tt::Subtree> {
+    // `trace_macros!` and `log_syntax!` only print things while compiling, so
+    // expand them to nothing, or to a unit value in expression position.
+    let loc: MacroCallLoc = db.lookup_intern_macro(id);
+    let expanded = match loc.kind {
+        MacroCallKind::FnLike { fragment: FragmentKind::Expr, .. } => quote! { () },
+        _ => quote! {},
+    };
+    ExpandResult::ok(expanded)
+}
+
 fn unquote_str(lit: &tt::Literal) -> Option<String> {
     let lit = ast::make::tokens::literal(&lit.to_string());
     let token = ast::String::cast(lit)?;
@@ -372,6 +412,84 @@ fn concat_expand(
     ExpandResult { value: Some(ExpandedEager::new(quote!(#text))), err }
 }
 
+fn concat_bytes_expand(
+    _db: &dyn AstDatabase,
+    _arg_id: MacroCallId,
+    tt: &tt::Subtree,
+) -> ExpandResult<Option<ExpandedEager>> {
+    let mut err = None;
+    let mut bytes = Vec::new();
+    for (i, t) in tt.token_trees.iter().enumerate() {
+        match t {
+            tt::TokenTree::Leaf(tt::Leaf::Literal(it)) if i % 2 == 0 => {
+                if !unquote_bytes(it, true, &mut bytes) {
+                    err.get_or_insert(mbe::ExpandError::UnexpectedToken);
+                }
+            }
+            // `[1, b'a']` arrays of single bytes
+            tt::TokenTree::Subtree(tree)
+                if i % 2 == 0 && tree.delimiter_kind() == Some(tt::DelimiterKind::Bracket) =>
+            {
+                for (j, t) in tree.token_trees.iter().enumerate() {
+                    match t {
+                        tt::TokenTree::Leaf(tt::Leaf::Literal(it))
+                            if j % 2 == 0 && unquote_bytes(it, false, &mut bytes) => {}
+                        tt::TokenTree::Leaf(tt::Leaf::Punct(punct))
+                            if j % 2 == 1 && punct.char == ',' => {}
+                        _ => {
+                            err.get_or_insert(mbe::ExpandError::UnexpectedToken);
+                        }
+                    }
+                }
+            }
+            tt::TokenTree::Leaf(tt::Leaf::Punct(punct)) if i % 2 == 1 && punct.char == ',' => (),
+            _ => {
+                err.get_or_insert(mbe::ExpandError::UnexpectedToken);
+            }
+        }
+    }
+    let text = bytes.into_iter().flat_map(std::ascii::escape_default).map(char::from);
+    let literal = tt::Literal {
+        text: format!("b\"{}\"", text.collect::<String>()).into(),
+        id: tt::TokenId::unspecified(),
+    };
+    let expanded =
+        tt::Subtree { delimiter: None, token_trees: vec![tt::Leaf::from(literal).into()] };
+    ExpandResult { value: Some(ExpandedEager::new(expanded)), err }
+}
+
+/// Appends the bytes of a byte, byte string or integer literal to `acc`.
+fn unquote_bytes(lit: &tt::Literal, allow_strings: bool, acc: &mut Vec<u8>) -> bool {
+    let byte_string;
+    // Bytes are unescaped as byte strings, `"` is the only character which
+    // needs an escape in byte strings but not in bytes.
+    let (text, is_byte) = match lit.text.strip_prefix("b'").and_then(|it| it.strip_suffix('\'')) {
+        Some("\"") => ("b\"\\\"\"", true),
+        Some(byte) => {
+            byte_string = format!("b\"{}\"", byte);
+            (byte_string.as_str(), true)
+        }
+        None => (lit.text.as_str(), false),
+    };
+    let token = ast::make::tokens::literal(text);
+    if let Some(string) = ast::ByteString::cast(token.clone()) {
+        return match string.value() {
+            Some(value) if (is_byte && value.len() == 1) || (!is_byte && allow_strings) => {
+                acc.extend_from_slice(&value);
+                true
+            }
+            _ => false,
+        };
+    }
+    match ast::IntNumber::cast(token).and_then(|it| it.value()) {
+        Some(value) if value <= u8::MAX as u128 => {
+            acc.push(value as u8);
+            true
+        }
+        _ => false,
+    }
+}
+
 fn concat_idents_expand(
     _db: &dyn AstDatabase,
     _arg_id: MacroCallId,
@@ -540,7 +658,7 @@ fn option_env_expand(
 
     let expanded = match get_env_inner(db, arg_id, &key) {
         None => quote! { std::option::Option::None::<&str> },
-        Some(s) => quote! { std::option::Some(#s) },
+        Some(s) => quote! { std::option::Option::Some(#s) },
     };
 
     ExpandResult::ok(Some(ExpandedEager::new(expanded)))
@@ -822,4 +940,57 @@ mod tests {
             expect![[r#"foobar"#]],
         );
     }
+
+    #[test]
+    fn test_concat_bytes_expand() {
+        check_expansion(
+            r##"
+            #[rustc_builtin_macro]
+            macro_rules! concat_bytes {}
+            concat_bytes!(b'A', b"BC", [68, b'E', 70], b'\n');
+            "##,
+            expect![[r#"b"ABCDEF\n""#]],
+        );
+    }
+
+    #[test]
+    fn test_const_format_args_expand() {
+        check_expansion(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! const_format_args {
+                ($fmt:expr) => ({ /* compiler built-in */ });
+                ($fmt:expr, $($args:tt)*) => ({ /* compiler built-in */ })
+            }
+            const_format_args!("{}", "a");
+            "#,
+            expect![[
+                r#"std::fmt::Arguments::new_v1(&[], &[std::fmt::ArgumentV1::new(&("a"),std::fmt::Display::fmt),])"#
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_unreachable_expand() {
+        check_expansion(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! unreachable {}
+            unreachable!("{}", 92);
+            "#,
+            expect![[r#"$crate::panic::unreachable_2015!("{}",92)"#]],
+        );
+    }
+
+    #[test]
+    fn test_trace_macros_expand() {
+        check_expansion(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! trace_macros {}
+            trace_macros!(true);
+            "#,
+            expect![[r#"()"#]],
+        );
+    }
 }
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use base_db::{salsa, SourceDatabase};
+use base_db::{salsa, ProcMacroDiagnostic, ProcMacroExpansion, ProcMacroSpans, SourceDatabase};
 use mbe::{ExpandError, ExpandResult};
 use parser::FragmentKind;
 use syntax::{
@@ -47,8 +47,15 @@ impl TokenExpander {
         tt: &tt::Subtree,
     ) -> mbe::ExpandResult<tt::Subtree> {
         match self {
-            TokenExpander::MacroRules { mac, .. } => mac.expand(tt),
-            TokenExpander::MacroDef { mac, .. } => mac.expand(tt),
+            // Tracing is only worth it for a note on why the expansion failed.
+            TokenExpander::MacroRules { mac, .. } => match mac.expand(tt) {
+                res if res.err.is_some() => with_trace_note(mac.expand_with_trace(tt)),
+                res => res,
+            },
+            TokenExpander::MacroDef { mac, .. } => match mac.expand(tt) {
+                res if res.err.is_some() => with_trace_note(mac.expand_with_trace(tt)),
+                res => res,
+            },
             TokenExpander::Builtin(it) => it.expand(db, id, tt),
             // FIXME switch these to ExpandResult as well
             TokenExpander::BuiltinAttr(it) => it.expand(db, id, tt).into(),
@@ -57,11 +64,22 @@ impl TokenExpander {
                 // We store the result in salsa db to prevent non-deterministic behavior in
                 // some proc-macro implementation
                 // See #4315 for details
-                db.expand_proc_macro(id).into()
+                db.expand_proc_macro(id).map(|it| it.subtree).into()
             }
         }
     }
 
+    fn trace(&self, tt: &tt::Subtree) -> Option<mbe::ExpansionTrace> {
+        match self {
+            TokenExpander::MacroRules { mac, .. } => Some(mac.expand_with_trace(tt).1),
+            TokenExpander::MacroDef { mac, .. } => Some(mac.expand_with_trace(tt).1),
+            TokenExpander::Builtin(..)
+            | TokenExpander::BuiltinAttr(..)
+            | TokenExpander::BuiltinDerive(..)
+            | TokenExpander::ProcMacro(..) => None,
+        }
+    }
+
     pub(crate) fn map_id_down(&self, id: tt::TokenId) -> tt::TokenId {
         match self {
             TokenExpander::MacroRules { mac, .. } => mac.map_id_down(id),
@@ -125,7 +143,14 @@ pub trait AstDatabase: SourceDatabase {
     /// proc macros, since they are not deterministic in general, and
     /// non-determinism breaks salsa in a very, very, very bad way. @edwin0cheng
     /// heroically debugged this once!
-    fn expand_proc_macro(&self, call: MacroCallId) -> Result<tt::Subtree, mbe::ExpandError>;
+    fn expand_proc_macro(&self, call: MacroCallId) -> Result<ProcMacroExpansion, mbe::ExpandError>;
+    /// Firewall query that returns the diagnostics emitted by a procedural
+    /// macro. Empty for all other kinds of macros.
+    fn proc_macro_diagnostics(&self, call: MacroCallId) -> Arc<Vec<ProcMacroDiagnostic>>;
+    /// Source locations of the input tokens of a proc macro call, relative to
+    /// the call. This is a firewall query: edits outside of the call don't
+    /// re-run the macro.
+    fn proc_macro_spans(&self, call: MacroCallId) -> Arc<ProcMacroSpans>;
     /// Firewall query that returns the error from the `macro_expand` query.
     fn macro_expand_error(&self, macro_call: MacroCallId) -> Option<ExpandError>;
 
@@ -165,6 +190,35 @@ pub fn expand_speculative(
     Some((node.syntax_node(), token))
 }
 
+/// Records how the arguments of a declarative macro call were matched against
+/// the rules of the macro. Returns `None` for other kinds of macros.
+pub fn trace_macro_expansion(
+    db: &dyn AstDatabase,
+    macro_call: MacroCallId,
+) -> Option<mbe::ExpansionTrace> {
+    let loc: MacroCallLoc = db.lookup_intern_macro(macro_call);
+    if loc.eager.is_some() {
+        return None;
+    }
+    let macro_def = db.macro_def(loc.def)?;
+    let macro_arg = db.macro_arg(macro_call)?;
+    macro_def.trace(&macro_arg.0)
+}
+
+/// Appends a description of the failed rule to the expansion error, so that
+/// "no rules matched" style diagnostics say where matching stopped.
+fn with_trace_note(
+    (res, trace): (ExpandResult<tt::Subtree>, mbe::ExpansionTrace),
+) -> ExpandResult<tt::Subtree> {
+    match (res.err, trace.failure_note()) {
+        (Some(err), Some(note)) => ExpandResult {
+            value: res.value,
+            err: Some(ExpandError::Other(format!("{} ({})", err, note))),
+        },
+        (err, _) => ExpandResult { value: res.value, err },
+    }
+}
+
 fn ast_id_map(db: &dyn AstDatabase, file_id: HirFileId) -> Arc<AstIdMap> {
     let map = db.parse_or_expand(file_id).map(|it| AstIdMap::from_source(&it)).unwrap_or_default();
     Arc::new(map)
@@ -369,7 +423,7 @@ fn macro_expand_with_arg(
 fn expand_proc_macro(
     db: &dyn AstDatabase,
     id: MacroCallId,
-) -> Result<tt::Subtree, mbe::ExpandError> {
+) -> Result<ProcMacroExpansion, mbe::ExpandError> {
     let loc: MacroCallLoc = db.lookup_intern_macro(id);
     let macro_arg = match db.macro_arg(id) {
         Some(it) => it,
@@ -386,11 +440,73 @@ fn expand_proc_macro(
     };
 
     let attr_arg = match &loc.kind {
-        MacroCallKind::Attr { attr_args, .. } => Some(attr_args),
+        MacroCallKind::Attr { attr_args, .. } => {
+            // The attribute arguments are lowered separately from the item, so their token ids
+            // overlap with the item's ones. Move them out of the way, for them not to be
+            // mistaken for item tokens.
+            let shift = macro_arg.1.entries().map(|(id, _)| id.0 + 1).max().unwrap_or(0);
+            let mut attr_args = attr_args.clone();
+            shift_token_ids(&mut attr_args, shift);
+            Some(attr_args)
+        }
         _ => None,
     };
 
-    expander.expand(db, loc.krate, &macro_arg.0, attr_arg)
+    let spans = db.proc_macro_spans(id);
+    expander.expand(db, loc.krate, &macro_arg.0, attr_arg.as_ref(), &spans)
+}
+
+fn proc_macro_diagnostics(db: &dyn AstDatabase, id: MacroCallId) -> Arc<Vec<ProcMacroDiagnostic>> {
+    let loc: MacroCallLoc = db.lookup_intern_macro(id);
+    if !loc.def.is_proc_macro() {
+        return Default::default();
+    }
+    match db.expand_proc_macro(id) {
+        Ok(expansion) => Arc::new(expansion.diagnostics),
+        Err(_) => Default::default(),
+    }
+}
+
+fn proc_macro_spans(db: &dyn AstDatabase, id: MacroCallId) -> Arc<ProcMacroSpans> {
+    let loc: MacroCallLoc = db.lookup_intern_macro(id);
+    // FIXME: map calls produced by other macros back to their original file
+    let file_id = match loc.kind.file_id().0 {
+        HirFileIdRepr::FileId(file_id) => file_id,
+        HirFileIdRepr::MacroFile(_) => return Default::default(),
+    };
+    let (arg, macro_arg) = match (loc.kind.arg(db), db.macro_arg(id)) {
+        (Some(arg), Some(macro_arg)) => (arg, macro_arg),
+        _ => return Default::default(),
+    };
+
+    // Attributes removed from the input are blanked out rather than deleted,
+    // so the token ranges are relative to the original text of the argument.
+    // Absolute positions are left out on purpose, they are mapped back from
+    // these on our side when needed (see `proc_macro::diagnostic_range`).
+    Arc::new(ProcMacroSpans {
+        file: db.file_path(file_id).map(|path| path.to_string()).unwrap_or_default(),
+        text: arg.text().to_string(),
+        tokens: macro_arg.1.entries().collect(),
+    })
+}
+
+fn shift_token_ids(subtree: &mut tt::Subtree, shift: u32) {
+    let shift_id = |id: &mut tt::TokenId| {
+        if *id != tt::TokenId::unspecified() {
+            id.0 += shift;
+        }
+    };
+    if let Some(delimiter) = &mut subtree.delimiter {
+        shift_id(&mut delimiter.id);
+    }
+    for tt in &mut subtree.token_trees {
+        match tt {
+            tt::TokenTree::Leaf(tt::Leaf::Ident(it)) => shift_id(&mut it.id),
+            tt::TokenTree::Leaf(tt::Leaf::Punct(it)) => shift_id(&mut it.id),
+            tt::TokenTree::Leaf(tt::Leaf::Literal(it)) => shift_id(&mut it.id),
+            tt::TokenTree::Subtree(it) => shift_token_ids(it, shift),
+        }
+    }
 }
 
 fn is_self_replicating(from: &SyntaxNode, to: &SyntaxNode) -> bool {
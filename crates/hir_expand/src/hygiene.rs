@@ -1,7 +1,8 @@
 //! This modules handles hygiene information.
 //!
 //! Specifically, `ast` + `Hygiene` allows you to create a `Name`. Note that, at
-//! this moment, this is horribly incomplete and handles only `$crate`.
+//! this moment, this is horribly incomplete and handles only `$crate`, and the
+//! mixed-site hygiene of local variables (see [`SyntaxContext`]).
 use std::sync::Arc;
 
 use base_db::CrateId;
@@ -9,14 +10,71 @@ use db::TokenExpander;
 use either::Either;
 use mbe::Origin;
 use parser::SyntaxKind;
-use syntax::{ast, AstNode, SyntaxNode, TextRange, TextSize};
+use syntax::{
+    ast::{self, NameOwner},
+    AstNode, SyntaxNode, SyntaxToken, TextRange, TextSize,
+};
 
 use crate::{
     db::{self, AstDatabase},
     name::{AsName, Name},
-    HirFileId, HirFileIdRepr, InFile, MacroCallLoc, MacroDefKind, MacroFile,
+    HirFileId, HirFileIdRepr, InFile, MacroCallId, MacroCallLoc, MacroDefKind, MacroFile,
 };
 
+/// The syntax context of an identifier, used to resolve local variables hygienically.
+///
+/// `macro_rules!` macros have mixed-site hygiene: local variables and labels introduced by
+/// tokens written in the macro definition are only visible to other tokens written in the
+/// same definition (and expanded by the same call). Identifiers that were written in the
+/// source or passed in as macro arguments are in the root context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SyntaxContext(Option<MacroCallId>);
+
+impl SyntaxContext {
+    pub const ROOT: SyntaxContext = SyntaxContext(None);
+
+    pub fn is_root(self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Returns the contexts whose local variables are visible to an identifier in this context,
+    /// innermost first.
+    ///
+    /// For a macro defined inside a block, this includes the context of the macro definition,
+    /// as the locals in scope at the definition are visible to the macro body.
+    pub fn visible_contexts(self, db: &dyn AstDatabase) -> Vec<SyntaxContext> {
+        std::iter::successors(Some(self), |ctx| ctx.parent(db)).collect()
+    }
+
+    fn parent(self, db: &dyn AstDatabase) -> Option<SyntaxContext> {
+        let loc = db.lookup_intern_macro(self.0?);
+        let def = match loc.def.kind {
+            MacroDefKind::Declarative(id) => id,
+            _ => return None,
+        };
+        let mac = def.to_node(db);
+        if !mac.syntax().ancestors().any(|it| ast::BlockExpr::can_cast(it.kind())) {
+            return None;
+        }
+        let name = mac.name()?.syntax().first_token()?;
+        Some(Hygiene::new(db, def.file_id).syntax_context(db, &name))
+    }
+}
+
+impl PartialOrd for SyntaxContext {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SyntaxContext {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        use base_db::salsa::InternKey;
+        let key = |ctx: &Self| ctx.0.map(|id| id.as_intern_id());
+        key(self).cmp(&key(other))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Hygiene {
     frames: Option<HygieneFrames>,
@@ -66,6 +124,48 @@ impl Hygiene {
             token = mapped.value;
         }
     }
+
+    /// Computes the [`SyntaxContext`] of `token`, by following it up through the macro
+    /// arguments until we reach the `macro_rules!` definition it was written in, if any. Tokens
+    /// produced by other macros are followed up to the macro call.
+    pub fn syntax_context(&self, db: &dyn AstDatabase, token: &SyntaxToken) -> SyntaxContext {
+        let frames = match &self.frames {
+            Some(it) => it,
+            None => return SyntaxContext::ROOT,
+        };
+        let mut token = token.text_range();
+        let mut current = frames.0.clone();
+
+        loop {
+            let expansion = match &current.expansion {
+                Some(it) => it,
+                None => return SyntaxContext::ROOT,
+            };
+            let call_id = expansion.file.macro_call_id;
+            let mapped = match expansion.map_ident_up(db, token) {
+                Some((_, Origin::Def)) => return SyntaxContext(Some(call_id)),
+                Some((mapped, Origin::Call)) => mapped.value,
+                // The token was produced by the expander itself.
+                None => {
+                    let loc = db.lookup_intern_macro(call_id);
+                    match loc.def.kind {
+                        MacroDefKind::Declarative(_) => return SyntaxContext(Some(call_id)),
+                        // Proc macros and built-in macros produce tokens with call-site
+                        // hygiene, so they get the context of the macro call itself.
+                        _ => match loc.kind.to_node(db).value.first_token() {
+                            Some(it) => it.text_range(),
+                            None => return SyntaxContext::ROOT,
+                        },
+                    }
+                }
+            };
+            current = match &current.call_site {
+                Some(it) => it.clone(),
+                None => return SyntaxContext::ROOT,
+            };
+            token = mapped;
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -19,7 +19,7 @@ mod input;
 use base_db::ProcMacroKind;
 use either::Either;
 
-pub use mbe::{ExpandError, ExpandResult};
+pub use mbe::{ExpandError, ExpandResult, ExpansionTrace, MatchFailure, RuleTrace};
 pub use parser::FragmentKind;
 
 use std::hash::Hash;
@@ -115,6 +115,15 @@ impl HirFileId {
         }
     }
 
+    /// If this is a macro expansion file, returns the id of the macro call that
+    /// produced it.
+    pub fn macro_call_id(self) -> Option<MacroCallId> {
+        match self.0 {
+            HirFileIdRepr::FileId(_) => None,
+            HirFileIdRepr::MacroFile(macro_file) => Some(macro_file.macro_call_id),
+        }
+    }
+
     /// Return expansion information if it is a macro-expansion file
     pub fn expansion_info(self, db: &dyn db::AstDatabase) -> Option<ExpansionInfo> {
         match self.0 {
@@ -577,7 +586,9 @@ pub fn to_fragment_kind(call: &ast::MacroCall) -> FragmentKind {
         FOR_EXPR => FragmentKind::Expr,
         PATH_EXPR => FragmentKind::Expr,
         CLOSURE_EXPR => FragmentKind::Expr,
-        CONDITION => FragmentKind::Expr,
+        IF_EXPR => FragmentKind::Expr,
+        WHILE_EXPR => FragmentKind::Expr,
+        LET_EXPR => FragmentKind::Expr,
         BREAK_EXPR => FragmentKind::Expr,
         RETURN_EXPR => FragmentKind::Expr,
         MATCH_EXPR => FragmentKind::Expr,
@@ -224,11 +224,16 @@ pub mod known {
         include_str,
         format_args,
         format_args_nl,
+        const_format_args,
         env,
         option_env,
         llvm_asm,
         asm,
         global_asm,
+        unreachable,
+        trace_macros,
+        log_syntax,
+        concat_bytes,
         // Builtin derives
         Copy,
         Clone,
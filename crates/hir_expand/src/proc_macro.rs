@@ -1,7 +1,8 @@
 //! Proc Macro Expander stub
 
-use crate::db::AstDatabase;
-use base_db::{CrateId, ProcMacroId};
+use crate::{db::AstDatabase, InFile, MacroCallId, MacroCallLoc};
+use base_db::{CrateId, ProcMacroExpansion, ProcMacroId, ProcMacroSpans};
+use syntax::TextRange;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct ProcMacroExpander {
@@ -38,7 +39,8 @@ impl ProcMacroExpander {
         calling_crate: CrateId,
         tt: &tt::Subtree,
         attr_arg: Option<&tt::Subtree>,
-    ) -> Result<tt::Subtree, mbe::ExpandError> {
+        spans: &ProcMacroSpans,
+    ) -> Result<ProcMacroExpansion, mbe::ExpandError> {
         match self.proc_macro_id {
             Some(id) => {
                 let krate_graph = db.crate_graph();
@@ -50,9 +52,29 @@ impl ProcMacroExpander {
                 // Proc macros have access to the environment variables of the invoking crate.
                 let env = &krate_graph[calling_crate].env;
 
-                proc_macro.expander.expand(tt, attr_arg, env).map_err(mbe::ExpandError::from)
+                proc_macro.expander.expand(tt, attr_arg, env, spans).map_err(mbe::ExpandError::from)
             }
             None => Err(mbe::ExpandError::UnresolvedProcMacro),
         }
     }
 }
+
+/// Maps the input tokens a proc macro diagnostic points at back to the source
+/// of the macro call. Returns `None` if none of the tokens come from the call's
+/// input.
+pub fn diagnostic_range(
+    db: &dyn AstDatabase,
+    call: MacroCallId,
+    spans: &[tt::TokenId],
+) -> Option<InFile<TextRange>> {
+    let loc: MacroCallLoc = db.lookup_intern_macro(call);
+    let arg = loc.kind.arg(db)?;
+    let macro_arg = db.macro_arg(call)?;
+    let range = macro_arg
+        .1
+        .entries()
+        .filter(|(id, _)| spans.contains(id))
+        .map(|(_, range)| range)
+        .reduce(|acc, range| acc.cover(range))?;
+    Some(InFile::new(loc.kind.file_id(), range + arg.text_range().start()))
+}
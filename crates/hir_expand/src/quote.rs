@@ -84,6 +84,14 @@ macro_rules! __quote {
     // Literal
     ( $tt:literal ) => { vec![$crate::quote::ToTokenTree::to_token($tt).into()] };
     // Ident
+    ( _ ) => {
+        vec![ {
+            tt::Leaf::Ident(tt::Ident {
+                text: "_".into(),
+                id: tt::TokenId::unspecified(),
+            }).into()
+        }]
+    };
     ( $tt:ident ) => {
         vec![ {
             tt::Leaf::Ident(tt::Ident {
@@ -96,7 +104,13 @@ macro_rules! __quote {
     // Puncts
     // FIXME: Not all puncts are handled
     ( -> ) => {$crate::__quote!(@PUNCT '-', '>')};
+    ( => ) => {$crate::__quote!(@PUNCT '=', '>')};
+    ( == ) => {$crate::__quote!(@PUNCT '=', '=')};
+    ( = ) => {$crate::__quote!(@PUNCT '=')};
+    ( && ) => {$crate::__quote!(@PUNCT '&', '&')};
+    ( .. ) => {$crate::__quote!(@PUNCT '.', '.')};
     ( & ) => {$crate::__quote!(@PUNCT '&')};
+    ( * ) => {$crate::__quote!(@PUNCT '*')};
     ( , ) => {$crate::__quote!(@PUNCT ',')};
     ( : ) => {$crate::__quote!(@PUNCT ':')};
     ( ; ) => {$crate::__quote!(@PUNCT ';')};
@@ -5,7 +5,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use base_db::{salsa, AnchoredPath, CrateId, FileId, FileLoader, FileLoaderDelegate};
+use base_db::{salsa, AnchoredPath, CrateId, FileId, FileLoader, FileLoaderDelegate, VfsPath};
 use rustc_hash::FxHashSet;
 
 #[salsa::database(
@@ -43,6 +43,9 @@ impl FileLoader for TestDB {
     fn resolve_path(&self, path: AnchoredPath) -> Option<FileId> {
         FileLoaderDelegate(self).resolve_path(path)
     }
+    fn file_path(&self, file_id: FileId) -> Option<VfsPath> {
+        FileLoaderDelegate(self).file_path(file_id)
+    }
     fn relevant_crates(&self, file_id: FileId) -> Arc<FxHashSet<CrateId>> {
         FileLoaderDelegate(self).relevant_crates(file_id)
     }
@@ -1,14 +1,33 @@
 //! Constant evaluation details
+//!
+//! This is a small interpreter over [`Body`] which is used to compute the values of `const` and
+//! `static` items, array lengths and the like. It understands literals, arithmetic, paths to other
+//! constants, casts, blocks with local bindings, simple control flow and calls to `const fn`s.
 
-use std::convert::TryInto;
+use std::{cell::Cell, convert::TryFrom, fmt, sync::Arc};
 
+use base_db::CrateId;
+use chalk_ir::{IntTy, Scalar, UintTy};
 use hir_def::{
-    builtin_type::BuiltinUint,
-    expr::{Expr, Literal},
-    type_ref::ConstScalar,
+    body::Body,
+    builtin_type::{BuiltinInt, BuiltinUint},
+    expr::{
+        ArithOp, BinaryOp, CmpOp, Expr, ExprId, LabelId, Literal, LogicOp, Ordering, Pat, PatId,
+        UnaryOp,
+    },
+    path::ModPath,
+    resolver::{resolver_for_expr, HasResolver, Resolver, ValueNs},
+    type_ref::{ConstScalar, ConstScalarOrPath},
+    ConstId, DefWithBodyId, FunctionId, HasModule, StaticId,
 };
+use hir_expand::{hygiene::SyntaxContext, name::Name};
+use rustc_hash::FxHashMap;
 
-use crate::{Const, ConstData, ConstValue, Interner, TyKind};
+use crate::{
+    db::HirDatabase, layout::layout_of_ty, lower::TypeParamLoweringMode, make_only_type_binders,
+    utils::generics, Const, ConstData, ConstValue, Interner, Substitution, Ty, TyKind,
+    TyLoweringContext, ValueTyDefId,
+};
 
 /// Extension trait for [`Const`]
 pub trait ConstExt {
@@ -35,11 +54,95 @@ impl ConstExt for Const {
     }
 }
 
-// FIXME: support more than just evaluating literals
-pub fn eval_usize(expr: &Expr) -> Option<u64> {
-    match expr {
-        Expr::Literal(Literal::Uint(v, None | Some(BuiltinUint::Usize))) => (*v).try_into().ok(),
-        _ => None,
+/// A value computed by the const evaluator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputedExpr {
+    Literal(Literal),
+    Tuple(Box<[ComputedExpr]>),
+}
+
+impl ComputedExpr {
+    fn unit() -> ComputedExpr {
+        ComputedExpr::Tuple(Box::new([]))
+    }
+
+    /// Gets a target usize out of the value, if it is a non-negative integer.
+    pub fn as_usize(&self) -> Option<u64> {
+        match self {
+            ComputedExpr::Literal(Literal::Uint(v, _)) => u64::try_from(*v).ok(),
+            ComputedExpr::Literal(Literal::Int(v, _)) => u64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Gets the value out, if it is an integer that fits into an `i128`.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            ComputedExpr::Literal(Literal::Uint(v, _)) => i128::try_from(*v).ok(),
+            ComputedExpr::Literal(Literal::Int(v, _)) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ComputedExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputedExpr::Literal(lit) => match lit {
+                Literal::Int(v, _) => write!(f, "{}", v),
+                Literal::Uint(v, _) => write!(f, "{}", v),
+                Literal::Bool(v) => write!(f, "{}", v),
+                Literal::Char(c) => write!(f, "{:?}", c),
+                Literal::String(s) => write!(f, "{:?}", s),
+                Literal::ByteString(bs) => {
+                    write!(f, "b\"")?;
+                    for &b in bs.iter() {
+                        write!(f, "{}", std::ascii::escape_default(b))?;
+                    }
+                    write!(f, "\"")
+                }
+                Literal::Float(bits, _) => write!(f, "{}", f64::from_bits(*bits)),
+            },
+            ComputedExpr::Tuple(fields) => {
+                write!(f, "(")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", field)?;
+                }
+                if fields.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstEvalError {
+    /// The expression uses a feature the evaluator doesn't understand (yet).
+    NotSupported(&'static str),
+    /// The expression is ill-typed.
+    TypeError,
+    /// The expression has missing parts or unresolved paths.
+    IncompleteExpr,
+    /// Evaluation panicked, e.g. because of an arithmetic overflow.
+    Panic(String),
+    /// Evaluation is cyclic or doesn't terminate in a reasonable number of steps.
+    Loop,
+}
+
+impl fmt::Display for ConstEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstEvalError::NotSupported(what) => write!(f, "not supported: {}", what),
+            ConstEvalError::TypeError => write!(f, "type error"),
+            ConstEvalError::IncompleteExpr => write!(f, "incomplete expression"),
+            ConstEvalError::Panic(msg) => write!(f, "evaluation panicked: {}", msg),
+            ConstEvalError::Loop => write!(f, "cycle or infinite loop during evaluation"),
+        }
     }
 }
 
@@ -53,3 +156,830 @@ pub fn usize_const(value: Option<u64>) -> Const {
     }
     .intern(&Interner)
 }
+
+/// Evaluates `expr` of the body of `owner` to a target usize. This is used while inferring
+/// `owner`, so the types of the subexpressions are provided by `infer` instead of being queried.
+pub fn eval_usize(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    expr: ExprId,
+    infer: &mut dyn FnMut(ExprId) -> Ty,
+) -> Option<u64> {
+    let body = db.body(owner);
+    let steps = Cell::new(0);
+    let mut ev = Evaluator::new(db, owner, body, placeholder_substs(db, owner), 0, &steps);
+    ev.infer = Some(infer);
+    let value = ev.eval_root(expr).ok()?;
+    coerce(value, &TyKind::Scalar(Scalar::Uint(UintTy::Usize)).intern(&Interner)).ok()?.as_usize()
+}
+
+/// Evaluates a constant written outside of a body, like an array length or an
+/// enum discriminant, to an integer.
+pub(crate) fn eval_const_scalar_or_path(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    value: &ConstScalarOrPath,
+) -> Option<i128> {
+    eval_scalar_or_path(db, resolver, value).ok()?.as_i128()
+}
+
+fn eval_scalar_or_path(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    value: &ConstScalarOrPath,
+) -> Result<ComputedExpr, ConstEvalError> {
+    match value {
+        ConstScalarOrPath::Scalar(scalar) => match scalar.as_usize() {
+            Some(v) => Ok(ComputedExpr::Literal(Literal::Uint(v.into(), None))),
+            None => Err(ConstEvalError::IncompleteExpr),
+        },
+        ConstScalarOrPath::Path(path) => {
+            match resolver.resolve_path_in_value_ns_fully(db.upcast(), path, SyntaxContext::ROOT) {
+                Some(ValueNs::ConstId(it)) => db.const_eval(it),
+                Some(_) => Err(ConstEvalError::TypeError),
+                None => Err(ConstEvalError::IncompleteExpr),
+            }
+        }
+        ConstScalarOrPath::UnaryOp { op, expr } => {
+            eval_unary(*op, eval_scalar_or_path(db, resolver, expr)?)
+        }
+        ConstScalarOrPath::BinaryOp { op, lhs, rhs } => eval_arith(
+            *op,
+            eval_scalar_or_path(db, resolver, lhs)?,
+            eval_scalar_or_path(db, resolver, rhs)?,
+        ),
+    }
+}
+
+pub(crate) fn const_eval_query(
+    db: &dyn HirDatabase,
+    def: ConstId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let body = db.body(def.into());
+    let steps = Cell::new(0);
+    let substs = placeholder_substs(db, def.into());
+    let value = Evaluator::new(db, def.into(), body.clone(), substs, 0, &steps)
+        .eval_root(body.body_expr)?;
+    let resolver = def.resolver(db.upcast());
+    let ty = TyLoweringContext::new(db, &resolver).lower_ty(&db.const_data(def).type_ref);
+    coerce(value, &ty)
+}
+
+pub(crate) fn const_eval_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &ConstId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    Err(ConstEvalError::Loop)
+}
+
+pub(crate) fn const_eval_static_query(
+    db: &dyn HirDatabase,
+    def: StaticId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let body = db.body(def.into());
+    let steps = Cell::new(0);
+    let value =
+        Evaluator::new(db, def.into(), body.clone(), Substitution::empty(&Interner), 0, &steps)
+            .eval_root(body.body_expr)?;
+    let resolver = def.resolver(db.upcast());
+    let ty = TyLoweringContext::new(db, &resolver).lower_ty(&db.static_data(def).type_ref);
+    coerce(value, &ty)
+}
+
+pub(crate) fn const_eval_static_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &StaticId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    Err(ConstEvalError::Loop)
+}
+
+/// Maximum number of nested `const fn` calls.
+const MAX_CALL_DEPTH: usize = 64;
+/// Maximum number of expressions evaluated for a single constant.
+const MAX_STEPS: usize = 100_000;
+
+fn placeholder_substs(db: &dyn HirDatabase, owner: DefWithBodyId) -> Substitution {
+    match owner.as_generic_def_id() {
+        Some(def) => generics(db.upcast(), def).type_params_subst(db),
+        None => Substitution::empty(&Interner),
+    }
+}
+
+/// Non-local control flow out of an expression.
+enum Interrupt {
+    Error(ConstEvalError),
+    Return(ComputedExpr),
+    Break { label: Option<Name>, value: ComputedExpr },
+    Continue { label: Option<Name> },
+}
+
+impl From<ConstEvalError> for Interrupt {
+    fn from(err: ConstEvalError) -> Self {
+        Interrupt::Error(err)
+    }
+}
+
+type EvalResult = Result<ComputedExpr, Interrupt>;
+
+struct Evaluator<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: Arc<Body>,
+    /// The generic arguments of `owner`; types in the body are lowered with bound variables
+    /// which are then substituted with these.
+    substs: Substitution,
+    locals: FxHashMap<PatId, ComputedExpr>,
+    /// Types of the expressions of `owner`, if we are evaluating during its inference.
+    infer: Option<&'a mut dyn FnMut(ExprId) -> Ty>,
+    depth: usize,
+    steps: &'a Cell<usize>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(
+        db: &'a dyn HirDatabase,
+        owner: DefWithBodyId,
+        body: Arc<Body>,
+        substs: Substitution,
+        depth: usize,
+        steps: &'a Cell<usize>,
+    ) -> Self {
+        Evaluator {
+            db,
+            owner,
+            body,
+            substs,
+            locals: FxHashMap::default(),
+            infer: None,
+            depth,
+            steps,
+        }
+    }
+
+    fn eval_root(&mut self, expr: ExprId) -> Result<ComputedExpr, ConstEvalError> {
+        match self.eval_expr(expr) {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            Err(Interrupt::Error(err)) => Err(err),
+            // `break` or `continue` outside of a loop
+            Err(Interrupt::Break { .. } | Interrupt::Continue { .. }) => {
+                Err(ConstEvalError::TypeError)
+            }
+        }
+    }
+
+    fn eval_expr(&mut self, expr: ExprId) -> EvalResult {
+        self.steps.set(self.steps.get() + 1);
+        if self.steps.get() > MAX_STEPS {
+            return Err(ConstEvalError::Loop.into());
+        }
+
+        let body = self.body.clone();
+        match &body[expr] {
+            Expr::Missing => Err(ConstEvalError::IncompleteExpr.into()),
+            Expr::Literal(lit) => self.eval_literal(expr, lit),
+            Expr::Path(p) => self.eval_path(expr, p.mod_path()),
+            Expr::Block { statements, tail, label, .. } => {
+                let res = self.eval_block(statements, *tail);
+                match res {
+                    Err(Interrupt::Break { label: Some(name), value })
+                        if label.is_some_and(|l| body[l].name == name) =>
+                    {
+                        Ok(value)
+                    }
+                    res => res,
+                }
+            }
+            Expr::Unsafe { body } | Expr::Const { body } => self.eval_expr(*body),
+            Expr::MacroStmts { tail } => self.eval_expr(*tail),
+            Expr::If { condition, then_branch, else_branch } => {
+                if self.eval_bool(*condition)? {
+                    self.eval_expr(*then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.eval_expr(*else_branch)
+                } else {
+                    Ok(ComputedExpr::unit())
+                }
+            }
+            Expr::Let { pat, expr } => {
+                let value = self.eval_expr(*expr)?;
+                Ok(ComputedExpr::Literal(Literal::Bool(self.match_pat(*pat, &value)?)))
+            }
+            Expr::Match { expr, arms } => {
+                let value = self.eval_expr(*expr)?;
+                for arm in arms {
+                    if !self.match_pat(arm.pat, &value)? {
+                        continue;
+                    }
+                    if let Some(guard) = arm.guard {
+                        if !self.eval_bool(guard)? {
+                            continue;
+                        }
+                    }
+                    return self.eval_expr(arm.expr);
+                }
+                Err(ConstEvalError::Panic("non-exhaustive match".to_string()).into())
+            }
+            Expr::Loop { body: loop_body, label } => loop {
+                match self.eval_expr(*loop_body) {
+                    Ok(_) => {}
+                    Err(Interrupt::Break { label: target, value })
+                        if self.is_loop_target(*label, &target) =>
+                    {
+                        return Ok(value)
+                    }
+                    Err(Interrupt::Continue { label: target })
+                        if self.is_loop_target(*label, &target) => {}
+                    Err(interrupt) => return Err(interrupt),
+                }
+            },
+            Expr::While { condition, body: loop_body, label } => {
+                while self.eval_bool(*condition)? {
+                    match self.eval_expr(*loop_body) {
+                        Ok(_) => {}
+                        Err(Interrupt::Break { label: target, .. })
+                            if self.is_loop_target(*label, &target) =>
+                        {
+                            break
+                        }
+                        Err(Interrupt::Continue { label: target })
+                            if self.is_loop_target(*label, &target) => {}
+                        Err(interrupt) => return Err(interrupt),
+                    }
+                }
+                Ok(ComputedExpr::unit())
+            }
+            Expr::Break { expr, label } => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(*expr)?,
+                    None => ComputedExpr::unit(),
+                };
+                Err(Interrupt::Break { label: label.clone(), value })
+            }
+            Expr::Continue { label } => Err(Interrupt::Continue { label: label.clone() }),
+            Expr::Return { expr } => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(*expr)?,
+                    None => ComputedExpr::unit(),
+                };
+                Err(Interrupt::Return(value))
+            }
+            Expr::Tuple { exprs } => {
+                let fields =
+                    exprs.iter().map(|&it| self.eval_expr(it)).collect::<Result<Vec<_>, _>>()?;
+                Ok(ComputedExpr::Tuple(fields.into_boxed_slice()))
+            }
+            Expr::Field { expr, name } => match (self.eval_expr(*expr)?, name.as_tuple_index()) {
+                (ComputedExpr::Tuple(fields), Some(idx)) => {
+                    fields.get(idx).cloned().ok_or_else(|| ConstEvalError::TypeError.into())
+                }
+                _ => Err(ConstEvalError::NotSupported("field access").into()),
+            },
+            Expr::Cast { expr, type_ref } => {
+                let value = self.eval_expr(*expr)?;
+                let resolver = resolver_for_expr(self.db.upcast(), self.owner, *expr);
+                let ty = self.lower_ty(&resolver, type_ref);
+                Ok(cast(value, &ty)?)
+            }
+            Expr::UnaryOp { expr, op } => {
+                let value = self.eval_expr(*expr)?;
+                Ok(eval_unary(*op, value)?)
+            }
+            Expr::BinaryOp { lhs, rhs, op } => {
+                let op = op.ok_or(ConstEvalError::IncompleteExpr)?;
+                self.eval_binary(*lhs, *rhs, op)
+            }
+            Expr::Call { callee, args } => self.eval_call(*callee, args),
+            _ => Err(ConstEvalError::NotSupported("expression kind").into()),
+        }
+    }
+
+    fn eval_bool(&mut self, expr: ExprId) -> Result<bool, Interrupt> {
+        match self.eval_expr(expr)? {
+            ComputedExpr::Literal(Literal::Bool(b)) => Ok(b),
+            _ => Err(ConstEvalError::TypeError.into()),
+        }
+    }
+
+    fn eval_literal(&mut self, expr: ExprId, lit: &Literal) -> EvalResult {
+        match lit {
+            Literal::Bool(_) | Literal::ByteString(_) => Ok(ComputedExpr::Literal(lit.clone())),
+            Literal::Int(..) | Literal::Uint(..) => {
+                let (value, kind) =
+                    int_of(lit).ok_or(ConstEvalError::NotSupported("u128 values"))?;
+                let kind = kind.or_else(|| {
+                    let infer = self.infer.as_mut()?;
+                    IntKind::from_ty(&infer(expr))
+                });
+                Ok(ComputedExpr::Literal(int_lit(value, kind, "overflow")?))
+            }
+            // FIXME: body lowering doesn't record the values of these literals yet
+            Literal::String(_) | Literal::Char(_) | Literal::Float(..) => {
+                Err(ConstEvalError::NotSupported("string, char and float literals").into())
+            }
+        }
+    }
+
+    fn eval_path(&mut self, expr: ExprId, path: &ModPath) -> EvalResult {
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+        let value = resolver
+            .resolve_path_in_value_ns_fully(self.db.upcast(), path, self.body.path_context(expr))
+            .ok_or(ConstEvalError::IncompleteExpr)?;
+        match value {
+            ValueNs::LocalBinding(pat) => {
+                self.locals.get(&pat).cloned().ok_or_else(|| ConstEvalError::TypeError.into())
+            }
+            ValueNs::ConstId(it) => Ok(self.db.const_eval(it)?),
+            ValueNs::StaticId(it) => Ok(self.db.const_eval_static(it)?),
+            ValueNs::GenericParam(_) => {
+                Err(ConstEvalError::NotSupported("const generic parameters").into())
+            }
+            _ => Err(ConstEvalError::NotSupported("path").into()),
+        }
+    }
+
+    fn eval_block(
+        &mut self,
+        statements: &[hir_def::expr::Statement],
+        tail: Option<ExprId>,
+    ) -> EvalResult {
+        for stmt in statements {
+            match stmt {
+                hir_def::expr::Statement::Let { pat, initializer, else_branch, .. } => {
+                    if let Some(initializer) = initializer {
+                        let value = self.eval_expr(*initializer)?;
+                        if !self.match_pat(*pat, &value)? {
+                            // The else block of a `let ... else` has to diverge,
+                            // which is an interruption of the evaluation.
+                            if let Some(else_branch) = else_branch {
+                                self.eval_expr(*else_branch)?;
+                            }
+                            return Err(ConstEvalError::TypeError.into());
+                        }
+                    }
+                }
+                hir_def::expr::Statement::Expr { expr, .. } => {
+                    self.eval_expr(*expr)?;
+                }
+            }
+        }
+        match tail {
+            Some(tail) => self.eval_expr(tail),
+            None => Ok(ComputedExpr::unit()),
+        }
+    }
+
+    fn is_loop_target(&self, loop_label: Option<LabelId>, target: &Option<Name>) -> bool {
+        match target {
+            None => true,
+            Some(name) => loop_label.is_some_and(|l| self.body[l].name == *name),
+        }
+    }
+
+    /// Checks whether `value` matches `pat`, binding the variables of the pattern.
+    fn match_pat(&mut self, pat: PatId, value: &ComputedExpr) -> Result<bool, Interrupt> {
+        let body = self.body.clone();
+        match &body[pat] {
+            Pat::Wild => Ok(true),
+            Pat::Bind { subpat, .. } => {
+                if let Some(subpat) = subpat {
+                    if !self.match_pat(*subpat, value)? {
+                        return Ok(false);
+                    }
+                }
+                self.locals.insert(pat, value.clone());
+                Ok(true)
+            }
+            Pat::Lit(expr) => {
+                let lit = self.eval_expr(*expr)?;
+                Ok(values_eq(&lit, value))
+            }
+            Pat::Range { start, end } => {
+                let start = self.eval_expr(*start)?;
+                let end = self.eval_expr(*end)?;
+                match (as_int(&start), as_int(value), as_int(&end)) {
+                    (Some(start), Some(v), Some(end)) => Ok(start <= v && v <= end),
+                    _ => Err(ConstEvalError::TypeError.into()),
+                }
+            }
+            Pat::Or(pats) => {
+                for &pat in pats {
+                    if self.match_pat(pat, value)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Pat::Tuple { args, ellipsis: None } => match value {
+                ComputedExpr::Tuple(fields) if fields.len() == args.len() => {
+                    for (&pat, field) in args.iter().zip(fields.iter()) {
+                        if !self.match_pat(pat, field)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                _ => Err(ConstEvalError::TypeError.into()),
+            },
+            Pat::Missing => Err(ConstEvalError::IncompleteExpr.into()),
+            _ => Err(ConstEvalError::NotSupported("pattern kind").into()),
+        }
+    }
+
+    fn eval_binary(&mut self, lhs: ExprId, rhs: ExprId, op: BinaryOp) -> EvalResult {
+        match op {
+            BinaryOp::LogicOp(op) => {
+                let lhs = self.eval_bool(lhs)?;
+                let value = match op {
+                    LogicOp::And => lhs && self.eval_bool(rhs)?,
+                    LogicOp::Or => lhs || self.eval_bool(rhs)?,
+                };
+                Ok(ComputedExpr::Literal(Literal::Bool(value)))
+            }
+            BinaryOp::ArithOp(op) => {
+                let lhs = self.eval_expr(lhs)?;
+                let rhs = self.eval_expr(rhs)?;
+                Ok(eval_arith(op, lhs, rhs)?)
+            }
+            BinaryOp::CmpOp(op) => {
+                let lhs = self.eval_expr(lhs)?;
+                let rhs = self.eval_expr(rhs)?;
+                Ok(ComputedExpr::Literal(Literal::Bool(eval_cmp(op, &lhs, &rhs)?)))
+            }
+            BinaryOp::Assignment { op } => {
+                let pat = match &self.body[lhs] {
+                    Expr::Path(p) => {
+                        let resolver = resolver_for_expr(self.db.upcast(), self.owner, lhs);
+                        match resolver.resolve_path_in_value_ns_fully(
+                            self.db.upcast(),
+                            p.mod_path(),
+                            self.body.path_context(lhs),
+                        ) {
+                            Some(ValueNs::LocalBinding(pat)) => pat,
+                            _ => return Err(ConstEvalError::NotSupported("assignment").into()),
+                        }
+                    }
+                    _ => return Err(ConstEvalError::NotSupported("assignment").into()),
+                };
+                let mut value = self.eval_expr(rhs)?;
+                if let Some(op) = op {
+                    let current =
+                        self.locals.get(&pat).cloned().ok_or(ConstEvalError::TypeError)?;
+                    value = eval_arith(op, current, value)?;
+                }
+                self.locals.insert(pat, value);
+                Ok(ComputedExpr::unit())
+            }
+        }
+    }
+
+    fn eval_call(&mut self, callee: ExprId, args: &[ExprId]) -> EvalResult {
+        let body = self.body.clone();
+        let path = match &body[callee] {
+            Expr::Path(path) => path,
+            _ => return Err(ConstEvalError::NotSupported("call of a non-path expression").into()),
+        };
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, callee);
+        let func = match resolver.resolve_path_in_value_ns_fully(
+            self.db.upcast(),
+            path.mod_path(),
+            self.body.path_context(callee),
+        ) {
+            Some(ValueNs::FunctionId(it)) => it,
+            Some(_) => return Err(ConstEvalError::NotSupported("call of a non-function").into()),
+            None => return Err(ConstEvalError::IncompleteExpr.into()),
+        };
+        let args = args.iter().map(|&it| self.eval_expr(it)).collect::<Result<Vec<_>, _>>()?;
+        let substs = TyLoweringContext::new(self.db, &resolver)
+            .with_type_param_mode(TypeParamLoweringMode::Variable)
+            .substs_from_path(path, ValueTyDefId::FunctionId(func), true);
+        let substs = make_only_type_binders(self.substs.len(&Interner), substs)
+            .substitute(&Interner, &self.substs);
+        Ok(self.call_function(func, args, substs)?)
+    }
+
+    fn call_function(
+        &mut self,
+        func: FunctionId,
+        args: Vec<ComputedExpr>,
+        substs: Substitution,
+    ) -> Result<ComputedExpr, ConstEvalError> {
+        let data = self.db.function_data(func);
+        // FIXME: check that the ABI of the extern block is `rust-intrinsic`
+        if data.is_in_extern_block() {
+            let krate = self.owner.module(self.db.upcast()).krate();
+            return eval_intrinsic(self.db, krate, &data.name, &substs);
+        }
+        if !data.is_const() {
+            return Err(ConstEvalError::NotSupported("call of a non-const fn"));
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(ConstEvalError::Loop);
+        }
+
+        let body = self.db.body(func.into());
+        if body.params.len() != args.len() {
+            return Err(ConstEvalError::TypeError);
+        }
+        let resolver = func.resolver(self.db.upcast());
+        let mut callee =
+            Evaluator::new(self.db, func.into(), body.clone(), substs, self.depth + 1, self.steps);
+        for ((&pat, arg), type_ref) in body.params.iter().zip(args).zip(data.params.iter()) {
+            let arg = coerce(arg, &callee.lower_ty(&resolver, type_ref))?;
+            if !callee.match_pat(pat, &arg).map_err(|_| ConstEvalError::TypeError)? {
+                return Err(ConstEvalError::TypeError);
+            }
+        }
+        let value = callee.eval_root(body.body_expr)?;
+        coerce(value, &callee.lower_ty(&resolver, &data.ret_type))
+    }
+
+    /// Lowers a type in the body of `self.owner` and substitutes the generic arguments.
+    fn lower_ty(&self, resolver: &Resolver, type_ref: &hir_def::type_ref::TypeRef) -> Ty {
+        let ty = TyLoweringContext::new(self.db, resolver)
+            .with_type_param_mode(TypeParamLoweringMode::Variable)
+            .lower_ty(type_ref);
+        make_only_type_binders(self.substs.len(&Interner), ty).substitute(&Interner, &self.substs)
+    }
+}
+
+fn eval_intrinsic(
+    db: &dyn HirDatabase,
+    krate: CrateId,
+    name: &Name,
+    substs: &Substitution,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let ty = substs
+        .as_slice(&Interner)
+        .first()
+        .and_then(|it| it.ty(&Interner))
+        .ok_or(ConstEvalError::TypeError)?;
+    let layout = match name.to_string().as_str() {
+        "size_of" | "min_align_of" | "pref_align_of" => layout_of_ty(db, ty, krate)
+            .map_err(|_| ConstEvalError::NotSupported("layout of type"))?,
+        _ => return Err(ConstEvalError::NotSupported("intrinsic")),
+    };
+    let value = if name.to_string() == "size_of" { layout.size } else { layout.align };
+    Ok(ComputedExpr::Literal(Literal::Uint(value.into(), Some(BuiltinUint::Usize))))
+}
+
+/// The type of an integer value, as far as it is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IntKind {
+    Int(BuiltinInt),
+    Uint(BuiltinUint),
+}
+
+impl IntKind {
+    fn from_ty(ty: &Ty) -> Option<IntKind> {
+        let kind = match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Int(it)) => IntKind::Int(match it {
+                IntTy::Isize => BuiltinInt::Isize,
+                IntTy::I8 => BuiltinInt::I8,
+                IntTy::I16 => BuiltinInt::I16,
+                IntTy::I32 => BuiltinInt::I32,
+                IntTy::I64 => BuiltinInt::I64,
+                IntTy::I128 => BuiltinInt::I128,
+            }),
+            TyKind::Scalar(Scalar::Uint(it)) => IntKind::Uint(match it {
+                UintTy::Usize => BuiltinUint::Usize,
+                UintTy::U8 => BuiltinUint::U8,
+                UintTy::U16 => BuiltinUint::U16,
+                UintTy::U32 => BuiltinUint::U32,
+                UintTy::U64 => BuiltinUint::U64,
+                UintTy::U128 => BuiltinUint::U128,
+            }),
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    // FIXME: `usize` and `isize` assume a 64-bit target
+    fn bits(self) -> u32 {
+        match self {
+            IntKind::Int(BuiltinInt::I8) | IntKind::Uint(BuiltinUint::U8) => 8,
+            IntKind::Int(BuiltinInt::I16) | IntKind::Uint(BuiltinUint::U16) => 16,
+            IntKind::Int(BuiltinInt::I32) | IntKind::Uint(BuiltinUint::U32) => 32,
+            IntKind::Int(BuiltinInt::I64 | BuiltinInt::Isize)
+            | IntKind::Uint(BuiltinUint::U64 | BuiltinUint::Usize) => 64,
+            IntKind::Int(BuiltinInt::I128) | IntKind::Uint(BuiltinUint::U128) => 128,
+        }
+    }
+
+    fn range(self) -> (i128, i128) {
+        match (self, self.bits()) {
+            (IntKind::Int(_), 128) => (i128::MIN, i128::MAX),
+            (IntKind::Int(_), bits) => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+            // FIXME: values of `u128` above `i128::MAX` are not supported
+            (IntKind::Uint(_), 128) => (0, i128::MAX),
+            (IntKind::Uint(_), bits) => (0, (1 << bits) - 1),
+        }
+    }
+
+    /// Truncates `value` to this integer type, as `as` casts do.
+    fn wrap(self, value: i128) -> i128 {
+        let bits = self.bits();
+        if bits == 128 {
+            return value;
+        }
+        let value = value & ((1 << bits) - 1);
+        match self {
+            IntKind::Int(_) if value >= 1 << (bits - 1) => value - (1 << bits),
+            _ => value,
+        }
+    }
+}
+
+fn int_of(lit: &Literal) -> Option<(i128, Option<IntKind>)> {
+    match lit {
+        Literal::Int(v, kind) => Some((*v, kind.map(IntKind::Int))),
+        Literal::Uint(v, kind) => Some((i128::try_from(*v).ok()?, kind.map(IntKind::Uint))),
+        _ => None,
+    }
+}
+
+fn as_int(value: &ComputedExpr) -> Option<i128> {
+    match value {
+        ComputedExpr::Literal(lit) => int_of(lit).map(|(v, _)| v),
+        _ => None,
+    }
+}
+
+fn int_lit(value: i128, kind: Option<IntKind>, op: &str) -> Result<Literal, ConstEvalError> {
+    match kind {
+        Some(kind) => {
+            let (min, max) = kind.range();
+            if value < min || value > max {
+                return Err(ConstEvalError::Panic(format!("attempt to {} with overflow", op)));
+            }
+            Ok(match kind {
+                IntKind::Int(it) => Literal::Int(value, Some(it)),
+                IntKind::Uint(it) => Literal::Uint(value as u128, Some(it)),
+            })
+        }
+        None if value >= 0 => Ok(Literal::Uint(value as u128, None)),
+        None => Ok(Literal::Int(value, None)),
+    }
+}
+
+fn values_eq(lhs: &ComputedExpr, rhs: &ComputedExpr) -> bool {
+    match (lhs, rhs) {
+        (ComputedExpr::Tuple(lhs), ComputedExpr::Tuple(rhs)) => {
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter()).all(|(l, r)| values_eq(l, r))
+        }
+        _ => match (as_int(lhs), as_int(rhs)) {
+            (Some(l), Some(r)) => l == r,
+            _ => lhs == rhs,
+        },
+    }
+}
+
+/// Converts the result of an evaluation to the expected type, e.g. giving an unsuffixed integer
+/// literal its type.
+fn coerce(value: ComputedExpr, ty: &Ty) -> Result<ComputedExpr, ConstEvalError> {
+    match (&value, IntKind::from_ty(ty)) {
+        (ComputedExpr::Literal(lit), Some(kind)) => match int_of(lit) {
+            Some((v, None)) => Ok(ComputedExpr::Literal(int_lit(v, Some(kind), "convert")?)),
+            Some((_, Some(k))) if k == kind => Ok(value),
+            _ => Err(ConstEvalError::TypeError),
+        },
+        _ => Ok(value),
+    }
+}
+
+fn cast(value: ComputedExpr, ty: &Ty) -> Result<ComputedExpr, ConstEvalError> {
+    let kind = match IntKind::from_ty(ty) {
+        Some(kind) => kind,
+        None => return Err(ConstEvalError::NotSupported("cast to non-integer type")),
+    };
+    let v = match &value {
+        ComputedExpr::Literal(Literal::Bool(b)) => *b as i128,
+        ComputedExpr::Literal(lit) => {
+            int_of(lit).ok_or(ConstEvalError::NotSupported("cast of non-integer value"))?.0
+        }
+        ComputedExpr::Tuple(_) => return Err(ConstEvalError::TypeError),
+    };
+    if v < 0 && kind == IntKind::Uint(BuiltinUint::U128) {
+        return Err(ConstEvalError::NotSupported("u128 values"));
+    }
+    Ok(ComputedExpr::Literal(int_lit(kind.wrap(v), Some(kind), "cast")?))
+}
+
+fn eval_unary(op: UnaryOp, value: ComputedExpr) -> Result<ComputedExpr, ConstEvalError> {
+    let lit = match value {
+        ComputedExpr::Literal(lit) => lit,
+        ComputedExpr::Tuple(_) => return Err(ConstEvalError::TypeError),
+    };
+    match (op, lit) {
+        (UnaryOp::Not, Literal::Bool(b)) => Ok(ComputedExpr::Literal(Literal::Bool(!b))),
+        (UnaryOp::Not, lit) => {
+            let (v, kind) = int_of(&lit).ok_or(ConstEvalError::TypeError)?;
+            let kind =
+                kind.ok_or(ConstEvalError::NotSupported("`!` on an integer of unknown type"))?;
+            Ok(ComputedExpr::Literal(int_lit(kind.wrap(!v), Some(kind), "negate")?))
+        }
+        (UnaryOp::Neg, lit) => {
+            let (v, kind) = int_of(&lit).ok_or(ConstEvalError::TypeError)?;
+            if let Some(IntKind::Uint(_)) = kind {
+                return Err(ConstEvalError::TypeError);
+            }
+            let v = v.checked_neg().ok_or_else(|| overflow("negate"))?;
+            Ok(ComputedExpr::Literal(int_lit(v, kind, "negate")?))
+        }
+        (UnaryOp::Deref, _) => Err(ConstEvalError::NotSupported("deref")),
+    }
+}
+
+fn overflow(op: &str) -> ConstEvalError {
+    ConstEvalError::Panic(format!("attempt to {} with overflow", op))
+}
+
+fn eval_arith(
+    op: ArithOp,
+    lhs: ComputedExpr,
+    rhs: ComputedExpr,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let (lhs, rhs) = match (lhs, rhs) {
+        (ComputedExpr::Literal(lhs), ComputedExpr::Literal(rhs)) => (lhs, rhs),
+        _ => return Err(ConstEvalError::TypeError),
+    };
+    if let (Literal::Bool(l), Literal::Bool(r)) = (&lhs, &rhs) {
+        let value = match op {
+            ArithOp::BitAnd => l & r,
+            ArithOp::BitOr => l | r,
+            ArithOp::BitXor => l ^ r,
+            _ => return Err(ConstEvalError::TypeError),
+        };
+        return Ok(ComputedExpr::Literal(Literal::Bool(value)));
+    }
+
+    let (l, lkind) = int_of(&lhs).ok_or(ConstEvalError::TypeError)?;
+    let (r, rkind) = int_of(&rhs).ok_or(ConstEvalError::TypeError)?;
+    let kind = match op {
+        ArithOp::Shl | ArithOp::Shr => lkind,
+        _ => match (lkind, rkind) {
+            (Some(l), Some(r)) if l != r => return Err(ConstEvalError::TypeError),
+            (l, r) => l.or(r),
+        },
+    };
+    let (value, op_name) = match op {
+        ArithOp::Add => (l.checked_add(r), "add"),
+        ArithOp::Sub => (l.checked_sub(r), "subtract"),
+        ArithOp::Mul => (l.checked_mul(r), "multiply"),
+        ArithOp::Div => {
+            if r == 0 {
+                return Err(ConstEvalError::Panic("attempt to divide by zero".to_string()));
+            }
+            (l.checked_div(r), "divide")
+        }
+        ArithOp::Rem => {
+            if r == 0 {
+                return Err(ConstEvalError::Panic(
+                    "attempt to calculate the remainder with a divisor of zero".to_string(),
+                ));
+            }
+            (l.checked_rem(r), "calculate the remainder")
+        }
+        ArithOp::Shl | ArithOp::Shr => {
+            let bits = kind.map_or(128, IntKind::bits);
+            let name = if op == ArithOp::Shl { "shift left" } else { "shift right" };
+            let shift = match u32::try_from(r) {
+                Ok(shift) if shift < bits => shift,
+                _ => return Err(overflow(name)),
+            };
+            let value = if op == ArithOp::Shl { l << shift } else { l >> shift };
+            let value = kind.map_or(value, |kind| kind.wrap(value));
+            (Some(value), name)
+        }
+        ArithOp::BitAnd => (Some(l & r), "and"),
+        ArithOp::BitOr => (Some(l | r), "or"),
+        ArithOp::BitXor => (Some(l ^ r), "xor"),
+    };
+    let value = value.ok_or_else(|| overflow(op_name))?;
+    Ok(ComputedExpr::Literal(int_lit(value, kind, op_name)?))
+}
+
+fn eval_cmp(op: CmpOp, lhs: &ComputedExpr, rhs: &ComputedExpr) -> Result<bool, ConstEvalError> {
+    match op {
+        CmpOp::Eq { negated } => Ok(values_eq(lhs, rhs) != negated),
+        CmpOp::Ord { ordering, strict } => {
+            let (l, r) = match (lhs, rhs) {
+                (
+                    ComputedExpr::Literal(Literal::Bool(l)),
+                    ComputedExpr::Literal(Literal::Bool(r)),
+                ) => (*l as i128, *r as i128),
+                _ => (
+                    as_int(lhs).ok_or(ConstEvalError::TypeError)?,
+                    as_int(rhs).ok_or(ConstEvalError::TypeError)?,
+                ),
+            };
+            Ok(match (ordering, strict) {
+                (Ordering::Less, true) => l < r,
+                (Ordering::Less, false) => l <= r,
+                (Ordering::Greater, true) => l > r,
+                (Ordering::Greater, false) => l >= r,
+            })
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Type inference-based diagnostics.
 mod expr;
+mod liveness;
 mod match_check;
 mod unsafe_check;
 mod decl_check;
@@ -13,10 +14,14 @@ use syntax::{ast, AstPtr};
 
 use crate::db::HirDatabase;
 
+pub(crate) use crate::diagnostics::expr::unreachable_code_query;
+
 pub use crate::diagnostics::{
     expr::{
         record_literal_missing_fields, record_pattern_missing_fields, BodyValidationDiagnostic,
+        UnreachableCode,
     },
+    liveness::LivenessDiagnostic,
     unsafe_check::missing_unsafe,
 };
 
@@ -15,9 +15,11 @@ use crate::{
     db::HirDatabase,
     diagnostics::match_check::{
         self,
-        usefulness::{compute_match_usefulness, expand_pattern, MatchCheckCtx, PatternArena},
+        usefulness::{
+            compute_match_usefulness, expand_pattern, MatchCheckCtx, PatternArena, Reachability,
+        },
     },
-    AdtId, InferenceResult, Interner, TyExt, TyKind,
+    AdtId, InferenceDiagnostic, InferenceResult, Interner, TyExt, TyKind,
 };
 
 pub(crate) use hir_def::{
@@ -50,6 +52,9 @@ pub enum BodyValidationDiagnostic {
     MissingMatchArms {
         match_expr: ExprId,
     },
+    UnreachableMatchArm {
+        arm_pat: PatId,
+    },
 }
 
 impl BodyValidationDiagnostic {
@@ -62,6 +67,46 @@ impl BodyValidationDiagnostic {
     }
 }
 
+/// The code of a body which can never be executed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UnreachableCode {
+    /// The first unreachable statement or tail expression of a block, the
+    /// rest of the block is unreachable as well.
+    pub stmts: Vec<Either<ExprId, PatId>>,
+    /// The patterns of match arms which can never be taken.
+    pub match_arms: Vec<PatId>,
+}
+
+/// Unlike `BodyValidationDiagnostic::collect`, this only checks `match`
+/// expressions, so that highlighting doesn't have to run all of the
+/// diagnostics.
+pub(crate) fn unreachable_code_query(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+) -> Arc<UnreachableCode> {
+    let _p = profile::span("unreachable_code_query");
+    let infer = db.infer(owner);
+    let stmts = infer
+        .diagnostics
+        .iter()
+        .filter_map(|it| match it {
+            InferenceDiagnostic::UnreachableCode { node } => Some(*node),
+            _ => None,
+        })
+        .collect();
+    let mut validator = ExprValidator::new(owner, infer);
+    validator.validate_matches(db);
+    let match_arms = validator
+        .diagnostics
+        .into_iter()
+        .filter_map(|it| match it {
+            BodyValidationDiagnostic::UnreachableMatchArm { arm_pat } => Some(arm_pat),
+            _ => None,
+        })
+        .collect();
+    Arc::new(UnreachableCode { stmts, match_arms })
+}
+
 struct ExprValidator {
     owner: DefWithBodyId,
     infer: Arc<InferenceResult>,
@@ -120,6 +165,15 @@ impl ExprValidator {
         }
     }
 
+    fn validate_matches(&mut self, db: &dyn HirDatabase) {
+        let body = db.body(self.owner);
+        for (id, expr) in body.exprs.iter() {
+            if let Expr::Match { expr, arms } = expr {
+                self.validate_match(id, *expr, arms, db, self.infer.clone());
+            }
+        }
+    }
+
     fn check_for_filter_map_next(&mut self, db: &dyn HirDatabase) {
         // Find the FunctionIds for Iterator::filter_map and Iterator::next
         let iterator_path = path![core::iter::Iterator];
@@ -327,8 +381,16 @@ impl ExprValidator {
         };
         let report = compute_match_usefulness(&cx, &m_arms);
 
-        // FIXME Report unreacheble arms
-        // https://github.com/rust-lang/rust/blob/25c15cdbe/compiler/rustc_mir_build/src/thir/pattern/check_match.rs#L200-L201
+        for ((_, reachability), arm) in report.arm_usefulness.iter().zip(arms) {
+            // The arms of desugared `if let` and `while let` expressions have
+            // no syntax; an irrefutable `if let` is not an unreachable arm.
+            if matches!(reachability, Reachability::Unreachable)
+                && source_map.pat_syntax(arm.pat).is_ok()
+            {
+                self.diagnostics
+                    .push(BodyValidationDiagnostic::UnreachableMatchArm { arm_pat: arm.pat });
+            }
+        }
 
         let witnesses = report.non_exhaustiveness_witnesses;
         // FIXME Report witnesses
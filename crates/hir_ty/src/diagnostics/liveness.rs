@@ -0,0 +1,611 @@
+//! Liveness and initialization analysis of bodies.
+//!
+//! The body is walked once in evaluation order, tracking which bindings may
+//! be initialized and which assignments may still be read at each point.
+//! Branches are walked separately and their states joined, loops are walked
+//! until their entry state stops changing. The results are used to report
+//! unused variables, needless `mut` annotations and assignments whose value is
+//! never read.
+
+use std::mem;
+
+use hir_def::{
+    body::Body,
+    expr::{BinaryOp, BindingAnnotation, Expr, ExprId, LabelId, Pat, PatId, Statement, UnaryOp},
+    path::Path,
+    resolver::{resolver_for_expr, ResolveValueResult, ValueNs},
+    AttrDefId, DefWithBodyId, Lookup,
+};
+use hir_expand::name::{name, Name};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    db::HirDatabase, Adjust, AutoBorrow, InferenceResult, Interner, Mutability, TyExt, TyKind,
+};
+
+mod allow {
+    pub(super) const UNUSED: &str = "unused";
+    pub(super) const UNUSED_VARIABLES: &str = "unused_variables";
+    pub(super) const UNUSED_MUT: &str = "unused_mut";
+    pub(super) const UNUSED_ASSIGNMENTS: &str = "unused_assignments";
+}
+
+pub enum LivenessDiagnostic {
+    /// A binding which is never read.
+    UnusedVariable { pat: PatId },
+    /// A `mut` binding which is never mutated.
+    UnusedMut { pat: PatId },
+    /// An assignment to `pat` whose value is never read.
+    UnusedAssignment { expr: ExprId, pat: PatId },
+}
+
+impl LivenessDiagnostic {
+    pub fn collect(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<LivenessDiagnostic> {
+        let _p = profile::span("LivenessDiagnostic::collect");
+        let body = db.body(owner);
+        if let Expr::Missing = body[body.body_expr] {
+            // A declaration without a body, like a required trait method.
+            return Vec::new();
+        }
+        let infer = db.infer(owner);
+
+        let mut ctx = LivenessCtx::new(db, owner, &body, &infer);
+        let mut state = State::reachable();
+        for &param in &body.params {
+            ctx.bind_pat(&mut state, param, true, None);
+        }
+        ctx.walk_expr(&mut state, body.body_expr);
+
+        let mut res = ctx.finish();
+        let owner = match owner {
+            DefWithBodyId::FunctionId(it) => it.into(),
+            DefWithBodyId::StaticId(it) => it.into(),
+            DefWithBodyId::ConstId(it) => it.into(),
+        };
+        let unused_variables_allowed = lint_allowed(db, owner, allow::UNUSED_VARIABLES);
+        let unused_mut_allowed = lint_allowed(db, owner, allow::UNUSED_MUT);
+        let unused_assignments_allowed = lint_allowed(db, owner, allow::UNUSED_ASSIGNMENTS);
+        res.retain(|diagnostic| match diagnostic {
+            LivenessDiagnostic::UnusedVariable { .. } => !unused_variables_allowed,
+            LivenessDiagnostic::UnusedMut { .. } => !unused_mut_allowed,
+            LivenessDiagnostic::UnusedAssignment { .. } => !unused_assignments_allowed,
+        });
+        res
+    }
+}
+
+/// Checks whether `lint`, or the whole `unused` group, is allowed with `#[allow]` on the item or
+/// on one of the items and modules containing it.
+fn lint_allowed(db: &dyn HirDatabase, id: AttrDefId, lint: &str) -> bool {
+    let attrs = db.attrs(id);
+    let is_allowed = attrs.by_key("allow").tt_values().any(|tt| {
+        tt.token_trees.iter().any(|tt| match tt {
+            tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => {
+                ident.text == lint || ident.text == allow::UNUSED
+            }
+            _ => false,
+        })
+    });
+
+    is_allowed
+        // go upwards one step or give up
+        || match id {
+            AttrDefId::ModuleId(m) => m.containing_module(db.upcast()).map(|v| v.into()),
+            AttrDefId::FunctionId(f) => Some(f.lookup(db.upcast()).container.into()),
+            AttrDefId::StaticId(sid) => Some(sid.lookup(db.upcast()).container.into()),
+            AttrDefId::ConstId(cid) => Some(cid.lookup(db.upcast()).container.into()),
+            AttrDefId::TraitId(tid) => Some(tid.lookup(db.upcast()).container.into()),
+            AttrDefId::ImplId(iid) => Some(iid.lookup(db.upcast()).container.into()),
+            _ => None,
+        }
+        .map(|parent| lint_allowed(db, parent, lint))
+        .unwrap_or(false)
+}
+
+/// The facts known at some point of the body.
+#[derive(Clone, Default, PartialEq, Eq)]
+struct State {
+    reachable: bool,
+    /// Bindings which are initialized on some path to this point.
+    maybe_init: FxHashSet<PatId>,
+    /// Assignments which have not been read yet on some path to this point.
+    pending: FxHashMap<PatId, FxHashSet<ExprId>>,
+}
+
+impl State {
+    fn reachable() -> State {
+        State { reachable: true, ..State::default() }
+    }
+
+    fn unreachable() -> State {
+        State::default()
+    }
+
+    fn join(&mut self, other: State) {
+        if !other.reachable {
+            return;
+        }
+        if !self.reachable {
+            *self = other;
+            return;
+        }
+        self.maybe_init.extend(other.maybe_init);
+        for (pat, exprs) in other.pending {
+            self.pending.entry(pat).or_default().extend(exprs);
+        }
+    }
+}
+
+/// A loop or labeled block which can be the target of `break` or `continue`.
+struct Frame {
+    label: Option<Name>,
+    is_loop: bool,
+    breaks: State,
+    continues: State,
+}
+
+struct LivenessCtx<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    /// Maps each binding to the first binding with the same name in its
+    /// pattern, so that the bindings of `A(x) | B(x)` are one variable.
+    canonical: FxHashMap<PatId, PatId>,
+    frames: Vec<Frame>,
+    closure_depth: usize,
+    used: FxHashSet<PatId>,
+    mutated: FxHashSet<PatId>,
+    captured: FxHashSet<PatId>,
+    assignments: Vec<(ExprId, PatId)>,
+    read_assignments: FxHashSet<ExprId>,
+}
+
+impl<'a> LivenessCtx<'a> {
+    fn new(
+        db: &'a dyn HirDatabase,
+        owner: DefWithBodyId,
+        body: &'a Body,
+        infer: &'a InferenceResult,
+    ) -> LivenessCtx<'a> {
+        LivenessCtx {
+            db,
+            owner,
+            body,
+            infer,
+            canonical: canonical_bindings(body),
+            frames: Vec::new(),
+            closure_depth: 0,
+            used: FxHashSet::default(),
+            mutated: FxHashSet::default(),
+            captured: FxHashSet::default(),
+            assignments: Vec::new(),
+            read_assignments: FxHashSet::default(),
+        }
+    }
+
+    fn finish(self) -> Vec<LivenessDiagnostic> {
+        let mut res = Vec::new();
+        let is_ignored = |name: &Name| {
+            let text = name.to_string();
+            // Capitalized bindings are most likely unresolved unit structs,
+            // variants or constants, like `None` without a sysroot.
+            *name == Name::missing()
+                || *name == name![self]
+                || text.starts_with('_')
+                || text.starts_with(char::is_uppercase)
+        };
+        for (pat, data) in self.body.pats.iter() {
+            let (mode, name) = match data {
+                Pat::Bind { mode, name, .. } => (mode, name),
+                _ => continue,
+            };
+            if self.canonical.get(&pat) != Some(&pat) {
+                continue;
+            }
+            if !self.used.contains(&pat) && !is_ignored(name) {
+                res.push(LivenessDiagnostic::UnusedVariable { pat });
+            }
+            if *mode == BindingAnnotation::Mutable && !self.mutated.contains(&pat) {
+                res.push(LivenessDiagnostic::UnusedMut { pat });
+            }
+        }
+        for &(expr, pat) in &self.assignments {
+            // Never read variables are already reported as unused, and for
+            // captured ones we can't tell when the closure reads them.
+            if self.read_assignments.contains(&expr)
+                || !self.used.contains(&pat)
+                || self.captured.contains(&pat)
+            {
+                continue;
+            }
+            match &self.body[pat] {
+                Pat::Bind { name, .. } if !is_ignored(name) => {
+                    res.push(LivenessDiagnostic::UnusedAssignment { expr, pat })
+                }
+                _ => {}
+            }
+        }
+        res
+    }
+
+    fn walk_expr(&mut self, state: &mut State, expr: ExprId) {
+        let body = self.body;
+        match &body[expr] {
+            Expr::Path(path) => {
+                if let Some(pat) = self.resolve_local(expr, path) {
+                    self.read(state, pat);
+                }
+            }
+            &Expr::If { condition, then_branch, else_branch } => {
+                self.walk_expr(state, condition);
+                let mut else_state = state.clone();
+                self.walk_expr(state, then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.walk_expr(&mut else_state, else_branch);
+                }
+                state.join(else_state);
+            }
+            &Expr::Let { pat, expr: scrutinee } => {
+                self.walk_expr(state, scrutinee);
+                self.bind_pat(state, pat, true, Some(scrutinee));
+            }
+            Expr::Block { statements, tail, label, .. } => {
+                if let Some(label) = label {
+                    self.frames.push(Frame::new(Some(body[*label].name.clone()), false));
+                }
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { pat, initializer, else_branch, .. } => {
+                            if let Some(initializer) = initializer {
+                                self.walk_expr(state, *initializer);
+                            }
+                            if let Some(else_branch) = else_branch {
+                                let mut else_state = state.clone();
+                                self.walk_expr(&mut else_state, *else_branch);
+                                state.join(else_state);
+                            }
+                            self.bind_pat(state, *pat, initializer.is_some(), *initializer);
+                        }
+                        Statement::Expr { expr, .. } => self.walk_expr(state, *expr),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.walk_expr(state, *tail);
+                }
+                if label.is_some() {
+                    let frame = self.frames.pop().unwrap();
+                    state.join(frame.breaks);
+                }
+            }
+            &Expr::Loop { body: loop_body, label } => {
+                self.walk_loop(state, label, |this, state| {
+                    this.walk_expr(state, loop_body);
+                    State::unreachable()
+                });
+            }
+            &Expr::While { condition, body: loop_body, label } => {
+                self.walk_loop(state, label, |this, state| {
+                    this.walk_expr(state, condition);
+                    let exit = state.clone();
+                    this.walk_expr(state, loop_body);
+                    exit
+                });
+            }
+            &Expr::For { iterable, pat, body: loop_body, label } => {
+                self.walk_expr(state, iterable);
+                self.walk_loop(state, label, |this, state| {
+                    let exit = state.clone();
+                    this.bind_pat(state, pat, true, None);
+                    this.walk_expr(state, loop_body);
+                    exit
+                });
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                self.walk_expr(state, *scrutinee);
+                let mut exit = State::unreachable();
+                for arm in arms {
+                    let mut arm_state = state.clone();
+                    self.bind_pat(&mut arm_state, arm.pat, true, Some(*scrutinee));
+                    if let Some(guard) = arm.guard {
+                        self.walk_expr(&mut arm_state, guard);
+                    }
+                    self.walk_expr(&mut arm_state, arm.expr);
+                    exit.join(arm_state);
+                }
+                *state = exit;
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::LogicOp(_)) } => {
+                self.walk_expr(state, lhs);
+                let short_circuit = state.clone();
+                self.walk_expr(state, rhs);
+                state.join(short_circuit);
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { op }) } => {
+                self.walk_expr(state, rhs);
+                self.assign(state, expr, lhs, op.is_some());
+            }
+            &Expr::Ref { expr: inner, mutability, .. } => {
+                self.walk_expr(state, inner);
+                if mutability == hir_def::type_ref::Mutability::Mut {
+                    self.mutate_place(inner);
+                }
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                self.walk_expr(state, *receiver);
+                for &arg in args {
+                    self.walk_expr(state, arg);
+                }
+                if self.receiver_is_mutated(expr, *receiver) {
+                    self.mutate_place(*receiver);
+                }
+            }
+            Expr::Call { callee, args } => {
+                self.walk_expr(state, *callee);
+                for &arg in args {
+                    self.walk_expr(state, arg);
+                }
+                // Calling an `FnMut` closure needs a mutable place.
+                match self.infer[*callee].kind(&Interner) {
+                    TyKind::FnDef(..) | TyKind::Function(_) => {}
+                    _ => self.mutate_place(*callee),
+                }
+            }
+            Expr::Lambda { args, body: closure_body, .. } => {
+                let mut closure_state = State { pending: FxHashMap::default(), ..state.clone() };
+                self.walk_closure(|this| {
+                    for &arg in args {
+                        this.bind_pat(&mut closure_state, arg, true, None);
+                    }
+                    this.walk_expr(&mut closure_state, *closure_body);
+                });
+            }
+            &Expr::Async { body: async_body } => {
+                let mut async_state = State { pending: FxHashMap::default(), ..state.clone() };
+                self.walk_closure(|this| this.walk_expr(&mut async_state, async_body));
+            }
+            Expr::Return { expr: value } => {
+                if let Some(value) = value {
+                    self.walk_expr(state, *value);
+                }
+                *state = State::unreachable();
+            }
+            Expr::Break { expr: value, label } => {
+                if let Some(value) = value {
+                    self.walk_expr(state, *value);
+                }
+                let exiting = mem::replace(state, State::unreachable());
+                if let Some(frame) = self.find_frame(label.as_ref(), false) {
+                    frame.breaks.join(exiting);
+                }
+            }
+            Expr::Continue { label } => {
+                let exiting = mem::replace(state, State::unreachable());
+                if let Some(frame) = self.find_frame(label.as_ref(), true) {
+                    frame.continues.join(exiting);
+                }
+            }
+            it => it.walk_child_exprs(|child| self.walk_expr(state, child)),
+        }
+
+        if self.infer[expr].is_never() {
+            *state = State::unreachable();
+        }
+    }
+
+    /// Walks a loop whose single iteration is walked by `iteration`, which
+    /// returns the state in which the loop exits without a `break`.
+    fn walk_loop(
+        &mut self,
+        state: &mut State,
+        label: Option<LabelId>,
+        mut iteration: impl FnMut(&mut Self, &mut State) -> State,
+    ) {
+        let label = label.map(|label| self.body[label].name.clone());
+        let mut entry = state.clone();
+        loop {
+            self.frames.push(Frame::new(label.clone(), true));
+            let mut iteration_state = entry.clone();
+            let mut exit = iteration(self, &mut iteration_state);
+            let frame = self.frames.pop().unwrap();
+            exit.join(frame.breaks);
+
+            let mut next_entry = entry.clone();
+            next_entry.join(iteration_state);
+            next_entry.join(frame.continues);
+            if next_entry == entry {
+                *state = exit;
+                return;
+            }
+            entry = next_entry;
+        }
+    }
+
+    /// Walks the body of a closure or an async block. It may run any number
+    /// of times later, so its state doesn't flow back into the enclosing body.
+    fn walk_closure(&mut self, f: impl FnOnce(&mut Self)) {
+        let frames = mem::take(&mut self.frames);
+        self.closure_depth += 1;
+        f(self);
+        self.closure_depth -= 1;
+        self.frames = frames;
+    }
+
+    fn find_frame(&mut self, label: Option<&Name>, is_continue: bool) -> Option<&mut Frame> {
+        self.frames.iter_mut().rev().find(|frame| match label {
+            Some(label) => frame.label.as_ref() == Some(label) && (frame.is_loop || !is_continue),
+            None => frame.is_loop,
+        })
+    }
+
+    /// Introduces the bindings of `pat`. `scrutinee` is the place the pattern
+    /// is matched against, which is mutably borrowed by `ref mut` bindings.
+    fn bind_pat(
+        &mut self,
+        state: &mut State,
+        pat: PatId,
+        initialized: bool,
+        scrutinee: Option<ExprId>,
+    ) {
+        let body = self.body;
+        if let Pat::Bind { mode, .. } = &body[pat] {
+            let binding = self.canonical(pat);
+            state.pending.remove(&binding);
+            if initialized {
+                state.maybe_init.insert(binding);
+            } else {
+                state.maybe_init.remove(&binding);
+            }
+            if *mode == BindingAnnotation::RefMut {
+                if let Some(scrutinee) = scrutinee {
+                    self.mutate_place(scrutinee);
+                }
+            }
+        }
+        body[pat].walk_child_pats(|child| self.bind_pat(state, child, initialized, scrutinee));
+    }
+
+    fn assign(&mut self, state: &mut State, assignment: ExprId, lhs: ExprId, compound: bool) {
+        let body = self.body;
+        let binding = match &body[lhs] {
+            Expr::Path(path) => self.resolve_local(lhs, path),
+            _ => None,
+        };
+        let binding = match binding {
+            Some(it) => it,
+            None => {
+                // Assignment to a field, an index or through a deref: this
+                // reads the base and mutates it.
+                self.walk_expr(state, lhs);
+                self.mutate_place(lhs);
+                return;
+            }
+        };
+        if compound {
+            self.read(state, binding);
+        }
+        // Assigning to a variable which can't be initialized yet is its
+        // initialization and doesn't require `mut`.
+        if compound || state.maybe_init.contains(&binding) {
+            self.mutated.insert(binding);
+        }
+        state.maybe_init.insert(binding);
+        if state.reachable {
+            if !self.assignments.iter().any(|&(it, _)| it == assignment) {
+                self.assignments.push((assignment, binding));
+            }
+            state.pending.insert(binding, std::iter::once(assignment).collect());
+        }
+    }
+
+    fn read(&mut self, state: &mut State, binding: PatId) {
+        self.used.insert(binding);
+        if let Some(assignments) = state.pending.remove(&binding) {
+            self.read_assignments.extend(assignments);
+        }
+    }
+
+    /// Records a mutation of the place `expr`, which mutates the binding the
+    /// place is based on unless it goes through a reference or a pointer.
+    fn mutate_place(&mut self, expr: ExprId) {
+        let body = self.body;
+        let base = match &body[expr] {
+            Expr::Path(path) => {
+                if let Some(binding) = self.resolve_local(expr, path) {
+                    self.mutated.insert(binding);
+                }
+                return;
+            }
+            Expr::Field { expr: base, .. } => *base,
+            Expr::Index { base, .. } => *base,
+            Expr::UnaryOp { expr: base, op: UnaryOp::Deref } => *base,
+            _ => return,
+        };
+        match self.infer[base].kind(&Interner) {
+            TyKind::Ref(..) | TyKind::Raw(..) => {}
+            _ => self.mutate_place(base),
+        }
+    }
+
+    /// Whether the receiver of `method_call` is mutably borrowed by the
+    /// method call's autoref, or by an overloaded `DerefMut`.
+    fn receiver_is_mutated(&self, method_call: ExprId, receiver: ExprId) -> bool {
+        if self.infer.method_resolution(method_call).is_none() {
+            // We don't know how the receiver is used, so be conservative.
+            return true;
+        }
+        let mut ty = &self.infer[receiver];
+        for adjustment in self.infer.expr_adjustments(receiver) {
+            match adjustment.kind {
+                Adjust::Deref(None) => {
+                    if let TyKind::Ref(..) | TyKind::Raw(..) = ty.kind(&Interner) {
+                        return false;
+                    }
+                }
+                Adjust::Deref(Some(deref)) => return deref.0 == Mutability::Mut,
+                Adjust::Borrow(AutoBorrow::Ref(m)) | Adjust::Borrow(AutoBorrow::RawPtr(m)) => {
+                    return m == Mutability::Mut
+                }
+                Adjust::NeverToAny | Adjust::Pointer(_) => {}
+            }
+            ty = &adjustment.target;
+        }
+        false
+    }
+
+    fn resolve_local(&mut self, expr: ExprId, path: &Path) -> Option<PatId> {
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+        let syntax_context = self.body.path_context(expr);
+        let pat = match resolver.resolve_path_in_value_ns(
+            self.db.upcast(),
+            path.mod_path(),
+            syntax_context,
+        ) {
+            Some(ResolveValueResult::ValueNs(ValueNs::LocalBinding(pat))) => pat,
+            _ => return None,
+        };
+        let binding = self.canonical(pat);
+        if self.closure_depth > 0 {
+            self.captured.insert(binding);
+        }
+        Some(binding)
+    }
+
+    fn canonical(&self, pat: PatId) -> PatId {
+        self.canonical.get(&pat).copied().unwrap_or(pat)
+    }
+}
+
+impl Frame {
+    fn new(label: Option<Name>, is_loop: bool) -> Frame {
+        Frame { label, is_loop, breaks: State::unreachable(), continues: State::unreachable() }
+    }
+}
+
+fn canonical_bindings(body: &Body) -> FxHashMap<PatId, PatId> {
+    let mut has_parent = FxHashSet::default();
+    for (_, pat) in body.pats.iter() {
+        pat.walk_child_pats(|child| {
+            has_parent.insert(child);
+        });
+    }
+
+    let mut res = FxHashMap::default();
+    for (root, _) in body.pats.iter() {
+        if has_parent.contains(&root) {
+            continue;
+        }
+        let mut first_by_name: FxHashMap<Name, PatId> = FxHashMap::default();
+        let mut stack = vec![root];
+        while let Some(pat) = stack.pop() {
+            if let Pat::Bind { name, .. } = &body[pat] {
+                let first = *first_by_name.entry(name.clone()).or_insert(pat);
+                res.insert(pat, first);
+            }
+            let mut children = Vec::new();
+            body[pat].walk_child_pats(|child| children.push(child));
+            stack.extend(children.into_iter().rev());
+        }
+    }
+    res
+}
@@ -1115,7 +1115,7 @@ pub(crate) enum Reachability {
 /// The output of checking a match for exhaustiveness and arm reachability.
 pub(crate) struct UsefulnessReport {
     /// For each arm of the input, whether that arm is reachable after the arms above it.
-    pub(crate) _arm_usefulness: Vec<(MatchArm, Reachability)>,
+    pub(crate) arm_usefulness: Vec<(MatchArm, Reachability)>,
     /// If the match is exhaustive, this is empty. If not, this contains witnesses for the lack of
     /// exhaustiveness.
     pub(crate) non_exhaustiveness_witnesses: Vec<Pat>,
@@ -1159,7 +1159,7 @@ pub(crate) fn compute_match_usefulness(
         WithWitnesses(pats) => pats.into_iter().map(Witness::single_pattern).collect(),
         NoWitnesses(_) => panic!("bug"),
     };
-    UsefulnessReport { _arm_usefulness: arm_usefulness, non_exhaustiveness_witnesses }
+    UsefulnessReport { arm_usefulness, non_exhaustiveness_witnesses }
 }
 
 pub(crate) type PatternArena = Arena<Pat>;
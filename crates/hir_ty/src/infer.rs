@@ -16,7 +16,7 @@
 use std::ops::Index;
 use std::sync::Arc;
 
-use chalk_ir::{cast::Cast, DebruijnIndex, Mutability};
+use chalk_ir::{cast::Cast, DebruijnIndex, Mutability, Safety};
 use hir_def::{
     body::Body,
     data::{ConstData, FunctionData, StaticData},
@@ -28,7 +28,8 @@ use hir_def::{
     AdtId, AssocItemId, DefWithBodyId, EnumVariantId, FieldId, FunctionId, HasModule, Lookup,
     TraitId, TypeAliasId, VariantId,
 };
-use hir_expand::name::name;
+use hir_expand::name::{name, Name};
+use itertools::Either;
 use la_arena::ArenaMap;
 use rustc_hash::FxHashMap;
 use stdx::impl_from;
@@ -37,7 +38,7 @@ use syntax::SmolStr;
 use super::{DomainGoal, InEnvironment, ProjectionTy, TraitEnvironment, TraitRef, Ty};
 use crate::{
     db::HirDatabase, fold_tys, lower::ImplTraitLoweringMode, to_assoc_type_id, AliasEq, AliasTy,
-    Goal, Interner, Substitution, TyBuilder, TyExt, TyKind,
+    Goal, ImplTraitId, Interner, Substitution, TyBuilder, TyExt, TyKind,
 };
 
 // This lint has a false positive here. See the link below for details.
@@ -47,12 +48,16 @@ use crate::{
 pub use unify::could_unify;
 pub(crate) use unify::unify;
 
+use cast::CastCheck;
+pub use cast::CastError;
+
 mod unify;
 mod path;
 mod expr;
 mod pat;
 mod coerce;
 mod closure;
+mod cast;
 
 /// The entry point of type inference.
 pub(crate) fn infer_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<InferenceResult> {
@@ -103,17 +108,60 @@ impl Default for BindingMode {
 }
 
 #[derive(Debug)]
-pub(crate) struct InferOk {
+pub(crate) struct InferOk<T = ()> {
+    value: T,
     goals: Vec<InEnvironment<Goal>>,
 }
 #[derive(Debug)]
 pub(crate) struct TypeError;
-pub(crate) type InferResult = Result<InferOk, TypeError>;
+pub(crate) type InferResult<T = ()> = Result<InferOk<T>, TypeError>;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum InferenceDiagnostic {
-    NoSuchField { expr: ExprId },
-    BreakOutsideOfLoop { expr: ExprId },
+    NoSuchField {
+        expr: ExprId,
+    },
+    BreakOutsideOfLoop {
+        expr: ExprId,
+    },
+    /// The else block of a `let ... else` statement which doesn't diverge.
+    NonDivergingLetElse {
+        expr: ExprId,
+    },
+    UnresolvedField {
+        expr: ExprId,
+        receiver: Ty,
+        name: Name,
+    },
+    UnresolvedMethodCall {
+        expr: ExprId,
+        receiver: Ty,
+        name: Name,
+    },
+    /// The first statement or tail expression of a block which is never
+    /// reached because an earlier statement diverges. Let statements are
+    /// identified by their pattern.
+    UnreachableCode {
+        node: Either<ExprId, PatId>,
+    },
+    InvalidCast {
+        expr: ExprId,
+        error: CastError,
+        expr_ty: Ty,
+        cast_ty: Ty,
+    },
+    /// A cast to the type the expression already has.
+    TrivialCast {
+        expr: ExprId,
+        ty: Ty,
+    },
+    /// A valid numeric cast which might not preserve the value, like a
+    /// truncating or sign-changing integer cast.
+    LossyCast {
+        expr: ExprId,
+        expr_ty: Ty,
+        cast_ty: Ty,
+    },
 }
 
 /// A mismatch between an expected and an inferred type.
@@ -123,6 +171,76 @@ pub struct TypeMismatch {
     pub actual: Ty,
 }
 
+/// An implicit conversion applied to an expression, like an autoderef, an
+/// autoref or an unsizing coercion. See `rustc_middle::ty::adjustment`.
+///
+/// An expression can have several adjustments; each of them is applied to the
+/// result of the previous one, and `target` is the type after the adjustment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Adjustment {
+    pub kind: Adjust,
+    pub target: Ty,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Adjust {
+    /// Go from `!` to any type.
+    NeverToAny,
+    /// Dereference once, producing a place. `None` for a builtin deref of a
+    /// reference or raw pointer, `Some` for a call to `Deref::deref` or
+    /// `DerefMut::deref_mut`.
+    Deref(Option<OverloadedDeref>),
+    /// Take the address of the place and produce a reference or raw pointer.
+    Borrow(AutoBorrow),
+    /// A pointer-to-pointer cast, like reifying a fn item or an unsizing.
+    Pointer(PointerCast),
+}
+
+/// An overloaded autoderef step, with the mutability of the `&self` or
+/// `&mut self` receiver of the `deref` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OverloadedDeref(pub Mutability);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AutoBorrow {
+    /// Converts from T to &T.
+    Ref(Mutability),
+    /// Converts from T to *T.
+    RawPtr(Mutability),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerCast {
+    /// Go from a fn-item type to a fn-pointer type.
+    ReifyFnPointer,
+    /// Go from a safe fn pointer to an unsafe fn pointer.
+    UnsafeFnPointer,
+    /// Go from a non-capturing closure to an fn pointer or an unsafe fn pointer.
+    ClosureFnPointer(Safety),
+    /// Go from a mut raw pointer to a const raw pointer.
+    MutToConstPointer,
+    /// Unsize a pointer/reference value, e.g. `&[T; n]` to `&[T]`, or
+    /// `Box<T>` to `Box<dyn Trait>`.
+    Unsize,
+}
+
+/// How a closure captures a variable of the enclosing body. The variants are
+/// ordered from the weakest to the strongest capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CaptureKind {
+    ByRef,
+    ByMutRef,
+    ByValue,
+}
+
+/// A variable captured by a closure. Variables are always captured as a whole,
+/// like in the 2018 edition.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CapturedItem {
+    pub local: PatId,
+    pub kind: CaptureKind,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 struct InternedStandardTypes {
     unknown: Ty,
@@ -157,6 +275,10 @@ pub struct InferenceResult {
     standard_types: InternedStandardTypes,
     /// Stores the types which were implicitly dereferenced in pattern binding modes.
     pub pat_adjustments: FxHashMap<PatId, Vec<Ty>>,
+    /// For each expression, the implicit conversions applied to its value, in order.
+    pub expr_adjustments: FxHashMap<ExprId, Vec<Adjustment>>,
+    /// For each closure expression, the captured variables in order of first use.
+    closure_captures: FxHashMap<ExprId, Vec<CapturedItem>>,
 }
 
 impl InferenceResult {
@@ -178,6 +300,12 @@ impl InferenceResult {
     pub fn assoc_resolutions_for_pat(&self, id: PatId) -> Option<AssocItemId> {
         self.assoc_resolutions.get(&id.into()).copied()
     }
+    pub fn expr_adjustments(&self, expr: ExprId) -> &[Adjustment] {
+        self.expr_adjustments.get(&expr).map_or(&[], |it| it.as_slice())
+    }
+    pub fn closure_captures(&self, closure: ExprId) -> &[CapturedItem] {
+        self.closure_captures.get(&closure).map_or(&[], |it| it.as_slice())
+    }
     pub fn type_mismatch_for_expr(&self, expr: ExprId) -> Option<&TypeMismatch> {
         self.type_mismatches.get(&expr.into())
     }
@@ -233,6 +361,7 @@ struct InferenceContext<'a> {
     return_ty: Ty,
     diverges: Diverges,
     breakables: Vec<BreakableContext>,
+    deferred_cast_checks: Vec<CastCheck>,
 }
 
 #[derive(Clone, Debug)]
@@ -269,6 +398,7 @@ impl<'a> InferenceContext<'a> {
             resolver,
             diverges: Diverges::Maybe,
             breakables: Vec::new(),
+            deferred_cast_checks: Vec::new(),
         }
     }
 
@@ -282,6 +412,7 @@ impl<'a> InferenceContext<'a> {
 
         // make sure diverging type variables are marked as such
         self.table.propagate_diverging_flag();
+        self.check_casts();
         let mut result = std::mem::take(&mut self.result);
         for ty in result.type_of_expr.values_mut() {
             *ty = self.table.resolve_completely(ty.clone());
@@ -296,6 +427,30 @@ impl<'a> InferenceContext<'a> {
         for (_, subst) in result.method_resolutions.values_mut() {
             *subst = self.table.resolve_completely(subst.clone());
         }
+        for adjustment in result.expr_adjustments.values_mut().flatten() {
+            adjustment.target = self.table.resolve_completely(adjustment.target.clone());
+        }
+        for diagnostic in result.diagnostics.iter_mut() {
+            match diagnostic {
+                InferenceDiagnostic::UnresolvedField { receiver, .. }
+                | InferenceDiagnostic::UnresolvedMethodCall { receiver, .. } => {
+                    *receiver = self.table.resolve_completely(receiver.clone());
+                }
+                InferenceDiagnostic::TrivialCast { ty, .. } => {
+                    *ty = self.table.resolve_completely(ty.clone());
+                }
+                InferenceDiagnostic::InvalidCast { expr_ty, cast_ty, .. }
+                | InferenceDiagnostic::LossyCast { expr_ty, cast_ty, .. } => {
+                    *expr_ty = self.table.resolve_completely(expr_ty.clone());
+                    *cast_ty = self.table.resolve_completely(cast_ty.clone());
+                }
+                InferenceDiagnostic::NoSuchField { .. }
+                | InferenceDiagnostic::BreakOutsideOfLoop { .. }
+                | InferenceDiagnostic::NonDivergingLetElse { .. }
+                | InferenceDiagnostic::UnreachableCode { .. } => (),
+            }
+        }
+        result.closure_captures = self.infer_closure_captures(&result);
         result
     }
 
@@ -303,6 +458,14 @@ impl<'a> InferenceContext<'a> {
         self.result.type_of_expr.insert(expr, ty);
     }
 
+    fn write_expr_adj(&mut self, expr: ExprId, adjustments: Vec<Adjustment>) {
+        if adjustments.is_empty() {
+            self.result.expr_adjustments.remove(&expr);
+        } else {
+            self.result.expr_adjustments.insert(expr, adjustments);
+        }
+    }
+
     fn write_method_resolution(&mut self, expr: ExprId, func: FunctionId, subst: Substitution) {
         self.result.method_resolutions.insert(expr, (func, subst));
     }
@@ -563,8 +726,51 @@ impl<'a> InferenceContext<'a> {
         } else {
             &*data.ret_type
         };
-        let return_ty = self.make_ty_with_mode(return_ty, ImplTraitLoweringMode::Disallowed); // FIXME implement RPIT
-        self.return_ty = return_ty;
+        let return_ty = self.make_ty_with_mode(return_ty, ImplTraitLoweringMode::Opaque);
+        self.return_ty = self.insert_vars_for_impl_trait(return_ty);
+    }
+
+    /// Replaces the opaque types of the function's own return position `impl
+    /// Trait`s by inference variables, so that the body can be checked against
+    /// the hidden type while the bounds are still registered as obligations.
+    fn insert_vars_for_impl_trait(&mut self, ty: Ty) -> Ty {
+        let func = match self.owner {
+            DefWithBodyId::FunctionId(it) => it,
+            _ => return ty,
+        };
+        let rpits = match self.db.return_type_impl_traits(func) {
+            Some(it) => it,
+            None => return ty,
+        };
+        let mut obligations = Vec::new();
+        let ty = fold_tys(
+            ty,
+            |ty, _| {
+                let opaque_ty = match ty.kind(&Interner) {
+                    TyKind::Alias(AliasTy::Opaque(opaque_ty)) => opaque_ty,
+                    _ => return ty,
+                };
+                let idx = match self.db.lookup_intern_impl_trait_id(opaque_ty.opaque_ty_id.into()) {
+                    ImplTraitId::ReturnTypeImplTrait(it, idx) if it == func => idx,
+                    _ => return ty,
+                };
+                let var = self.table.new_type_var();
+                let bounds = (*rpits)
+                    .as_ref()
+                    .map(|rpits| rpits.impl_traits[idx as usize].bounds.clone())
+                    .substitute(&Interner, &opaque_ty.substitution)
+                    .substitute(&Interner, &Substitution::from1(&Interner, var.clone()));
+                obligations.extend(
+                    bounds.into_iter().map(|bound| bound.into_value_and_skipped_binders().0),
+                );
+                var
+            },
+            DebruijnIndex::INNERMOST,
+        );
+        for clause in obligations {
+            self.push_obligation(clause.cast(&Interner));
+        }
+        ty
     }
 
     fn infer_body(&mut self) {
@@ -688,7 +894,7 @@ impl<'a> InferenceContext<'a> {
 enum Expectation {
     None,
     HasType(Ty),
-    // Castable(Ty), // rustc has this, we currently just don't propagate an expectation for casts
+    Castable(Ty),
     RValueLikeUnsized(Ty),
 }
 
@@ -740,6 +946,7 @@ impl Expectation {
         match self {
             Expectation::None => Expectation::None,
             Expectation::HasType(t) => Expectation::HasType(table.resolve_ty_shallow(t)),
+            Expectation::Castable(t) => Expectation::Castable(table.resolve_ty_shallow(t)),
             Expectation::RValueLikeUnsized(t) => {
                 Expectation::RValueLikeUnsized(table.resolve_ty_shallow(t))
             }
@@ -749,17 +956,18 @@ impl Expectation {
     fn to_option(&self, table: &mut unify::InferenceTable) -> Option<Ty> {
         match self.resolve(table) {
             Expectation::None => None,
-            Expectation::HasType(t) |
-            // Expectation::Castable(t) |
-            Expectation::RValueLikeUnsized(t) => Some(t),
+            Expectation::HasType(t)
+            | Expectation::Castable(t)
+            | Expectation::RValueLikeUnsized(t) => Some(t),
         }
     }
 
     fn only_has_type(&self, table: &mut unify::InferenceTable) -> Option<Ty> {
         match self {
             Expectation::HasType(t) => Some(table.resolve_ty_shallow(t)),
-            // Expectation::Castable(_) |
-            Expectation::RValueLikeUnsized(_) | Expectation::None => None,
+            Expectation::Castable(_) | Expectation::RValueLikeUnsized(_) | Expectation::None => {
+                None
+            }
         }
     }
 
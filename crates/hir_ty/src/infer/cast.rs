@@ -0,0 +1,325 @@
+//! Checking of `as` casts, see `rustc_typeck::check::cast`.
+//!
+//! Casts are checked once the whole body has been inferred, since the type of
+//! the casted expression (an integer literal, for example) is often not known
+//! at the point where the cast is encountered.
+
+use chalk_ir::{FloatTy, IntTy, Mutability, Scalar, UintTy};
+use hir_def::{
+    expr::{Expr, ExprId, Literal, UnaryOp},
+    type_ref::TypeRef,
+    AdtId,
+};
+
+use crate::{Interner, Ty, TyExt, TyKind};
+
+use super::{InferenceContext, InferenceDiagnostic};
+
+/// A cast whose validity is checked after inference, in `resolve_all`.
+#[derive(Clone, Debug)]
+pub(super) struct CastCheck {
+    /// The cast expression itself.
+    expr: ExprId,
+    /// The expression being casted.
+    source_expr: ExprId,
+    expr_ty: Ty,
+    cast_ty: Ty,
+}
+
+impl CastCheck {
+    pub(super) fn new(expr: ExprId, source_expr: ExprId, expr_ty: Ty, cast_ty: Ty) -> Self {
+        CastCheck { expr, source_expr, expr_ty, cast_ty }
+    }
+}
+
+/// The reason an `as` cast is rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CastError {
+    /// A cast between types which are not both primitive, like a struct or a
+    /// data-carrying enum.
+    NonScalar,
+    /// A cast to `bool`, which is only allowed from `bool` itself.
+    CastToBool,
+    /// A cast to `char`, which is only allowed from `u8`.
+    CastToChar,
+    /// A cast of a `bool`, `char` or fieldless enum to a pointer, which has to
+    /// go through an integer first.
+    NeedViaInt,
+    /// A cast of a reference to an integer, which has to go through a raw
+    /// pointer first.
+    NeedViaPtr,
+    /// A cast of a fat pointer to an integer, which has to go through a thin
+    /// pointer first.
+    NeedViaThinPtr,
+    /// A cast of a thin pointer to a fat one.
+    SizedUnsizedCast,
+    /// A cast between fat pointers with different kinds of metadata, like a
+    /// slice pointer to a trait object pointer.
+    DifferingKinds,
+    /// Any other invalid cast between primitive types, like `bool as f32` or
+    /// `&T as *mut T`.
+    IllegalCast,
+}
+
+/// The categories of types which take part in primitive casts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CastTy {
+    Int(IntTyKind),
+    Float,
+    FnPtr,
+    Ptr(Ty, Mutability),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IntTyKind {
+    Bool,
+    Char,
+    CEnum,
+    U8,
+    I,
+}
+
+/// The metadata of a raw pointer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PointerKind {
+    Thin,
+    /// Slices and `str`.
+    Length,
+    /// Trait objects, with their principal trait.
+    VTable(Option<hir_def::TraitId>),
+}
+
+impl InferenceContext<'_> {
+    pub(super) fn check_casts(&mut self) {
+        for check in std::mem::take(&mut self.deferred_cast_checks) {
+            let expr_ty = self.table.resolve_completely(check.expr_ty.clone());
+            let cast_ty = self.table.resolve_completely(check.cast_ty.clone());
+            if expr_ty.is_unknown() || cast_ty.is_unknown() || expr_ty.is_never() {
+                continue;
+            }
+            let expr = check.expr;
+            if expr_ty == cast_ty {
+                if !self.is_implicit_cast(&check) {
+                    self.push_diagnostic(InferenceDiagnostic::TrivialCast { expr, ty: cast_ty });
+                }
+                continue;
+            }
+            match self.check_cast(&expr_ty, &cast_ty) {
+                Ok(()) => {
+                    if self.is_lossy_cast(&expr_ty, &cast_ty) {
+                        self.push_diagnostic(InferenceDiagnostic::LossyCast {
+                            expr,
+                            expr_ty,
+                            cast_ty,
+                        });
+                    }
+                }
+                Err(error) => self.push_diagnostic(InferenceDiagnostic::InvalidCast {
+                    expr,
+                    error,
+                    expr_ty,
+                    cast_ty,
+                }),
+            }
+        }
+    }
+
+    /// Whether the types of the cast are the same for reasons other than the
+    /// user writing it that way: unsuffixed literals take the type they are
+    /// cast to, and `as _` takes the type of the casted expression.
+    fn is_implicit_cast(&self, check: &CastCheck) -> bool {
+        if let Expr::Cast { type_ref, .. } = &self.body[check.expr] {
+            if matches!(&**type_ref, TypeRef::Placeholder) {
+                return true;
+            }
+        }
+        let mut source = check.source_expr;
+        if let Expr::UnaryOp { expr, op: UnaryOp::Neg } = &self.body[source] {
+            source = *expr;
+        }
+        matches!(
+            &self.body[source],
+            Expr::Literal(Literal::Int(_, None) | Literal::Uint(_, None) | Literal::Float(_, None))
+        )
+    }
+
+    fn check_cast(&mut self, expr_ty: &Ty, cast_ty: &Ty) -> Result<(), CastError> {
+        // Anything that coerces can also be cast, like `&mut T as *const T`.
+        if self.coerce(None, expr_ty, cast_ty) {
+            return Ok(());
+        }
+
+        let (t_from, t_cast) = match (self.cast_ty_of(expr_ty), self.cast_ty_of(cast_ty)) {
+            (Some(t_from), Some(t_cast)) => (t_from, t_cast),
+            _ => return self.check_non_scalar_cast(expr_ty, cast_ty),
+        };
+
+        match (t_from, t_cast) {
+            (_, CastTy::Int(IntTyKind::CEnum)) | (_, CastTy::FnPtr) => Err(CastError::NonScalar),
+            (_, CastTy::Int(IntTyKind::Bool)) => Err(CastError::CastToBool),
+            (CastTy::Int(IntTyKind::U8), CastTy::Int(IntTyKind::Char)) => Ok(()),
+            (_, CastTy::Int(IntTyKind::Char)) => Err(CastError::CastToChar),
+            (CastTy::Int(IntTyKind::Bool | IntTyKind::Char | IntTyKind::CEnum), CastTy::Float) => {
+                Err(CastError::IllegalCast)
+            }
+            (CastTy::Int(_) | CastTy::Float, CastTy::Int(_) | CastTy::Float) => Ok(()),
+
+            (CastTy::Ptr(from, _), CastTy::Ptr(to, _)) => {
+                match (self.pointer_kind(&from), self.pointer_kind(&to)) {
+                    (_, Some(PointerKind::Thin)) | (None, _) | (_, None) => Ok(()),
+                    (Some(PointerKind::Thin), Some(_)) => Err(CastError::SizedUnsizedCast),
+                    (Some(from), Some(to)) if from == to => Ok(()),
+                    (Some(_), Some(_)) => Err(CastError::DifferingKinds),
+                }
+            }
+            (CastTy::Ptr(from, _), CastTy::Int(_)) => match self.pointer_kind(&from) {
+                Some(PointerKind::Thin) | None => Ok(()),
+                Some(_) => Err(CastError::NeedViaThinPtr),
+            },
+            (CastTy::FnPtr, CastTy::Int(_)) => Ok(()),
+            (CastTy::Ptr(..) | CastTy::FnPtr, CastTy::Float) => Err(CastError::IllegalCast),
+
+            (CastTy::Int(IntTyKind::U8 | IntTyKind::I) | CastTy::FnPtr, CastTy::Ptr(to, _)) => {
+                match self.pointer_kind(&to) {
+                    Some(PointerKind::Thin) | None => Ok(()),
+                    Some(_) => Err(CastError::IllegalCast),
+                }
+            }
+            (CastTy::Int(_), CastTy::Ptr(..)) => Err(CastError::NeedViaInt),
+            (CastTy::Float, CastTy::Ptr(..)) => Err(CastError::IllegalCast),
+        }
+    }
+
+    /// Classifies casts involving types which don't take part in primitive
+    /// casts, like references. Array references may still be cast to a
+    /// pointer to their element type.
+    fn check_non_scalar_cast(&self, expr_ty: &Ty, cast_ty: &Ty) -> Result<(), CastError> {
+        let (inner, from_mutability) = match expr_ty.kind(&Interner) {
+            TyKind::Ref(mutability, _, inner) => (inner, *mutability),
+            _ => return Err(CastError::NonScalar),
+        };
+        match cast_ty.kind(&Interner) {
+            TyKind::Raw(to_mutability, to_inner) => {
+                if from_mutability == Mutability::Not && *to_mutability == Mutability::Mut {
+                    return Err(CastError::IllegalCast);
+                }
+                match inner.kind(&Interner) {
+                    TyKind::Array(elem, _) if elem == to_inner => Ok(()),
+                    _ => Err(CastError::IllegalCast),
+                }
+            }
+            TyKind::Scalar(Scalar::Int(_) | Scalar::Uint(_)) => Err(CastError::NeedViaPtr),
+            _ => Err(CastError::NonScalar),
+        }
+    }
+
+    fn cast_ty_of(&self, ty: &Ty) -> Option<CastTy> {
+        let cast_ty = match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Bool) => CastTy::Int(IntTyKind::Bool),
+            TyKind::Scalar(Scalar::Char) => CastTy::Int(IntTyKind::Char),
+            TyKind::Scalar(Scalar::Uint(UintTy::U8)) => CastTy::Int(IntTyKind::U8),
+            TyKind::Scalar(Scalar::Int(_) | Scalar::Uint(_)) => CastTy::Int(IntTyKind::I),
+            TyKind::Scalar(Scalar::Float(_)) => CastTy::Float,
+            TyKind::Adt(crate::AdtId(AdtId::EnumId(id)), _) => {
+                let data = self.db.enum_data(*id);
+                let fieldless = !data.variants.is_empty()
+                    && data.variants.iter().all(|(_, v)| v.variant_data.fields().is_empty());
+                if !fieldless {
+                    return None;
+                }
+                CastTy::Int(IntTyKind::CEnum)
+            }
+            TyKind::Raw(mutability, inner) => CastTy::Ptr(inner.clone(), *mutability),
+            // Function items are reified to function pointers first.
+            TyKind::Function(_) | TyKind::FnDef(..) => CastTy::FnPtr,
+            _ => return None,
+        };
+        Some(cast_ty)
+    }
+
+    /// The kind of metadata a pointer to `ty` has, or `None` if it can't be
+    /// determined, like for type parameters which might be unsized.
+    fn pointer_kind(&self, ty: &Ty) -> Option<PointerKind> {
+        match ty.kind(&Interner) {
+            TyKind::Slice(_) | TyKind::Str => Some(PointerKind::Length),
+            TyKind::Dyn(_) => Some(PointerKind::VTable(ty.dyn_trait())),
+            TyKind::Adt(crate::AdtId(AdtId::StructId(id)), subst) => {
+                let fields = self.db.field_types((*id).into());
+                match fields.iter().last() {
+                    Some((_, last)) => {
+                        self.pointer_kind(&last.clone().substitute(&Interner, subst))
+                    }
+                    None => Some(PointerKind::Thin),
+                }
+            }
+            TyKind::Tuple(_, subst) => match subst.iter(&Interner).last() {
+                Some(last) => self.pointer_kind(last.assert_ty_ref(&Interner)),
+                None => Some(PointerKind::Thin),
+            },
+            TyKind::Placeholder(_)
+            | TyKind::Alias(_)
+            | TyKind::AssociatedType(..)
+            | TyKind::OpaqueType(..)
+            | TyKind::BoundVar(_)
+            | TyKind::InferenceVar(..)
+            | TyKind::Error => None,
+            _ => Some(PointerKind::Thin),
+        }
+    }
+
+    /// Whether a valid cast between primitive types might not preserve the
+    /// value, like truncating integer casts or float to integer casts.
+    fn is_lossy_cast(&self, expr_ty: &Ty, cast_ty: &Ty) -> bool {
+        let from = match expr_ty.kind(&Interner) {
+            TyKind::Scalar(it) => *it,
+            TyKind::Raw(..) | TyKind::Function(_) | TyKind::FnDef(..) => {
+                return match cast_ty.kind(&Interner) {
+                    TyKind::Scalar(to @ (Scalar::Int(_) | Scalar::Uint(_))) => {
+                        self.scalar_bits(*to) < self.pointer_bits()
+                    }
+                    _ => false,
+                };
+            }
+            _ => return false,
+        };
+        let to = match cast_ty.kind(&Interner) {
+            TyKind::Scalar(it) => *it,
+            _ => return false,
+        };
+        let (from_bits, to_bits) = (self.scalar_bits(from), self.scalar_bits(to));
+        match (from, to) {
+            (Scalar::Float(_), Scalar::Float(_)) => to_bits < from_bits,
+            (Scalar::Float(_), _) => true,
+            (Scalar::Int(_) | Scalar::Uint(_), Scalar::Float(to)) => {
+                let mantissa_bits = if to == FloatTy::F32 { 24 } else { 53 };
+                from_bits > mantissa_bits
+            }
+            (Scalar::Char, _) => to_bits < 32,
+            (Scalar::Int(_), Scalar::Int(_)) | (Scalar::Uint(_), Scalar::Uint(_)) => {
+                to_bits < from_bits
+            }
+            (Scalar::Uint(_), Scalar::Int(_)) => to_bits <= from_bits,
+            (Scalar::Int(_), Scalar::Uint(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn scalar_bits(&self, scalar: Scalar) -> u64 {
+        match scalar {
+            Scalar::Bool | Scalar::Int(IntTy::I8) | Scalar::Uint(UintTy::U8) => 8,
+            Scalar::Int(IntTy::I16) | Scalar::Uint(UintTy::U16) => 16,
+            Scalar::Char
+            | Scalar::Int(IntTy::I32)
+            | Scalar::Uint(UintTy::U32)
+            | Scalar::Float(FloatTy::F32) => 32,
+            Scalar::Int(IntTy::I64) | Scalar::Uint(UintTy::U64) | Scalar::Float(FloatTy::F64) => 64,
+            Scalar::Int(IntTy::I128) | Scalar::Uint(UintTy::U128) => 128,
+            Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize) => self.pointer_bits(),
+        }
+    }
+
+    fn pointer_bits(&self) -> u64 {
+        let krate = self.resolver.krate();
+        krate.map_or(64, |krate| self.db.target_data_layout(krate).pointer_size * 8)
+    }
+}
@@ -1,15 +1,27 @@
-//! Inference of closure parameter types based on the closure's expected type.
+//! Inference of closure parameter types based on the closure's expected type,
+//! and of the variables captured by closures.
 
-use chalk_ir::{cast::Cast, AliasTy, FnSubst, WhereClause};
-use hir_def::HasModule;
+use chalk_ir::{cast::Cast, AliasTy, FnSubst, Mutability, WhereClause};
+use hir_def::{
+    expr::{BinaryOp, BindingAnnotation, CaptureBy, Expr, ExprId, Pat, PatId, Statement, UnaryOp},
+    lang_item::LangItemTarget,
+    path::Path,
+    resolver::{resolver_for_expr, ResolveValueResult, ValueNs},
+    HasModule,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
 use smallvec::SmallVec;
 
 use crate::{
-    to_chalk_trait_id, utils, ChalkTraitId, DynTy, FnPointer, FnSig, Interner, Substitution, Ty,
+    lower::lower_to_chalk_mutability, to_chalk_trait_id, utils, Canonical, CanonicalVarKinds,
+    ChalkTraitId, DynTy, FnPointer, FnSig, InEnvironment, Interner, Substitution, Ty, TyBuilder,
     TyExt, TyKind,
 };
 
-use super::{Expectation, InferenceContext};
+use super::{
+    Adjust, AutoBorrow, CaptureKind, CapturedItem, Expectation, InferenceContext, InferenceResult,
+    OverloadedDeref,
+};
 
 impl InferenceContext<'_> {
     pub(super) fn deduce_closure_type_from_expectations(
@@ -24,7 +36,7 @@ impl InferenceContext<'_> {
         };
 
         // Deduction from where-clauses in scope, as well as fn-pointer coercion are handled here.
-        self.coerce(closure_ty, &expected_ty);
+        self.coerce(None, closure_ty, &expected_ty);
 
         // Deduction based on the expected `dyn Fn` is done separately.
         if let TyKind::Dyn(dyn_ty) = expected_ty.kind(&Interner) {
@@ -90,3 +102,381 @@ impl InferenceContext<'_> {
         None
     }
 }
+
+impl InferenceContext<'_> {
+    /// Computes the variables captured by each closure of the body, once all
+    /// types, adjustments and method resolutions are known.
+    pub(super) fn infer_closure_captures(
+        &self,
+        result: &InferenceResult,
+    ) -> FxHashMap<ExprId, Vec<CapturedItem>> {
+        if !self.body.exprs.iter().any(|(_, expr)| matches!(expr, Expr::Lambda { .. })) {
+            return FxHashMap::default();
+        }
+        let mut collector = CaptureCollector {
+            ctx: self,
+            result,
+            closures: Vec::new(),
+            local_depth: FxHashMap::default(),
+            mutating_closures: FxHashSet::default(),
+            captures: FxHashMap::default(),
+        };
+        collector.walk_expr(self.body.body_expr);
+        collector.captures
+    }
+}
+
+struct CaptureCollector<'a, 'b> {
+    ctx: &'a InferenceContext<'b>,
+    result: &'a InferenceResult,
+    /// The closures enclosing the current expression, innermost last.
+    closures: Vec<(ExprId, CaptureBy)>,
+    /// For each binding, the number of closures enclosing its declaration.
+    local_depth: FxHashMap<PatId, usize>,
+    /// Closures which mutate one of their captures, and so are `FnMut`.
+    mutating_closures: FxHashSet<ExprId>,
+    captures: FxHashMap<ExprId, Vec<CapturedItem>>,
+}
+
+impl CaptureCollector<'_, '_> {
+    /// Walks an expression whose value is used, moving it unless it is `Copy`.
+    fn walk_expr(&mut self, expr: ExprId) {
+        if let Some(kind) = self.borrow_adjustment(expr) {
+            self.walk_place(expr, kind);
+            return;
+        }
+        self.walk_expr_without_adjust(expr);
+    }
+
+    /// Walks an expression, ignoring the adjustments applied to its value.
+    fn walk_expr_without_adjust(&mut self, expr: ExprId) {
+        let body = &self.ctx.body;
+        match &body[expr] {
+            Expr::Path(_) | Expr::Field { .. } | Expr::Index { .. } => self.consume(expr),
+            Expr::UnaryOp { op: UnaryOp::Deref, .. } => self.consume(expr),
+            Expr::Ref { expr: place, mutability, .. } => {
+                let kind = match lower_to_chalk_mutability(*mutability) {
+                    Mutability::Mut => CaptureKind::ByMutRef,
+                    Mutability::Not => CaptureKind::ByRef,
+                };
+                self.walk_place(*place, kind);
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { .. }) } => {
+                self.walk_place(lhs, CaptureKind::ByMutRef);
+                self.walk_expr(rhs);
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::CmpOp(_)) } => {
+                self.walk_place(lhs, CaptureKind::ByRef);
+                self.walk_place(rhs, CaptureKind::ByRef);
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                if self.result.method_resolution(expr).is_some() {
+                    self.walk_expr(*receiver);
+                } else {
+                    // We don't know how the receiver is used.
+                    self.walk_place(*receiver, CaptureKind::ByRef);
+                }
+                for &arg in args {
+                    self.walk_expr(arg);
+                }
+            }
+            Expr::Call { callee, args } => {
+                let kind = self.call_kind(*callee);
+                self.walk_place(*callee, kind);
+                for &arg in args {
+                    self.walk_expr(arg);
+                }
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                let kind = arms
+                    .iter()
+                    .map(|arm| self.pat_capture_kind(arm.pat))
+                    .max()
+                    .unwrap_or(CaptureKind::ByRef);
+                self.walk_place(*scrutinee, kind);
+                for arm in arms {
+                    self.declare(arm.pat);
+                    if let Some(guard) = arm.guard {
+                        self.walk_expr(guard);
+                    }
+                    self.walk_expr(arm.expr);
+                }
+            }
+            &Expr::Let { pat, expr: scrutinee } => {
+                let kind = self.pat_capture_kind(pat);
+                self.walk_place(scrutinee, kind);
+                self.declare(pat);
+            }
+            Expr::Block { statements, tail, .. } => {
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { pat, initializer, else_branch, .. } => {
+                            if let Some(initializer) = initializer {
+                                let kind = self.pat_capture_kind(*pat);
+                                self.walk_place(*initializer, kind);
+                            }
+                            if let Some(else_branch) = else_branch {
+                                self.walk_expr(*else_branch);
+                            }
+                            self.declare(*pat);
+                        }
+                        Statement::Expr { expr, .. } => self.walk_expr(*expr),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.walk_expr(*tail);
+                }
+            }
+            &Expr::For { iterable, pat, body: loop_body, .. } => {
+                self.walk_expr(iterable);
+                self.declare(pat);
+                self.walk_expr(loop_body);
+            }
+            Expr::Lambda { args, body: closure_body, capture_by, .. } => {
+                self.captures.entry(expr).or_default();
+                self.closures.push((expr, *capture_by));
+                for &arg in args {
+                    self.declare(arg);
+                }
+                self.walk_expr(*closure_body);
+                self.closures.pop();
+            }
+            it => it.walk_child_exprs(|child| self.walk_expr(child)),
+        }
+    }
+
+    /// Walks an expression which is used as a place, e.g. borrowed or
+    /// assigned to, capturing the variable the place is based on with `kind`.
+    fn walk_place(&mut self, expr: ExprId, kind: CaptureKind) {
+        let body = &self.ctx.body;
+        match &body[expr] {
+            Expr::Path(path) => {
+                if let Some(local) = self.resolve_local(expr, path) {
+                    self.capture(local, kind);
+                }
+            }
+            &Expr::Field { expr: base, .. } => {
+                let kind = self.kind_through(base, kind, false);
+                self.walk_place(base, kind);
+            }
+            &Expr::UnaryOp { expr: base, op: UnaryOp::Deref } => {
+                let kind = self.kind_through(base, kind, true);
+                self.walk_place(base, kind);
+            }
+            &Expr::Index { base, index } => {
+                self.walk_expr(index);
+                // Nothing can be moved out of an index expression.
+                let kind = self.kind_through(base, kind.min(CaptureKind::ByMutRef), false);
+                self.walk_place(base, kind);
+            }
+            // A borrowed temporary; its adjustments are what got us here.
+            _ => self.walk_expr_without_adjust(expr),
+        }
+    }
+
+    fn consume(&mut self, expr: ExprId) {
+        let moves = match self.place_local(expr) {
+            Some(local) => self.is_captured(local) && !self.is_copy(&self.result[expr]),
+            None => false,
+        };
+        let kind = if moves { CaptureKind::ByValue } else { CaptureKind::ByRef };
+        self.walk_place(expr, kind);
+    }
+
+    /// The capture needed for the base of a projection, given the capture
+    /// needed for the projected place. Going through a reference, the base is
+    /// only ever borrowed.
+    fn kind_through(&self, base: ExprId, kind: CaptureKind, is_deref: bool) -> CaptureKind {
+        match self.result[base].kind(&Interner) {
+            TyKind::Ref(Mutability::Mut, ..) => kind.min(CaptureKind::ByMutRef),
+            TyKind::Ref(Mutability::Not, ..) | TyKind::Raw(..) => CaptureKind::ByRef,
+            // Moving out of an overloaded deref is not possible, except for `Box`.
+            TyKind::Adt(chalk_ir::AdtId(adt), _)
+                if is_deref && Some(*adt) != self.ctx.resolve_boxed_box() =>
+            {
+                kind.min(CaptureKind::ByMutRef)
+            }
+            _ => kind,
+        }
+    }
+
+    /// The capture needed for a place which is implicitly borrowed, like an
+    /// autoref'd method receiver or a reborrowed `&mut` argument.
+    fn borrow_adjustment(&self, expr: ExprId) -> Option<CaptureKind> {
+        self.result.expr_adjustments(expr).iter().find_map(|adjustment| match adjustment.kind {
+            Adjust::Borrow(AutoBorrow::Ref(m))
+            | Adjust::Borrow(AutoBorrow::RawPtr(m))
+            | Adjust::Deref(Some(OverloadedDeref(m))) => Some(match m {
+                Mutability::Mut => CaptureKind::ByMutRef,
+                Mutability::Not => CaptureKind::ByRef,
+            }),
+            Adjust::Deref(None) | Adjust::NeverToAny | Adjust::Pointer(_) => None,
+        })
+    }
+
+    /// Calling a closure which mutates its captures needs a mutable place.
+    fn call_kind(&self, callee: ExprId) -> CaptureKind {
+        match self.result[callee].kind(&Interner) {
+            TyKind::Closure(id, _) => {
+                let (_, closure) = self.ctx.db.lookup_intern_closure((*id).into());
+                if self.mutating_closures.contains(&closure) {
+                    CaptureKind::ByMutRef
+                } else {
+                    CaptureKind::ByRef
+                }
+            }
+            _ => CaptureKind::ByRef,
+        }
+    }
+
+    /// The capture needed for a place which is matched against `pat`.
+    fn pat_capture_kind(&self, pat: PatId) -> CaptureKind {
+        let mut kind = CaptureKind::ByRef;
+        self.walk_pat(pat, None, false, &mut kind);
+        kind
+    }
+
+    fn walk_pat(
+        &self,
+        pat: PatId,
+        mut default_ref: Option<Mutability>,
+        behind_ref: bool,
+        kind: &mut CaptureKind,
+    ) {
+        // Matching a non-reference pattern against a reference switches to
+        // binding by reference.
+        for ty in self.result.pat_adjustments.get(&pat).into_iter().flatten() {
+            if let TyKind::Ref(m, ..) = ty.kind(&Interner) {
+                default_ref = Some(match default_ref {
+                    Some(Mutability::Not) => Mutability::Not,
+                    _ => *m,
+                });
+            }
+        }
+        let body = &self.ctx.body;
+        match &body[pat] {
+            Pat::Bind { mode, subpat, .. } => {
+                let binding_kind = match mode {
+                    BindingAnnotation::Ref => CaptureKind::ByRef,
+                    BindingAnnotation::RefMut => CaptureKind::ByMutRef,
+                    BindingAnnotation::Unannotated | BindingAnnotation::Mutable => {
+                        match default_ref {
+                            Some(Mutability::Mut) => CaptureKind::ByMutRef,
+                            Some(Mutability::Not) => CaptureKind::ByRef,
+                            None if behind_ref || self.is_copy(&self.result[pat]) => {
+                                CaptureKind::ByRef
+                            }
+                            None => CaptureKind::ByValue,
+                        }
+                    }
+                };
+                *kind = (*kind).max(binding_kind);
+                if let Some(subpat) = subpat {
+                    self.walk_pat(*subpat, default_ref, behind_ref, kind);
+                }
+            }
+            Pat::Ref { pat: inner, .. } => self.walk_pat(*inner, None, true, kind),
+            it => it.walk_child_pats(|child| self.walk_pat(child, default_ref, behind_ref, kind)),
+        }
+    }
+
+    /// Records the bindings of `pat` as declared in the innermost closure.
+    fn declare(&mut self, pat: PatId) {
+        let depth = self.closures.len();
+        let body = &self.ctx.body;
+        let mut stack = vec![pat];
+        while let Some(pat) = stack.pop() {
+            if let Pat::Bind { .. } = &body[pat] {
+                self.local_depth.insert(pat, depth);
+            }
+            body[pat].walk_child_pats(|child| stack.push(child));
+        }
+    }
+
+    /// Records that `local` is used with `kind` by the innermost closure,
+    /// which means it is captured by all closures between the use and the
+    /// declaration of the variable.
+    fn capture(&mut self, local: PatId, kind: CaptureKind) {
+        let depth = self.local_depth.get(&local).copied().unwrap_or(0);
+        let mut kind = kind;
+        for idx in (depth..self.closures.len()).rev() {
+            let (closure, capture_by) = self.closures[idx];
+            if kind == CaptureKind::ByMutRef {
+                self.mutating_closures.insert(closure);
+            }
+            let captured_kind = match capture_by {
+                CaptureBy::Value => CaptureKind::ByValue,
+                CaptureBy::Ref => kind,
+            };
+            let captures = self.captures.entry(closure).or_default();
+            match captures.iter_mut().find(|it| it.local == local) {
+                Some(it) => it.kind = it.kind.max(captured_kind),
+                None => captures.push(CapturedItem { local, kind: captured_kind }),
+            }
+            // Moving a variable into the inner closure uses it by value in
+            // the outer one.
+            if capture_by == CaptureBy::Value {
+                kind = if self.is_copy(&self.result[local]) {
+                    CaptureKind::ByRef
+                } else {
+                    CaptureKind::ByValue
+                };
+            }
+        }
+    }
+
+    /// Whether `local` is declared outside of the innermost closure.
+    fn is_captured(&self, local: PatId) -> bool {
+        self.local_depth.get(&local).copied().unwrap_or(0) < self.closures.len()
+    }
+
+    /// The variable a place expression is based on, if any.
+    fn place_local(&self, expr: ExprId) -> Option<PatId> {
+        let body = &self.ctx.body;
+        match &body[expr] {
+            Expr::Path(path) => self.resolve_local(expr, path),
+            Expr::Field { expr: base, .. }
+            | Expr::Index { base, .. }
+            | Expr::UnaryOp { expr: base, op: UnaryOp::Deref } => self.place_local(*base),
+            _ => None,
+        }
+    }
+
+    fn resolve_local(&self, expr: ExprId, path: &Path) -> Option<PatId> {
+        let db = self.ctx.db;
+        let resolver = resolver_for_expr(db.upcast(), self.ctx.owner, expr);
+        let syntax_context = self.ctx.body.path_context(expr);
+        match resolver.resolve_path_in_value_ns(db.upcast(), path.mod_path(), syntax_context) {
+            Some(ResolveValueResult::ValueNs(ValueNs::LocalBinding(pat))) => Some(pat),
+            _ => None,
+        }
+    }
+
+    fn is_copy(&self, ty: &Ty) -> bool {
+        match ty.kind(&Interner) {
+            TyKind::Scalar(_)
+            | TyKind::Ref(Mutability::Not, ..)
+            | TyKind::Raw(..)
+            | TyKind::FnDef(..)
+            | TyKind::Function(_)
+            | TyKind::Never
+            // Don't report moves of values we know nothing about.
+            | TyKind::Error => return true,
+            TyKind::Ref(Mutability::Mut, ..) | TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => {
+                return false
+            }
+            _ => (),
+        }
+        let (krate, copy_trait) =
+            match (self.ctx.resolver.krate(), self.ctx.resolve_lang_item("copy")) {
+                (Some(krate), Some(LangItemTarget::TraitId(it))) => (krate, it),
+                _ => return false,
+            };
+        let trait_ref = TyBuilder::trait_ref(self.ctx.db, copy_trait).push(ty.clone()).build();
+        let goal = Canonical {
+            value: InEnvironment::new(&self.ctx.trait_env.env, trait_ref.cast(&Interner)),
+            binders: CanonicalVarKinds::empty(&Interner),
+        };
+        self.ctx.db.trait_solve(krate, goal).is_some()
+    }
+}
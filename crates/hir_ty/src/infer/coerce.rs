@@ -9,21 +9,44 @@ use chalk_ir::{cast::Cast, Mutability, TyVariableKind};
 use hir_def::{expr::ExprId, lang_item::LangItemTarget};
 
 use crate::{
-    autoderef, infer::TypeMismatch, static_lifetime, Canonical, DomainGoal, FnPointer, FnSig,
-    Interner, Solution, Substitution, Ty, TyBuilder, TyExt, TyKind,
+    autoderef,
+    infer::{Adjust, Adjustment, AutoBorrow, OverloadedDeref, PointerCast, TypeMismatch},
+    static_lifetime, Canonical, DomainGoal, FnPointer, FnSig, Interner, Solution, Substitution, Ty,
+    TyBuilder, TyExt, TyKind,
 };
 
 use super::{InEnvironment, InferOk, InferResult, InferenceContext, TypeError};
 
+/// The result of a coercion: the adjustments to apply to the coerced
+/// expression, and the goals that need to hold for the coercion to be valid.
+type CoerceResult = InferResult<Vec<Adjustment>>;
+
+/// Do not require any adjustments, i.e. coerce `x -> x`.
+fn identity(_: Ty) -> Vec<Adjustment> {
+    vec![]
+}
+
+fn simple(kind: Adjust) -> impl FnOnce(Ty) -> Vec<Adjustment> {
+    move |target| vec![Adjustment { kind, target }]
+}
+
+fn success(adjustments: Vec<Adjustment>, goals: Vec<InEnvironment<crate::Goal>>) -> CoerceResult {
+    Ok(InferOk { value: adjustments, goals })
+}
+
 impl<'a> InferenceContext<'a> {
     /// Unify two types, but may coerce the first one to the second one
-    /// using "implicit coercion rules" if needed.
-    pub(super) fn coerce(&mut self, from_ty: &Ty, to_ty: &Ty) -> bool {
+    /// using "implicit coercion rules" if needed. The adjustments this needs
+    /// are recorded for `expr`, if given.
+    pub(super) fn coerce(&mut self, expr: Option<ExprId>, from_ty: &Ty, to_ty: &Ty) -> bool {
         let from_ty = self.resolve_ty_shallow(from_ty);
         let to_ty = self.resolve_ty_shallow(to_ty);
         match self.coerce_inner(from_ty, &to_ty) {
             Ok(result) => {
-                self.table.register_infer_ok(result);
+                let adjustments = self.table.register_infer_ok(result);
+                if let Some(expr) = expr {
+                    self.write_expr_adj(expr, adjustments);
+                }
                 true
             }
             Err(_) => {
@@ -61,8 +84,12 @@ impl<'a> InferenceContext<'a> {
             let result1 = self.coerce_inner(ty1.clone(), &target_ty);
             let result2 = self.coerce_inner(ty2.clone(), &target_ty);
             if let (Ok(result1), Ok(result2)) = (result1, result2) {
+                // FIXME: record the adjustments of the previous branches as well
                 self.table.register_infer_ok(result1);
-                self.table.register_infer_ok(result2);
+                let adjustments = self.table.register_infer_ok(result2);
+                if let Some(id) = id {
+                    self.write_expr_adj(id, adjustments);
+                }
                 return target_ty;
             }
         }
@@ -72,9 +99,9 @@ impl<'a> InferenceContext<'a> {
         // type is a type variable and the new one is `!`, trying it the other
         // way around first would mean we make the type variable `!`, instead of
         // just marking it as possibly diverging.
-        if self.coerce(&ty2, &ty1) {
+        if self.coerce(id, &ty2, &ty1) {
             ty1
-        } else if self.coerce(&ty1, &ty2) {
+        } else if self.coerce(None, &ty1, &ty2) {
             ty2
         } else {
             if let Some(id) = id {
@@ -87,7 +114,7 @@ impl<'a> InferenceContext<'a> {
         }
     }
 
-    fn coerce_inner(&mut self, from_ty: Ty, to_ty: &Ty) -> InferResult {
+    fn coerce_inner(&mut self, from_ty: Ty, to_ty: &Ty) -> CoerceResult {
         if from_ty.is_never() {
             // Subtle: If we are coercing from `!` to `?T`, where `?T` is an unbound
             // type variable, we want `?T` to fallback to `!` if not
@@ -99,10 +126,10 @@ impl<'a> InferenceContext<'a> {
             match to_ty.kind(&Interner) {
                 TyKind::InferenceVar(tv, TyVariableKind::General) => {
                     self.table.set_diverging(*tv, true);
+                    return success(vec![], vec![]);
                 }
-                _ => {}
+                _ => return success(simple(Adjust::NeverToAny)(to_ty.clone()), vec![]),
             }
-            return Ok(InferOk { goals: Vec::new() });
         }
 
         // Consider coercing the subtype to a DST
@@ -143,35 +170,61 @@ impl<'a> InferenceContext<'a> {
             }
             _ => {
                 // Otherwise, just use unification rules.
-                self.table.try_unify(&from_ty, to_ty)
+                self.unify_and(&from_ty, to_ty, identity)
             }
         }
     }
 
-    fn coerce_ptr(&mut self, from_ty: Ty, to_ty: &Ty, to_mt: Mutability) -> InferResult {
-        let (_is_ref, from_mt, from_inner) = match from_ty.kind(&Interner) {
-            TyKind::Ref(mt, _, ty) => (true, mt, ty),
-            TyKind::Raw(mt, ty) => (false, mt, ty),
-            _ => return self.table.try_unify(&from_ty, to_ty),
+    /// Unify two types and, if that succeeds, compute the adjustments from
+    /// the (first) type that was unified.
+    fn unify_and<F>(&mut self, t1: &Ty, t2: &Ty, f: F) -> CoerceResult
+    where
+        F: FnOnce(Ty) -> Vec<Adjustment>,
+    {
+        let InferOk { goals, .. } = self.table.try_unify(t1, t2)?;
+        success(f(t1.clone()), goals)
+    }
+
+    fn coerce_ptr(&mut self, from_ty: Ty, to_ty: &Ty, to_mt: Mutability) -> CoerceResult {
+        let (is_ref, from_mt, from_inner) = match from_ty.kind(&Interner) {
+            TyKind::Ref(mt, _, ty) => (true, *mt, ty.clone()),
+            TyKind::Raw(mt, ty) => (false, *mt, ty.clone()),
+            _ => return self.unify_and(&from_ty, to_ty, identity),
         };
 
-        coerce_mutabilities(*from_mt, to_mt)?;
+        coerce_mutabilities(from_mt, to_mt)?;
 
         // Check that the types which they point at are compatible.
         let from_raw = TyKind::Raw(to_mt, from_inner.clone()).intern(&Interner);
-        // FIXME: behavior differs based on is_ref once we're computing adjustments
-        self.table.try_unify(&from_raw, to_ty)
+        if is_ref {
+            // `&T` to `*const T`: reborrow the referent as a raw pointer.
+            self.unify_and(&from_raw, to_ty, |target| {
+                vec![
+                    Adjustment { kind: Adjust::Deref(None), target: from_inner },
+                    Adjustment { kind: Adjust::Borrow(AutoBorrow::RawPtr(to_mt)), target },
+                ]
+            })
+        } else if from_mt != to_mt {
+            self.unify_and(
+                &from_raw,
+                to_ty,
+                simple(Adjust::Pointer(PointerCast::MutToConstPointer)),
+            )
+        } else {
+            self.unify_and(&from_raw, to_ty, identity)
+        }
     }
 
     /// Reborrows `&mut A` to `&mut B` and `&(mut) A` to `&B`.
     /// To match `A` with `B`, autoderef will be performed,
     /// calling `deref`/`deref_mut` where necessary.
-    fn coerce_ref(&mut self, from_ty: Ty, to_ty: &Ty, to_mt: Mutability) -> InferResult {
-        match from_ty.kind(&Interner) {
+    fn coerce_ref(&mut self, from_ty: Ty, to_ty: &Ty, to_mt: Mutability) -> CoerceResult {
+        let from_mt = match from_ty.kind(&Interner) {
             TyKind::Ref(mt, _, _) => {
                 coerce_mutabilities(*mt, to_mt)?;
+                *mt
             }
-            _ => return self.table.try_unify(&from_ty, to_ty),
+            _ => return self.unify_and(&from_ty, to_ty, identity),
         };
 
         // NOTE: this code is mostly copied and adapted from rustc, and
@@ -191,16 +244,18 @@ impl<'a> InferenceContext<'a> {
         );
         let mut first_error = None;
         let mut found = None;
+        // The types we've deref'd through so far, starting with `from_ty`.
+        let mut steps = Vec::new();
 
         for (autoderefs, referent_ty) in autoderef.enumerate() {
+            let referent_ty = canonicalized.decanonicalize_ty(referent_ty.value);
+            steps.push(referent_ty.clone());
             if autoderefs == 0 {
                 // Don't let this pass, otherwise it would cause
                 // &T to autoref to &&T.
                 continue;
             }
 
-            let referent_ty = canonicalized.decanonicalize_ty(referent_ty.value);
-
             // At this point, we have deref'd `a` to `referent_ty`.  So
             // imagine we are coercing from `&'a mut Vec<T>` to `&'b mut [T]`.
             // In the autoderef loop for `&'a mut Vec<T>`, we would get
@@ -227,7 +282,7 @@ impl<'a> InferenceContext<'a> {
             let derefd_from_ty = TyKind::Ref(to_mt, lt, referent_ty).intern(&Interner);
             match self.table.try_unify(&derefd_from_ty, to_ty) {
                 Ok(result) => {
-                    found = Some(result);
+                    found = Some((result, autoderefs, derefd_from_ty));
                     break;
                 }
                 Err(err) => {
@@ -243,7 +298,7 @@ impl<'a> InferenceContext<'a> {
         // (e.g., in example above, the failure from relating `Vec<T>`
         // to the target type), since that should be the least
         // confusing.
-        let result = match found {
+        let (InferOk { goals, .. }, autoderefs, derefd_from_ty) = match found {
             Some(d) => d,
             None => {
                 let err = first_error.expect("coerce_borrowed_pointer had no error");
@@ -251,11 +306,31 @@ impl<'a> InferenceContext<'a> {
             }
         };
 
-        Ok(result)
+        if autoderefs == 1 && from_mt == Mutability::Not {
+            // Reborrowing `&T` as `&T` is a no-op, so don't record it (rustc
+            // doesn't either).
+            return success(vec![], goals);
+        }
+
+        let mut adjustments: Vec<_> = steps[..=autoderefs]
+            .windows(2)
+            .map(|pair| {
+                let overloaded = match pair[0].kind(&Interner) {
+                    TyKind::Ref(..) | TyKind::Raw(..) => None,
+                    _ => Some(OverloadedDeref(to_mt)),
+                };
+                Adjustment { kind: Adjust::Deref(overloaded), target: pair[1].clone() }
+            })
+            .collect();
+        adjustments.push(Adjustment {
+            kind: Adjust::Borrow(AutoBorrow::Ref(to_mt)),
+            target: derefd_from_ty,
+        });
+        success(adjustments, goals)
     }
 
     /// Attempts to coerce from the type of a Rust function item into a function pointer.
-    fn coerce_from_fn_item(&mut self, from_ty: Ty, to_ty: &Ty) -> InferResult {
+    fn coerce_from_fn_item(&mut self, from_ty: Ty, to_ty: &Ty) -> CoerceResult {
         match to_ty.kind(&Interner) {
             TyKind::Function(_) => {
                 let from_sig = from_ty.callable_sig(self.db).expect("FnDef had no sig");
@@ -267,11 +342,27 @@ impl<'a> InferenceContext<'a> {
 
                 let from_sig = from_sig.to_fn_ptr();
                 let from_fn_pointer = TyKind::Function(from_sig.clone()).intern(&Interner);
-                let ok = self.coerce_from_safe_fn(from_fn_pointer, &from_sig, to_ty)?;
-
-                Ok(ok)
+                let reified = from_fn_pointer.clone();
+                self.coerce_from_safe_fn(
+                    from_fn_pointer,
+                    &from_sig,
+                    to_ty,
+                    |unsafe_ty| {
+                        vec![
+                            Adjustment {
+                                kind: Adjust::Pointer(PointerCast::ReifyFnPointer),
+                                target: reified,
+                            },
+                            Adjustment {
+                                kind: Adjust::Pointer(PointerCast::UnsafeFnPointer),
+                                target: unsafe_ty,
+                            },
+                        ]
+                    },
+                    simple(Adjust::Pointer(PointerCast::ReifyFnPointer)),
+                )
             }
-            _ => self.table.try_unify(&from_ty, to_ty),
+            _ => self.unify_and(&from_ty, to_ty, identity),
         }
     }
 
@@ -280,26 +371,38 @@ impl<'a> InferenceContext<'a> {
         from_ty: Ty,
         from_f: &FnPointer,
         to_ty: &Ty,
-    ) -> InferResult {
-        self.coerce_from_safe_fn(from_ty, from_f, to_ty)
+    ) -> CoerceResult {
+        self.coerce_from_safe_fn(
+            from_ty,
+            from_f,
+            to_ty,
+            simple(Adjust::Pointer(PointerCast::UnsafeFnPointer)),
+            identity,
+        )
     }
 
-    fn coerce_from_safe_fn(
+    fn coerce_from_safe_fn<F, G>(
         &mut self,
         from_ty: Ty,
         from_fn_ptr: &FnPointer,
         to_ty: &Ty,
-    ) -> InferResult {
+        to_unsafe: F,
+        normal: G,
+    ) -> CoerceResult
+    where
+        F: FnOnce(Ty) -> Vec<Adjustment>,
+        G: FnOnce(Ty) -> Vec<Adjustment>,
+    {
         if let TyKind::Function(to_fn_ptr) = to_ty.kind(&Interner) {
             if let (chalk_ir::Safety::Safe, chalk_ir::Safety::Unsafe) =
                 (from_fn_ptr.sig.safety, to_fn_ptr.sig.safety)
             {
                 let from_unsafe =
                     TyKind::Function(safe_to_unsafe_fn_ty(from_fn_ptr.clone())).intern(&Interner);
-                return self.table.try_unify(&from_unsafe, to_ty);
+                return self.unify_and(&from_unsafe, to_ty, to_unsafe);
             }
         }
-        self.table.try_unify(&from_ty, to_ty)
+        self.unify_and(&from_ty, to_ty, normal)
     }
 
     /// Attempts to coerce from the type of a non-capturing closure into a
@@ -309,7 +412,7 @@ impl<'a> InferenceContext<'a> {
         from_ty: Ty,
         from_substs: &Substitution,
         to_ty: &Ty,
-    ) -> InferResult {
+    ) -> CoerceResult {
         match to_ty.kind(&Interner) {
             TyKind::Function(fn_ty) /* if from_substs is non-capturing (FIXME) */ => {
                 // We coerce the closure, which has fn type
@@ -320,16 +423,20 @@ impl<'a> InferenceContext<'a> {
                 //     `unsafe fn(arg0,arg1,...) -> _`
                 let safety = fn_ty.sig.safety;
                 let pointer_ty = coerce_closure_fn_ty(from_substs, safety);
-                self.table.try_unify(&pointer_ty, to_ty)
+                self.unify_and(
+                    &pointer_ty,
+                    to_ty,
+                    simple(Adjust::Pointer(PointerCast::ClosureFnPointer(safety))),
+                )
             }
-            _ => self.table.try_unify(&from_ty, to_ty),
+            _ => self.unify_and(&from_ty, to_ty, identity),
         }
     }
 
     /// Coerce a type using `from_ty: CoerceUnsized<ty_ty>`
     ///
     /// See: <https://doc.rust-lang.org/nightly/std/marker/trait.CoerceUnsized.html>
-    fn try_coerce_unsized(&mut self, from_ty: &Ty, to_ty: &Ty) -> InferResult {
+    fn try_coerce_unsized(&mut self, from_ty: &Ty, to_ty: &Ty) -> CoerceResult {
         // These 'if' statements require some explanation.
         // The `CoerceUnsized` trait is special - it is only
         // possible to write `impl CoerceUnsized<B> for A` where
@@ -359,20 +466,34 @@ impl<'a> InferenceContext<'a> {
         }
 
         // Handle reborrows before trying to solve `Source: CoerceUnsized<Target>`.
-        let coerce_from = match (from_ty.kind(&Interner), to_ty.kind(&Interner)) {
+        let reborrow = match (from_ty.kind(&Interner), to_ty.kind(&Interner)) {
             (TyKind::Ref(from_mt, _, from_inner), TyKind::Ref(to_mt, _, _)) => {
                 coerce_mutabilities(*from_mt, *to_mt)?;
 
                 let lt = static_lifetime();
-                TyKind::Ref(*to_mt, lt, from_inner.clone()).intern(&Interner)
+                Some((
+                    Adjustment { kind: Adjust::Deref(None), target: from_inner.clone() },
+                    Adjustment {
+                        kind: Adjust::Borrow(AutoBorrow::Ref(*to_mt)),
+                        target: TyKind::Ref(*to_mt, lt, from_inner.clone()).intern(&Interner),
+                    },
+                ))
             }
             (TyKind::Ref(from_mt, _, from_inner), TyKind::Raw(to_mt, _)) => {
                 coerce_mutabilities(*from_mt, *to_mt)?;
 
-                TyKind::Raw(*to_mt, from_inner.clone()).intern(&Interner)
+                Some((
+                    Adjustment { kind: Adjust::Deref(None), target: from_inner.clone() },
+                    Adjustment {
+                        kind: Adjust::Borrow(AutoBorrow::RawPtr(*to_mt)),
+                        target: TyKind::Raw(*to_mt, from_inner.clone()).intern(&Interner),
+                    },
+                ))
             }
-            _ => from_ty.clone(),
+            _ => None,
         };
+        let coerce_from =
+            reborrow.as_ref().map_or_else(|| from_ty.clone(), |(_, adj)| adj.target.clone());
 
         let krate = self.resolver.krate().unwrap();
         let coerce_unsized_trait = match self.db.lang_item(krate, "coerce_unsized".into()) {
@@ -418,7 +539,13 @@ impl<'a> InferenceContext<'a> {
             _ => return Err(TypeError),
         };
 
-        Ok(InferOk { goals: Vec::new() })
+        let mut adjustments = match reborrow {
+            Some((deref, autoref)) => vec![deref, autoref],
+            None => Vec::new(),
+        };
+        adjustments
+            .push(Adjustment { kind: Adjust::Pointer(PointerCast::Unsize), target: to_ty.clone() });
+        success(adjustments, Vec::new())
     }
 }
 
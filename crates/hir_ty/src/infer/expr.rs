@@ -11,6 +11,7 @@ use hir_def::{
     AssocContainerId, FieldId, Lookup,
 };
 use hir_expand::name::{name, Name};
+use itertools::Either;
 use stdx::always;
 use syntax::ast::RangeOp;
 
@@ -18,18 +19,21 @@ use crate::{
     autoderef, consteval,
     lower::lower_to_chalk_mutability,
     mapping::from_chalk,
-    method_resolution, op,
+    method_resolution::{self, ReceiverAdjustments},
+    op,
     primitive::{self, UintTy},
     static_lifetime, to_chalk_trait_id,
     traits::FnTrait,
     utils::{generics, Generics},
     AdtId, Binders, CallableDefId, FnPointer, FnSig, FnSubst, InEnvironment, Interner,
     ProjectionTyExt, Rawness, Scalar, Substitution, TraitRef, Ty, TyBuilder, TyExt, TyKind,
+    TypeWalk,
 };
 
 use super::{
-    find_breakable, BindingMode, BreakableContext, Diverges, Expectation, InferenceContext,
-    InferenceDiagnostic, TypeMismatch,
+    cast::CastCheck, find_breakable, Adjust, Adjustment, AutoBorrow, BindingMode, BreakableContext,
+    Diverges, Expectation, InferenceContext, InferenceDiagnostic, OverloadedDeref, PointerCast,
+    TypeMismatch,
 };
 
 impl<'a> InferenceContext<'a> {
@@ -55,8 +59,11 @@ impl<'a> InferenceContext<'a> {
     /// Return the type after possible coercion.
     pub(super) fn infer_expr_coerce(&mut self, expr: ExprId, expected: &Expectation) -> Ty {
         let ty = self.infer_expr_inner(expr, expected);
+        if self.resolve_ty_shallow(&ty).is_never() {
+            self.diverges = Diverges::Always;
+        }
         let ty = if let Some(target) = expected.only_has_type(&mut self.table) {
-            if !self.coerce(&ty, &target) {
+            if !self.coerce(Some(expr), &ty, &target) {
                 self.result
                     .type_mismatches
                     .insert(expr.into(), TypeMismatch { expected: target, actual: ty.clone() });
@@ -125,7 +132,6 @@ impl<'a> InferenceContext<'a> {
         let ty = match &body[tgt_expr] {
             Expr::Missing => self.err_ty(),
             Expr::If { condition, then_branch, else_branch } => {
-                // if let is desugared to match, so this is always simple if
                 self.infer_expr(
                     *condition,
                     &Expectation::has_type(TyKind::Scalar(Scalar::Bool).intern(&Interner)),
@@ -150,6 +156,11 @@ impl<'a> InferenceContext<'a> {
 
                 result_ty
             }
+            Expr::Let { pat, expr } => {
+                let input_ty = self.infer_expr(*expr, &Expectation::none());
+                self.infer_pat(*pat, &input_ty, BindingMode::default());
+                TyKind::Scalar(Scalar::Bool).intern(&Interner)
+            }
             Expr::Block { statements, tail, label, id: _ } => {
                 let old_resolver = mem::replace(
                     &mut self.resolver,
@@ -186,7 +197,11 @@ impl<'a> InferenceContext<'a> {
             Expr::Async { body } => {
                 // Use the first type parameter as the output type of future.
                 // existenail type AsyncBlockImplTrait<InnerType>: Future<Output = InnerType>
+                // Diverging inside of the async block doesn't make the block
+                // itself diverge, it just never resolves.
+                let prev_diverges = mem::replace(&mut self.diverges, Diverges::Maybe);
                 let inner_ty = self.infer_expr(*body, &Expectation::none());
+                self.diverges = prev_diverges;
                 let impl_trait_id = crate::ImplTraitId::AsyncBlockTypeImplTrait(self.owner, *body);
                 let opaque_ty_id = self.db.intern_impl_trait_id(impl_trait_id).into();
                 TyKind::OpaqueType(opaque_ty_id, Substitution::from1(&Interner, inner_ty))
@@ -217,7 +232,6 @@ impl<'a> InferenceContext<'a> {
                     break_ty: self.err_ty(),
                     label: label.map(|label| self.body[label].name.clone()),
                 });
-                // while let is desugared to a match loop, so this is always simple while
                 self.infer_expr(
                     *condition,
                     &Expectation::has_type(TyKind::Scalar(Scalar::Bool).intern(&Interner)),
@@ -247,7 +261,7 @@ impl<'a> InferenceContext<'a> {
                 self.diverges = Diverges::Maybe;
                 TyBuilder::unit()
             }
-            Expr::Lambda { body, args, ret_type, arg_types } => {
+            Expr::Lambda { body, args, ret_type, arg_types, .. } => {
                 assert_eq!(args.len(), arg_types.len());
 
                 let mut sig_tys = Vec::new();
@@ -400,7 +414,7 @@ impl<'a> InferenceContext<'a> {
                     self.infer_expr_coerce(*expr, &Expectation::has_type(self.return_ty.clone()));
                 } else {
                     let unit = TyBuilder::unit();
-                    self.coerce(&unit, &self.return_ty.clone());
+                    self.coerce(None, &unit, &self.return_ty.clone());
                 }
                 TyKind::Never.intern(&Interner)
             }
@@ -450,7 +464,7 @@ impl<'a> InferenceContext<'a> {
             }
             Expr::Field { expr, name } => {
                 let receiver_ty = self.infer_expr_inner(*expr, &Expectation::none());
-                let canonicalized = self.canonicalize(receiver_ty);
+                let canonicalized = self.canonicalize(receiver_ty.clone());
                 let ty = autoderef::autoderef(
                     self.db,
                     self.resolver.krate(),
@@ -459,7 +473,8 @@ impl<'a> InferenceContext<'a> {
                         environment: self.trait_env.env.clone(),
                     },
                 )
-                .find_map(|derefed_ty| {
+                .enumerate()
+                .find_map(|(autoderefs, derefed_ty)| {
                     let def_db = self.db.upcast();
                     let module = self.resolver.module();
                     let is_visible = |field_id: &FieldId| {
@@ -508,8 +523,26 @@ impl<'a> InferenceContext<'a> {
                         }
                         _ => None,
                     }
-                })
-                .unwrap_or_else(|| self.err_ty());
+                    .map(|ty| (autoderefs, ty))
+                });
+                let ty = match ty {
+                    Some((autoderefs, ty)) => {
+                        let (_, adjustments) =
+                            self.autoderef_adjustments(receiver_ty, autoderefs, Mutability::Not);
+                        self.write_expr_adj(*expr, adjustments);
+                        ty
+                    }
+                    None => {
+                        if self.is_known_receiver(&receiver_ty) {
+                            self.push_diagnostic(InferenceDiagnostic::UnresolvedField {
+                                expr: tgt_expr,
+                                receiver: receiver_ty,
+                                name: name.clone(),
+                            });
+                        }
+                        self.err_ty()
+                    }
+                };
                 let ty = self.insert_type_vars(ty);
                 self.normalize_associated_types_in(ty)
             }
@@ -522,27 +555,27 @@ impl<'a> InferenceContext<'a> {
                 self.resolve_associated_type(inner_ty, self.resolve_ops_try_ok())
             }
             Expr::Cast { expr, type_ref } => {
-                // FIXME: propagate the "castable to" expectation (and find a test case that shows this is necessary)
-                let _inner_ty = self.infer_expr_inner(*expr, &Expectation::none());
                 let cast_ty = self.make_ty(type_ref);
-                // FIXME check the cast...
+                let inner_ty =
+                    self.infer_expr_inner(*expr, &Expectation::Castable(cast_ty.clone()));
+                self.deferred_cast_checks.push(CastCheck::new(
+                    tgt_expr,
+                    *expr,
+                    inner_ty,
+                    cast_ty.clone(),
+                ));
                 cast_ty
             }
             Expr::Ref { expr, rawness, mutability } => {
                 let mutability = lower_to_chalk_mutability(*mutability);
-                let expectation = if let Some((exp_inner, exp_rawness, exp_mutability)) = expected
+                let expectation = if let Some((exp_inner, ..)) = expected
                     .only_has_type(&mut self.table)
                     .as_ref()
                     .and_then(|t| t.as_reference_or_ptr())
                 {
-                    if exp_mutability == Mutability::Mut && mutability == Mutability::Not {
-                        // FIXME: record type error - expected mut reference but found shared ref,
-                        // which cannot be coerced
-                    }
-                    if exp_rawness == Rawness::Ref && *rawness == Rawness::RawPtr {
-                        // FIXME: record type error - expected reference but found ptr,
-                        // which cannot be coerced
-                    }
+                    // Expecting a `&mut` where a shared reference is found, or a reference
+                    // where a pointer is found, is recorded as a mismatch by the coercion
+                    // to the expected type.
                     Expectation::rvalue_hint(Ty::clone(exp_inner))
                 } else {
                     Expectation::none()
@@ -566,7 +599,11 @@ impl<'a> InferenceContext<'a> {
                 }
             }
             Expr::UnaryOp { expr, op } => {
-                let inner_ty = self.infer_expr_inner(*expr, &Expectation::none());
+                let inner_expected = match op {
+                    UnaryOp::Neg | UnaryOp::Not => expected.clone(),
+                    UnaryOp::Deref => Expectation::none(),
+                };
+                let inner_ty = self.infer_expr_inner(*expr, &inner_expected);
                 let inner_ty = self.resolve_ty_shallow(&inner_ty);
                 match op {
                     UnaryOp::Deref => match self.resolver.krate() {
@@ -755,8 +792,10 @@ impl<'a> InferenceContext<'a> {
                             ),
                         );
 
-                        let repeat_expr = &self.body.exprs[*repeat];
-                        consteval::eval_usize(repeat_expr)
+                        let (result, table) = (&self.result, &mut self.table);
+                        consteval::eval_usize(self.db, self.owner, *repeat, &mut |expr| {
+                            table.resolve_ty_shallow(&result[expr])
+                        })
                     }
                 };
 
@@ -782,21 +821,28 @@ impl<'a> InferenceContext<'a> {
                         TyKind::Scalar(Scalar::Int(primitive::int_ty_from_builtin(*int_ty)))
                             .intern(&Interner)
                     }
-                    None => self.table.new_integer_var(),
+                    None => self.unsuffixed_int_literal_ty(expected),
                 },
                 Literal::Uint(_v, ty) => match ty {
                     Some(int_ty) => {
                         TyKind::Scalar(Scalar::Uint(primitive::uint_ty_from_builtin(*int_ty)))
                             .intern(&Interner)
                     }
-                    None => self.table.new_integer_var(),
+                    None => self.unsuffixed_int_literal_ty(expected),
                 },
                 Literal::Float(_v, ty) => match ty {
                     Some(float_ty) => {
                         TyKind::Scalar(Scalar::Float(primitive::float_ty_from_builtin(*float_ty)))
                             .intern(&Interner)
                     }
-                    None => self.table.new_float_var(),
+                    None => match expected.to_option(&mut self.table) {
+                        Some(ty)
+                            if matches!(ty.kind(&Interner), TyKind::Scalar(Scalar::Float(_))) =>
+                        {
+                            ty
+                        }
+                        _ => self.table.new_float_var(),
+                    },
                 },
             },
             Expr::MacroStmts { tail } => self.infer_expr_inner(*tail, expected),
@@ -807,15 +853,43 @@ impl<'a> InferenceContext<'a> {
         ty
     }
 
+    /// The type of an integer literal without suffix: the expected type if it
+    /// is (or can be cast from) an integer, or a new integer variable.
+    fn unsuffixed_int_literal_ty(&mut self, expected: &Expectation) -> Ty {
+        let expected = expected.to_option(&mut self.table);
+        let opt_ty = expected.as_ref().and_then(|ty| match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Int(_) | Scalar::Uint(_)) => Some(ty.clone()),
+            TyKind::Scalar(Scalar::Char) => {
+                Some(TyKind::Scalar(Scalar::Uint(UintTy::U8)).intern(&Interner))
+            }
+            TyKind::Raw(..) | TyKind::FnDef(..) | TyKind::Function(_) => {
+                Some(TyKind::Scalar(Scalar::Uint(UintTy::Usize)).intern(&Interner))
+            }
+            _ => None,
+        });
+        opt_ty.unwrap_or_else(|| self.table.new_integer_var())
+    }
+
     fn infer_block(
         &mut self,
         statements: &[Statement],
         tail: Option<ExprId>,
         expected: &Expectation,
     ) -> Ty {
+        // Only the first unreachable statement of a block is reported, and
+        // nothing is reported in blocks which are unreachable as a whole.
+        let mut report_unreachable = !self.diverges.is_always();
         for stmt in statements {
+            if report_unreachable && self.diverges.is_always() {
+                let node = match stmt {
+                    Statement::Let { pat, .. } => Either::Right(*pat),
+                    Statement::Expr { expr, .. } => Either::Left(*expr),
+                };
+                self.push_diagnostic(InferenceDiagnostic::UnreachableCode { node });
+                report_unreachable = false;
+            }
             match stmt {
-                Statement::Let { pat, type_ref, initializer } => {
+                Statement::Let { pat, type_ref, initializer, else_branch } => {
                     let decl_ty = type_ref
                         .as_ref()
                         .map(|tr| self.make_ty(tr))
@@ -832,6 +906,20 @@ impl<'a> InferenceContext<'a> {
                         }
                     }
 
+                    if let Some(else_branch) = else_branch {
+                        // The else block has to diverge, but whether it does
+                        // doesn't affect the code after the statement.
+                        let previous_diverges = mem::replace(&mut self.diverges, Diverges::Maybe);
+                        let else_ty = self.infer_expr_inner(*else_branch, &Expectation::none());
+                        let never = TyKind::Never.intern(&Interner);
+                        if !self.coerce(Some(*else_branch), &else_ty, &never) {
+                            self.push_diagnostic(InferenceDiagnostic::NonDivergingLetElse {
+                                expr: *else_branch,
+                            });
+                        }
+                        self.diverges = previous_diverges;
+                    }
+
                     self.infer_pat(*pat, &ty, BindingMode::default());
                 }
                 Statement::Expr { expr, .. } => {
@@ -841,6 +929,11 @@ impl<'a> InferenceContext<'a> {
         }
 
         let ty = if let Some(expr) = tail {
+            if report_unreachable && self.diverges.is_always() {
+                self.push_diagnostic(InferenceDiagnostic::UnreachableCode {
+                    node: Either::Left(expr),
+                });
+            }
             self.infer_expr_coerce(expr, expected)
         } else {
             // Citing rustc: if there is no explicit tail expression,
@@ -855,7 +948,7 @@ impl<'a> InferenceContext<'a> {
                 self.table.new_maybe_never_var()
             } else {
                 if let Some(t) = expected.only_has_type(&mut self.table) {
-                    self.coerce(&TyBuilder::unit(), &t);
+                    self.coerce(None, &TyBuilder::unit(), &t);
                 }
                 TyBuilder::unit()
             }
@@ -888,18 +981,28 @@ impl<'a> InferenceContext<'a> {
             )
         });
         let (receiver_ty, method_ty, substs) = match resolved {
-            Some((ty, func)) => {
+            Some((ty, func, adjustments)) => {
                 let ty = canonicalized_receiver.decanonicalize_ty(ty);
+                self.adjust_method_receiver(receiver, receiver_ty, adjustments);
                 let generics = generics(self.db.upcast(), func.into());
                 let substs = self.substs_for_method_call(generics, generic_args, &ty);
                 self.write_method_resolution(tgt_expr, func, substs.clone());
                 (ty, self.db.value_ty(func.into()), substs)
             }
-            None => (
-                receiver_ty,
-                Binders::empty(&Interner, self.err_ty()),
-                Substitution::empty(&Interner),
-            ),
+            None => {
+                if self.is_known_receiver(&receiver_ty) {
+                    self.push_diagnostic(InferenceDiagnostic::UnresolvedMethodCall {
+                        expr: tgt_expr,
+                        receiver: receiver_ty.clone(),
+                        name: method_name.clone(),
+                    });
+                }
+                (
+                    receiver_ty,
+                    Binders::empty(&Interner, self.err_ty()),
+                    Substitution::empty(&Interner),
+                )
+            }
         };
         let method_ty = method_ty.substitute(&Interner, &substs);
         self.register_obligations_for_call(&method_ty);
@@ -919,6 +1022,85 @@ impl<'a> InferenceContext<'a> {
         self.normalize_associated_types_in(ret_ty)
     }
 
+    /// Whether a failed field or method lookup on a receiver of type `ty` should
+    /// be reported. Nothing can be said yet about receivers whose type isn't
+    /// fully known, like `&{unknown}` or `Vec<{unknown}>`.
+    fn is_known_receiver(&mut self, ty: &Ty) -> bool {
+        let ty = self.table.resolve_completely(ty.clone());
+        let mut known = true;
+        ty.walk(&mut |ty| known &= !ty.is_unknown());
+        known
+    }
+
+    /// Records the adjustments method resolution applied to the receiver of a
+    /// method call.
+    fn adjust_method_receiver(
+        &mut self,
+        receiver: ExprId,
+        receiver_ty: Ty,
+        adjustments: ReceiverAdjustments,
+    ) {
+        let mutability = adjustments.autoref.unwrap_or(Mutability::Not);
+        let (mut ty, mut adjust) =
+            self.autoderef_adjustments(receiver_ty, adjustments.autoderefs, mutability);
+        if let Some(m) = adjustments.autoref {
+            ty = TyKind::Ref(m, static_lifetime(), ty).intern(&Interner);
+            adjust
+                .push(Adjustment { kind: Adjust::Borrow(AutoBorrow::Ref(m)), target: ty.clone() });
+        }
+        if adjustments.unsize_array {
+            let unsized_ty = match ty.kind(&Interner) {
+                TyKind::Array(elem, _) => Some(TyKind::Slice(elem.clone()).intern(&Interner)),
+                TyKind::Ref(m, lt, inner) => match inner.kind(&Interner) {
+                    TyKind::Array(elem, _) => Some(
+                        TyKind::Ref(*m, lt.clone(), TyKind::Slice(elem.clone()).intern(&Interner))
+                            .intern(&Interner),
+                    ),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(target) = unsized_ty {
+                adjust.push(Adjustment { kind: Adjust::Pointer(PointerCast::Unsize), target });
+            }
+        }
+        self.write_expr_adj(receiver, adjust);
+    }
+
+    /// Autoderefs `ty` `autoderefs` times, returning the resulting type and the
+    /// adjustments for doing so. Overloaded derefs get the given mutability.
+    fn autoderef_adjustments(
+        &mut self,
+        mut ty: Ty,
+        autoderefs: usize,
+        mutability: Mutability,
+    ) -> (Ty, Vec<Adjustment>) {
+        let mut adjustments = Vec::with_capacity(autoderefs);
+        for _ in 0..autoderefs {
+            let overloaded = match ty.kind(&Interner) {
+                TyKind::Ref(..) | TyKind::Raw(..) => None,
+                _ => Some(OverloadedDeref(mutability)),
+            };
+            let canonicalized = self.canonicalize(ty.clone());
+            let derefd = self.resolver.krate().and_then(|krate| {
+                autoderef::deref(
+                    self.db,
+                    krate,
+                    InEnvironment {
+                        goal: &canonicalized.value,
+                        environment: self.trait_env.env.clone(),
+                    },
+                )
+            });
+            ty = match derefd {
+                Some(derefd) => canonicalized.decanonicalize_ty(derefd.value),
+                None => break,
+            };
+            adjustments.push(Adjustment { kind: Adjust::Deref(overloaded), target: ty.clone() });
+        }
+        (ty, adjustments)
+    }
+
     fn check_call_arguments(&mut self, args: &[ExprId], param_tys: &[Ty]) {
         // Quoting https://github.com/rust-lang/rust/blob/6ef275e6c3cb1384ec78128eceeb4963ff788dca/src/librustc_typeck/check/mod.rs#L3325 --
         // We do this in a pretty awful way: first we type-check any arguments
@@ -99,6 +99,8 @@ impl<'a> InferenceContext<'a> {
     ) -> Ty {
         let body = Arc::clone(&self.body); // avoid borrow checker problem
         let mut expected = self.resolve_ty_shallow(expected);
+        // The type of the pattern before match ergonomics removed references from it.
+        let mut unadjusted_ty = None;
 
         if is_non_ref_pat(&body, pat) {
             let mut pat_adjustments = Vec::new();
@@ -113,6 +115,7 @@ impl<'a> InferenceContext<'a> {
             }
 
             if !pat_adjustments.is_empty() {
+                unadjusted_ty = Some(pat_adjustments[0].clone());
                 pat_adjustments.shrink_to_fit();
                 self.result.pat_adjustments.insert(pat, pat_adjustments);
             }
@@ -278,7 +281,7 @@ impl<'a> InferenceContext<'a> {
                 .insert(pat.into(), TypeMismatch { expected, actual: ty.clone() });
         }
         self.write_pat_ty(pat, ty.clone());
-        ty
+        unadjusted_ty.unwrap_or(ty)
     }
 }
 
@@ -8,7 +8,7 @@ use hir_def::{
     resolver::{ResolveValueResult, Resolver, TypeNs, ValueNs},
     AdtId, AssocContainerId, AssocItemId, EnumVariantId, Lookup,
 };
-use hir_expand::name::Name;
+use hir_expand::{hygiene::SyntaxContext, name::Name};
 
 use crate::{
     method_resolution, Interner, Substitution, TraitRefExt, Ty, TyBuilder, TyExt, TyKind,
@@ -51,8 +51,15 @@ impl<'a> InferenceContext<'a> {
                 id,
             )?
         } else {
-            let value_or_partial =
-                resolver.resolve_path_in_value_ns(self.db.upcast(), path.mod_path())?;
+            let syntax_context = match id {
+                ExprOrPatId::ExprId(expr) => self.body.path_context(expr),
+                ExprOrPatId::PatId(_) => SyntaxContext::ROOT,
+            };
+            let value_or_partial = resolver.resolve_path_in_value_ns(
+                self.db.upcast(),
+                path.mod_path(),
+                syntax_context,
+            )?;
 
             match value_or_partial {
                 ResolveValueResult::ValueNs(it) => (it, None),
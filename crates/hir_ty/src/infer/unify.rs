@@ -324,7 +324,7 @@ impl<'a> InferenceTable<'a> {
             t1,
             t2,
         ) {
-            Ok(result) => Ok(InferOk { goals: result.goals }),
+            Ok(result) => Ok(InferOk { goals: result.goals, value: () }),
             Err(chalk_ir::NoSolution) => Err(TypeError),
         }
     }
@@ -347,8 +347,9 @@ impl<'a> InferenceTable<'a> {
         }
     }
 
-    pub(crate) fn register_infer_ok(&mut self, infer_ok: InferOk) {
+    pub(crate) fn register_infer_ok<T>(&mut self, infer_ok: InferOk<T>) -> T {
         infer_ok.goals.into_iter().for_each(|goal| self.register_obligation_in_env(goal));
+        infer_ok.value
     }
 
     pub(crate) fn resolve_obligations_as_possible(&mut self) {
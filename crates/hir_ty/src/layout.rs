@@ -0,0 +1,763 @@
+//! Computes the memory layout of types: their size, alignment, field offsets
+//! and niches.
+//!
+//! This follows what rustc does in `rustc_middle::ty::layout` closely enough to
+//! give the same answer for the common cases, but it is not meant to be exact:
+//! only the classic "all other variants are zero-sized" niche-filling
+//! optimization is performed for enums.
+
+use std::{cmp, fmt, ops::RangeInclusive, sync::Arc};
+
+use base_db::CrateId;
+use chalk_ir::{FloatTy, IntTy, UintTy};
+use hir_def::{
+    adt::ReprOptions,
+    builtin_type::{BuiltinInt, BuiltinUint},
+    resolver::HasResolver,
+    type_ref::ConstScalar,
+    AdtId, AttrDefId, EnumId, UnionId, VariantId,
+};
+use itertools::Either;
+
+use crate::{
+    consteval, db::HirDatabase, CaptureKind, ConcreteConst, ConstValue, Interner, Scalar,
+    Substitution, Ty, TyKind,
+};
+
+/// The parts of a target's LLVM data layout string which matter for the
+/// layout of types. Sizes and alignments are in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetDataLayout {
+    pub big_endian: bool,
+    pub pointer_size: u64,
+    pub pointer_align: u64,
+    /// The ABI alignments of `i8`, `i16`, `i32`, `i64` and `i128`.
+    pub int_aligns: [u64; 5],
+    pub f32_align: u64,
+    pub f64_align: u64,
+    pub aggregate_align: u64,
+}
+
+impl Default for TargetDataLayout {
+    /// The layout of `x86_64-unknown-linux-gnu`, used when the target's layout
+    /// is not known.
+    fn default() -> TargetDataLayout {
+        TargetDataLayout::parse(
+            "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128",
+        )
+        .unwrap()
+    }
+}
+
+impl TargetDataLayout {
+    /// Parses an LLVM data layout string like `e-m:e-i64:64-n8:16:32:64-S128`,
+    /// filling in LLVM's defaults for everything it doesn't mention.
+    pub fn parse(spec: &str) -> Result<TargetDataLayout, String> {
+        let mut res = TargetDataLayout {
+            big_endian: false,
+            pointer_size: 8,
+            pointer_align: 8,
+            int_aligns: [1, 2, 4, 4, 4],
+            f32_align: 4,
+            f64_align: 8,
+            aggregate_align: 1,
+        };
+        let bytes = |bits: Option<&str>, item: &str| -> Result<u64, String> {
+            let bits = bits.ok_or_else(|| format!("missing value in `{}`", item))?;
+            let bits = bits.parse::<u64>().map_err(|_| format!("invalid value in `{}`", item))?;
+            // An alignment of 0 means byte-aligned.
+            Ok(cmp::max(bits / 8, 1))
+        };
+        for item in spec.split('-') {
+            let mut parts = item.split(':');
+            match parts.next().unwrap_or_default() {
+                "e" => res.big_endian = false,
+                "E" => res.big_endian = true,
+                "p" | "p0" => {
+                    res.pointer_size = bytes(parts.next(), item)?;
+                    res.pointer_align = bytes(parts.next(), item)?;
+                }
+                "a" => res.aggregate_align = bytes(parts.next(), item)?,
+                "f32" => res.f32_align = bytes(parts.next(), item)?,
+                "f64" => res.f64_align = bytes(parts.next(), item)?,
+                kind if kind.starts_with('i') => {
+                    let idx = match &kind[1..] {
+                        "8" => 0,
+                        "16" => 1,
+                        "32" => 2,
+                        "64" => 3,
+                        "128" => 4,
+                        _ => continue,
+                    };
+                    res.int_aligns[idx] = bytes(parts.next(), item)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+
+    fn int_align(&self, size: u64) -> u64 {
+        match size {
+            1 => self.int_aligns[0],
+            2 => self.int_aligns[1],
+            4 => self.int_aligns[2],
+            8 => self.int_aligns[3],
+            _ => self.int_aligns[4],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The type is dynamically sized.
+    Unsized,
+    /// The type contains generic parameters or types we couldn't infer.
+    HasPlaceholder,
+    /// The length of an array couldn't be evaluated.
+    UnknownArrayLength,
+    /// The explicit discriminant of an enum variant couldn't be evaluated.
+    UnknownDiscriminant,
+    /// The type contains itself without indirection.
+    RecursiveType,
+    SizeOverflow,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LayoutError::Unsized => "type is unsized",
+            LayoutError::HasPlaceholder => "type contains generic parameters or unknown types",
+            LayoutError::UnknownArrayLength => "array length is unknown",
+            LayoutError::UnknownDiscriminant => "enum discriminant is unknown",
+            LayoutError::RecursiveType => "type has infinite size",
+            LayoutError::SizeOverflow => "type is too big",
+        })
+    }
+}
+
+/// The memory layout of a type. Sizes, offsets and alignments are in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub size: u64,
+    pub align: u64,
+    pub fields: FieldsShape,
+    pub variants: Variants,
+    /// The niche with the most invalid values, which enums containing this
+    /// type can use to store their discriminant.
+    pub largest_niche: Option<Niche>,
+    pub uninhabited: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldsShape {
+    /// Scalars and other types without fields.
+    Primitive,
+    /// All fields start at offset 0.
+    Union(usize),
+    /// `count` elements `stride` bytes apart.
+    Array { stride: u64, count: u64 },
+    /// The offsets of the fields, in declaration order.
+    Arbitrary { offsets: Vec<u64> },
+}
+
+impl FieldsShape {
+    pub fn offset(&self, idx: usize) -> Option<u64> {
+        match self {
+            FieldsShape::Primitive => None,
+            FieldsShape::Union(count) => (idx < *count).then_some(0),
+            FieldsShape::Array { stride, count } => {
+                (idx as u64).checked_mul(*stride).filter(|_| (idx as u64) < *count)
+            }
+            FieldsShape::Arbitrary { offsets } => offsets.get(idx).copied(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Variants {
+    /// Structs, unions, and enums with at most one variant.
+    Single,
+    /// Enums with more than one variant, with the discriminant stored in `tag`.
+    Multiple { tag: Niche, tag_encoding: TagEncoding, variants: Vec<Layout> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagEncoding {
+    /// The tag directly stores the discriminant of the variant.
+    Direct,
+    /// The tag is a niche of `dataful_variant`; the variants in
+    /// `niche_variants` are encoded as consecutive invalid values starting at
+    /// `niche_start`.
+    Niche { dataful_variant: usize, niche_variants: RangeInclusive<usize>, niche_start: u128 },
+}
+
+/// A scalar at `offset` of which only the values in the (possibly wrapping)
+/// range `valid_start..=valid_end` are valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Niche {
+    pub offset: u64,
+    pub size: u64,
+    pub valid_start: u128,
+    pub valid_end: u128,
+}
+
+impl Niche {
+    fn max_value(&self) -> u128 {
+        if self.size >= 16 {
+            u128::MAX
+        } else {
+            (1 << (self.size * 8)) - 1
+        }
+    }
+
+    /// The number of invalid values.
+    pub fn available(&self) -> u128 {
+        self.valid_start.wrapping_sub(self.valid_end).wrapping_sub(1) & self.max_value()
+    }
+
+    /// Reserves `count` invalid values right after the valid range, returning
+    /// the first of them and the niche which remains.
+    fn reserve(&self, count: u128) -> Option<(u128, Niche)> {
+        if count > self.available() {
+            return None;
+        }
+        let start = self.valid_end.wrapping_add(1) & self.max_value();
+        let valid_end = self.valid_end.wrapping_add(count) & self.max_value();
+        Some((start, Niche { valid_end, ..*self }))
+    }
+}
+
+pub(crate) fn target_data_layout_query(
+    db: &dyn HirDatabase,
+    krate: CrateId,
+) -> Arc<TargetDataLayout> {
+    let crate_graph = db.crate_graph();
+    let layout = crate_graph[krate].target_layout.as_deref().and_then(|it| {
+        TargetDataLayout::parse(it)
+            .map_err(|e| log::error!("failed to parse target data layout: {}", e))
+            .ok()
+    });
+    Arc::new(layout.unwrap_or_default())
+}
+
+/// Computes the layout of `ty` for the target of `krate`.
+pub fn layout_of_ty(db: &dyn HirDatabase, ty: &Ty, krate: CrateId) -> Result<Layout, LayoutError> {
+    let dl = &*db.target_data_layout(krate);
+    let layout = match ty.kind(&Interner) {
+        TyKind::Adt(crate::AdtId(def), subst) => {
+            return db.layout_of_adt(*def, subst.clone(), krate).map(|it| (*it).clone())
+        }
+        TyKind::Scalar(scalar) => match scalar {
+            Scalar::Bool => scalar_layout(1, dl.int_align(1), Some((0, 1))),
+            Scalar::Char => scalar_layout(4, dl.int_align(4), Some((0, 0x10FFFF))),
+            Scalar::Int(it) => {
+                let size = match it {
+                    IntTy::Isize => dl.pointer_size,
+                    IntTy::I8 => 1,
+                    IntTy::I16 => 2,
+                    IntTy::I32 => 4,
+                    IntTy::I64 => 8,
+                    IntTy::I128 => 16,
+                };
+                scalar_layout(size, dl.int_align(size), None)
+            }
+            Scalar::Uint(it) => {
+                let size = match it {
+                    UintTy::Usize => dl.pointer_size,
+                    UintTy::U8 => 1,
+                    UintTy::U16 => 2,
+                    UintTy::U32 => 4,
+                    UintTy::U64 => 8,
+                    UintTy::U128 => 16,
+                };
+                scalar_layout(size, dl.int_align(size), None)
+            }
+            Scalar::Float(FloatTy::F32) => scalar_layout(4, dl.f32_align, None),
+            Scalar::Float(FloatTy::F64) => scalar_layout(8, dl.f64_align, None),
+        },
+        TyKind::Tuple(_, subst) => {
+            let fields = subst
+                .iter(&Interner)
+                .map(|it| layout_of_ty(db, it.assert_ty_ref(&Interner), krate))
+                .collect::<Result<Vec<_>, _>>()?;
+            univariant(dl, &fields, &ReprOptions::default(), None)?
+        }
+        TyKind::Array(elem, len) => {
+            let count = match &len.data(&Interner).value {
+                ConstValue::Concrete(ConcreteConst { interned: ConstScalar::Usize(it) }) => *it,
+                _ => return Err(LayoutError::UnknownArrayLength),
+            };
+            let elem = layout_of_ty(db, elem, krate)?;
+            let size = elem.size.checked_mul(count).ok_or(LayoutError::SizeOverflow)?;
+            Layout {
+                size,
+                align: elem.align,
+                fields: FieldsShape::Array { stride: elem.size, count },
+                variants: Variants::Single,
+                largest_niche: elem.largest_niche.filter(|_| count != 0),
+                uninhabited: elem.uninhabited && count != 0,
+            }
+        }
+        TyKind::Ref(_, _, pointee) => pointer_layout(db, dl, pointee, true),
+        TyKind::Raw(_, pointee) => pointer_layout(db, dl, pointee, false),
+        TyKind::Function(_) => {
+            scalar_layout(dl.pointer_size, dl.pointer_align, Some((1, u128::MAX)))
+        }
+        // Function items are zero-sized.
+        TyKind::FnDef(..) => univariant(dl, &[], &ReprOptions::default(), None)?,
+        TyKind::Never => {
+            Layout { uninhabited: true, ..univariant(dl, &[], &Default::default(), None)? }
+        }
+        TyKind::Closure(id, _) => {
+            let (def, expr) = db.lookup_intern_closure((*id).into());
+            let infer = db.infer(def);
+            let fields = infer
+                .closure_captures(expr)
+                .iter()
+                .map(|capture| {
+                    let ty = &infer[capture.local];
+                    match capture.kind {
+                        CaptureKind::ByValue => layout_of_ty(db, ty, krate),
+                        CaptureKind::ByRef | CaptureKind::ByMutRef => {
+                            Ok(pointer_layout(db, dl, ty, true))
+                        }
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            univariant(dl, &fields, &ReprOptions::default(), None)?
+        }
+        TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) | TyKind::Foreign(_) => {
+            return Err(LayoutError::Unsized)
+        }
+        _ => return Err(LayoutError::HasPlaceholder),
+    };
+    Ok(layout)
+}
+
+pub(crate) fn layout_of_adt_query(
+    db: &dyn HirDatabase,
+    def: AdtId,
+    subst: Substitution,
+    krate: CrateId,
+) -> Result<Arc<Layout>, LayoutError> {
+    let dl = &*db.target_data_layout(krate);
+    let field_layouts = |variant: VariantId| -> Result<Vec<Layout>, LayoutError> {
+        let field_types = db.field_types(variant);
+        variant
+            .variant_data(db.upcast())
+            .fields()
+            .iter()
+            .map(|(id, _)| {
+                let ty = field_types[id].clone().substitute(&Interner, &subst);
+                layout_of_ty(db, &ty, krate)
+            })
+            .collect()
+    };
+    let layout = match def {
+        AdtId::StructId(id) => {
+            let repr = db.struct_data(id).repr.unwrap_or_default();
+            let mut layout = univariant(dl, &field_layouts(id.into())?, &repr, None)?;
+            if let Some(niche) = valid_range_niche(db, def, &layout) {
+                layout.largest_niche = Some(niche);
+            }
+            layout
+        }
+        AdtId::UnionId(id) => union_layout(db, dl, id, field_layouts(id.into())?),
+        AdtId::EnumId(id) => {
+            let variants = db
+                .enum_data(id)
+                .variants
+                .iter()
+                .map(|(local_id, _)| {
+                    field_layouts(hir_def::EnumVariantId { parent: id, local_id }.into())
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            enum_layout(db, dl, id, &variants)?
+        }
+    };
+    Ok(Arc::new(layout))
+}
+
+pub(crate) fn layout_of_adt_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &AdtId,
+    _subst: &Substitution,
+    _krate: &CrateId,
+) -> Result<Arc<Layout>, LayoutError> {
+    Err(LayoutError::RecursiveType)
+}
+
+fn scalar_layout(size: u64, align: u64, valid_range: Option<(u128, u128)>) -> Layout {
+    let niche = Niche { offset: 0, size, valid_start: 0, valid_end: 0 };
+    let niche = valid_range.map(|(valid_start, valid_end)| Niche {
+        valid_start,
+        valid_end: valid_end & niche.max_value(),
+        ..niche
+    });
+    Layout {
+        size,
+        align,
+        fields: FieldsShape::Primitive,
+        variants: Variants::Single,
+        largest_niche: niche,
+        uninhabited: false,
+    }
+}
+
+fn pointer_layout(
+    db: &dyn HirDatabase,
+    dl: &TargetDataLayout,
+    pointee: &Ty,
+    non_null: bool,
+) -> Layout {
+    let data = scalar_layout(dl.pointer_size, dl.pointer_align, non_null.then_some((1, u128::MAX)));
+    let metadata = match pointee_tail(db, pointee) {
+        Some(PointerMetadata::Length) => scalar_layout(dl.pointer_size, dl.pointer_align, None),
+        Some(PointerMetadata::VTable) => {
+            scalar_layout(dl.pointer_size, dl.pointer_align, Some((1, u128::MAX)))
+        }
+        None => return data,
+    };
+    // Neither part of a wide pointer can be too big, so this can't fail.
+    univariant(dl, &[data, metadata], &ReprOptions { c: true, ..Default::default() }, None).unwrap()
+}
+
+enum PointerMetadata {
+    Length,
+    VTable,
+}
+
+/// The metadata of pointers to `ty`, or `None` if they are thin.
+fn pointee_tail(db: &dyn HirDatabase, ty: &Ty) -> Option<PointerMetadata> {
+    let mut ty = ty.clone();
+    // Bound the walk, as structs can contain themselves behind indirection.
+    for _ in 0..32 {
+        ty = match ty.kind(&Interner) {
+            TyKind::Str | TyKind::Slice(_) => return Some(PointerMetadata::Length),
+            TyKind::Dyn(_) => return Some(PointerMetadata::VTable),
+            TyKind::Tuple(_, subst) => {
+                subst.iter(&Interner).last()?.assert_ty_ref(&Interner).clone()
+            }
+            TyKind::Adt(crate::AdtId(AdtId::StructId(id)), subst) => {
+                let variant = VariantId::from(*id);
+                let (last, _) = variant.variant_data(db.upcast()).fields().iter().last()?;
+                db.field_types(variant)[last].clone().substitute(&Interner, subst)
+            }
+            _ => return None,
+        };
+    }
+    None
+}
+
+fn align_to(offset: u64, align: u64) -> u64 {
+    offset.div_ceil(align) * align
+}
+
+/// Lays out `fields` like a struct. If `prefix` is given, as `(size, align)`,
+/// the fields come after it; this is used for the variants of tagged enums.
+fn univariant(
+    dl: &TargetDataLayout,
+    fields: &[Layout],
+    repr: &ReprOptions,
+    prefix: Option<(u64, u64)>,
+) -> Result<Layout, LayoutError> {
+    let effective_align = |field: &Layout| match repr.packed {
+        Some(pack) => cmp::min(pack, field.align),
+        None => field.align,
+    };
+
+    let mut order: Vec<usize> = (0..fields.len()).collect();
+    if !repr.c && repr.packed.is_none() {
+        match prefix {
+            // Put the fields with the biggest alignment first, so that there
+            // is no padding between fields.
+            None => order
+                .sort_by_key(|&i| (fields[i].size != 0, cmp::Reverse(effective_align(&fields[i])))),
+            // After a tag, the fields with the smallest alignment go first, so
+            // that they can use the space up to the next alignment boundary.
+            Some(_) => order.sort_by_key(|&i| effective_align(&fields[i])),
+        }
+    }
+
+    let mut align = if repr.packed.is_some() { 1 } else { dl.aggregate_align };
+    let mut offset = 0;
+    if let Some((prefix_size, prefix_align)) = prefix {
+        offset = prefix_size;
+        align = cmp::max(align, prefix_align);
+    }
+    let mut offsets = vec![0; fields.len()];
+    let mut largest_niche: Option<Niche> = None;
+    let mut uninhabited = false;
+    for &idx in &order {
+        let field = &fields[idx];
+        let field_align = effective_align(field);
+        offset = align_to(offset, field_align);
+        align = cmp::max(align, field_align);
+        offsets[idx] = offset;
+        if let Some(niche) = field.largest_niche {
+            if niche.available() > largest_niche.map_or(0, |it| it.available()) {
+                largest_niche = Some(Niche { offset: offset + niche.offset, ..niche });
+            }
+        }
+        uninhabited |= field.uninhabited;
+        offset = offset.checked_add(field.size).ok_or(LayoutError::SizeOverflow)?;
+    }
+    if let Some(repr_align) = repr.align {
+        align = cmp::max(align, repr_align);
+    }
+    Ok(Layout {
+        size: align_to(offset, align),
+        align,
+        fields: FieldsShape::Arbitrary { offsets },
+        variants: Variants::Single,
+        largest_niche,
+        uninhabited,
+    })
+}
+
+fn union_layout(
+    db: &dyn HirDatabase,
+    dl: &TargetDataLayout,
+    id: UnionId,
+    fields: Vec<Layout>,
+) -> Layout {
+    let repr = db.union_data(id).repr.unwrap_or_default();
+    let mut align = if repr.packed.is_some() { 1 } else { dl.aggregate_align };
+    let mut size = 0;
+    for field in &fields {
+        align =
+            cmp::max(align, repr.packed.map_or(field.align, |pack| cmp::min(pack, field.align)));
+        size = cmp::max(size, field.size);
+    }
+    if let Some(repr_align) = repr.align {
+        align = cmp::max(align, repr_align);
+    }
+    Layout {
+        size: align_to(size, align),
+        align,
+        fields: FieldsShape::Union(fields.len()),
+        variants: Variants::Single,
+        largest_niche: None,
+        uninhabited: false,
+    }
+}
+
+fn enum_layout(
+    db: &dyn HirDatabase,
+    dl: &TargetDataLayout,
+    id: EnumId,
+    variants: &[Vec<Layout>],
+) -> Result<Layout, LayoutError> {
+    let repr = db.enum_data(id).repr.unwrap_or_default();
+    let explicit_repr = repr.c || repr.int.is_some();
+
+    let mut present = variants.iter().filter(|fields| !is_absent(fields));
+    match (present.next(), present.next()) {
+        (None, _) => {
+            return Ok(Layout { uninhabited: true, ..univariant(dl, &[], &repr, None)? });
+        }
+        (Some(fields), None) if !explicit_repr => return univariant(dl, fields, &repr, None),
+        _ => {}
+    }
+
+    let discriminants = enum_discriminants(db, id)?;
+    let tagged = tagged_enum_layout(dl, &repr, variants, &discriminants)?;
+    if explicit_repr {
+        return Ok(tagged);
+    }
+    match niche_filling_enum_layout(dl, &repr, variants)? {
+        Some(niche_filling) if niche_filling.size <= tagged.size => Ok(niche_filling),
+        _ => Ok(tagged),
+    }
+}
+
+/// Whether a variant with the given fields can't be constructed and takes no
+/// space, so that it doesn't need to be represented at all.
+fn is_absent(fields: &[Layout]) -> bool {
+    fields.iter().any(|it| it.uninhabited) && fields.iter().all(|it| it.size == 0)
+}
+
+/// The discriminants of the variants of `id`, in declaration order.
+fn enum_discriminants(db: &dyn HirDatabase, id: EnumId) -> Result<Vec<i128>, LayoutError> {
+    let resolver = id.resolver(db.upcast());
+    let mut next = 0;
+    db.enum_data(id)
+        .variants
+        .iter()
+        .map(|(_, variant)| {
+            let discriminant = match &variant.discriminant {
+                Some(value) => consteval::eval_const_scalar_or_path(db, &resolver, value)
+                    .ok_or(LayoutError::UnknownDiscriminant)?,
+                None => next,
+            };
+            next = discriminant.wrapping_add(1);
+            Ok(discriminant)
+        })
+        .collect()
+}
+
+/// Stores the discriminant in a niche of the only variant that isn't
+/// zero-sized, like `Option<&T>` does.
+fn niche_filling_enum_layout(
+    dl: &TargetDataLayout,
+    repr: &ReprOptions,
+    variants: &[Vec<Layout>],
+) -> Result<Option<Layout>, LayoutError> {
+    let variant_layouts = variants
+        .iter()
+        .map(|fields| univariant(dl, fields, repr, None))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut dataful = variant_layouts.iter().enumerate().filter(|(_, it)| it.size != 0);
+    let (dataful_variant, dataful_layout) = match (dataful.next(), dataful.next()) {
+        (Some(it), None) => it,
+        _ => return Ok(None),
+    };
+    let niche = match dataful_layout.largest_niche {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    // Absent variants at either end don't need a value of the niche.
+    let mut niche_variants =
+        (0..variants.len()).filter(|&idx| idx != dataful_variant && !is_absent(&variants[idx]));
+    let niche_variants = match (niche_variants.next(), niche_variants.next_back()) {
+        (Some(first), last) => first..=last.unwrap_or(first),
+        (None, _) => return Ok(None),
+    };
+    let count = (niche_variants.end() - niche_variants.start() + 1) as u128;
+    let (niche_start, largest_niche) = match niche.reserve(count) {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    let align = variant_layouts.iter().map(|it| it.align).max().unwrap_or(1);
+    Ok(Some(Layout {
+        size: align_to(dataful_layout.size, align),
+        align,
+        fields: FieldsShape::Arbitrary { offsets: vec![niche.offset] },
+        largest_niche: Some(largest_niche),
+        uninhabited: variant_layouts.iter().all(|it| it.uninhabited),
+        variants: Variants::Multiple {
+            tag: niche,
+            tag_encoding: TagEncoding::Niche { dataful_variant, niche_variants, niche_start },
+            variants: variant_layouts,
+        },
+    }))
+}
+
+/// Stores the discriminant of the variant in a tag before the fields.
+fn tagged_enum_layout(
+    dl: &TargetDataLayout,
+    repr: &ReprOptions,
+    variants: &[Vec<Layout>],
+    discriminants: &[i128],
+) -> Result<Layout, LayoutError> {
+    // Variants that can't be constructed don't need a valid tag value.
+    let (min, max) = variants
+        .iter()
+        .zip(discriminants)
+        .filter(|(fields, _)| !fields.iter().any(|it| it.uninhabited))
+        .fold(None, |range, (_, &discr)| match range {
+            Some((min, max)) => Some((cmp::min(min, discr), cmp::max(max, discr))),
+            None => Some((discr, discr)),
+        })
+        .unwrap_or((0, 0));
+    let fits = |size: u64| {
+        let bits = size * 8;
+        if bits >= 128 {
+            true
+        } else if min < 0 {
+            -(1 << (bits - 1)) <= min && max < 1 << (bits - 1)
+        } else {
+            max < 1 << bits
+        }
+    };
+    let tag_size = match repr.int {
+        Some(Either::Left(int)) => match int {
+            BuiltinInt::Isize => dl.pointer_size,
+            BuiltinInt::I8 => 1,
+            BuiltinInt::I16 => 2,
+            BuiltinInt::I32 => 4,
+            BuiltinInt::I64 => 8,
+            BuiltinInt::I128 => 16,
+        },
+        Some(Either::Right(uint)) => match uint {
+            BuiltinUint::Usize => dl.pointer_size,
+            BuiltinUint::U8 => 1,
+            BuiltinUint::U16 => 2,
+            BuiltinUint::U32 => 4,
+            BuiltinUint::U64 => 8,
+            BuiltinUint::U128 => 16,
+        },
+        // At least the size of a C `int`.
+        None if repr.c => [4, 8].iter().copied().find(|&size| fits(size)).unwrap_or(16),
+        None => [1, 2, 4, 8].iter().copied().find(|&size| fits(size)).unwrap_or(16),
+    };
+    let tag_align = dl.int_align(tag_size);
+    let mut prefix = (tag_size, tag_align);
+    if repr.c {
+        // `#[repr(C)]` enums are a tag followed by a union of the variants.
+        let payload_align = variants.iter().flatten().map(|it| it.align).max().unwrap_or(1);
+        prefix.0 = align_to(tag_size, payload_align);
+    }
+    let variant_layouts = variants
+        .iter()
+        .map(|fields| univariant(dl, fields, repr, Some(prefix)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let align = variant_layouts.iter().map(|it| it.align).max().unwrap_or(1);
+    let size = variant_layouts.iter().map(|it| it.size).max().unwrap_or(0);
+    let tag = Niche { offset: 0, size: tag_size, valid_start: 0, valid_end: 0 };
+    let tag = Niche {
+        valid_start: min as u128 & tag.max_value(),
+        valid_end: max as u128 & tag.max_value(),
+        ..tag
+    };
+    Ok(Layout {
+        size: align_to(size, align),
+        align,
+        fields: FieldsShape::Arbitrary { offsets: vec![0] },
+        largest_niche: Some(tag).filter(|it| it.available() != 0),
+        uninhabited: variant_layouts.iter().all(|it| it.uninhabited),
+        variants: Variants::Multiple {
+            tag,
+            tag_encoding: TagEncoding::Direct,
+            variants: variant_layouts,
+        },
+    })
+}
+
+/// The niche given by the `#[rustc_layout_scalar_valid_range_start/end]`
+/// attributes, which `NonNull` and the `NonZero` integers use.
+fn valid_range_niche(db: &dyn HirDatabase, def: AdtId, layout: &Layout) -> Option<Niche> {
+    let attrs = db.attrs(AttrDefId::AdtId(def));
+    let get = |key| {
+        let tt = attrs.by_key(key).tt_values().next()?;
+        match tt.token_trees.first()? {
+            tt::TokenTree::Leaf(tt::Leaf::Literal(lit)) => parse_int(&lit.text),
+            _ => None,
+        }
+    };
+    let start = get("rustc_layout_scalar_valid_range_start");
+    let end = get("rustc_layout_scalar_valid_range_end");
+    if start.is_none() && end.is_none() {
+        return None;
+    }
+    let niche = Niche { offset: 0, size: layout.size, valid_start: 0, valid_end: 0 };
+    Some(Niche {
+        valid_start: start.unwrap_or(0),
+        valid_end: end.unwrap_or_else(|| niche.max_value()),
+        ..niche
+    })
+}
+
+fn parse_int(text: &str) -> Option<u128> {
+    let mut text = text.replace('_', "");
+    // Strip a suffix like `u32`.
+    if let Some(idx) = text.find(&['u', 'i'][..]) {
+        text.truncate(idx);
+    }
+    match text.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
@@ -22,6 +22,7 @@ mod walk;
 pub mod db;
 pub mod diagnostics;
 pub mod display;
+pub mod layout;
 pub mod method_resolution;
 pub mod primitive;
 pub mod traits;
@@ -49,7 +50,10 @@ use crate::{db::HirDatabase, utils::generics};
 pub use autoderef::autoderef;
 pub use builder::TyBuilder;
 pub use chalk_ext::*;
-pub use infer::{could_unify, InferenceDiagnostic, InferenceResult};
+pub use infer::{
+    could_unify, Adjust, Adjustment, AutoBorrow, CaptureKind, CapturedItem, CastError,
+    InferenceDiagnostic, InferenceResult, OverloadedDeref, PointerCast,
+};
 pub use interner::Interner;
 pub use lower::{
     associated_type_shorthand_candidates, callable_item_sig, CallableDefId, ImplTraitLoweringMode,
@@ -6,7 +6,7 @@
 //!
 //! This usually involves resolving names, collecting generic arguments etc.
 use std::cell::{Cell, RefCell};
-use std::{iter, sync::Arc};
+use std::{convert::TryFrom, iter, sync::Arc};
 
 use base_db::CrateId;
 use chalk_ir::{cast::Cast, fold::Shift, interner::HasInterner, Mutability, Safety};
@@ -176,7 +176,9 @@ impl<'a> TyLoweringContext<'a> {
             TypeRef::Array(inner, len) => {
                 let inner_ty = self.lower_ty(inner);
 
-                let const_len = consteval::usize_const(len.as_usize());
+                let len = consteval::eval_const_scalar_or_path(self.db, self.resolver, len)
+                    .and_then(|it| u64::try_from(it).ok());
+                let const_len = consteval::usize_const(len);
 
                 TyKind::Array(inner_ty, const_len).intern(&Interner)
             }
@@ -249,7 +251,14 @@ impl<'a> TyLoweringContext<'a> {
                         let impl_trait_id = ImplTraitId::ReturnTypeImplTrait(func, idx);
                         let opaque_ty_id = self.db.intern_impl_trait_id(impl_trait_id).into();
                         let generics = generics(self.db.upcast(), func.into());
-                        let parameters = generics.bound_vars_subst(self.in_binders);
+                        let parameters = match self.type_param_mode {
+                            TypeParamLoweringMode::Placeholder => {
+                                generics.type_params_subst(self.db)
+                            }
+                            TypeParamLoweringMode::Variable => {
+                                generics.bound_vars_subst(self.in_binders)
+                            }
+                        };
                         TyKind::Alias(AliasTy::Opaque(OpaqueTy {
                             opaque_ty_id,
                             substitution: parameters,
@@ -368,8 +368,19 @@ pub fn def_crates(
     Some(res)
 }
 
+/// The adjustments method resolution applied to the receiver of a method call
+/// to find the method: some autoderefs, then possibly an autoref, and finally
+/// possibly an unsizing of an array to a slice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ReceiverAdjustments {
+    pub(crate) autoderefs: usize,
+    pub(crate) autoref: Option<Mutability>,
+    pub(crate) unsize_array: bool,
+}
+
 /// Look up the method with the given name, returning the actual autoderefed
-/// receiver type (but without autoref applied yet).
+/// receiver type (but without autoref applied yet), and the adjustments that
+/// were needed to get there.
 pub(crate) fn lookup_method(
     ty: &Canonical<Ty>,
     db: &dyn HirDatabase,
@@ -378,8 +389,9 @@ pub(crate) fn lookup_method(
     traits_in_scope: &FxHashSet<TraitId>,
     visible_from_module: Option<ModuleId>,
     name: &Name,
-) -> Option<(Ty, FunctionId)> {
-    iterate_method_candidates(
+) -> Option<(Ty, FunctionId, ReceiverAdjustments)> {
+    let mut slot = None;
+    iterate_method_candidates_impl(
         ty,
         db,
         env,
@@ -388,11 +400,15 @@ pub(crate) fn lookup_method(
         visible_from_module,
         Some(name),
         LookupMode::MethodCall,
-        |ty, f| match f {
-            AssocItemId::FunctionId(f) => Some((ty.clone(), f)),
-            _ => None,
+        &mut |adjustments, ty, item| {
+            assert!(slot.is_none());
+            if let AssocItemId::FunctionId(func) = item {
+                slot = Some((ty.clone(), func, adjustments));
+            }
+            slot.is_some()
         },
-    )
+    );
+    slot
 }
 
 /// Whether we're looking up a dotted method call (like `v.len()`) or a path
@@ -431,7 +447,7 @@ pub fn iterate_method_candidates<T>(
         visible_from_module,
         name,
         mode,
-        &mut |ty, item| {
+        &mut |_, ty, item| {
             assert!(slot.is_none());
             slot = callback(ty, item);
             slot.is_some()
@@ -449,7 +465,7 @@ fn iterate_method_candidates_impl(
     visible_from_module: Option<ModuleId>,
     name: Option<&Name>,
     mode: LookupMode,
-    callback: &mut dyn FnMut(&Ty, AssocItemId) -> bool,
+    callback: &mut dyn FnMut(ReceiverAdjustments, &Ty, AssocItemId) -> bool,
 ) -> bool {
     match mode {
         LookupMode::MethodCall => {
@@ -477,8 +493,23 @@ fn iterate_method_candidates_impl(
             // types*.
 
             let deref_chain = autoderef_method_receiver(db, krate, ty);
+            // The last step of the chain might be an unsizing of an array
+            // instead of a deref (arrays don't implement `Deref`).
+            let unsized_array = match deref_chain.as_slice() {
+                [.., array, slice] => {
+                    matches!(array.value.kind(&Interner), TyKind::Array(..))
+                        && matches!(slice.value.kind(&Interner), TyKind::Slice(..))
+                }
+                _ => false,
+            };
             for i in 0..deref_chain.len() {
+                let adjustments = if unsized_array && i == deref_chain.len() - 1 {
+                    ReceiverAdjustments { autoderefs: i - 1, autoref: None, unsize_array: true }
+                } else {
+                    ReceiverAdjustments { autoderefs: i, autoref: None, unsize_array: false }
+                };
                 if iterate_method_candidates_with_autoref(
+                    adjustments,
                     &deref_chain[i..],
                     db,
                     env.clone(),
@@ -503,13 +534,14 @@ fn iterate_method_candidates_impl(
                 traits_in_scope,
                 visible_from_module,
                 name,
-                callback,
+                &mut |ty, item| callback(ReceiverAdjustments::default(), ty, item),
             )
         }
     }
 }
 
 fn iterate_method_candidates_with_autoref(
+    adjustments: ReceiverAdjustments,
     deref_chain: &[Canonical<Ty>],
     db: &dyn HirDatabase,
     env: Arc<TraitEnvironment>,
@@ -517,7 +549,7 @@ fn iterate_method_candidates_with_autoref(
     traits_in_scope: &FxHashSet<TraitId>,
     visible_from_module: Option<ModuleId>,
     name: Option<&Name>,
-    mut callback: &mut dyn FnMut(&Ty, AssocItemId) -> bool,
+    callback: &mut dyn FnMut(ReceiverAdjustments, &Ty, AssocItemId) -> bool,
 ) -> bool {
     if iterate_method_candidates_by_receiver(
         &deref_chain[0],
@@ -528,7 +560,7 @@ fn iterate_method_candidates_with_autoref(
         traits_in_scope,
         visible_from_module,
         name,
-        &mut callback,
+        &mut |ty, item| callback(adjustments, ty, item),
     ) {
         return true;
     }
@@ -537,6 +569,7 @@ fn iterate_method_candidates_with_autoref(
         value: TyKind::Ref(Mutability::Not, static_lifetime(), deref_chain[0].value.clone())
             .intern(&Interner),
     };
+    let autorefd = ReceiverAdjustments { autoref: Some(Mutability::Not), ..adjustments };
     if iterate_method_candidates_by_receiver(
         &refed,
         deref_chain,
@@ -546,7 +579,7 @@ fn iterate_method_candidates_with_autoref(
         traits_in_scope,
         visible_from_module,
         name,
-        &mut callback,
+        &mut |ty, item| callback(autorefd, ty, item),
     ) {
         return true;
     }
@@ -555,6 +588,7 @@ fn iterate_method_candidates_with_autoref(
         value: TyKind::Ref(Mutability::Mut, static_lifetime(), deref_chain[0].value.clone())
             .intern(&Interner),
     };
+    let autorefd = ReceiverAdjustments { autoref: Some(Mutability::Mut), ..adjustments };
     if iterate_method_candidates_by_receiver(
         &ref_muted,
         deref_chain,
@@ -564,7 +598,7 @@ fn iterate_method_candidates_with_autoref(
         traits_in_scope,
         visible_from_module,
         name,
-        &mut callback,
+        &mut |ty, item| callback(autorefd, ty, item),
     ) {
         return true;
     }
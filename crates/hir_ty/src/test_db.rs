@@ -7,6 +7,7 @@ use std::{
 
 use base_db::{
     salsa, AnchoredPath, CrateId, FileId, FileLoader, FileLoaderDelegate, SourceDatabase, Upcast,
+    VfsPath,
 };
 use hir_def::{db::DefDatabase, ModuleId};
 use hir_expand::db::AstDatabase;
@@ -80,6 +81,9 @@ impl FileLoader for TestDB {
     fn resolve_path(&self, path: AnchoredPath) -> Option<FileId> {
         FileLoaderDelegate(self).resolve_path(path)
     }
+    fn file_path(&self, file_id: FileId) -> Option<VfsPath> {
+        FileLoaderDelegate(self).file_path(file_id)
+    }
     fn relevant_crates(&self, file_id: FileId) -> Arc<FxHashSet<CrateId>> {
         FileLoaderDelegate(self).relevant_crates(file_id)
     }
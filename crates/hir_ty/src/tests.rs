@@ -8,15 +8,18 @@ mod method_resolution;
 mod macros;
 mod display_source_code;
 mod incremental;
+mod consteval;
+mod layout;
+mod closure_captures;
 
 use std::{collections::HashMap, env, sync::Arc};
 
-use base_db::{fixture::WithFixture, FileRange, SourceDatabaseExt};
+use base_db::{fixture::WithFixture, FileRange, ProcMacro, SourceDatabaseExt};
 use expect_test::Expect;
 use hir_def::{
     body::{Body, BodySourceMap, SyntheticSyntax},
     db::DefDatabase,
-    expr::{ExprId, PatId},
+    expr::{ExprId, Pat, PatId},
     item_scope::ItemScope,
     nameres::DefMap,
     src::HasSource,
@@ -33,7 +36,8 @@ use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Registry};
 use tracing_tree::HierarchicalLayer;
 
 use crate::{
-    db::HirDatabase, display::HirDisplay, infer::TypeMismatch, test_db::TestDB, InferenceResult, Ty,
+    db::HirDatabase, display::HirDisplay, infer::TypeMismatch, test_db::TestDB, CaptureKind,
+    InferenceResult, Ty,
 };
 
 // These tests compare the inference results for all expressions in a file
@@ -57,28 +61,42 @@ fn setup_tracing() -> Option<tracing::subscriber::DefaultGuard> {
 }
 
 fn check_types(ra_fixture: &str) {
-    check_impl(ra_fixture, false, true, false)
+    check_impl(ra_fixture, Vec::new(), false, true, false)
+}
+
+/// Like `check_types`, but the fixture crate named `proc_macros` is given the
+/// `proc_macros` expanders.
+fn check_types_with_proc_macros(ra_fixture: &str, proc_macros: Vec<ProcMacro>) {
+    check_impl(ra_fixture, proc_macros, false, true, false)
 }
 
 fn check_types_source_code(ra_fixture: &str) {
-    check_impl(ra_fixture, false, true, true)
+    check_impl(ra_fixture, Vec::new(), false, true, true)
 }
 
 fn check_no_mismatches(ra_fixture: &str) {
-    check_impl(ra_fixture, true, false, false)
+    check_impl(ra_fixture, Vec::new(), true, false, false)
 }
 
 fn check(ra_fixture: &str) {
-    check_impl(ra_fixture, false, false, false)
+    check_impl(ra_fixture, Vec::new(), false, false, false)
 }
 
-fn check_impl(ra_fixture: &str, allow_none: bool, only_types: bool, display_source: bool) {
+fn check_impl(
+    ra_fixture: &str,
+    proc_macros: Vec<ProcMacro>,
+    allow_none: bool,
+    only_types: bool,
+    display_source: bool,
+) {
     let _tracing = setup_tracing();
-    let (db, files) = TestDB::with_many_files(ra_fixture);
+    let (db, files) = TestDB::with_many_files_and_proc_macros(ra_fixture, proc_macros);
 
     let mut had_annotations = false;
     let mut mismatches = HashMap::new();
     let mut types = HashMap::new();
+    let mut adjustments = HashMap::new();
+    let mut captures = HashMap::new();
     for (file_id, annotations) in db.extract_annotations() {
         for (range, expected) in annotations {
             let file_range = FileRange { file_id, range };
@@ -88,6 +106,16 @@ fn check_impl(ra_fixture: &str, allow_none: bool, only_types: bool, display_sour
                 types.insert(file_range, expected.trim_start_matches("type: ").to_string());
             } else if expected.starts_with("expected") {
                 mismatches.insert(file_range, expected);
+            } else if expected.starts_with("adjustments:") {
+                adjustments.insert(
+                    file_range,
+                    expected.trim_start_matches("adjustments:").trim_start().to_string(),
+                );
+            } else if expected.starts_with("captures:") {
+                captures.insert(
+                    file_range,
+                    expected.trim_start_matches("captures:").trim_start().to_string(),
+                );
             } else {
                 panic!("unexpected annotation: {}", expected);
             }
@@ -122,7 +150,7 @@ fn check_impl(ra_fixture: &str, allow_none: bool, only_types: bool, display_sour
     });
     let mut unexpected_type_mismatches = String::new();
     for def in defs {
-        let (_body, body_source_map) = db.body_with_source_map(def);
+        let (body, body_source_map) = db.body_with_source_map(def);
         let inference_result = db.infer(def);
 
         for (pat, ty) in inference_result.type_of_pat.iter() {
@@ -155,6 +183,34 @@ fn check_impl(ra_fixture: &str, allow_none: bool, only_types: bool, display_sour
                 };
                 assert_eq!(actual, expected);
             }
+            if let Some(expected) = adjustments.remove(&range) {
+                let actual = inference_result
+                    .expr_adjustments(expr)
+                    .iter()
+                    .map(|adjustment| format!("{:?}", adjustment.kind))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                assert_eq!(actual, expected);
+            }
+            if let Some(expected) = captures.remove(&range) {
+                let actual = inference_result
+                    .closure_captures(expr)
+                    .iter()
+                    .map(|capture| {
+                        let name = match &body[capture.local] {
+                            Pat::Bind { name, .. } => name.to_string(),
+                            _ => "?".to_string(),
+                        };
+                        match capture.kind {
+                            CaptureKind::ByRef => format!("&{}", name),
+                            CaptureKind::ByMutRef => format!("&mut {}", name),
+                            CaptureKind::ByValue => format!("move {}", name),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                assert_eq!(actual, expected);
+            }
         }
 
         for (pat, mismatch) in inference_result.pat_type_mismatches() {
@@ -212,6 +268,18 @@ fn check_impl(ra_fixture: &str, allow_none: bool, only_types: bool, display_sour
             format_to!(buf, "{:?}: type {}\n", t.0.range, t.1);
         }
     }
+    if !adjustments.is_empty() {
+        format_to!(buf, "Unchecked adjustments annotations:\n");
+        for t in adjustments {
+            format_to!(buf, "{:?}: adjustments {}\n", t.0.range, t.1);
+        }
+    }
+    if !captures.is_empty() {
+        format_to!(buf, "Unchecked captures annotations:\n");
+        for t in captures {
+            format_to!(buf, "{:?}: captures {}\n", t.0.range, t.1);
+        }
+    }
     assert!(buf.is_empty(), "{}", buf);
 }
 
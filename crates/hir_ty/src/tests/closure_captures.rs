@@ -0,0 +1,120 @@
+use super::check;
+
+#[test]
+fn capture_kinds() {
+    check(
+        r#"
+//- minicore: copy
+struct S;
+impl S {
+    fn get(&self) {}
+    fn set(&mut self) {}
+    fn consume(self) {}
+}
+fn test(a: S, mut b: S, c: S, mut d: i32, e: i32) {
+    let _f = || { a.get(); b.set(); c.consume(); d += e; };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: &a, &mut b, move c, &mut d, &e
+}
+"#,
+    );
+}
+
+#[test]
+fn capture_through_references_and_fields() {
+    check(
+        r#"
+//- minicore: copy
+struct S { f: i32, g: String }
+struct String;
+fn test(r: &S, m: &mut S, s: S, t: S, mut u: S) {
+    let _f = || { let _x = r.g; m.f = 1; let _y = s.f; let _z = t.g; u.f = 2; };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: &r, &mut m, &s, move t, &mut u
+}
+"#,
+    );
+}
+
+#[test]
+fn capture_by_patterns() {
+    check(
+        r#"
+//- minicore: copy, option
+struct S;
+fn test(a: Option<S>, b: Option<S>, mut c: Option<S>, d: &Option<S>) {
+    let _f = || { if let Some(_x) = a {} };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: move a
+    let _g = || { if let Some(ref _x) = b {} };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: &b
+    let _h = || { match c { Some(ref mut _x) => {}, None => {} } };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: &mut c
+    let _i = || { if let Some(_x) = d {} };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: &d
+}
+"#,
+    );
+}
+
+#[test]
+fn move_closures_and_nesting() {
+    check(
+        r#"
+//- minicore: copy
+struct S;
+fn test(a: S, b: i32, mut c: S) {
+    let _f = move || { let _x = &a; b };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: move a, move b
+    let _g = || { let _h = move || &c; };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures: move c
+    let _i = || { let _j = || c = S; };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^ captures: &mut c
+}
+"#,
+    );
+}
+
+#[test]
+fn locals_of_the_closure_are_not_captured() {
+    check(
+        r#"
+//- minicore: copy
+fn test(a: i32) {
+    let _f = |x: i32| { let y = x; let a = y; a };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ captures:
+    let _g = || a;
+           //^^^^ captures: &a
+}
+"#,
+    );
+}
+
+#[test]
+fn calling_closures() {
+    check(
+        r#"
+//- minicore: fn, copy
+fn test() {
+    let mut n = 0;
+    let mut inc = || n += 1;
+    let _f = || inc();
+           //^^^^^^^^ captures: &mut inc
+    let _g = || { let h = || n; h() };
+           //^^^^^^^^^^^^^^^^^^^^^^^^ captures: &n
+}
+"#,
+    );
+}
+
+#[test]
+fn capture_of_opaque_type() {
+    check(
+        r#"
+//- minicore: copy, iterators
+fn make() -> impl Iterator<Item = ()> { loop {} }
+fn test() {
+    let it = make();
+    let _f = || { let _x = it; };
+           //^^^^^^^^^^^^^^^^^^^ captures: move it
+}
+"#,
+    );
+}
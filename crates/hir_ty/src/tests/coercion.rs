@@ -507,3 +507,42 @@ fn main() {
         "#,
     );
 }
+
+#[test]
+fn coerce_adjustments() {
+    check(
+        r#"
+//- minicore: coerce_unsized, deref
+struct Foo;
+struct Wrapper(Foo);
+impl core::ops::Deref for Wrapper {
+    type Target = Foo;
+    fn deref(&self) -> &Foo { loop {} }
+}
+fn takes_ref(_: &Foo) {}
+fn takes_slice(_: &[u8]) {}
+fn takes_ptr(_: *const Foo) {}
+fn takes_fn(_: fn()) {}
+fn foo() {}
+
+fn test(w: &Wrapper, r: &Foo, m: &mut Foo, p: *mut Foo) {
+    takes_ref(w);
+           // ^ adjustments: Deref(None), Deref(Some(OverloadedDeref(Not))), Borrow(Ref(Not))
+    takes_ref(r);
+           // ^ adjustments:
+    takes_ref(m);
+           // ^ adjustments: Deref(None), Borrow(Ref(Not))
+    takes_slice(&[1, 2]);
+             // ^^^^^^^ adjustments: Deref(None), Borrow(Ref(Not)), Pointer(Unsize)
+    takes_ptr(r);
+           // ^ adjustments: Deref(None), Borrow(RawPtr(Not))
+    takes_ptr(p);
+           // ^ adjustments: Pointer(MutToConstPointer)
+    takes_fn(foo);
+          // ^^^ adjustments: Pointer(ReifyFnPointer)
+    let _: Foo = return;
+              // ^^^^^^ adjustments: NeverToAny
+}
+"#,
+    );
+}
@@ -0,0 +1,224 @@
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, ModuleDefId};
+
+use crate::{
+    consteval::{ComputedExpr, ConstEvalError},
+    db::HirDatabase,
+    test_db::TestDB,
+};
+
+use super::check_types;
+
+fn eval_goal(ra_fixture: &str) -> Result<ComputedExpr, ConstEvalError> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let const_id = scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::ConstId(it) => {
+                let name = db.const_data(it).name.clone()?;
+                if name.to_string() == "GOAL" {
+                    Some(it)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .expect("no const named `GOAL` found in the fixture");
+    db.const_eval(const_id)
+}
+
+fn check_number(ra_fixture: &str, answer: i128) {
+    let value = eval_goal(ra_fixture).unwrap();
+    match &value {
+        ComputedExpr::Literal(hir_def::expr::Literal::Int(v, _)) => assert_eq!(*v, answer),
+        ComputedExpr::Literal(hir_def::expr::Literal::Uint(v, _)) => {
+            assert_eq!(*v as i128, answer)
+        }
+        _ => panic!("expected a number, got {:?}", value),
+    }
+}
+
+fn check_fail(ra_fixture: &str, error: ConstEvalError) {
+    assert_eq!(eval_goal(ra_fixture), Err(error));
+}
+
+#[test]
+fn literals_and_arithmetic() {
+    check_number(r#"const GOAL: usize = 2 + 2;"#, 4);
+    check_number(r#"const GOAL: i32 = 7 - 10 * 2 / 4 % 3;"#, 5);
+    check_number(r#"const GOAL: u8 = (1 << 4) | 0b0011 & 0b0110 ^ 1;"#, 19);
+    check_number(r#"const GOAL: i64 = -(3 - 10);"#, 7);
+}
+
+#[test]
+fn paths_to_other_consts_and_statics() {
+    check_number(
+        r#"
+const A: usize = 4 + 4;
+static B: usize = A * 2;
+const GOAL: usize = A + B;
+"#,
+        24,
+    );
+    check_number(
+        r#"
+mod m {
+    pub const SIZE: u32 = 3;
+}
+const GOAL: u32 = m::SIZE * m::SIZE;
+"#,
+        9,
+    );
+}
+
+#[test]
+fn casts() {
+    check_number(r#"const GOAL: u8 = 300u32 as u8;"#, 44);
+    check_number(r#"const GOAL: i8 = 255u8 as i8;"#, -1);
+    check_number(r#"const GOAL: usize = true as usize + (-1i32) as u16 as usize;"#, 65536);
+}
+
+#[test]
+fn blocks_and_control_flow() {
+    check_number(
+        r#"
+const GOAL: usize = {
+    let a = 5;
+    let (b, c) = (a * 2, 3);
+    if b > 7 && c == 3 { b + c } else { 0 }
+};
+"#,
+        13,
+    );
+    check_number(
+        r#"
+const GOAL: u32 = {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < 10 {
+        i += 1;
+        if i % 2 == 0 {
+            continue;
+        }
+        sum += i;
+    }
+    sum
+};
+"#,
+        25,
+    );
+    check_number(
+        r#"
+const GOAL: i32 = match 4 {
+    0 | 1 => 10,
+    x @ (2 | 3 | 4) if x != 3 => x * 100,
+    _ => 0,
+};
+"#,
+        400,
+    );
+}
+
+#[test]
+fn const_fn_calls() {
+    check_number(
+        r#"
+const fn double(x: usize) -> usize {
+    x * 2
+}
+const fn fact(n: u64) -> u64 {
+    if n == 0 {
+        return 1;
+    }
+    n * fact(n - 1)
+}
+const GOAL: u64 = fact(5) + double(3) as u64;
+"#,
+        126,
+    );
+}
+
+#[test]
+fn size_of_intrinsic() {
+    check_number(
+        r#"
+mod intrinsics {
+    extern "rust-intrinsic" {
+        pub fn size_of<T>() -> usize;
+    }
+}
+mod mem {
+    pub const fn size_of<T>() -> usize {
+        unsafe { crate::intrinsics::size_of::<T>() }
+    }
+}
+struct S { a: u8, b: u32 }
+const GOAL: usize = mem::size_of::<u64>() + mem::size_of::<[u16; 3]>() + mem::size_of::<S>();
+"#,
+        22,
+    );
+}
+
+#[test]
+fn errors() {
+    check_fail(
+        r#"const GOAL: u8 = 255 + 1;"#,
+        ConstEvalError::Panic("attempt to convert with overflow".to_string()),
+    );
+    check_fail(
+        r#"const GOAL: u8 = 255u8 + 1;"#,
+        ConstEvalError::Panic("attempt to add with overflow".to_string()),
+    );
+    check_fail(
+        r#"const GOAL: i32 = 1 / 0;"#,
+        ConstEvalError::Panic("attempt to divide by zero".to_string()),
+    );
+    check_fail(
+        r#"
+const A: usize = GOAL;
+const GOAL: usize = A;
+"#,
+        ConstEvalError::Loop,
+    );
+    check_fail(
+        r#"
+fn not_const() -> usize { 1 }
+const GOAL: usize = not_const();
+"#,
+        ConstEvalError::NotSupported("call of a non-const fn"),
+    );
+}
+
+#[test]
+fn array_lengths() {
+    check_types(
+        r#"
+const SIZE: usize = 4 + 4;
+const fn twice(n: usize) -> usize { n * 2 }
+fn f() {
+    let a: [u8; SIZE] = [0; SIZE];
+    a;
+  //^ [u8; 8]
+    let b = [0u8; SIZE * 2];
+    b;
+  //^ [u8; 16]
+    let c = [1; twice(SIZE) + 1];
+    c;
+  //^ [i32; 17]
+    let d = [0; { let n = 3; n * n }];
+    d;
+  //^ [i32; 9]
+    let e: [u8; SIZE * 2] = [0; 16];
+    e;
+  //^ [u8; 16]
+    let g: [u8; (SIZE - 1) << 1] = [0; 14];
+    g;
+  //^ [u8; 14]
+}
+"#,
+    );
+}
@@ -0,0 +1,206 @@
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, AdtId, ModuleDefId};
+
+use crate::{
+    db::HirDatabase,
+    layout::{layout_of_ty, Layout, LayoutError, TargetDataLayout},
+    test_db::TestDB,
+    Interner, Substitution, TyKind,
+};
+
+/// Computes the layout of the item named `Goal`, which is either an ADT or a
+/// type alias.
+fn eval_goal(ra_fixture: &str) -> Result<Layout, LayoutError> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let ty = scope
+        .declarations()
+        .find_map(|def| {
+            let (name, ty) = match def {
+                ModuleDefId::AdtId(adt) => {
+                    let name = match adt {
+                        AdtId::StructId(it) => db.struct_data(it).name.clone(),
+                        AdtId::UnionId(it) => db.union_data(it).name.clone(),
+                        AdtId::EnumId(it) => db.enum_data(it).name.clone(),
+                    };
+                    (name, TyKind::Adt(crate::AdtId(adt), Substitution::empty(&Interner)))
+                }
+                ModuleDefId::TypeAliasId(it) => (
+                    db.type_alias_data(it).name.clone(),
+                    db.ty(it.into()).skip_binders().kind(&Interner).clone(),
+                ),
+                _ => return None,
+            };
+            (name.to_string() == "Goal").then(|| ty.intern(&Interner))
+        })
+        .expect("no item named `Goal` found in the fixture");
+    layout_of_ty(&db, &ty, module_id.krate())
+}
+
+fn check_size_and_align(ra_fixture: &str, size: u64, align: u64) {
+    let layout = eval_goal(ra_fixture).unwrap();
+    assert_eq!((layout.size, layout.align), (size, align));
+}
+
+fn check_offsets(ra_fixture: &str, offsets: &[u64]) {
+    let layout = eval_goal(ra_fixture).unwrap();
+    let actual: Vec<_> = (0..offsets.len()).map(|idx| layout.fields.offset(idx).unwrap()).collect();
+    assert_eq!(actual, offsets);
+}
+
+fn check_niches(ra_fixture: &str, niches: u128) {
+    let layout = eval_goal(ra_fixture).unwrap();
+    assert_eq!(layout.largest_niche.map_or(0, |it| it.available()), niches);
+}
+
+#[test]
+fn data_layout_parsing() {
+    let dl =
+        TargetDataLayout::parse("e-m:e-p:32:32-Fi8-i64:64-v128:64:128-a:0:32-n32-S64").unwrap();
+    assert_eq!(dl.pointer_size, 4);
+    assert_eq!(dl.int_aligns, [1, 2, 4, 8, 4]);
+    assert_eq!(dl.aggregate_align, 1);
+    assert!(!dl.big_endian);
+    let dl = TargetDataLayout::parse("E-m:e-i64:64-n32:64-S128").unwrap();
+    assert!(dl.big_endian);
+    assert_eq!(dl.pointer_size, 8);
+}
+
+#[test]
+fn primitives() {
+    check_size_and_align(r#"type Goal = u8;"#, 1, 1);
+    check_size_and_align(r#"type Goal = i128;"#, 16, 16);
+    check_size_and_align(r#"type Goal = usize;"#, 8, 8);
+    check_size_and_align(r#"type Goal = f32;"#, 4, 4);
+    check_size_and_align(r#"type Goal = char;"#, 4, 4);
+    check_size_and_align(r#"type Goal = ();"#, 0, 1);
+    check_size_and_align(r#"type Goal = fn(u8) -> u8;"#, 8, 8);
+    check_niches(r#"type Goal = bool;"#, 254);
+    check_niches(r#"type Goal = char;"#, (1 << 32) - 0x110000);
+}
+
+#[test]
+fn pointers() {
+    check_size_and_align(r#"type Goal = &'static u8;"#, 8, 8);
+    check_size_and_align(r#"type Goal = *const u8;"#, 8, 8);
+    check_size_and_align(r#"type Goal = &'static [u8];"#, 16, 8);
+    check_size_and_align(r#"type Goal = &'static str;"#, 16, 8);
+    check_size_and_align(r#"trait Tr {} type Goal = &'static dyn Tr;"#, 16, 8);
+    check_size_and_align(r#"struct S { a: u8, b: [u8] } type Goal = *mut S;"#, 16, 8);
+    check_niches(r#"type Goal = &'static u8;"#, 1);
+    check_niches(r#"type Goal = *const u8;"#, 0);
+    assert_eq!(eval_goal(r#"type Goal = str;"#), Err(LayoutError::Unsized));
+}
+
+#[test]
+fn tuples_and_arrays() {
+    check_size_and_align(r#"type Goal = (u8, u32, u16);"#, 8, 4);
+    check_offsets(r#"type Goal = (u8, u32, u16);"#, &[6, 0, 4]);
+    check_size_and_align(r#"type Goal = [u16; 5];"#, 10, 2);
+    check_size_and_align(r#"const N: usize = 2 * 3; type Goal = [u32; N];"#, 24, 4);
+    check_size_and_align(r#"type Goal = [u64; 0];"#, 0, 8);
+}
+
+#[test]
+fn structs() {
+    check_size_and_align(r#"struct Goal { a: u8, b: u32, c: u8 }"#, 8, 4);
+    check_offsets(r#"struct Goal { a: u8, b: u32, c: u8 }"#, &[4, 0, 5]);
+    check_size_and_align(r#"struct Goal(u16, (u8, u64));"#, 24, 8);
+    check_size_and_align(r#"struct Goal;"#, 0, 1);
+    check_niches(r#"struct Goal { a: u32, b: bool }"#, 254);
+}
+
+#[test]
+fn repr_attributes() {
+    check_size_and_align(r#"#[repr(C)] struct Goal { a: u8, b: u32, c: u8 }"#, 12, 4);
+    check_offsets(r#"#[repr(C)] struct Goal { a: u8, b: u32, c: u8 }"#, &[0, 4, 8]);
+    check_size_and_align(r#"#[repr(packed)] struct Goal { a: u8, b: u32 }"#, 5, 1);
+    check_offsets(r#"#[repr(packed)] struct Goal { a: u8, b: u32 }"#, &[0, 1]);
+    check_size_and_align(r#"#[repr(C, packed(2))] struct Goal { a: u8, b: u32 }"#, 6, 2);
+    check_size_and_align(r#"#[repr(align(16))] struct Goal { a: u8 }"#, 16, 16);
+    check_size_and_align(r#"#[repr(transparent)] struct Goal { a: u32, b: () }"#, 4, 4);
+    check_size_and_align(r#"#[repr(C)] union Goal { a: u8, b: u32, c: [u8; 7] }"#, 8, 4);
+}
+
+#[test]
+fn enums() {
+    check_size_and_align(r#"enum Goal {}"#, 0, 1);
+    check_size_and_align(r#"enum Goal { A, B, C }"#, 1, 1);
+    check_niches(r#"enum Goal { A, B, C }"#, 253);
+    check_size_and_align(r#"enum Goal { A(u32) }"#, 4, 4);
+    check_size_and_align(r#"enum Goal { A(u8), B(u32) }"#, 8, 4);
+    check_size_and_align(r#"#[repr(u32)] enum Goal { A, B }"#, 4, 4);
+    check_size_and_align(r#"#[repr(C)] enum Goal { A(u8), B(u16) }"#, 8, 4);
+    check_size_and_align(r#"#[repr(u8)] enum Goal { A(u8), B(u16) }"#, 4, 2);
+    assert!(eval_goal(r#"enum Goal { A(Goal) }"#).is_err());
+}
+
+#[test]
+fn enum_niches() {
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<&'static u32>;
+"#,
+        8,
+        8,
+    );
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<Option<bool>>;
+"#,
+        1,
+        1,
+    );
+    check_niches(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<Option<bool>>;
+"#,
+        252,
+    );
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<u32>;
+"#,
+        8,
+        4,
+    );
+    check_size_and_align(
+        r#"
+#[rustc_layout_scalar_valid_range_start(1)]
+struct NonZeroU64(u64);
+enum Option<T> { None, Some(T) }
+type Goal = Option<NonZeroU64>;
+"#,
+        8,
+        8,
+    );
+    check_size_and_align(r#"enum Goal { A, B(char), C }"#, 4, 4);
+}
+
+#[test]
+fn enum_discriminants() {
+    check_size_and_align(r#"enum Goal { A = 255, B }"#, 2, 2);
+    check_niches(r#"enum Goal { A = 1, B = 3 }"#, 253);
+    check_size_and_align(r#"const X: usize = 1 << 16; enum Goal { A = X, B }"#, 4, 4);
+    check_size_and_align(r#"#[repr(C)] enum Goal { A = 5000000000 }"#, 8, 8);
+    check_size_and_align(r#"enum Goal { A = -1, B = 127 }"#, 1, 1);
+    check_size_and_align(r#"enum Goal { A = -129, B }"#, 2, 2);
+    check_size_and_align(r#"enum Goal { A = 1 << 8, B }"#, 2, 2);
+    assert_eq!(eval_goal(r#"enum Goal { A = unknown, B }"#), Err(LayoutError::UnknownDiscriminant));
+}
+
+#[test]
+fn enum_uninhabited_variants() {
+    check_size_and_align(r#"enum Never {} enum Goal { A(u32), B(Never) }"#, 4, 4);
+    assert!(
+        eval_goal(r#"enum Never {} enum Goal { A(Never), B((), Never) }"#).unwrap().uninhabited
+    );
+    check_niches(r#"enum Never {} enum Goal { A(Never), B(bool), C }"#, 253);
+    check_niches(r#"enum Never {} enum Goal { A(u8, Never), B, C }"#, 254);
+}
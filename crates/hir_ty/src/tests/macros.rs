@@ -1,7 +1,12 @@
+use std::sync::Arc;
+
+use base_db::{
+    Env, ProcMacro, ProcMacroExpander, ProcMacroExpansion, ProcMacroKind, ProcMacroSpans,
+};
 use expect_test::expect;
 use test_utils::{bench, bench_fixture, skip_slow_tests};
 
-use super::{check_infer, check_types};
+use super::{check_infer, check_no_mismatches, check_types, check_types_with_proc_macros};
 
 #[test]
 fn cfg_impl_def() {
@@ -630,6 +635,182 @@ fn infer_local_macro() {
     );
 }
 
+#[test]
+fn infer_macro_local_variable_hygiene() {
+    check_types(
+        r#"
+macro_rules! m {
+    ($e:expr) => {{ let x = 0u8; $e }};
+}
+fn main() {
+    let x = 1u32;
+    let a = m!(x);
+    a;
+} //^ u32
+"#,
+    );
+}
+
+#[test]
+fn infer_macro_local_variable_not_visible_to_caller() {
+    check_types(
+        r#"
+macro_rules! m {
+    ($e:expr) => { let x = $e; };
+}
+fn main() {
+    let x = 1u32;
+    m!(0u8);
+    x;
+} //^ u32
+"#,
+    );
+}
+
+#[test]
+fn infer_local_macro_sees_locals_at_definition() {
+    check_types(
+        r#"
+fn main() {
+    let y = 1u32;
+    macro_rules! m {
+        () => {{ let x = 0u8; (x, y) }};
+    }
+    let x = 1i32;
+    let a = m!();
+    a;
+} //^ (u8, u32)
+"#,
+    );
+}
+
+#[test]
+fn infer_proc_macro_local_variable_call_site_hygiene() {
+    /// Expands to the identifier `x`, which has no span in the input.
+    #[derive(Debug)]
+    struct UseXExpander;
+
+    impl ProcMacroExpander for UseXExpander {
+        fn expand(
+            &self,
+            _: &tt::Subtree,
+            _: Option<&tt::Subtree>,
+            _: &Env,
+            _: &ProcMacroSpans,
+        ) -> Result<ProcMacroExpansion, tt::ExpansionError> {
+            let x = tt::Leaf::Ident(tt::Ident { text: "x".into(), id: tt::TokenId::unspecified() });
+            let subtree = tt::Subtree { delimiter: None, token_trees: vec![x.into()] };
+            Ok(ProcMacroExpansion { subtree, diagnostics: Vec::new() })
+        }
+    }
+
+    check_types_with_proc_macros(
+        r#"
+//- /proc_macros.rs crate:proc_macros
+#[proc_macro]
+pub fn use_x(item: TokenStream) -> TokenStream { item }
+//- /main.rs crate:main deps:proc_macros
+use proc_macros::use_x;
+
+fn main() {
+    let x = 1u32;
+    let a = use_x!();
+    a;
+} //^ u32
+"#,
+        vec![ProcMacro {
+            name: "use_x".into(),
+            kind: ProcMacroKind::FuncLike,
+            expander: Arc::new(UseXExpander),
+        }],
+    );
+}
+
+#[test]
+fn infer_attr_macro_on_assoc_items() {
+    check_infer(
+        r#"
+#[rustc_builtin_macro]
+pub macro global_allocator($item:item) {}
+
+struct S;
+impl S {
+    #[global_allocator]
+    fn foo(&self) -> u32 { 92 }
+}
+trait Tr {
+    #[global_allocator]
+    fn bar() -> u8 { 0 }
+}
+fn f() {
+    #[global_allocator]
+    fn baz() -> u16 { 1 }
+    S.foo();
+}
+"#,
+        expect![[r#"
+            !11..14 '{0}': u8
+            !12..13 '0': u8
+            !12..15 '{1}': u16
+            !13..14 '1': u16
+            !7..11 'self': &S
+            !17..21 '{92}': u32
+            !18..20 '92': u32
+            212..278 '{     ...o(); }': ()
+            268..269 'S': S
+            268..275 'S.foo()': u32
+        "#]],
+    );
+}
+
+#[test]
+fn registered_tool_attr_on_assoc_item_is_not_expanded() {
+    check_infer(
+        r#"
+#![register_tool(tool)]
+mod tool {
+    #[rustc_builtin_macro]
+    pub macro global_allocator($item:item) {}
+}
+
+struct S;
+impl S {
+    #[tool::global_allocator]
+    fn foo(&self) -> u32 { 92 }
+}
+"#,
+        expect![[r#"
+            172..176 'self': &S
+            185..191 '{ 92 }': u32
+            187..189 '92': u32
+        "#]],
+    );
+}
+
+#[test]
+fn infer_unexpandable_attr_macro_on_assoc_item() {
+    check_types(
+        r#"
+//- /main.rs crate:main deps:macros
+struct S;
+impl S {
+    #[macros::attr]
+    fn foo(&self) -> u32 { 92 }
+}
+fn f() {
+    S.foo();
+} //^^^^^^^ u32
+
+//- /macros.rs crate:macros
+pub struct TokenStream;
+#[proc_macro_attribute]
+pub fn attr(_args: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_local_inner_macros() {
     check_types(
@@ -963,13 +1144,78 @@ fn infer_builtin_macros_env() {
         }
         "#,
         expect![[r#"
-            !0..22 '"__RA_...TED__"': &str
+            !0..5 '"bar"': &str
             62..90 '{     ...o"); }': ()
             72..73 'x': &str
         "#]],
     );
 }
 
+#[test]
+fn infer_builtin_macros_include_out_dir() {
+    check_types(
+        r#"
+//- /main.rs env:OUT_DIR=/out
+#[rustc_builtin_macro]
+macro_rules! include {() => {}}
+#[rustc_builtin_macro]
+macro_rules! concat {() => {}}
+#[rustc_builtin_macro]
+macro_rules! env {() => {}}
+
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+fn main() {
+    generated();
+} //^^^^^^^^^^^ u32
+
+//- /out/generated.rs
+fn generated() -> u32 {0}
+"#,
+    );
+}
+
+#[test]
+fn infer_builtin_macros_token_tree_name() {
+    check_types(
+        r#"
+#[rustc_builtin_macro(concat_bytes)]
+macro_rules! my_concat_bytes {() => {}}
+
+fn main() {
+    let x = my_concat_bytes!(b'a', [98]);
+    x;
+} //^ &[u8; 2]
+"#,
+    );
+}
+
+#[test]
+fn derive_bodies_type_check() {
+    check_no_mismatches(
+        r#"
+//- minicore: derive, copy, default, eq, ord, option
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+enum E {
+    A(u8, u32),
+    B { x: i32 },
+    #[default]
+    C,
+}
+
+#[derive(Clone, Default, PartialEq, PartialOrd)]
+struct S {
+    e: E,
+    f: (u8, u8),
+}
+
+fn test(s: S) {
+    let b: bool = s == s.clone();
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_derive_clone_simple() {
     check_types(
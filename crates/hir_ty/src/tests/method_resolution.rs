@@ -1,6 +1,6 @@
 use expect_test::expect;
 
-use super::{check_infer, check_types};
+use super::{check, check_infer, check_types};
 
 #[test]
 fn infer_slice_method() {
@@ -1349,3 +1349,43 @@ impl<'a, T> IntoIterator for &'a [T] {
     "#,
     );
 }
+
+#[test]
+fn receiver_adjustments() {
+    check(
+        r#"
+//- minicore: deref, slice
+struct Foo;
+impl Foo {
+    fn by_ref(&self) {}
+    fn by_mut(&mut self) {}
+    fn by_value(self) {}
+}
+struct Wrapper(Foo);
+impl core::ops::Deref for Wrapper {
+    type Target = Foo;
+    fn deref(&self) -> &Foo { loop {} }
+}
+struct Bar { foo: Foo }
+trait SliceExt { fn first(&self); }
+impl<T> SliceExt for [T] { fn first(&self) {} }
+
+fn test(mut foo: Foo, r: &Foo, w: Wrapper, b: &&Bar, a: [u8; 2]) {
+    foo.by_ref();
+ // ^^^ adjustments: Borrow(Ref(Not))
+    foo.by_mut();
+ // ^^^ adjustments: Borrow(Ref(Mut))
+    r.by_ref();
+ // ^ adjustments:
+    w.by_ref();
+ // ^ adjustments: Deref(Some(OverloadedDeref(Not))), Borrow(Ref(Not))
+    b.foo;
+ // ^ adjustments: Deref(None), Deref(None)
+    a.first();
+ // ^ adjustments: Borrow(Ref(Not)), Pointer(Unsize)
+    foo.by_value();
+ // ^^^ adjustments:
+}
+"#,
+    );
+}
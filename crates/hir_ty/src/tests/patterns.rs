@@ -1,6 +1,6 @@
 use expect_test::expect;
 
-use super::{check, check_infer, check_infer_with_mismatches, check_types};
+use super::{check, check_infer, check_infer_with_mismatches, check_no_mismatches, check_types};
 
 #[test]
 fn infer_pattern() {
@@ -55,6 +55,7 @@ fn infer_pattern() {
             139..140 'g': {unknown}
             143..144 'e': {unknown}
             157..204 'if let...     }': ()
+            160..175 'let [val] = opt': bool
             164..169 '[val]': [{unknown}]
             165..168 'val': {unknown}
             172..175 'opt': [{unknown}]
@@ -62,6 +63,7 @@ fn infer_pattern() {
             190..191 'h': {unknown}
             194..197 'val': {unknown}
             210..236 'if let...rue {}': ()
+            213..233 'let x ... &true': bool
             217..225 'x @ true': &bool
             221..225 'true': bool
             221..225 'true': bool
@@ -111,36 +113,42 @@ fn infer_literal_pattern() {
             37..38 'x': &i32
             46..208 '{     ...) {} }': ()
             52..75 'if let...y() {}': ()
+            55..72 'let "f... any()': bool
             59..64 '"foo"': &str
             59..64 '"foo"': &str
             67..70 'any': fn any<&str>() -> &str
             67..72 'any()': &str
             73..75 '{}': ()
             80..99 'if let...y() {}': ()
+            83..96 'let 1 = any()': bool
             87..88 '1': i32
             87..88 '1': i32
             91..94 'any': fn any<i32>() -> i32
             91..96 'any()': i32
             97..99 '{}': ()
             104..126 'if let...y() {}': ()
+            107..123 'let 1u... any()': bool
             111..115 '1u32': u32
             111..115 '1u32': u32
             118..121 'any': fn any<u32>() -> u32
             118..123 'any()': u32
             124..126 '{}': ()
             131..153 'if let...y() {}': ()
+            134..150 'let 1f... any()': bool
             138..142 '1f32': f32
             138..142 '1f32': f32
             145..148 'any': fn any<f32>() -> f32
             145..150 'any()': f32
             151..153 '{}': ()
             158..179 'if let...y() {}': ()
+            161..176 'let 1.0 = any()': bool
             165..168 '1.0': f64
             165..168 '1.0': f64
             171..174 'any': fn any<f64>() -> f64
             171..176 'any()': f64
             177..179 '{}': ()
             184..206 'if let...y() {}': ()
+            187..203 'let tr... any()': bool
             191..195 'true': bool
             191..195 'true': bool
             198..201 'any': fn any<bool>() -> bool
@@ -163,10 +171,12 @@ fn infer_range_pattern() {
             8..9 'x': &i32
             17..75 '{     ...2 {} }': ()
             23..45 'if let...u32 {}': ()
+            26..42 'let 1....= 2u32': bool
             30..35 '1..76': u32
             38..42 '2u32': u32
             43..45 '{}': ()
             50..73 'if let...u32 {}': ()
+            53..70 'let 1....= 2u32': bool
             57..63 '1..=76': u32
             66..70 '2u32': u32
             71..73 '{}': ()
@@ -567,6 +577,21 @@ fn main() {
     )
 }
 
+#[test]
+fn match_ergonomics_in_tuple_pat() {
+    check_no_mismatches(
+        r#"
+enum E { A(u8), B }
+fn test(a: &E, b: &E) {
+    match (a, b) {
+        (E::A(x), E::A(y)) => {}
+        _ => {}
+    }
+}
+"#,
+    );
+}
+
 #[test]
 fn match_ergonomics_in_closure_params() {
     check_infer(
@@ -889,3 +914,43 @@ fn main() {
         "#]],
     );
 }
+
+#[test]
+fn let_else() {
+    check_types(
+        r#"
+//- minicore: option
+fn f(x: Option<(u8, i64)>) -> i64 {
+    let Some((a, b)) = x else { return 0 };
+    a;
+  //^ u8
+    let Some(d) = Some(b) else { loop {} };
+    d
+  //^ i64
+}
+"#,
+    );
+}
+
+#[test]
+fn let_chains() {
+    check_types(
+        r#"
+//- minicore: option, result
+fn f(x: Option<(u8, i64)>, y: Result<&str, ()>) {
+    if let Some((a, b)) = x && a > 3 && let Ok(s) = y {
+        b;
+      //^ i64
+        s;
+      //^ &str
+    }
+    while let Some(c) = x && (let Err(e) = y) {
+        c;
+      //^ (u8, i64)
+        e;
+      //^ ()
+    }
+}
+"#,
+    );
+}
@@ -918,6 +918,26 @@ fn test() {
     );
 }
 
+#[test]
+fn infer_cast_literals() {
+    check_types(
+        r#"
+fn test() {
+    let a = 1 as u8;
+          //^ u8
+    let b = -1 as i64;
+           //^ i64
+    let c = 1.5 as f32;
+          //^^^ f32
+    let d = 0 as *const u8;
+          //^ usize
+    let e = 1u32 as u8;
+          //^^^^ u32
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_method_argument_autoderef() {
     check_infer(
@@ -964,7 +984,7 @@ fn test(a: A<i32>) {
             278..279 'A': A<i32>(*mut i32) -> A<i32>
             278..292 'A(0 as *mut _)': A<i32>
             278..307 'A(0 as...B(a)))': &i32
-            280..281 '0': i32
+            280..281 '0': usize
             280..291 '0 as *mut _': *mut i32
             297..306 '&&B(B(a))': &&B<B<A<i32>>>
             298..306 '&B(B(a))': &B<B<A<i32>>>
@@ -1202,14 +1222,13 @@ fn infer_array() {
 
             let b = [a, ["b"]];
             let x: [u8; 0] = [];
-            // FIXME: requires const evaluation/taking type from rhs somehow
             let y: [u8; 2+2] = [1,2,3,4];
         }
         "#,
         expect![[r#"
             8..9 'x': &str
             17..18 'y': isize
-            27..395 '{     ...,4]; }': ()
+            27..326 '{     ...,4]; }': ()
             37..38 'a': [&str; 1]
             41..44 '[x]': [&str; 1]
             42..43 'x': &str
@@ -1259,12 +1278,12 @@ fn infer_array() {
             259..262 '"b"': &str
             274..275 'x': [u8; 0]
             287..289 '[]': [u8; 0]
-            368..369 'y': [u8; _]
-            383..392 '[1,2,3,4]': [u8; 4]
-            384..385 '1': u8
-            386..387 '2': u8
-            388..389 '3': u8
-            390..391 '4': u8
+            299..300 'y': [u8; 4]
+            314..323 '[1,2,3,4]': [u8; 4]
+            315..316 '1': u8
+            317..318 '2': u8
+            319..320 '3': u8
+            321..322 '4': u8
         "#]],
     );
 }
@@ -2248,6 +2267,7 @@ fn generic_default_in_struct_literal() {
             176..193 'Thing ...1i32 }': Thing<i32>
             187..191 '1i32': i32
             199..240 'if let...     }': ()
+            202..221 'let Th... } = z': bool
             206..217 'Thing { t }': Thing<i32>
             214..215 't': i32
             220..221 'z': Thing<i32>
@@ -1164,6 +1164,46 @@ fn test() {
     );
 }
 
+#[test]
+fn infer_in_return_pos_impl_trait_body() {
+    check_types(
+        r#"
+//- minicore: iterator
+trait Trait<T> {
+    fn foo(&self) -> T;
+}
+struct S<T>(T);
+impl<T> Trait<T> for S<T> {
+    fn foo(&self) -> T { loop {} }
+}
+struct Once<T>(Option<T>);
+impl<T> Iterator for Once<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> { loop {} }
+}
+fn default<T>() -> T { loop {} }
+
+fn foo() -> impl Trait<u32> {
+    let x = S(default());
+    x
+  //^ S<u32>
+}
+fn bar<T>(t: T) -> (impl Iterator<Item = T>, impl Trait<i64>) {
+    let it = Once(None);
+    (it, S(default()))
+   //^^ Once<T>
+}
+fn test() {
+    let (mut it, s) = bar(1u8);
+    it.next();
+  //^^^^^^^^^ Option<u8>
+    s.foo();
+  //^^^^^^^ i64
+}
+"#,
+    );
+}
+
 #[test]
 fn dyn_trait() {
     check_infer(
@@ -2327,7 +2367,7 @@ fn test() -> impl Trait<i32> {
             171..182 '{ loop {} }': T
             173..180 'loop {}': !
             178..180 '{}': ()
-            213..309 '{     ...t()) }': S<{unknown}>
+            213..309 '{     ...t()) }': S<i32>
             223..225 's1': S<u32>
             228..229 'S': S<u32>(u32) -> S<u32>
             228..240 'S(default())': S<u32>
@@ -2343,10 +2383,10 @@ fn test() -> impl Trait<i32> {
             276..288 'S(default())': S<i32>
             278..285 'default': fn default<i32>() -> i32
             278..287 'default()': i32
-            295..296 'S': S<{unknown}>({unknown}) -> S<{unknown}>
-            295..307 'S(default())': S<{unknown}>
-            297..304 'default': fn default<{unknown}>() -> {unknown}
-            297..306 'default()': {unknown}
+            295..296 'S': S<i32>(i32) -> S<i32>
+            295..307 'S(default())': S<i32>
+            297..304 'default': fn default<i32>() -> i32
+            297..306 'default()': i32
         "#]],
     );
 }
@@ -1,28 +1,41 @@
 use std::iter;
 
 use hir::Semantics;
-use ide_db::{helpers::pick_best_token, RootDatabase};
-use syntax::{ast, ted, AstNode, NodeOrToken, SyntaxKind, SyntaxKind::*, SyntaxNode, WalkEvent, T};
+use ide_db::{base_db::FileRange, helpers::pick_best_token, RootDatabase};
+use syntax::{
+    ast, AstNode, NodeOrToken, SyntaxKind, SyntaxKind::*, SyntaxNode, SyntaxToken, TextRange,
+    TextSize, T,
+};
 
 use crate::FilePosition;
 
 pub struct ExpandedMacro {
     pub name: String,
     pub expansion: String,
+    /// Maps ranges of tokens in `expansion` to the tokens they originate from,
+    /// either in the macro call or in the macro definition.
+    pub ranges: Vec<(TextRange, FileRange)>,
 }
 
 // Feature: Expand Macro Recursively
 //
-// Shows the full macro expansion of the macro at current cursor.
+// Shows the full macro expansion of the macro at current cursor. The
+// one-step variant only expands the macro call itself, leaving the macro calls
+// in its expansion as is. On a `#[derive]` attribute, shows the impls generated
+// by the derives.
 //
 // |===
 // | Editor  | Action Name
 //
-// | VS Code | **Rust Analyzer: Expand macro recursively**
+// | VS Code | **Rust Analyzer: Expand macro recursively**, **Rust Analyzer: Expand macro one step**
 // |===
 //
 // image::https://user-images.githubusercontent.com/48062697/113020648-b3973180-917a-11eb-84a9-ecb921293dc5.gif[]
-pub(crate) fn expand_macro(db: &RootDatabase, position: FilePosition) -> Option<ExpandedMacro> {
+pub(crate) fn expand_macro(
+    db: &RootDatabase,
+    position: FilePosition,
+    recursive: bool,
+) -> Option<ExpandedMacro> {
     let sema = Semantics::new(db);
     let file = sema.parse(position.file_id);
 
@@ -30,14 +43,21 @@ pub(crate) fn expand_macro(db: &RootDatabase, position: FilePosition) -> Option<
         SyntaxKind::IDENT => 1,
         _ => 0,
     })?;
-    let mut expanded = None;
+    let mut expanded = Vec::new();
     let mut name = None;
     for node in tok.ancestors() {
+        if let Some(attr) = ast::Attr::cast(node.clone()) {
+            if attr.as_simple_call().map(|(name, _)| name == "derive").unwrap_or(false) {
+                expanded = sema.expand_derive_macro(&attr)?;
+                name = Some("derive".to_string());
+                break;
+            }
+        }
+
         if let Some(item) = ast::Item::cast(node.clone()) {
-            expanded = sema.expand_attr_macro(&item);
-            if expanded.is_some() {
+            if let Some(node) = sema.expand_attr_macro(&item) {
+                expanded.push(node);
                 // FIXME: add the macro name
-                // FIXME: make this recursive too
                 name = Some("?".to_string());
                 break;
             }
@@ -45,55 +65,60 @@ pub(crate) fn expand_macro(db: &RootDatabase, position: FilePosition) -> Option<
 
         if let Some(mac) = ast::MacroCall::cast(node) {
             name = Some(mac.path()?.segment()?.name_ref()?.to_string());
-            expanded = expand_macro_recur(&sema, &mac);
+            expanded.extend(sema.expand(&mac));
             break;
         }
     }
+    if expanded.is_empty() {
+        return None;
+    }
+
+    let mut tokens = Vec::new();
+    for node in &expanded {
+        collect_tokens(&sema, node, recursive, &mut tokens);
+    }
 
     // FIXME:
     // macro expansion may lose all white space information
     // But we hope someday we can use ra_fmt for that
-    let expansion = insert_whitespaces(expanded?);
-    Some(ExpandedMacro { name: name?, expansion })
+    let (expansion, token_ranges) = insert_whitespaces(&tokens);
+    let ranges = tokens
+        .iter()
+        .zip(token_ranges)
+        .filter_map(|(token, range)| Some((range, sema.original_token_range(token)?)))
+        .collect();
+    Some(ExpandedMacro { name: name?, expansion, ranges })
 }
 
-fn expand_macro_recur(
+/// Collects the tokens of `node`, replacing macro calls with the tokens of
+/// their expansions if `recursive` is set.
+fn collect_tokens(
     sema: &Semantics<RootDatabase>,
-    macro_call: &ast::MacroCall,
-) -> Option<SyntaxNode> {
-    let expanded = sema.expand(macro_call)?.clone_for_update();
-
-    let children = expanded.descendants().filter_map(ast::MacroCall::cast);
-    let mut replacements = Vec::new();
-
-    for child in children {
-        if let Some(new_node) = expand_macro_recur(sema, &child) {
-            // check if the whole original syntax is replaced
-            if expanded == *child.syntax() {
-                return Some(new_node);
-            }
-            replacements.push((child, new_node));
+    node: &SyntaxNode,
+    recursive: bool,
+    acc: &mut Vec<SyntaxToken>,
+) {
+    if recursive {
+        if let Some(nested) = ast::MacroCall::cast(node.clone()).and_then(|mac| sema.expand(&mac)) {
+            return collect_tokens(sema, &nested, recursive, acc);
+        }
+    }
+    for element in node.children_with_tokens() {
+        match element {
+            NodeOrToken::Node(node) => collect_tokens(sema, &node, recursive, acc),
+            NodeOrToken::Token(token) => acc.push(token),
         }
     }
-
-    replacements.into_iter().rev().for_each(|(old, new)| ted::replace(old.syntax(), new));
-    Some(expanded)
 }
 
 // FIXME: It would also be cool to share logic here and in the mbe tests,
 // which are pretty unreadable at the moment.
-fn insert_whitespaces(syn: SyntaxNode) -> String {
+/// Renders `tokens` with some whitespace in between, returning the text and
+/// the range of each token in it.
+fn insert_whitespaces(tokens: &[SyntaxToken]) -> (String, Vec<TextRange>) {
     let mut res = String::new();
-    let mut token_iter = syn
-        .preorder_with_tokens()
-        .filter_map(|event| {
-            if let WalkEvent::Enter(NodeOrToken::Token(token)) = event {
-                Some(token)
-            } else {
-                None
-            }
-        })
-        .peekable();
+    let mut ranges = Vec::with_capacity(tokens.len());
+    let mut token_iter = tokens.iter().peekable();
 
     let mut indent = 0;
     let mut last: Option<SyntaxKind> = None;
@@ -105,47 +130,49 @@ fn insert_whitespaces(syn: SyntaxNode) -> String {
         let is_last =
             |f: fn(SyntaxKind) -> bool, default| -> bool { last.map(f).unwrap_or(default) };
 
+        let mut before = String::new();
+        let mut after = String::new();
         match token.kind() {
-            k if is_text(k) && is_next(|it| !it.is_punct(), true) => {
-                res.push_str(token.text());
-                res.push(' ');
-            }
+            k if is_text(k) && is_next(|it| !it.is_punct(), true) => after.push(' '),
             L_CURLY if is_next(|it| it != R_CURLY, true) => {
                 indent += 1;
                 if is_last(is_text, false) {
-                    res.push(' ');
+                    before.push(' ');
                 }
-                res.push_str("{\n");
-                res.extend(iter::repeat(" ").take(2 * indent));
+                after.push('\n');
+                after.extend(iter::repeat(" ").take(2 * indent));
             }
             R_CURLY if is_last(|it| it != L_CURLY, true) => {
                 indent = indent.saturating_sub(1);
-                res.push('\n');
-                res.extend(iter::repeat(" ").take(2 * indent));
-                res.push_str("}");
+                before.push('\n');
+                before.extend(iter::repeat(" ").take(2 * indent));
             }
             R_CURLY => {
-                res.push_str("}\n");
-                res.extend(iter::repeat(" ").take(2 * indent));
-            }
-            LIFETIME_IDENT if is_next(|it| it == IDENT, true) => {
-                res.push_str(token.text());
-                res.push(' ');
+                after.push('\n');
+                after.extend(iter::repeat(" ").take(2 * indent));
             }
+            LIFETIME_IDENT if is_next(|it| it == IDENT, true) => after.push(' '),
             T![;] => {
-                res.push_str(";\n");
-                res.extend(iter::repeat(" ").take(2 * indent));
+                after.push('\n');
+                after.extend(iter::repeat(" ").take(2 * indent));
             }
-            T![->] => res.push_str(" -> "),
-            T![=] => res.push_str(" = "),
-            T![=>] => res.push_str(" => "),
-            _ => res.push_str(token.text()),
+            T![->] | T![=] | T![=>] => {
+                before.push(' ');
+                after.push(' ');
+            }
+            _ => (),
         }
 
+        res.push_str(&before);
+        let start = TextSize::of(res.as_str());
+        res.push_str(token.text());
+        ranges.push(TextRange::at(start, TextSize::of(token.text())));
+        res.push_str(&after);
+
         last = Some(token.kind());
     }
 
-    return res;
+    return (res, ranges);
 
     fn is_text(k: SyntaxKind) -> bool {
         k.is_keyword() || k.is_literal() || k == IDENT
@@ -160,11 +187,36 @@ mod tests {
 
     fn check(ra_fixture: &str, expect: Expect) {
         let (analysis, pos) = fixture::position(ra_fixture);
-        let expansion = analysis.expand_macro(pos).unwrap().unwrap();
+        let expansion = analysis.expand_macro(pos, true).unwrap().unwrap();
+        let actual = format!("{}\n{}", expansion.name, expansion.expansion);
+        expect.assert_eq(&actual);
+    }
+
+    fn check_one_step(ra_fixture: &str, expect: Expect) {
+        let (analysis, pos) = fixture::position(ra_fixture);
+        let expansion = analysis.expand_macro(pos, false).unwrap().unwrap();
         let actual = format!("{}\n{}", expansion.name, expansion.expansion);
         expect.assert_eq(&actual);
     }
 
+    fn check_ranges(ra_fixture: &str, expect: Expect) {
+        let (analysis, pos) = fixture::position(ra_fixture);
+        let expansion = analysis.expand_macro(pos, true).unwrap().unwrap();
+        let text = analysis.file_text(pos.file_id).unwrap();
+        let actual = expansion
+            .ranges
+            .iter()
+            .map(|(range, original)| {
+                assert_eq!(original.file_id, pos.file_id);
+                format!(
+                    "{:?} {} -> {:?} {}\n",
+                    range, &expansion.expansion[*range], original.range, &text[original.range]
+                )
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
     #[test]
     fn macro_expand_recursive_expansion() {
         check(
@@ -187,6 +239,48 @@ f$0oo!();
         );
     }
 
+    #[test]
+    fn macro_expand_one_step() {
+        check_one_step(
+            r#"
+macro_rules! bar {
+    () => { fn  b() {} }
+}
+macro_rules! foo {
+    () => { bar!(); }
+}
+f$0oo!();
+"#,
+            expect![[r#"
+                foo
+                bar!();
+            "#]],
+        );
+    }
+
+    #[test]
+    fn macro_expand_maps_tokens_to_source() {
+        check_ranges(
+            r#"
+macro_rules! bar {
+    ($i:ident) => { fn $i() {} }
+}
+macro_rules! foo {
+    ($i:ident) => { bar!($i); }
+}
+f$0oo!(baz);
+"#,
+            expect![[r#"
+                0..2 fn -> 39..41 fn
+                3..6 baz -> 112..115 baz
+                6..7 ( -> 44..45 (
+                7..8 ) -> 45..46 )
+                8..9 { -> 47..48 {
+                9..10 } -> 48..49 }
+            "#]],
+        );
+    }
+
     #[test]
     fn macro_expand_multiple_lines() {
         check(
@@ -314,4 +408,54 @@ fn main() {
                 0 "#]],
         );
     }
+
+    #[test]
+    fn macro_expand_derive() {
+        check(
+            r#"
+//- minicore: clone, derive
+#[derive(Cl$0one)]
+struct Foo {
+    bar: u32,
+}
+"#,
+            expect![[r#"
+                derive
+                impl< >core::clone::Clone for Foo< >{
+                  fn clone(&self) -> Self {
+                    match self {
+                      Foo {
+                        bar:__self_0,
+                      } => Foo {
+                        bar:core::clone::Clone::clone(__self_0),
+                      },
+                    }
+                  }
+                }"#]],
+        );
+    }
+
+    #[test]
+    fn macro_expand_derive_multiple() {
+        check(
+            r#"
+//- minicore: copy, derive
+#[derive(Copy, Cl$0one)]
+enum Foo {
+    A,
+    B(u8),
+}
+"#,
+            expect![[r#"
+                derive
+                impl< >core::marker::Copy for Foo< >{}
+                impl< >core::clone::Clone for Foo< >{
+                  fn clone(&self) -> Self {
+                    match self {
+                      Foo::A => Foo::A,Foo::B(__self_0,) => Foo::B(core::clone::Clone::clone(__self_0),),
+                    }
+                  }
+                }"#]],
+        );
+    }
 }
@@ -858,6 +858,38 @@ fn bar() {
         );
     }
 
+    #[test]
+    fn goto_def_for_local_shadowed_in_macro_def() {
+        check(
+            r#"
+macro_rules! m {
+    ($e:expr) => {{ let x = 0u8; $e }};
+}
+fn main() {
+    let x = 92;
+      //^
+    m!(x$0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_local_passed_to_macro_binding_same_name() {
+        check(
+            r#"
+macro_rules! m {
+    ($e:expr) => {{ let x = $e; x }};
+}
+fn main() {
+    let x = 92;
+      //^
+    m!($0x);
+}
+"#,
+        );
+    }
+
     #[test]
     fn goto_def_for_field_init_shorthand() {
         check(
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use either::Either;
 use hir::{AsAssocItem, HasAttrs, HasSource, HirDisplay, Semantics};
 use ide_db::{
@@ -188,10 +190,36 @@ pub(crate) fn hover(
     } else {
         ty.display(db).to_string().into()
     };
+    if let Some(captures) =
+        ast::ClosureExpr::cast(node.clone()).and_then(|it| closure_captures(&sema, &it))
+    {
+        res.markup = format!("{}\ncaptures: {}", res.markup, captures).into();
+    }
     let range = sema.original_range(&node).range;
     Some(RangeInfo::new(range, res))
 }
 
+/// Renders the captures of a closure like `&x, &mut buf, move name`.
+fn closure_captures(sema: &Semantics<RootDatabase>, closure: &ast::ClosureExpr) -> Option<String> {
+    let captures = sema.closure_captures(closure)?;
+    if captures.is_empty() {
+        return None;
+    }
+    let captures = captures
+        .iter()
+        .map(|capture| {
+            let name =
+                capture.local.name(sema.db).map_or_else(|| "_".to_string(), |it| it.to_string());
+            match capture.kind {
+                hir::CaptureKind::SharedRef => format!("&{}", name),
+                hir::CaptureKind::MutableRef => format!("&mut {}", name),
+                hir::CaptureKind::Move => format!("move {}", name),
+            }
+        })
+        .join(", ");
+    Some(captures)
+}
+
 fn try_hover_for_attribute(token: &SyntaxToken) -> Option<RangeInfo<HoverResult>> {
     let attr = token.ancestors().find_map(ast::Attr::cast)?;
     let (path, tt) = attr.as_simple_call()?;
@@ -421,16 +449,26 @@ fn hover_for_definition(
             },
             it.attrs(db).docs(),
         ),
-        Definition::Field(def) => label_and_docs(db, def),
+        Definition::Field(def) => label_and_layout_info_and_docs(db, def, || {
+            let layout = def.layout(db).ok()?;
+            let offset = def.offset(db)?;
+            Some(format!("{}, offset = {}", layout_info(&layout), offset))
+        }),
         Definition::ModuleDef(it) => match it {
             hir::ModuleDef::Module(it) => label_and_docs(db, it),
             hir::ModuleDef::Function(it) => label_and_docs(db, it),
-            hir::ModuleDef::Adt(it) => label_and_docs(db, it),
+            hir::ModuleDef::Adt(it) => {
+                label_and_layout_info_and_docs(db, it, || Some(layout_info(&it.layout(db).ok()?)))
+            }
             hir::ModuleDef::Variant(it) => label_and_docs(db, it),
-            hir::ModuleDef::Const(it) => label_and_docs(db, it),
-            hir::ModuleDef::Static(it) => label_and_docs(db, it),
+            hir::ModuleDef::Const(it) => label_value_and_docs(db, it, || it.eval(db).ok()),
+            hir::ModuleDef::Static(it) => {
+                label_value_and_docs(db, it, || if it.is_mut(db) { None } else { it.eval(db).ok() })
+            }
             hir::ModuleDef::Trait(it) => label_and_docs(db, it),
-            hir::ModuleDef::TypeAlias(it) => label_and_docs(db, it),
+            hir::ModuleDef::TypeAlias(it) => label_and_layout_info_and_docs(db, it, || {
+                Some(layout_info(&it.ty(db).layout(db).ok()?))
+            }),
             hir::ModuleDef::BuiltinType(it) => {
                 return famous_defs
                     .and_then(|fd| hover_for_builtin(fd, it))
@@ -459,6 +497,47 @@ fn hover_for_definition(
         let docs = def.attrs(db).docs();
         (label, docs)
     }
+
+    fn label_and_layout_info_and_docs<D>(
+        db: &RootDatabase,
+        def: D,
+        layout_extractor: impl FnOnce() -> Option<String>,
+    ) -> (String, Option<hir::Documentation>)
+    where
+        D: HasAttrs + HirDisplay,
+    {
+        let label = match layout_extractor() {
+            Some(layout) => format!("{} // {}", def.display(db), layout),
+            None => def.display(db).to_string(),
+        };
+        let docs = def.attrs(db).docs();
+        (label, docs)
+    }
+
+    fn layout_info(layout: &hir::Layout) -> String {
+        let mut res = format!("size = {}, align = {}", layout.size, layout.align);
+        if let Some(niche) = layout.largest_niche.filter(|it| it.available() != 0) {
+            format_to!(res, ", niches = {}", niche.available());
+        }
+        res
+    }
+
+    fn label_value_and_docs<D, V>(
+        db: &RootDatabase,
+        def: D,
+        value_extractor: impl FnOnce() -> Option<V>,
+    ) -> (String, Option<hir::Documentation>)
+    where
+        D: HasAttrs + HirDisplay,
+        V: Display,
+    {
+        let label = match value_extractor() {
+            Some(value) => format!("{} = {}", def.display(db), value),
+            None => def.display(db).to_string(),
+        };
+        let docs = def.attrs(db).docs();
+        (label, docs)
+    }
 }
 
 fn hover_for_local(it: hir::Local, db: &RootDatabase) -> Option<Markup> {
@@ -471,7 +550,7 @@ fn hover_for_local(it: hir::Local, db: &RootDatabase) -> Option<Markup> {
             let let_kw = if ident
                 .syntax()
                 .parent()
-                .map_or(false, |p| p.kind() == LET_STMT || p.kind() == CONDITION)
+                .map_or(false, |p| p.kind() == LET_STMT || p.kind() == LET_EXPR)
             {
                 "let "
             } else {
@@ -638,6 +717,40 @@ fn main() {
         );
     }
 
+    #[test]
+    fn hover_shows_closure_captures() {
+        check(
+            r#"
+struct Buf;
+impl Buf {
+    fn push(&mut self, _: i32) {}
+}
+struct Name;
+fn drop<T>(_: T) {}
+
+fn main() {
+    let x = 0;
+    let mut buf = Buf;
+    let name = Name;
+    let c = |$0| {
+        buf.push(x);
+        drop(name);
+    };
+}
+"#,
+            expect![[r#"
+                *|| {
+                        buf.push(x);
+                        drop(name);
+                    }*
+                ```rust
+                || -> ()
+                ```
+                captures: &mut buf, &x, move name
+            "#]],
+        );
+    }
+
     #[test]
     fn hover_remove_markdown_if_configured() {
         check_hover_no_markdown(
@@ -849,6 +962,59 @@ fn main() { }
         );
     }
 
+    #[test]
+    fn hover_shows_layout_info() {
+        check(
+            r#"
+struct Foo$0 { a: u8, b: u32, c: bool }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                struct Foo // size = 8, align = 4, niches = 254
+                ```
+            "#]],
+        );
+        check(
+            r#"
+struct Foo { a: u8, b$0: u32, c: bool }
+"#,
+            expect![[r#"
+                *b*
+
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                b: u32 // size = 4, align = 4, offset = 0
+                ```
+            "#]],
+        );
+        check(
+            r#"
+enum Option<T> { None, Some(T) }
+type Foo$0 = Option<&'static u32>;
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                type Foo = Option<&'static u32> // size = 8, align = 8
+                ```
+            "#]],
+        );
+    }
+
     #[test]
     fn hover_shows_struct_field_info() {
         // Hovering over the field when instantiating
@@ -868,7 +1034,7 @@ fn main() {
                 ```
 
                 ```rust
-                field_a: u32
+                field_a: u32 // size = 4, align = 4, offset = 0
                 ```
             "#]],
         );
@@ -890,7 +1056,7 @@ fn main() {
                 ```
 
                 ```rust
-                field_a: u32
+                field_a: u32 // size = 4, align = 4, offset = 0
                 ```
             "#]],
         );
@@ -908,7 +1074,7 @@ fn main() {
                 ```
 
                 ```rust
-                const foo: u32
+                const foo: u32 = 123
                 ```
             "#]],
         );
@@ -922,7 +1088,7 @@ fn main() {
                 ```
 
                 ```rust
-                static foo: u32
+                static foo: u32 = 456
                 ```
             "#]],
         );
@@ -1191,7 +1357,7 @@ fn main() {
                 ```
 
                 ```rust
-                const C: u32
+                const C: u32 = 1
                 ```
             "#]],
         )
@@ -1697,7 +1863,7 @@ fn foo() { let bar = Ba$0r; }
                 ```
 
                 ```rust
-                struct Bar
+                struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1733,7 +1899,7 @@ fn foo() { let bar = Ba$0r; }
                 ```
 
                 ```rust
-                struct Bar
+                struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1762,7 +1928,7 @@ fn foo() { let bar = Ba$0r; }
                 ```
 
                 ```rust
-                struct Bar
+                struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1790,7 +1956,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1816,7 +1982,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1846,7 +2012,7 @@ pub struct Bar {
                 ```
 
                 ```rust
-                field: ()
+                field: () // size = 0, align = 1, offset = 0
                 ```
 
                 ---
@@ -1874,7 +2040,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1904,7 +2070,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1930,7 +2096,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1956,7 +2122,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -1983,7 +2149,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -2009,7 +2175,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -2035,7 +2201,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -2063,7 +2229,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -2084,7 +2250,7 @@ pub trait Foo {
 /// [buzz]: Foo::buzz
 pub struct B$0ar
 "#,
-            expect![[r#"
+            expect![[r##"
                 *Bar*
 
                 ```rust
@@ -2092,13 +2258,13 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
 
                 [Foo](https://docs.rs/test/*/test/trait.Foo.html#tymethod.buzz)
-            "#]],
+            "##]],
         );
     }
 
@@ -2118,7 +2284,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -2145,7 +2311,7 @@ pub struct B$0ar
                 ```
 
                 ```rust
-                pub struct Bar
+                pub struct Bar // size = 0, align = 1
                 ```
 
                 ---
@@ -2199,7 +2365,7 @@ struct S {
                 ```
 
                 ```rust
-                field: i32
+                field: i32 // size = 4, align = 4, offset = 0
                 ```
 
                 ---
@@ -3751,7 +3917,7 @@ fn foo() {
                 ```
 
                 ```rust
-                const FOO: usize
+                const FOO: usize = 3
                 ```
 
                 ---
@@ -3761,6 +3927,43 @@ fn foo() {
         );
     }
 
+    #[test]
+    fn hover_const_eval() {
+        check(
+            r#"
+const fn double(x: u32) -> u32 { x * 2 }
+const BASE: u32 = 4 + 4;
+const FOO$0: u32 = double(BASE) + 1;
+"#,
+            expect![[r#"
+                *FOO*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const FOO: u32 = 17
+                ```
+            "#]],
+        );
+        check(
+            r#"
+const SIZE: usize = 2 * 3;
+fn f() {
+    let buf$0 = [0u8; SIZE + 1];
+}
+"#,
+            expect![[r#"
+                *buf*
+
+                ```rust
+                let buf: [u8; 7]
+                ```
+            "#]],
+        );
+    }
+
     #[test]
     fn hover_mod_def() {
         check(
@@ -3930,7 +4133,7 @@ pub fn gimme() -> theitem::TheItem {
                 ```
 
                 ```rust
-                pub struct TheItem
+                pub struct TheItem // size = 0, align = 1
                 ```
 
                 ---
@@ -4035,7 +4238,7 @@ mod string {
                 ```
 
                 ```rust
-                struct String
+                struct String // size = 0, align = 1
                 ```
 
                 ---
@@ -2,6 +2,7 @@ use either::Either;
 use hir::{known, Callable, HirDisplay, Semantics};
 use ide_db::helpers::FamousDefs;
 use ide_db::RootDatabase;
+use itertools::Itertools;
 use stdx::to_lower_snake_case;
 use syntax::{
     ast::{self, ArgListOwner, AstNode, NameOwner},
@@ -15,6 +16,7 @@ pub struct InlayHintsConfig {
     pub type_hints: bool,
     pub parameter_hints: bool,
     pub chaining_hints: bool,
+    pub closure_capture_hints: bool,
     pub max_length: Option<usize>,
 }
 
@@ -23,6 +25,7 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    ClosureCaptureHint,
 }
 
 #[derive(Debug)]
@@ -42,6 +45,7 @@ pub struct InlayHint {
 // * types of local variables
 // * names of function arguments
 // * types of chained expressions
+// * captures of `move` closures
 //
 // **Note:** VS Code does not have native support for inlay hints https://github.com/microsoft/vscode/issues/16221[yet] and the hints are implemented using decorations.
 // This approach has limitations, the caret movement and bracket highlighting near the edges of the hint may be weird:
@@ -74,6 +78,7 @@ pub(crate) fn inlay_hints(
                 ast::CallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::MethodCallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::IdentPat(it) => { get_bind_pat_hints(&mut res, &sema, config, it); },
+                ast::ClosureExpr(it) => { get_closure_capture_hints(&mut res, &sema, config, it); },
                 _ => (),
             }
         }
@@ -176,6 +181,34 @@ fn get_param_name_hints(
     Some(())
 }
 
+fn get_closure_capture_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    closure: ast::ClosureExpr,
+) -> Option<()> {
+    if !config.closure_capture_hints {
+        return None;
+    }
+
+    // Everything is captured by value, so only list the names.
+    let move_token = closure.move_token()?;
+    let captures = sema.closure_captures(&closure)?;
+    if captures.is_empty() {
+        return None;
+    }
+    let label = captures
+        .iter()
+        .map(|capture| capture.local.name(sema.db).map_or_else(|| "_".into(), |it| it.to_string()))
+        .join(", ");
+    acc.push(InlayHint {
+        range: move_token.text_range(),
+        kind: InlayKind::ClosureCaptureHint,
+        label: label.into(),
+    });
+    Some(())
+}
+
 fn get_bind_pat_hints(
     acc: &mut Vec<InlayHint>,
     sema: &Semantics<RootDatabase>,
@@ -286,14 +319,7 @@ fn should_not_display_type_hint(
                 ast::LetStmt(it) => return it.ty().is_some(),
                 ast::Param(it) => return it.ty().is_some(),
                 ast::MatchArm(_it) => return pat_is_enum_variant(db, bind_pat, pat_ty),
-                ast::IfExpr(it) => {
-                    return it.condition().and_then(|condition| condition.pat()).is_some()
-                        && pat_is_enum_variant(db, bind_pat, pat_ty);
-                },
-                ast::WhileExpr(it) => {
-                    return it.condition().and_then(|condition| condition.pat()).is_some()
-                        && pat_is_enum_variant(db, bind_pat, pat_ty);
-                },
+                ast::LetExpr(_it) => return pat_is_enum_variant(db, bind_pat, pat_ty),
                 ast::ForExpr(it) => {
                     // We *should* display hint only if user provided "in {expr}" and we know the type of expr (and it's not unit).
                     // Type of expr should be iterable.
@@ -442,6 +468,7 @@ mod tests {
         type_hints: true,
         parameter_hints: true,
         chaining_hints: true,
+        closure_capture_hints: true,
         max_length: None,
     };
 
@@ -455,6 +482,7 @@ mod tests {
                 parameter_hints: true,
                 type_hints: false,
                 chaining_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             ra_fixture,
@@ -467,6 +495,7 @@ mod tests {
                 parameter_hints: false,
                 type_hints: true,
                 chaining_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             ra_fixture,
@@ -479,6 +508,7 @@ mod tests {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_capture_hints: false,
                 max_length: None,
             },
             ra_fixture,
@@ -507,6 +537,7 @@ mod tests {
                 type_hints: false,
                 parameter_hints: false,
                 chaining_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1198,6 +1229,7 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1254,6 +1286,7 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1298,6 +1331,7 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1343,6 +1377,7 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1392,4 +1427,32 @@ fn main() {
             "#]],
         );
     }
+
+    // Closure capture hint tests
+
+    #[test]
+    fn closure_capture_hints_only_for_move_closures() {
+        check_with_config(
+            InlayHintsConfig {
+                parameter_hints: false,
+                type_hints: false,
+                chaining_hints: false,
+                closure_capture_hints: true,
+                max_length: None,
+            },
+            r#"
+struct Name;
+fn main() {
+    let x = 0;
+    let name = Name;
+    let _ = || x + 1;
+    let _ = move || {
+          //^^^^ x, name
+        let _ = x;
+        let _ = name;
+    };
+    let _ = move || 1;
+}"#,
+        );
+    }
 }
@@ -49,6 +49,7 @@ mod ssr;
 mod status;
 mod syntax_highlighting;
 mod syntax_tree;
+mod trace_macro_expansion;
 mod typing;
 mod view_crate_graph;
 mod view_hir;
@@ -90,6 +91,7 @@ pub use crate::{
         tags::{Highlight, HlMod, HlMods, HlOperator, HlPunct, HlTag},
         HlRange,
     },
+    trace_macro_expansion::{MacroExpansionTrace, MacroRuleTrace},
 };
 pub use hir::{Documentation, Semantics};
 pub use ide_assists::{
@@ -224,6 +226,7 @@ impl Analysis {
             cfg_options,
             Env::default(),
             Default::default(),
+            None,
         );
         change.change_file(file_id, Some(Arc::new(text)));
         change.set_crate_graph(crate_graph);
@@ -303,8 +306,21 @@ impl Analysis {
         self.with_db(|db| view_crate_graph::view_crate_graph(db, full))
     }
 
-    pub fn expand_macro(&self, position: FilePosition) -> Cancellable<Option<ExpandedMacro>> {
-        self.with_db(|db| expand_macro::expand_macro(db, position))
+    /// Expands the macro call at `position`, either just the call itself or
+    /// together with all the macro calls in its expansion.
+    pub fn expand_macro(
+        &self,
+        position: FilePosition,
+        recursive: bool,
+    ) -> Cancellable<Option<ExpandedMacro>> {
+        self.with_db(|db| expand_macro::expand_macro(db, position, recursive))
+    }
+
+    pub fn trace_macro_expansion(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<MacroExpansionTrace>> {
+        self.with_db(|db| trace_macro_expansion::trace_macro_expansion(db, position))
     }
 
     /// Returns an edit to remove all newlines in the range, cleaning up minor
@@ -142,6 +142,7 @@ pub struct HlRange {
 // mutable:: Emitted for mutable locals and statics.
 // static:: Emitted for "static" functions, also known as functions that do not take a `self` param, as well as statics and consts.
 // trait:: Emitted for associated trait items.
+// unreachable:: Emitted for code which can never be executed, like statements after a `return`.
 // unsafe:: Emitted for unsafe operations, like unsafe function calls, as well as the `unsafe` token.
 //
 //
@@ -198,6 +199,7 @@ fn traverse(
     let mut current_macro: Option<ast::Macro> = None;
     let mut macro_highlighter = MacroHighlighter::default();
     let mut inside_attribute = false;
+    let mut unreachable_ranges: Vec<TextRange> = Vec::new();
 
     // Walk all nodes, keeping track of whether we are inside a macro or not.
     // If in macro, expand it first and highlight the expanded code.
@@ -257,6 +259,15 @@ fn traverse(
             }
             _ => (),
         }
+        // Injected code, like the code blocks of doc comments, is not a body of
+        // its own, so whether it can be reached can't be told.
+        if let WalkEvent::Enter(NodeOrToken::Node(node)) = &event {
+            let func = ast::Fn::cast(node.clone()).filter(|_| !syntactic_name_ref_highlighting);
+            if let Some(func) = func {
+                unreachable_ranges.extend(unreachable_code_ranges(sema, root, &func));
+            }
+        }
+
         match &event {
             WalkEvent::Enter(NodeOrToken::Node(node)) if ast::Attr::can_cast(node.kind()) => {
                 inside_attribute = true
@@ -345,6 +356,9 @@ fn traverse(
             if inside_attribute {
                 highlight = highlight | HlMod::Attribute;
             }
+            if unreachable_ranges.iter().any(|it| it.contains_range(range)) {
+                highlight = highlight | HlMod::Unreachable;
+            }
 
             hl.add(HlRange { range, highlight, binding_hash });
         }
@@ -367,6 +381,35 @@ fn traverse(
     }
 }
 
+/// Computes the ranges of code in `func` which can never be executed. An
+/// unreachable statement makes the rest of its block unreachable as well.
+fn unreachable_code_ranges(
+    sema: &Semantics<RootDatabase>,
+    root: InFile<&SyntaxNode>,
+    func: &ast::Fn,
+) -> Vec<TextRange> {
+    let func = match sema.to_def(func) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let file_root = match root.value.ancestors().last() {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    func.unreachable_code(sema.db)
+        .into_iter()
+        .filter(|it| it.node.file_id == root.file_id)
+        .map(|it| {
+            let node = it.node.value.to_node(&file_root);
+            if ast::MatchArm::can_cast(node.kind()) {
+                return node.text_range();
+            }
+            let last = node.siblings(Direction::Next).last().unwrap_or_else(|| node.clone());
+            TextRange::new(node.text_range().start(), last.text_range().end())
+        })
+        .collect()
+}
+
 fn macro_call_range(macro_call: &ast::MacroCall) -> Option<TextRange> {
     let path = macro_call.path()?;
     let name_ref = path.segment()?.name_ref()?;
@@ -87,6 +87,7 @@ pre                 { color: #DCDCCC; background: #3F3F3F; font-size: 22px; padd
 .keyword            { color: #F0DFAF; font-weight: bold; }
 .keyword.unsafe     { color: #BC8383; font-weight: bold; }
 .control            { font-style: italic; }
+.unreachable        { opacity: 0.5; }
 
 .unresolved_reference { color: #FC5555; text-decoration: wavy underline; }
 </style>
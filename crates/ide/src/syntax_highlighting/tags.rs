@@ -74,6 +74,8 @@ pub enum HlMod {
     Library,
     /// Used for public items.
     Public,
+    /// Used for code which can never be executed.
+    Unreachable,
     // Keep this last!
     /// Used for unsafe functions, unsafe traits, mutable statics, union accesses and unsafe operations.
     Unsafe,
@@ -199,6 +201,7 @@ impl HlMod {
         HlMod::Async,
         HlMod::Library,
         HlMod::Public,
+        HlMod::Unreachable,
         HlMod::Unsafe,
     ];
 
@@ -219,6 +222,7 @@ impl HlMod {
             HlMod::Async => "async",
             HlMod::Library => "library",
             HlMod::Public => "public",
+            HlMod::Unreachable => "unreachable",
             HlMod::Unsafe => "unsafe",
         }
     }
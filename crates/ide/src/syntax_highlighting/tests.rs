@@ -770,6 +770,27 @@ impl t for foo {
     )
 }
 
+#[test]
+fn test_unreachable_highlighting() {
+    check_highlighting(
+        r#"
+enum E { A, B }
+fn foo(e: E) -> u32 {
+    match e {
+        E::A => {}
+        E::B => return 0,
+        _ => {}
+    }
+    loop {}
+    let x = 92;
+    x
+}
+"#,
+        expect_file!["./test_data/highlight_unreachable.html"],
+        false,
+    );
+}
+
 #[test]
 fn test_injection() {
     check_highlighting(
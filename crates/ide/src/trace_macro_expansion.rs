@@ -0,0 +1,165 @@
+use either::Either;
+use hir::{HasSource, MatchFailure, Semantics};
+use ide_db::{helpers::pick_best_token, RootDatabase};
+use syntax::{ast, AstNode, SyntaxKind};
+
+use crate::FilePosition;
+
+pub struct MacroExpansionTrace {
+    pub name: String,
+    pub rules: Vec<MacroRuleTrace>,
+    /// Index into `rules` of the rule used for the expansion.
+    pub selected: Option<usize>,
+}
+
+pub struct MacroRuleTrace {
+    /// Index of the rule in the macro definition.
+    pub index: usize,
+    /// Source text of the rule's matcher, if the definition is available.
+    pub pattern: Option<String>,
+    pub matched: bool,
+    pub matched_token_trees: usize,
+    pub total_token_trees: usize,
+    /// The first part of the matcher which did not match the input.
+    pub failed_at: Option<String>,
+    pub error: Option<String>,
+}
+
+// Feature: Trace Macro Expansion
+//
+// Shows which rules of the `macro_rules!` macro at the cursor were tried, how
+// far each of them matched the input and which fragment failed.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **Rust Analyzer: Trace macro expansion**
+// |===
+pub(crate) fn trace_macro_expansion(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<MacroExpansionTrace> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+
+    let tok = pick_best_token(file.syntax().token_at_offset(position.offset), |kind| match kind {
+        SyntaxKind::IDENT => 1,
+        _ => 0,
+    })?;
+    let mac = tok.ancestors().find_map(ast::MacroCall::cast)?;
+    let name = mac.path()?.segment()?.name_ref()?.to_string();
+    let trace = sema.trace_macro_expansion(&mac)?;
+
+    let patterns = sema
+        .resolve_macro_call(&mac)
+        .and_then(|def| def.source(db))
+        .map(|src| rule_patterns(src.value))
+        .unwrap_or_default();
+
+    let selected = trace.selected.and_then(|idx| trace.rules.iter().position(|it| it.index == idx));
+    let rules = trace
+        .rules
+        .into_iter()
+        .map(|rule| MacroRuleTrace {
+            index: rule.index,
+            pattern: patterns.get(rule.index).cloned(),
+            matched: rule.matched,
+            matched_token_trees: rule.matched_tts,
+            total_token_trees: rule.total_tts,
+            failed_at: rule.failure.map(|failure| match failure {
+                MatchFailure::Fragment { name, kind } => format!("${}:{}", name, kind),
+                MatchFailure::Token(token) => token,
+            }),
+            error: rule.err.map(|err| err.to_string()),
+        })
+        .collect();
+    Some(MacroExpansionTrace { name, rules, selected })
+}
+
+/// Collects the matchers of a macro definition, in order.
+fn rule_patterns(def: Either<ast::Macro, ast::Fn>) -> Vec<String> {
+    let token_trees: Vec<ast::TokenTree> = match def {
+        Either::Left(ast::Macro::MacroRules(mac)) => match mac.token_tree() {
+            Some(body) => body.syntax().children().filter_map(ast::TokenTree::cast).collect(),
+            None => return Vec::new(),
+        },
+        Either::Left(ast::Macro::MacroDef(mac)) => {
+            let trees: Vec<_> = mac.syntax().children().filter_map(ast::TokenTree::cast).collect();
+            match trees.as_slice() {
+                // `macro m($args) { .. }` has a single rule.
+                [args, _] => return vec![args.syntax().text().to_string()],
+                [body] => body.syntax().children().filter_map(ast::TokenTree::cast).collect(),
+                _ => return Vec::new(),
+            }
+        }
+        Either::Right(_) => return Vec::new(),
+    };
+    token_trees.iter().step_by(2).map(|it| it.syntax().text().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, pos) = fixture::position(ra_fixture);
+        let trace = analysis.trace_macro_expansion(pos).unwrap().unwrap();
+        let mut actual = format!("{} (selected: {:?})\n", trace.name, trace.selected);
+        for rule in trace.rules {
+            actual += &format!(
+                "#{} {} matched={} {}/{} failed_at={:?} error={:?}\n",
+                rule.index,
+                rule.pattern.unwrap_or_default(),
+                rule.matched,
+                rule.matched_token_trees,
+                rule.total_token_trees,
+                rule.failed_at,
+                rule.error,
+            );
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn trace_failed_expansion() {
+        check(
+            r#"
+macro_rules! foo {
+    (fn $i:ident) => {};
+    (struct $i:ident;) => {};
+}
+fn main() {
+    f$0oo!(struct 92;);
+}
+"#,
+            expect![[r#"
+                foo (selected: Some(1))
+                #0 (fn $i:ident) matched=false 0/3 failed_at=Some("fn") error=Some("unexpected token in input")
+                #1 (struct $i:ident;) matched=false 1/3 failed_at=Some("$i:ident") error=Some("expected ident")
+            "#]],
+        );
+    }
+
+    #[test]
+    fn trace_stops_at_first_matching_rule() {
+        check(
+            r#"
+macro_rules! foo {
+    ($e:expr) => {};
+    ($($t:tt)*) => {};
+    () => {};
+}
+fn main() {
+    f$0oo!(1 2);
+}
+"#,
+            expect![[r#"
+                foo (selected: Some(1))
+                #0 ($e:expr) matched=false 1/2 failed_at=None error=Some("leftover tokens")
+                #1 ($($t:tt)*) matched=true 2/2 failed_at=None error=None
+            "#]],
+        );
+    }
+}
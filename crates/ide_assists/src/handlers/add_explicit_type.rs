@@ -216,8 +216,9 @@ fn main() {
         check_assist_not_applicable(
             add_explicit_type,
             r#"
+fn len() -> usize { 4 }
 fn main() {
-    let $0l = [0.0; 2+2];
+    let $0l = [0.0; len()];
 }
 "#,
         );
@@ -0,0 +1,293 @@
+use syntax::{
+    ast::{self, edit::AstNodeEdit, NameOwner},
+    AstNode, T,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_match_to_let_else
+//
+// Converts a `let` binding whose initializer is a match with one diverging
+// arm to a `let ... else` statement.
+//
+// ```
+// # //- minicore: option
+// fn foo(opt: Option<i32>) -> i32 {
+//     $0let x = match opt {
+//         Some(it) => it,
+//         None => return 0,
+//     };
+//     x
+// }
+// ```
+// ->
+// ```
+// fn foo(opt: Option<i32>) -> i32 {
+//     let Some(x) = opt else { return 0 };
+//     x
+// }
+// ```
+pub(crate) fn convert_match_to_let_else(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let let_kw = ctx.find_token_syntax_at_offset(T![let])?;
+    let let_stmt = let_kw.ancestors().find_map(ast::LetStmt::cast)?;
+    if let_stmt.ty().is_some() || let_stmt.let_else().is_some() {
+        return None;
+    }
+    let binding = match let_stmt.pat()? {
+        ast::Pat::IdentPat(it) if it.pat().is_none() => it,
+        _ => return None,
+    };
+    let match_expr = match let_stmt.initializer()? {
+        ast::Expr::MatchExpr(it) => it,
+        _ => return None,
+    };
+    let scrutinee = match_expr.expr()?;
+
+    let arms: Vec<_> = match_expr.match_arm_list()?.arms().collect();
+    let (binding_arm, diverging_arm) = match arms.as_slice() {
+        [first, second] if is_diverging(ctx, second) => (first, second),
+        [first, second] if is_diverging(ctx, first) => (second, first),
+        _ => return None,
+    };
+    if binding_arm.guard().is_some() || diverging_arm.guard().is_some() {
+        return None;
+    }
+    // The else block can't refer to the bindings of the diverging arm.
+    let diverging_pat = diverging_arm.pat()?;
+    if bindings(ctx, &diverging_pat).next().is_some() {
+        return None;
+    }
+
+    // The binding arm has to evaluate to the only binding of its pattern,
+    // which is then renamed to the binding of the `let`.
+    let arm_pat = binding_arm.pat()?;
+    let arm_binding = single_binding(ctx, &arm_pat)?;
+    let arm_local = ctx.sema.to_def(&arm_binding)?;
+    match binding_arm.expr()? {
+        ast::Expr::PathExpr(path) => match ctx.sema.resolve_path(&path.path()?)? {
+            hir::PathResolution::Local(local) if local == arm_local => (),
+            _ => return None,
+        },
+        _ => return None,
+    }
+
+    // A `ref` binding of the arm has to stay a reference, which can't be
+    // combined with the binding mode of the `let`.
+    let new_binding = if arm_binding.ref_token().is_some() {
+        if binding.ref_token().is_some() || binding.mut_token().is_some() {
+            return None;
+        }
+        let mut_kw = if arm_binding.mut_token().is_some() { "mut " } else { "" };
+        format!("ref {}{}", mut_kw, binding.name()?)
+    } else {
+        binding.to_string()
+    };
+
+    let else_block = match diverging_arm.expr()? {
+        ast::Expr::BlockExpr(block) => block.dedent(1.into()).to_string(),
+        expr => format!("{{ {} }}", expr),
+    };
+
+    let target = let_stmt.syntax().text_range();
+    acc.add(
+        AssistId("convert_match_to_let_else", AssistKind::RefactorRewrite),
+        "Convert match to let-else",
+        target,
+        |builder| {
+            let pat_start = arm_pat.syntax().text_range().start();
+            let binding_range = arm_binding.syntax().text_range() - pat_start;
+            let mut pat = arm_pat.syntax().text().to_string();
+            pat.replace_range(std::ops::Range::<usize>::from(binding_range), &new_binding);
+            builder.replace(target, format!("let {} = {} else {};", pat, scrutinee, else_block));
+        },
+    )
+}
+
+fn is_diverging(ctx: &AssistContext, arm: &ast::MatchArm) -> bool {
+    arm.expr().map_or(false, |expr| expr_diverges(ctx, &expr))
+}
+
+/// Blocks take the type of the match, so for them we look at the statement
+/// that makes them diverge.
+fn expr_diverges(ctx: &AssistContext, expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::BlockExpr(block) => match block.tail_expr() {
+            Some(tail) => expr_diverges(ctx, &tail),
+            None => match block.statements().last() {
+                Some(ast::Stmt::ExprStmt(stmt)) => {
+                    stmt.expr().map_or(false, |expr| expr_diverges(ctx, &expr))
+                }
+                _ => false,
+            },
+        },
+        _ => ctx.sema.type_of_expr(expr).map_or(false, |ty| ty.is_never()),
+    }
+}
+
+/// The identifier patterns in `pat` which introduce bindings, as opposed to
+/// referring to constants or unit variants.
+fn bindings<'a>(
+    ctx: &'a AssistContext,
+    pat: &ast::Pat,
+) -> impl Iterator<Item = ast::IdentPat> + 'a {
+    pat.syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .filter(move |it| ctx.sema.resolve_bind_pat_to_const(it).is_none())
+}
+
+fn single_binding(ctx: &AssistContext, pat: &ast::Pat) -> Option<ast::IdentPat> {
+    let mut bindings = bindings(ctx, pat);
+    let binding = bindings.next()?;
+    if bindings.next().is_some() || binding.pat().is_some() {
+        return None;
+    }
+    Some(binding)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn convert_option_match() {
+        check_assist(
+            convert_match_to_let_else,
+            r#"
+//- minicore: option
+fn foo(opt: Option<i32>) {
+    $0let mut x = match opt {
+        Some(y) => y,
+        None => return,
+    };
+}
+"#,
+            r#"
+fn foo(opt: Option<i32>) {
+    let Some(mut x) = opt else { return };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_with_diverging_arm_first_and_block() {
+        check_assist(
+            convert_match_to_let_else,
+            r#"
+//- minicore: result
+fn foo(res: Result<(u8, i32), ()>) -> i32 {
+    loop {
+        $0let x = match res {
+            Err(_) => {
+                break;
+            }
+            Ok((_, y)) => y,
+        };
+        return x;
+    }
+    0
+}
+"#,
+            r#"
+fn foo(res: Result<(u8, i32), ()>) -> i32 {
+    loop {
+        let Ok((_, x)) = res else {
+            break;
+        };
+        return x;
+    }
+    0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_keeps_ref_binding() {
+        check_assist(
+            convert_match_to_let_else,
+            r#"
+//- minicore: option
+fn foo(opt: Option<String>) {
+    $0let x = match opt {
+        Some(ref mut y) => y,
+        None => return,
+    };
+}
+"#,
+            r#"
+fn foo(opt: Option<String>) {
+    let Some(ref mut x) = opt else { return };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_if_ref_binding_is_bound_mutably() {
+        check_assist_not_applicable(
+            convert_match_to_let_else,
+            r#"
+//- minicore: option
+fn foo(opt: Option<String>) {
+    $0let mut x = match opt {
+        Some(ref y) => y,
+        None => return,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_if_no_arm_diverges() {
+        check_assist_not_applicable(
+            convert_match_to_let_else,
+            r#"
+//- minicore: option
+fn foo(opt: Option<i32>) {
+    $0let x = match opt {
+        Some(y) => y,
+        None => 0,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_if_else_uses_bindings() {
+        check_assist_not_applicable(
+            convert_match_to_let_else,
+            r#"
+//- minicore: result
+fn foo(res: Result<i32, u8>) -> Result<(), u8> {
+    $0let x = match res {
+        Ok(y) => y,
+        Err(e) => return Err(e),
+    };
+    Ok(())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_if_arm_transforms_binding() {
+        check_assist_not_applicable(
+            convert_match_to_let_else,
+            r#"
+//- minicore: option
+fn foo(opt: Option<i32>) {
+    $0let x = match opt {
+        Some(y) => y + 1,
+        None => return,
+    };
+}
+"#,
+        );
+    }
+}
@@ -14,7 +14,7 @@ use syntax::{
 
 use crate::{
     assist_context::{AssistContext, Assists},
-    utils::invert_boolean_expression,
+    utils::{invert_boolean_expression, is_let_condition},
     AssistId, AssistKind,
 };
 
@@ -49,26 +49,27 @@ pub(crate) fn convert_to_guarded_return(acc: &mut Assists, ctx: &AssistContext)
     let cond = if_expr.condition()?;
 
     // Check if there is an IfLet that we can handle.
-    let if_let_pat = match cond.pat() {
-        None => None, // No IfLet, supported.
-        Some(ast::Pat::TupleStructPat(pat)) if pat.fields().count() == 1 => {
+    let (if_let_pat, cond_expr) = match cond {
+        ast::Expr::LetExpr(let_expr) => {
+            let pat = match let_expr.pat()? {
+                ast::Pat::TupleStructPat(pat) if pat.fields().count() == 1 => pat,
+                _ => return None, // Unsupported IfLet.
+            };
             let path = pat.path()?;
-            match path.qualifier() {
-                None => {
-                    let bound_ident = pat.fields().next().unwrap();
-                    if ast::IdentPat::can_cast(bound_ident.syntax().kind()) {
-                        Some((path, bound_ident))
-                    } else {
-                        return None;
-                    }
-                }
-                Some(_) => return None,
+            if path.qualifier().is_some() {
+                return None;
+            }
+            let bound_ident = pat.fields().next().unwrap();
+            if !ast::IdentPat::can_cast(bound_ident.syntax().kind()) {
+                return None;
             }
+            (Some((path, bound_ident)), let_expr.expr()?)
         }
-        Some(_) => return None, // Unsupported IfLet.
+        // Let chains can't be inverted.
+        cond if is_let_condition(&cond) => return None,
+        cond => (None, cond), // No IfLet, supported.
     };
 
-    let cond_expr = cond.expr()?;
     let then_block = if_expr.then_branch()?;
 
     let parent_block = if_expr.syntax().parent()?.ancestors().find_map(ast::BlockExpr::cast)?;
@@ -116,8 +117,7 @@ pub(crate) fn convert_to_guarded_return(acc: &mut Assists, ctx: &AssistContext)
                         let then_branch =
                             make::block_expr(once(make::expr_stmt(early_expression).into()), None);
                         let cond = invert_boolean_expression(&ctx.sema, cond_expr);
-                        make::expr_if(make::condition(cond, None), then_branch, None)
-                            .indent(if_indent_level)
+                        make::expr_if(cond, then_branch, None).indent(if_indent_level)
                     };
                     replace(new_expr.syntax(), &then_block, &parent_block, &if_expr)
                 }
@@ -1,15 +1,5 @@
-use hir::HirDisplay;
-use ide_db::{base_db::FileId, helpers::SnippetCap};
-use rustc_hash::{FxHashMap, FxHashSet};
-use stdx::to_lower_snake_case;
-use syntax::{
-    ast::{
-        self,
-        edit::{AstNodeEdit, IndentLevel},
-        make, ArgListOwner, AstNode, ModuleItemOwner,
-    },
-    SyntaxKind, SyntaxNode, TextSize,
-};
+use ide_db::helpers::generate_function::FunctionBuilder;
+use syntax::{ast, AstNode, TextRange};
 
 use crate::{
     utils::{render_snippet, Cursor},
@@ -42,6 +32,10 @@ use crate::{
 //
 // ```
 pub(crate) fn generate_function(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    gen_fn(acc, ctx).or_else(|| gen_method(acc, ctx))
+}
+
+fn gen_fn(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
     let path_expr: ast::PathExpr = ctx.find_node_at_offset()?;
     let call = path_expr.syntax().parent().and_then(ast::CallExpr::cast)?;
     let path = path_expr.path()?;
@@ -59,330 +53,60 @@ pub(crate) fn generate_function(acc: &mut Assists, ctx: &AssistContext) -> Optio
         None => None,
     };
 
-    let function_builder = FunctionBuilder::from_call(ctx, &call, &path, target_module)?;
+    let function_builder =
+        FunctionBuilder::from_call(&ctx.sema, ctx.frange.file_id, &call, &path, target_module)?;
+    let target = call.syntax().text_range();
+    add_func_to_accumulator(acc, ctx, target, function_builder, "function")
+}
 
+/// Generates a method for the receiver type of an unresolved method call, in an
+/// inherent impl of the type or in a new impl block after its definition.
+fn gen_method(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let call: ast::MethodCallExpr = ctx.find_node_at_offset()?;
+    if ctx.sema.resolve_method_call(&call).is_some() {
+        return None;
+    }
+    let function_builder = FunctionBuilder::from_method_call(&ctx.sema, &call)?;
     let target = call.syntax().text_range();
+    add_func_to_accumulator(acc, ctx, target, function_builder, "method")
+}
+
+fn add_func_to_accumulator(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+    target: TextRange,
+    function_builder: FunctionBuilder,
+    kind: &str,
+) -> Option<()> {
     acc.add(
         AssistId("generate_function", AssistKind::Generate),
-        format!("Generate `{}` function", function_builder.fn_name),
+        format!("Generate `{}` {}", function_builder.fn_name(), kind),
         target,
         |builder| {
-            let function_template = function_builder.render();
+            let function_template = function_builder.render(ctx.config.snippet_cap);
             builder.edit_file(function_template.file);
-            let new_fn = function_template.to_string(ctx.config.snippet_cap);
+            let insert_offset = function_template.insert_offset;
             match ctx.config.snippet_cap {
-                Some(cap) => builder.insert_snippet(cap, function_template.insert_offset, new_fn),
-                None => builder.insert(function_template.insert_offset, new_fn),
+                Some(cap) => {
+                    let new_fn = match &function_template.ret_type {
+                        Some(ret_type) if function_template.should_render_snippet => {
+                            let fn_text = render_snippet(
+                                cap,
+                                function_template.fn_def.syntax(),
+                                Cursor::Replace(ret_type.syntax()),
+                            );
+                            function_template.with_fn_text(&fn_text)
+                        }
+                        _ => function_template.text(),
+                    };
+                    builder.insert_snippet(cap, insert_offset, new_fn)
+                }
+                None => builder.insert(insert_offset, function_template.text()),
             }
         },
     )
 }
 
-struct FunctionTemplate {
-    insert_offset: TextSize,
-    leading_ws: String,
-    fn_def: ast::Fn,
-    ret_type: ast::RetType,
-    should_render_snippet: bool,
-    trailing_ws: String,
-    file: FileId,
-}
-
-impl FunctionTemplate {
-    fn to_string(&self, cap: Option<SnippetCap>) -> String {
-        let f = match (cap, self.should_render_snippet) {
-            (Some(cap), true) => {
-                render_snippet(cap, self.fn_def.syntax(), Cursor::Replace(self.ret_type.syntax()))
-            }
-            _ => self.fn_def.to_string(),
-        };
-        format!("{}{}{}", self.leading_ws, f, self.trailing_ws)
-    }
-}
-
-struct FunctionBuilder {
-    target: GeneratedFunctionTarget,
-    fn_name: ast::Name,
-    type_params: Option<ast::GenericParamList>,
-    params: ast::ParamList,
-    ret_type: ast::RetType,
-    should_render_snippet: bool,
-    file: FileId,
-    needs_pub: bool,
-}
-
-impl FunctionBuilder {
-    /// Prepares a generated function that matches `call`.
-    /// The function is generated in `target_module` or next to `call`
-    fn from_call(
-        ctx: &AssistContext,
-        call: &ast::CallExpr,
-        path: &ast::Path,
-        target_module: Option<hir::Module>,
-    ) -> Option<Self> {
-        let mut file = ctx.frange.file_id;
-        let target = match &target_module {
-            Some(target_module) => {
-                let module_source = target_module.definition_source(ctx.db());
-                let (in_file, target) = next_space_for_fn_in_module(ctx.sema.db, &module_source)?;
-                file = in_file;
-                target
-            }
-            None => next_space_for_fn_after_call_site(call)?,
-        };
-        let needs_pub = target_module.is_some();
-        let target_module = target_module.or_else(|| ctx.sema.scope(target.syntax()).module())?;
-        let fn_name = fn_name(path)?;
-        let (type_params, params) = fn_args(ctx, target_module, call)?;
-
-        // should_render_snippet intends to express a rough level of confidence about
-        // the correctness of the return type.
-        //
-        // If we are able to infer some return type, and that return type is not unit, we
-        // don't want to render the snippet. The assumption here is in this situation the
-        // return type is just as likely to be correct as any other part of the generated
-        // function.
-        //
-        // In the case where the return type is inferred as unit it is likely that the
-        // user does in fact intend for this generated function to return some non unit
-        // type, but that the current state of their code doesn't allow that return type
-        // to be accurately inferred.
-        let (ret_ty, should_render_snippet) = {
-            match ctx.sema.type_of_expr(&ast::Expr::CallExpr(call.clone())) {
-                Some(ty) if ty.is_unknown() || ty.is_unit() => (make::ty_unit(), true),
-                Some(ty) => {
-                    let rendered = ty.display_source_code(ctx.db(), target_module.into());
-                    match rendered {
-                        Ok(rendered) => (make::ty(&rendered), false),
-                        Err(_) => (make::ty_unit(), true),
-                    }
-                }
-                None => (make::ty_unit(), true),
-            }
-        };
-        let ret_type = make::ret_type(ret_ty);
-
-        Some(Self {
-            target,
-            fn_name,
-            type_params,
-            params,
-            ret_type,
-            should_render_snippet,
-            file,
-            needs_pub,
-        })
-    }
-
-    fn render(self) -> FunctionTemplate {
-        let placeholder_expr = make::ext::expr_todo();
-        let fn_body = make::block_expr(vec![], Some(placeholder_expr));
-        let visibility = if self.needs_pub { Some(make::visibility_pub_crate()) } else { None };
-        let mut fn_def = make::fn_(
-            visibility,
-            self.fn_name,
-            self.type_params,
-            self.params,
-            fn_body,
-            Some(self.ret_type),
-        );
-        let leading_ws;
-        let trailing_ws;
-
-        let insert_offset = match self.target {
-            GeneratedFunctionTarget::BehindItem(it) => {
-                let indent = IndentLevel::from_node(&it);
-                leading_ws = format!("\n\n{}", indent);
-                fn_def = fn_def.indent(indent);
-                trailing_ws = String::new();
-                it.text_range().end()
-            }
-            GeneratedFunctionTarget::InEmptyItemList(it) => {
-                let indent = IndentLevel::from_node(&it);
-                leading_ws = format!("\n{}", indent + 1);
-                fn_def = fn_def.indent(indent + 1);
-                trailing_ws = format!("\n{}", indent);
-                it.text_range().start() + TextSize::of('{')
-            }
-        };
-
-        FunctionTemplate {
-            insert_offset,
-            leading_ws,
-            ret_type: fn_def.ret_type().unwrap(),
-            should_render_snippet: self.should_render_snippet,
-            fn_def,
-            trailing_ws,
-            file: self.file,
-        }
-    }
-}
-
-enum GeneratedFunctionTarget {
-    BehindItem(SyntaxNode),
-    InEmptyItemList(SyntaxNode),
-}
-
-impl GeneratedFunctionTarget {
-    fn syntax(&self) -> &SyntaxNode {
-        match self {
-            GeneratedFunctionTarget::BehindItem(it) => it,
-            GeneratedFunctionTarget::InEmptyItemList(it) => it,
-        }
-    }
-}
-
-fn fn_name(call: &ast::Path) -> Option<ast::Name> {
-    let name = call.segment()?.syntax().to_string();
-    Some(make::name(&name))
-}
-
-/// Computes the type variables and arguments required for the generated function
-fn fn_args(
-    ctx: &AssistContext,
-    target_module: hir::Module,
-    call: &ast::CallExpr,
-) -> Option<(Option<ast::GenericParamList>, ast::ParamList)> {
-    let mut arg_names = Vec::new();
-    let mut arg_types = Vec::new();
-    for arg in call.arg_list()?.args() {
-        arg_names.push(match fn_arg_name(&arg) {
-            Some(name) => name,
-            None => String::from("arg"),
-        });
-        arg_types.push(match fn_arg_type(ctx, target_module, &arg) {
-            Some(ty) => ty,
-            None => String::from("()"),
-        });
-    }
-    deduplicate_arg_names(&mut arg_names);
-    let params = arg_names.into_iter().zip(arg_types).map(|(name, ty)| {
-        make::param(make::ext::simple_ident_pat(make::name(&name)).into(), make::ty(&ty))
-    });
-    Some((None, make::param_list(None, params)))
-}
-
-/// Makes duplicate argument names unique by appending incrementing numbers.
-///
-/// ```
-/// let mut names: Vec<String> =
-///     vec!["foo".into(), "foo".into(), "bar".into(), "baz".into(), "bar".into()];
-/// deduplicate_arg_names(&mut names);
-/// let expected: Vec<String> =
-///     vec!["foo_1".into(), "foo_2".into(), "bar_1".into(), "baz".into(), "bar_2".into()];
-/// assert_eq!(names, expected);
-/// ```
-fn deduplicate_arg_names(arg_names: &mut Vec<String>) {
-    let arg_name_counts = arg_names.iter().fold(FxHashMap::default(), |mut m, name| {
-        *m.entry(name).or_insert(0) += 1;
-        m
-    });
-    let duplicate_arg_names: FxHashSet<String> = arg_name_counts
-        .into_iter()
-        .filter(|(_, count)| *count >= 2)
-        .map(|(name, _)| name.clone())
-        .collect();
-
-    let mut counter_per_name = FxHashMap::default();
-    for arg_name in arg_names.iter_mut() {
-        if duplicate_arg_names.contains(arg_name) {
-            let counter = counter_per_name.entry(arg_name.clone()).or_insert(1);
-            arg_name.push('_');
-            arg_name.push_str(&counter.to_string());
-            *counter += 1;
-        }
-    }
-}
-
-fn fn_arg_name(fn_arg: &ast::Expr) -> Option<String> {
-    match fn_arg {
-        ast::Expr::CastExpr(cast_expr) => fn_arg_name(&cast_expr.expr()?),
-        _ => {
-            let s = fn_arg
-                .syntax()
-                .descendants()
-                .filter(|d| ast::NameRef::can_cast(d.kind()))
-                .last()?
-                .to_string();
-            Some(to_lower_snake_case(&s))
-        }
-    }
-}
-
-fn fn_arg_type(
-    ctx: &AssistContext,
-    target_module: hir::Module,
-    fn_arg: &ast::Expr,
-) -> Option<String> {
-    let ty = ctx.sema.type_of_expr(fn_arg)?;
-    if ty.is_unknown() {
-        return None;
-    }
-
-    if let Ok(rendered) = ty.display_source_code(ctx.db(), target_module.into()) {
-        Some(rendered)
-    } else {
-        None
-    }
-}
-
-/// Returns the position inside the current mod or file
-/// directly after the current block
-/// We want to write the generated function directly after
-/// fns, impls or macro calls, but inside mods
-fn next_space_for_fn_after_call_site(expr: &ast::CallExpr) -> Option<GeneratedFunctionTarget> {
-    let mut ancestors = expr.syntax().ancestors().peekable();
-    let mut last_ancestor: Option<SyntaxNode> = None;
-    while let Some(next_ancestor) = ancestors.next() {
-        match next_ancestor.kind() {
-            SyntaxKind::SOURCE_FILE => {
-                break;
-            }
-            SyntaxKind::ITEM_LIST => {
-                if ancestors.peek().map(|a| a.kind()) == Some(SyntaxKind::MODULE) {
-                    break;
-                }
-            }
-            _ => {}
-        }
-        last_ancestor = Some(next_ancestor);
-    }
-    last_ancestor.map(GeneratedFunctionTarget::BehindItem)
-}
-
-fn next_space_for_fn_in_module(
-    db: &dyn hir::db::AstDatabase,
-    module_source: &hir::InFile<hir::ModuleSource>,
-) -> Option<(FileId, GeneratedFunctionTarget)> {
-    let file = module_source.file_id.original_file(db);
-    let assist_item = match &module_source.value {
-        hir::ModuleSource::SourceFile(it) => {
-            if let Some(last_item) = it.items().last() {
-                GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
-            } else {
-                GeneratedFunctionTarget::BehindItem(it.syntax().clone())
-            }
-        }
-        hir::ModuleSource::Module(it) => {
-            if let Some(last_item) = it.item_list().and_then(|it| it.items().last()) {
-                GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
-            } else {
-                GeneratedFunctionTarget::InEmptyItemList(it.item_list()?.syntax().clone())
-            }
-        }
-        hir::ModuleSource::BlockExpr(it) => {
-            if let Some(last_item) =
-                it.statements().take_while(|stmt| matches!(stmt, ast::Stmt::Item(_))).last()
-            {
-                GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
-            } else {
-                GeneratedFunctionTarget::InEmptyItemList(it.syntax().clone())
-            }
-        }
-    };
-    Some((file, assist_item))
-}
-
 #[cfg(test)]
 mod tests {
     use crate::tests::{check_assist, check_assist_not_applicable};
@@ -1146,8 +870,7 @@ fn bar(baz: ()) {}
 
     #[test]
     fn create_method_with_no_args() {
-        // FIXME: This is wrong, this should just work.
-        check_assist_not_applicable(
+        check_assist(
             generate_function,
             r#"
 struct Foo;
@@ -1156,7 +879,131 @@ impl Foo {
         self.bar()$0;
     }
 }
-        "#,
+"#,
+            r#"
+struct Foo;
+impl Foo {
+    fn foo(&self) {
+        self.bar();
+    }
+
+    fn bar(&self) ${0:-> ()} {
+        todo!()
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn create_method_in_new_impl() {
+        check_assist(
+            generate_function,
+            r"
+struct Foo;
+fn foo() {
+    Foo.bar$0();
+}
+",
+            r"
+struct Foo;
+
+impl Foo {
+    fn bar(&self) ${0:-> ()} {
+        todo!()
+    }
+}
+fn foo() {
+    Foo.bar();
+}
+",
+        )
+    }
+
+    #[test]
+    fn create_method_in_existing_impl() {
+        check_assist(
+            generate_function,
+            r"
+struct Foo;
+impl Foo {
+    fn baz(&self) {}
+}
+fn foo(foo: &Foo) {
+    let x: u32 = foo.bar$0(1usize);
+}
+",
+            r"
+struct Foo;
+impl Foo {
+    fn baz(&self) {}
+
+    fn bar(&self, arg: usize) -> u32 {
+        todo!()
+    }
+}
+fn foo(foo: &Foo) {
+    let x: u32 = foo.bar(1usize);
+}
+",
+        )
+    }
+
+    #[test]
+    fn create_method_in_other_module() {
+        check_assist(
+            generate_function,
+            r"
+mod m {
+    pub struct Foo;
+    impl Foo {}
+}
+fn foo() {
+    m::Foo.bar$0();
+}
+",
+            r"
+mod m {
+    pub struct Foo;
+    impl Foo {
+        pub(crate) fn bar(&self) ${0:-> ()} {
+            todo!()
+        }
+    }
+}
+fn foo() {
+    m::Foo.bar();
+}
+",
+        )
+    }
+
+    #[test]
+    fn create_method_not_applicable_if_resolved() {
+        check_assist_not_applicable(
+            generate_function,
+            r"
+struct Foo;
+impl Foo {
+    fn bar(&self) {}
+}
+fn foo() {
+    Foo.bar$0();
+}
+",
+        )
+    }
+
+    #[test]
+    fn create_method_not_applicable_for_generic_type_without_impl() {
+        check_assist_not_applicable(
+            generate_function,
+            r"
+struct Foo<T>(T);
+fn foo() {
+    Foo(0).bar$0();
+}
+",
         )
     }
 }
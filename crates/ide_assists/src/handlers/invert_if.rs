@@ -5,7 +5,7 @@ use syntax::{
 
 use crate::{
     assist_context::{AssistContext, Assists},
-    utils::invert_boolean_expression,
+    utils::{invert_boolean_expression, is_let_condition},
     AssistId, AssistKind,
 };
 
@@ -37,12 +37,12 @@ pub(crate) fn invert_if(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
         return None;
     }
 
-    // This assist should not apply for if-let.
-    if expr.condition()?.pat().is_some() {
+    let cond = expr.condition()?;
+    // This assist should not apply for if-let, including let chains.
+    if is_let_condition(&cond) {
         return None;
     }
 
-    let cond = expr.condition()?.expr()?;
     let then_node = expr.then_branch()?.syntax().clone();
     let else_block = match expr.else_branch()? {
         ast::ElseBranch::Block(it) => it,
@@ -123,6 +123,10 @@ mod tests {
         check_assist_not_applicable(
             invert_if,
             "fn f() { i$0f let Some(_) = Some(1) { 1 } else { 0 } }",
+        );
+        check_assist_not_applicable(
+            invert_if,
+            "fn f() { i$0f true && let Some(_) = Some(1) { 1 } else { 0 } }",
         )
     }
 
@@ -3,7 +3,7 @@ use syntax::{
     SyntaxKind::WHITESPACE,
 };
 
-use crate::{AssistContext, AssistId, AssistKind, Assists};
+use crate::{utils::is_let_condition, AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: move_guard_to_arm_body
 //
@@ -39,12 +39,9 @@ pub(crate) fn move_guard_to_arm_body(acc: &mut Assists, ctx: &AssistContext) ->
 
     let guard_condition = guard.expr()?;
     let arm_expr = match_arm.expr()?;
-    let if_expr = make::expr_if(
-        make::condition(guard_condition, None),
-        make::block_expr(None, Some(arm_expr.clone())),
-        None,
-    )
-    .indent(arm_expr.indent_level());
+    let if_expr =
+        make::expr_if(guard_condition, make::block_expr(None, Some(arm_expr.clone())), None)
+            .indent(arm_expr.indent_level());
 
     let target = guard.syntax().text_range();
     acc.add(
@@ -115,7 +112,7 @@ pub(crate) fn move_arm_cond_to_match_guard(acc: &mut Assists, ctx: &AssistContex
         return None;
     }
     // Not support moving if let to arm guard
-    if cond.pat().is_some() {
+    if is_let_condition(&cond) {
         return None;
     }
 
@@ -108,7 +108,9 @@ fn is_leaf_or_control_flow_expr(macro_call: &ast::MacroCall) -> bool {
         || match macro_call.syntax().parent() {
             Some(parent) => match_ast! {
                 match parent {
-                    ast::Condition(_it) => true,
+                    ast::IfExpr(_it) => true,
+                    ast::WhileExpr(_it) => true,
+                    ast::LetExpr(_it) => true,
                     ast::MatchExpr(_it) => true,
                     _ => false,
                 }
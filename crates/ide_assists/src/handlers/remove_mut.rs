@@ -1,4 +1,5 @@
-use syntax::{SyntaxKind, TextRange, T};
+use ide_db::helpers::mut_keyword_removal_range;
+use syntax::T;
 
 use crate::{AssistContext, AssistId, AssistKind, Assists};
 
@@ -19,11 +20,7 @@ use crate::{AssistContext, AssistId, AssistKind, Assists};
 // ```
 pub(crate) fn remove_mut(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
     let mut_token = ctx.find_token_syntax_at_offset(T![mut])?;
-    let delete_from = mut_token.text_range().start();
-    let delete_to = match mut_token.next_token() {
-        Some(it) if it.kind() == SyntaxKind::WHITESPACE => it.text_range().end(),
-        _ => mut_token.text_range().end(),
-    };
+    let delete_range = mut_keyword_removal_range(&mut_token);
 
     let target = mut_token.text_range();
     acc.add(
@@ -31,7 +28,7 @@ pub(crate) fn remove_mut(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
         "Remove `mut` keyword",
         target,
         |builder| {
-            builder.delete(TextRange::new(delete_from, delete_to));
+            builder.delete(delete_range);
         },
     )
 }
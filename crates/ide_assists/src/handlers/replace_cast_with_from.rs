@@ -0,0 +1,294 @@
+use hir::ModuleDef;
+use ide_db::helpers::{
+    insert_use::{insert_use, ImportScope},
+    mod_path_to_ast, FamousDefs,
+};
+use syntax::{ast, AstNode};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_cast_with_from
+//
+// Replaces an `as` cast between primitive types with a `From` conversion if
+// it is lossless, or with a checked `TryFrom` conversion between integers.
+//
+// ```
+// fn main() {
+//     let x = 1u8;
+//     let y = x $0as u32;
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let x = 1u8;
+//     let y = u32::from(x);
+// }
+// ```
+pub(crate) fn replace_cast_with_from(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let cast_expr = ctx.find_node_at_offset::<ast::CastExpr>()?;
+    let expr = cast_expr.expr()?;
+    // `_` is inferred, so it's replaced by the type it stands for.
+    let target_ty = match cast_expr.ty()? {
+        ast::Type::InferType(_) => None,
+        ty => Some(ty),
+    };
+
+    let from = ctx.sema.type_of_expr(&expr)?.as_builtin()?.name().to_string();
+    let to = ctx
+        .sema
+        .type_of_expr(&ast::Expr::CastExpr(cast_expr.clone()))?
+        .as_builtin()?
+        .name()
+        .to_string();
+    if from == to {
+        return None;
+    }
+    let target_ty = target_ty.map_or_else(|| to.clone(), |it| it.to_string());
+
+    let expr = match &expr {
+        ast::Expr::ParenExpr(it) => it.expr()?,
+        _ => expr,
+    };
+    let target = cast_expr.syntax().text_range();
+
+    if has_from_impl(&from, &to) {
+        return acc.add(
+            AssistId("replace_cast_with_from", AssistKind::RefactorRewrite),
+            format!("Replace `as` with `{}::from`", target_ty),
+            target,
+            |builder| {
+                builder.replace(target, format!("{}::from({})", target_ty, expr));
+            },
+        );
+    }
+
+    if !has_try_from_impl(&from, &to) {
+        return None;
+    }
+    let scope = ctx.sema.scope(cast_expr.syntax());
+    let module = scope.module()?;
+    let try_from = FamousDefs(&ctx.sema, Some(module.krate())).core_convert_TryFrom()?;
+    let import = if scope.traits_in_scope().contains(&try_from.into()) {
+        None
+    } else {
+        let path = module.find_use_path(ctx.db(), ModuleDef::Trait(try_from))?;
+        let import_scope =
+            ImportScope::find_insert_use_container_with_macros(cast_expr.syntax(), &ctx.sema)?;
+        Some((path, import_scope))
+    };
+
+    acc.add(
+        AssistId("replace_cast_with_from", AssistKind::RefactorRewrite),
+        format!("Replace `as` with `{}::try_from`", target_ty),
+        target,
+        |builder| {
+            builder.replace(target, format!("{}::try_from({}).unwrap()", target_ty, expr));
+            if let Some((path, scope)) = import {
+                let scope = match scope {
+                    ImportScope::File(it) => ImportScope::File(builder.make_mut(it)),
+                    ImportScope::Module(it) => ImportScope::Module(builder.make_mut(it)),
+                    ImportScope::Block(it) => ImportScope::Block(builder.make_mut(it)),
+                };
+                insert_use(&scope, mod_path_to_ast(&path), &ctx.config.insert_use);
+            }
+        },
+    )
+}
+
+/// The signedness and width of fixed-size integer types.
+fn fixed_int(name: &str) -> Option<(bool, u32)> {
+    let res = match name {
+        "u8" => (false, 8),
+        "u16" => (false, 16),
+        "u32" => (false, 32),
+        "u64" => (false, 64),
+        "u128" => (false, 128),
+        "i8" => (true, 8),
+        "i16" => (true, 16),
+        "i32" => (true, 32),
+        "i64" => (true, 64),
+        "i128" => (true, 128),
+        _ => return None,
+    };
+    Some(res)
+}
+
+fn is_int(name: &str) -> bool {
+    fixed_int(name).is_some() || name == "usize" || name == "isize"
+}
+
+/// Whether the standard library has a `From` impl between the two primitive
+/// types. Pointer-sized integers only convert from small integers, since
+/// their width depends on the target.
+fn has_from_impl(from: &str, to: &str) -> bool {
+    match (from, to) {
+        ("bool", to) => is_int(to),
+        ("u8", "char") | ("char", "u32" | "u64" | "u128") | ("f32", "f64") => true,
+        (from, "f32") => matches!(from, "u8" | "u16" | "i8" | "i16"),
+        (from, "f64") => matches!(from, "u8" | "u16" | "u32" | "i8" | "i16" | "i32"),
+        (from, "usize") => matches!(from, "u8" | "u16"),
+        (from, "isize") => matches!(from, "u8" | "i8" | "i16"),
+        (from, to) => match (fixed_int(from), fixed_int(to)) {
+            (Some((from_signed, from_bits)), Some((to_signed, to_bits))) => {
+                (from_signed == to_signed || to_signed) && to_bits > from_bits
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Whether the standard library has a `TryFrom` impl between the two
+/// primitive types, which is the case for all integer types.
+fn has_try_from_impl(from: &str, to: &str) -> bool {
+    (is_int(from) && is_int(to)) || (from == "u32" && to == "char")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable, check_assist_target};
+
+    use super::*;
+
+    #[test]
+    fn replace_lossless_casts_with_from() {
+        check_assist(
+            replace_cast_with_from,
+            r#"
+fn f(x: i16) -> i64 {
+    x as$0 i64
+}
+"#,
+            r#"
+fn f(x: i16) -> i64 {
+    i64::from(x)
+}
+"#,
+        );
+        check_assist(
+            replace_cast_with_from,
+            r#"
+fn f(a: u8, b: u8) -> f64 {
+    (a + b) as$0 f64
+}
+"#,
+            r#"
+fn f(a: u8, b: u8) -> f64 {
+    f64::from(a + b)
+}
+"#,
+        );
+        check_assist(
+            replace_cast_with_from,
+            r#"
+fn f(b: bool) -> usize {
+    b as$0 usize
+}
+"#,
+            r#"
+fn f(b: bool) -> usize {
+    usize::from(b)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_fallible_casts_with_try_from() {
+        check_assist(
+            replace_cast_with_from,
+            r#"
+//- minicore: try_from
+fn f(x: u64) -> u8 {
+    x as$0 u8
+}
+"#,
+            r#"
+use core::convert::TryFrom;
+
+fn f(x: u64) -> u8 {
+    u8::try_from(x).unwrap()
+}
+"#,
+        );
+        check_assist(
+            replace_cast_with_from,
+            r#"
+//- minicore: try_from
+use core::convert::TryFrom;
+
+fn f(x: u32) -> usize {
+    x as$0 usize
+}
+"#,
+            r#"
+use core::convert::TryFrom;
+
+fn f(x: u32) -> usize {
+    usize::try_from(x).unwrap()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_inferred_cast_type() {
+        check_assist(
+            replace_cast_with_from,
+            r#"
+fn f(x: u8) -> u32 {
+    x as$0 _
+}
+"#,
+            r#"
+fn f(x: u8) -> u32 {
+    u32::from(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn target_is_the_cast() {
+        check_assist_target(
+            replace_cast_with_from,
+            r#"
+fn f(x: u8) -> u32 {
+    1 + x as$0 u32
+}
+"#,
+            "x as u32",
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_other_casts() {
+        check_assist_not_applicable(
+            replace_cast_with_from,
+            r#"
+//- minicore: try_from
+fn f(x: f64, p: *const u8, y: u8) {
+    let _ = x as$0 i32;
+    let _ = p as usize;
+    let _ = y as u8;
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_cast_with_from,
+            r#"
+fn f(p: *const u8) -> usize {
+    p as$0 usize
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_cast_with_from,
+            r#"
+fn f(y: u8) -> u8 {
+    y as$0 u8
+}
+"#,
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use hir::ModuleDef;
 use ide_db::helpers::{import_assets::NameToImport, mod_path_to_ast};
-use ide_db::items_locator;
+use ide_db::{items_locator, RootDatabase};
 use itertools::Itertools;
 use syntax::{
     ast::{self, make, AstNode, NameOwner},
-    SyntaxKind::{IDENT, WHITESPACE},
+    SyntaxKind::{BLOCK_EXPR, IDENT, MATCH_ARM, MATCH_ARM_LIST, WHITESPACE},
+    SyntaxNode, SyntaxToken, T,
 };
 
 use crate::{
@@ -18,7 +19,8 @@ use crate::{
 
 // Assist: replace_derive_with_manual_impl
 //
-// Converts a `derive` impl into a manual one.
+// Converts a `derive` impl into a manual one. For the builtin derives of `core`
+// traits, the impl contains the code the derive generates.
 //
 // ```
 // # trait Debug { fn fmt(&self, f: &mut Formatter) -> Result<()>; }
@@ -110,9 +112,24 @@ fn add_assist(
         target,
         |builder| {
             let insert_pos = adt.syntax().text_range().end();
+            update_attribute(builder, input, &trait_name, attr);
+            if let Some(items) = trait_.and_then(|trait_| derived_items(ctx.db(), trait_, adt)) {
+                let trait_path = trait_path.to_string();
+                match ctx.config.snippet_cap {
+                    Some(cap) => builder.insert_snippet(
+                        cap,
+                        insert_pos,
+                        generate_trait_impl_text(adt, &trait_path, &format!("    $0{}", items)),
+                    ),
+                    None => builder.insert(
+                        insert_pos,
+                        generate_trait_impl_text(adt, &trait_path, &format!("    {}", items)),
+                    ),
+                }
+                return;
+            }
             let impl_def_with_items =
                 impl_def_from_trait(&ctx.sema, &annotated_name, trait_, trait_path);
-            update_attribute(builder, input, &trait_name, attr);
             let trait_path = format!("{}", trait_path);
             match (ctx.config.snippet_cap, impl_def_with_items) {
                 (None, _) => {
@@ -166,6 +183,90 @@ fn impl_def_from_trait(
     Some((impl_def, first_assoc_item))
 }
 
+/// Renders the items the builtin derive of `trait_` generates for `adt`, if `trait_` is a
+/// derivable trait from `core`.
+fn derived_items(db: &RootDatabase, trait_: hir::Trait, adt: &ast::Adt) -> Option<String> {
+    if trait_.module(db).krate().display_name(db).as_deref() != Some("core") {
+        return None;
+    }
+    let impl_ = hir::builtin_derive_impl(&trait_.name(db), adt)?;
+    let items: Vec<_> =
+        impl_.assoc_item_list()?.assoc_items().map(|item| format_derived(item.syntax())).collect();
+    if items.is_empty() {
+        return None;
+    }
+    Some(items.join("\n\n    "))
+}
+
+/// Inserts whitespace into `node`, which comes from a macro expansion and has none, assuming
+/// it is an impl item.
+fn format_derived(node: &SyntaxNode) -> String {
+    let is_block_brace = |token: &SyntaxToken| {
+        matches!(token.kind(), T!['{'] | T!['}'])
+            && matches!(token.parent().map(|it| it.kind()), Some(BLOCK_EXPR | MATCH_ARM_LIST))
+    };
+    let is_text = |token: &SyntaxToken| {
+        token.kind().is_keyword() || token.kind().is_literal() || token.kind() == IDENT
+    };
+
+    let tokens: Vec<_> = node
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() != WHITESPACE)
+        .collect();
+    let mut buf = String::new();
+    let mut indent = 1;
+    for (idx, token) in tokens.iter().enumerate() {
+        let next = match tokens.get(idx + 1) {
+            Some(it) => it,
+            None => {
+                buf.push_str(token.text());
+                break;
+            }
+        };
+        let is_arm_comma = token.kind() == T![,]
+            && matches!(token.parent().map(|it| it.kind()), Some(MATCH_ARM | MATCH_ARM_LIST));
+        // Drop trailing commas in tuples and struct literals.
+        if token.kind() == T![,] && !is_arm_comma && matches!(next.kind(), T![')'] | T!['}']) {
+            if next.kind() == T!['}'] {
+                buf.push(' ');
+            }
+            continue;
+        }
+        buf.push_str(token.text());
+
+        let newline = if is_block_brace(token) && token.kind() == T!['{'] {
+            if next.kind() == T!['}'] {
+                continue;
+            }
+            indent += 1;
+            true
+        } else if is_block_brace(next) && next.kind() == T!['}'] {
+            indent -= 1;
+            true
+        } else {
+            is_arm_comma || token.kind() == T![;]
+        };
+        if newline {
+            buf.push('\n');
+            buf.push_str(&"    ".repeat(indent));
+            continue;
+        }
+
+        let space = match (token.kind(), next.kind()) {
+            (T![,] | T![:], _) | (_, T!['{']) | (T!['{'], _) | (_, T!['}']) => true,
+            (T![=>] | T![->] | T![=] | T![==] | T![&&], _)
+            | (_, T![=>] | T![->] | T![=] | T![==] | T![&&]) => true,
+            (T![match], _) => true,
+            _ => is_text(token) && is_text(next),
+        };
+        if space {
+            buf.push(' ');
+        }
+    }
+    buf
+}
+
 fn update_attribute(
     builder: &mut AssistBuilder,
     input: &ast::TokenTree,
@@ -247,6 +348,79 @@ impl fmt::Debug for Foo {
 "#,
         )
     }
+    #[test]
+    fn add_custom_impl_clone_generates_body() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            r#"
+//- minicore: clone, derive
+#[derive(Clo$0ne)]
+struct Foo {
+    bar: u32,
+    baz: (u8, u8),
+}
+"#,
+            r#"
+struct Foo {
+    bar: u32,
+    baz: (u8, u8),
+}
+
+impl Clone for Foo {
+    $0fn clone(&self) -> Self {
+        match self {
+            Foo { bar: __self_0, baz: __self_1 } => Foo { bar: core::clone::Clone::clone(__self_0), baz: core::clone::Clone::clone(__self_1) },
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_partial_ord_generates_body() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            r#"
+//- minicore: ord, derive
+#[derive(PartialOrd$0)]
+enum Foo {
+    A(u8),
+    B,
+}
+"#,
+            r#"
+enum Foo {
+    A(u8),
+    B,
+}
+
+impl PartialOrd for Foo {
+    $0fn partial_cmp(&self, other: &Self) -> core::option::Option<core::cmp::Ordering> {
+        match (self, other) {
+            (Foo::A(__self_0), Foo::A(__arg1_0)) => match core::cmp::PartialOrd::partial_cmp(__self_0, __arg1_0) {
+                core::option::Option::Some(core::cmp::Ordering::Equal) => core::option::Option::Some(core::cmp::Ordering::Equal),
+                cmp => cmp,
+            },
+            (Foo::B, Foo::B) => core::option::Option::Some(core::cmp::Ordering::Equal),
+            _ => {
+                let __self_vi = match self {
+                    Foo::A(..) => 0,
+                    Foo::B => 1,
+                };
+                let __arg1_vi = match other {
+                    Foo::A(..) => 0,
+                    Foo::B => 1,
+                };
+                core::cmp::PartialOrd::partial_cmp(&__self_vi, &__arg1_vi)
+            },
+        }
+    }
+}
+"#,
+        )
+    }
+
     #[test]
     fn add_custom_impl_all() {
         check_assist(
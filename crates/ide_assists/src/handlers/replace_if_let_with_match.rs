@@ -12,7 +12,7 @@ use syntax::{
 };
 
 use crate::{
-    utils::{does_pat_match_variant, unwrap_trivial_block},
+    utils::{does_pat_match_variant, is_let_condition, unwrap_trivial_block},
     AssistContext, AssistId, AssistKind, Assists,
 };
 
@@ -52,15 +52,18 @@ pub(crate) fn replace_if_let_with_match(acc: &mut Assists, ctx: &AssistContext)
             None
         }
     });
-    let scrutinee_to_be_expr = if_expr.condition()?.expr()?;
+    let scrutinee_to_be_expr = match if_expr.condition()? {
+        ast::Expr::LetExpr(let_expr) => let_expr.expr()?,
+        cond => cond,
+    };
 
     let mut pat_seen = false;
     let mut cond_bodies = Vec::new();
     for if_expr in if_exprs {
-        let cond = if_expr.condition()?;
-        let expr = cond.expr()?;
-        let cond = match cond.pat() {
-            Some(pat) => {
+        let cond = match if_expr.condition()? {
+            ast::Expr::LetExpr(let_expr) => {
+                let pat = let_expr.pat()?;
+                let expr = let_expr.expr()?;
                 if scrutinee_to_be_expr.syntax().text() != expr.syntax().text() {
                     // Only if all condition expressions are equal we can merge them into a match
                     return None;
@@ -68,7 +71,9 @@ pub(crate) fn replace_if_let_with_match(acc: &mut Assists, ctx: &AssistContext)
                 pat_seen = true;
                 Either::Left(pat)
             }
-            None => Either::Right(expr),
+            // Let chains can't be turned into a single match arm.
+            cond if is_let_condition(&cond) => return None,
+            cond => Either::Right(cond),
         };
         let body = if_expr.then_branch()?;
         cond_bodies.push((cond, body));
@@ -198,7 +203,7 @@ pub(crate) fn replace_match_with_if_let(acc: &mut Assists, ctx: &AssistContext)
         "Replace with if let",
         target,
         move |edit| {
-            let condition = make::condition(scrutinee, Some(if_let_pat));
+            let condition = make::expr_let(if_let_pat, scrutinee).into();
             let then_block = match then_expr.reset_indent() {
                 ast::Expr::BlockExpr(block) => block,
                 expr => make::block_expr(iter::empty(), Some(expr)),
@@ -290,6 +295,18 @@ fn main() {
         )
     }
 
+    #[test]
+    fn test_if_let_with_match_unapplicable_for_let_chains() {
+        check_assist_not_applicable(
+            replace_if_let_with_match,
+            r#"
+fn main() {
+    if $0let true = true && let Some(1) = None {} else {}
+}
+"#,
+        )
+    }
+
     #[test]
     fn test_if_let_with_match_no_else() {
         check_assist(
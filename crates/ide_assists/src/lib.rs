@@ -59,6 +59,7 @@ mod handlers {
     mod convert_comment_block;
     mod convert_iter_for_each_to_for;
     mod convert_into_to_from;
+    mod convert_match_to_let_else;
     mod convert_tuple_struct_to_named_struct;
     mod early_return;
     mod expand_glob_import;
@@ -102,6 +103,7 @@ mod handlers {
     mod remove_unused_param;
     mod reorder_fields;
     mod reorder_impl;
+    mod replace_cast_with_from;
     mod replace_derive_with_manual_impl;
     mod replace_for_loop_with_for_each;
     mod replace_if_let_with_match;
@@ -129,6 +131,7 @@ mod handlers {
             convert_comment_block::convert_comment_block,
             convert_iter_for_each_to_for::convert_iter_for_each_to_for,
             convert_into_to_from::convert_into_to_from,
+            convert_match_to_let_else::convert_match_to_let_else,
             convert_tuple_struct_to_named_struct::convert_tuple_struct_to_named_struct,
             early_return::convert_to_guarded_return,
             expand_glob_import::expand_glob_import,
@@ -175,6 +178,7 @@ mod handlers {
             remove_unused_param::remove_unused_param,
             reorder_fields::reorder_fields,
             reorder_impl::reorder_impl,
+            replace_cast_with_from::replace_cast_with_from,
             replace_derive_with_manual_impl::replace_derive_with_manual_impl,
             replace_for_loop_with_for_each::replace_for_loop_with_for_each,
             replace_if_let_with_match::replace_if_let_with_match,
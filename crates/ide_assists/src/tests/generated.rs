@@ -258,6 +258,29 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_match_to_let_else() {
+    check_doc_test(
+        "convert_match_to_let_else",
+        r#####"
+//- minicore: option
+fn foo(opt: Option<i32>) -> i32 {
+    $0let x = match opt {
+        Some(it) => it,
+        None => return 0,
+    };
+    x
+}
+"#####,
+        r#####"
+fn foo(opt: Option<i32>) -> i32 {
+    let Some(x) = opt else { return 0 };
+    x
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_to_guarded_return() {
     check_doc_test(
@@ -1333,6 +1356,25 @@ impl Foo for Bar {
     )
 }
 
+#[test]
+fn doctest_replace_cast_with_from() {
+    check_doc_test(
+        "replace_cast_with_from",
+        r#####"
+fn main() {
+    let x = 1u8;
+    let y = x $0as u32;
+}
+"#####,
+        r#####"
+fn main() {
+    let x = 1u8;
+    let y = u32::from(x);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_derive_with_manual_impl() {
     check_doc_test(
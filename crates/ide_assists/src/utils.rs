@@ -212,6 +212,12 @@ pub(crate) fn vis_offset(node: &SyntaxNode) -> TextSize {
         .unwrap_or_else(|| node.text_range().start())
 }
 
+/// Whether the condition of an `if` or `while` binds variables, either as a
+/// single `let` or as part of a let chain.
+pub(crate) fn is_let_condition(cond: &ast::Expr) -> bool {
+    cond.syntax().descendants().any(|it| ast::LetExpr::can_cast(it.kind()))
+}
+
 pub(crate) fn invert_boolean_expression(
     sema: &Semantics<RootDatabase>,
     expr: ast::Expr,
@@ -431,10 +431,9 @@ impl<'a> CompletionContext<'a> {
                             .and_then(|e| self.sema.type_of_expr(&e));
                         (ty, None)
                     },
-                    ast::IfExpr(it) => {
+                    ast::LetExpr(it) => {
                         cov_mark::hit!(expected_type_if_let_without_leading_char);
-                        let ty = it.condition()
-                            .and_then(|cond| cond.expr())
+                        let ty = it.expr()
                             .and_then(|e| self.sema.type_of_expr(&e));
                         (ty, None)
                     },
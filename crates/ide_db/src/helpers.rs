@@ -4,6 +4,7 @@ pub mod insert_use;
 pub mod merge_imports;
 pub mod rust_doc;
 pub mod generated_lints;
+pub mod generate_function;
 
 use std::collections::VecDeque;
 
@@ -12,7 +13,7 @@ use either::Either;
 use hir::{Crate, Enum, ItemInNs, MacroDef, Module, ModuleDef, Name, ScopeDef, Semantics, Trait};
 use syntax::{
     ast::{self, make, LoopBodyOwner},
-    AstNode, SyntaxKind, SyntaxToken, TokenAtOffset, WalkEvent,
+    AstNode, SyntaxKind, SyntaxToken, TextRange, TokenAtOffset, WalkEvent,
 };
 
 use crate::RootDatabase;
@@ -33,6 +34,17 @@ pub fn pick_best_token(
     tokens.max_by_key(move |t| f(t.kind()))
 }
 
+/// Returns the range to delete to remove the given `mut` keyword, including the
+/// whitespace following it.
+pub fn mut_keyword_removal_range(mut_token: &SyntaxToken) -> TextRange {
+    let delete_from = mut_token.text_range().start();
+    let delete_to = match mut_token.next_token() {
+        Some(it) if it.kind() == SyntaxKind::WHITESPACE => it.text_range().end(),
+        _ => mut_token.text_range().end(),
+    };
+    TextRange::new(delete_from, delete_to)
+}
+
 /// Converts the mod path struct into its ast representation.
 pub fn mod_path_to_ast(path: &hir::ModPath) -> ast::Path {
     let _p = profile::span("mod_path_to_ast");
@@ -118,6 +130,10 @@ impl FamousDefs<'_, '_> {
         self.find_trait("core:convert:Into")
     }
 
+    pub fn core_convert_TryFrom(&self) -> Option<Trait> {
+        self.find_trait("core:convert:TryFrom")
+    }
+
     pub fn core_option_Option(&self) -> Option<Enum> {
         self.find_enum("core:option:Option")
     }
@@ -257,6 +273,7 @@ pub fn for_each_tail_expr(expr: &ast::Expr, cb: &mut dyn FnMut(&ast::Expr)) {
         | ast::Expr::FieldExpr(_)
         | ast::Expr::ForExpr(_)
         | ast::Expr::IndexExpr(_)
+        | ast::Expr::LetExpr(_)
         | ast::Expr::Literal(_)
         | ast::Expr::MacroCall(_)
         | ast::Expr::MacroStmts(_)
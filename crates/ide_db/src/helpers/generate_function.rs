@@ -0,0 +1,417 @@
+//! Generation of function stubs for unresolved function and method calls.
+use base_db::FileId;
+use hir::{HasSource, HirDisplay, Semantics};
+use rustc_hash::{FxHashMap, FxHashSet};
+use stdx::to_lower_snake_case;
+use syntax::{
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        make, ArgListOwner, AstNode, GenericParamsOwner, ModuleItemOwner,
+    },
+    SyntaxKind, SyntaxNode, TextSize,
+};
+
+use crate::{helpers::SnippetCap, RootDatabase};
+
+/// A rendered function stub, together with the place to insert it at.
+pub struct FunctionTemplate {
+    pub file: FileId,
+    pub insert_offset: TextSize,
+    pub fn_def: ast::Fn,
+    /// The return type, left out when it is a unit guess that can't be rendered as a snippet.
+    pub ret_type: Option<ast::RetType>,
+    /// Whether the return type is a guess that the user should look at.
+    pub should_render_snippet: bool,
+    leading_ws: String,
+    trailing_ws: String,
+}
+
+impl FunctionTemplate {
+    /// Returns the text to insert, with `fn_text` standing for the function
+    /// itself, e.g. as a snippet.
+    pub fn with_fn_text(&self, fn_text: &str) -> String {
+        format!("{}{}{}", self.leading_ws, fn_text, self.trailing_ws)
+    }
+
+    /// Returns the text to insert.
+    pub fn text(&self) -> String {
+        self.with_fn_text(&self.fn_def.to_string())
+    }
+}
+
+pub struct FunctionBuilder {
+    target: GeneratedFunctionTarget,
+    fn_name: ast::Name,
+    type_params: Option<ast::GenericParamList>,
+    params: ast::ParamList,
+    ret_type: ast::RetType,
+    should_render_snippet: bool,
+    file: FileId,
+    needs_pub: bool,
+    /// Set when the function is a method that needs a new impl block for the
+    /// given type.
+    impl_for: Option<hir::Name>,
+}
+
+impl FunctionBuilder {
+    /// Prepares a generated function that matches `call`, which is in `file`.
+    /// The function is generated in `target_module` or next to `call`
+    pub fn from_call(
+        sema: &Semantics<RootDatabase>,
+        file: FileId,
+        call: &ast::CallExpr,
+        path: &ast::Path,
+        target_module: Option<hir::Module>,
+    ) -> Option<Self> {
+        let mut file = file;
+        let target = match &target_module {
+            Some(target_module) => {
+                let module_source = target_module.definition_source(sema.db);
+                let (in_file, target) = next_space_for_fn_in_module(sema.db, &module_source)?;
+                file = in_file;
+                target
+            }
+            None => next_space_for_fn_after_call_site(call)?,
+        };
+        let needs_pub = target_module.is_some();
+        let target_module = target_module.or_else(|| sema.scope(target.syntax()).module())?;
+        let fn_name = fn_name(path)?;
+        let (type_params, params) = fn_args(sema, target_module, None, call.arg_list()?)?;
+        let (ret_type, should_render_snippet) =
+            make_return_type(sema, &ast::Expr::CallExpr(call.clone()), target_module);
+
+        Some(Self {
+            target,
+            fn_name,
+            type_params,
+            params,
+            ret_type,
+            should_render_snippet,
+            file,
+            needs_pub,
+            impl_for: None,
+        })
+    }
+
+    /// Prepares a method for the receiver type of `call`, generated in an
+    /// inherent impl of the type or in a new impl block after its definition.
+    pub fn from_method_call(
+        sema: &Semantics<RootDatabase>,
+        call: &ast::MethodCallExpr,
+    ) -> Option<Self> {
+        let db = sema.db;
+        let fn_name = make::name(&call.name_ref()?.text());
+        let receiver_ty = sema.type_of_expr(&call.receiver()?)?.strip_references();
+        let adt = receiver_ty.as_adt()?;
+        let adt_source = match adt {
+            hir::Adt::Struct(it) => it.source(db)?.map(ast::Adt::from),
+            hir::Adt::Enum(it) => it.source(db)?.map(ast::Adt::from),
+            hir::Adt::Union(it) => it.source(db)?.map(ast::Adt::from),
+        };
+        if adt_source.file_id.call_node(db).is_some() {
+            return None;
+        }
+        let file = adt_source.file_id.original_file(db);
+        let target_module = adt.module(db);
+
+        let impl_ = hir::Impl::all_for_type(db, receiver_ty)
+            .into_iter()
+            .filter(|impl_| impl_.trait_(db).is_none())
+            .filter_map(|impl_| impl_.source(db))
+            .find(|src| src.file_id == adt_source.file_id);
+        let (target, impl_for) = match impl_ {
+            Some(impl_) => {
+                let assoc_item_list = impl_.value.assoc_item_list()?;
+                let target = match assoc_item_list.assoc_items().last() {
+                    Some(last_item) => {
+                        GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
+                    }
+                    None => {
+                        GeneratedFunctionTarget::InEmptyItemList(assoc_item_list.syntax().clone())
+                    }
+                };
+                (target, None)
+            }
+            None => {
+                // Writing out the generic parameters of a new impl is left to the user.
+                if adt_source.value.generic_param_list().is_some() {
+                    return None;
+                }
+                let adt_node = adt_source.value.syntax().clone();
+                (GeneratedFunctionTarget::BehindItem(adt_node), Some(adt.name(db)))
+            }
+        };
+
+        let needs_pub = sema.scope(call.syntax()).module() != Some(target_module);
+        let (type_params, params) =
+            fn_args(sema, target_module, Some(make::self_param()), call.arg_list()?)?;
+        let (ret_type, should_render_snippet) =
+            make_return_type(sema, &ast::Expr::MethodCallExpr(call.clone()), target_module);
+        Some(Self {
+            target,
+            fn_name,
+            type_params,
+            params,
+            ret_type,
+            should_render_snippet,
+            file,
+            needs_pub,
+            impl_for,
+        })
+    }
+
+    pub fn fn_name(&self) -> &ast::Name {
+        &self.fn_name
+    }
+
+    /// Renders the function. Without `cap`, a return type that is only a unit guess is omitted.
+    pub fn render(self, cap: Option<SnippetCap>) -> FunctionTemplate {
+        let placeholder_expr = make::ext::expr_todo();
+        let fn_body = make::block_expr(vec![], Some(placeholder_expr));
+        let visibility = if self.needs_pub { Some(make::visibility_pub_crate()) } else { None };
+        let ret_type =
+            if self.should_render_snippet && cap.is_none() { None } else { Some(self.ret_type) };
+        let mut fn_def =
+            make::fn_(visibility, self.fn_name, self.type_params, self.params, fn_body, ret_type);
+        let leading_ws;
+        let trailing_ws;
+
+        let insert_offset = match self.target {
+            GeneratedFunctionTarget::BehindItem(it) => match self.impl_for {
+                Some(ty) => {
+                    let indent = IndentLevel::from_node(&it);
+                    leading_ws = format!("\n\n{}impl {} {{\n{}", indent, ty, indent + 1);
+                    fn_def = fn_def.indent(indent + 1);
+                    trailing_ws = format!("\n{}}}", indent);
+                    it.text_range().end()
+                }
+                None => {
+                    let indent = IndentLevel::from_node(&it);
+                    leading_ws = format!("\n\n{}", indent);
+                    fn_def = fn_def.indent(indent);
+                    trailing_ws = String::new();
+                    it.text_range().end()
+                }
+            },
+            GeneratedFunctionTarget::InEmptyItemList(it) => {
+                let indent = IndentLevel::from_node(&it);
+                leading_ws = format!("\n{}", indent + 1);
+                fn_def = fn_def.indent(indent + 1);
+                trailing_ws = format!("\n{}", indent);
+                it.text_range().start() + TextSize::of('{')
+            }
+        };
+
+        FunctionTemplate {
+            file: self.file,
+            insert_offset,
+            ret_type: fn_def.ret_type(),
+            should_render_snippet: self.should_render_snippet,
+            fn_def,
+            leading_ws,
+            trailing_ws,
+        }
+    }
+}
+
+enum GeneratedFunctionTarget {
+    BehindItem(SyntaxNode),
+    InEmptyItemList(SyntaxNode),
+}
+
+impl GeneratedFunctionTarget {
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            GeneratedFunctionTarget::BehindItem(it) => it,
+            GeneratedFunctionTarget::InEmptyItemList(it) => it,
+        }
+    }
+}
+
+fn fn_name(call: &ast::Path) -> Option<ast::Name> {
+    let name = call.segment()?.syntax().to_string();
+    Some(make::name(&name))
+}
+
+/// Computes the return type of the generated function from the type expected
+/// for `call`, and whether that type should be rendered as a snippet.
+fn make_return_type(
+    sema: &Semantics<RootDatabase>,
+    call: &ast::Expr,
+    target_module: hir::Module,
+) -> (ast::RetType, bool) {
+    // should_render_snippet intends to express a rough level of confidence about
+    // the correctness of the return type.
+    //
+    // If we are able to infer some return type, and that return type is not unit, we
+    // don't want to render the snippet. The assumption here is in this situation the
+    // return type is just as likely to be correct as any other part of the generated
+    // function.
+    //
+    // In the case where the return type is inferred as unit it is likely that the
+    // user does in fact intend for this generated function to return some non unit
+    // type, but that the current state of their code doesn't allow that return type
+    // to be accurately inferred.
+    let (ret_ty, should_render_snippet) = {
+        match sema.type_of_expr(call) {
+            Some(ty) if ty.is_unknown() || ty.is_unit() => (make::ty_unit(), true),
+            Some(ty) => {
+                let rendered = ty.display_source_code(sema.db, target_module.into());
+                match rendered {
+                    Ok(rendered) => (make::ty(&rendered), false),
+                    Err(_) => (make::ty_unit(), true),
+                }
+            }
+            None => (make::ty_unit(), true),
+        }
+    };
+    (make::ret_type(ret_ty), should_render_snippet)
+}
+
+/// Computes the type variables and arguments required for the generated function
+fn fn_args(
+    sema: &Semantics<RootDatabase>,
+    target_module: hir::Module,
+    self_param: Option<ast::SelfParam>,
+    arg_list: ast::ArgList,
+) -> Option<(Option<ast::GenericParamList>, ast::ParamList)> {
+    let mut arg_names = Vec::new();
+    let mut arg_types = Vec::new();
+    for arg in arg_list.args() {
+        arg_names.push(match fn_arg_name(&arg) {
+            Some(name) => name,
+            None => String::from("arg"),
+        });
+        arg_types.push(match fn_arg_type(sema, target_module, &arg) {
+            Some(ty) => ty,
+            None => String::from("()"),
+        });
+    }
+    deduplicate_arg_names(&mut arg_names);
+    let params = arg_names.into_iter().zip(arg_types).map(|(name, ty)| {
+        make::param(make::ext::simple_ident_pat(make::name(&name)).into(), make::ty(&ty))
+    });
+    Some((None, make::param_list(self_param, params)))
+}
+
+/// Makes duplicate argument names unique by appending incrementing numbers.
+///
+/// ```
+/// let mut names: Vec<String> =
+///     vec!["foo".into(), "foo".into(), "bar".into(), "baz".into(), "bar".into()];
+/// deduplicate_arg_names(&mut names);
+/// let expected: Vec<String> =
+///     vec!["foo_1".into(), "foo_2".into(), "bar_1".into(), "baz".into(), "bar_2".into()];
+/// assert_eq!(names, expected);
+/// ```
+fn deduplicate_arg_names(arg_names: &mut Vec<String>) {
+    let arg_name_counts = arg_names.iter().fold(FxHashMap::default(), |mut m, name| {
+        *m.entry(name).or_insert(0) += 1;
+        m
+    });
+    let duplicate_arg_names: FxHashSet<String> = arg_name_counts
+        .into_iter()
+        .filter(|(_, count)| *count >= 2)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let mut counter_per_name = FxHashMap::default();
+    for arg_name in arg_names.iter_mut() {
+        if duplicate_arg_names.contains(arg_name) {
+            let counter = counter_per_name.entry(arg_name.clone()).or_insert(1);
+            arg_name.push('_');
+            arg_name.push_str(&counter.to_string());
+            *counter += 1;
+        }
+    }
+}
+
+fn fn_arg_name(fn_arg: &ast::Expr) -> Option<String> {
+    match fn_arg {
+        ast::Expr::CastExpr(cast_expr) => fn_arg_name(&cast_expr.expr()?),
+        _ => {
+            let s = fn_arg
+                .syntax()
+                .descendants()
+                .filter(|d| ast::NameRef::can_cast(d.kind()))
+                .last()?
+                .to_string();
+            Some(to_lower_snake_case(&s))
+        }
+    }
+}
+
+fn fn_arg_type(
+    sema: &Semantics<RootDatabase>,
+    target_module: hir::Module,
+    fn_arg: &ast::Expr,
+) -> Option<String> {
+    let ty = sema.type_of_expr(fn_arg)?;
+    if ty.is_unknown() {
+        return None;
+    }
+
+    if let Ok(rendered) = ty.display_source_code(sema.db, target_module.into()) {
+        Some(rendered)
+    } else {
+        None
+    }
+}
+
+/// Returns the position inside the current mod or file
+/// directly after the current block
+/// We want to write the generated function directly after
+/// fns, impls or macro calls, but inside mods
+fn next_space_for_fn_after_call_site(expr: &ast::CallExpr) -> Option<GeneratedFunctionTarget> {
+    let mut ancestors = expr.syntax().ancestors().peekable();
+    let mut last_ancestor: Option<SyntaxNode> = None;
+    while let Some(next_ancestor) = ancestors.next() {
+        match next_ancestor.kind() {
+            SyntaxKind::SOURCE_FILE => {
+                break;
+            }
+            SyntaxKind::ITEM_LIST => {
+                if ancestors.peek().map(|a| a.kind()) == Some(SyntaxKind::MODULE) {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        last_ancestor = Some(next_ancestor);
+    }
+    last_ancestor.map(GeneratedFunctionTarget::BehindItem)
+}
+
+fn next_space_for_fn_in_module(
+    db: &dyn hir::db::AstDatabase,
+    module_source: &hir::InFile<hir::ModuleSource>,
+) -> Option<(FileId, GeneratedFunctionTarget)> {
+    let file = module_source.file_id.original_file(db);
+    let assist_item = match &module_source.value {
+        hir::ModuleSource::SourceFile(it) => {
+            if let Some(last_item) = it.items().last() {
+                GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
+            } else {
+                GeneratedFunctionTarget::BehindItem(it.syntax().clone())
+            }
+        }
+        hir::ModuleSource::Module(it) => {
+            if let Some(last_item) = it.item_list().and_then(|it| it.items().last()) {
+                GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
+            } else {
+                GeneratedFunctionTarget::InEmptyItemList(it.item_list()?.syntax().clone())
+            }
+        }
+        hir::ModuleSource::BlockExpr(it) => {
+            if let Some(last_item) =
+                it.statements().take_while(|stmt| matches!(stmt, ast::Stmt::Item(_))).last()
+            {
+                GeneratedFunctionTarget::BehindItem(last_item.syntax().clone())
+            } else {
+                GeneratedFunctionTarget::InEmptyItemList(it.syntax().clone())
+            }
+        }
+    };
+    Some((file, assist_item))
+}
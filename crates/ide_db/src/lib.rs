@@ -23,7 +23,7 @@ use std::{fmt, sync::Arc};
 
 use base_db::{
     salsa::{self, Durability},
-    AnchoredPath, CrateId, FileId, FileLoader, FileLoaderDelegate, SourceDatabase, Upcast,
+    AnchoredPath, CrateId, FileId, FileLoader, FileLoaderDelegate, SourceDatabase, Upcast, VfsPath,
 };
 use hir::db::{AstDatabase, DefDatabase, HirDatabase};
 use rustc_hash::FxHashSet;
@@ -78,6 +78,9 @@ impl FileLoader for RootDatabase {
     fn resolve_path(&self, path: AnchoredPath) -> Option<FileId> {
         FileLoaderDelegate(self).resolve_path(path)
     }
+    fn file_path(&self, file_id: FileId) -> Option<VfsPath> {
+        FileLoaderDelegate(self).file_path(file_id)
+    }
     fn relevant_crates(&self, file_id: FileId) -> Arc<FxHashSet<CrateId>> {
         FileLoaderDelegate(self).relevant_crates(file_id)
     }
@@ -101,7 +101,7 @@ fn check_pat_field_shorthand(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_diagnostics, check_fix};
+    use crate::tests::{check_diagnostics, check_fix, check_fix_for};
 
     #[test]
     fn test_check_expr_field_shorthand() {
@@ -109,16 +109,19 @@ mod tests {
             r#"
 struct A { a: &'static str }
 fn main() { A { a: "hello" } }
+          //^^^^^^^^^^^^^^^^ error: expected (), found A
 "#,
         );
         check_diagnostics(
             r#"
 struct A(usize);
 fn main() { A { 0: 0 } }
+          //^^^^^^^^^^ error: expected (), found A
 "#,
         );
 
-        check_fix(
+        check_fix_for(
+            "use-field-shorthand",
             r#"
 struct A { a: &'static str }
 fn main() {
@@ -135,7 +138,8 @@ fn main() {
 "#,
         );
 
-        check_fix(
+        check_fix_for(
+            "use-field-shorthand",
             r#"
 struct A { a: &'static str, b: &'static str }
 fn main() {
@@ -0,0 +1,155 @@
+use hir::{CastError, HirDisplay};
+
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: invalid-cast
+//
+// This diagnostic is triggered if an `as` cast is not allowed between the
+// two types, like casting a struct or casting a `bool` to a float.
+pub(crate) fn invalid_cast(ctx: &DiagnosticsContext<'_>, d: &hir::InvalidCast) -> Diagnostic {
+    let from = d.expr_ty.display(ctx.sema.db);
+    let to = d.cast_ty.display(ctx.sema.db);
+    let message = match d.error {
+        CastError::NonScalar => format!("non-primitive cast: `{}` as `{}`", from, to),
+        CastError::CastToBool => format!("cannot cast `{}` as `bool`", from),
+        CastError::CastToChar => format!("only `u8` can be cast as `char`, not `{}`", from),
+        CastError::NeedViaInt => {
+            format!("casting `{}` as `{}` is invalid: cast through an integer first", from, to)
+        }
+        CastError::NeedViaPtr => {
+            format!("casting `{}` as `{}` is invalid: cast through a raw pointer first", from, to)
+        }
+        CastError::NeedViaThinPtr => {
+            format!("casting `{}` as `{}` is invalid: cast through a thin pointer first", from, to)
+        }
+        CastError::SizedUnsizedCast => {
+            format!("cannot cast thin pointer `{}` to fat pointer `{}`", from, to)
+        }
+        CastError::DifferingKinds => {
+            format!("casting `{}` as `{}` is invalid: the pointer metadata may not match", from, to)
+        }
+        CastError::IllegalCast => format!("casting `{}` as `{}` is invalid", from, to),
+    };
+    Diagnostic::new(
+        "invalid-cast",
+        message,
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn numeric_casts() {
+        check_diagnostics(
+            r#"
+fn f(x: i32, b: bool, c: char) {
+    let _ = x as u8;
+    let _ = x as f64;
+    let _ = b as i32;
+    let _ = c as u32;
+    let _ = 97u8 as char;
+    let _ = x as bool;
+          //^^^^^^^^^ error: cannot cast `i32` as `bool`
+    let _ = x as char;
+          //^^^^^^^^^ error: only `u8` can be cast as `char`, not `i32`
+    let _ = b as f32;
+          //^^^^^^^^ error: casting `bool` as `f32` is invalid
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn enum_casts() {
+        check_diagnostics(
+            r#"
+enum Fieldless { A, B = 10 }
+enum WithData { A(u8), B }
+struct S;
+fn f(e: Fieldless, d: WithData) {
+    let _ = e as i64;
+    let _ = d as i64;
+          //^^^^^^^^ error: non-primitive cast: `WithData` as `i64`
+    let _ = S as u8;
+          //^^^^^^^ error: non-primitive cast: `S` as `u8`
+    let _ = 0 as Fieldless;
+          //^^^^^^^^^^^^^^ error: non-primitive cast: `i32` as `Fieldless`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn pointer_casts() {
+        check_diagnostics(
+            r#"
+//- minicore: sized
+trait Tr {}
+fn f(r: &u8, m: &mut [u8; 2], p: *const u8, s: *const [u8], d: *const dyn Tr, g: fn()) {
+    let _ = r as *const u8;
+    let _ = m as *mut u8;
+    let _ = m as *const [u8; 2];
+    let _ = p as usize;
+    let _ = p as *const i32;
+    let _ = p as *mut u8;
+    let _ = s as *const u8;
+    let _ = s as *const [i32];
+    let _ = g as usize;
+    let _ = g as *const ();
+    let _ = 0usize as *const u8;
+    let _ = f as usize;
+    let _ = r as *mut u8;
+          //^^^^^^^^^^^^ error: casting `&u8` as `*mut u8` is invalid
+    let _ = r as usize;
+          //^^^^^^^^^^ error: casting `&u8` as `usize` is invalid: cast through a raw pointer first
+    let _ = s as usize;
+          //^^^^^^^^^^ error: casting `*const [u8]` as `usize` is invalid: cast through a thin pointer first
+    let _ = p as *const [u8];
+          //^^^^^^^^^^^^^^^^ error: cannot cast thin pointer `*const u8` to fat pointer `*const [u8]`
+    let _ = s as *const dyn Tr;
+          //^^^^^^^^^^^^^^^^^^ error: casting `*const [u8]` as `*const dyn Tr` is invalid: the pointer metadata may not match
+    let _ = true as *const u8;
+          //^^^^^^^^^^^^^^^^^ error: casting `bool` as `*const u8` is invalid: cast through an integer first
+    let _ = p as f32;
+          //^^^^^^^^ error: casting `*const u8` as `f32` is invalid
+    let _ = 0usize as fn();
+          //^^^^^^^^^^^^^^ error: non-primitive cast: `usize` as `fn()`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn coercion_casts() {
+        check_diagnostics(
+            r#"
+//- minicore: coerce_unsized
+trait Tr {}
+impl Tr for u8 {}
+fn f(a: &[u8; 2], x: &u8, m: &mut u8) {
+    let _ = a as &[u8];
+    let _ = x as &dyn Tr;
+    let _ = m as &u8;
+    let _ = (|| ()) as fn();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unknown_types_are_not_checked() {
+        check_diagnostics(
+            r#"
+fn f<T>(x: Unknown, p: *const T) {
+    let _ = x as u8;
+    let _ = 1 as Unknown;
+    let _ = p as *const [T];
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use hir::HirDisplay;
+
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: lossy-cast
+//
+// This diagnostic is triggered by numeric `as` casts which might not preserve
+// the value, like truncating or sign-changing integer casts and casts between
+// integers and floats. The `replace_cast_with_from` assist rewrites such casts
+// to a checked conversion.
+pub(crate) fn lossy_cast(ctx: &DiagnosticsContext<'_>, d: &hir::LossyCast) -> Option<Diagnostic> {
+    // Macros often cast between types given as arguments.
+    if d.expr.file_id.expansion_info(ctx.sema.db).is_some() {
+        return None;
+    }
+
+    let res = Diagnostic::new(
+        "lossy-cast",
+        format!(
+            "lossy cast: `{}` as `{}` may not preserve the value",
+            d.expr_ty.display(ctx.sema.db),
+            d.cast_ty.display(ctx.sema.db)
+        ),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .severity(Severity::WeakWarning)
+    .experimental();
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics_for;
+
+    #[test]
+    fn integer_casts() {
+        check_diagnostics_for(
+            "lossy-cast",
+            r#"
+fn f(a: u8, b: i32, c: u64, d: usize) {
+    let _ = a as u16;
+    let _ = a as i16;
+    let _ = b as i64;
+    let _ = c as usize;
+    let _ = d as u64;
+    let _ = a as i8;
+          //^^^^^^^ weak: lossy cast: `u8` as `i8` may not preserve the value
+    let _ = b as u32;
+          //^^^^^^^^ weak: lossy cast: `i32` as `u32` may not preserve the value
+    let _ = c as u32;
+          //^^^^^^^^ weak: lossy cast: `u64` as `u32` may not preserve the value
+    let _ = d as i64;
+          //^^^^^^^^ weak: lossy cast: `usize` as `i64` may not preserve the value
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn float_and_char_casts() {
+        check_diagnostics_for(
+            "lossy-cast",
+            r#"
+fn f(a: u16, b: u32, x: f32, y: f64, c: char, p: *const u8) {
+    let _ = a as f32;
+    let _ = b as f64;
+    let _ = x as f64;
+    let _ = c as u32;
+    let _ = p as usize;
+    let _ = b as f32;
+          //^^^^^^^^ weak: lossy cast: `u32` as `f32` may not preserve the value
+    let _ = y as f32;
+          //^^^^^^^^ weak: lossy cast: `f64` as `f32` may not preserve the value
+    let _ = x as i32;
+          //^^^^^^^^ weak: lossy cast: `f32` as `i32` may not preserve the value
+    let _ = c as u8;
+          //^^^^^^^ weak: lossy cast: `char` as `u8` may not preserve the value
+    let _ = p as u32;
+          //^^^^^^^^ weak: lossy cast: `*const u8` as `u32` may not preserve the value
+}
+"#,
+        );
+    }
+}
@@ -1,24 +1,150 @@
-use crate::{Diagnostic, DiagnosticsContext};
+use ide_db::base_db::ProcMacroDiagnosticLevel;
+
+use crate::{Diagnostic, DiagnosticsContext, Severity};
 
 // Diagnostic: macro-error
 //
-// This diagnostic is shown for macro expansion errors.
+// This diagnostic is shown for macro expansion errors, and for errors and warnings emitted by
+// procedural macros.
 pub(crate) fn macro_error(ctx: &DiagnosticsContext<'_>, d: &hir::MacroError) -> Diagnostic {
-    Diagnostic::new(
-        "macro-error",
-        d.message.clone(),
-        ctx.sema.diagnostics_display_range(d.node.clone()).range,
-    )
-    .experimental()
+    // Use more accurate position if available.
+    let display_range = d
+        .precise_location
+        .unwrap_or_else(|| ctx.sema.diagnostics_display_range(d.node.clone()).range);
+    let severity = match d.level {
+        ProcMacroDiagnosticLevel::Error => Severity::Error,
+        ProcMacroDiagnosticLevel::Warning => Severity::Warning,
+        ProcMacroDiagnosticLevel::Note | ProcMacroDiagnosticLevel::Help => Severity::WeakWarning,
+    };
+    Diagnostic::new("macro-error", d.message.clone(), display_range)
+        .severity(severity)
+        .experimental()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use ide_db::base_db::{
+        Env, ProcMacro, ProcMacroDiagnostic, ProcMacroDiagnosticLevel, ProcMacroExpander,
+        ProcMacroExpansion, ProcMacroKind, ProcMacroSpans,
+    };
+
     use crate::{
-        tests::{check_diagnostics, check_diagnostics_with_config},
+        tests::{
+            check_diagnostics, check_diagnostics_with_config, check_diagnostics_with_proc_macros,
+        },
         DiagnosticsConfig,
     };
 
+    /// Warns about identifiers named `bad`, or leaves a note on the whole call
+    /// if there are none. Expands to nothing.
+    #[derive(Debug)]
+    struct LintExpander;
+
+    impl ProcMacroExpander for LintExpander {
+        fn expand(
+            &self,
+            subtree: &tt::Subtree,
+            _: Option<&tt::Subtree>,
+            _: &Env,
+            _: &ProcMacroSpans,
+        ) -> Result<ProcMacroExpansion, tt::ExpansionError> {
+            fn find_bad(subtree: &tt::Subtree) -> Option<tt::TokenId> {
+                subtree.token_trees.iter().find_map(|tt| match tt {
+                    tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) if ident.text == "bad" => {
+                        Some(ident.id)
+                    }
+                    tt::TokenTree::Leaf(_) => None,
+                    tt::TokenTree::Subtree(subtree) => find_bad(subtree),
+                })
+            }
+            let diagnostic = match find_bad(subtree) {
+                Some(id) => ProcMacroDiagnostic {
+                    level: ProcMacroDiagnosticLevel::Warning,
+                    message: "`bad` is a bad name".to_string(),
+                    spans: vec![id],
+                    children: Vec::new(),
+                },
+                None => ProcMacroDiagnostic {
+                    level: ProcMacroDiagnosticLevel::Note,
+                    message: "nothing to lint".to_string(),
+                    spans: Vec::new(),
+                    children: Vec::new(),
+                },
+            };
+            Ok(ProcMacroExpansion { subtree: Default::default(), diagnostics: vec![diagnostic] })
+        }
+    }
+
+    /// Expands to `compile_error!("derive failed");`, like a derive rejecting its input.
+    #[derive(Debug)]
+    struct CompileErrorExpander;
+
+    impl ProcMacroExpander for CompileErrorExpander {
+        fn expand(
+            &self,
+            _: &tt::Subtree,
+            _: Option<&tt::Subtree>,
+            _: &Env,
+            _: &ProcMacroSpans,
+        ) -> Result<ProcMacroExpansion, tt::ExpansionError> {
+            let unspecified = tt::TokenId::unspecified();
+            let message = tt::Subtree {
+                delimiter: Some(tt::Delimiter {
+                    id: unspecified,
+                    kind: tt::DelimiterKind::Parenthesis,
+                }),
+                token_trees: vec![tt::Leaf::Literal(tt::Literal {
+                    text: "\"derive failed\"".into(),
+                    id: unspecified,
+                })
+                .into()],
+            };
+            let subtree = tt::Subtree {
+                delimiter: None,
+                token_trees: vec![
+                    tt::Leaf::Ident(tt::Ident { text: "compile_error".into(), id: unspecified })
+                        .into(),
+                    tt::Leaf::Punct(tt::Punct {
+                        char: '!',
+                        spacing: tt::Spacing::Alone,
+                        id: unspecified,
+                    })
+                    .into(),
+                    message.into(),
+                    tt::Leaf::Punct(tt::Punct {
+                        char: ';',
+                        spacing: tt::Spacing::Alone,
+                        id: unspecified,
+                    })
+                    .into(),
+                ],
+            };
+            Ok(ProcMacroExpansion { subtree, diagnostics: Vec::new() })
+        }
+    }
+
+    fn lint_proc_macros() -> Vec<ProcMacro> {
+        vec![
+            ProcMacro {
+                name: "Lint".into(),
+                kind: ProcMacroKind::CustomDerive,
+                expander: Arc::new(LintExpander),
+            },
+            ProcMacro {
+                name: "lint".into(),
+                kind: ProcMacroKind::FuncLike,
+                expander: Arc::new(LintExpander),
+            },
+            ProcMacro {
+                name: "Fail".into(),
+                kind: ProcMacroKind::CustomDerive,
+                expander: Arc::new(CompileErrorExpander),
+            },
+        ]
+    }
+
     #[test]
     fn builtin_macro_fails_expansion() {
         check_diagnostics(
@@ -141,7 +267,22 @@ fn f() {
     m!();
 
     m!(hi);
-  //^^^^^^ error: leftover tokens
+  //^^^^^^ error: leftover tokens (rule 1 of 1 matched 0 of 1 token trees)
+}
+      "#,
+        );
+    }
+    #[test]
+    fn macro_rules_diag_names_failed_fragment() {
+        check_diagnostics(
+            r#"
+macro_rules! m {
+    (fn $i:ident) => {};
+    (struct $i:ident;) => {};
+}
+fn f() {
+    m!(struct 92;);
+  //^^^^^^^^^^^^^^ error: expected ident (rule 2 of 2 matched 1 of 3 token trees, failed at `$i:ident`)
 }
       "#,
         );
@@ -166,8 +307,58 @@ macro_rules! outer {
 
 fn f() {
     outer!();
-} //^^^^^^^^ error: leftover tokens
+} //^^^^^^^^ error: leftover tokens (rule 1 of 1 matched 0 of 1 token trees)
 "#,
         )
     }
+
+    #[test]
+    fn proc_macro_emitted_diagnostics() {
+        check_diagnostics_with_proc_macros(
+            r#"
+//- /proc_macros.rs crate:proc_macros
+#[proc_macro_derive(Lint)]
+pub fn derive_lint(item: TokenStream) -> TokenStream { item }
+#[proc_macro]
+pub fn lint(item: TokenStream) -> TokenStream { item }
+//- /main.rs crate:main deps:proc_macros
+use proc_macros::{lint, Lint};
+
+#[derive(Lint)]
+struct S { bad: u32 }
+         //^^^ warn: `bad` is a bad name
+
+fn f(bad: u32) {
+    lint!(1 + bad);
+            //^^^ warn: `bad` is a bad name
+    lint!();
+  //^^^^^^^ weak: nothing to lint
+}
+"#,
+            lint_proc_macros(),
+        );
+    }
+
+    #[test]
+    fn compile_error_from_derive() {
+        check_diagnostics_with_proc_macros(
+            r#"
+//- /proc_macros.rs crate:proc_macros
+#[proc_macro_derive(Fail)]
+pub fn derive_fail(item: TokenStream) -> TokenStream { item }
+//- /main.rs crate:main deps:proc_macros
+use proc_macros::Fail;
+
+#[rustc_builtin_macro]
+macro_rules! compile_error {}
+
+  compile_error!("item failed");
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: item failed
+
+  #[derive(Fail)] struct S;
+//^^^^^^^^^^^^^^^^^^^^^^^^^ error: derive failed
+"#,
+            lint_proc_macros(),
+        );
+    }
 }
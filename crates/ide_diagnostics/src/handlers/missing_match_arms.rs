@@ -278,6 +278,7 @@ fn main() {
     match (true, false) {
         (true, false, true) => (),
         (true) => (),
+      // ^^^^ error: expected (bool, bool), found bool
     }
     match (true, false) { (true,) => {} }
     match (0) { () => () }
@@ -910,12 +911,13 @@ fn main() {
 
         #[test]
         fn reference_patterns_in_fields() {
-            cov_mark::check_count!(validate_match_bailed_out, 2);
+            cov_mark::check_count!(validate_match_bailed_out, 1);
 
             check_diagnostics(
                 r#"
 fn main() {
     match (&false,) {
+        //^^^^^^^^^ error: missing match arm
         (true,) => {}
     }
     match (&false,) {
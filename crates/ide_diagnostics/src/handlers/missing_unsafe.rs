@@ -20,7 +20,7 @@ mod tests {
         check_diagnostics(
             r#"
 fn main() {
-    let x = &5 as *const usize;
+    let x = &5 as *const i32;
     unsafe { let y = *x; }
     let z = *x;
 }         //^^ error: this operation is unsafe and requires an unsafe function or block
@@ -36,13 +36,13 @@ struct HasUnsafe;
 
 impl HasUnsafe {
     unsafe fn unsafe_fn(&self) {
-        let x = &5 as *const usize;
+        let x = &5 as *const i32;
         let y = *x;
     }
 }
 
 unsafe fn unsafe_fn() {
-    let x = &5 as *const usize;
+    let x = &5 as *const i32;
     let y = *x;
 }
 
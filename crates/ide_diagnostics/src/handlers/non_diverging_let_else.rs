@@ -0,0 +1,58 @@
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: non-diverging-let-else
+//
+// This diagnostic is triggered if the `else` block of a `let ... else`
+// statement can complete normally instead of diverging with `return`,
+// `break`, `continue` or a call to a function returning `!`.
+pub(crate) fn non_diverging_let_else(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::NonDivergingLetElse,
+) -> Diagnostic {
+    Diagnostic::new(
+        "non-diverging-let-else",
+        "`else` clause of `let...else` does not diverge",
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn non_diverging_else() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn f(x: Option<i32>) -> i32 {
+    let Some(a) = x else {};
+                       //^^ error: `else` clause of `let...else` does not diverge
+    let Some(b) = x else { 1; };
+                       //^^^^^^ error: `else` clause of `let...else` does not diverge
+    a + b
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn diverging_else() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn never() -> ! { loop {} }
+fn f(x: Option<i32>) -> i32 {
+    let Some(a) = x else { return 0 };
+    let Some(b) = x else { return 0; };
+    loop {
+        let Some(c) = x else { break };
+        let Some(d) = x else { continue; };
+        let Some(e) = x else { never() };
+    }
+    a + b
+}
+"#,
+        );
+    }
+}
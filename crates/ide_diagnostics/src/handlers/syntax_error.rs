@@ -0,0 +1,168 @@
+//! Renders errors reported by the lexer, the parser and the syntax tree
+//! validation, with fixes for the simple cases of a missing token.
+
+use ide_db::{base_db::FileId, source_change::SourceChange};
+use syntax::{ParseErrorCode, SyntaxError, SyntaxKind, SyntaxNode, TextRange, T};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic};
+
+// Diagnostic: syntax-error
+//
+// This diagnostic is triggered if the code doesn't parse.
+pub(crate) fn syntax_error(file_id: FileId, root: &SyntaxNode, error: &SyntaxError) -> Diagnostic {
+    Diagnostic::new("syntax-error", format!("Syntax Error: {}", error), error.range())
+        .with_fixes(fixes(file_id, root, error))
+}
+
+fn fixes(file_id: FileId, root: &SyntaxNode, error: &SyntaxError) -> Option<Vec<Assist>> {
+    if error.code() != Some(ParseErrorCode::MissingToken) {
+        return None;
+    }
+    let offset = error.range().start();
+    let fixes: Vec<_> = error
+        .expected()
+        .iter()
+        .filter_map(|&kind| {
+            let token = insertable_token(kind)?;
+            let insert = if kind == T![=>] {
+                let followed_by_space =
+                    matches!(root.text().char_at(offset), Some(c) if c.is_whitespace());
+                format!(" {}{}", token, if followed_by_space { "" } else { " " })
+            } else {
+                token.to_string()
+            };
+            let edit = TextEdit::insert(offset, insert);
+            Some(fix(
+                "insert_missing_token",
+                &format!("Insert `{}`", token),
+                SourceChange::from_text_edit(file_id, edit),
+                TextRange::empty(offset),
+            ))
+        })
+        .collect();
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(fixes)
+    }
+}
+
+fn insertable_token(kind: SyntaxKind) -> Option<&'static str> {
+    let res = match kind {
+        T![;] => ";",
+        T![,] => ",",
+        T![')'] => ")",
+        T!['}'] => "}",
+        T![=>] => "=>",
+        _ => return None,
+    };
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_fix, check_fix_for, check_no_fix};
+
+    #[test]
+    fn missing_semicolon() {
+        check_fix(
+            r#"
+fn main() {
+    let x = 92$0
+    let y = x;
+}
+"#,
+            r#"
+fn main() {
+    let x = 92;
+    let y = x;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn missing_comma() {
+        check_fix(
+            r#"
+fn f(a: i32, b: i32) {}
+fn main() {
+    f(1$0 2);
+}
+"#,
+            r#"
+fn f(a: i32, b: i32) {}
+fn main() {
+    f(1, 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn missing_paren() {
+        check_fix_for(
+            "syntax-error",
+            r#"
+fn f(a: i32) {}
+fn main() {
+    f(1$0;
+}
+"#,
+            r#"
+fn f(a: i32) {}
+fn main() {
+    f(1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn missing_curly() {
+        check_fix(
+            r#"
+fn main() {
+    let _x = 92;$0
+"#,
+            r#"
+fn main() {
+    let _x = 92;}
+"#,
+        );
+    }
+
+    #[test]
+    fn missing_fat_arrow() {
+        check_fix(
+            r#"
+fn main() {
+    match 92 {
+        0$0 (),
+        _ => (),
+    }
+}
+"#,
+            r#"
+fn main() {
+    match 92 {
+        0 => (),
+        _ => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_fix_for_missing_expression() {
+        check_no_fix(
+            r#"
+fn main() {
+    let _ = $0;
+}
+"#,
+        );
+    }
+}
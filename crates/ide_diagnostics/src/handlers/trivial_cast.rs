@@ -0,0 +1,99 @@
+use hir::{db::AstDatabase, HirDisplay};
+use ide_db::{assists::Assist, source_change::SourceChange};
+use syntax::{ast, AstNode, TextRange};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: trivial-cast
+//
+// This diagnostic is triggered if an expression is cast to the type it
+// already has.
+pub(crate) fn trivial_cast(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::TrivialCast,
+) -> Option<Diagnostic> {
+    // Macros often cast between types given as arguments, which may coincide.
+    if d.expr.file_id.expansion_info(ctx.sema.db).is_some() {
+        return None;
+    }
+
+    let ty = d.ty.display(ctx.sema.db);
+    let res = Diagnostic::new(
+        "trivial-cast",
+        format!("trivial cast: `{}` as `{}`", ty, ty),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .severity(Severity::WeakWarning)
+    .experimental()
+    .with_fixes(fixes(ctx, d));
+    Some(res)
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::TrivialCast) -> Option<Vec<Assist>> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    let cast_expr = match d.expr.value.to_node(&root) {
+        ast::Expr::CastExpr(it) => it,
+        _ => return None,
+    };
+    let inner = cast_expr.expr()?;
+    let range = cast_expr.syntax().text_range();
+    let edit = TextEdit::delete(TextRange::new(inner.syntax().text_range().end(), range.end()));
+    let source_change =
+        SourceChange::from_text_edit(d.expr.file_id.original_file(ctx.sema.db), edit);
+    Some(vec![fix("remove_cast", "Remove unnecessary cast", source_change, range)])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics_for, check_fix_for};
+
+    #[test]
+    fn trivial_casts() {
+        check_diagnostics_for(
+            "trivial-cast",
+            r#"
+fn f(x: u8, y: &str) {
+    let _ = x as u8;
+          //^^^^^^^ 💡 weak: trivial cast: `u8` as `u8`
+    let _ = y as &str;
+          //^^^^^^^^^ 💡 weak: trivial cast: `&str` as `&str`
+    let _ = x as u16;
+    let _ = 1 as u8;
+    let _ = -1 as i8;
+    let _ = x as _;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_trivial_casts_in_macros() {
+        check_diagnostics_for(
+            "trivial-cast",
+            r#"
+macro_rules! to_u8 { ($e:expr) => { $e as u8 } }
+fn f(x: u8) {
+    let _ = to_u8!(x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_trivial_cast() {
+        check_fix_for(
+            "trivial-cast",
+            r#"
+fn f(x: u32) -> u32 {
+    (x as$0 u32) + 1
+}
+"#,
+            r#"
+fn f(x: u32) -> u32 {
+    (x) + 1
+}
+"#,
+        );
+    }
+}
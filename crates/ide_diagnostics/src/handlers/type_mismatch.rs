@@ -0,0 +1,337 @@
+use hir::{db::AstDatabase, HirDisplay};
+use ide_db::{assists::Assist, helpers::FamousDefs, source_change::SourceChange};
+use syntax::{
+    ast::{self, PrefixOp},
+    AstNode,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: type-mismatch
+//
+// This diagnostic is triggered when the type of an expression does not match
+// the expected type.
+pub(crate) fn type_mismatch(ctx: &DiagnosticsContext<'_>, d: &hir::TypeMismatch) -> Diagnostic {
+    Diagnostic::new(
+        "type-mismatch",
+        format!(
+            "expected {}, found {}",
+            d.expected.display(ctx.sema.db),
+            d.actual.display(ctx.sema.db)
+        ),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::TypeMismatch) -> Option<Vec<Assist>> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    let expr = d.expr.value.to_node(&root);
+    let file_id = d.expr.file_id.original_file(ctx.sema.db);
+
+    let mut edits = Vec::new();
+    remove_deref(ctx, d, &expr, &mut edits);
+    add_reference(ctx, d, &expr, &mut edits);
+    add_into(ctx, d, &expr, &mut edits);
+    wrap_in_variant(ctx, d, &expr, &mut edits);
+
+    let target = expr.syntax().text_range();
+    let fixes = edits
+        .into_iter()
+        .map(|(id, label, edit)| {
+            fix(id, &label, SourceChange::from_text_edit(file_id, edit), target)
+        })
+        .collect::<Vec<_>>();
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(fixes)
+    }
+}
+
+type Edit = (&'static str, String, TextEdit);
+
+fn add_reference(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::TypeMismatch,
+    expr: &ast::Expr,
+    acc: &mut Vec<Edit>,
+) -> Option<()> {
+    let pointee = d.expected.remove_ref()?;
+    if !pointee.could_unify_with(ctx.sema.db, &d.actual) {
+        return None;
+    }
+    let ampersand = if d.expected.is_mutable_reference() { "&mut " } else { "&" };
+    let range = expr.syntax().text_range();
+    let edit = TextEdit::replace(range, format!("{}{}", ampersand, parenthesize(expr)));
+    acc.push(("add_reference_here", format!("Add reference here: `{}`", ampersand.trim()), edit));
+    Some(())
+}
+
+fn remove_deref(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::TypeMismatch,
+    expr: &ast::Expr,
+    acc: &mut Vec<Edit>,
+) -> Option<()> {
+    let prefix_expr = match expr {
+        ast::Expr::PrefixExpr(it) if it.op_kind() == Some(PrefixOp::Deref) => it,
+        _ => return None,
+    };
+    let inner = prefix_expr.expr()?;
+    let inner_ty = ctx.sema.type_of_expr(&inner)?;
+    if !inner_ty.could_unify_with(ctx.sema.db, &d.expected) {
+        return None;
+    }
+    let edit = TextEdit::replace(expr.syntax().text_range(), inner.syntax().text().to_string());
+    acc.push(("remove_deref", "Remove dereference".to_string(), edit));
+    Some(())
+}
+
+fn add_into(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::TypeMismatch,
+    expr: &ast::Expr,
+    acc: &mut Vec<Edit>,
+) -> Option<()> {
+    let krate = ctx.sema.scope(expr.syntax()).krate();
+    let into_trait = FamousDefs(&ctx.sema, krate).core_convert_Into()?;
+    if !d.actual.impls_trait(ctx.sema.db, into_trait, &[d.expected.clone()]) {
+        return None;
+    }
+    let edit =
+        TextEdit::replace(expr.syntax().text_range(), format!("{}.into()", parenthesize(expr)));
+    acc.push(("add_into", "Convert with `.into()`".to_string(), edit));
+    Some(())
+}
+
+fn wrap_in_variant(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::TypeMismatch,
+    expr: &ast::Expr,
+    acc: &mut Vec<Edit>,
+) -> Option<()> {
+    let adt = d.expected.as_adt()?;
+    let famous_defs = FamousDefs(&ctx.sema, ctx.sema.scope(expr.syntax()).krate());
+    let variant = if Some(adt) == famous_defs.core_option_Option().map(hir::Adt::from) {
+        "Some"
+    } else if Some(adt) == famous_defs.core_result_Result().map(hir::Adt::from) {
+        "Ok"
+    } else {
+        return None;
+    };
+    let inner = d.expected.type_arguments().next()?;
+    if !inner.could_unify_with(ctx.sema.db, &d.actual) {
+        return None;
+    }
+    let edit =
+        TextEdit::replace(expr.syntax().text_range(), format!("{}({})", variant, expr.syntax()));
+    acc.push(("wrap_in_variant", format!("Wrap with {}", variant), edit));
+    Some(())
+}
+
+/// Wraps `expr` in parentheses if it would otherwise bind too loosely when
+/// used as an operand of a prefix operator or a method call.
+fn parenthesize(expr: &ast::Expr) -> String {
+    match expr {
+        ast::Expr::BinExpr(_)
+        | ast::Expr::CastExpr(_)
+        | ast::Expr::RangeExpr(_)
+        | ast::Expr::ClosureExpr(_) => format!("({})", expr.syntax()),
+        _ => expr.syntax().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix, check_no_fix};
+
+    #[test]
+    fn mismatch_in_let() {
+        check_diagnostics(
+            r#"
+struct S;
+fn f() {
+    let _x: u32 = S;
+                //^ error: expected u32, found S
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn mismatch_in_reference_mutability() {
+        check_diagnostics(
+            r#"
+fn f() {
+    let _x: &mut i32 = &0;
+                     //^^ error: expected &mut i32, found &i32
+    let _y: &i32 = &raw const 0;
+                 //^^^^^^^^^^^^ error: expected &i32, found *const i32
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_mismatch_with_unknown_types() {
+        check_diagnostics(
+            r#"
+fn f() {
+    let _x: u32 = Unresolved;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_reference_to_argument() {
+        check_fix(
+            r#"
+fn test(_arg: &i32) {}
+fn main() {
+    test(123$0);
+}
+"#,
+            r#"
+fn test(_arg: &i32) {}
+fn main() {
+    test(&123);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_mutable_reference() {
+        check_fix(
+            r#"
+fn test(_arg: &mut i32) {}
+fn main() {
+    let mut x = 0;
+    test($0x);
+}
+"#,
+            r#"
+fn test(_arg: &mut i32) {}
+fn main() {
+    let mut x = 0;
+    test(&mut x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_reference_parenthesizes() {
+        check_fix(
+            r#"
+fn test(_arg: &i32) {}
+fn main() {
+    test($01 + 2);
+}
+"#,
+            r#"
+fn test(_arg: &i32) {}
+fn main() {
+    test(&(1 + 2));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_deref() {
+        check_fix(
+            r#"
+struct S;
+fn test(_arg: &S) {}
+fn main(s: &S) {
+    test($0*s);
+}
+"#,
+            r#"
+struct S;
+fn test(_arg: &S) {}
+fn main(s: &S) {
+    test(s);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_into() {
+        check_fix(
+            r#"
+//- minicore: from
+struct A;
+struct B;
+impl From<A> for B {
+    fn from(_: A) -> B { B }
+}
+fn main() {
+    let _b: B = $0A;
+}
+"#,
+            r#"
+struct A;
+struct B;
+impl From<A> for B {
+    fn from(_: A) -> B { B }
+}
+fn main() {
+    let _b: B = A.into();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wrap_in_some() {
+        check_fix(
+            r#"
+//- minicore: option
+fn main() {
+    let _x: Option<i32> = $092;
+}
+"#,
+            r#"
+fn main() {
+    let _x: Option<i32> = Some(92);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wrap_in_ok() {
+        check_fix(
+            r#"
+//- minicore: result
+fn main() {
+    let _x: Result<i32, ()> = $092;
+}
+"#,
+            r#"
+fn main() {
+    let _x: Result<i32, ()> = Ok(92);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_fix_for_unrelated_types() {
+        check_no_fix(
+            r#"
+struct S;
+fn main() {
+    let _x: u32 = $0S;
+}
+"#,
+        );
+    }
+}
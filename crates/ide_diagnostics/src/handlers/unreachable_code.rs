@@ -0,0 +1,166 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unreachable-code
+//
+// This diagnostic is shown for statements following an expression which
+// always diverges, like `return` or `panic!()`, and for match arms which are
+// covered by the arms before them.
+pub(crate) fn unreachable_code(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnreachableCode,
+) -> Option<Diagnostic> {
+    // Code generated by a macro is often unreachable by design.
+    if d.node.file_id.expansion_info(ctx.sema.db).is_some() {
+        return None;
+    }
+
+    let res = Diagnostic::new(
+        "unreachable-code",
+        "unreachable code",
+        ctx.sema.diagnostics_display_range(d.node.clone()).range,
+    )
+    .severity(Severity::WeakWarning)
+    .with_unused(true)
+    .experimental();
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics_for;
+
+    #[test]
+    fn statements_after_return() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+fn f() -> i32 {
+    return 1;
+    let x = 2;
+  //^^^^^^^^^^ weak: unreachable code
+    x + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn tail_after_diverging_statement() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+fn f(c: bool) -> i32 {
+    if c {
+        return 1;
+    } else {
+        loop {}
+    }
+    2
+  //^ weak: unreachable code
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_one_diagnostic_per_block() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+fn f() {
+    loop {}
+    g();
+  //^^^^ weak: unreachable code
+    g();
+    {
+        g();
+    }
+}
+fn g() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_maybe_diverging_code() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+fn f(c: bool) -> i32 {
+    if c {
+        return 1;
+    }
+    let _fut = async { return 1 };
+    let _cl = || return 2;
+    loop {
+        if c {
+            break;
+        }
+    }
+    while c {
+        return 3;
+    }
+    4
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn labeled_break_out_of_loop() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+fn f() {
+    'outer: loop {
+        loop {
+            break 'outer;
+        }
+    }
+    g();
+}
+fn g() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn unreachable_match_arms() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+enum E { A, B }
+fn f(e: E, a: bool, b: bool) {
+    match e {
+        E::A => (),
+        E::B => (),
+        _ => (),
+      //^^^^^^^ weak: unreachable code
+    }
+    match (a, b) {
+        (true, _) if b => (),
+        (true, _) => (),
+        (_, false) => (),
+        (true, true) => {}
+      //^^^^^^^^^^^^^^^^^^ weak: unreachable code
+        (false, true) => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_irrefutable_if_let() {
+        check_diagnostics_for(
+            "unreachable-code",
+            r#"
+struct S(u8);
+fn f(s: S) {
+    if let S(_) = s {}
+    while let S(_) = S(0) {}
+}
+"#,
+        );
+    }
+}
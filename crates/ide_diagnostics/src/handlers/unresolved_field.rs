@@ -0,0 +1,199 @@
+use hir::{db::AstDatabase, HasSource, HirDisplay};
+use ide_db::{
+    assists::Assist,
+    base_db::{SourceDatabase, SourceDatabaseExt},
+    source_change::SourceChange,
+};
+use syntax::{
+    algo,
+    ast::{self, make},
+    AstNode,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: unresolved-field
+//
+// This diagnostic is triggered if a field does not exist on a given type.
+pub(crate) fn unresolved_field(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedField,
+) -> Diagnostic {
+    Diagnostic::new(
+        "unresolved-field",
+        format!("no field `{}` on type `{}`", d.name, d.receiver.display(ctx.sema.db)),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::UnresolvedField) -> Option<Vec<Assist>> {
+    let db = ctx.sema.db;
+    let root = db.parse_or_expand(d.expr.file_id)?;
+    let field_expr = match d.expr.value.to_node(&root) {
+        ast::Expr::FieldExpr(it) => it,
+        _ => return None,
+    };
+    let field_name = field_expr.name_ref()?;
+    if field_name.as_tuple_field().is_some() {
+        return None;
+    }
+
+    let strukt = match d.receiver.strip_references().as_adt()? {
+        hir::Adt::Struct(it) => it,
+        _ => return None,
+    };
+    let struct_src = strukt.source(db)?;
+    if struct_src.file_id.call_node(db).is_some() {
+        // Don't try to edit the output of a macro.
+        return None;
+    }
+    let struct_file_id = struct_src.file_id.original_file(db);
+    if db.source_root(db.file_source_root(struct_file_id)).is_library {
+        return None;
+    }
+
+    let ty = field_ty(ctx, &field_expr, strukt.module(db));
+    let field = make::record_field(None, make::name(&field_name.text()), ty).clone_for_update();
+
+    let struct_root = db.parse(struct_file_id).tree().syntax().clone_for_update();
+    let struct_def: ast::Struct =
+        algo::find_node_at_range(&struct_root, struct_src.value.syntax().text_range())?;
+    let (range, replacement) = match struct_def.field_list() {
+        Some(ast::FieldList::RecordFieldList(field_list)) => {
+            let range = field_list.syntax().text_range();
+            field_list.add_field(field);
+            (range, field_list.to_string())
+        }
+        Some(ast::FieldList::TupleFieldList(_)) => return None,
+        None => {
+            // A unit struct, `struct S;` becomes `struct S { field: Ty }`.
+            let semicolon = struct_def.semicolon_token()?;
+            (semicolon.text_range(), format!(" {}", make::record_field_list(Some(field))))
+        }
+    };
+
+    let edit = TextEdit::replace(range, replacement);
+    Some(vec![fix(
+        "add_field_to_struct",
+        &format!("Add field `{}` to `{}`", field_name, strukt.name(db)),
+        SourceChange::from_text_edit(struct_file_id, edit),
+        field_expr.syntax().text_range(),
+    )])
+}
+
+/// Guesses the type of the new field from an assignment to it, falling back to
+/// `()` when nothing better is known.
+fn field_ty(
+    ctx: &DiagnosticsContext<'_>,
+    field_expr: &ast::FieldExpr,
+    module: hir::Module,
+) -> ast::Type {
+    let assigned = field_expr
+        .syntax()
+        .parent()
+        .and_then(ast::BinExpr::cast)
+        .filter(|it| {
+            it.op_kind() == Some(ast::BinOp::Assignment)
+                && it.lhs().map_or(false, |lhs| lhs.syntax() == field_expr.syntax())
+        })
+        .and_then(|it| it.rhs())
+        .and_then(|rhs| ctx.sema.type_of_expr(&rhs));
+    match assigned {
+        Some(ty) if !ty.is_unknown() => match ty.display_source_code(ctx.sema.db, module.into()) {
+            Ok(ty) => make::ty(&ty),
+            Err(_) => make::ty_unit(),
+        },
+        _ => make::ty_unit(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn unresolved_field() {
+        check_diagnostics(
+            r#"
+struct Foo { bar: i32 }
+fn foo(foo: Foo) {
+    foo.bar;
+    foo.baz;
+  //^^^^^^^ 💡 error: no field `baz` on type `Foo`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_on_unknown_receiver() {
+        check_diagnostics(
+            r#"
+fn foo() {
+    Unknown.baz;
+}
+fn bar(x: &Unknown, t: (Unknown, u32)) {
+    x.baz;
+    t.baz;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unresolved_field_through_reference() {
+        check_diagnostics(
+            r#"
+struct Foo;
+fn foo(foo: &&Foo) {
+    foo.baz;
+  //^^^^^^^ 💡 error: no field `baz` on type `&&Foo`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_field_from_assignment() {
+        check_fix(
+            r#"
+struct Foo {
+    bar: i32,
+}
+fn foo(mut foo: Foo) {
+    foo.baz$0 = 92u8;
+}
+"#,
+            r#"
+struct Foo {
+    bar: i32,
+    baz: u8,
+}
+fn foo(mut foo: Foo) {
+    foo.baz = 92u8;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_field_to_unit_struct() {
+        check_fix(
+            r#"
+struct Foo;
+fn foo(foo: Foo) {
+    foo.baz$0;
+}
+"#,
+            r#"
+struct Foo { baz: () }
+fn foo(foo: Foo) {
+    foo.baz;
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use hir::{db::AstDatabase, HirDisplay};
+use ide_db::{
+    assists::Assist,
+    helpers::{
+        generate_function::FunctionBuilder,
+        import_assets::ImportAssets,
+        insert_use::{insert_use, ImportScope},
+        mod_path_to_ast,
+    },
+    source_change::SourceChange,
+};
+use syntax::{algo, ast, AstNode, TextRange};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: unresolved-method
+//
+// This diagnostic is triggered if a method does not exist on a given type.
+pub(crate) fn unresolved_method(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedMethodCall,
+) -> Diagnostic {
+    Diagnostic::new(
+        "unresolved-method",
+        format!("no method `{}` on type `{}`", d.name, d.receiver.display(ctx.sema.db)),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d))
+}
+
+/// Offers to import the traits in scope of which the method would resolve, or
+/// to generate the method.
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::UnresolvedMethodCall) -> Option<Vec<Assist>> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    let method_call = match d.expr.value.to_node(&root) {
+        ast::Expr::MethodCallExpr(it) => it,
+        _ => return None,
+    };
+    let target = ctx.sema.original_range(method_call.syntax()).range;
+    let mut fixes = import_fixes(ctx, &method_call, target).unwrap_or_default();
+    fixes.extend(generate_method_fix(ctx, &method_call, target));
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(fixes)
+    }
+}
+
+fn import_fixes(
+    ctx: &DiagnosticsContext<'_>,
+    method_call: &ast::MethodCallExpr,
+    target: TextRange,
+) -> Option<Vec<Assist>> {
+    let import_assets = ImportAssets::for_method_call(method_call, &ctx.sema)?;
+    let imports = import_assets.search_for_imports(&ctx.sema, ctx.config.insert_use.prefix_kind);
+    if imports.is_empty() {
+        return None;
+    }
+
+    let scope =
+        ImportScope::find_insert_use_container_with_macros(method_call.syntax(), &ctx.sema)?;
+    let scope_range = ctx.sema.original_range(scope.as_syntax_node());
+    let fixes = imports
+        .into_iter()
+        .filter_map(|import| {
+            let scope_mut = scope_for_update(&scope)?;
+            insert_use(&scope_mut, mod_path_to_ast(&import.import_path), &ctx.config.insert_use);
+            let mut builder = TextEdit::builder();
+            algo::diff(scope.as_syntax_node(), scope_mut.as_syntax_node())
+                .into_text_edit(&mut builder);
+            Some(fix(
+                "auto_import",
+                &format!("Import `{}`", import.import_path),
+                SourceChange::from_text_edit(scope_range.file_id, builder.finish()),
+                target,
+            ))
+        })
+        .collect();
+    Some(fixes)
+}
+
+/// Generates a stub of the method, like the `generate_function` assist.
+fn generate_method_fix(
+    ctx: &DiagnosticsContext<'_>,
+    method_call: &ast::MethodCallExpr,
+    target: TextRange,
+) -> Option<Assist> {
+    let function_builder = FunctionBuilder::from_method_call(&ctx.sema, method_call)?;
+    let label = format!("Generate `{}` method", function_builder.fn_name());
+    let template = function_builder.render(None);
+    let edit = TextEdit::insert(template.insert_offset, template.text());
+    Some(fix(
+        "generate_function",
+        &label,
+        SourceChange::from_text_edit(template.file, edit),
+        target,
+    ))
+}
+
+/// Returns a mutable copy of `scope`, which still lives in a copy of the
+/// whole file so that indentation is computed correctly.
+fn scope_for_update(scope: &ImportScope) -> Option<ImportScope> {
+    let node = scope.as_syntax_node();
+    let root = node.ancestors().last()?.clone_for_update();
+    let range = node.text_range();
+    let scope = match scope {
+        ImportScope::File(_) => ImportScope::File(ast::SourceFile::cast(root)?),
+        ImportScope::Module(_) => ImportScope::Module(algo::find_node_at_range(&root, range)?),
+        ImportScope::Block(_) => ImportScope::Block(algo::find_node_at_range(&root, range)?),
+    };
+    Some(scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix, check_no_fix};
+
+    #[test]
+    fn unresolved_method() {
+        check_diagnostics(
+            r#"
+struct Foo;
+impl Foo {
+    fn bar(&self) {}
+}
+fn foo(foo: Foo) {
+    foo.bar();
+    foo.baz();
+  //^^^^^^^^^ 💡 error: no method `baz` on type `Foo`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_on_unknown_receiver() {
+        check_diagnostics(
+            r#"
+fn foo() {
+    Unknown.baz();
+    let x = Default::default();
+    x.baz();
+}
+fn bar(x: &Unknown, v: Vec<Unknown>) {
+    x.baz();
+    v.baz();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_fix_on_builtin_type() {
+        check_no_fix(
+            r#"
+fn foo(x: u32) {
+    x.baz$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generate_method() {
+        check_fix(
+            r#"
+struct Foo;
+impl Foo {
+    fn bar(&self) {}
+}
+fn foo(foo: Foo) -> u32 {
+    foo.baz$0(1u8)
+}
+"#,
+            r#"
+struct Foo;
+impl Foo {
+    fn bar(&self) {}
+
+    fn baz(&self, arg: u8) -> u32 {
+        todo!()
+    }
+}
+fn foo(foo: Foo) -> u32 {
+    foo.baz(1u8)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generate_method_in_new_impl() {
+        check_fix(
+            r#"
+mod m {
+    pub struct Foo;
+}
+fn foo(foo: &m::Foo) {
+    foo.baz$0();
+}
+"#,
+            r#"
+mod m {
+    pub struct Foo;
+
+    impl Foo {
+        pub(crate) fn baz(&self) {
+            todo!()
+        }
+    }
+}
+fn foo(foo: &m::Foo) {
+    foo.baz();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn import_trait() {
+        check_fix(
+            r#"
+mod m {
+    pub trait Bar {
+        fn bar(&self);
+    }
+    impl Bar for super::Foo {
+        fn bar(&self) {}
+    }
+}
+struct Foo;
+fn foo(foo: Foo) {
+    foo.bar$0();
+}
+"#,
+            r#"
+use m::Bar;
+
+mod m {
+    pub trait Bar {
+        fn bar(&self);
+    }
+    impl Bar for super::Foo {
+        fn bar(&self) {}
+    }
+}
+struct Foo;
+fn foo(foo: Foo) {
+    foo.bar();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn import_trait_in_module() {
+        check_fix(
+            r#"
+mod m {
+    pub trait Bar {
+        fn bar(&self);
+    }
+    impl Bar for () {
+        fn bar(&self) {}
+    }
+}
+mod n {
+    fn foo() {
+        ().bar$0();
+    }
+}
+"#,
+            r#"
+mod m {
+    pub trait Bar {
+        fn bar(&self);
+    }
+    impl Bar for () {
+        fn bar(&self) {}
+    }
+}
+mod n {
+    use crate::m::Bar;
+
+    fn foo() {
+        ().bar();
+    }
+}
+"#,
+        );
+    }
+}
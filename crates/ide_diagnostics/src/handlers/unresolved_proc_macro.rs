@@ -1,10 +1,13 @@
+use stdx::format_to;
+
 use crate::{Diagnostic, DiagnosticsContext, Severity};
 
 // Diagnostic: unresolved-proc-macro
 //
 // This diagnostic is shown when a procedural macro can not be found. This usually means that
 // procedural macro support is simply disabled (and hence is only a weak hint instead of an error),
-// but can also indicate project setup problems.
+// but can also indicate project setup problems. It is also shown for proc macros which were
+// disabled after repeatedly crashing or hanging the proc macro server.
 //
 // If you are seeing a lot of "proc macro not expanded" warnings, you can add this option to the
 // `rust-analyzer.diagnostics.disabled` list to prevent them from showing. Alternatively you can
@@ -18,10 +21,13 @@ pub(crate) fn unresolved_proc_macro(
         .precise_location
         .unwrap_or_else(|| ctx.sema.diagnostics_display_range(d.node.clone()).range);
     // FIXME: it would be nice to tell the user whether proc macros are currently disabled
-    let message = match &d.macro_name {
+    let mut message = match &d.macro_name {
         Some(name) => format!("proc macro `{}` not expanded", name),
         None => "proc macro not expanded".to_string(),
     };
+    if let Some(reason) = &d.reason {
+        format_to!(message, ": {}", reason);
+    }
 
     Diagnostic::new("unresolved-proc-macro", message, display_range).severity(Severity::WeakWarning)
 }
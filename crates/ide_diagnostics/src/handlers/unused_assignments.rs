@@ -0,0 +1,160 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unused-assignments
+//
+// This diagnostic is triggered if the value assigned to a variable is never
+// read, because the variable is overwritten or goes out of scope first.
+pub(crate) fn unused_assignments(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnusedAssignment,
+) -> Diagnostic {
+    let name = d.local.name(ctx.sema.db).map(|it| it.to_string()).unwrap_or_default();
+    Diagnostic::new(
+        "unused-assignments",
+        format!("value assigned to `{}` is never read", name),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .severity(Severity::WeakWarning)
+    .experimental()
+    .with_unused(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics_for;
+
+    #[test]
+    fn overwritten_assignment() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+fn f() -> i32 {
+    let mut x = 1;
+    x = 2;
+  //^^^^^ weak: value assigned to `x` is never read
+    x = 3;
+    x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn assignment_at_end_of_scope() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+fn f(mut x: i32) {
+    let _y = x;
+    x += 1;
+  //^^^^^^ weak: value assigned to `x` is never read
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn assignment_read_in_branch() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+fn f(c: bool) -> i32 {
+    let mut x = 0;
+    if c {
+        x = 1;
+    } else {
+        x = 2;
+    }
+    x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn assignment_read_by_next_iteration() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+fn f() {
+    let mut x = 0;
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+        if x > 5 { continue; }
+        x = i;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn assignment_before_labeled_break() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+fn f() -> i32 {
+    let mut x = 0;
+    'outer: loop {
+        loop {
+            x = 1;
+            break 'outer;
+        }
+    }
+    x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn captured_variables_are_not_reported() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+fn f() {
+    let mut x = 0;
+    let mut c = || x;
+    x = 1;
+    c();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed_unused_assignments() {
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+#![allow(unused_assignments)]
+fn f() -> i32 {
+    let mut x = 0;
+    x = 1;
+    x = 2;
+    x
+}
+"#,
+        );
+        check_diagnostics_for(
+            "unused-assignments",
+            r#"
+#[allow(unused)]
+fn f() -> i32 {
+    let mut x = 0;
+    x = 1;
+    x
+}
+#[allow(unused_mut)]
+fn g() -> i32 {
+    let mut x = 0;
+    x = 1;
+  //^^^^^ weak: value assigned to `x` is never read
+    x = 2;
+    x
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use either::Either;
+use hir::InFile;
+use ide_db::{
+    assists::Assist, base_db::FileId, helpers::mut_keyword_removal_range,
+    source_change::SourceChange,
+};
+use syntax::{AstNode, SyntaxNodePtr, SyntaxToken, TextRange};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unused-mut
+//
+// This diagnostic is triggered if a variable is declared `mut`, but is never
+// mutated.
+pub(crate) fn unused_mut(ctx: &DiagnosticsContext<'_>, d: &hir::UnusedMut) -> Option<Diagnostic> {
+    let source = d.local.source(ctx.sema.db);
+    let (node, mut_token) = match &source.value {
+        Either::Left(ident_pat) => (ident_pat.syntax().clone(), ident_pat.mut_token()?),
+        Either::Right(self_param) => (self_param.syntax().clone(), self_param.mut_token()?),
+    };
+    let range = ctx
+        .sema
+        .diagnostics_display_range(InFile::new(source.file_id, SyntaxNodePtr::new(&node)))
+        .range;
+    let file_id = source.file_id.original_file(ctx.sema.db);
+    let res = Diagnostic::new("unused-mut", "variable does not need to be mutable", range)
+        .severity(Severity::WeakWarning)
+        .experimental()
+        .with_fixes(Some(vec![remove_mut(file_id, &mut_token, range)]));
+    Some(res)
+}
+
+/// Removes the `mut` keyword, like the `remove_mut` assist.
+fn remove_mut(file_id: FileId, mut_token: &SyntaxToken, target: TextRange) -> Assist {
+    let edit = TextEdit::delete(mut_keyword_removal_range(mut_token));
+    fix("remove_mut", "Remove `mut` keyword", SourceChange::from_text_edit(file_id, edit), target)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics_for, check_fix_for};
+
+    #[test]
+    fn unused_mut() {
+        check_diagnostics_for(
+            "unused-mut",
+            r#"
+fn f() {
+    let mut x = 1;
+      //^^^^^ 💡 weak: variable does not need to be mutable
+    let mut y = 1;
+    y += x;
+    let mut z;
+      //^^^^^ 💡 weak: variable does not need to be mutable
+    z = y;
+    let mut w = 0;
+    loop {
+        w = z;
+        if w > 0 { break; }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn mutation_through_borrows() {
+        check_diagnostics_for(
+            "unused-mut",
+            r#"
+struct S { f: i32 }
+impl S {
+    fn set(&mut self) {}
+    fn get(&self) -> i32 { self.f }
+}
+fn f(mut a: S, mut b: S, mut c: S, d: &mut S, mut e: &mut S) {
+                                            //^^^^^ 💡 weak: variable does not need to be mutable
+    a.set();
+    b.f = 1;
+    let _r = &mut c;
+    d.set();
+    e.f = b.get();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unused_mut_self() {
+        check_diagnostics_for(
+            "unused-mut",
+            r#"
+struct S;
+impl S {
+    fn f(mut self) {}
+       //^^^^^^^^ 💡 weak: variable does not need to be mutable
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn ref_mut_binding_mutates_scrutinee() {
+        check_diagnostics_for(
+            "unused-mut",
+            r#"
+fn f() {
+    let mut x = Some(1);
+    if let Some(ref mut y) = x {
+        *y = 2;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_mut_closure_call() {
+        check_diagnostics_for(
+            "unused-mut",
+            r#"
+//- minicore: fn
+fn f() {
+    let mut x = 0;
+    let mut inc = || x += 1;
+    inc();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_mut() {
+        check_fix_for(
+            "unused-mut",
+            r#"
+fn f() {
+    let mut$0 x = 1;
+    let _y = x;
+}
+"#,
+            r#"
+fn f() {
+    let x = 1;
+    let _y = x;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed_unused_mut() {
+        check_diagnostics_for(
+            "unused-mut",
+            r#"
+#[allow(unused_mut)]
+fn f() {
+    let mut x = 1;
+    let _y = x;
+}
+#[allow(unused_variables)]
+fn g() {
+    let mut x = 1;
+      //^^^^^ 💡 weak: variable does not need to be mutable
+    let _y = x;
+}
+#[allow(unused)]
+fn h(mut a: i32) -> i32 {
+    a
+}
+"#,
+        );
+    }
+}
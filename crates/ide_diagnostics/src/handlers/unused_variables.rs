@@ -0,0 +1,198 @@
+use either::Either;
+use hir::{HirFileId, InFile};
+use ide_db::{assists::Assist, source_change::SourceChange};
+use syntax::{
+    ast::{self, NameOwner},
+    AstNode, SyntaxNodePtr,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unused-variables
+//
+// This diagnostic is triggered if a local variable is never read.
+pub(crate) fn unused_variables(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnusedVariable,
+) -> Option<Diagnostic> {
+    let source = d.local.source(ctx.sema.db);
+    let name = match &source.value {
+        Either::Left(ident_pat) => ident_pat.name()?,
+        Either::Right(_) => return None,
+    };
+    let range = ctx
+        .sema
+        .diagnostics_display_range(InFile::new(source.file_id, SyntaxNodePtr::new(name.syntax())))
+        .range;
+    let res = Diagnostic::new("unused-variables", format!("unused variable: `{}`", name), range)
+        .severity(Severity::WeakWarning)
+        .experimental()
+        .with_unused(true)
+        .with_fixes(fixes(ctx, source.file_id, &name));
+    Some(res)
+}
+
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    file_id: HirFileId,
+    name: &ast::Name,
+) -> Option<Vec<Assist>> {
+    let file_id = file_id.original_file(ctx.sema.db);
+    let new_name = format!("_{}", name);
+    let mut builder = TextEdit::builder();
+    // `S { x }` has to become `S { x: _x }` to still match the field.
+    let ident_pat = name.syntax().parent().and_then(ast::IdentPat::cast)?;
+    if let Some(field) = ident_pat.syntax().parent().and_then(ast::RecordPatField::cast) {
+        if field.name_ref().is_none() {
+            builder.insert(ident_pat.syntax().text_range().start(), format!("{}: ", name));
+        }
+    }
+    let range = name.syntax().text_range();
+    builder.replace(range, new_name.clone());
+    Some(vec![fix(
+        "rename_unused_variable",
+        &format!("Rename to `{}`", new_name),
+        SourceChange::from_text_edit(file_id, builder.finish()),
+        range,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics_for, check_fix_for};
+
+    #[test]
+    fn unused_variables() {
+        check_diagnostics_for(
+            "unused-variables",
+            r#"
+fn f(a: i32, _b: i32) {
+   //^ 💡 weak: unused variable: `a`
+    let x = 92;
+      //^ 💡 weak: unused variable: `x`
+    let y = 92;
+    let _z = y;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_in_closure_and_macro() {
+        check_diagnostics_for(
+            "unused-variables",
+            r#"
+macro_rules! id { ($e:expr) => { $e } }
+fn f() {
+    let x = 1;
+    let y = 2;
+    let _c = || x;
+    id!(y);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn or_pattern_bindings_are_one_variable() {
+        check_diagnostics_for(
+            "unused-variables",
+            r#"
+enum E { A(i32), B(i32) }
+fn f(e: E) -> i32 {
+    match e {
+        E::A(x) | E::B(x) => x,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_self_and_missing_body() {
+        check_diagnostics_for(
+            "unused-variables",
+            r#"
+struct S;
+impl S {
+    fn f(self) {}
+}
+trait T {
+    fn g(x: i32);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_with_unresolved_macro() {
+        check_diagnostics_for(
+            "unused-variables",
+            r#"
+fn f() {
+    let x = 1;
+    unresolved!(x);
+  //^^^^^^^^^^ error: unresolved macro `unresolved!`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rename_to_underscore() {
+        check_fix_for(
+            "unused-variables",
+            r#"
+fn f() {
+    let x$0 = 92;
+}
+"#,
+            r#"
+fn f() {
+    let _x = 92;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rename_record_pat_shorthand() {
+        check_fix_for(
+            "unused-variables",
+            r#"
+struct S { x: i32 }
+fn f(S { x$0 }: S) {}
+"#,
+            r#"
+struct S { x: i32 }
+fn f(S { x: _x }: S) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed_unused_variables() {
+        check_diagnostics_for(
+            "unused-variables",
+            r#"
+#[allow(unused_variables)]
+fn f(a: i32) {
+    let x = 92;
+}
+#[allow(unused_mut)]
+fn g() {
+    let x = 92;
+      //^ 💡 weak: unused variable: `x`
+}
+mod m {
+    #![allow(unused)]
+    struct S;
+    impl S {
+        fn h(&self, a: i32) {}
+    }
+}
+"#,
+        );
+    }
+}
@@ -27,6 +27,8 @@ mod handlers {
     pub(crate) mod break_outside_of_loop;
     pub(crate) mod inactive_code;
     pub(crate) mod incorrect_case;
+    pub(crate) mod invalid_cast;
+    pub(crate) mod lossy_cast;
     pub(crate) mod macro_error;
     pub(crate) mod mismatched_arg_count;
     pub(crate) mod missing_fields;
@@ -34,25 +36,36 @@ mod handlers {
     pub(crate) mod missing_ok_or_some_in_tail_expr;
     pub(crate) mod missing_unsafe;
     pub(crate) mod no_such_field;
+    pub(crate) mod non_diverging_let_else;
     pub(crate) mod remove_this_semicolon;
     pub(crate) mod replace_filter_map_next_with_find_map;
+    pub(crate) mod trivial_cast;
+    pub(crate) mod type_mismatch;
     pub(crate) mod unimplemented_builtin_macro;
+    pub(crate) mod unreachable_code;
     pub(crate) mod unresolved_extern_crate;
+    pub(crate) mod unresolved_field;
     pub(crate) mod unresolved_import;
     pub(crate) mod unresolved_macro_call;
+    pub(crate) mod unresolved_method;
     pub(crate) mod unresolved_module;
     pub(crate) mod unresolved_proc_macro;
+    pub(crate) mod unused_assignments;
+    pub(crate) mod unused_mut;
+    pub(crate) mod unused_variables;
 
     // The handlers bellow are unusual, the implement the diagnostics as well.
     pub(crate) mod field_shorthand;
+    pub(crate) mod syntax_error;
     pub(crate) mod useless_braces;
     pub(crate) mod unlinked_file;
 }
 
-use hir::{diagnostics::AnyDiagnostic, Semantics};
+use hir::{diagnostics::AnyDiagnostic, PrefixKind, Semantics};
 use ide_db::{
     assists::{Assist, AssistId, AssistKind, AssistResolveStrategy},
     base_db::{FileId, SourceDatabase},
+    helpers::insert_use::{ImportGranularity, InsertUseConfig},
     label::Label,
     source_change::SourceChange,
     RootDatabase,
@@ -118,15 +131,32 @@ impl Diagnostic {
 #[derive(Debug, Copy, Clone)]
 pub enum Severity {
     Error,
-    // We don't actually emit this one yet, but we should at some point.
-    // Warning,
+    Warning,
     WeakWarning,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct DiagnosticsConfig {
     pub disable_experimental: bool,
     pub disabled: FxHashSet<String>,
+    /// How fixes that import items, like missing traits, insert the `use`.
+    pub insert_use: InsertUseConfig,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        DiagnosticsConfig {
+            disable_experimental: false,
+            disabled: FxHashSet::default(),
+            insert_use: InsertUseConfig {
+                granularity: ImportGranularity::Crate,
+                enforce_granularity: false,
+                prefix_kind: PrefixKind::Plain,
+                group: true,
+                skip_glob_imports: true,
+            },
+        }
+    }
 }
 
 struct DiagnosticsContext<'a> {
@@ -148,9 +178,11 @@ pub fn diagnostics(
 
     // [#34344] Only take first 128 errors to prevent slowing down editor/ide, the number 128 is chosen arbitrarily.
     res.extend(
-        parse.errors().iter().take(128).map(|err| {
-            Diagnostic::new("syntax-error", format!("Syntax Error: {}", err), err.range())
-        }),
+        parse
+            .errors()
+            .iter()
+            .take(128)
+            .map(|err| handlers::syntax_error::syntax_error(file_id, parse.tree().syntax(), err)),
     );
 
     for node in parse.tree().syntax().descendants() {
@@ -175,6 +207,7 @@ pub fn diagnostics(
         let d = match diag {
             AnyDiagnostic::BreakOutsideOfLoop(d) => handlers::break_outside_of_loop::break_outside_of_loop(&ctx, &d),
             AnyDiagnostic::IncorrectCase(d) => handlers::incorrect_case::incorrect_case(&ctx, &d),
+            AnyDiagnostic::InvalidCast(d) => handlers::invalid_cast::invalid_cast(&ctx, &d),
             AnyDiagnostic::MacroError(d) => handlers::macro_error::macro_error(&ctx, &d),
             AnyDiagnostic::MismatchedArgCount(d) => handlers::mismatched_arg_count::mismatched_arg_count(&ctx, &d),
             AnyDiagnostic::MissingFields(d) => handlers::missing_fields::missing_fields(&ctx, &d),
@@ -182,19 +215,44 @@ pub fn diagnostics(
             AnyDiagnostic::MissingOkOrSomeInTailExpr(d) => handlers::missing_ok_or_some_in_tail_expr::missing_ok_or_some_in_tail_expr(&ctx, &d),
             AnyDiagnostic::MissingUnsafe(d) => handlers::missing_unsafe::missing_unsafe(&ctx, &d),
             AnyDiagnostic::NoSuchField(d) => handlers::no_such_field::no_such_field(&ctx, &d),
+            AnyDiagnostic::NonDivergingLetElse(d) => handlers::non_diverging_let_else::non_diverging_let_else(&ctx, &d),
             AnyDiagnostic::RemoveThisSemicolon(d) => handlers::remove_this_semicolon::remove_this_semicolon(&ctx, &d),
             AnyDiagnostic::ReplaceFilterMapNextWithFindMap(d) => handlers::replace_filter_map_next_with_find_map::replace_filter_map_next_with_find_map(&ctx, &d),
+            AnyDiagnostic::TypeMismatch(d) => handlers::type_mismatch::type_mismatch(&ctx, &d),
             AnyDiagnostic::UnimplementedBuiltinMacro(d) => handlers::unimplemented_builtin_macro::unimplemented_builtin_macro(&ctx, &d),
             AnyDiagnostic::UnresolvedExternCrate(d) => handlers::unresolved_extern_crate::unresolved_extern_crate(&ctx, &d),
+            AnyDiagnostic::UnresolvedField(d) => handlers::unresolved_field::unresolved_field(&ctx, &d),
             AnyDiagnostic::UnresolvedImport(d) => handlers::unresolved_import::unresolved_import(&ctx, &d),
             AnyDiagnostic::UnresolvedMacroCall(d) => handlers::unresolved_macro_call::unresolved_macro_call(&ctx, &d),
+            AnyDiagnostic::UnresolvedMethodCall(d) => handlers::unresolved_method::unresolved_method(&ctx, &d),
             AnyDiagnostic::UnresolvedModule(d) => handlers::unresolved_module::unresolved_module(&ctx, &d),
             AnyDiagnostic::UnresolvedProcMacro(d) => handlers::unresolved_proc_macro::unresolved_proc_macro(&ctx, &d),
+            AnyDiagnostic::UnusedAssignment(d) => handlers::unused_assignments::unused_assignments(&ctx, &d),
 
             AnyDiagnostic::InactiveCode(d) => match handlers::inactive_code::inactive_code(&ctx, &d) {
                 Some(it) => it,
                 None => continue,
             }
+            AnyDiagnostic::LossyCast(d) => match handlers::lossy_cast::lossy_cast(&ctx, &d) {
+                Some(it) => it,
+                None => continue,
+            }
+            AnyDiagnostic::TrivialCast(d) => match handlers::trivial_cast::trivial_cast(&ctx, &d) {
+                Some(it) => it,
+                None => continue,
+            }
+            AnyDiagnostic::UnreachableCode(d) => match handlers::unreachable_code::unreachable_code(&ctx, &d) {
+                Some(it) => it,
+                None => continue,
+            }
+            AnyDiagnostic::UnusedMut(d) => match handlers::unused_mut::unused_mut(&ctx, &d) {
+                Some(it) => it,
+                None => continue,
+            }
+            AnyDiagnostic::UnusedVariable(d) => match handlers::unused_variables::unused_variables(&ctx, &d) {
+                Some(it) => it,
+                None => continue,
+            }
         };
         res.push(d)
     }
@@ -229,7 +287,7 @@ mod tests {
     use expect_test::Expect;
     use ide_db::{
         assists::AssistResolveStrategy,
-        base_db::{fixture::WithFixture, SourceDatabaseExt},
+        base_db::{fixture::WithFixture, FileId, ProcMacro, SourceDatabaseExt},
         RootDatabase,
     };
     use stdx::trim_indent;
@@ -244,7 +302,13 @@ mod tests {
     ///  * that the contents of the file containing the cursor match `after` after the diagnostic fix is applied
     #[track_caller]
     pub(crate) fn check_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
-        check_nth_fix(0, ra_fixture_before, ra_fixture_after);
+        check_nth_fix(None, 0, ra_fixture_before, ra_fixture_after);
+    }
+    /// Like `check_fix`, but checks the fix of the last `code` diagnostic, for
+    /// fixtures where other diagnostics are reported as well.
+    #[track_caller]
+    pub(crate) fn check_fix_for(code: &str, ra_fixture_before: &str, ra_fixture_after: &str) {
+        check_nth_fix(Some(code), 0, ra_fixture_before, ra_fixture_after);
     }
     /// Takes a multi-file input fixture with annotated cursor positions,
     /// and checks that:
@@ -253,23 +317,30 @@ mod tests {
     ///  * that the contents of the file containing the cursor match `after` after each diagnostic fix is applied
     pub(crate) fn check_fixes(ra_fixture_before: &str, ra_fixtures_after: Vec<&str>) {
         for (i, ra_fixture_after) in ra_fixtures_after.iter().enumerate() {
-            check_nth_fix(i, ra_fixture_before, ra_fixture_after)
+            check_nth_fix(None, i, ra_fixture_before, ra_fixture_after)
         }
     }
 
     #[track_caller]
-    fn check_nth_fix(nth: usize, ra_fixture_before: &str, ra_fixture_after: &str) {
+    fn check_nth_fix(
+        code: Option<&str>,
+        nth: usize,
+        ra_fixture_before: &str,
+        ra_fixture_after: &str,
+    ) {
         let after = trim_indent(ra_fixture_after);
 
+        let mut config = test_config();
+        if let Some(code) = code {
+            config.disabled.remove(code);
+        }
         let (db, file_position) = RootDatabase::with_position(ra_fixture_before);
-        let diagnostic = super::diagnostics(
-            &db,
-            &DiagnosticsConfig::default(),
-            &AssistResolveStrategy::All,
-            file_position.file_id,
-        )
-        .pop()
-        .expect("no diagnostics");
+        let mut diagnostics =
+            super::diagnostics(&db, &config, &AssistResolveStrategy::All, file_position.file_id);
+        if let Some(code) = code {
+            diagnostics.retain(|d| d.code.as_str() == code);
+        }
+        let diagnostic = diagnostics.pop().expect("no diagnostics");
         let fix = &diagnostic.fixes.expect("diagnostic misses fixes")[nth];
         let actual = {
             let source_change = fix.source_change.as_ref().unwrap();
@@ -318,17 +389,56 @@ mod tests {
 
     #[track_caller]
     pub(crate) fn check_diagnostics(ra_fixture: &str) {
+        check_diagnostics_with_config(test_config(), ra_fixture)
+    }
+
+    /// Like `check_diagnostics`, but with the `code` diagnostic enabled, for
+    /// the tests of the diagnostics which are disabled by `test_config`.
+    #[track_caller]
+    pub(crate) fn check_diagnostics_for(code: &str, ra_fixture: &str) {
+        let mut config = test_config();
+        config.disabled.remove(code);
+        check_diagnostics_with_config(config, ra_fixture)
+    }
+
+    /// Disables diagnostics that would clutter the annotations of most tests.
+    fn test_config() -> DiagnosticsConfig {
         let mut config = DiagnosticsConfig::default();
         config.disabled.insert("inactive-code".to_string());
-        check_diagnostics_with_config(config, ra_fixture)
+        config.disabled.insert("unused-variables".to_string());
+        config.disabled.insert("unused-mut".to_string());
+        config.disabled.insert("unused-assignments".to_string());
+        config.disabled.insert("unreachable-code".to_string());
+        config.disabled.insert("trivial-cast".to_string());
+        config.disabled.insert("lossy-cast".to_string());
+        config
     }
 
     #[track_caller]
     pub(crate) fn check_diagnostics_with_config(config: DiagnosticsConfig, ra_fixture: &str) {
         let (db, files) = RootDatabase::with_many_files(ra_fixture);
+        check_annotated_diagnostics(&db, &config, files)
+    }
+
+    /// Like `check_diagnostics`, but the fixture crate named `proc_macros` is
+    /// given the `proc_macros` expanders.
+    #[track_caller]
+    pub(crate) fn check_diagnostics_with_proc_macros(
+        ra_fixture: &str,
+        proc_macros: Vec<ProcMacro>,
+    ) {
+        let (db, files) = RootDatabase::with_many_files_and_proc_macros(ra_fixture, proc_macros);
+        check_annotated_diagnostics(&db, &test_config(), files)
+    }
+
+    #[track_caller]
+    fn check_annotated_diagnostics(
+        db: &RootDatabase,
+        config: &DiagnosticsConfig,
+        files: Vec<FileId>,
+    ) {
         for file_id in files {
-            let diagnostics =
-                super::diagnostics(&db, &config, &AssistResolveStrategy::All, file_id);
+            let diagnostics = super::diagnostics(db, config, &AssistResolveStrategy::All, file_id);
 
             let expected = extract_annotations(&*db.file_text(file_id));
             let mut actual = diagnostics
@@ -341,6 +451,7 @@ mod tests {
                     }
                     annotation.push_str(match d.severity {
                         Severity::Error => "error",
+                        Severity::Warning => "warn",
                         Severity::WeakWarning => "weak",
                     });
                     annotation.push_str(": ");
@@ -6,6 +6,7 @@ mod matcher;
 mod transcriber;
 
 use rustc_hash::FxHashMap;
+use stdx::format_to;
 use syntax::SmolStr;
 
 use crate::{ExpandError, ExpandResult};
@@ -14,8 +15,17 @@ pub(crate) fn expand_rules(
     rules: &[crate::Rule],
     input: &tt::Subtree,
 ) -> ExpandResult<tt::Subtree> {
-    let mut match_: Option<(matcher::Match, &crate::Rule)> = None;
-    for rule in rules {
+    expand_rules_traced(rules, input, None)
+}
+
+/// Like [`expand_rules`], but records every attempted rule into `trace`.
+pub(crate) fn expand_rules_traced(
+    rules: &[crate::Rule],
+    input: &tt::Subtree,
+    mut trace: Option<&mut ExpansionTrace>,
+) -> ExpandResult<tt::Subtree> {
+    let mut match_: Option<(matcher::Match, &crate::Rule, usize)> = None;
+    for (idx, rule) in rules.iter().enumerate() {
         let new_match = matcher::match_(&rule.lhs, input);
 
         if new_match.err.is_none() {
@@ -24,22 +34,33 @@ pub(crate) fn expand_rules(
             // `test_repeat_bad_var` test fail.
             let ExpandResult { value, err: transcribe_err } =
                 transcriber::transcribe(&rule.rhs, &new_match.bindings);
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(idx, input, &new_match, transcribe_err.clone());
+            }
             if transcribe_err.is_none() {
+                if let Some(trace) = trace {
+                    trace.selected = Some(idx);
+                }
                 return ExpandResult::ok(value);
             }
+        } else if let Some(trace) = trace.as_deref_mut() {
+            trace.push(idx, input, &new_match, None);
         }
         // Use the rule if we matched more tokens, or bound variables count
-        if let Some((prev_match, _)) = &match_ {
+        if let Some((prev_match, ..)) = &match_ {
             if (new_match.unmatched_tts, -(new_match.bound_count as i32))
                 < (prev_match.unmatched_tts, -(prev_match.bound_count as i32))
             {
-                match_ = Some((new_match, rule));
+                match_ = Some((new_match, rule, idx));
             }
         } else {
-            match_ = Some((new_match, rule));
+            match_ = Some((new_match, rule, idx));
         }
     }
-    if let Some((match_, rule)) = match_ {
+    if let Some((match_, rule, idx)) = match_ {
+        if let Some(trace) = trace {
+            trace.selected = Some(idx);
+        }
         // if we got here, there was no match without errors
         let ExpandResult { value, err: transcribe_err } =
             transcriber::transcribe(&rule.rhs, &match_.bindings);
@@ -49,6 +70,84 @@ pub(crate) fn expand_rules(
     }
 }
 
+/// A record of how a `macro_rules` invocation was matched against the rules of
+/// the macro, in the order in which they were tried.
+///
+/// Expansion stops at the first rule which matches and transcribes without
+/// errors, so the rules after it do not show up here.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExpansionTrace {
+    pub rules: Vec<RuleTrace>,
+    /// The rule which was used to produce the expansion, if any. When no rule
+    /// matched, this is the one which got the furthest.
+    pub selected: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTrace {
+    /// Index of the rule in the macro definition.
+    pub index: usize,
+    /// Whether the matcher accepted the whole input.
+    pub matched: bool,
+    /// How many top-level token trees of the input the matcher consumed
+    /// before it stopped.
+    pub matched_tts: usize,
+    pub total_tts: usize,
+    /// The first part of the pattern which failed to match.
+    pub failure: Option<MatchFailure>,
+    /// The error which prevented the rule from being used, if any.
+    pub err: Option<ExpandError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchFailure {
+    /// A `$name:kind` fragment could not be parsed.
+    Fragment { name: SmolStr, kind: SmolStr },
+    /// A literal token of the pattern was not found in the input.
+    Token(String),
+}
+
+impl ExpansionTrace {
+    fn push(
+        &mut self,
+        index: usize,
+        input: &tt::Subtree,
+        match_: &matcher::Match,
+        transcribe_err: Option<ExpandError>,
+    ) {
+        let total_tts = input.token_trees.len();
+        self.rules.push(RuleTrace {
+            index,
+            matched: match_.err.is_none(),
+            matched_tts: total_tts.saturating_sub(match_.unmatched_top_tts),
+            total_tts,
+            failure: match_.failure.clone(),
+            err: match_.err.clone().or(transcribe_err),
+        });
+    }
+
+    /// Describes why the selected rule failed, for use in diagnostics.
+    pub fn failure_note(&self) -> Option<String> {
+        let selected = self.selected?;
+        let rule = self.rules.iter().find(|it| it.index == selected && it.err.is_some())?;
+        let mut note = format!(
+            "rule {} of {} matched {} of {} token trees",
+            selected + 1,
+            self.rules.len(),
+            rule.matched_tts,
+            rule.total_tts
+        );
+        match &rule.failure {
+            Some(MatchFailure::Fragment { name, kind }) => {
+                format_to!(note, ", failed at `${}:{}`", name, kind)
+            }
+            Some(MatchFailure::Token(token)) => format_to!(note, ", expected `{}`", token),
+            None => (),
+        }
+        Some(note)
+    }
+}
+
 /// The actual algorithm for expansion is not too hard, but is pretty tricky.
 /// `Bindings` structure is the key to understanding what we are doing here.
 ///
@@ -136,6 +235,35 @@ mod tests {
         // Add an err test case for ($($i:ident)) => ($())
     }
 
+    #[test]
+    fn test_expansion_trace() {
+        let rules = create_rules(&format_macro(
+            "(fn $i:ident) => (); (struct $i:ident;) => (); (struct) => ();",
+        ));
+
+        let (res, trace) = trace_first(&rules, "foo!{struct 92;}");
+        assert!(res.err.is_some());
+        assert_eq!(trace.rules.len(), 3);
+        assert_eq!(trace.selected, Some(1));
+        assert_eq!(trace.rules[0].failure, Some(MatchFailure::Token("fn".to_string())));
+        assert_eq!(
+            trace.rules[1].failure,
+            Some(MatchFailure::Fragment { name: "i".into(), kind: "ident".into() })
+        );
+        assert!(!trace.rules[2].matched);
+        assert_eq!(
+            trace.failure_note().as_deref(),
+            Some("rule 2 of 3 matched 1 of 3 token trees, failed at `$i:ident`")
+        );
+
+        let (res, trace) = trace_first(&rules, "foo!{struct}");
+        assert_eq!(res.err, None);
+        assert_eq!(trace.rules.len(), 3);
+        assert_eq!(trace.selected, Some(2));
+        assert!(trace.rules[2].matched);
+        assert_eq!(trace.failure_note(), None);
+    }
+
     fn assert_err(macro_body: &str, invocation: &str, err: ExpandError) {
         assert_eq!(
             expand_first(&create_rules(&format_macro(macro_body)), invocation).err,
@@ -172,4 +300,19 @@ mod tests {
 
         expand_rules(&rules.rules, &invocation_tt)
     }
+
+    fn trace_first(
+        rules: &crate::MacroRules,
+        invocation: &str,
+    ) -> (ExpandResult<tt::Subtree>, ExpansionTrace) {
+        let source_file = ast::SourceFile::parse(invocation).ok().unwrap();
+        let macro_invocation =
+            source_file.syntax().descendants().find_map(ast::MacroCall::cast).unwrap();
+
+        let (invocation_tt, _) = ast_to_token_tree(&macro_invocation.token_tree().unwrap());
+
+        let mut trace = ExpansionTrace::default();
+        let res = expand_rules_traced(&rules.rules, &invocation_tt, Some(&mut trace));
+        (res, trace)
+    }
 }
@@ -62,7 +62,7 @@
 use std::rc::Rc;
 
 use crate::{
-    expander::{Binding, Bindings, Fragment},
+    expander::{Binding, Bindings, Fragment, MatchFailure},
     parser::{Op, OpDelimited, OpDelimitedIter, RepeatKind, Separator},
     tt_iter::TtIter,
     ExpandError, MetaTemplate,
@@ -105,8 +105,12 @@ pub(super) struct Match {
     /// We currently just keep the first error and count the rest to compare matches.
     pub(super) err: Option<ExpandError>,
     pub(super) err_count: usize,
-    /// How many top-level token trees were left to match.
+    /// How many token trees were left to match.
     pub(super) unmatched_tts: usize,
+    /// How many top-level token trees were left to match.
+    pub(super) unmatched_top_tts: usize,
+    /// The part of the pattern which produced the first error.
+    pub(super) failure: Option<MatchFailure>,
     /// The number of bound variables
     pub(super) bound_count: usize,
 }
@@ -117,6 +121,15 @@ impl Match {
         self.err = prev_err.or(Some(err));
         self.err_count += 1;
     }
+
+    /// Only the first failure is kept, so it's only built if there was no
+    /// error before.
+    fn add_failure(&mut self, err: ExpandError, failure: impl FnOnce() -> MatchFailure) {
+        if self.err.is_none() {
+            self.failure = Some(failure());
+        }
+        self.add_err(err);
+    }
 }
 
 /// Matching errors are added to the `Match`.
@@ -493,7 +506,10 @@ fn match_loop_inner<'t>(
                             }
                         }
                         Some(err) => {
-                            res.add_err(err);
+                            res.add_failure(err, || MatchFailure::Fragment {
+                                name: name.clone(),
+                                kind: kind.clone(),
+                            });
                             if let Some(fragment) = match_res.value {
                                 bindings_builder.push_fragment(&mut item.bindings, name, fragment);
                             }
@@ -505,7 +521,7 @@ fn match_loop_inner<'t>(
             }
             OpDelimited::Op(Op::Leaf(leaf)) => {
                 if let Err(err) = match_leaf(leaf, &mut src.clone()) {
-                    res.add_err(err);
+                    res.add_failure(err, || MatchFailure::Token(leaf.to_string()));
                     item.is_error = true;
                 } else {
                     item.dot.next();
@@ -605,10 +621,13 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
             || bb_items.len() > 1
         {
             res.unmatched_tts += src.len();
+            // The subtree we are currently in was already consumed from its parent.
+            let in_subtree = !stack.is_empty();
             while let Some(it) = stack.pop() {
                 src = it;
                 res.unmatched_tts += src.len();
             }
+            res.unmatched_top_tts = src.len() + in_subtree as usize;
             res.add_err(err!("leftover tokens"));
 
             if let Some(error_reover_item) = error_recover_item {
@@ -636,7 +655,7 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
             stdx::always!(bb_items.len() == 1);
             let mut item = bb_items.pop().unwrap();
 
-            if let Some(OpDelimited::Op(Op::Var { name, .. })) = item.dot.peek() {
+            if let Some(OpDelimited::Op(Op::Var { name, kind, .. })) = item.dot.peek() {
                 let (iter, match_res) = item.meta_result.take().unwrap();
                 match match_res.value {
                     Some(fragment) => {
@@ -648,7 +667,10 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
                     None => {}
                 }
                 if let Some(err) = match_res.err {
-                    res.add_err(err);
+                    res.add_failure(err, || MatchFailure::Fragment {
+                        name: name.clone(),
+                        kind: kind.clone().unwrap_or_default(),
+                    });
                 }
                 src = iter.clone();
                 item.dot.next();
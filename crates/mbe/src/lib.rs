@@ -65,6 +65,7 @@ impl fmt::Display for ExpandError {
 }
 
 pub use crate::{
+    expander::{ExpansionTrace, MatchFailure, RuleTrace},
     syntax_bridge::{
         ast_to_token_tree, parse_exprs_with_sep, parse_to_token_tree, syntax_node_to_token_tree,
         token_tree_to_syntax_node,
@@ -202,6 +203,18 @@ impl MacroRules {
         expander::expand_rules(&self.rules, &tt)
     }
 
+    /// Expands the macro, recording which rules were tried and why they failed.
+    pub fn expand_with_trace(
+        &self,
+        tt: &tt::Subtree,
+    ) -> (ExpandResult<tt::Subtree>, ExpansionTrace) {
+        let mut tt = tt.clone();
+        self.shift.shift_all(&mut tt);
+        let mut trace = ExpansionTrace::default();
+        let res = expander::expand_rules_traced(&self.rules, &tt, Some(&mut trace));
+        (res, trace)
+    }
+
     pub fn map_id_down(&self, id: tt::TokenId) -> tt::TokenId {
         self.shift.shift(id)
     }
@@ -255,6 +268,18 @@ impl MacroDef {
         expander::expand_rules(&self.rules, &tt)
     }
 
+    /// Expands the macro, recording which rules were tried and why they failed.
+    pub fn expand_with_trace(
+        &self,
+        tt: &tt::Subtree,
+    ) -> (ExpandResult<tt::Subtree>, ExpansionTrace) {
+        let mut tt = tt.clone();
+        self.shift.shift_all(&mut tt);
+        let mut trace = ExpansionTrace::default();
+        let res = expander::expand_rules_traced(&self.rules, &tt, Some(&mut trace));
+        (res, trace)
+    }
+
     pub fn map_id_down(&self, id: tt::TokenId) -> tt::TokenId {
         self.shift.shift(id)
     }
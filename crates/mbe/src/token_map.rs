@@ -49,6 +49,14 @@ impl TokenMap {
         range.by_kind(kind)
     }
 
+    /// Returns all token ids with their relative range. The range of a
+    /// delimiter covers the whole delimited group.
+    pub fn entries(&self) -> impl Iterator<Item = (tt::TokenId, TextRange)> + '_ {
+        self.entries.iter().map(|&(token_id, range)| match range {
+            TokenTextRange::Token(it) | TokenTextRange::Delimiter(it) => (token_id, it),
+        })
+    }
+
     pub(crate) fn shrink_to_fit(&mut self) {
         self.entries.shrink_to_fit();
     }
@@ -40,6 +40,8 @@ mod types;
 
 use crate::{
     parser::{CompletedMarker, Marker, Parser},
+    ParseError,
+    ParseErrorCode::*,
     SyntaxKind::{self, *},
     TokenSet,
 };
@@ -60,6 +62,11 @@ pub(crate) mod fragments {
     };
 
     pub(crate) fn expr(p: &mut Parser) {
+        // `let` is only an expression inside of `if` and `while` conditions.
+        if p.at(T![let]) {
+            p.error(ParseError::new(ExpectedExpression, "expected expression"));
+            return;
+        }
         let _ = expressions::expr_with_attrs(p);
     }
 
@@ -115,7 +122,7 @@ pub(crate) fn reparser(
     first_child: Option<SyntaxKind>,
     parent: Option<SyntaxKind>,
 ) -> Option<fn(&mut Parser)> {
-    let res = match node {
+    let res: fn(&mut Parser) = match node {
         BLOCK_EXPR => expressions::block_expr,
         RECORD_FIELD_LIST => items::record_field_list,
         RECORD_EXPR_FIELD_LIST => items::record_expr_field_list,
@@ -123,18 +130,50 @@ pub(crate) fn reparser(
         MATCH_ARM_LIST => items::match_arm_list,
         USE_TREE_LIST => items::use_tree_list,
         EXTERN_ITEM_LIST => items::extern_item_list,
-        TOKEN_TREE if first_child? == T!['{'] => items::token_tree,
+        TOKEN_TREE if matches!(first_child?, T!['{'] | T!['('] | T!['[']) => items::token_tree,
         ASSOC_ITEM_LIST => match parent? {
             IMPL => items::assoc_item_list,
             TRAIT => items::assoc_item_list,
             _ => return None,
         },
         ITEM_LIST => items::item_list,
-        _ => return None,
+        MATCH_ARM if parent? == MATCH_ARM_LIST => expressions::match_arm_with_comma,
+        ATTR | LABEL | ERROR => return None,
+        _ => match parent? {
+            SOURCE_FILE if is_item(node) => |p| items::item_or_macro(p, false),
+            ITEM_LIST | ASSOC_ITEM_LIST | EXTERN_ITEM_LIST if is_item(node) => {
+                |p| items::item_or_macro(p, true)
+            }
+            // Statements, block-local items and the tail expression.
+            BLOCK_EXPR => |p| expressions::stmt(p, expressions::StmtWithSemi::Yes, false),
+            _ => return None,
+        },
     };
     Some(res)
 }
 
+fn is_item(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        CONST
+            | ENUM
+            | EXTERN_BLOCK
+            | EXTERN_CRATE
+            | FN
+            | IMPL
+            | MACRO_CALL
+            | MACRO_RULES
+            | MACRO_DEF
+            | MODULE
+            | STATIC
+            | STRUCT
+            | TRAIT
+            | TYPE_ALIAS
+            | UNION
+            | USE
+    )
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum BlockLike {
     Block,
@@ -241,7 +280,7 @@ fn name_r(p: &mut Parser, recovery: TokenSet) {
         p.bump(IDENT);
         m.complete(p, NAME);
     } else {
-        p.err_recover("expected a name", recovery);
+        p.err_recover(ParseError::new(ExpectedName, "expected a name"), recovery);
     }
 }
 
@@ -255,7 +294,7 @@ fn name_ref(p: &mut Parser) {
         p.bump(IDENT);
         m.complete(p, NAME_REF);
     } else {
-        p.err_and_bump("expected identifier");
+        p.err_and_bump(ParseError::new(ExpectedName, "expected identifier"));
     }
 }
 
@@ -273,7 +312,7 @@ fn lifetime(p: &mut Parser) {
     m.complete(p, LIFETIME);
 }
 
-fn error_block(p: &mut Parser, message: &str) {
+fn error_block<T: Into<ParseError>>(p: &mut Parser, message: T) {
     assert!(p.at(T!['{']));
     let m = p.start();
     p.error(message);
@@ -20,7 +20,7 @@ pub(super) fn meta(p: &mut Parser) {
         T![=] => {
             p.bump(T![=]);
             if expressions::expr(p).0.is_none() {
-                p.error("expected expression");
+                p.error(ParseError::new(ExpectedExpression, "expected expression"));
             }
         }
         T!['('] | T!['['] | T!['{'] => items::token_tree(p),
@@ -44,10 +44,10 @@ fn attr(p: &mut Parser, inner: bool) {
         meta(p);
 
         if !p.eat(T![']']) {
-            p.error("expected `]`");
+            p.error(ParseError::missing(&[T![']']], "expected `]`"));
         }
     } else {
-        p.error("expected `[`");
+        p.error(ParseError::missing(&[T!['[']], "expected `[`"));
     }
     attr.complete(p, ATTR);
 }
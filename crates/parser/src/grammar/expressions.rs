@@ -1,6 +1,6 @@
 mod atom;
 
-pub(crate) use self::atom::{block_expr, match_arm_list};
+pub(crate) use self::atom::{block_expr, match_arm_list, match_arm_with_comma};
 pub(super) use self::atom::{literal, LITERAL_FIRST};
 use super::*;
 
@@ -47,6 +47,13 @@ fn expr_no_struct(p: &mut Parser) {
     expr_bp(p, r, 1);
 }
 
+/// Parses the scrutinee of a `let` expression, which stops before the lazy
+/// boolean operators so that `let` expressions can be chained with `&&`.
+fn expr_let(p: &mut Parser) {
+    let r = Restrictions { forbid_structs: true, prefer_stmt: false };
+    expr_bp(p, r, 5);
+}
+
 fn is_expr_stmt_attr_allowed(kind: SyntaxKind) -> bool {
     let forbid = matches!(kind, BIN_EXPR | RANGE_EXPR);
     !forbid
@@ -156,6 +163,20 @@ pub(super) fn stmt(p: &mut Parser, with_semi: StmtWithSemi, prefer_expr: bool) {
             expressions::expr_with_attrs(p);
         }
 
+        // test let_else
+        // fn foo() {
+        //     let Some(x) = opt else { return };
+        //     let Ok(y): Result<i32, ()> = res else {
+        //         panic!()
+        //     };
+        // }
+        if p.at(T![else]) {
+            let m = p.start();
+            p.bump(T![else]);
+            block_expr(p);
+            m.complete(p, LET_ELSE);
+        }
+
         match with_semi {
             StmtWithSemi::Yes => {
                 p.expect(T![;]);
@@ -517,7 +538,7 @@ fn field_expr(p: &mut Parser, lhs: CompletedMarker) -> CompletedMarker {
         // FIXME: How to recover and instead parse INT + T![.]?
         p.bump_any();
     } else {
-        p.error("expected field name or number")
+        p.error(ParseError::new(ExpectedName, "expected field name or number"))
     }
     m.complete(p, FIELD_EXPR)
 }
@@ -562,11 +583,22 @@ fn arg_list(p: &mut Parser) {
         if !expr_with_attrs(p) {
             break;
         }
-        if !p.at(T![')']) && !p.expect(T![,]) {
+        // test_err arg_list_recovery
+        // fn main() {
+        //     foo(1 2);
+        //     foo(1;
+        // }
+        if p.at(T![')']) || p.eat(T![,]) {
+            continue;
+        }
+        // Only ask for a `,` if another argument follows, otherwise it's the
+        // `)` which is missing.
+        if !p.at_ts(EXPR_FIRST) || p.at(T!['{']) {
             break;
         }
+        p.expect(T![,]);
     }
-    p.eat(T![')']);
+    p.expect(T![')']);
     m.complete(p, ARG_LIST);
 }
 
@@ -636,7 +668,7 @@ pub(crate) fn record_expr_field_list(p: &mut Parser) {
                 m.abandon(p);
             }
             _ => {
-                p.err_and_bump("expected identifier");
+                p.err_and_bump(ParseError::new(ExpectedName, "expected identifier"));
                 m.abandon(p);
             }
         }
@@ -54,12 +54,13 @@ pub(super) const ATOM_EXPR_FIRST: TokenSet =
         T![async],
         T![try],
         T![const],
+        T![let],
         T![loop],
         T![for],
         LIFETIME_IDENT,
     ]));
 
-const EXPR_RECOVERY_SET: TokenSet = TokenSet::new(&[LET_KW, R_DOLLAR]);
+const EXPR_RECOVERY_SET: TokenSet = TokenSet::new(&[LET_KW, R_DOLLAR, T![;]]);
 
 pub(super) fn atom_expr(p: &mut Parser, r: Restrictions) -> Option<(CompletedMarker, BlockLike)> {
     if let Some(m) = literal(p) {
@@ -77,6 +78,7 @@ pub(super) fn atom_expr(p: &mut Parser, r: Restrictions) -> Option<(CompletedMar
         T![move] if la == T![|] => closure_expr(p),
         T![async] if la == T![|] || (la == T![move] && p.nth(2) == T![|]) => closure_expr(p),
         T![if] => if_expr(p),
+        T![let] => let_expr(p),
 
         T![loop] => loop_expr(p, None),
         T![box] => box_expr(p, None),
@@ -145,7 +147,10 @@ pub(super) fn atom_expr(p: &mut Parser, r: Restrictions) -> Option<(CompletedMar
         T![continue] => continue_expr(p),
         T![break] => break_expr(p, r),
         _ => {
-            p.err_recover("expected expression", EXPR_RECOVERY_SET);
+            p.err_recover(
+                ParseError::new(ExpectedExpression, "expected expression"),
+                EXPR_RECOVERY_SET,
+            );
             return None;
         }
     };
@@ -255,7 +260,7 @@ fn closure_expr(p: &mut Parser) -> CompletedMarker {
     } else if p.at_ts(EXPR_FIRST) {
         expr(p);
     } else {
-        p.error("expected expression");
+        p.error(ParseError::new(ExpectedExpression, "expected expression"));
     }
     m.complete(p, CLOSURE_EXPR)
 }
@@ -272,7 +277,7 @@ fn if_expr(p: &mut Parser) -> CompletedMarker {
     assert!(p.at(T![if]));
     let m = p.start();
     p.bump(T![if]);
-    condition(p);
+    expr_no_struct(p);
     block_expr(p);
     if p.at(T![else]) {
         p.bump(T![else]);
@@ -321,7 +326,7 @@ fn while_expr(p: &mut Parser, m: Option<Marker>) -> CompletedMarker {
     assert!(p.at(T![while]));
     let m = m.unwrap_or_else(|| p.start());
     p.bump(T![while]);
-    condition(p);
+    expr_no_struct(p);
     block_expr(p);
     m.complete(p, WHILE_EXPR)
 }
@@ -349,14 +354,21 @@ fn for_expr(p: &mut Parser, m: Option<Marker>) -> CompletedMarker {
 //     while let Some(_) | Some(_) = None {}
 //     while let | Some(_) = None {}
 // }
-fn condition(p: &mut Parser) {
+
+// test let_expr
+// fn foo() {
+//     if let Some(_) = None && true {}
+//     if let Some(a) = x && a > 3 && let Ok(b) = y {}
+//     while 1 == 5 && (let None = None) {}
+// }
+fn let_expr(p: &mut Parser) -> CompletedMarker {
+    assert!(p.at(T![let]));
     let m = p.start();
-    if p.eat(T![let]) {
-        patterns::pattern_top(p);
-        p.expect(T![=]);
-    }
-    expr_no_struct(p);
-    m.complete(p, CONDITION);
+    p.bump(T![let]);
+    patterns::pattern_top(p);
+    p.expect(T![=]);
+    expr_let(p);
+    m.complete(p, LET_EXPR)
 }
 
 // test match_expr
@@ -374,7 +386,7 @@ fn match_expr(p: &mut Parser) -> CompletedMarker {
     if p.at(T!['{']) {
         match_arm_list(p);
     } else {
-        p.error("expected `{`")
+        p.error(ParseError::missing(&[T!['{']], "expected `{`"))
     }
     m.complete(p, MATCH_EXPR)
 }
@@ -419,6 +431,16 @@ pub(crate) fn match_arm_list(p: &mut Parser) {
     m.complete(p, MATCH_ARM_LIST);
 }
 
+/// Parses a single arm of a `match_arm_list`, including the check for the
+/// trailing comma which the list performs after the arm. Used when an arm is
+/// reparsed on its own: the missing comma is reported outside of the arm, so
+/// the reparse is rejected in favor of reparsing the whole list.
+pub(crate) fn match_arm_with_comma(p: &mut Parser) {
+    if !match_arm(p).is_block() && !p.at(T![,]) && !p.at(T!['}']) {
+        p.error(ParseError::missing(&[T![,]], "expected COMMA"));
+    }
+}
+
 // test match_arm
 // fn foo() {
 //     match () {
@@ -477,7 +499,7 @@ fn match_guard(p: &mut Parser) -> CompletedMarker {
 // fn d() { 1; 2 }
 pub(crate) fn block_expr(p: &mut Parser) {
     if !p.at(T!['{']) {
-        p.error("expected a block");
+        p.error(ParseError::new(ExpectedBlock, "expected a block"));
         return;
     }
     block_expr_unchecked(p);
@@ -67,16 +67,16 @@ pub(super) fn item_or_macro(p: &mut Parser, stop_on_r_curly: bool) {
     } else {
         m.abandon(p);
         if p.at(T!['{']) {
-            error_block(p, "expected an item");
+            error_block(p, ParseError::new(ExpectedItem, "expected an item"));
         } else if p.at(T!['}']) && !stop_on_r_curly {
             let e = p.start();
-            p.error("unmatched `}`");
+            p.error(ParseError::new(UnmatchedDelimiter, "unmatched `}`"));
             p.bump(T!['}']);
             e.complete(p, ERROR);
         } else if !p.at(EOF) && !p.at(T!['}']) {
-            p.err_and_bump("expected an item");
+            p.err_and_bump(ParseError::new(ExpectedItem, "expected an item"));
         } else {
-            p.error("expected an item");
+            p.error(ParseError::new(ExpectedItem, "expected an item"));
         }
     }
 }
@@ -214,7 +214,13 @@ pub(super) fn maybe_item(p: &mut Parser, m: Marker) -> Result<(), Marker> {
         // unsafe extern "C" {}
         T![extern] => {
             abi(p);
-            extern_item_list(p);
+            // test_err extern_after_abi
+            // extern extern crate foo;
+            if p.at(T!['{']) {
+                extern_item_list(p);
+            } else {
+                p.error(ParseError::missing(&[T!['{']], "expected `{`"));
+            }
             m.complete(p, EXTERN_BLOCK);
         }
 
@@ -225,7 +231,7 @@ pub(super) fn maybe_item(p: &mut Parser, m: Marker) -> Result<(), Marker> {
                 if has_mods {
                     p.error("expected existential, fn, trait or impl");
                 } else {
-                    p.error("expected an item");
+                    p.error(ParseError::new(ExpectedItem, "expected an item"));
                 }
                 m.complete(p, ERROR);
             }
@@ -383,7 +389,7 @@ pub(crate) fn mod_item(p: &mut Parser, m: Marker) {
     if p.at(T!['{']) {
         item_list(p);
     } else if !p.eat(T![;]) {
-        p.error("expected `;` or `{`");
+        p.error(ParseError::missing(&[T![;], T!['{']], "expected `;` or `{`"));
     }
     m.complete(p, MODULE);
 }
@@ -425,7 +431,7 @@ fn macro_rules(p: &mut Parser, m: Marker) {
             p.expect(T![;]);
         }
         T!['{'] => token_tree(p),
-        _ => p.error("expected `{`, `[`, `(`"),
+        _ => p.error(ParseError::missing(&[T!['{'], T!['['], T!['(']], "expected `{`, `[`, `(`")),
     }
     m.complete(p, MACRO_RULES);
 }
@@ -439,13 +445,15 @@ fn macro_def(p: &mut Parser, m: Marker) {
     if p.at(T!['{']) {
         token_tree(p);
     } else if !p.at(T!['(']) {
-        p.error("unmatched `(`");
+        p.error(ParseError::new(UnmatchedDelimiter, "unmatched `(`"));
     } else {
         let m = p.start();
         token_tree(p);
         match p.current() {
             T!['{'] | T!['['] | T!['('] => token_tree(p),
-            _ => p.error("expected `{`, `[`, `(`"),
+            _ => {
+                p.error(ParseError::missing(&[T!['{'], T!['['], T!['(']], "expected `{`, `[`, `(`"))
+            }
         }
         m.complete(p, TOKEN_TREE);
     }
@@ -472,7 +480,7 @@ pub(super) fn macro_call_after_excl(p: &mut Parser) -> BlockLike {
             BlockLike::NotBlock
         }
         _ => {
-            p.error("expected `{`, `[`, `(`");
+            p.error(ParseError::missing(&[T!['{'], T!['['], T!['(']], "expected `{`, `[`, `(`"));
             BlockLike::NotBlock
         }
     }
@@ -491,11 +499,13 @@ pub(crate) fn token_tree(p: &mut Parser) {
         match p.current() {
             T!['{'] | T!['('] | T!['['] => token_tree(p),
             T!['}'] => {
-                p.error("unmatched `}`");
+                p.error(ParseError::new(UnmatchedDelimiter, "unmatched `}`"));
                 m.complete(p, TOKEN_TREE);
                 return;
             }
-            T![')'] | T![']'] => p.err_and_bump("unmatched brace"),
+            T![')'] | T![']'] => {
+                p.err_and_bump(ParseError::new(UnmatchedDelimiter, "unmatched brace"))
+            }
             _ => p.bump_any(),
         }
     }
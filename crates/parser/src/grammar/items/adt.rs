@@ -25,7 +25,7 @@ fn struct_or_union(p: &mut Parser, m: Marker, kw: SyntaxKind, def: SyntaxKind) {
                 T!['{'] => record_field_list(p),
                 _ => {
                     //FIXME: special case `(` error message
-                    p.error("expected `;` or `{`");
+                    p.error(ParseError::missing(&[T![;], T!['{']], "expected `;` or `{`"));
                 }
             }
         }
@@ -42,10 +42,10 @@ fn struct_or_union(p: &mut Parser, m: Marker, kw: SyntaxKind, def: SyntaxKind) {
             p.expect(T![;]);
         }
         _ if kw == T![struct] => {
-            p.error("expected `;`, `{`, or `(`");
+            p.error(ParseError::missing(&[T![;], T!['{'], T!['(']], "expected `;`, `{`, or `(`"));
         }
         _ => {
-            p.error("expected `{`");
+            p.error(ParseError::missing(&[T!['{']], "expected `{`"));
         }
     }
     m.complete(p, def);
@@ -60,7 +60,7 @@ pub(super) fn enum_(p: &mut Parser, m: Marker) {
     if p.at(T!['{']) {
         variant_list(p);
     } else {
-        p.error("expected `{`")
+        p.error(ParseError::missing(&[T!['{']], "expected `{`"))
     }
     m.complete(p, ENUM);
 }
@@ -160,7 +160,7 @@ fn tuple_field_list(p: &mut Parser) {
         attributes::outer_attrs(p);
         opt_visibility(p);
         if !p.at_ts(types::TYPE_FIRST) {
-            p.error("expected a type");
+            p.error(ParseError::new(ExpectedType, "expected a type"));
             m.complete(p, ERROR);
             break;
         }
@@ -25,7 +25,7 @@ pub(super) fn trait_(p: &mut Parser) {
     if p.at(T!['{']) {
         assoc_item_list(p);
     } else {
-        p.error("expected `{`");
+        p.error(ParseError::missing(&[T!['{']], "expected `{`"));
     }
 }
 
@@ -56,7 +56,7 @@ pub(super) fn impl_(p: &mut Parser) {
     if p.at(T!['{']) {
         assoc_item_list(p);
     } else {
-        p.error("expected `{`");
+        p.error(ParseError::missing(&[T!['{']], "expected `{`"));
     }
 }
 
@@ -81,7 +81,7 @@ pub(crate) fn assoc_item_list(p: &mut Parser) {
 
     while !p.at(EOF) && !p.at(T!['}']) {
         if p.at(T!['{']) {
-            error_block(p, "expected an item");
+            error_block(p, ParseError::new(ExpectedItem, "expected an item"));
             continue;
         }
         item_or_macro(p, true);
@@ -92,7 +92,7 @@ fn use_tree(p: &mut Parser, top_level: bool) {
                         T!['{'] => use_tree_list(p),
                         _ => {
                             // is this unreachable?
-                            p.error("expected `{` or `*`");
+                            p.error(ParseError::missing(&[T!['{'], T![*]], "expected `{` or `*`"));
                         }
                     }
                 }
@@ -86,7 +86,10 @@ fn path_segment(p: &mut Parser, mode: Mode, first: bool) {
                 m.complete(p, NAME_REF);
             }
             _ => {
-                p.err_recover("expected identifier", items::ITEM_RECOVERY_SET);
+                p.err_recover(
+                    ParseError::new(ExpectedName, "expected identifier"),
+                    items::ITEM_RECOVERY_SET,
+                );
                 if empty {
                     // test_err empty_segment
                     // use crate::;
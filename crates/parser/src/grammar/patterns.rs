@@ -108,7 +108,7 @@ fn atom_pat(p: &mut Parser, recovery_set: TokenSet) -> Option<CompletedMarker> {
         T!['['] => slice_pat(p),
 
         _ => {
-            p.err_recover("expected pattern", recovery_set);
+            p.err_recover(ParseError::new(ExpectedPattern, "expected pattern"), recovery_set);
             return None;
         }
     };
@@ -202,7 +202,7 @@ fn record_pat_field_list(p: &mut Parser) {
         match p.current() {
             // A trailing `..` is *not* treated as a REST_PAT.
             T![.] if p.at(T![..]) => p.bump(T![..]),
-            T!['{'] => error_block(p, "expected ident"),
+            T!['{'] => error_block(p, ParseError::new(ExpectedName, "expected ident")),
 
             _ => {
                 let m = p.start();
@@ -311,7 +311,7 @@ fn tuple_pat(p: &mut Parser) -> CompletedMarker {
     while !p.at(EOF) && !p.at(T![')']) {
         has_pat = true;
         if !p.at_ts(PATTERN_FIRST) {
-            p.error("expected a pattern");
+            p.error(ParseError::new(ExpectedPattern, "expected a pattern"));
             break;
         }
         has_rest |= p.at(T![..]);
@@ -343,7 +343,7 @@ fn slice_pat(p: &mut Parser) -> CompletedMarker {
 fn pat_list(p: &mut Parser, ket: SyntaxKind) {
     while !p.at(EOF) && !p.at(ket) {
         if !p.at_ts(PATTERN_FIRST) {
-            p.error("expected a pattern");
+            p.error(ParseError::new(ExpectedPattern, "expected a pattern"));
             break;
         }
 
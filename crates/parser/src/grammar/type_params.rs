@@ -161,7 +161,7 @@ pub(super) fn opt_where_clause(p: &mut Parser) {
         }
 
         if !comma {
-            p.error("expected comma");
+            p.error(ParseError::missing(&[T![,]], "expected comma"));
         }
     }
 
@@ -188,7 +188,7 @@ fn where_predicate(p: &mut Parser) {
             if p.at(T![:]) {
                 bounds(p);
             } else {
-                p.error("expected colon");
+                p.error(ParseError::missing(&[T![:]], "expected colon"));
             }
         }
         T![impl] => {
@@ -209,7 +209,7 @@ fn where_predicate(p: &mut Parser) {
             if p.at(T![:]) {
                 bounds(p);
             } else {
-                p.error("expected colon");
+                p.error(ParseError::missing(&[T![:]], "expected colon"));
             }
         }
     }
@@ -49,7 +49,7 @@ fn type_with_bounds_cond(p: &mut Parser, allow_bounds: bool) {
         T![<] => path_type_(p, allow_bounds),
         _ if paths::is_use_path_start(p) => path_or_macro_type_(p, allow_bounds),
         _ => {
-            p.err_recover("expected type", TYPE_RECOVERY_SET);
+            p.err_recover(ParseError::new(ExpectedType, "expected type"), TYPE_RECOVERY_SET);
         }
     }
 }
@@ -150,7 +150,7 @@ fn array_or_slice_type(p: &mut Parser) {
         // test_err array_type_missing_semi
         // type T = [() 92];
         _ => {
-            p.error("expected `;` or `]`");
+            p.error(ParseError::missing(&[T![;], T![']']], "expected `;` or `]`"));
             SLICE_TYPE
         }
     };
@@ -197,7 +197,7 @@ fn fn_ptr_type(p: &mut Parser) {
     // type F = unsafe ();
     if !p.eat(T![fn]) {
         m.abandon(p);
-        p.error("expected `fn`");
+        p.error(ParseError::missing(&[T![fn]], "expected `fn`"));
         return;
     }
     if p.at(T!['(']) {
@@ -217,7 +217,7 @@ pub(super) fn for_binder(p: &mut Parser) {
     if p.at(T![<]) {
         type_params::opt_generic_param_list(p);
     } else {
-        p.error("expected `<`");
+        p.error(ParseError::missing(&[T![<]], "expected `<`"));
     }
 }
 
@@ -24,8 +24,57 @@ pub(crate) use token_set::TokenSet;
 
 pub use syntax_kind::SyntaxKind;
 
+/// Stable, machine readable classification of a [`ParseError`], which
+/// doesn't depend on the wording of the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorCode {
+    /// One of the [`ParseError::expected`] tokens is missing.
+    MissingToken,
+    ExpectedExpression,
+    ExpectedPattern,
+    ExpectedType,
+    ExpectedItem,
+    ExpectedBlock,
+    /// An identifier, a name or a field name is missing.
+    ExpectedName,
+    UnmatchedDelimiter,
+    Other,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ParseError(pub Box<String>);
+pub struct ParseError {
+    pub code: ParseErrorCode,
+    /// Tokens which would have been accepted where the error occurred. Only
+    /// filled in when there are a few of them, like for a missing `;`.
+    pub expected: Vec<SyntaxKind>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(code: ParseErrorCode, message: impl Into<String>) -> ParseError {
+        ParseError { code, expected: Vec::new(), message: message.into() }
+    }
+
+    pub(crate) fn missing(expected: &[SyntaxKind], message: impl Into<String>) -> ParseError {
+        ParseError {
+            code: ParseErrorCode::MissingToken,
+            expected: expected.to_vec(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<&'_ str> for ParseError {
+    fn from(message: &str) -> ParseError {
+        ParseError::new(ParseErrorCode::Other, message)
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> ParseError {
+        ParseError::new(ParseErrorCode::Other, message)
+    }
+}
 
 /// `TokenSource` abstracts the source of the tokens parser operates on.
 ///
@@ -127,11 +176,12 @@ pub fn parse_fragment(
     parse_from_tokens(token_source, tree_sink, parser)
 }
 
-/// A parsing function for a specific braced-block.
+/// A parsing function for a specific node which can be reparsed in isolation,
+/// like a braced block, an item, a statement or a token tree.
 pub struct Reparser(fn(&mut parser::Parser));
 
 impl Reparser {
-    /// If the node is a braced block, return the corresponding `Reparser`.
+    /// If the node can be reparsed on its own, return the corresponding `Reparser`.
     pub fn for_node(
         node: SyntaxKind,
         first_child: Option<SyntaxKind>,
@@ -142,8 +192,9 @@ impl Reparser {
 
     /// Re-parse given tokens using this `Reparser`.
     ///
-    /// Tokens must start with `{`, end with `}` and form a valid brace
-    /// sequence.
+    /// The parser may consume fewer tokens than given or look past the end
+    /// of the node, so the caller is responsible for checking that exactly
+    /// the tokens of the node were used to produce a single node.
     pub fn parse(self, token_source: &mut dyn TokenSource, tree_sink: &mut dyn TreeSink) {
         let Reparser(r) = self;
         let mut p = parser::Parser::new(token_source);
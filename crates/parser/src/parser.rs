@@ -194,12 +194,13 @@ impl<'t> Parser<'t> {
         self.do_bump(kind, 1);
     }
 
-    /// Emit error with the `message`
+    /// Emit error with the `message`, which is either a plain string or a
+    /// `ParseError` with a code.
     /// FIXME: this should be much more fancy and support
     /// structured errors with spans and notes, like rustc
     /// does.
-    pub(crate) fn error<T: Into<String>>(&mut self, message: T) {
-        let msg = ParseError(Box::new(message.into()));
+    pub(crate) fn error<T: Into<ParseError>>(&mut self, message: T) {
+        let msg = message.into();
         self.push_event(Event::Error { msg })
     }
 
@@ -209,12 +210,12 @@ impl<'t> Parser<'t> {
         if self.eat(kind) {
             return true;
         }
-        self.error(format!("expected {:?}", kind));
+        self.error(ParseError::missing(&[kind], format!("expected {:?}", kind)));
         false
     }
 
     /// Create an error node and consume the next token.
-    pub(crate) fn err_and_bump(&mut self, message: &str) {
+    pub(crate) fn err_and_bump<T: Into<ParseError>>(&mut self, message: T) {
         match self.current() {
             L_DOLLAR | R_DOLLAR => {
                 let m = self.start();
@@ -229,7 +230,7 @@ impl<'t> Parser<'t> {
     }
 
     /// Create an error node and consume the next token.
-    pub(crate) fn err_recover(&mut self, message: &str, recovery: TokenSet) {
+    pub(crate) fn err_recover<T: Into<ParseError>>(&mut self, message: T, recovery: TokenSet) {
         match self.current() {
             T!['{'] | T!['}'] | L_DOLLAR | R_DOLLAR => {
                 self.error(message);
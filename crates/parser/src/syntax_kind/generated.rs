@@ -180,7 +180,7 @@ pub enum SyntaxKind {
     CLOSURE_EXPR,
     IF_EXPR,
     WHILE_EXPR,
-    CONDITION,
+    LET_EXPR,
     LOOP_EXPR,
     FOR_EXPR,
     CONTINUE_EXPR,
@@ -234,6 +234,7 @@ pub enum SyntaxKind {
     NAME,
     NAME_REF,
     LET_STMT,
+    LET_ELSE,
     EXPR_STMT,
     GENERIC_PARAM_LIST,
     GENERIC_PARAM,
@@ -10,19 +10,27 @@ mod process;
 mod rpc;
 mod version;
 
-use base_db::{Env, ProcMacro};
+use base_db::{
+    Env, ProcMacro, ProcMacroDiagnostic, ProcMacroDiagnosticLevel, ProcMacroExpansion,
+    ProcMacroSpans,
+};
 use std::{
     ffi::OsStr,
     io,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use tt::{SmolStr, Subtree};
 
 use crate::process::{ProcMacroProcessSrv, ProcMacroProcessThread};
 
-pub use rpc::{ExpansionResult, ExpansionTask, ListMacrosResult, ListMacrosTask, ProcMacroKind};
+pub use process::DEFAULT_EXPANSION_TIMEOUT;
+pub use rpc::{
+    DiagnosticLevel, ExpansionDiagnostic, ExpansionResult, ExpansionSpans, ExpansionTask,
+    ListMacrosResult, ListMacrosTask, ProcMacroKind,
+};
 pub use version::{read_dylib_info, RustCInfo};
 
 #[derive(Debug, Clone)]
@@ -47,17 +55,44 @@ impl base_db::ProcMacroExpander for ProcMacroProcessExpander {
         subtree: &Subtree,
         attr: Option<&Subtree>,
         env: &Env,
-    ) -> Result<Subtree, tt::ExpansionError> {
+        spans: &ProcMacroSpans,
+    ) -> Result<ProcMacroExpansion, tt::ExpansionError> {
         let task = ExpansionTask {
             macro_body: subtree.clone(),
             macro_name: self.name.to_string(),
             attributes: attr.cloned(),
             lib: self.dylib_path.to_path_buf(),
             env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            spans: ExpansionSpans {
+                file: spans.file.clone(),
+                text: spans.text.clone(),
+                tokens: spans
+                    .tokens
+                    .iter()
+                    .map(|(id, range)| (id.0, (range.start().into(), range.end().into())))
+                    .collect(),
+            },
         };
 
         let result: ExpansionResult = self.process.send_task(msg::Request::ExpansionMacro(task))?;
-        Ok(result.expansion)
+        Ok(ProcMacroExpansion {
+            subtree: result.expansion,
+            diagnostics: result.diagnostics.into_iter().map(diagnostic_from_rpc).collect(),
+        })
+    }
+}
+
+fn diagnostic_from_rpc(diagnostic: ExpansionDiagnostic) -> ProcMacroDiagnostic {
+    ProcMacroDiagnostic {
+        level: match diagnostic.level {
+            DiagnosticLevel::Error => ProcMacroDiagnosticLevel::Error,
+            DiagnosticLevel::Warning => ProcMacroDiagnosticLevel::Warning,
+            DiagnosticLevel::Note => ProcMacroDiagnosticLevel::Note,
+            DiagnosticLevel::Help => ProcMacroDiagnosticLevel::Help,
+        },
+        message: diagnostic.message,
+        spans: diagnostic.spans.into_iter().map(tt::TokenId).collect(),
+        children: diagnostic.children.into_iter().map(diagnostic_from_rpc).collect(),
     }
 }
 
@@ -68,11 +103,14 @@ pub struct ProcMacroClient {
 }
 
 impl ProcMacroClient {
+    /// Spawns the proc macro server. Expansions it does not answer within
+    /// `expansion_timeout` fail, and the server is restarted.
     pub fn extern_process(
         process_path: PathBuf,
         args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+        expansion_timeout: Duration,
     ) -> io::Result<ProcMacroClient> {
-        let (thread, process) = ProcMacroProcessSrv::run(process_path, args)?;
+        let (thread, process) = ProcMacroProcessSrv::run(process_path, args, expansion_timeout)?;
         Ok(ProcMacroClient { process: Arc::new(process), thread })
     }
 
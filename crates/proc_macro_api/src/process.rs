@@ -1,22 +1,35 @@
 //! Handle process life-time and message passing for proc-macro client
+//!
+//! The server process is supervised by the client thread: if it dies or does
+//! not answer a request within the expansion timeout, it is killed and started
+//! again for the next request. A macro which brings the server down
+//! `MAX_CRASHES` times is not sent to the server anymore.
 
 use std::{
+    collections::HashMap,
     convert::{TryFrom, TryInto},
     ffi::{OsStr, OsString},
-    io::{self, BufRead, BufReader, Write},
+    io::{self, BufReader},
     path::{Path, PathBuf},
-    process::{Child, Command, Stdio},
+    process::{ChildStdin, Command, Stdio},
     sync::{Arc, Weak},
+    time::Duration,
 };
 
-use crossbeam_channel::{bounded, Receiver, Sender};
+use crossbeam_channel::{bounded, unbounded, Receiver, RecvTimeoutError, Sender};
 use stdx::JodChild;
 
 use crate::{
-    msg::{ErrorCode, Message, Request, Response, ResponseError},
+    msg::{Message, Request, Response},
     rpc::{ListMacrosResult, ListMacrosTask, ProcMacroKind},
 };
 
+/// How long we wait for the server to answer a single request, unless
+/// configured otherwise.
+pub const DEFAULT_EXPANSION_TIMEOUT: Duration = Duration::from_secs(10);
+/// How many times a macro may crash (or hang) the server before we stop calling it.
+const MAX_CRASHES: usize = 3;
+
 #[derive(Debug, Default)]
 pub(crate) struct ProcMacroProcessSrv {
     inner: Weak<Sender<Task>>,
@@ -33,12 +46,14 @@ impl ProcMacroProcessSrv {
     pub(crate) fn run(
         process_path: PathBuf,
         args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+        expansion_timeout: Duration,
     ) -> io::Result<(ProcMacroProcessThread, ProcMacroProcessSrv)> {
-        let process = Process::run(process_path, args)?;
+        let args: Vec<OsString> = args.into_iter().map(|s| s.as_ref().into()).collect();
+        let supervisor = Supervisor::new(process_path, args, expansion_timeout)?;
 
         let (task_tx, task_rx) = bounded(0);
         let handle = jod_thread::spawn(move || {
-            client_loop(task_rx, process);
+            client_loop(task_rx, supervisor);
         });
 
         let task_tx = Arc::new(task_tx);
@@ -75,71 +90,144 @@ impl ProcMacroProcessSrv {
             .recv()
             .map_err(|_| tt::ExpansionError::Unknown("proc macro server crashed".into()))?;
 
-        match res {
-            Some(Response::Error(err)) => Err(tt::ExpansionError::ExpansionError(err.message)),
-            Some(res) => Ok(res.try_into().map_err(|err| {
+        match res? {
+            Response::Error(err) => Err(tt::ExpansionError::ExpansionError(err.message)),
+            res => Ok(res.try_into().map_err(|err| {
                 tt::ExpansionError::Unknown(format!("Fail to get response, reason : {:#?} ", err))
             })?),
-            None => Err(tt::ExpansionError::Unknown("Empty result".into())),
         }
     }
 }
 
-fn client_loop(task_rx: Receiver<Task>, mut process: Process) {
-    let (mut stdin, mut stdout) = process.stdio().expect("couldn't access child stdio");
-
-    let mut buf = String::new();
-
+fn client_loop(task_rx: Receiver<Task>, mut supervisor: Supervisor) {
     for Task { req, result_tx } in task_rx {
-        match send_request(&mut stdin, &mut stdout, req, &mut buf) {
-            Ok(res) => result_tx.send(res).unwrap(),
-            Err(err) => {
-                log::error!(
-                    "proc macro server crashed, server process state: {:?}, server request error: {:?}",
-                    process.child.try_wait(),
-                    err
-                );
-                let res = Response::Error(ResponseError {
-                    code: ErrorCode::ServerErrorEnd,
-                    message: "proc macro server crashed".into(),
-                });
-                result_tx.send(res.into()).unwrap();
-                // Exit the thread.
-                break;
-            }
-        }
+        result_tx.send(supervisor.send_request(req)).unwrap();
     }
 }
 
 struct Task {
     req: Request,
-    result_tx: Sender<Option<Response>>,
+    result_tx: Sender<Result<Response, tt::ExpansionError>>,
+}
+
+/// Owns the server process, restarting it when it dies and keeping track of
+/// the macros which made it crash.
+struct Supervisor {
+    path: PathBuf,
+    args: Vec<OsString>,
+    timeout: Duration,
+    process: Option<Process>,
+    /// Crash count and last error, per `(dylib, macro name)`.
+    crashes: HashMap<(PathBuf, String), (usize, String)>,
+}
+
+impl Supervisor {
+    fn new(path: PathBuf, args: Vec<OsString>, timeout: Duration) -> io::Result<Supervisor> {
+        let process = Process::run(&path, &args)?;
+        Ok(Supervisor { path, args, timeout, process: Some(process), crashes: HashMap::new() })
+    }
+
+    fn send_request(&mut self, req: Request) -> Result<Response, tt::ExpansionError> {
+        let macro_key = match &req {
+            Request::ExpansionMacro(task) => Some((task.lib.clone(), task.macro_name.clone())),
+            Request::ListMacro(_) => None,
+        };
+        if let Some((count, reason)) = macro_key.as_ref().and_then(|key| self.crashes.get(key)) {
+            if *count >= MAX_CRASHES {
+                return Err(tt::ExpansionError::Disabled(format!(
+                    "it crashed the proc macro server {} times, last error: {}",
+                    count, reason
+                )));
+            }
+        }
+
+        let timeout = self.timeout;
+        let res = self.process()?.send_request(req, timeout);
+        res.map_err(|err| {
+            log::error!("proc macro server crashed, server request error: {:?}", err);
+            // Kill the server, it is restarted on the next request.
+            self.process = None;
+            if let Some(key) = macro_key {
+                let entry = self.crashes.entry(key).or_insert((0, String::new()));
+                *entry = (entry.0 + 1, err.to_string());
+            }
+            tt::ExpansionError::Unknown(format!("proc macro server crashed: {}", err))
+        })
+    }
+
+    /// Returns the running server, starting a new one if the previous one exited.
+    fn process(&mut self) -> Result<&mut Process, tt::ExpansionError> {
+        if let Some(process) = &mut self.process {
+            if !matches!(process.child.try_wait(), Ok(None)) {
+                log::error!("proc macro server exited, restarting it");
+                self.process = None;
+            }
+        }
+        if self.process.is_none() {
+            let process = Process::run(&self.path, &self.args).map_err(|err| {
+                tt::ExpansionError::Unknown(format!("failed to restart proc macro server: {}", err))
+            })?;
+            self.process = Some(process);
+        }
+        Ok(self.process.as_mut().unwrap())
+    }
 }
 
 struct Process {
     child: JodChild,
+    stdin: ChildStdin,
+    responses: Receiver<io::Result<Response>>,
 }
 
 impl Process {
-    fn run(
-        path: PathBuf,
-        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
-    ) -> io::Result<Process> {
-        let args: Vec<OsString> = args.into_iter().map(|s| s.as_ref().into()).collect();
-        let child = JodChild(mk_child(&path, &args)?);
-        Ok(Process { child })
-    }
+    fn run(path: &Path, args: &[OsString]) -> io::Result<Process> {
+        let mut child = JodChild(mk_child(path, args)?);
+        let stdin = child.stdin.take().expect("couldn't access child stdin");
+        let stdout = child.stdout.take().expect("couldn't access child stdout");
 
-    fn stdio(&mut self) -> Option<(impl Write, impl BufRead)> {
-        let stdin = self.child.stdin.take()?;
-        let stdout = self.child.stdout.take()?;
-        let read = BufReader::new(stdout);
+        // Responses are read on a separate thread, so that we can give up on a
+        // request which takes too long. The thread is detached: it stops once
+        // the server's stdout is closed or `responses` is dropped.
+        let (response_tx, responses) = unbounded();
+        jod_thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            let mut buf = String::new();
+            loop {
+                let res = Response::read(&mut stdout, &mut buf).and_then(|res| {
+                    res.ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::UnexpectedEof, "proc macro server exited")
+                    })
+                });
+                let stop = res.is_err();
+                if response_tx.send(res).is_err() || stop {
+                    break;
+                }
+            }
+        })
+        .detach();
 
-        Some((stdin, read))
+        Ok(Process { child, stdin, responses })
+    }
+
+    fn send_request(&mut self, req: Request, timeout: Duration) -> io::Result<Response> {
+        req.write(&mut self.stdin)?;
+        match self.responses.recv_timeout(timeout) {
+            Ok(res) => res,
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("no response after {:?}", timeout),
+            )),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "proc macro server exited"))
+            }
+        }
     }
 }
 
-fn mk_child(path: &Path, args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> io::Result<Child> {
+fn mk_child(
+    path: &Path,
+    args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+) -> io::Result<std::process::Child> {
     Command::new(&path)
         .args(args)
         .stdin(Stdio::piped())
@@ -148,12 +236,72 @@ fn mk_child(path: &Path, args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> i
         .spawn()
 }
 
-fn send_request(
-    mut writer: &mut impl Write,
-    mut reader: &mut impl BufRead,
-    req: Request,
-    buf: &mut String,
-) -> io::Result<Option<Response>> {
-    req.write(&mut writer)?;
-    Response::read(&mut reader, buf)
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::rpc::ExpansionTask;
+
+    use super::*;
+
+    fn fake_server(script: &str, timeout: Duration) -> Supervisor {
+        let args = vec!["-c".into(), script.into()];
+        Supervisor::new("sh".into(), args, timeout).unwrap()
+    }
+
+    fn list_macros() -> Request {
+        Request::ListMacro(ListMacrosTask { lib: "libfoo.so".into() })
+    }
+
+    fn expand_foo() -> Request {
+        Request::ExpansionMacro(ExpansionTask {
+            macro_body: tt::Subtree::default(),
+            macro_name: "foo".into(),
+            attributes: None,
+            lib: "libfoo.so".into(),
+            env: Vec::new(),
+            spans: Default::default(),
+        })
+    }
+
+    #[test]
+    fn restarts_exited_server() {
+        // Answers a single request, then exits.
+        let mut srv = fake_server(
+            r#"read line; echo '{"ListMacro":{"macros":[]}}'"#,
+            Duration::from_secs(10),
+        );
+
+        assert!(matches!(srv.send_request(list_macros()), Ok(Response::ListMacro(_))));
+        srv.process.as_mut().unwrap().child.wait().unwrap();
+        assert!(matches!(srv.send_request(list_macros()), Ok(Response::ListMacro(_))));
+    }
+
+    #[test]
+    fn disables_macro_hanging_the_server() {
+        // Hangs on expansion requests, answers anything else.
+        let mut srv = fake_server(
+            r#"read line; case "$line" in
+                *ExpansionMacro*) exec sleep 60;;
+                *) echo '{"ListMacro":{"macros":[]}}';;
+            esac"#,
+            Duration::from_millis(100),
+        );
+
+        for _ in 0..MAX_CRASHES {
+            match srv.send_request(expand_foo()) {
+                Err(tt::ExpansionError::Unknown(msg)) => {
+                    assert!(msg.contains("no response after"), "{}", msg)
+                }
+                res => panic!("unexpected result: {:?}", res),
+            }
+        }
+        match srv.send_request(expand_foo()) {
+            Err(tt::ExpansionError::Disabled(reason)) => {
+                assert!(reason.contains("3 times"), "{}", reason)
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // Other requests still go to the (restarted) server.
+        assert!(matches!(srv.send_request(list_macros()), Ok(Response::ListMacro(_))));
+    }
 }
@@ -54,12 +54,49 @@ pub struct ExpansionTask {
 
     /// Environment variables to set during macro expansion.
     pub env: Vec<(String, String)>,
+
+    /// Source locations of the tokens of `macro_body`.
+    #[serde(default)]
+    pub spans: ExpansionSpans,
+}
+
+/// Where the tokens of a macro call are in the source, see `base_db::ProcMacroSpans`.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ExpansionSpans {
+    /// Path of the file containing the macro call.
+    pub file: String,
+    /// Source text of the macro call. Locations are relative to its start.
+    pub text: String,
+    /// Token ids with their byte range in `text`.
+    pub tokens: Vec<(u32, (u32, u32))>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct ExpansionResult {
     #[serde(with = "SubtreeDef")]
     pub expansion: Subtree,
+
+    /// Diagnostics the macro emitted while expanding.
+    #[serde(default)]
+    pub diagnostics: Vec<ExpansionDiagnostic>,
+}
+
+/// A diagnostic emitted by a proc macro, with `proc_macro::Diagnostic::emit`.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ExpansionDiagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+    /// Ids of the input tokens the diagnostic points at, empty for the whole macro call.
+    pub spans: Vec<u32>,
+    pub children: Vec<ExpansionDiagnostic>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+    Note,
+    Help,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -274,6 +311,7 @@ mod tests {
             attributes: None,
             lib: Default::default(),
             env: Default::default(),
+            spans: Default::default(),
         };
 
         let json = serde_json::to_string(&task).unwrap();
@@ -281,7 +319,15 @@ mod tests {
 
         assert_eq!(task.macro_body, back.macro_body);
 
-        let result = ExpansionResult { expansion: tt };
+        let result = ExpansionResult {
+            expansion: tt,
+            diagnostics: vec![ExpansionDiagnostic {
+                level: DiagnosticLevel::Warning,
+                message: "unused".into(),
+                spans: vec![0, 2],
+                children: Vec::new(),
+            }],
+        };
         let json = serde_json::to_string(&result).unwrap();
         let back: ExpansionResult = serde_json::from_str(&json).unwrap();
 
@@ -1,6 +1,7 @@
 //! Reading proc-macro rustc version information from binary data
 
 use std::{
+    convert::TryInto,
     fs::File,
     io::{self, Read},
     path::Path,
@@ -49,7 +50,7 @@ pub fn read_dylib_info(dylib_path: &Path) -> io::Result<RustCInfo> {
     if date.len() == 0 {
         return Err(err!("date format error"));
     }
-    let date = date[0..date.len() - 2].to_string();
+    let date = date[0..date.len() - 1].to_string();
 
     let version_numbers = version
         .split('.')
@@ -80,16 +81,20 @@ fn read_section<'a>(dylib_binary: &'a [u8], section_name: &str) -> io::Result<&'
 ///
 /// binary file.
 /// A proc macro crate binary's ".rustc" section has following byte layout:
-/// * [b'r',b'u',b's',b't',0,0,0,5] is the first 8 bytes
-/// * ff060000 734e6150 is followed, it's the snappy format magic bytes,
-///   means bytes from here(including this sequence) are compressed in
-///   snappy compression format. Version info is inside here, so decompress
-///   this.
-/// The bytes you get after decompressing the snappy format portion has
-/// following layout:
-/// * [b'r',b'u',b's',b't',0,0,0,5] is the first 8 bytes(again)
-/// * [crate root bytes] next 4 bytes is to store crate root position,
-///   according to rustc's source code comment
+/// * [b'r',b'u',b's',b't',0,0,0,V] is the first 8 bytes, where V is the
+///   metadata version
+/// * for metadata versions 7 and 8, the next 4 bytes are the big-endian length
+///   of the data that follows; for versions 9 and 10 it is an 8 byte
+///   little-endian length
+/// * the data itself is either compressed in snappy format (it starts with the
+///   snappy magic bytes ff060000 734e6150), or, for recent compilers, stored
+///   uncompressed. Version info is inside here.
+///
+/// The bytes you get after decompressing the data portion has following
+/// layout:
+/// * [b'r',b'u',b's',b't',0,0,0,V] is the first 8 bytes(again)
+/// * [crate root bytes] next 4 bytes (8 bytes since version 9) is to store
+///   crate root position, according to rustc's source code comment
 /// * [length byte] next 1 byte tells us how many bytes we should read next
 ///   for the version string's utf8 bytes
 /// * [version string bytes encoded in utf8] <- GET THIS BOI
@@ -102,32 +107,55 @@ fn read_version(dylib_path: &Path) -> io::Result<String> {
 
     let dot_rustc = read_section(&dylib_mmaped, ".rustc")?;
 
-    let header = &dot_rustc[..8];
-    const EXPECTED_HEADER: [u8; 8] = [b'r', b'u', b's', b't', 0, 0, 0, 5];
-    // check if header is valid
-    if header != EXPECTED_HEADER {
+    let header = dot_rustc
+        .get(..8)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "section header is truncated"))?;
+    if header[..4] != *b"rust" {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
-            format!("only metadata version 5 is supported, section header was: {:?}", header),
+            format!("unknown metadata header: {:?}", header),
         ));
     }
-
-    let snappy_portion = &dot_rustc[8..];
-
-    let mut snappy_decoder = SnapDecoder::new(snappy_portion);
-
-    // the bytes before version string bytes, so this basically is:
-    // 8 bytes for [b'r',b'u',b's',b't',0,0,0,5]
-    // 4 bytes for [crate root bytes]
-    // 1 byte for length of version string
-    // so 13 bytes in total, and we should check the 13th byte
-    // to know the length
-    let mut bytes_before_version = [0u8; 13];
-    snappy_decoder.read_exact(&mut bytes_before_version)?;
-    let length = bytes_before_version[12]; // what? can't use -1 indexing?
+    let version = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+
+    // `bytes_before_version` is the header again, the crate root position and
+    // one byte for the length of the version string.
+    let (data_portion, bytes_before_version) = match version {
+        5 | 6 => (&dot_rustc[8..], 13),
+        7 | 8 => {
+            let len_bytes = truncated_check(dot_rustc.get(8..12))?;
+            let data_len = u32::from_be_bytes(len_bytes.try_into().unwrap()) as usize;
+            (truncated_check(dot_rustc.get(12..12 + data_len))?, 13)
+        }
+        9 | 10 => {
+            let len_bytes = truncated_check(dot_rustc.get(8..16))?;
+            let data_len = u64::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+            (truncated_check(dot_rustc.get(16..16 + data_len))?, 17)
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported metadata version {}", version),
+            ));
+        }
+    };
+
+    let mut uncompressed: Box<dyn Read> = if data_portion.starts_with(b"rust") {
+        Box::new(data_portion)
+    } else {
+        Box::new(SnapDecoder::new(data_portion))
+    };
+
+    let mut bytes_before_version = vec![0u8; bytes_before_version];
+    uncompressed.read_exact(&mut bytes_before_version)?;
+    let length = bytes_before_version[bytes_before_version.len() - 1];
 
     let mut version_string_utf8 = vec![0u8; length as usize];
-    snappy_decoder.read_exact(&mut version_string_utf8)?;
+    uncompressed.read_exact(&mut version_string_utf8)?;
     let version_string = String::from_utf8(version_string_utf8);
     version_string.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
+
+fn truncated_check(data: Option<&[u8]>) -> io::Result<&[u8]> {
+    data.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "metadata is truncated"))
+}
@@ -401,26 +401,47 @@ fn run_client<A: for<'a, 's> DecodeMut<'a, 's, ()>, R: Encode<()>>(
     b
 }
 
-impl Client<fn(crate::TokenStream) -> crate::TokenStream> {
-    pub fn expand1(f: fn(crate::TokenStream) -> crate::TokenStream) -> Self {
+impl Client<fn(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream> {
+    pub fn expand1(
+        f: fn(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream,
+    ) -> Self {
         extern "C" fn run(
             bridge: Bridge<'_>,
-            f: impl FnOnce(crate::TokenStream) -> crate::TokenStream,
+            f: impl FnOnce(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream,
         ) -> Buffer<u8> {
-            run_client(bridge, |input| f(crate::TokenStream(input)).0)
+            run_client(bridge, |input| f(crate::abis::abi_1_47::TokenStream(input)).0)
         }
         Client { get_handle_counters: HandleCounters::get, run, f }
     }
 }
 
-impl Client<fn(crate::TokenStream, crate::TokenStream) -> crate::TokenStream> {
-    pub fn expand2(f: fn(crate::TokenStream, crate::TokenStream) -> crate::TokenStream) -> Self {
+impl
+    Client<
+        fn(
+            crate::abis::abi_1_47::TokenStream,
+            crate::abis::abi_1_47::TokenStream,
+        ) -> crate::abis::abi_1_47::TokenStream,
+    >
+{
+    pub fn expand2(
+        f: fn(
+            crate::abis::abi_1_47::TokenStream,
+            crate::abis::abi_1_47::TokenStream,
+        ) -> crate::abis::abi_1_47::TokenStream,
+    ) -> Self {
         extern "C" fn run(
             bridge: Bridge<'_>,
-            f: impl FnOnce(crate::TokenStream, crate::TokenStream) -> crate::TokenStream,
+            f: impl FnOnce(
+                crate::abis::abi_1_47::TokenStream,
+                crate::abis::abi_1_47::TokenStream,
+            ) -> crate::abis::abi_1_47::TokenStream,
         ) -> Buffer<u8> {
             run_client(bridge, |(input, input2)| {
-                f(crate::TokenStream(input), crate::TokenStream(input2)).0
+                f(
+                    crate::abis::abi_1_47::TokenStream(input),
+                    crate::abis::abi_1_47::TokenStream(input2),
+                )
+                .0
             })
         }
         Client { get_handle_counters: HandleCounters::get, run, f }
@@ -433,17 +454,24 @@ pub enum ProcMacro {
     CustomDerive {
         trait_name: &'static str,
         attributes: &'static [&'static str],
-        client: Client<fn(crate::TokenStream) -> crate::TokenStream>,
+        client:
+            Client<fn(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream>,
     },
 
     Attr {
         name: &'static str,
-        client: Client<fn(crate::TokenStream, crate::TokenStream) -> crate::TokenStream>,
+        client: Client<
+            fn(
+                crate::abis::abi_1_47::TokenStream,
+                crate::abis::abi_1_47::TokenStream,
+            ) -> crate::abis::abi_1_47::TokenStream,
+        >,
     },
 
     Bang {
         name: &'static str,
-        client: Client<fn(crate::TokenStream) -> crate::TokenStream>,
+        client:
+            Client<fn(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream>,
     },
 }
 
@@ -465,19 +493,25 @@ impl ProcMacro {
     pub fn custom_derive(
         trait_name: &'static str,
         attributes: &'static [&'static str],
-        expand: fn(crate::TokenStream) -> crate::TokenStream,
+        expand: fn(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream,
     ) -> Self {
         ProcMacro::CustomDerive { trait_name, attributes, client: Client::expand1(expand) }
     }
 
     pub fn attr(
         name: &'static str,
-        expand: fn(crate::TokenStream, crate::TokenStream) -> crate::TokenStream,
+        expand: fn(
+            crate::abis::abi_1_47::TokenStream,
+            crate::abis::abi_1_47::TokenStream,
+        ) -> crate::abis::abi_1_47::TokenStream,
     ) -> Self {
         ProcMacro::Attr { name, client: Client::expand2(expand) }
     }
 
-    pub fn bang(name: &'static str, expand: fn(crate::TokenStream) -> crate::TokenStream) -> Self {
+    pub fn bang(
+        name: &'static str,
+        expand: fn(crate::abis::abi_1_47::TokenStream) -> crate::abis::abi_1_47::TokenStream,
+    ) -> Self {
         ProcMacro::Bang { name, client: Client::expand1(expand) }
     }
 }
@@ -133,7 +133,9 @@ impl Extend<TokenStream> for TokenStream {
 
 /// Public implementation details for the `TokenStream` type, such as iterators.
 pub mod token_stream {
-    use crate::proc_macro::{bridge, Group, Ident, Literal, Punct, TokenStream, TokenTree};
+    use crate::abis::abi_1_47::proc_macro::{
+        bridge, Group, Ident, Literal, Punct, TokenStream, TokenTree,
+    };
 
     /// An iterator over `TokenStream`'s `TokenTree`s.
     /// The iteration is "shallow", e.g., the iterator doesn't recurse into delimited groups,
@@ -8,12 +8,16 @@
 //!
 //! FIXME: No span and source file information is implemented yet
 
-use crate::proc_macro::bridge::{self, server};
+use crate::abis::abi_1_47::proc_macro::bridge::{self, server};
 
+use proc_macro_api::{DiagnosticLevel, ExpansionDiagnostic};
+
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::iter::FromIterator;
 use std::ops::Bound;
+use std::rc::Rc;
 use std::{ascii, vec::IntoIter};
 
 type Group = tt::Subtree;
@@ -97,9 +101,9 @@ impl Extend<TokenStream> for TokenStream {
     }
 }
 
-type Level = crate::proc_macro::Level;
-type LineColumn = crate::proc_macro::LineColumn;
-type SourceFile = crate::proc_macro::SourceFile;
+type Level = crate::abis::abi_1_47::proc_macro::Level;
+type LineColumn = crate::abis::abi_1_47::proc_macro::LineColumn;
+type SourceFile = crate::abis::abi_1_47::proc_macro::SourceFile;
 
 /// A structure representing a diagnostic message and associated children
 /// messages.
@@ -116,6 +120,25 @@ impl Diagnostic {
     pub fn new<T: Into<String>>(level: Level, message: T) -> Diagnostic {
         Diagnostic { level, message: message.into(), spans: vec![], children: vec![] }
     }
+
+    fn into_external(self) -> ExpansionDiagnostic {
+        ExpansionDiagnostic {
+            level: match self.level {
+                Level::Error => DiagnosticLevel::Error,
+                Level::Warning => DiagnosticLevel::Warning,
+                Level::Note => DiagnosticLevel::Note,
+                Level::Help => DiagnosticLevel::Help,
+            },
+            message: self.message,
+            spans: self
+                .spans
+                .into_iter()
+                .filter(|&span| span != tt::TokenId::unspecified())
+                .map(|span| span.0)
+                .collect(),
+            children: self.children.into_iter().map(Diagnostic::into_external).collect(),
+        }
+    }
 }
 
 // Rustc Server Ident has to be `Copyable`
@@ -304,6 +327,15 @@ pub struct TokenStreamIter {
 pub struct Rustc {
     ident_interner: IdentInterner,
     // FIXME: store span information here.
+    /// Diagnostics emitted by the macro, shared with the caller as the bridge
+    /// consumes the server.
+    diagnostics: Rc<RefCell<Vec<ExpansionDiagnostic>>>,
+}
+
+impl Rustc {
+    pub(crate) fn diagnostics(&self) -> Rc<RefCell<Vec<ExpansionDiagnostic>>> {
+        self.diagnostics.clone()
+    }
 }
 
 impl server::Types for Rustc {
@@ -648,18 +680,18 @@ impl server::Diagnostic for Rustc {
 
     fn sub(
         &mut self,
-        _diag: &mut Self::Diagnostic,
-        _level: Level,
-        _msg: &str,
-        _spans: Self::MultiSpan,
+        diag: &mut Self::Diagnostic,
+        level: Level,
+        msg: &str,
+        spans: Self::MultiSpan,
     ) {
-        // FIXME handle diagnostic
-        //
+        let mut child = Diagnostic::new(level, msg);
+        child.spans = spans;
+        diag.children.push(child);
     }
 
-    fn emit(&mut self, _diag: Self::Diagnostic) {
-        // FIXME handle diagnostic
-        // diag.emit()
+    fn emit(&mut self, diag: Self::Diagnostic) {
+        self.diagnostics.borrow_mut().push(diag.into_external());
     }
 }
 
@@ -735,11 +767,11 @@ impl server::MultiSpan for Rustc {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::proc_macro::bridge::server::Literal;
+    use crate::abis::abi_1_47::proc_macro::bridge::server::Literal;
 
     #[test]
     fn test_rustc_server_literals() {
-        let mut srv = Rustc { ident_interner: IdentInterner::default() };
+        let mut srv = Rustc { ident_interner: IdentInterner::default(), ..Rustc::default() };
         assert_eq!(srv.integer("1234").text, "1234");
 
         assert_eq!(srv.typed_integer("12", "u8").text, "12u8");
@@ -0,0 +1,116 @@
+//! Macro ABI for version 1.95 of rustc
+
+#[allow(dead_code)]
+#[doc(hidden)]
+mod proc_macro;
+
+#[doc(hidden)]
+mod rustc_server;
+
+use std::io;
+
+use libloading::Library;
+use proc_macro_api::{ExpansionDiagnostic, ExpansionSpans, ProcMacroKind};
+
+use super::PanicMessage;
+
+pub(crate) use rustc_server::TokenStream;
+
+pub(crate) struct Abi {
+    exported_macros: Vec<proc_macro::bridge::client::ProcMacro>,
+}
+
+impl From<proc_macro::bridge::PanicMessage> for PanicMessage {
+    fn from(p: proc_macro::bridge::PanicMessage) -> Self {
+        Self { message: p.as_str().map(|s| s.to_string()) }
+    }
+}
+
+impl Abi {
+    pub(crate) unsafe fn from_lib(lib: &Library, symbol_name: String) -> io::Result<Abi> {
+        let macros: libloading::Symbol<&&[proc_macro::bridge::client::ProcMacro]> = lib
+            .get(symbol_name.as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self { exported_macros: macros.to_vec() })
+    }
+
+    pub(crate) fn expand(
+        &self,
+        macro_name: &str,
+        macro_body: &tt::Subtree,
+        attributes: Option<&tt::Subtree>,
+        spans: &ExpansionSpans,
+    ) -> Result<(tt::Subtree, Vec<ExpansionDiagnostic>), PanicMessage> {
+        let server = rustc_server::RustAnalyzer::new(spans);
+        let diagnostics = server.diagnostics();
+        let parsed_body = TokenStream::with_subtree(macro_body.clone());
+
+        let parsed_attributes =
+            attributes.map_or(TokenStream::new(), |attr| TokenStream::with_subtree(attr.clone()));
+
+        for proc_macro in &self.exported_macros {
+            match proc_macro {
+                proc_macro::bridge::client::ProcMacro::CustomDerive {
+                    trait_name, client, ..
+                } if *trait_name == macro_name => {
+                    let res = client.run(
+                        &proc_macro::bridge::server::SAME_THREAD,
+                        server,
+                        parsed_body,
+                        false,
+                    );
+                    return res
+                        .map(|it| (it.into_subtree(), diagnostics.take()))
+                        .map_err(PanicMessage::from);
+                }
+                proc_macro::bridge::client::ProcMacro::Bang { name, client }
+                    if *name == macro_name =>
+                {
+                    let res = client.run(
+                        &proc_macro::bridge::server::SAME_THREAD,
+                        server,
+                        parsed_body,
+                        false,
+                    );
+                    return res
+                        .map(|it| (it.into_subtree(), diagnostics.take()))
+                        .map_err(PanicMessage::from);
+                }
+                proc_macro::bridge::client::ProcMacro::Attr { name, client }
+                    if *name == macro_name =>
+                {
+                    let res = client.run(
+                        &proc_macro::bridge::server::SAME_THREAD,
+                        server,
+                        parsed_attributes,
+                        parsed_body,
+                        false,
+                    );
+                    return res
+                        .map(|it| (it.into_subtree(), diagnostics.take()))
+                        .map_err(PanicMessage::from);
+                }
+                _ => continue,
+            }
+        }
+
+        Err(proc_macro::bridge::PanicMessage::String("Nothing to expand".to_string()).into())
+    }
+
+    pub(crate) fn list_macros(&self) -> Vec<(String, ProcMacroKind)> {
+        self.exported_macros
+            .iter()
+            .map(|proc_macro| match proc_macro {
+                proc_macro::bridge::client::ProcMacro::CustomDerive { trait_name, .. } => {
+                    (trait_name.to_string(), ProcMacroKind::CustomDerive)
+                }
+                proc_macro::bridge::client::ProcMacro::Bang { name, .. } => {
+                    (name.to_string(), ProcMacroKind::FuncLike)
+                }
+                proc_macro::bridge::client::ProcMacro::Attr { name, .. } => {
+                    (name.to_string(), ProcMacroKind::Attr)
+                }
+            })
+            .collect()
+    }
+}
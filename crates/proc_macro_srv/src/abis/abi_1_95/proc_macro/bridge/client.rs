@@ -0,0 +1,86 @@
+//! lib-proc-macro Client-side types.
+//!
+//! Copy from <https://github.com/rust-lang/rust/blob/1.95.0/library/proc_macro/src/bridge/client.rs>
+//! augmented with removing unstable features
+//!
+//! Only the types shared with the server are kept: the client runtime itself
+//! lives in the proc-macro dylib, we just need matching layouts for
+//! `ProcMacro` and `Client` to be able to call into it.
+
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicU32;
+
+use super::*;
+
+#[repr(C)]
+pub(super) struct HandleCounters {
+    pub(super) token_stream: AtomicU32,
+    pub(super) span: AtomicU32,
+}
+
+// The client-side handle types only mark server values, see `server::MarkedTokenStream`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TokenStream;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol;
+
+/// A client-side RPC entry-point, which may be using a different `proc_macro`
+/// from the one used by the server, but can be invoked compatibly.
+///
+/// Note that the (phantom) `I` ("input") and `O` ("output") type parameters
+/// decorate the `Client<I, O>` with the RPC "interface" of the entry-point, but
+/// do not themselves participate in ABI, at all, only facilitate type-checking.
+///
+/// E.g. `Client<TokenStream, TokenStream>` is the common proc macro interface,
+/// used for `#[proc_macro] fn foo(input: TokenStream) -> TokenStream`,
+/// indicating that the RPC input and output will be serialized token streams,
+/// and forcing the use of APIs that take/return `S::TokenStream`, server-side.
+#[repr(C)]
+pub struct Client<I, O> {
+    pub(super) handle_counters: &'static HandleCounters,
+
+    pub(super) run: extern "C" fn(BridgeConfig<'_>) -> Buffer,
+
+    pub(super) _marker: PhantomData<fn(I) -> O>,
+}
+
+impl<I, O> Copy for Client<I, O> {}
+impl<I, O> Clone for Client<I, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum ProcMacro {
+    CustomDerive {
+        trait_name: &'static str,
+        attributes: &'static [&'static str],
+        client: Client<TokenStream, TokenStream>,
+    },
+
+    Attr {
+        name: &'static str,
+        client: Client<(TokenStream, TokenStream), TokenStream>,
+    },
+
+    Bang {
+        name: &'static str,
+        client: Client<TokenStream, TokenStream>,
+    },
+}
+
+impl ProcMacro {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcMacro::CustomDerive { trait_name, .. } => trait_name,
+            ProcMacro::Attr { name, .. } => name,
+            ProcMacro::Bang { name, .. } => name,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! lib-proc-macro Symbol encoding.
+//!
+//! Copy from <https://github.com/rust-lang/rust/blob/1.95.0/library/proc_macro/src/bridge/symbol.rs>
+//! augmented with removing unstable features
+//!
+//! Symbols are sent over RPC as strings. The client-side interner is not
+//! needed here, only the server-side encoding of `MarkedSymbol` is kept.
+
+use super::*;
+
+impl<S: server::Server> Decode<'_, '_, server::HandleStore<S>> for server::MarkedSymbol<S> {
+    fn decode(r: &mut &[u8], s: &mut server::HandleStore<S>) -> Self {
+        Mark::mark(S::intern_symbol(<&str>::decode(r, s)))
+    }
+}
+
+impl<S: server::Server> Encode<server::HandleStore<S>> for server::MarkedSymbol<S> {
+    fn encode(self, w: &mut Buffer, s: &mut server::HandleStore<S>) {
+        S::with_symbol_string(&self.unmark(), |sym| sym.encode(w, s))
+    }
+}
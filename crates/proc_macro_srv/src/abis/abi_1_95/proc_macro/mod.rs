@@ -0,0 +1,43 @@
+//! lib-proc-macro main module
+//!
+//! Copy from <https://github.com/rust-lang/rust/blob/1.95.0/library/proc_macro/src/lib.rs>
+//! augmented with removing unstable features
+//!
+//! Only the bridge and the plain enums it sends over RPC are needed on the
+//! server side.
+
+// NOTE: Because we just copy the bridge module from rustc for ABI compatible
+// There are some unused stuffs inside it.
+// We suppress these warning here.
+#[doc(hidden)]
+#[allow(unused_macros)]
+#[allow(unused_variables)]
+pub mod bridge;
+
+/// Describes how a sequence of token trees is delimited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Delimiter {
+    /// `( ... )`
+    Parenthesis,
+    /// `{ ... }`
+    Brace,
+    /// `[ ... ]`
+    Bracket,
+    /// `Ø ... Ø`
+    /// An invisible delimiter, that may, for example, appear around tokens coming from a
+    /// "macro variable" `$var`.
+    None,
+}
+
+/// An enum representing a diagnostic level.
+#[derive(Copy, Clone, Debug)]
+pub enum Level {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// A note.
+    Note,
+    /// A help message.
+    Help,
+}
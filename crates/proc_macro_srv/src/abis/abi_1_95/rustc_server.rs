@@ -0,0 +1,774 @@
+//! Rustc proc-macro server implementation with tt, for the 1.95 bridge
+//!
+//! Based on idea from <https://github.com/fedochet/rust-proc-macro-expander>
+//! The lib-proc-macro server backend is `TokenStream`-agnostic, such that
+//! we could provide any TokenStream implementation.
+//! The original idea from fedochet is using proc-macro2 as backend,
+//! we use tt instead for better integration with RA.
+//!
+//! Compared to the 1.47 bridge, the 1.95 one sends token trees by value and
+//! interns identifiers and literal contents as strings, so there is no need
+//! for server-side `Group`/`Ident`/`Literal` handles.
+//!
+//! Spans are the token ids of the macro input. Their source location comes
+//! with the expansion task, see `ExpansionSpans`.
+
+use std::{cell::RefCell, collections::HashMap, fmt, ops::Bound, ops::Range, rc::Rc, str::FromStr};
+
+use proc_macro_api::{DiagnosticLevel, ExpansionDiagnostic, ExpansionSpans};
+
+use super::proc_macro::{
+    bridge::{self, server, LitKind},
+    Level,
+};
+
+type Span = tt::TokenId;
+type Symbol = tt::SmolStr;
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenStream {
+    pub token_trees: Vec<tt::TokenTree>,
+}
+
+impl TokenStream {
+    pub fn new() -> Self {
+        TokenStream::default()
+    }
+
+    pub fn with_subtree(subtree: tt::Subtree) -> Self {
+        if subtree.delimiter.is_some() {
+            TokenStream { token_trees: vec![tt::TokenTree::Subtree(subtree)] }
+        } else {
+            TokenStream { token_trees: subtree.token_trees }
+        }
+    }
+
+    pub fn into_subtree(self) -> tt::Subtree {
+        tt::Subtree { delimiter: None, token_trees: self.token_trees }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.token_trees.is_empty()
+    }
+
+    fn push_tree(&mut self, tree: tt::TokenTree) {
+        match tree {
+            tt::TokenTree::Subtree(subtree) if subtree.delimiter.is_none() => {
+                self.token_trees.extend(subtree.token_trees);
+            }
+            _ => self.token_trees.push(tree),
+        }
+    }
+}
+
+/// Attempts to break the string into tokens and parse those tokens into a token stream.
+/// All tokens in the parsed stream get `Span::call_site()` spans.
+impl FromStr for TokenStream {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<TokenStream, String> {
+        let (subtree, _token_map) =
+            mbe::parse_to_token_tree(src).ok_or("Failed to parse from mbe")?;
+        Ok(TokenStream::with_subtree(subtree_with_unspecified_ids(subtree)))
+    }
+}
+
+impl fmt::Display for TokenStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_token_trees(f, &self.token_trees)
+    }
+}
+
+fn write_token_trees(f: &mut fmt::Formatter<'_>, tkns: &[tt::TokenTree]) -> fmt::Result {
+    let mut last_to_joint = true;
+    for tkn in tkns {
+        if !last_to_joint {
+            f.write_str(" ")?;
+        }
+        last_to_joint = false;
+        match tkn {
+            tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => f.write_str(&ident.text)?,
+            tt::TokenTree::Leaf(tt::Leaf::Literal(literal)) => f.write_str(&literal.text)?,
+            tt::TokenTree::Leaf(tt::Leaf::Punct(punct)) => {
+                last_to_joint = punct.spacing == tt::Spacing::Joint;
+                write!(f, "{}", punct.char)?;
+            }
+            tt::TokenTree::Subtree(subtree) => {
+                let (open, close) = match subtree.delimiter.map(|it| it.kind) {
+                    None => ("", ""),
+                    Some(tt::DelimiterKind::Brace) => ("{", "}"),
+                    Some(tt::DelimiterKind::Parenthesis) => ("(", ")"),
+                    Some(tt::DelimiterKind::Bracket) => ("[", "]"),
+                };
+                f.write_str(open)?;
+                write_token_trees(f, &subtree.token_trees)?;
+                f.write_str(close)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn subtree_with_unspecified_ids(subtree: tt::Subtree) -> tt::Subtree {
+    tt::Subtree {
+        delimiter: subtree.delimiter.map(|d| tt::Delimiter { id: tt::TokenId::unspecified(), ..d }),
+        token_trees: subtree
+            .token_trees
+            .into_iter()
+            .map(|tt| match tt {
+                tt::TokenTree::Leaf(leaf) => tt::TokenTree::Leaf(match leaf {
+                    tt::Leaf::Literal(lit) => {
+                        tt::Leaf::Literal(tt::Literal { id: tt::TokenId::unspecified(), ..lit })
+                    }
+                    tt::Leaf::Punct(punct) => {
+                        tt::Leaf::Punct(tt::Punct { id: tt::TokenId::unspecified(), ..punct })
+                    }
+                    tt::Leaf::Ident(ident) => {
+                        tt::Leaf::Ident(tt::Ident { id: tt::TokenId::unspecified(), ..ident })
+                    }
+                }),
+                tt::TokenTree::Subtree(subtree) => {
+                    tt::TokenTree::Subtree(subtree_with_unspecified_ids(subtree))
+                }
+            })
+            .collect(),
+    }
+}
+
+type TokenTree = bridge::TokenTree<TokenStream, Span, Symbol>;
+
+/// Converts a token tree sent by the client, `span` maps its spans to token ids.
+fn tree_to_internal(tree: TokenTree, span: impl Fn(Span) -> Span) -> tt::TokenTree {
+    match tree {
+        bridge::TokenTree::Group(group) => {
+            let delimiter = delim_to_internal(group.delimiter, span(group.span.entire));
+            let token_trees = group.stream.map(|it| it.token_trees).unwrap_or_default();
+            tt::TokenTree::Subtree(tt::Subtree { delimiter, token_trees })
+        }
+        bridge::TokenTree::Punct(punct) => tt::TokenTree::Leaf(tt::Leaf::Punct(tt::Punct {
+            char: punct.ch as char,
+            spacing: if punct.joint { tt::Spacing::Joint } else { tt::Spacing::Alone },
+            id: span(punct.span),
+        })),
+        bridge::TokenTree::Ident(ident) => {
+            let text = if ident.is_raw { format!("r#{}", ident.sym).into() } else { ident.sym };
+            tt::TokenTree::Leaf(tt::Leaf::Ident(tt::Ident { text, id: span(ident.span) }))
+        }
+        bridge::TokenTree::Literal(literal) => {
+            let text = literal_to_text(literal.kind, &literal.symbol, literal.suffix.as_deref());
+            tt::TokenTree::Leaf(tt::Leaf::Literal(tt::Literal { text, id: span(literal.span) }))
+        }
+    }
+}
+
+fn tree_to_external(tree: tt::TokenTree) -> TokenTree {
+    match tree {
+        tt::TokenTree::Subtree(subtree) => {
+            let span = subtree.delimiter.map_or_else(tt::TokenId::unspecified, |it| it.id);
+            bridge::TokenTree::Group(bridge::Group {
+                delimiter: delim_to_external(subtree.delimiter),
+                stream: if subtree.token_trees.is_empty() {
+                    None
+                } else {
+                    Some(TokenStream { token_trees: subtree.token_trees })
+                },
+                span: bridge::DelimSpan::from_single(span),
+            })
+        }
+        tt::TokenTree::Leaf(tt::Leaf::Punct(punct)) => bridge::TokenTree::Punct(bridge::Punct {
+            ch: punct.char as u8,
+            joint: punct.spacing == tt::Spacing::Joint,
+            span: punct.id,
+        }),
+        tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => {
+            let (sym, is_raw) = match ident.text.strip_prefix("r#") {
+                Some(sym) => (sym.into(), true),
+                None => (ident.text, false),
+            };
+            bridge::TokenTree::Ident(bridge::Ident { sym, is_raw, span: ident.id })
+        }
+        tt::TokenTree::Leaf(tt::Leaf::Literal(literal)) => {
+            let (kind, symbol, suffix) = literal_from_text(&literal.text);
+            bridge::TokenTree::Literal(bridge::Literal { kind, symbol, suffix, span: literal.id })
+        }
+    }
+}
+
+fn delim_to_internal(d: bridge::Delimiter, id: Span) -> Option<tt::Delimiter> {
+    let kind = match d {
+        bridge::Delimiter::Parenthesis => tt::DelimiterKind::Parenthesis,
+        bridge::Delimiter::Brace => tt::DelimiterKind::Brace,
+        bridge::Delimiter::Bracket => tt::DelimiterKind::Bracket,
+        bridge::Delimiter::None => return None,
+    };
+    Some(tt::Delimiter { id, kind })
+}
+
+fn delim_to_external(d: Option<tt::Delimiter>) -> bridge::Delimiter {
+    match d.map(|it| it.kind) {
+        Some(tt::DelimiterKind::Parenthesis) => bridge::Delimiter::Parenthesis,
+        Some(tt::DelimiterKind::Brace) => bridge::Delimiter::Brace,
+        Some(tt::DelimiterKind::Bracket) => bridge::Delimiter::Bracket,
+        None => bridge::Delimiter::None,
+    }
+}
+
+/// Assembles the source text of a literal from the pieces the client sends.
+fn literal_to_text(kind: LitKind, symbol: &str, suffix: Option<&str>) -> tt::SmolStr {
+    let quoted = |prefix: &str, quote: char, hashes: u8| {
+        let hashes = "#".repeat(hashes as usize);
+        format!("{}{}{}{}{}{}", prefix, hashes, quote, symbol, quote, hashes)
+    };
+    let mut text = match kind {
+        LitKind::Byte => quoted("b", '\'', 0),
+        LitKind::Char => quoted("", '\'', 0),
+        LitKind::Str => quoted("", '"', 0),
+        LitKind::StrRaw(n) => quoted("r", '"', n),
+        LitKind::ByteStr => quoted("b", '"', 0),
+        LitKind::ByteStrRaw(n) => quoted("br", '"', n),
+        LitKind::CStr => quoted("c", '"', 0),
+        LitKind::CStrRaw(n) => quoted("cr", '"', n),
+        LitKind::Integer | LitKind::Float | LitKind::ErrWithGuar => symbol.to_string(),
+    };
+    text.push_str(suffix.unwrap_or_default());
+    text.into()
+}
+
+/// Splits the source text of a literal into its kind, contents and suffix.
+fn literal_from_text(text: &str) -> (LitKind, Symbol, Option<Symbol>) {
+    let (prefix, rest) = match text.find(&['\'', '"', '#'][..]) {
+        Some(idx) if text[..idx].chars().all(|c| c.is_ascii_alphabetic()) => text.split_at(idx),
+        _ => return number_from_text(text),
+    };
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    let quote = match rest[hashes..].chars().next() {
+        Some(quote) => quote,
+        None => return (LitKind::ErrWithGuar, text.into(), None),
+    };
+    let closing = format!("{}{}", quote, "#".repeat(hashes));
+    let contents = &rest[hashes + 1..];
+    let (symbol, suffix) = match contents.rfind(&closing) {
+        Some(end) => (&contents[..end], &contents[end + closing.len()..]),
+        None => return (LitKind::ErrWithGuar, text.into(), None),
+    };
+    let hashes = hashes as u8;
+    let kind = match (prefix, quote) {
+        ("b", '\'') => LitKind::Byte,
+        ("", '\'') => LitKind::Char,
+        ("", '"') => LitKind::Str,
+        ("r", '"') => LitKind::StrRaw(hashes),
+        ("b", '"') => LitKind::ByteStr,
+        ("br", '"') => LitKind::ByteStrRaw(hashes),
+        ("c", '"') => LitKind::CStr,
+        ("cr", '"') => LitKind::CStrRaw(hashes),
+        _ => LitKind::ErrWithGuar,
+    };
+    (kind, symbol.into(), if suffix.is_empty() { None } else { Some(suffix.into()) })
+}
+
+fn number_from_text(text: &str) -> (LitKind, Symbol, Option<Symbol>) {
+    let is_based = text.starts_with("0x") || text.starts_with("0o") || text.starts_with("0b");
+    let suffix_start = if is_based {
+        text[2..].find(&['i', 'u'][..]).map(|idx| idx + 2)
+    } else {
+        text.find(&['i', 'u', 'f'][..])
+    };
+    let (symbol, suffix) = match suffix_start {
+        Some(idx) => (&text[..idx], Some(text[idx..].into())),
+        None => (text, None),
+    };
+    let is_float = !is_based
+        && (symbol.contains(&['.', 'e', 'E'][..])
+            || matches!(suffix.as_deref(), Some("f32") | Some("f64")));
+    let kind = if is_float { LitKind::Float } else { LitKind::Integer };
+    (kind, symbol.into(), suffix)
+}
+
+/// Ids of the spans made up by the macro (with `Span::join`, `Span::start`, ...)
+/// count down from here, far away from the ids of the input tokens.
+const SYNTHETIC_SPANS_START: u32 = u32::MAX - 1;
+
+#[derive(Default)]
+pub struct RustAnalyzer {
+    file: String,
+    /// Source text of the macro call, empty if we don't know where it is. All
+    /// positions we hand out (byte ranges, lines and columns) are relative to
+    /// its start: the client doesn't send where the call is in the file, for
+    /// edits elsewhere not to re-run the macro.
+    text: String,
+    /// Ranges of the input tokens in `text`.
+    tokens: HashMap<Span, Range<u32>>,
+    /// Ranges of the spans made up by the macro, with the input token they
+    /// derive from, which is what ends up in the expansion.
+    synthetic: Vec<(Range<u32>, Span)>,
+    saved: Vec<Span>,
+    /// Diagnostics emitted by the macro, shared with the caller as the bridge
+    /// consumes the server.
+    diagnostics: Rc<RefCell<Vec<ExpansionDiagnostic>>>,
+}
+
+impl RustAnalyzer {
+    pub(crate) fn new(spans: &ExpansionSpans) -> RustAnalyzer {
+        RustAnalyzer {
+            file: spans.file.clone(),
+            text: spans.text.clone(),
+            tokens: spans
+                .tokens
+                .iter()
+                .map(|&(id, (start, end))| (tt::TokenId(id), start..end))
+                .collect(),
+            ..RustAnalyzer::default()
+        }
+    }
+
+    pub(crate) fn diagnostics(&self) -> Rc<RefCell<Vec<ExpansionDiagnostic>>> {
+        self.diagnostics.clone()
+    }
+
+    fn diagnostic_to_external(&self, diagnostic: bridge::Diagnostic<Span>) -> ExpansionDiagnostic {
+        ExpansionDiagnostic {
+            level: match diagnostic.level {
+                Level::Error => DiagnosticLevel::Error,
+                Level::Warning => DiagnosticLevel::Warning,
+                Level::Note => DiagnosticLevel::Note,
+                Level::Help => DiagnosticLevel::Help,
+            },
+            message: diagnostic.message,
+            spans: diagnostic
+                .spans
+                .into_iter()
+                .map(|span| self.origin(span))
+                .filter(|&span| span != tt::TokenId::unspecified())
+                .map(|span| span.0)
+                .collect(),
+            children: diagnostic
+                .children
+                .into_iter()
+                .map(|child| self.diagnostic_to_external(child))
+                .collect(),
+        }
+    }
+
+    /// Returns the range of `span` in the text of the macro call. Spans which
+    /// are not from the input, like `Span::call_site()`, cover the whole call.
+    fn range(&self, span: Span) -> Option<Range<u32>> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let range = match self.synthetic(span) {
+            Some((range, _)) => range.clone(),
+            None => match self.tokens.get(&span) {
+                Some(range) => range.clone(),
+                None => 0..self.text.len() as u32,
+            },
+        };
+        Some(range)
+    }
+
+    fn synthetic(&self, span: Span) -> Option<&(Range<u32>, Span)> {
+        let idx = SYNTHETIC_SPANS_START.checked_sub(span.0)?;
+        self.synthetic.get(idx as usize)
+    }
+
+    /// Returns the input token `span` derives from.
+    fn origin(&self, span: Span) -> Span {
+        match self.synthetic(span) {
+            Some(&(_, origin)) => origin,
+            None => span,
+        }
+    }
+
+    fn make_span(&mut self, range: Range<u32>, from: Span) -> Span {
+        let origin = self.origin(from);
+        self.synthetic.push((range, origin));
+        tt::TokenId(SYNTHETIC_SPANS_START - (self.synthetic.len() as u32 - 1))
+    }
+
+    /// Returns the 1-based line and 0-based column of `offset` in the text of
+    /// the macro call.
+    fn line_column(&self, offset: u32) -> (usize, usize) {
+        let before = &self.text[..offset as usize];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |idx| idx + 1)..].chars().count();
+        (line, column)
+    }
+}
+
+impl server::Server for RustAnalyzer {
+    type TokenStream = TokenStream;
+    type Span = Span;
+    type Symbol = Symbol;
+
+    fn globals(&mut self) -> bridge::ExpnGlobals<Self::Span> {
+        // The unspecified id stands for the whole macro call, see `RustAnalyzer::range`.
+        // FIXME: there is no hygiene, so the three of them are the same
+        bridge::ExpnGlobals {
+            def_site: tt::TokenId::unspecified(),
+            call_site: tt::TokenId::unspecified(),
+            mixed_site: tt::TokenId::unspecified(),
+        }
+    }
+
+    fn intern_symbol(ident: &str) -> Self::Symbol {
+        ident.into()
+    }
+
+    fn with_symbol_string(symbol: &Self::Symbol, f: impl FnOnce(&str)) {
+        f(symbol)
+    }
+
+    fn injected_env_var(&mut self, _var: &str) -> Option<String> {
+        // The env vars of the expansion task are set on the server process itself.
+        None
+    }
+
+    fn track_env_var(&mut self, _var: &str, _value: Option<&str>) {
+        // FIXME: track env var accesses
+        // https://github.com/rust-lang/rust/pull/71858
+    }
+
+    fn track_path(&mut self, _path: &str) {}
+
+    fn literal_from_str(
+        &mut self,
+        s: &str,
+    ) -> Result<bridge::Literal<Self::Span, Self::Symbol>, String> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, s),
+        };
+        let literal = match TokenStream::from_str(unsigned)?.token_trees.as_slice() {
+            [tt::TokenTree::Leaf(tt::Leaf::Literal(literal))] => literal.clone(),
+            _ => return Err(format!("not a literal: {}", s)),
+        };
+        let (kind, symbol, suffix) = literal_from_text(&literal.text);
+        let symbol = if negative { format!("-{}", symbol).into() } else { symbol };
+        Ok(bridge::Literal { kind, symbol, suffix, span: tt::TokenId::unspecified() })
+    }
+
+    fn emit_diagnostic(&mut self, diagnostic: bridge::Diagnostic<Self::Span>) {
+        let diagnostic = self.diagnostic_to_external(diagnostic);
+        self.diagnostics.borrow_mut().push(diagnostic);
+    }
+
+    fn ts_drop(&mut self, stream: Self::TokenStream) {
+        drop(stream);
+    }
+
+    fn ts_clone(&mut self, stream: &Self::TokenStream) -> Self::TokenStream {
+        stream.clone()
+    }
+
+    fn ts_is_empty(&mut self, stream: &Self::TokenStream) -> bool {
+        stream.is_empty()
+    }
+
+    fn ts_expand_expr(&mut self, stream: &Self::TokenStream) -> Result<Self::TokenStream, ()> {
+        // FIXME: expand macros in the expression
+        Ok(stream.clone())
+    }
+
+    fn ts_from_str(&mut self, src: &str) -> Result<Self::TokenStream, String> {
+        TokenStream::from_str(src)
+    }
+
+    fn ts_to_string(&mut self, stream: &Self::TokenStream) -> String {
+        stream.to_string()
+    }
+
+    fn ts_from_token_tree(&mut self, tree: TokenTree) -> Self::TokenStream {
+        let mut stream = TokenStream::new();
+        stream.push_tree(tree_to_internal(tree, |span| self.origin(span)));
+        stream
+    }
+
+    fn ts_concat_trees(
+        &mut self,
+        base: Option<Self::TokenStream>,
+        trees: Vec<TokenTree>,
+    ) -> Self::TokenStream {
+        let mut stream = base.unwrap_or_default();
+        trees
+            .into_iter()
+            .for_each(|tree| stream.push_tree(tree_to_internal(tree, |span| self.origin(span))));
+        stream
+    }
+
+    fn ts_concat_streams(
+        &mut self,
+        base: Option<Self::TokenStream>,
+        streams: Vec<Self::TokenStream>,
+    ) -> Self::TokenStream {
+        let mut stream = base.unwrap_or_default();
+        streams.into_iter().flat_map(|it| it.token_trees).for_each(|tree| stream.push_tree(tree));
+        stream
+    }
+
+    fn ts_into_trees(&mut self, stream: Self::TokenStream) -> Vec<TokenTree> {
+        stream.token_trees.into_iter().map(tree_to_external).collect()
+    }
+
+    fn span_debug(&mut self, span: Self::Span) -> String {
+        match self.range(span) {
+            Some(range) => {
+                format!("#{} bytes({}..{})", span.0, range.start, range.end)
+            }
+            None => format!("{:?}", span.0),
+        }
+    }
+
+    fn span_parent(&mut self, _span: Self::Span) -> Option<Self::Span> {
+        None
+    }
+
+    fn span_source(&mut self, span: Self::Span) -> Self::Span {
+        span
+    }
+
+    fn span_byte_range(&mut self, span: Self::Span) -> Range<usize> {
+        match self.range(span) {
+            Some(range) => range.start as usize..range.end as usize,
+            None => 0..0,
+        }
+    }
+
+    fn span_start(&mut self, span: Self::Span) -> Self::Span {
+        match self.range(span) {
+            Some(range) => self.make_span(range.start..range.start, span),
+            None => span,
+        }
+    }
+
+    fn span_end(&mut self, span: Self::Span) -> Self::Span {
+        match self.range(span) {
+            Some(range) => self.make_span(range.end..range.end, span),
+            None => span,
+        }
+    }
+
+    fn span_line(&mut self, span: Self::Span) -> usize {
+        self.range(span).map_or(1, |range| self.line_column(range.start).0)
+    }
+
+    fn span_column(&mut self, span: Self::Span) -> usize {
+        self.range(span).map_or(1, |range| self.line_column(range.start).1)
+    }
+
+    fn span_file(&mut self, _span: Self::Span) -> String {
+        self.file.clone()
+    }
+
+    fn span_local_file(&mut self, _span: Self::Span) -> Option<String> {
+        if self.file.is_empty() {
+            None
+        } else {
+            Some(self.file.clone())
+        }
+    }
+
+    fn span_join(&mut self, first: Self::Span, second: Self::Span) -> Option<Self::Span> {
+        let (first_range, second_range) = (self.range(first)?, self.range(second)?);
+        let range =
+            first_range.start.min(second_range.start)..first_range.end.max(second_range.end);
+        Some(self.make_span(range, first))
+    }
+
+    fn span_subspan(
+        &mut self,
+        span: Self::Span,
+        start: Bound<usize>,
+        end: Bound<usize>,
+    ) -> Option<Self::Span> {
+        let range = self.range(span)?;
+        let len = (range.end - range.start) as usize;
+        let start = match start {
+            Bound::Included(it) => it,
+            Bound::Excluded(it) => it.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match end {
+            Bound::Included(it) => it.checked_add(1)?,
+            Bound::Excluded(it) => it,
+            Bound::Unbounded => len,
+        };
+        if start > end || end > len {
+            return None;
+        }
+        Some(self.make_span(range.start + start as u32..range.start + end as u32, span))
+    }
+
+    fn span_resolved_at(&mut self, span: Self::Span, _at: Self::Span) -> Self::Span {
+        // FIXME: handle hygiene
+        span
+    }
+
+    fn span_source_text(&mut self, span: Self::Span) -> Option<String> {
+        let range = self.range(span)?;
+        self.text.get(range.start as usize..range.end as usize).map(ToString::to_string)
+    }
+
+    fn span_save_span(&mut self, span: Self::Span) -> usize {
+        self.saved.push(span);
+        self.saved.len() - 1
+    }
+
+    fn span_recover_proc_macro_span(&mut self, id: usize) -> Self::Span {
+        self.saved.get(id).copied().unwrap_or_else(tt::TokenId::unspecified)
+    }
+
+    fn symbol_normalize_and_validate_ident(&mut self, string: &str) -> Result<Self::Symbol, ()> {
+        let mut chars = string.chars();
+        match chars.next() {
+            Some(c) if c == '_' || c.is_alphabetic() => {}
+            _ => return Err(()),
+        }
+        if chars.all(|c| c == '_' || c.is_alphanumeric()) {
+            Ok(string.into())
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rustc_server_literals() {
+        let mut srv = RustAnalyzer::default();
+        let check = |srv: &mut RustAnalyzer, text: &str, kind, symbol: &str, suffix| {
+            let literal = server::Server::literal_from_str(srv, text).unwrap();
+            assert_eq!(literal.kind, kind, "{}", text);
+            assert_eq!(literal.symbol, symbol, "{}", text);
+            assert_eq!(literal.suffix.as_deref(), suffix, "{}", text);
+            let text_back = literal_to_text(kind, &literal.symbol, suffix);
+            assert_eq!(text_back, text, "{}", text);
+        };
+
+        check(&mut srv, "1234", LitKind::Integer, "1234", None);
+        check(&mut srv, "12u8", LitKind::Integer, "12", Some("u8"));
+        check(&mut srv, "-1i64", LitKind::Integer, "-1", Some("i64"));
+        check(&mut srv, "0xffu32", LitKind::Integer, "0xff", Some("u32"));
+        check(&mut srv, "1.5", LitKind::Float, "1.5", None);
+        check(&mut srv, "1e10", LitKind::Float, "1e10", None);
+        check(&mut srv, "2f32", LitKind::Float, "2", Some("f32"));
+        check(&mut srv, "'c'", LitKind::Char, "c", None);
+        check(&mut srv, "b'c'", LitKind::Byte, "c", None);
+        check(&mut srv, "\"hello\"", LitKind::Str, "hello", None);
+        check(&mut srv, "r#\"a\"b\"#", LitKind::StrRaw(1), "a\"b", None);
+        check(&mut srv, "b\"1234586\\x88\"", LitKind::ByteStr, "1234586\\x88", None);
+        check(&mut srv, "br\"x\"", LitKind::ByteStrRaw(0), "x", None);
+
+        assert!(server::Server::literal_from_str(&mut srv, "ident").is_err());
+    }
+
+    #[test]
+    fn test_rustc_server_to_string() {
+        let s = TokenStream {
+            token_trees: vec![
+                tt::TokenTree::Leaf(tt::Leaf::Ident(tt::Ident {
+                    text: "struct".into(),
+                    id: tt::TokenId::unspecified(),
+                })),
+                tt::TokenTree::Leaf(tt::Leaf::Ident(tt::Ident {
+                    text: "T".into(),
+                    id: tt::TokenId::unspecified(),
+                })),
+                tt::TokenTree::Subtree(tt::Subtree {
+                    delimiter: Some(tt::Delimiter {
+                        id: tt::TokenId::unspecified(),
+                        kind: tt::DelimiterKind::Brace,
+                    }),
+                    token_trees: vec![],
+                }),
+            ],
+        };
+
+        assert_eq!(s.to_string(), "struct T {}");
+    }
+
+    #[test]
+    fn test_rustc_server_spans() {
+        // `foo(a,\n  bc)`, positions are relative to the `(`.
+        let spans = ExpansionSpans {
+            file: "/src/lib.rs".into(),
+            text: "(a,\n  bc)".into(),
+            tokens: vec![(0, (0, 9)), (1, (1, 2)), (2, (2, 3)), (3, (6, 8))],
+        };
+        let mut srv = RustAnalyzer::new(&spans);
+        let (a, bc) = (tt::TokenId(1), tt::TokenId(3));
+
+        assert_eq!(server::Server::span_byte_range(&mut srv, bc), 6..8);
+        assert_eq!(server::Server::span_line(&mut srv, bc), 2);
+        assert_eq!(server::Server::span_column(&mut srv, bc), 2);
+        assert_eq!(server::Server::span_local_file(&mut srv, bc).as_deref(), Some("/src/lib.rs"));
+
+        let joined = server::Server::span_join(&mut srv, a, bc).unwrap();
+        assert_eq!(server::Server::span_source_text(&mut srv, joined).unwrap(), "a,\n  bc");
+        let end = server::Server::span_end(&mut srv, joined);
+        assert_eq!(server::Server::span_column(&mut srv, end), 4);
+        let c = server::Server::span_subspan(&mut srv, bc, Bound::Included(1), Bound::Unbounded);
+        assert_eq!(server::Server::span_source_text(&mut srv, c.unwrap()).unwrap(), "c");
+
+        let call_site = server::Server::globals(&mut srv).call_site;
+        assert_eq!(server::Server::span_source_text(&mut srv, call_site).unwrap(), "(a,\n  bc)");
+
+        // Made up spans are replaced with the input token they come from.
+        let tree = bridge::TokenTree::Punct(bridge::Punct { ch: b';', joint: false, span: end });
+        let stream = server::Server::ts_from_token_tree(&mut srv, tree);
+        assert_eq!(format!("{:?}", stream.into_subtree()), "SUBTREE $\n  PUNCH   ; [alone] 1");
+    }
+
+    #[test]
+    fn test_rustc_server_diagnostics() {
+        let spans = ExpansionSpans {
+            text: "(a, bc)".into(),
+            tokens: vec![(0, (0, 7)), (1, (1, 2)), (2, (2, 3)), (3, (4, 6))],
+            ..ExpansionSpans::default()
+        };
+        let mut srv = RustAnalyzer::new(&spans);
+        let bc = tt::TokenId(3);
+        let c = server::Server::span_subspan(&mut srv, bc, Bound::Included(1), Bound::Unbounded);
+        let call_site = server::Server::globals(&mut srv).call_site;
+        let note = bridge::Diagnostic {
+            level: Level::Note,
+            message: "note".into(),
+            spans: vec![call_site],
+            children: vec![],
+        };
+        let diagnostic = bridge::Diagnostic {
+            level: Level::Warning,
+            message: "warning".into(),
+            spans: vec![c.unwrap()],
+            children: vec![note],
+        };
+        server::Server::emit_diagnostic(&mut srv, diagnostic);
+
+        // Spans map back to input tokens, the call site is not a token of its own.
+        let emitted = srv.diagnostics().take();
+        assert_eq!(
+            emitted,
+            vec![ExpansionDiagnostic {
+                level: DiagnosticLevel::Warning,
+                message: "warning".into(),
+                spans: vec![3],
+                children: vec![ExpansionDiagnostic {
+                    level: DiagnosticLevel::Note,
+                    message: "note".into(),
+                    spans: vec![],
+                    children: vec![],
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rustc_server_trees_roundtrip() {
+        let mut srv = RustAnalyzer::default();
+        let stream = TokenStream::from_str("r#fn (a, 'b', \"c\") -> [1u8; 2]").unwrap();
+        let trees = server::Server::ts_into_trees(&mut srv, stream.clone());
+        let back = server::Server::ts_concat_trees(&mut srv, None, trees);
+        assert_eq!(back.token_trees, stream.token_trees);
+    }
+}
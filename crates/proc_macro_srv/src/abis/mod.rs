@@ -0,0 +1,105 @@
+//! Procedural macros are implemented by compiling the macro providing crate
+//! to a dynamic library with a particular ABI which the compiler uses to expand
+//! macros. Unfortunately this ABI is not specified and can change from version
+//! to version of the compiler. To support this we copy the ABI from the rust
+//! compiler into submodules of this module (e.g proc_macro_srv::abis::abi_1_47).
+//!
+//! All of these ABIs are subsumed in the `Abi` enum, which exposes a simple
+//! interface the rest of rust analyzer can use to interact with the macro
+//! library.
+//!
+//! Each ABI is selected from the rustc version recorded in the `.rustc` section
+//! of the dylib, see `Abi::from_lib`.
+//!
+//! # Adding a new ABI
+//!
+//! To add a new ABI you'll need to copy the source of the target proc_macro
+//! crate from the source tree of the Rust compiler into this directory tree.
+//! Then you'll need to modify it
+//! - Remove any feature! or other things which won't compile on stable
+//! - change any absolute imports to relative imports within the ABI tree
+//!
+//! Then you'll need to add a branch to the `Abi` enum and an implementation of
+//! `Abi::expand`, `Abi::list_macros` and `Abi::from_lib` for the new ABI. See
+//! `proc_macro_srv/src/abis/abi_1_47/mod.rs` for an example. Finally you'll
+//! need to update the conditionals in `Abi::from_lib` to return your new ABI
+//! for the relevant versions of the rust compiler.
+
+mod abi_1_47;
+mod abi_1_95;
+
+use std::io;
+
+use libloading::Library;
+use proc_macro_api::{ExpansionDiagnostic, ExpansionSpans, ProcMacroKind, RustCInfo};
+
+#[cfg(test)]
+pub(crate) use abi_1_95::TokenStream;
+
+#[derive(Debug)]
+pub struct PanicMessage {
+    message: Option<String>,
+}
+
+impl PanicMessage {
+    pub fn as_str(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+pub(crate) enum Abi {
+    Abi1_47(abi_1_47::Abi),
+    Abi1_95(abi_1_95::Abi),
+}
+
+impl Abi {
+    /// Load a new ABI.
+    ///
+    /// # Arguments
+    ///
+    /// * `lib` - The dynamic library containing the macro implementations
+    /// * `symbol_name` - The symbol name under which the macros are exported
+    /// * `info` - RustCInfo about the compiler that was used to compile the
+    ///   macro crate. This is the information we use to figure out which ABI
+    ///   to return
+    pub(crate) fn from_lib(lib: &Library, symbol_name: String, info: RustCInfo) -> io::Result<Abi> {
+        match (info.version.0, info.version.1) {
+            (1, 47..=54) => {
+                let inner = unsafe { abi_1_47::Abi::from_lib(lib, symbol_name) }?;
+                Ok(Abi::Abi1_47(inner))
+            }
+            (1, 95) => {
+                let inner = unsafe { abi_1_95::Abi::from_lib(lib, symbol_name) }?;
+                Ok(Abi::Abi1_95(inner))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "proc-macro dylib was built by rustc {}.{}.{}, whose proc-macro ABI \
+                     is not supported (supported: 1.47 to 1.54, 1.95)",
+                    info.version.0, info.version.1, info.version.2
+                ),
+            )),
+        }
+    }
+
+    pub fn expand(
+        &self,
+        macro_name: &str,
+        macro_body: &tt::Subtree,
+        attributes: Option<&tt::Subtree>,
+        spans: &ExpansionSpans,
+    ) -> Result<(tt::Subtree, Vec<ExpansionDiagnostic>), PanicMessage> {
+        match self {
+            Self::Abi1_47(abi) => abi.expand(macro_name, macro_body, attributes, spans),
+            Self::Abi1_95(abi) => abi.expand(macro_name, macro_body, attributes, spans),
+        }
+    }
+
+    pub fn list_macros(&self) -> Vec<(String, ProcMacroKind)> {
+        match self {
+            Self::Abi1_47(abi) => abi.list_macros(),
+            Self::Abi1_95(abi) => abi.list_macros(),
+        }
+    }
+}
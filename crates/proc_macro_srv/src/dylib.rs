@@ -9,9 +9,9 @@ use std::{
 use libloading::Library;
 use memmap2::Mmap;
 use object::Object;
-use proc_macro_api::ProcMacroKind;
+use proc_macro_api::{read_dylib_info, ExpansionDiagnostic, ExpansionSpans, ProcMacroKind};
 
-use crate::{proc_macro::bridge, rustc_server::TokenStream};
+use crate::abis::{Abi, PanicMessage};
 
 const NEW_REGISTRAR_SYMBOL: &str = "_rustc_proc_macro_decls_";
 
@@ -75,9 +75,9 @@ fn load_library(file: &Path) -> Result<Library, libloading::Error> {
 }
 
 struct ProcMacroLibraryLibloading {
-    // Hold the dylib to prevent it from unloading
+    // Hold on to the library so it doesn't unload
     _lib: Library,
-    exported_macros: Vec<bridge::client::ProcMacro>,
+    abi: Abi,
 }
 
 impl ProcMacroLibraryLibloading {
@@ -86,14 +86,11 @@ impl ProcMacroLibraryLibloading {
             invalid_data_err(format!("Cannot find registrar symbol in file {}", file.display()))
         })?;
 
-        let lib = load_library(file).map_err(invalid_data_err)?;
-        let exported_macros = {
-            let macros: libloading::Symbol<&&[bridge::client::ProcMacro]> =
-                unsafe { lib.get(symbol_name.as_bytes()) }.map_err(invalid_data_err)?;
-            macros.to_vec()
-        };
+        let version_info = read_dylib_info(file)?;
 
-        Ok(ProcMacroLibraryLibloading { _lib: lib, exported_macros })
+        let lib = load_library(file).map_err(invalid_data_err)?;
+        let abi = Abi::from_lib(&lib, symbol_name, version_info)?;
+        Ok(ProcMacroLibraryLibloading { _lib: lib, abi })
     }
 }
 
@@ -119,69 +116,13 @@ impl Expander {
         macro_name: &str,
         macro_body: &tt::Subtree,
         attributes: Option<&tt::Subtree>,
-    ) -> Result<tt::Subtree, bridge::PanicMessage> {
-        let parsed_body = TokenStream::with_subtree(macro_body.clone());
-
-        let parsed_attributes = attributes
-            .map_or(crate::rustc_server::TokenStream::new(), |attr| {
-                TokenStream::with_subtree(attr.clone())
-            });
-
-        for proc_macro in &self.inner.exported_macros {
-            match proc_macro {
-                bridge::client::ProcMacro::CustomDerive { trait_name, client, .. }
-                    if *trait_name == macro_name =>
-                {
-                    let res = client.run(
-                        &crate::proc_macro::bridge::server::SameThread,
-                        crate::rustc_server::Rustc::default(),
-                        parsed_body,
-                        false,
-                    );
-                    return res.map(|it| it.into_subtree());
-                }
-                bridge::client::ProcMacro::Bang { name, client } if *name == macro_name => {
-                    let res = client.run(
-                        &crate::proc_macro::bridge::server::SameThread,
-                        crate::rustc_server::Rustc::default(),
-                        parsed_body,
-                        false,
-                    );
-                    return res.map(|it| it.into_subtree());
-                }
-                bridge::client::ProcMacro::Attr { name, client } if *name == macro_name => {
-                    let res = client.run(
-                        &crate::proc_macro::bridge::server::SameThread,
-                        crate::rustc_server::Rustc::default(),
-                        parsed_attributes,
-                        parsed_body,
-                        false,
-                    );
-                    return res.map(|it| it.into_subtree());
-                }
-                _ => continue,
-            }
-        }
-
-        Err(bridge::PanicMessage::String("Nothing to expand".to_string()))
+        spans: &ExpansionSpans,
+    ) -> Result<(tt::Subtree, Vec<ExpansionDiagnostic>), PanicMessage> {
+        self.inner.abi.expand(macro_name, macro_body, attributes, spans)
     }
 
     pub fn list_macros(&self) -> Vec<(String, ProcMacroKind)> {
-        self.inner
-            .exported_macros
-            .iter()
-            .map(|proc_macro| match proc_macro {
-                bridge::client::ProcMacro::CustomDerive { trait_name, .. } => {
-                    (trait_name.to_string(), ProcMacroKind::CustomDerive)
-                }
-                bridge::client::ProcMacro::Bang { name, .. } => {
-                    (name.to_string(), ProcMacroKind::FuncLike)
-                }
-                bridge::client::ProcMacro::Attr { name, .. } => {
-                    (name.to_string(), ProcMacroKind::Attr)
-                }
-            })
-            .collect()
+        self.inner.abi.list_macros()
     }
 }
 
@@ -8,19 +8,14 @@
 //! * We use `tt` for proc-macro `TokenStream` server, it is easier to manipulate and interact with
 //!   RA than `proc-macro2` token stream.
 //! * By **copying** the whole rustc `lib_proc_macro` code, we are able to build this with `stable`
-//!   rustc rather than `unstable`. (Although in general ABI compatibility is still an issue)…
+//!   rustc rather than `unstable`. We keep one copy per supported bridge ABI, and pick one
+//!   according to the rustc version a proc-macro dylib was built with (see the `abis` module).
 #![allow(unreachable_pub)]
 
-#[allow(dead_code)]
-#[doc(hidden)]
-mod proc_macro;
-
-#[doc(hidden)]
-mod rustc_server;
-
 mod dylib;
 
-use proc_macro::bridge::client::TokenStream;
+mod abis;
+
 use proc_macro_api::{ExpansionResult, ExpansionTask, ListMacrosResult, ListMacrosTask};
 use std::{
     collections::{hash_map::Entry, HashMap},
@@ -44,7 +39,12 @@ impl ProcMacroSrv {
             env::set_var(k, v);
         }
 
-        let result = expander.expand(&task.macro_name, &task.macro_body, task.attributes.as_ref());
+        let result = expander.expand(
+            &task.macro_name,
+            &task.macro_body,
+            task.attributes.as_ref(),
+            &task.spans,
+        );
 
         for (k, _) in &task.env {
             match &prev_env[k.as_str()] {
@@ -54,7 +54,7 @@ impl ProcMacroSrv {
         }
 
         match result {
-            Ok(expansion) => Ok(ExpansionResult { expansion }),
+            Ok((expansion, diagnostics)) => Ok(ExpansionResult { expansion, diagnostics }),
             Err(msg) => {
                 let msg = msg.as_str().unwrap_or("<unknown error>");
                 Err(format!("proc-macro panicked: {}", msg))
@@ -55,6 +55,24 @@ fn test_fn_like_macro2() {
     );
 }
 
+#[test]
+fn test_fn_like_macro_spans() {
+    assert_expand_with_spans(
+        "fn_like_span_info",
+        "(ident,\n    [1])",
+        expect![[r#"
+            SUBTREE $
+              LITERAL "ident /src/lib.rs:1:1-1:6 Some(\"ident\")" 4294967295
+              PUNCH   , [alone] 4294967295
+              LITERAL ", /src/lib.rs:1:6-1:7 Some(\",\")" 4294967295
+              PUNCH   , [alone] 4294967295
+              LITERAL "[1] /src/lib.rs:2:4-2:7 Some(\"[1]\")" 4294967295
+              PUNCH   , [alone] 4294967295
+              LITERAL "call_site /src/lib.rs:1:0-2:8 Some(\"(ident,\\n    [1])\")" 4294967295
+              PUNCH   , [alone] 4294967295"#]],
+    );
+}
+
 #[test]
 fn test_attr_macro() {
     // Corresponds to
@@ -84,6 +102,7 @@ fn list_test_macros() {
         fn_like_panic [FuncLike]
         fn_like_error [FuncLike]
         fn_like_clone_tokens [FuncLike]
+        fn_like_span_info [FuncLike]
         attr_noop [Attr]
         attr_panic [Attr]
         attr_error [Attr]
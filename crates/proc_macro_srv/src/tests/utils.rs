@@ -3,7 +3,7 @@
 use crate::dylib;
 use crate::ProcMacroSrv;
 use expect_test::Expect;
-use proc_macro_api::ListMacrosTask;
+use proc_macro_api::{ExpansionSpans, ListMacrosTask};
 use std::str::FromStr;
 
 pub mod fixtures {
@@ -12,8 +12,8 @@ pub mod fixtures {
     }
 }
 
-fn parse_string(code: &str) -> Option<crate::rustc_server::TokenStream> {
-    Some(crate::rustc_server::TokenStream::from_str(code).unwrap())
+fn parse_string(code: &str) -> Option<crate::abis::TokenStream> {
+    Some(crate::abis::TokenStream::from_str(code).unwrap())
 }
 
 pub fn assert_expand(macro_name: &str, ra_fixture: &str, expect: Expect) {
@@ -30,7 +30,30 @@ fn assert_expand_impl(macro_name: &str, input: &str, attr: Option<&str>, expect:
     let fixture = parse_string(input).unwrap();
     let attr = attr.map(|attr| parse_string(attr).unwrap().into_subtree());
 
-    let res = expander.expand(macro_name, &fixture.into_subtree(), attr.as_ref()).unwrap();
+    let (res, _) = expander
+        .expand(macro_name, &fixture.into_subtree(), attr.as_ref(), &ExpansionSpans::default())
+        .unwrap();
+    expect.assert_eq(&format!("{:?}", res));
+}
+
+/// Expands a function-like macro as if it was called in `/src/lib.rs`. Span
+/// locations are relative to the start of `args` (delimiters included).
+pub fn assert_expand_with_spans(macro_name: &str, args: &str, expect: Expect) {
+    let path = fixtures::proc_macro_test_dylib_path();
+    let expander = dylib::Expander::new(&path).unwrap();
+    let (mut subtree, token_map) = mbe::parse_to_token_tree(args).unwrap();
+    // Proc macros get their arguments without the delimiters.
+    subtree.delimiter = None;
+    let spans = ExpansionSpans {
+        file: "/src/lib.rs".into(),
+        text: args.into(),
+        tokens: token_map
+            .entries()
+            .map(|(id, range)| (id.0, (range.start().into(), range.end().into())))
+            .collect(),
+    };
+
+    let (res, _) = expander.expand(macro_name, &subtree, None, &spans).unwrap();
     expect.assert_eq(&format!("{:?}", res));
 }
 
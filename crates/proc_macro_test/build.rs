@@ -16,7 +16,6 @@ fn main() {
     let out_dir = Path::new(&out_dir);
 
     let name = "proc_macro_test_impl";
-    let version = "0.0.0";
     let target_dir = out_dir.join("target");
     let output = Command::new(toolchain::cargo())
         .current_dir("imp")
@@ -35,11 +34,10 @@ fn main() {
     for message in Message::parse_stream(output.stdout.as_slice()) {
         match message.unwrap() {
             Message::CompilerArtifact(artifact) => {
-                if artifact.target.kind.contains(&"proc-macro".to_string()) {
-                    let repr = format!("{} {}", name, version);
-                    if artifact.package_id.repr.starts_with(&repr) {
-                        artifact_path = Some(PathBuf::from(&artifact.filenames[0]));
-                    }
+                if artifact.target.kind.contains(&"proc-macro".to_string())
+                    && artifact.target.name == name
+                {
+                    artifact_path = Some(PathBuf::from(&artifact.filenames[0]));
                 }
             }
             _ => (), // Unknown message
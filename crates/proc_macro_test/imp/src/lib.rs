@@ -1,6 +1,6 @@
 //! Exports a few trivial procedural macros for testing.
 
-use proc_macro::{Group, Ident, Punct, TokenStream, TokenTree};
+use proc_macro::{Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 
 #[proc_macro]
 pub fn fn_like_noop(args: TokenStream) -> TokenStream {
@@ -22,6 +22,30 @@ pub fn fn_like_clone_tokens(args: TokenStream) -> TokenStream {
     clone_stream(args)
 }
 
+#[proc_macro]
+pub fn fn_like_span_info(args: TokenStream) -> TokenStream {
+    let spans = args.into_iter().map(|tt| (tt.to_string(), tt.span()));
+    let spans = spans.chain(Some(("call_site".to_string(), Span::call_site())));
+    spans
+        .flat_map(|(name, span)| {
+            let info = format!(
+                "{} {}:{}:{}-{}:{} {:?}",
+                name,
+                span.file(),
+                span.start().line(),
+                span.start().column(),
+                span.end().line(),
+                span.end().column(),
+                span.source_text(),
+            );
+            vec![
+                TokenTree::Literal(Literal::string(&info)),
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            ]
+        })
+        .collect()
+}
+
 #[proc_macro_attribute]
 pub fn attr_noop(_args: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -291,7 +291,9 @@ fn inject_cargo_env(package: &cargo_metadata::Package, build_data: &mut PackageB
     let env = &mut build_data.envs;
 
     // FIXME: Missing variables:
-    // CARGO_PKG_HOMEPAGE, CARGO_CRATE_NAME, CARGO_BIN_NAME, CARGO_BIN_EXE_<name>
+    // CARGO_PKG_HOMEPAGE, CARGO_BIN_NAME, CARGO_BIN_EXE_<name>
+    //
+    // `CARGO_CRATE_NAME` depends on the target and is set in `add_target_crate_root`.
 
     let mut manifest_dir = package.manifest_path.clone();
     manifest_dir.pop();
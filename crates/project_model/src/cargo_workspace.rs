@@ -2,7 +2,7 @@
 
 use std::iter;
 use std::path::PathBuf;
-use std::{convert::TryInto, ops, process::Command, sync::Arc};
+use std::{convert::TryInto, fs, ops, process::Command, sync::Arc};
 
 use anyhow::{Context, Result};
 use base_db::Edition;
@@ -32,6 +32,11 @@ pub struct CargoWorkspace {
     targets: Arena<TargetData>,
     workspace_root: AbsPathBuf,
     build_data_config: BuildDataConfig,
+    /// The target triple the workspace is built for, `None` if it could not be
+    /// determined.
+    target: Option<String>,
+    /// The triple of the machine running build scripts and proc macros.
+    host: Option<String>,
 }
 
 impl ops::Index<Package> for CargoWorkspace {
@@ -124,6 +129,9 @@ pub struct PackageData {
     pub id: String,
     // The contents of [package.metadata.rust-analyzer]
     pub metadata: RustAnalyzerPackageMetaData,
+    /// The `forced-target` of the package, which it is always built for
+    /// regardless of the workspace target.
+    pub forced_target: Option<String>,
 }
 
 #[derive(Deserialize, Default, Debug, Clone, Eq, PartialEq)]
@@ -251,15 +259,18 @@ impl CargoWorkspace {
         if let Some(parent) = cargo_toml.parent() {
             meta.current_dir(parent.to_path_buf());
         }
+        let host = rustc_discover_host_triple(cargo_toml);
         let target = if let Some(target) = &config.target {
             Some(target.clone())
         } else if let stdout @ Some(_) = cargo_config_build_target(cargo_toml) {
             stdout
         } else {
-            rustc_discover_host_triple(cargo_toml)
+            host.clone()
         };
-        if let Some(target) = target {
-            meta.other_options(vec![String::from("--filter-platform"), target]);
+        // FIXME: dependencies of packages with a `forced-target` are filtered for
+        // the workspace target as well.
+        if let Some(target) = &target {
+            meta.other_options(vec![String::from("--filter-platform"), target.clone()]);
         }
 
         // FIXME: Currently MetadataCommand is not based on parse_stream,
@@ -283,6 +294,17 @@ impl CargoWorkspace {
             )
         })?;
 
+        CargoWorkspace::new(cargo_toml, config, meta, target, host)
+    }
+
+    /// Builds the workspace from the output of `cargo metadata`.
+    pub(crate) fn new(
+        cargo_toml: &AbsPath,
+        config: &CargoConfig,
+        mut meta: cargo_metadata::Metadata,
+        target: Option<String>,
+        host: Option<String>,
+    ) -> Result<CargoWorkspace> {
         let mut pkg_by_id = FxHashMap::default();
         let mut packages = Arena::default();
         let mut targets = Arena::default();
@@ -292,19 +314,33 @@ impl CargoWorkspace {
         meta.packages.sort_by(|a, b| a.id.cmp(&b.id));
         for meta_pkg in &meta.packages {
             let cargo_metadata::Package {
-                id, edition, name, manifest_path, version, metadata, ..
+                id,
+                edition,
+                name,
+                manifest_path,
+                version,
+                metadata,
+                source,
+                ..
             } = meta_pkg;
             let meta = from_value::<PackageMetadata>(metadata.clone()).unwrap_or_default();
             let is_member = ws_members.contains(id);
             let edition = edition
                 .parse::<Edition>()
                 .with_context(|| format!("Failed to parse edition {}", edition))?;
+            let manifest = AbsPathBuf::assert(PathBuf::from(&manifest_path));
+            // `cargo metadata` doesn't report the `forced-target`, and the
+            // unstable feature can't be used by published packages anyway.
+            let forced_target = match source {
+                Some(_) => None,
+                None => manifest_forced_target(&manifest),
+            };
 
             let pkg = packages.alloc(PackageData {
                 id: id.repr.clone(),
                 name: name.clone(),
                 version: version.to_string(),
-                manifest: AbsPathBuf::assert(PathBuf::from(&manifest_path)),
+                manifest,
                 targets: Vec::new(),
                 is_member,
                 edition,
@@ -312,6 +348,7 @@ impl CargoWorkspace {
                 features: meta_pkg.features.clone().into_iter().collect(),
                 active_features: Vec::new(),
                 metadata: meta.rust_analyzer.unwrap_or_default(),
+                forced_target,
             });
             let pkg_data = &mut packages[pkg];
             pkg_by_id.insert(id, pkg);
@@ -366,7 +403,7 @@ impl CargoWorkspace {
         let build_data_config =
             BuildDataConfig::new(cargo_toml.to_path_buf(), config.clone(), Arc::new(meta.packages));
 
-        Ok(CargoWorkspace { packages, targets, workspace_root, build_data_config })
+        Ok(CargoWorkspace { packages, targets, workspace_root, build_data_config, target, host })
     }
 
     pub fn packages<'a>(&'a self) -> impl Iterator<Item = Package> + ExactSizeIterator + 'a {
@@ -384,6 +421,16 @@ impl CargoWorkspace {
         &self.workspace_root
     }
 
+    /// The target triple the workspace is analysed for.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// The triple of the machine build scripts and proc macros run on.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
     pub fn package_flag(&self, package: &PackageData) -> String {
         if self.is_unique(&*package.name) {
             package.name.clone()
@@ -440,3 +487,73 @@ fn cargo_config_build_target(cargo_toml: &AbsPath) -> Option<String> {
         Err(_) => None,
     }
 }
+
+/// Reads `package.forced-target` from a `Cargo.toml`.
+fn manifest_forced_target(manifest: &AbsPath) -> Option<String> {
+    let text = fs::read_to_string(manifest).ok()?;
+    match parse_forced_target(&text) {
+        Ok(it) => it,
+        Err(e) => {
+            log::warn!("failed to parse {}: {}", manifest.display(), e);
+            None
+        }
+    }
+}
+
+fn parse_forced_target(manifest: &str) -> Result<Option<String>, toml::de::Error> {
+    #[derive(Deserialize)]
+    struct Manifest {
+        package: Option<ManifestPackage>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    struct ManifestPackage {
+        forced_target: Option<String>,
+    }
+
+    let manifest: Manifest = toml::from_str(manifest)?;
+    Ok(manifest.package.and_then(|it| it.forced_target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_forced_target() {
+        let manifest = r#"
+[package]
+name = "firmware"
+version = "0.1.0"
+forced-target = "thumbv7em-none-eabihf"
+
+[dependencies]
+forced-target = "1.0"
+"#;
+        assert_eq!(
+            parse_forced_target(manifest).unwrap().as_deref(),
+            Some("thumbv7em-none-eabihf")
+        );
+    }
+
+    #[test]
+    fn reads_forced_target_in_any_form() {
+        let manifest = r#"
+package = { name = "firmware", version = "0.1.0", 'forced-target' = "wasm32-wasi" } # comment
+"#;
+        assert_eq!(parse_forced_target(manifest).unwrap().as_deref(), Some("wasm32-wasi"));
+    }
+
+    #[test]
+    fn no_forced_target() {
+        let manifest = r#"
+[workspace]
+members = ["forced-target"]
+
+[target.'cfg(unix)'.package]
+forced-target = "wasm32-wasi"
+"#;
+        assert_eq!(parse_forced_target(manifest).unwrap(), None);
+        assert!(parse_forced_target("[package\n").is_err());
+    }
+}
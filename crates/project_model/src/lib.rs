@@ -21,6 +21,7 @@ mod project_json;
 mod sysroot;
 mod workspace;
 mod rustc_cfg;
+mod target_data_layout;
 mod build_data;
 
 use std::{
@@ -44,7 +45,7 @@ pub use crate::{
     workspace::{CfgOverrides, PackageRoot, ProjectWorkspace},
 };
 
-pub use proc_macro_api::ProcMacroClient;
+pub use proc_macro_api::{ProcMacroClient, DEFAULT_EXPANSION_TIMEOUT};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub enum ProjectManifest {
@@ -0,0 +1,37 @@
+//! Runs `rustc --print target-spec-json` to get the target's data layout.
+
+use std::{process::Command, sync::Arc};
+
+use anyhow::{format_err, Result};
+use paths::AbsPath;
+
+use crate::utf8_stdout;
+
+pub(crate) fn get(cargo_toml: Option<&AbsPath>, target: Option<&str>) -> Option<Arc<str>> {
+    let _p = profile::span("target_data_layout::get");
+    match get_data_layout(cargo_toml, target) {
+        Ok(it) => Some(it.into()),
+        Err(e) => {
+            log::error!("failed to get target data layout: {:#}", e);
+            None
+        }
+    }
+}
+
+fn get_data_layout(cargo_toml: Option<&AbsPath>, target: Option<&str>) -> Result<String> {
+    let mut cmd = Command::new(toolchain::rustc());
+    // Run in the workspace, so that its `rust-toolchain` file is respected.
+    if let Some(cargo_toml) = cargo_toml {
+        cmd.current_dir(cargo_toml.parent().unwrap());
+    }
+    cmd.args(["-Z", "unstable-options", "--print", "target-spec-json"]).env("RUSTC_BOOTSTRAP", "1");
+    if let Some(target) = target {
+        cmd.args(["--target", target]);
+    }
+    let stdout = utf8_stdout(cmd)?;
+    let spec: serde_json::Value = serde_json::from_str(&stdout)?;
+    spec.get("data-layout")
+        .and_then(|it| it.as_str())
+        .map(ToString::to_string)
+        .ok_or_else(|| format_err!("target spec has no data layout"))
+}
@@ -2,7 +2,7 @@
 //! metadata` or `rust-project.json`) into representation stored in the salsa
 //! database -- `CrateGraph`.
 
-use std::{collections::VecDeque, fmt, fs, path::Path, process::Command};
+use std::{collections::VecDeque, fmt, fs, path::Path, process::Command, sync::Arc};
 
 use anyhow::{format_err, Context, Result};
 use base_db::{CrateDisplayName, CrateGraph, CrateId, CrateName, Edition, Env, FileId, ProcMacro};
@@ -18,8 +18,8 @@ use crate::{
     cfg_flag::CfgFlag,
     rustc_cfg,
     sysroot::SysrootCrate,
-    utf8_stdout, BuildDataCollector, CargoConfig, CargoWorkspace, ProjectJson, ProjectManifest,
-    Sysroot, TargetKind,
+    target_data_layout, utf8_stdout, BuildDataCollector, CargoConfig, CargoWorkspace, Package,
+    ProjectJson, ProjectManifest, Sysroot, TargetKind,
 };
 
 pub type CfgOverrides = FxHashMap<String, CfgDiff>;
@@ -42,16 +42,24 @@ pub enum ProjectWorkspace {
         cargo: CargoWorkspace,
         sysroot: Sysroot,
         rustc: Option<CargoWorkspace>,
-        /// Holds cfg flags for the current target. We get those by running
-        /// `rustc --print cfg`.
-        ///
-        /// FIXME: make this a per-crate map, as, eg, build.rs might have a
-        /// different target.
+        /// Holds cfg flags for the target of the workspace. We get those by
+        /// running `rustc --print cfg`.
         rustc_cfg: Vec<CfgFlag>,
+        /// The data layout of the workspace target, from `rustc --print target-spec-json`.
+        target_layout: Option<Arc<str>>,
+        /// The cfg flags and data layouts of the other targets crates are built
+        /// for: the host, for build scripts and proc macros, and the
+        /// `forced-target`s of packages. Keyed by target triple.
+        platform_cfgs: FxHashMap<String, (Vec<CfgFlag>, Option<Arc<str>>)>,
         cfg_overrides: CfgOverrides,
     },
     /// Project workspace was manually specified using a `rust-project.json` file.
-    Json { project: ProjectJson, sysroot: Option<Sysroot>, rustc_cfg: Vec<CfgFlag> },
+    Json {
+        project: ProjectJson,
+        sysroot: Option<Sysroot>,
+        rustc_cfg: Vec<CfgFlag>,
+        target_layout: Option<Arc<str>>,
+    },
 
     // FIXME: The primary limitation of this approach is that the set of detached files needs to be fixed at the beginning.
     // That's not the end user experience we should strive for.
@@ -63,14 +71,19 @@ pub enum ProjectWorkspace {
     // //
     /// Project with a set of disjoint files, not belonging to any particular workspace.
     /// Backed by basic sysroot crates for basic completion and highlighting.
-    DetachedFiles { files: Vec<AbsPathBuf>, sysroot: Sysroot, rustc_cfg: Vec<CfgFlag> },
+    DetachedFiles {
+        files: Vec<AbsPathBuf>,
+        sysroot: Sysroot,
+        rustc_cfg: Vec<CfgFlag>,
+        target_layout: Option<Arc<str>>,
+    },
 }
 
 impl fmt::Debug for ProjectWorkspace {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Make sure this isn't too verbose.
         match self {
-            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides } => f
+            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides, .. } => f
                 .debug_struct("Cargo")
                 .field("root", &cargo.workspace_root().file_name())
                 .field("n_packages", &cargo.packages().len())
@@ -82,7 +95,7 @@ impl fmt::Debug for ProjectWorkspace {
                 .field("n_rustc_cfg", &rustc_cfg.len())
                 .field("n_cfg_overrides", &cfg_overrides.len())
                 .finish(),
-            ProjectWorkspace::Json { project, sysroot, rustc_cfg } => {
+            ProjectWorkspace::Json { project, sysroot, rustc_cfg, .. } => {
                 let mut debug_struct = f.debug_struct("Json");
                 debug_struct.field("n_crates", &project.n_crates());
                 if let Some(sysroot) = sysroot {
@@ -91,7 +104,7 @@ impl fmt::Debug for ProjectWorkspace {
                 debug_struct.field("n_rustc_cfg", &rustc_cfg.len());
                 debug_struct.finish()
             }
-            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg } => f
+            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg, .. } => f
                 .debug_struct("DetachedFiles")
                 .field("n_files", &files.len())
                 .field("n_sysroot_crates", &sysroot.crates().len())
@@ -168,9 +181,20 @@ impl ProjectWorkspace {
                 };
 
                 let rustc_cfg = rustc_cfg::get(Some(&cargo_toml), config.target.as_deref());
+                let target_layout =
+                    target_data_layout::get(Some(&cargo_toml), config.target.as_deref());
+                let platform_cfgs = platform_cfgs(&cargo_toml, &cargo);
 
                 let cfg_overrides = config.cfg_overrides();
-                ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides }
+                ProjectWorkspace::Cargo {
+                    cargo,
+                    sysroot,
+                    rustc,
+                    rustc_cfg,
+                    target_layout,
+                    platform_cfgs,
+                    cfg_overrides,
+                }
             }
         };
 
@@ -186,7 +210,8 @@ impl ProjectWorkspace {
             None => None,
         };
         let rustc_cfg = rustc_cfg::get(None, target);
-        Ok(ProjectWorkspace::Json { project: project_json, sysroot, rustc_cfg })
+        let target_layout = target_data_layout::get(None, target);
+        Ok(ProjectWorkspace::Json { project: project_json, sysroot, rustc_cfg, target_layout })
     }
 
     pub fn load_detached_files(detached_files: Vec<AbsPathBuf>) -> Result<ProjectWorkspace> {
@@ -194,7 +219,13 @@ impl ProjectWorkspace {
             detached_files.first().ok_or_else(|| format_err!("No detached files to load"))?,
         )?;
         let rustc_cfg = rustc_cfg::get(None, None);
-        Ok(ProjectWorkspace::DetachedFiles { files: detached_files, sysroot, rustc_cfg })
+        let target_layout = target_data_layout::get(None, None);
+        Ok(ProjectWorkspace::DetachedFiles {
+            files: detached_files,
+            sysroot,
+            rustc_cfg,
+            target_layout,
+        })
     }
 
     /// Returns the roots for the current `ProjectWorkspace`
@@ -202,7 +233,7 @@ impl ProjectWorkspace {
     /// the root is a member of the current workspace
     pub fn to_roots(&self, build_data: Option<&BuildDataResult>) -> Vec<PackageRoot> {
         match self {
-            ProjectWorkspace::Json { project, sysroot, rustc_cfg: _ } => project
+            ProjectWorkspace::Json { project, sysroot, .. } => project
                 .crates()
                 .map(|(_, krate)| PackageRoot {
                     is_member: krate.is_workspace_member,
@@ -219,7 +250,7 @@ impl ProjectWorkspace {
                     })
                 }))
                 .collect::<Vec<_>>(),
-            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg: _, cfg_overrides: _ } => {
+            ProjectWorkspace::Cargo { cargo, sysroot, rustc, .. } => {
                 cargo
                     .packages()
                     .map(|pkg| {
@@ -317,32 +348,46 @@ impl ProjectWorkspace {
         };
 
         let mut crate_graph = match self {
-            ProjectWorkspace::Json { project, sysroot, rustc_cfg } => project_json_to_crate_graph(
+            ProjectWorkspace::Json { project, sysroot, rustc_cfg, target_layout } => {
+                project_json_to_crate_graph(
+                    rustc_cfg.clone(),
+                    target_layout.clone(),
+                    &proc_macro_loader,
+                    load,
+                    project,
+                    sysroot,
+                )
+            }
+            ProjectWorkspace::Cargo {
+                cargo,
+                sysroot,
+                rustc,
+                rustc_cfg,
+                target_layout,
+                platform_cfgs,
+                cfg_overrides,
+            } => cargo_to_crate_graph(
                 rustc_cfg.clone(),
+                target_layout.clone(),
+                platform_cfgs,
+                cfg_overrides,
                 &proc_macro_loader,
                 load,
-                project,
+                cargo,
+                build_data.and_then(|it| it.get(cargo.workspace_root())),
                 sysroot,
+                rustc,
+                rustc.as_ref().zip(build_data).and_then(|(it, map)| map.get(it.workspace_root())),
             ),
-            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides } => {
-                cargo_to_crate_graph(
+            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg, target_layout } => {
+                detached_files_to_crate_graph(
                     rustc_cfg.clone(),
-                    cfg_overrides,
-                    &proc_macro_loader,
+                    target_layout.clone(),
                     load,
-                    cargo,
-                    build_data.and_then(|it| it.get(cargo.workspace_root())),
+                    files,
                     sysroot,
-                    rustc,
-                    rustc
-                        .as_ref()
-                        .zip(build_data)
-                        .and_then(|(it, map)| map.get(it.workspace_root())),
                 )
             }
-            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg } => {
-                detached_files_to_crate_graph(rustc_cfg.clone(), load, files, sysroot)
-            }
         };
         if crate_graph.patch_cfg_if() {
             log::debug!("Patched std to depend on cfg-if")
@@ -364,17 +409,25 @@ impl ProjectWorkspace {
 
 fn project_json_to_crate_graph(
     rustc_cfg: Vec<CfgFlag>,
+    target_layout: Option<Arc<str>>,
     proc_macro_loader: &dyn Fn(&Path) -> Vec<ProcMacro>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
     project: &ProjectJson,
     sysroot: &Option<Sysroot>,
 ) -> CrateGraph {
     let mut crate_graph = CrateGraph::default();
-    let sysroot_deps = sysroot
-        .as_ref()
-        .map(|sysroot| sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), load));
+    let sysroot_deps = sysroot.as_ref().map(|sysroot| {
+        sysroot_to_crate_graph(
+            &mut crate_graph,
+            sysroot,
+            rustc_cfg.clone(),
+            target_layout.clone(),
+            load,
+        )
+    });
 
     let mut cfg_cache: FxHashMap<&str, Vec<CfgFlag>> = FxHashMap::default();
+    let mut layout_cache: FxHashMap<&str, Option<Arc<str>>> = FxHashMap::default();
     let crates: FxHashMap<CrateId, CrateId> = project
         .crates()
         .filter_map(|(crate_id, krate)| {
@@ -392,6 +445,13 @@ fn project_json_to_crate_graph(
                 }
                 None => &rustc_cfg,
             };
+            let target_layout = match krate.target.as_deref() {
+                Some(target) => layout_cache
+                    .entry(target)
+                    .or_insert_with(|| target_data_layout::get(None, Some(target)))
+                    .clone(),
+                None => target_layout.clone(),
+            };
 
             let mut cfg_options = CfgOptions::default();
             cfg_options.extend(target_cfgs.iter().chain(krate.cfg.iter()).cloned());
@@ -405,6 +465,7 @@ fn project_json_to_crate_graph(
                     cfg_options,
                     env,
                     proc_macro.unwrap_or_default(),
+                    target_layout,
                 ),
             )
         })
@@ -428,8 +489,30 @@ fn project_json_to_crate_graph(
     crate_graph
 }
 
+/// Queries the cfgs and data layouts of the targets other than the workspace
+/// one which crates of `cargo` are built for, the same way as the ones of the
+/// workspace target.
+fn platform_cfgs(
+    cargo_toml: &AbsPath,
+    cargo: &CargoWorkspace,
+) -> FxHashMap<String, (Vec<CfgFlag>, Option<Arc<str>>)> {
+    let mut res = FxHashMap::default();
+    let forced_targets = cargo.packages().filter_map(|pkg| cargo[pkg].forced_target.as_deref());
+    for triple in cargo.host().into_iter().chain(forced_targets) {
+        if to_platform(cargo, Some(triple)).is_none() || res.contains_key(triple) {
+            continue;
+        }
+        let rustc_cfg = rustc_cfg::get(Some(cargo_toml), Some(triple));
+        let target_layout = target_data_layout::get(Some(cargo_toml), Some(triple));
+        res.insert(triple.to_string(), (rustc_cfg, target_layout));
+    }
+    res
+}
+
 fn cargo_to_crate_graph(
     rustc_cfg: Vec<CfgFlag>,
+    target_layout: Option<Arc<str>>,
+    platform_cfgs: &FxHashMap<String, (Vec<CfgFlag>, Option<Arc<str>>)>,
     override_cfg: &CfgOverrides,
     proc_macro_loader: &dyn Fn(&Path) -> Vec<ProcMacro>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
@@ -441,8 +524,13 @@ fn cargo_to_crate_graph(
 ) -> CrateGraph {
     let _p = profile::span("cargo_to_crate_graph");
     let mut crate_graph = CrateGraph::default();
-    let (public_deps, libproc_macro) =
-        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), load);
+    let (public_deps, libproc_macro) = sysroot_to_crate_graph(
+        &mut crate_graph,
+        sysroot,
+        rustc_cfg.clone(),
+        target_layout.clone(),
+        load,
+    );
 
     let mut cfg_options = CfgOptions::default();
     cfg_options.extend(rustc_cfg);
@@ -453,42 +541,84 @@ fn cargo_to_crate_graph(
     cfg_options.insert_atom("test".into());
     cfg_options.insert_atom("debug_assertions".into());
 
+    // Crates built for another target than the workspace one (build scripts, proc macros and
+    // their dependencies, which run on the host, and packages with a `forced-target`) need the
+    // cfgs of that target.
+    let mut platform_cache: FxHashMap<Platform, (CfgOptions, Option<Arc<str>>)> =
+        FxHashMap::default();
+    platform_cache.insert(None, (cfg_options.clone(), target_layout.clone()));
+    let mut platform_cfg = |platform| match platform_cache.get(&platform) {
+        Some(it) => it.clone(),
+        None => {
+            let (rustc_cfg, target_layout) = &platform_cfgs[platform.unwrap()];
+            let mut cfg_options = CfgOptions::default();
+            cfg_options.extend(rustc_cfg.iter().cloned());
+            cfg_options.insert_atom("test".into());
+            cfg_options.insert_atom("debug_assertions".into());
+            let res = (cfg_options, target_layout.clone());
+            platform_cache.insert(platform, res.clone());
+            res
+        }
+    };
+
+    let host = to_platform(cargo, cargo.host());
+    let pkg_platforms = package_platforms(cargo, host);
+
+    // Crates of each package, for the platform a package is analysed for by default.
     let mut pkg_crates = FxHashMap::default();
+    // Crates of each package and platform.
+    let mut platform_crates: FxHashMap<(Package, Platform), Vec<(CrateId, TargetKind)>> =
+        FxHashMap::default();
+    let mut platform_lib_crates = FxHashMap::default();
     // Does any crate signal to rust-analyzer that they need the rustc_private crates?
     let mut has_private = false;
     // Next, create crates for each package, target pair
     for pkg in cargo.packages() {
-        let mut cfg_options = &cfg_options;
-        let mut replaced_cfg_options;
-        if let Some(overrides) = override_cfg.get(&cargo[pkg].name) {
-            // FIXME: this is sort of a hack to deal with #![cfg(not(test))] vanishing such as seen
-            // in ed25519_dalek (#7243), and libcore (#9203) (although you only hit that one while
-            // working on rust-lang/rust as that's the only time it appears outside sysroot).
-            //
-            // A more ideal solution might be to reanalyze crates based on where the cursor is and
-            // figure out the set of cfgs that would have to apply to make it active.
-
-            replaced_cfg_options = cfg_options.clone();
-            replaced_cfg_options.apply_diff(overrides.clone());
-            cfg_options = &replaced_cfg_options;
-        };
-
         has_private |= cargo[pkg].metadata.rustc_private;
-        let mut lib_tgt = None;
-        for &tgt in cargo[pkg].targets.iter() {
-            if let Some(file_id) = load(&cargo[tgt].root) {
+        let platforms = &pkg_platforms[&pkg];
+        for &platform in platforms {
+            // Other platforms than the first one only need the library, for the packages that
+            // depend on it.
+            let is_primary = platform == platforms[0];
+            let mut lib_tgt = None;
+            for &tgt in cargo[pkg].targets.iter() {
+                let kind = cargo[tgt].kind;
+                let tgt_platform = match kind {
+                    TargetKind::BuildScript if is_primary => host,
+                    TargetKind::Lib => platform,
+                    _ if is_primary => platform,
+                    _ => continue,
+                };
+                let file_id = match load(&cargo[tgt].root) {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let (mut cfg_options, target_layout) = platform_cfg(tgt_platform);
+                if let Some(overrides) = override_cfg.get(&cargo[pkg].name) {
+                    // FIXME: this is sort of a hack to deal with #![cfg(not(test))] vanishing such as seen
+                    // in ed25519_dalek (#7243), and libcore (#9203) (although you only hit that one while
+                    // working on rust-lang/rust as that's the only time it appears outside sysroot).
+                    //
+                    // A more ideal solution might be to reanalyze crates based on where the cursor is and
+                    // figure out the set of cfgs that would have to apply to make it active.
+                    cfg_options.apply_diff(overrides.clone());
+                };
                 let crate_id = add_target_crate_root(
                     &mut crate_graph,
                     &cargo[pkg],
                     build_data_map.and_then(|it| it.get(&cargo[pkg].id)),
                     &cfg_options,
+                    target_layout,
                     proc_macro_loader,
                     file_id,
                     &cargo[tgt].name,
                 );
-                if cargo[tgt].kind == TargetKind::Lib {
+                if kind == TargetKind::Lib {
                     lib_tgt = Some((crate_id, cargo[tgt].name.clone()));
-                    pkg_to_lib_crate.insert(pkg, crate_id);
+                    platform_lib_crates.insert((pkg, platform), crate_id);
+                    if is_primary {
+                        pkg_to_lib_crate.insert(pkg, crate_id);
+                    }
                 }
                 if cargo[tgt].is_proc_macro {
                     if let Some(proc_macro) = libproc_macro {
@@ -501,25 +631,28 @@ fn cargo_to_crate_graph(
                     }
                 }
 
-                pkg_crates.entry(pkg).or_insert_with(Vec::new).push((crate_id, cargo[tgt].kind));
+                platform_crates.entry((pkg, platform)).or_default().push((crate_id, kind));
+                if is_primary {
+                    pkg_crates.entry(pkg).or_insert_with(Vec::new).push((crate_id, kind));
+                }
             }
-        }
 
-        // Set deps to the core, std and to the lib target of the current package
-        for (from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
-            if let Some((to, name)) = lib_tgt.clone() {
-                if to != *from && *kind != TargetKind::BuildScript {
-                    // (build script can not depend on its library target)
-
-                    // For root projects with dashes in their name,
-                    // cargo metadata does not do any normalization,
-                    // so we do it ourselves currently
-                    let name = CrateName::normalize_dashes(&name);
-                    add_dep(&mut crate_graph, *from, name, to);
+            // Set deps to the core, std and to the lib target of the current package
+            for (from, kind) in platform_crates.get(&(pkg, platform)).into_iter().flatten() {
+                if let Some((to, name)) = lib_tgt.clone() {
+                    if to != *from && *kind != TargetKind::BuildScript {
+                        // (build script can not depend on its library target)
+
+                        // For root projects with dashes in their name,
+                        // cargo metadata does not do any normalization,
+                        // so we do it ourselves currently
+                        let name = CrateName::normalize_dashes(&name);
+                        add_dep(&mut crate_graph, *from, name, to);
+                    }
+                }
+                for (name, krate) in public_deps.iter() {
+                    add_dep(&mut crate_graph, *from, name.clone(), *krate);
                 }
-            }
-            for (name, krate) in public_deps.iter() {
-                add_dep(&mut crate_graph, *from, name.clone(), *krate);
             }
         }
     }
@@ -527,10 +660,10 @@ fn cargo_to_crate_graph(
     // Now add a dep edge from all targets of upstream to the lib
     // target of downstream.
     for pkg in cargo.packages() {
-        for dep in cargo[pkg].dependencies.iter() {
-            let name = CrateName::new(&dep.name).unwrap();
-            if let Some(&to) = pkg_to_lib_crate.get(&dep.pkg) {
-                for (from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
+        for &platform in &pkg_platforms[&pkg] {
+            for dep in cargo[pkg].dependencies.iter() {
+                let name = CrateName::new(&dep.name).unwrap();
+                for (from, kind) in platform_crates.get(&(pkg, platform)).into_iter().flatten() {
                     if dep.kind == DepKind::Build && *kind != TargetKind::BuildScript {
                         // Only build scripts may depend on build dependencies.
                         continue;
@@ -539,8 +672,13 @@ fn cargo_to_crate_graph(
                         // Build scripts may only depend on build dependencies.
                         continue;
                     }
-
-                    add_dep(&mut crate_graph, *from, name.clone(), to)
+                    let dep_platform = match kind {
+                        TargetKind::BuildScript => dependency_platform(cargo, dep.pkg, host, host),
+                        _ => dependency_platform(cargo, dep.pkg, platform, host),
+                    };
+                    if let Some(&to) = platform_lib_crates.get(&(dep.pkg, dep_platform)) {
+                        add_dep(&mut crate_graph, *from, name.clone(), to)
+                    }
                 }
             }
         }
@@ -556,6 +694,7 @@ fn cargo_to_crate_graph(
                 &mut crate_graph,
                 rustc_build_data_map,
                 &cfg_options,
+                target_layout,
                 proc_macro_loader,
                 &mut pkg_to_lib_crate,
                 &public_deps,
@@ -567,16 +706,90 @@ fn cargo_to_crate_graph(
     crate_graph
 }
 
+/// The target triple a crate is analysed for, `None` standing for the target of
+/// the workspace.
+type Platform<'a> = Option<&'a str>;
+
+fn to_platform<'a>(cargo: &'a CargoWorkspace, triple: Option<&'a str>) -> Platform<'a> {
+    triple.filter(|&it| Some(it) != cargo.target())
+}
+
+/// Returns the platform `pkg` is built for when a crate for `platform` depends
+/// on it.
+fn dependency_platform<'a>(
+    cargo: &'a CargoWorkspace,
+    pkg: Package,
+    platform: Platform<'a>,
+    host: Platform<'a>,
+) -> Platform<'a> {
+    if cargo[pkg].targets.iter().any(|&tgt| cargo[tgt].is_proc_macro) {
+        host
+    } else if let Some(forced_target) = &cargo[pkg].forced_target {
+        to_platform(cargo, Some(forced_target))
+    } else {
+        platform
+    }
+}
+
+/// Works out which platforms each package has to be analysed for, walking the
+/// dependency graph from the workspace members. The first platform of a package
+/// is the one all of its targets are analysed for, the others only need its
+/// library.
+fn package_platforms<'a>(
+    cargo: &'a CargoWorkspace,
+    host: Platform<'a>,
+) -> FxHashMap<Package, Vec<Platform<'a>>> {
+    let mut res: FxHashMap<Package, Vec<Platform<'a>>> = FxHashMap::default();
+    let mut queue: VecDeque<_> = cargo
+        .packages()
+        .filter(|&pkg| cargo[pkg].is_member)
+        .map(|pkg| (pkg, dependency_platform(cargo, pkg, None, host)))
+        .collect();
+    while let Some((pkg, platform)) = queue.pop_front() {
+        let platforms = res.entry(pkg).or_default();
+        if platforms.contains(&platform) {
+            continue;
+        }
+        platforms.push(platform);
+        for dep in cargo[pkg].dependencies.iter() {
+            let dep_platform = match dep.kind {
+                DepKind::Build => dependency_platform(cargo, dep.pkg, host, host),
+                DepKind::Normal | DepKind::Dev => {
+                    dependency_platform(cargo, dep.pkg, platform, host)
+                }
+            };
+            queue.push_back((dep.pkg, dep_platform));
+        }
+    }
+    for pkg in cargo.packages() {
+        let platforms = res.entry(pkg).or_default();
+        let own_platform = dependency_platform(cargo, pkg, None, host);
+        match platforms.iter().position(|&it| it == own_platform) {
+            Some(idx) => platforms.swap(0, idx),
+            // Packages the members don't depend on are analysed for their own platform.
+            None if platforms.is_empty() => platforms.push(own_platform),
+            None => (),
+        }
+    }
+    res
+}
+
 fn detached_files_to_crate_graph(
     rustc_cfg: Vec<CfgFlag>,
+    target_layout: Option<Arc<str>>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
     detached_files: &[AbsPathBuf],
     sysroot: &Sysroot,
 ) -> CrateGraph {
     let _p = profile::span("detached_files_to_crate_graph");
     let mut crate_graph = CrateGraph::default();
-    let (public_deps, _libproc_macro) =
-        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), load);
+    let (public_deps, _libproc_macro) = sysroot_to_crate_graph(
+        &mut crate_graph,
+        sysroot,
+        rustc_cfg.clone(),
+        target_layout.clone(),
+        load,
+    );
 
     let mut cfg_options = CfgOptions::default();
     cfg_options.extend(rustc_cfg);
@@ -601,6 +814,7 @@ fn detached_files_to_crate_graph(
             cfg_options.clone(),
             Env::default(),
             Vec::new(),
+            target_layout.clone(),
         );
 
         for (name, krate) in public_deps.iter() {
@@ -616,6 +830,7 @@ fn handle_rustc_crates(
     crate_graph: &mut CrateGraph,
     rustc_build_data_map: Option<&WorkspaceBuildData>,
     cfg_options: &CfgOptions,
+    target_layout: Option<Arc<str>>,
     proc_macro_loader: &dyn Fn(&Path) -> Vec<ProcMacro>,
     pkg_to_lib_crate: &mut FxHashMap<la_arena::Idx<crate::PackageData>, CrateId>,
     public_deps: &[(CrateName, CrateId)],
@@ -652,6 +867,7 @@ fn handle_rustc_crates(
                         &rustc_workspace[pkg],
                         rustc_build_data_map.and_then(|it| it.get(&rustc_workspace[pkg].id)),
                         cfg_options,
+                        target_layout.clone(),
                         proc_macro_loader,
                         file_id,
                         &rustc_workspace[tgt].name,
@@ -708,6 +924,7 @@ fn add_target_crate_root(
     pkg: &cargo_workspace::PackageData,
     build_data: Option<&PackageBuildData>,
     cfg_options: &CfgOptions,
+    target_layout: Option<Arc<str>>,
     proc_macro_loader: &dyn Fn(&Path) -> Vec<ProcMacro>,
     file_id: FileId,
     cargo_name: &str,
@@ -730,6 +947,7 @@ fn add_target_crate_root(
             env.set(k, v.clone());
         }
     }
+    env.set("CARGO_CRATE_NAME", cargo_name.replace('-', "_"));
 
     let proc_macro = build_data
         .as_ref()
@@ -753,6 +971,7 @@ fn add_target_crate_root(
         potential_cfg_options,
         env,
         proc_macro,
+        target_layout,
     );
 
     crate_id
@@ -762,6 +981,7 @@ fn sysroot_to_crate_graph(
     crate_graph: &mut CrateGraph,
     sysroot: &Sysroot,
     rustc_cfg: Vec<CfgFlag>,
+    target_layout: Option<Arc<str>>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
 ) -> (Vec<(CrateName, CrateId)>, Option<CrateId>) {
     let _p = profile::span("sysroot_to_crate_graph");
@@ -783,6 +1003,7 @@ fn sysroot_to_crate_graph(
                 cfg_options.clone(),
                 env,
                 proc_macro,
+                target_layout.clone(),
             );
             Some((krate, crate_id))
         })
@@ -811,3 +1032,133 @@ fn add_dep(graph: &mut CrateGraph, from: CrateId, name: CrateName, to: CrateId)
         log::error!("{}", err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+
+    const TARGET: &str = "wasm32-unknown-unknown";
+    const HOST: &str = "x86_64-unknown-linux-gnu";
+    const FORCED_TARGET: &str = "thumbv7em-none-eabihf";
+
+    /// A workspace of `app`, whose build script and proc macro `macros` depend on
+    /// `shared` like its library does, and of `firmware` with a `forced-target`.
+    fn workspace() -> CargoWorkspace {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data");
+        let root = root.to_str().unwrap();
+        let package = |name: &str, source: Option<&str>, kind: &str| {
+            json!({
+                "name": name,
+                "version": "0.1.0",
+                "id": name,
+                "source": source,
+                "dependencies": [],
+                "targets": [{
+                    "name": name,
+                    "kind": [kind],
+                    "src_path": format!("{}/{}/src/lib.rs", root, name),
+                }],
+                "features": {},
+                "manifest_path": format!("{}/{}/Cargo.toml", root, name),
+            })
+        };
+        let registry = Some("registry+https://github.com/rust-lang/crates.io-index");
+        let mut app = package("app", None, "lib");
+        app["targets"].as_array_mut().unwrap().push(json!({
+            "name": "build-script-build",
+            "kind": ["custom-build"],
+            "src_path": format!("{}/app/build.rs", root),
+        }));
+        let dep = |name: &str, kinds: &[Option<&str>]| {
+            let kinds: Vec<_> = kinds.iter().map(|kind| json!({ "kind": kind })).collect();
+            json!({ "name": name, "pkg": name, "dep_kinds": kinds })
+        };
+        let node = |name: &str, deps: Vec<serde_json::Value>| {
+            let dependencies: Vec<_> = deps.iter().map(|it| it["pkg"].clone()).collect();
+            json!({ "id": name, "deps": deps, "dependencies": dependencies })
+        };
+        let meta = json!({
+            "packages": [
+                app,
+                package("firmware", None, "lib"),
+                package("macros", registry, "proc-macro"),
+                package("shared", registry, "lib"),
+            ],
+            "workspace_members": ["app", "firmware"],
+            "resolve": {
+                "nodes": [
+                    node("app", vec![
+                        dep("firmware", &[None]),
+                        dep("macros", &[None]),
+                        dep("shared", &[None, Some("build")]),
+                    ]),
+                    node("firmware", vec![dep("shared", &[None])]),
+                    node("macros", vec![dep("shared", &[None])]),
+                    node("shared", vec![]),
+                ],
+                "root": null,
+            },
+            "workspace_root": root,
+            "target_directory": format!("{}/target", root),
+            "version": 1,
+        });
+        let cargo_toml = AbsPathBuf::assert(PathBuf::from(format!("{}/Cargo.toml", root)));
+        CargoWorkspace::new(
+            &cargo_toml,
+            &CargoConfig::default(),
+            serde_json::from_value(meta).unwrap(),
+            Some(TARGET.to_string()),
+            Some(HOST.to_string()),
+        )
+        .unwrap()
+    }
+
+    fn package(cargo: &CargoWorkspace, name: &str) -> Package {
+        cargo.packages().find(|&pkg| cargo[pkg].name == name).unwrap()
+    }
+
+    #[test]
+    fn dependency_platforms() {
+        let cargo = workspace();
+        let host = to_platform(&cargo, cargo.host());
+        assert_eq!(host, Some(HOST));
+        assert_eq!(
+            cargo[package(&cargo, "firmware")].forced_target.as_deref(),
+            Some(FORCED_TARGET)
+        );
+
+        let shared = package(&cargo, "shared");
+        assert_eq!(dependency_platform(&cargo, shared, None, host), None);
+        assert_eq!(dependency_platform(&cargo, shared, host, host), host);
+
+        // Proc macros always run on the host.
+        let macros = package(&cargo, "macros");
+        assert_eq!(dependency_platform(&cargo, macros, None, host), host);
+
+        // Packages with a `forced-target` are built for it by all dependents.
+        let firmware = package(&cargo, "firmware");
+        assert_eq!(dependency_platform(&cargo, firmware, None, host), Some(FORCED_TARGET));
+        assert_eq!(dependency_platform(&cargo, firmware, host, host), Some(FORCED_TARGET));
+    }
+
+    #[test]
+    fn package_platforms_follow_dependencies() {
+        let cargo = workspace();
+        let host = to_platform(&cargo, cargo.host());
+        let platforms = package_platforms(&cargo, host);
+
+        assert_eq!(platforms[&package(&cargo, "app")], vec![None]);
+        assert_eq!(platforms[&package(&cargo, "firmware")], vec![Some(FORCED_TARGET)]);
+        assert_eq!(platforms[&package(&cargo, "macros")], vec![Some(HOST)]);
+        // A normal dependency of the library, a build dependency of the build
+        // script, and a dependency of the proc macro and of `firmware`.
+        assert_eq!(
+            platforms[&package(&cargo, "shared")],
+            vec![None, Some(HOST), Some(FORCED_TARGET)]
+        );
+    }
+}
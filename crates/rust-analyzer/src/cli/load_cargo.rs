@@ -9,6 +9,7 @@ use ide::{AnalysisHost, Change};
 use ide_db::base_db::CrateGraph;
 use project_model::{
     BuildDataCollector, CargoConfig, ProcMacroClient, ProjectManifest, ProjectWorkspace,
+    DEFAULT_EXPANSION_TIMEOUT,
 };
 use vfs::{loader::Handle, AbsPath, AbsPathBuf};
 
@@ -49,7 +50,10 @@ fn load_workspace(
 
     let proc_macro_client = if config.with_proc_macro {
         let path = std::env::current_exe()?;
-        Some(ProcMacroClient::extern_process(path, &["proc-macro"]).unwrap())
+        Some(
+            ProcMacroClient::extern_process(path, &["proc-macro"], DEFAULT_EXPANSION_TIMEOUT)
+                .unwrap(),
+        )
     } else {
         None
     };
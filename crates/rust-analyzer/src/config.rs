@@ -7,7 +7,7 @@
 //! configure the server itself, feature flags are passed into analysis, and
 //! tweak things like automatic insertion of `()` in completions.
 
-use std::{ffi::OsString, iter, path::PathBuf};
+use std::{ffi::OsString, iter, path::PathBuf, time::Duration};
 
 use flycheck::FlycheckConfig;
 use ide::{
@@ -178,6 +178,9 @@ config_data! {
 
         /// Whether to show inlay type hints for method chains.
         inlayHints_chainingHints: bool      = "true",
+        /// Whether to show inlay hints listing the captured variables of
+        /// `move` closures.
+        inlayHints_closureCaptureHints: bool = "false",
         /// Maximum length for inlay hints. Set to null to have an unlimited length.
         inlayHints_maxLength: Option<usize> = "25",
         /// Whether to show function parameter name inlay hints at the call
@@ -222,6 +225,9 @@ config_data! {
         /// Internal config, path to proc-macro server executable (typically,
         /// this is rust-analyzer itself, but we override this in tests).
         procMacro_server: Option<PathBuf>          = "null",
+        /// Time in seconds after which an unanswered proc macro expansion
+        /// fails and the proc-macro server is restarted.
+        procMacro_expansionTimeout: usize          = "10",
 
         /// Command to be executed instead of 'cargo' for runnables.
         runnables_overrideCargo: Option<String> = "null",
@@ -578,6 +584,7 @@ impl Config {
         DiagnosticsConfig {
             disable_experimental: !self.data.diagnostics_enableExperimental,
             disabled: self.data.diagnostics_disabled.clone(),
+            insert_use: self.insert_use_config(),
         }
     }
     pub fn diagnostics_map(&self) -> DiagnosticsMapConfig {
@@ -598,6 +605,9 @@ impl Config {
         let path = self.data.procMacro_server.clone().or_else(|| std::env::current_exe().ok())?;
         Some((path, vec!["proc-macro".into()]))
     }
+    pub fn proc_macro_expansion_timeout(&self) -> Duration {
+        Duration::from_secs(self.data.procMacro_expansionTimeout as u64)
+    }
     pub fn expand_proc_attr_macros(&self) -> bool {
         self.data.experimental_procAttrMacros
     }
@@ -702,6 +712,7 @@ impl Config {
             type_hints: self.data.inlayHints_typeHints,
             parameter_hints: self.data.inlayHints_parameterHints,
             chaining_hints: self.data.inlayHints_chainingHints,
+            closure_capture_hints: self.data.inlayHints_closureCaptureHints,
             max_length: self.data.inlayHints_maxLength,
         }
     }
@@ -1010,6 +1021,10 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
+        "usize" => set! {
+            "type": "integer",
+            "minimum": 0,
+        },
         "Option<usize>" => set! {
             "type": ["null", "integer"],
             "minimum": 0,
@@ -5,6 +5,7 @@
 use std::{
     io::{Read, Write as _},
     process::{self, Command, Stdio},
+    sync::Arc,
 };
 
 use ide::{
@@ -37,7 +38,7 @@ use crate::{
     diff::diff,
     from_proto,
     global_state::{GlobalState, GlobalStateSnapshot},
-    line_index::LineEndings,
+    line_index::{LineEndings, LineIndex},
     lsp_ext::{self, InlayHint, InlayHintsParams, ViewCrateGraphParams, WorkspaceSymbolParams},
     lsp_utils::all_edits_are_disjoint,
     to_proto, LspError, Result,
@@ -161,8 +162,56 @@ pub(crate) fn handle_expand_macro(
     let line_index = snap.file_line_index(file_id)?;
     let offset = from_proto::offset(&line_index, params.position);
 
-    let res = snap.analysis.expand_macro(FilePosition { file_id, offset })?;
-    Ok(res.map(|it| lsp_ext::ExpandedMacro { name: it.name, expansion: it.expansion }))
+    let recursive = params.recursive.unwrap_or(true);
+    let res = match snap.analysis.expand_macro(FilePosition { file_id, offset }, recursive)? {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    let expansion_index = LineIndex {
+        index: Arc::new(ide::LineIndex::new(&res.expansion)),
+        endings: LineEndings::Unix,
+        encoding: snap.config.offset_encoding(),
+    };
+    let ranges = res
+        .ranges
+        .into_iter()
+        .map(|(range, original)| {
+            Ok(lsp_ext::ExpansionRange {
+                range: to_proto::range(&expansion_index, range),
+                original: to_proto::location(&snap, original)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(lsp_ext::ExpandedMacro { name: res.name, expansion: res.expansion, ranges }))
+}
+
+pub(crate) fn handle_trace_macro_expansion(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::ExpandMacroParams,
+) -> Result<Option<lsp_ext::MacroExpansionTrace>> {
+    let _p = profile::span("handle_trace_macro_expansion");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let offset = from_proto::offset(&line_index, params.position);
+
+    let res = snap.analysis.trace_macro_expansion(FilePosition { file_id, offset })?;
+    Ok(res.map(|it| lsp_ext::MacroExpansionTrace {
+        name: it.name,
+        rules: it
+            .rules
+            .into_iter()
+            .map(|rule| lsp_ext::MacroRuleTrace {
+                index: rule.index as u32,
+                pattern: rule.pattern,
+                matched: rule.matched,
+                matched_token_trees: rule.matched_token_trees as u32,
+                total_token_trees: rule.total_token_trees as u32,
+                failed_at: rule.failed_at,
+                error: rule.error,
+            })
+            .collect(),
+        selected: it.selected.map(|it| it as u32),
+    }))
 }
 
 pub(crate) fn handle_selection_range(
@@ -4,7 +4,7 @@ use std::{collections::HashMap, path::PathBuf};
 
 use lsp_types::request::Request;
 use lsp_types::{
-    notification::Notification, CodeActionKind, PartialResultParams, Position, Range,
+    notification::Notification, CodeActionKind, Location, PartialResultParams, Position, Range,
     TextDocumentIdentifier, WorkDoneProgressParams,
 };
 use serde::{Deserialize, Serialize};
@@ -104,6 +104,8 @@ impl Request for ExpandMacro {
 pub struct ExpandMacroParams {
     pub text_document: TextDocumentIdentifier,
     pub position: Position,
+    /// Whether to expand the macro calls in the expansion as well, defaults to `true`.
+    pub recursive: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -111,6 +113,44 @@ pub struct ExpandMacroParams {
 pub struct ExpandedMacro {
     pub name: String,
     pub expansion: String,
+    pub ranges: Vec<ExpansionRange>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpansionRange {
+    /// Range of a token in `ExpandedMacro::expansion`.
+    pub range: Range,
+    /// The token in the source it was produced from.
+    pub original: Location,
+}
+
+pub enum TraceMacroExpansion {}
+
+impl Request for TraceMacroExpansion {
+    type Params = ExpandMacroParams;
+    type Result = Option<MacroExpansionTrace>;
+    const METHOD: &'static str = "rust-analyzer/traceMacroExpansion";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroExpansionTrace {
+    pub name: String,
+    pub rules: Vec<MacroRuleTrace>,
+    pub selected: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroRuleTrace {
+    pub index: u32,
+    pub pattern: Option<String>,
+    pub matched: bool,
+    pub matched_token_trees: u32,
+    pub total_token_trees: u32,
+    pub failed_at: Option<String>,
+    pub error: Option<String>,
 }
 
 pub enum MatchingBrace {}
@@ -239,6 +279,7 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    ClosureCaptureHint,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
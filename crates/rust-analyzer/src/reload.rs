@@ -308,7 +308,11 @@ impl GlobalState {
         if self.proc_macro_client.is_none() {
             self.proc_macro_client = match self.config.proc_macro_srv() {
                 None => None,
-                Some((path, args)) => match ProcMacroClient::extern_process(path.clone(), args) {
+                Some((path, args)) => match ProcMacroClient::extern_process(
+                    path.clone(),
+                    args,
+                    self.config.proc_macro_expansion_timeout(),
+                ) {
                     Ok(it) => Some(it),
                     Err(err) => {
                         log::error!(
@@ -82,6 +82,7 @@ pub(crate) fn document_highlight_kind(
 pub(crate) fn diagnostic_severity(severity: Severity) -> lsp_types::DiagnosticSeverity {
     match severity {
         Severity::Error => lsp_types::DiagnosticSeverity::Error,
+        Severity::Warning => lsp_types::DiagnosticSeverity::Warning,
         Severity::WeakWarning => lsp_types::DiagnosticSeverity::Hint,
     }
 }
@@ -374,6 +375,7 @@ pub(crate) fn inlay_hint(line_index: &LineIndex, inlay_hint: InlayHint) -> lsp_e
             InlayKind::ParameterHint => lsp_ext::InlayKind::ParameterHint,
             InlayKind::TypeHint => lsp_ext::InlayKind::TypeHint,
             InlayKind::ChainingHint => lsp_ext::InlayKind::ChainingHint,
+            InlayKind::ClosureCaptureHint => lsp_ext::InlayKind::ClosureCaptureHint,
         },
     }
 }
@@ -507,6 +509,7 @@ fn semantic_token_type_and_modifiers(
             HlMod::Async => semantic_tokens::ASYNC,
             HlMod::Library => semantic_tokens::LIBRARY,
             HlMod::Public => semantic_tokens::PUBLIC,
+            HlMod::Unreachable => semantic_tokens::UNREACHABLE,
             HlMod::Unsafe => semantic_tokens::UNSAFE,
             HlMod::Callable => semantic_tokens::CALLABLE,
             HlMod::Static => lsp_types::SemanticTokenModifier::STATIC,
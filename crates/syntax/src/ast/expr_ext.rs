@@ -2,9 +2,9 @@
 
 use crate::{
     ast::{self, support, AstChildren, AstNode},
-    AstToken,
+    match_ast, AstToken,
     SyntaxKind::*,
-    SyntaxToken, T,
+    SyntaxNode, SyntaxToken, T,
 };
 
 impl ast::AttrsOwner for ast::Expr {}
@@ -50,15 +50,22 @@ impl ast::IfExpr {
         let res = match self.blocks().nth(1) {
             Some(block) => ElseBranch::Block(block),
             None => {
-                let elif: ast::IfExpr = support::child(self.syntax())?;
+                let elif = self.children_after_condition().find_map(ast::IfExpr::cast)?;
                 ElseBranch::IfExpr(elif)
             }
         };
         Some(res)
     }
 
-    pub fn blocks(&self) -> AstChildren<ast::BlockExpr> {
-        support::children(self.syntax())
+    /// The `then` and `else` blocks, which does not include a condition that
+    /// happens to be a block.
+    pub fn blocks(&self) -> impl Iterator<Item = ast::BlockExpr> {
+        self.children_after_condition().filter_map(ast::BlockExpr::cast)
+    }
+
+    fn children_after_condition(&self) -> impl Iterator<Item = SyntaxNode> {
+        let condition = self.condition().map(|it| it.syntax().clone());
+        self.syntax().children().skip_while(move |it| Some(it) != condition.as_ref()).skip(1)
     }
 }
 
@@ -397,7 +404,15 @@ impl ast::BlockExpr {
             Some(it) => it,
             None => return true,
         };
-        !matches!(parent.kind(), FN | IF_EXPR | WHILE_EXPR | LOOP_EXPR | EFFECT_EXPR)
+        let condition = match_ast! {
+            match parent {
+                ast::IfExpr(it) => it.condition(),
+                ast::WhileExpr(it) => it.condition(),
+                _ => return !matches!(parent.kind(), FN | LOOP_EXPR | EFFECT_EXPR),
+            }
+        };
+        // A block in the condition of an `if` or `while` is an ordinary expression.
+        condition.map_or(false, |it| it.syntax() == self.syntax())
     }
 }
 
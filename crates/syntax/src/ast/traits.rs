@@ -21,7 +21,11 @@ pub trait VisibilityOwner: AstNode {
 
 pub trait LoopBodyOwner: AstNode {
     fn loop_body(&self) -> Option<ast::BlockExpr> {
-        support::child(self.syntax())
+        // The condition of a `while` or the iterable of a `for` may be a block
+        // as well, in which case the body is the second one.
+        let mut blocks = support::children(self.syntax());
+        let first = blocks.next();
+        blocks.next().or(first)
     }
 
     fn label(&self) -> Option<ast::Label> {
@@ -9,7 +9,7 @@ use std::{
 
 use text_edit::Indel;
 
-use crate::{validation, AstNode, SourceFile, TextRange};
+use crate::{parsing, validation, AstNode, SourceFile, SyntaxError, SyntaxNode, TextRange};
 
 fn check_file_invariants(file: &SourceFile) {
     let root = file.syntax();
@@ -43,10 +43,14 @@ impl CheckReparse {
         text.get(delete_start..delete_start.checked_add(delete_len)?)?; // make sure delete is a valid range
         let delete =
             TextRange::at(delete_start.try_into().unwrap(), delete_len.try_into().unwrap());
-        let edited_text =
-            format!("{}{}{}", &text[..delete_start], &insert, &text[delete_start + delete_len..]);
         let edit = Indel { insert, delete };
-        Some(CheckReparse { text, edit, edited_text })
+        Some(CheckReparse::new(text, edit))
+    }
+
+    pub fn new(text: String, edit: Indel) -> Self {
+        let mut edited_text = text.clone();
+        edit.apply(&mut edited_text);
+        CheckReparse { text, edit, edited_text }
     }
 
     pub fn run(&self) {
@@ -69,7 +73,20 @@ impl CheckReparse {
                 );
             }
         }
-        // FIXME
-        // assert_eq!(new_file.errors(), full_reparse.errors());
+
+        // FIXME: validation errors are not recomputed by the incremental
+        // reparse, so only compare the errors reported by the lexer and parser.
+        let (green, errors) = parsing::parse_text(&self.text);
+        if let Some((_, mut new_errors, _)) =
+            parsing::incremental_reparse(&SyntaxNode::new_root(green), &self.edit, errors)
+        {
+            let (_, mut full_errors) = parsing::parse_text(&self.edited_text);
+            let sort = |errors: &mut Vec<SyntaxError>| {
+                errors.sort_by_key(|it| (it.range().start(), it.range().end(), it.to_string()))
+            };
+            sort(&mut new_errors);
+            sort(&mut full_errors);
+            assert_eq!(new_errors, full_errors, "different errors produced by the full reparse");
+        }
     }
 }
@@ -3,13 +3,22 @@
 //! We use two simple strategies for this:
 //!   - if the edit modifies only a single token (like changing an identifier's
 //!     letter), we replace only this token.
-//!   - otherwise, we search for the nearest `{}` block which contains the edit
-//!     and try to parse only this block.
+//!   - otherwise, we walk up from the edited element and try to reparse the
+//!     smallest node which can be parsed in isolation (a `{}` block, an item, a
+//!     statement, a match arm, a token tree, ...), reusing everything around it.
+//!
+//! A node is reparsed only if the result is guaranteed to be the same as the
+//! one of a full reparse: tokens at the edges of the node must not change, and
+//! the parser must consume exactly the tokens of the node, producing a single
+//! node of the same kind, without looking too far past the node's end.
+
+use std::cell::Cell;
 
-use parser::Reparser;
+use parser::{ParseError, Reparser, TokenSource, TreeSink};
 use text_edit::Indel;
 
 use crate::{
+    ast,
     parsing::{
         lexer::{lex_single_syntax_kind, tokenize, Token},
         text_token_source::TextTokenSource,
@@ -17,10 +26,14 @@ use crate::{
     },
     syntax_node::{GreenNode, GreenToken, NodeOrToken, SyntaxElement, SyntaxNode},
     SyntaxError,
-    SyntaxKind::*,
-    TextRange, TextSize, T,
+    SyntaxKind::{self, *},
+    SyntaxToken, TextRange, TextSize,
 };
 
+/// How many non-trivia tokens following the reparsed node are made available
+/// to the parser for lookahead.
+const LOOKAHEAD: usize = 8;
+
 pub(crate) fn incremental_reparse(
     node: &SyntaxNode,
     edit: &Indel,
@@ -30,7 +43,7 @@ pub(crate) fn incremental_reparse(
         return Some((green, merge_errors(errors, new_errors, old_range, edit), old_range));
     }
 
-    if let Some((green, new_errors, old_range)) = reparse_block(node, edit) {
+    if let Some((green, new_errors, old_range)) = reparse_node(node, edit, &errors) {
         return Some((green, merge_errors(errors, new_errors, old_range, edit), old_range));
     }
     None
@@ -53,6 +66,14 @@ fn reparse_token(
             }
 
             let mut new_text = get_text_after_edit(prev_token.clone().into(), edit);
+            // Blank lines and doc comments decide whether comments are attached
+            // to the following item.
+            if prev_token_kind == WHITESPACE
+                && matches!(prev_token.prev_token().map(|it| it.kind()), Some(COMMENT))
+                && prev_token.text().contains("\n\n") != new_text.contains("\n\n")
+            {
+                return None;
+            }
             let (new_token_kind, new_err) = lex_single_syntax_kind(&new_text)?;
 
             if new_token_kind != prev_token_kind
@@ -60,6 +81,12 @@ fn reparse_token(
             {
                 return None;
             }
+            if prev_token_kind == COMMENT
+                && ast::CommentKind::from_text(prev_token.text())
+                    != ast::CommentKind::from_text(&new_text)
+            {
+                return None;
+            }
 
             // Check that edited token is not a part of the bigger token.
             // E.g. if for source code `bruh"str"` the user removed `ruh`, then
@@ -84,26 +111,281 @@ fn reparse_token(
     }
 }
 
-fn reparse_block(
+fn reparse_node(
     root: &SyntaxNode,
     edit: &Indel,
+    errors: &[SyntaxError],
 ) -> Option<(GreenNode, Vec<SyntaxError>, TextRange)> {
-    let (node, reparser) = find_reparsable_node(root, edit.delete)?;
-    let text = get_text_after_edit(node.clone().into(), edit);
+    root.covering_element(edit.delete).ancestors().find_map(|node| {
+        let first_child = node.first_child_or_token().map(|it| it.kind());
+        let parent = node.parent().map(|it| it.kind());
+        let reparser = Reparser::for_node(node.kind(), first_child, parent)?;
+        reparse_node_with(&node, reparser, edit, errors)
+    })
+}
+
+fn reparse_node_with(
+    node: &SyntaxNode,
+    reparser: Reparser,
+    edit: &Indel,
+    errors: &[SyntaxError],
+) -> Option<(GreenNode, Vec<SyntaxError>, TextRange)> {
+    let range = node.text_range();
+    // Errors at the edges of the node may have been reported either while
+    // parsing the node or while parsing its parent, so we can't tell whether
+    // they should be kept.
+    if errors.iter().any(|err| {
+        let err_range = err.range();
+        err_range.start() <= range.start() && range.start() <= err_range.end()
+            || err_range.start() <= range.end() && range.end() <= err_range.end()
+    }) {
+        return None;
+    }
 
+    let text = get_text_after_edit(node.clone().into(), edit);
     let (tokens, new_lexer_errors) = tokenize(&text);
-    if !is_balanced(&tokens) {
+    if !has_same_start(node, &text, &tokens) || tokens.last()?.kind.is_trivia() {
         return None;
     }
 
-    let mut token_source = TextTokenSource::new(&text, &tokens);
-    let mut tree_sink = TextTreeSink::new(&text, &tokens);
-    reparser.parse(&mut token_source, &mut tree_sink);
+    let first_token = node.first_token()?;
+    let last_token = node.last_token()?;
+    if !is_lexed_in_isolation(first_token.prev_token(), &text, &tokens, last_token.next_token()) {
+        return None;
+    }
 
+    // Give the parser a few tokens following the node, so that it can make
+    // the same decisions as it would make while parsing the whole file.
+    let mut following_text = String::new();
+    let mut next = last_token.next_token();
+    let mut n_following = 0;
+    while n_following < LOOKAHEAD {
+        let token = match next {
+            Some(it) => it,
+            None => break,
+        };
+        if !token.kind().is_trivia() {
+            n_following += 1;
+        }
+        following_text.push_str(token.text());
+        next = token.next_token();
+    }
+    let at_file_end = next.is_none();
+    // The tree contains composite tokens like `..=`, the parser needs the raw
+    // ones.
+    let (following_tokens, _) = tokenize(&following_text);
+    let mut n_following = following_tokens.iter().filter(|it| !it.kind.is_trivia()).count();
+    if !at_file_end {
+        // The last token might be lexed differently without the text after it.
+        n_following = n_following.saturating_sub(1);
+    }
+    let lookahead_text = format!("{}{}", text, following_text);
+    let lookahead_tokens: Vec<_> = tokens.iter().chain(&following_tokens).copied().collect();
+    let n_node_tokens = tokens.iter().filter(|it| !it.kind.is_trivia()).count();
+
+    let mut token_source = LookaheadTokenSource {
+        inner: TextTokenSource::new(&lookahead_text, &lookahead_tokens),
+        pos: 0,
+        n_tokens: n_node_tokens + n_following,
+        at_file_end,
+        lookahead_exceeded: Cell::new(false),
+    };
+    let mut recording_sink = RecordingSink::default();
+    reparser.parse(&mut token_source, &mut recording_sink);
+    if token_source.lookahead_exceeded.get()
+        || !recording_sink.is_single_node(node.kind(), n_node_tokens)
+    {
+        return None;
+    }
+
+    let mut tree_sink = TextTreeSink::new(&text, &tokens);
+    recording_sink.replay(&mut tree_sink);
     let (green, mut new_parser_errors) = tree_sink.finish();
     new_parser_errors.extend(new_lexer_errors);
 
-    Some((node.replace_with(green), new_parser_errors, node.text_range()))
+    Some((node.replace_with(green), new_parser_errors, range))
+}
+
+/// Checks that the edited text of `node` starts with the same token as the
+/// node itself, including the leading trivia which was attached to the node.
+fn has_same_start(node: &SyntaxNode, text: &str, tokens: &[Token]) -> bool {
+    let mut offset = TextSize::from(0);
+    let mut old_tokens = node.descendants_with_tokens().filter_map(|it| it.into_token());
+    for token in tokens {
+        let old_token = match old_tokens.next() {
+            Some(it) => it,
+            None => return false,
+        };
+        let token_text = &text[TextRange::at(offset, token.len)];
+        if old_token.kind() != token.kind || old_token.text() != token_text {
+            return false;
+        }
+        if !token.kind.is_trivia() {
+            return true;
+        }
+        offset += token.len;
+    }
+    false
+}
+
+/// Checks that lexing `text` together with the tokens surrounding the node
+/// gives the same tokens, that is, the edit doesn't move the boundaries
+/// between the node and its neighbours.
+fn is_lexed_in_isolation(
+    prev: Option<SyntaxToken>,
+    text: &str,
+    tokens: &[Token],
+    next: Option<SyntaxToken>,
+) -> bool {
+    let prev_text = prev.as_ref().map_or("", |it| it.text());
+    let next_text = next.as_ref().map_or("", |it| it.text());
+    let (surrounded_tokens, _) = tokenize(&format!("{}{}{}", prev_text, text, next_text));
+
+    let mut offset = TextSize::from(0);
+    let mut surrounded_tokens = surrounded_tokens.iter();
+    while offset < TextSize::of(prev_text) {
+        match surrounded_tokens.next() {
+            Some(token) => offset += token.len,
+            None => return false,
+        }
+    }
+    offset == TextSize::of(prev_text)
+        && surrounded_tokens
+            .take(tokens.len())
+            .map(|it| (it.kind, it.len))
+            .eq(tokens.iter().map(|it| (it.kind, it.len)))
+}
+
+/// Token source over the tokens of the reparsed node followed by a few tokens
+/// after it, which remembers if the parser looked past them.
+struct LookaheadTokenSource<'t> {
+    inner: TextTokenSource<'t>,
+    pos: usize,
+    n_tokens: usize,
+    /// Whether the tokens reach the end of the file, so that the parser sees
+    /// the real `EOF` after them.
+    at_file_end: bool,
+    lookahead_exceeded: Cell<bool>,
+}
+
+impl LookaheadTokenSource<'_> {
+    fn check_lookahead(&self, n: usize) {
+        if !self.at_file_end && self.pos + n >= self.n_tokens {
+            self.lookahead_exceeded.set(true);
+        }
+    }
+}
+
+impl TokenSource for LookaheadTokenSource<'_> {
+    fn current(&self) -> parser::Token {
+        self.check_lookahead(0);
+        self.inner.current()
+    }
+
+    fn lookahead_nth(&self, n: usize) -> parser::Token {
+        self.check_lookahead(n);
+        self.inner.lookahead_nth(n)
+    }
+
+    fn bump(&mut self) {
+        if self.pos < self.n_tokens {
+            self.pos += 1;
+        }
+        self.inner.bump()
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        self.check_lookahead(0);
+        self.inner.is_keyword(kw)
+    }
+}
+
+enum SinkEvent {
+    Token { kind: SyntaxKind, n_tokens: u8 },
+    Start(SyntaxKind),
+    Finish,
+    Error(ParseError),
+}
+
+/// Records the output of the parser, so that it can be checked before
+/// building the tree.
+#[derive(Default)]
+struct RecordingSink {
+    events: Vec<SinkEvent>,
+}
+
+impl TreeSink for RecordingSink {
+    fn token(&mut self, kind: SyntaxKind, n_tokens: u8) {
+        self.events.push(SinkEvent::Token { kind, n_tokens })
+    }
+
+    fn start_node(&mut self, kind: SyntaxKind) {
+        self.events.push(SinkEvent::Start(kind))
+    }
+
+    fn finish_node(&mut self) {
+        self.events.push(SinkEvent::Finish)
+    }
+
+    fn error(&mut self, error: ParseError) {
+        self.events.push(SinkEvent::Error(error))
+    }
+}
+
+impl RecordingSink {
+    /// Checks that the parser produced a single node of `kind`, which spans
+    /// exactly `n_tokens` non-trivia tokens.
+    fn is_single_node(&self, kind: SyntaxKind, n_tokens: usize) -> bool {
+        let mut depth = 0usize;
+        let mut consumed = 0usize;
+        for (idx, event) in self.events.iter().enumerate() {
+            match event {
+                SinkEvent::Start(it) => {
+                    if depth == 0 && (idx != 0 || *it != kind) {
+                        return false;
+                    }
+                    // A node started after the last token would take the
+                    // trivia following the reparsed node.
+                    if consumed == n_tokens {
+                        return false;
+                    }
+                    depth += 1;
+                }
+                SinkEvent::Finish => {
+                    depth = match depth.checked_sub(1) {
+                        Some(it) => it,
+                        None => return false,
+                    };
+                    if depth == 0 && idx != self.events.len() - 1 {
+                        return false;
+                    }
+                }
+                SinkEvent::Token { n_tokens, .. } => {
+                    if depth == 0 {
+                        return false;
+                    }
+                    consumed += *n_tokens as usize;
+                }
+                SinkEvent::Error(_) => {
+                    if depth == 0 {
+                        return false;
+                    }
+                }
+            }
+        }
+        !self.events.is_empty() && depth == 0 && consumed == n_tokens
+    }
+
+    fn replay(self, sink: &mut dyn TreeSink) {
+        for event in self.events {
+            match event {
+                SinkEvent::Token { kind, n_tokens } => sink.token(kind, n_tokens),
+                SinkEvent::Start(kind) => sink.start_node(kind),
+                SinkEvent::Finish => sink.finish_node(),
+                SinkEvent::Error(error) => sink.error(error),
+            }
+        }
+    }
 }
 
 fn get_text_after_edit(element: SyntaxElement, edit: &Indel) -> String {
@@ -121,39 +403,6 @@ fn is_contextual_kw(text: &str) -> bool {
     matches!(text, "auto" | "default" | "union")
 }
 
-fn find_reparsable_node(node: &SyntaxNode, range: TextRange) -> Option<(SyntaxNode, Reparser)> {
-    let node = node.covering_element(range);
-
-    node.ancestors().find_map(|node| {
-        let first_child = node.first_child_or_token().map(|it| it.kind());
-        let parent = node.parent().map(|it| it.kind());
-        Reparser::for_node(node.kind(), first_child, parent).map(|r| (node, r))
-    })
-}
-
-fn is_balanced(tokens: &[Token]) -> bool {
-    if tokens.is_empty()
-        || tokens.first().unwrap().kind != T!['{']
-        || tokens.last().unwrap().kind != T!['}']
-    {
-        return false;
-    }
-    let mut balance = 0usize;
-    for t in &tokens[1..tokens.len() - 1] {
-        match t.kind {
-            T!['{'] => balance += 1,
-            T!['}'] => {
-                balance = match balance.checked_sub(1) {
-                    Some(b) => b,
-                    None => return false,
-                }
-            }
-            _ => (),
-        }
-    }
-    balance == 0
-}
-
 fn merge_errors(
     old_errors: Vec<SyntaxError>,
     new_errors: Vec<SyntaxError>,
@@ -304,7 +553,7 @@ fn foo() {
 }
 ",
             "123",
-            14,
+            2,
         );
         do_check(
             r"
@@ -313,7 +562,106 @@ extern {
 }
 ",
             " exit(code: c_int)",
-            11,
+            3,
+        );
+    }
+
+    #[test]
+    fn reparse_node_tests() {
+        do_check(
+            r"
+#[derive(Copy$0$0)]
+struct S;
+",
+            ", Clone",
+            6,
+        );
+        do_check(
+            r"
+fn foo<T>() where T: Copy$0$0 {}
+
+fn bar() {}
+",
+            " + Clone",
+            28,
+        );
+        do_check(
+            r"
+fn foo() {
+    match x {
+        A => 1$0$0,
+        B => 2,
+    }
+}
+",
+            "0",
+            6,
+        );
+        do_check(
+            r"
+fn foo() {
+    let x = 1$0$0;
+    let y = 2;
+}
+",
+            "1",
+            10,
+        );
+        do_check(
+            r"
+fn foo() {
+    foo($0$0)
+}
+",
+            "1, 2",
+            5,
+        );
+        do_check(
+            r"
+fn foo() {
+    println!($0$0);
+}
+",
+            "\"{}\", 1",
+            2,
+        );
+    }
+
+    #[test]
+    fn reparse_node_falls_back_to_parent() {
+        // The arm stops being block-like, so the comma becomes mandatory.
+        do_check(
+            r"
+fn foo() {
+    match x {
+        A => {}$0$0
+        B => 2,
+    }
+}
+",
+            " + 1",
+            39,
+        );
+        // The statement becomes the tail expression of the block.
+        do_check(
+            r"
+fn foo() {
+    bar()$0;$0
+}
+",
+            "",
+            14,
+        );
+        // The comment is no longer attached to the function.
+        do_check(
+            r"
+impl S {
+    // comment
+$0$0    fn foo() {}
+}
+",
+            "\n",
+            34,
         );
     }
 
@@ -2,12 +2,20 @@
 
 use std::fmt;
 
-use crate::{TextRange, TextSize};
+use parser::{ParseError, ParseErrorCode};
+
+use crate::{SyntaxKind, TextRange, TextSize};
 
 /// Represents the result of unsuccessful tokenization, parsing
 /// or tree validation.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct SyntaxError(String, TextRange);
+pub struct SyntaxError {
+    message: String,
+    range: TextRange,
+    /// Only set for errors reported by the parser.
+    code: Option<ParseErrorCode>,
+    expected: Vec<SyntaxKind>,
+}
 
 // FIXME: there was an unused SyntaxErrorKind previously (before this enum was removed)
 // It was introduced in this PR: https://github.com/rust-analyzer/rust-analyzer/pull/846/files#diff-827da9b03b8f9faa1bade5cdd44d5dafR95
@@ -21,24 +29,42 @@ pub struct SyntaxError(String, TextRange);
 
 impl SyntaxError {
     pub fn new(message: impl Into<String>, range: TextRange) -> Self {
-        Self(message.into(), range)
+        Self { message: message.into(), range, code: None, expected: Vec::new() }
     }
     pub fn new_at_offset(message: impl Into<String>, offset: TextSize) -> Self {
-        Self(message.into(), TextRange::empty(offset))
+        Self::new(message, TextRange::empty(offset))
+    }
+    pub fn from_parse_error(error: ParseError, offset: TextSize) -> Self {
+        Self {
+            message: error.message,
+            range: TextRange::empty(offset),
+            code: Some(error.code),
+            expected: error.expected,
+        }
     }
 
     pub fn range(&self) -> TextRange {
-        self.1
+        self.range
+    }
+
+    /// The code of the parser error, `None` for lexer and validation errors.
+    pub fn code(&self) -> Option<ParseErrorCode> {
+        self.code
+    }
+
+    /// Tokens which would have been accepted at the position of the error.
+    pub fn expected(&self) -> &[SyntaxKind] {
+        &self.expected
     }
 
     pub fn with_range(mut self, range: TextRange) -> Self {
-        self.1 = range;
+        self.range = range;
         self
     }
 }
 
 impl fmt::Display for SyntaxError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        self.message.fmt(f)
     }
 }
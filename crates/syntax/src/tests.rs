@@ -8,8 +8,12 @@ use ast::NameOwner;
 use expect_test::expect_file;
 use rayon::prelude::*;
 use test_utils::{bench, bench_fixture, project_root, skip_slow_tests};
+use text_edit::Indel;
 
-use crate::{ast, fuzz, tokenize, AstNode, SourceFile, SyntaxError, TextRange, TextSize, Token};
+use crate::{
+    ast, fuzz, tokenize, AstNode, ParseErrorCode, SourceFile, SyntaxError, TextRange, TextSize,
+    Token, T,
+};
 
 #[test]
 fn lexer_tests() {
@@ -42,6 +46,20 @@ fn main() {
     assert!(parse.ok().is_ok());
 }
 
+#[test]
+fn parse_error_codes() {
+    let parse = SourceFile::parse("fn f() { let x = 1 let y = ; }");
+    let errors: Vec<_> =
+        parse.errors().iter().map(|it| (it.code(), it.expected().to_vec())).collect();
+    assert_eq!(
+        errors,
+        vec![
+            (Some(ParseErrorCode::MissingToken), vec![T![;]]),
+            (Some(ParseErrorCode::ExpectedExpression), vec![]),
+        ]
+    );
+}
+
 #[test]
 fn benchmark_parser() {
     if skip_slow_tests() {
@@ -149,6 +167,76 @@ fn reparse_fuzz_tests() {
     }
 }
 
+/// Checks that incremental reparsing after random edits of the parser test
+/// data gives the same result as parsing the edited text from scratch.
+#[test]
+fn reparse_random_edits() {
+    const SNIPPETS: &[&str] = &[
+        "",
+        " ",
+        "\n",
+        "\n\n",
+        "a",
+        "foo",
+        "1",
+        "{",
+        "}",
+        "(",
+        ")",
+        "[",
+        "]",
+        ";",
+        ",",
+        ":",
+        "::",
+        "<",
+        ">",
+        "=>",
+        "|",
+        "!",
+        "#",
+        "'",
+        "\"",
+        "//",
+        "/*",
+        "*/",
+        "r#",
+        "fn",
+        "let",
+        "if",
+        "match",
+        "where",
+        "#[attr]",
+        "x => 1,",
+        "let x = 1;",
+        "fn f() {}",
+        "where T: Copy",
+        "m!()",
+    ];
+
+    let files = collect_rust_files(
+        &test_data_dir(),
+        &["parser/inline/ok", "parser/inline/err", "parser/ok", "parser/err"],
+    );
+    files.into_par_iter().for_each(|(path, text)| {
+        let mut rng = oorandom::Rand32::new(text.len() as u64);
+        for _ in 0..32 {
+            let start = rng.rand_range(0..text.len() as u32 + 1) as usize;
+            let end = (start + rng.rand_range(0..8) as usize).min(text.len());
+            if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                continue;
+            }
+            let insert = SNIPPETS[rng.rand_range(0..SNIPPETS.len() as u32) as usize];
+            let delete = TextRange::new((start as u32).into(), (end as u32).into());
+            let edit = Indel::replace(delete, insert.to_string());
+            let check = fuzz::CheckReparse::new(text.clone(), edit.clone());
+            if std::panic::catch_unwind(|| check.run()).is_err() {
+                panic!("failed to reparse {} after {:?}", path.display(), edit);
+            }
+        }
+    });
+}
+
 /// Test that Rust-analyzer can parse and validate the rust-analyzer
 #[test]
 fn self_hosting_parsing() {
@@ -100,6 +100,7 @@ pub(crate) fn validate(root: &SyntaxNode) -> Vec<SyntaxError> {
                 ast::RecordExprField(it) => validate_numeric_name(it.name_ref(), &mut errors),
                 ast::Visibility(it) => validate_visibility(it, &mut errors),
                 ast::RangeExpr(it) => validate_range_expr(it, &mut errors),
+                ast::LetExpr(it) => validate_let_expr(it, &mut errors),
                 ast::PathSegment(it) => validate_path_keywords(it, &mut errors),
                 ast::RefType(it) => validate_trait_object_ref_ty(it, &mut errors),
                 ast::PtrType(it) => validate_trait_object_ptr_ty(it, &mut errors),
@@ -238,6 +239,33 @@ fn validate_range_expr(expr: ast::RangeExpr, errors: &mut Vec<SyntaxError>) {
     }
 }
 
+fn validate_let_expr(let_: ast::LetExpr, errors: &mut Vec<SyntaxError>) {
+    let mut node = let_.syntax().clone();
+    while let Some(parent) = node.parent() {
+        node = parent;
+        match_ast! {
+            match (node.clone()) {
+                ast::ParenExpr(_it) => continue,
+                ast::BinExpr(it) => {
+                    if it.op_kind() == Some(ast::BinOp::BooleanAnd) {
+                        continue;
+                    }
+                },
+                // The branches are blocks, so we must be in the condition.
+                ast::IfExpr(_it) => return,
+                ast::WhileExpr(_it) => return,
+                ast::MatchGuard(_it) => return,
+                _ => (),
+            }
+        }
+        break;
+    }
+    errors.push(SyntaxError::new(
+        "`let` expressions are only supported in `if` and `while` conditions",
+        let_.syntax().text_range(),
+    ));
+}
+
 fn validate_path_keywords(segment: ast::PathSegment, errors: &mut Vec<SyntaxError>) {
     use ast::PathSegmentKind;
 
@@ -0,0 +1,6 @@
+fn foo() {
+    let _ = let Some(x) = None;
+    if let Some(a) = x || let Some(b) = y {}
+    while (let Some(c) = z) == true {}
+    f(let x = 1);
+}
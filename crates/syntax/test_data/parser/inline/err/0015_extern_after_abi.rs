@@ -0,0 +1 @@
+extern extern crate foo;
@@ -0,0 +1,4 @@
+fn main() {
+    foo(1 2);
+    foo(1;
+}
@@ -0,0 +1,6 @@
+fn foo() {
+    let Some(x) = opt else { return };
+    let Ok(y): Result<i32, ()> = res else {
+        panic!()
+    };
+}
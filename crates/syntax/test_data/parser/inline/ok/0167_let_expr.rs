@@ -0,0 +1,5 @@
+fn foo() {
+    if let Some(_) = None && true {}
+    if let Some(a) = x && a > 3 && let Ok(b) = y {}
+    while 1 == 5 && (let None = None) {}
+}
@@ -0,0 +1,5 @@
+2
+1
+if
+0..=;
+..=;
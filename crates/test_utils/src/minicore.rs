@@ -27,6 +27,7 @@
 //!     clone: sized
 //!     copy: clone
 //!     from: sized
+//!     try_from: from, result
 //!     eq: sized
 //!     ord: eq, option
 //!     derive:
@@ -82,6 +83,11 @@ pub mod default {
     pub trait Default: Sized {
         fn default() -> Self;
     }
+
+    // region:derive
+    #[rustc_builtin_macro]
+    pub macro Default($item:item) {}
+    // endregion:derive
 }
 // endregion:default
 
@@ -121,6 +127,13 @@ pub mod convert {
             t
         }
     }
+
+    // region:try_from
+    pub trait TryFrom<T>: Sized {
+        type Error;
+        fn try_from(value: T) -> crate::result::Result<Self, Self::Error>;
+    }
+    // endregion:try_from
 }
 // endregion:from
 
@@ -281,6 +281,8 @@ pub enum ExpansionError {
     JsonError(String),
     Unknown(String),
     ExpansionError(String),
+    /// The macro was not expanded at all, for the given reason.
+    Disabled(String),
 }
 
 impl fmt::Display for ExpansionError {
@@ -290,6 +292,7 @@ impl fmt::Display for ExpansionError {
             ExpansionError::JsonError(e) => write!(f, "JSON decoding error: {}", e),
             ExpansionError::Unknown(e) => e.fmt(f),
             ExpansionError::ExpansionError(e) => write!(f, "proc macro returned error: {}", e),
+            ExpansionError::Disabled(e) => write!(f, "proc macro is disabled: {}", e),
         }
     }
 }
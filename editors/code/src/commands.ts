@@ -498,12 +498,26 @@ export function viewFullCrateGraph(ctx: Ctx): Cmd {
     return crateGraph(ctx, true);
 }
 
-// Opens the virtual file that will show the syntax tree
+// Opens the virtual file that will show the macro expansion
 //
-// The contents of the file come from the `TextDocumentContentProvider`
+// The contents of the file come from the `TextDocumentContentProvider`.
+// Go to definition and hover requests in it are forwarded to the tokens of the
+// original source the expansion was produced from.
 export function expandMacro(ctx: Ctx): Cmd {
+    return expandMacroCommand(ctx, true);
+}
+
+export function expandMacroOneStep(ctx: Ctx): Cmd {
+    return expandMacroCommand(ctx, false);
+}
+
+function expandMacroCommand(ctx: Ctx, recursive: boolean): Cmd {
+    // Lines of the header `codeFormat` puts before the expansion.
+    const headerLines = 3;
+
     function codeFormat(expanded: ra.ExpandedMacro): string {
-        let result = `// Recursive expansion of ${expanded.name}! macro\n`;
+        const kind = recursive ? 'Recursive expansion' : 'Expansion';
+        let result = `// ${kind} of ${expanded.name}! macro\n`;
         result += '// ' + '='.repeat(result.length - 3);
         result += '\n\n';
         result += expanded.expansion;
@@ -512,8 +526,9 @@ export function expandMacro(ctx: Ctx): Cmd {
     }
 
     const tdcp = new class implements vscode.TextDocumentContentProvider {
-        uri = vscode.Uri.parse('rust-analyzer://expandMacro/[EXPANSION].rs');
+        uri = vscode.Uri.parse(`rust-analyzer://${recursive ? 'expandMacro' : 'expandMacroOneStep'}/[EXPANSION].rs`);
         eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+        ranges: ra.ExpansionRange[] = [];
         async provideTextDocumentContent(_uri: vscode.Uri): Promise<string> {
             const editor = vscode.window.activeTextEditor;
             const client = ctx.client;
@@ -524,13 +539,29 @@ export function expandMacro(ctx: Ctx): Cmd {
             const expanded = await client.sendRequest(ra.expandMacro, {
                 textDocument: ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
                 position,
+                recursive,
             });
 
-            if (expanded == null) return 'Not available';
+            if (expanded == null) {
+                this.ranges = [];
+                return 'Not available';
+            }
 
+            this.ranges = expanded.ranges;
             return codeFormat(expanded);
         }
 
+        original(document: vscode.TextDocument, position: vscode.Position): vscode.Location | undefined {
+            if (document.uri.toString() !== this.uri.toString()) return;
+            const line = position.line - headerLines;
+            const hit = this.ranges.find(({ range }) =>
+                range.start.line === line &&
+                range.start.character <= position.character &&
+                position.character <= range.end.character);
+            if (!hit) return;
+            return ctx.client.protocol2CodeConverter.asLocation(hit.original);
+        }
+
         get onDidChange(): vscode.Event<vscode.Uri> {
             return this.eventEmitter.event;
         }
@@ -542,6 +573,81 @@ export function expandMacro(ctx: Ctx): Cmd {
             tdcp,
         ),
     );
+    ctx.pushCleanup(vscode.languages.registerDefinitionProvider({ scheme: 'rust-analyzer' }, {
+        async provideDefinition(document, position) {
+            const original = tdcp.original(document, position);
+            if (!original) return;
+            return vscode.commands.executeCommand<vscode.Location[]>(
+                'vscode.executeDefinitionProvider', original.uri, original.range.start);
+        }
+    }));
+    ctx.pushCleanup(vscode.languages.registerHoverProvider({ scheme: 'rust-analyzer' }, {
+        async provideHover(document, position) {
+            const original = tdcp.original(document, position);
+            if (!original) return;
+            const hovers = await vscode.commands.executeCommand<vscode.Hover[]>(
+                'vscode.executeHoverProvider', original.uri, original.range.start);
+            return hovers?.[0];
+        }
+    }));
+
+    return async () => {
+        const document = await vscode.workspace.openTextDocument(tdcp.uri);
+        tdcp.eventEmitter.fire(tdcp.uri);
+        return vscode.window.showTextDocument(
+            document,
+            vscode.ViewColumn.Two,
+            true,
+        );
+    };
+}
+
+// Opens the virtual file that will show how the macro call was matched
+//
+// The contents of the file come from the `TextDocumentContentProvider`
+export function traceMacroExpansion(ctx: Ctx): Cmd {
+    function codeFormat(trace: ra.MacroExpansionTrace): string {
+        let result = `// Expansion trace of ${trace.name}! macro\n`;
+        result += '// ' + '='.repeat(result.length - 3);
+        result += '\n';
+        for (const [i, rule] of trace.rules.entries()) {
+            const selected = i === trace.selected ? ' (selected)' : '';
+            result += `\n// rule #${rule.index + 1}${selected}\n`;
+            if (rule.pattern) result += `${rule.pattern}\n`;
+            result += `// matched ${rule.matchedTokenTrees} of ${rule.totalTokenTrees} token trees\n`;
+            if (rule.failedAt) result += `// failed at \`${rule.failedAt}\`\n`;
+            if (rule.error) result += `// error: ${rule.error}\n`;
+        }
+
+        return result;
+    }
+
+    const tdcp = new class implements vscode.TextDocumentContentProvider {
+        uri = vscode.Uri.parse('rust-analyzer://traceMacroExpansion/[TRACE].rs');
+        eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+        async provideTextDocumentContent(_uri: vscode.Uri): Promise<string> {
+            const editor = vscode.window.activeTextEditor;
+            const client = ctx.client;
+            if (!editor || !client) return '';
+
+            const position = editor.selection.active;
+
+            const trace = await client.sendRequest(ra.traceMacroExpansion, {
+                textDocument: ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
+                position,
+            });
+
+            if (trace == null) return 'Not available';
+
+            return codeFormat(trace);
+        }
+
+        get onDidChange(): vscode.Event<vscode.Uri> {
+            return this.eventEmitter.event;
+        }
+    }();
+
+    ctx.pushCleanup(vscode.workspace.registerTextDocumentContentProvider('rust-analyzer', tdcp));
 
     return async () => {
         const document = await vscode.workspace.openTextDocument(tdcp.uri);
@@ -14,6 +14,7 @@ interface InlayHintsStyles {
     typeHints: InlayHintStyle;
     paramHints: InlayHintStyle;
     chainingHints: InlayHintStyle;
+    closureCaptureHints: InlayHintStyle;
 }
 
 
@@ -23,7 +24,8 @@ export function activateInlayHints(ctx: Ctx) {
         async onConfigChange() {
             const anyEnabled = ctx.config.inlayHints.typeHints
                 || ctx.config.inlayHints.parameterHints
-                || ctx.config.inlayHints.chainingHints;
+                || ctx.config.inlayHints.chainingHints
+                || ctx.config.inlayHints.closureCaptureHints;
             const enabled = ctx.config.inlayHints.enable && anyEnabled;
 
             if (!enabled) return this.dispose();
@@ -51,13 +53,14 @@ export function activateInlayHints(ctx: Ctx) {
     maybeUpdater.onConfigChange().catch(console.error);
 }
 
-function createHintStyle(hintKind: "type" | "parameter" | "chaining", smallerHints: boolean): InlayHintStyle {
+function createHintStyle(hintKind: "type" | "parameter" | "chaining" | "closureCapture", smallerHints: boolean): InlayHintStyle {
     // U+200C is a zero-width non-joiner to prevent the editor from forming a ligature
     // between code and type hints
     const [pos, render] = ({
         type: ["after", (label: string) => `\u{200c}: ${label}`],
         parameter: ["before", (label: string) => `${label}: `],
         chaining: ["after", (label: string) => `\u{200c}: ${label}`],
+        closureCapture: ["after", (label: string) => `\u{200c}(${label})`],
     } as const)[hintKind];
 
     const fg = new vscode.ThemeColor(`rust_analyzer.inlayHints.foreground.${hintKind}Hints`);
@@ -85,12 +88,14 @@ const smallHintsStyles = {
     typeHints: createHintStyle("type", true),
     paramHints: createHintStyle("parameter", true),
     chainingHints: createHintStyle("chaining", true),
+    closureCaptureHints: createHintStyle("closureCapture", true),
 };
 
 const biggerHintsStyles = {
     typeHints: createHintStyle("type", false),
     paramHints: createHintStyle("parameter", false),
     chainingHints: createHintStyle("chaining", false),
+    closureCaptureHints: createHintStyle("closureCapture", false),
 };
 
 class HintsUpdater implements Disposable {
@@ -128,7 +133,7 @@ class HintsUpdater implements Disposable {
 
     dispose() {
         this.sourceFiles.forEach(file => file.inlaysRequest?.cancel());
-        this.ctx.visibleRustEditors.forEach(editor => this.renderDecorations(editor, { param: [], type: [], chaining: [] }));
+        this.ctx.visibleRustEditors.forEach(editor => this.renderDecorations(editor, { param: [], type: [], chaining: [], closureCapture: [] }));
         this.disposables.forEach(d => d.dispose());
     }
 
@@ -193,21 +198,23 @@ class HintsUpdater implements Disposable {
     }
 
     private renderDecorations(editor: RustEditor, decorations: InlaysDecorations) {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
+        const { typeHints, paramHints, chainingHints, closureCaptureHints } = this.inlayHintsStyles;
         if (this.pendingDisposeDecorations !== undefined) {
-            const { typeHints, paramHints, chainingHints } = this.pendingDisposeDecorations;
+            const { typeHints, paramHints, chainingHints, closureCaptureHints } = this.pendingDisposeDecorations;
             editor.setDecorations(typeHints.decorationType, []);
             editor.setDecorations(paramHints.decorationType, []);
             editor.setDecorations(chainingHints.decorationType, []);
+            editor.setDecorations(closureCaptureHints.decorationType, []);
         }
         editor.setDecorations(typeHints.decorationType, decorations.type);
         editor.setDecorations(paramHints.decorationType, decorations.param);
         editor.setDecorations(chainingHints.decorationType, decorations.chaining);
+        editor.setDecorations(closureCaptureHints.decorationType, decorations.closureCapture);
     }
 
     private hintsToDecorations(hints: ra.InlayHint[]): InlaysDecorations {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
-        const decorations: InlaysDecorations = { type: [], param: [], chaining: [] };
+        const { typeHints, paramHints, chainingHints, closureCaptureHints } = this.inlayHintsStyles;
+        const decorations: InlaysDecorations = { type: [], param: [], chaining: [], closureCapture: [] };
         const conv = this.ctx.client.protocol2CodeConverter;
 
         for (const hint of hints) {
@@ -224,6 +231,10 @@ class HintsUpdater implements Disposable {
                     decorations.chaining.push(chainingHints.toDecoration(hint, conv));
                     continue;
                 }
+                case ra.InlayHint.Kind.ClosureCaptureHint: {
+                    decorations.closureCapture.push(closureCaptureHints.toDecoration(hint, conv));
+                    continue;
+                }
             }
         }
         return decorations;
@@ -251,6 +262,7 @@ interface InlaysDecorations {
     type: vscode.DecorationOptions[];
     param: vscode.DecorationOptions[];
     chaining: vscode.DecorationOptions[];
+    closureCapture: vscode.DecorationOptions[];
 }
 
 interface RustSourceFile {
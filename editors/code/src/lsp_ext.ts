@@ -42,13 +42,35 @@ export const viewCrateGraph = new lc.RequestType<ViewCrateGraphParams, string, v
 export interface ExpandMacroParams {
     textDocument: lc.TextDocumentIdentifier;
     position: lc.Position;
+    recursive?: boolean;
+}
+export interface ExpansionRange {
+    range: lc.Range;
+    original: lc.Location;
 }
 export interface ExpandedMacro {
     name: string;
     expansion: string;
+    ranges: ExpansionRange[];
 }
 export const expandMacro = new lc.RequestType<ExpandMacroParams, ExpandedMacro | null, void>("rust-analyzer/expandMacro");
 
+export interface MacroRuleTrace {
+    index: number;
+    pattern: string | null;
+    matched: boolean;
+    matchedTokenTrees: number;
+    totalTokenTrees: number;
+    failedAt: string | null;
+    error: string | null;
+}
+export interface MacroExpansionTrace {
+    name: string;
+    rules: MacroRuleTrace[];
+    selected: number | null;
+}
+export const traceMacroExpansion = new lc.RequestType<ExpandMacroParams, MacroExpansionTrace | null, void>("rust-analyzer/traceMacroExpansion");
+
 export interface MatchingBraceParams {
     textDocument: lc.TextDocumentIdentifier;
     positions: lc.Position[];
@@ -91,13 +113,14 @@ export interface TestInfo {
 
 export const relatedTests = new lc.RequestType<lc.TextDocumentPositionParams, TestInfo[], void>("rust-analyzer/relatedTests");
 
-export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint;
+export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint | InlayHint.ClosureCaptureHint;
 
 export namespace InlayHint {
     export const enum Kind {
         TypeHint = "TypeHint",
         ParamHint = "ParameterHint",
         ChainingHint = "ChainingHint",
+        ClosureCaptureHint = "ClosureCaptureHint",
     }
     interface Common {
         range: lc.Range;
@@ -106,6 +129,7 @@ export namespace InlayHint {
     export type TypeHint = Common & { kind: Kind.TypeHint };
     export type ParamHint = Common & { kind: Kind.ParamHint };
     export type ChainingHint = Common & { kind: Kind.ChainingHint };
+    export type ClosureCaptureHint = Common & { kind: Kind.ClosureCaptureHint };
 }
 export interface InlayHintsParams {
     textDocument: lc.TextDocumentIdentifier;
@@ -125,6 +125,8 @@ async function initCommonContext(context: vscode.ExtensionContext, ctx: Ctx) {
     ctx.registerCommand('viewCrateGraph', commands.viewCrateGraph);
     ctx.registerCommand('viewFullCrateGraph', commands.viewFullCrateGraph);
     ctx.registerCommand('expandMacro', commands.expandMacro);
+    ctx.registerCommand('expandMacroOneStep', commands.expandMacroOneStep);
+    ctx.registerCommand('traceMacroExpansion', commands.traceMacroExpansion);
     ctx.registerCommand('run', commands.run);
     ctx.registerCommand('copyRunCommandLine', commands.copyRunCommandLine);
     ctx.registerCommand('debug', commands.debug);
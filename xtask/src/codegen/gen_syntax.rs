@@ -526,6 +526,7 @@ fn lower(grammar: &Grammar) -> AstSrc {
         }
     }
 
+    extend_grammar(&mut res);
     deduplicate_fields(&mut res);
     extract_enums(&mut res);
     extract_struct_traits(&mut res);
@@ -533,6 +534,89 @@ fn lower(grammar: &Grammar) -> AstSrc {
     res
 }
 
+/// Adds the syntax which is not in the grammar of the `ungrammar` crate yet:
+///
+/// ```text
+/// LetStmt =
+///   Attr* 'let' Pat (':' Type)?
+///   '=' initializer:Expr
+///   LetElse?
+///   ';'
+///
+/// LetElse =
+///   'else' BlockExpr
+///
+/// Expr = ... | LetExpr | ...
+///
+/// IfExpr =
+///   Attr* 'if' condition:Expr then_branch:BlockExpr
+///   ('else' else_branch:(IfExpr | BlockExpr))?
+///
+/// LetExpr =
+///   Attr* 'let' Pat '=' Expr
+///
+/// WhileExpr =
+///   Attr* Label? 'while' condition:Expr
+///   loop_body:BlockExpr
+/// ```
+///
+/// `LetExpr` replaces the `Condition` node, and nodes are placed where `Grammar::iter` would
+/// put them.
+fn extend_grammar(ast: &mut AstSrc) {
+    fn node(name: &str, ty: &str) -> Field {
+        Field::Node {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            cardinality: Cardinality::Optional,
+        }
+    }
+
+    let let_stmt = ast.nodes.iter().position(|it| it.name == "LetStmt").unwrap();
+    let semicolon = ast.nodes[let_stmt].fields.len() - 1;
+    assert_eq!(ast.nodes[let_stmt].fields[semicolon], Field::Token(";".to_string()));
+    ast.nodes[let_stmt].fields.insert(semicolon, node("let_else", "LetElse"));
+    let let_else = AstNodeSrc {
+        doc: Vec::new(),
+        name: "LetElse".to_string(),
+        traits: Vec::new(),
+        fields: vec![Field::Token("else".to_string()), node("block_expr", "BlockExpr")],
+    };
+    ast.nodes.insert(let_stmt + 1, let_else);
+
+    ast.nodes.retain(|it| it.name != "Condition");
+    let index_expr = ast.nodes.iter().position(|it| it.name == "IndexExpr").unwrap();
+    let let_expr = AstNodeSrc {
+        doc: Vec::new(),
+        name: "LetExpr".to_string(),
+        traits: Vec::new(),
+        fields: vec![
+            Field::Node {
+                name: "attrs".to_string(),
+                ty: "Attr".to_string(),
+                cardinality: Cardinality::Many,
+            },
+            Field::Token("let".to_string()),
+            node("pat", "Pat"),
+            Field::Token("=".to_string()),
+            node("expr", "Expr"),
+        ],
+    };
+    ast.nodes.insert(index_expr + 1, let_expr);
+    for node in ast.nodes.iter_mut().filter(|it| it.name == "IfExpr" || it.name == "WhileExpr") {
+        for field in &mut node.fields {
+            if let Field::Node { ty, .. } = field {
+                if ty == "Condition" {
+                    *ty = "Expr".to_string();
+                }
+            }
+        }
+    }
+
+    let expr = ast.enums.iter_mut().find(|it| it.name == "Expr").unwrap();
+    let index_expr = expr.variants.iter().position(|it| it == "IndexExpr").unwrap();
+    expr.variants.insert(index_expr + 1, "LetExpr".to_string());
+}
+
 fn lower_enum(grammar: &Grammar, rule: &Rule) -> Option<Vec<String>> {
     let alternatives = match rule {
         Rule::Alt(it) => it,